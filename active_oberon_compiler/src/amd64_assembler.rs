@@ -3,10 +3,13 @@
 // Written by Richard Magnor Stenbro. Licensed under GPL v3
 // Inline assembler for X86-64 module for compiling and linking of projects written in ActiveOberon language
 
-use crate::amd64_instruction_set_neo::{CpuFlags, CPU_8086, CPU_186, CPU_286, CPU_486, CPU_386, CPU_PENTIUM, CPU_KATMAI, CPU_WILLAMETTE, CPU_PRESCOTT, CPU_AMD64, CPU_PROTECTED, CPU_PRIVILEGED, CPU_SSE, CPU_SSE2, CPU_SSE3, CPU_3DNOW, CPU_MMX, CPU_FPU};
+use std::collections::{HashMap, HashSet};
+use crate::diagnostics::{Diagnostic, DiagnosticMethods, E_ASSEMBLER_SYNTAX, E_ASSEMBLER_CPU_FLAG, E_ASSEMBLER_UNDEFINED, E_ASSEMBLER_DUPLICATE, E_ASSEMBLER_OPERAND, E_DIVISION_BY_ZERO};
+use crate::inline_assembler::{InlineAssembler, OberonVariable, Relocation};
+use crate::amd64_instruction_set_neo::{AMD64Operand, RegisterAMD64, RegisterAMD64Methods, register_amd64, encode_instruction_amd64_mode, CpuFlags, CPU_8086, CPU_186, CPU_286, CPU_486, CPU_386, CPU_PENTIUM, CPU_P6, CPU_KATMAI, CPU_WILLAMETTE, CPU_PRESCOTT, CPU_AMD64, CPU_PROTECTED, CPU_PRIVILEGED, CPU_SSE, CPU_SSE2, CPU_SSE3, CPU_3DNOW, CPU_MMX, CPU_FPU, CPU_AVX, CPU_AVX2, CPU_FMA, CPU_AVX512};
 
 #[derive(Clone, PartialEq, Debug)]
-enum AMD64Symbols {
+pub enum AMD64Symbols {
     Ident(u32, u32, Box<String>),
     Label(u32, u32, Box<String>),
     Number(u32, u32, i64),
@@ -34,7 +37,7 @@ enum AMD64Symbols {
 }
 
 #[derive(Clone, PartialEq, Debug)]
-enum AMD64Node {
+pub enum AMD64Node {
     None,
     Number(u32, u32, i64),
     String(u32, u32, Box<String>),
@@ -47,6 +50,68 @@ enum AMD64Node {
     UnaryMinus(u32, u32, Box<AMD64Symbols>, Box<AMD64Node>),
     UnaryPlus(u32, u32, Box<AMD64Symbols>, Box<AMD64Node>),
     Negate(u32, u32, Box<AMD64Symbols>, Box<AMD64Node>),
+    Location(u32, u32),
+    Memory(u32, u32, Option<u32>, Box<AMD64Node>),
+    Instruction(u32, u32, Box<String>, Box<Vec<Box<AMD64Node>>>),
+    Label(u32, u32, Box<String>),
+    Data(u32, u32, u32, Box<Vec<Box<AMD64Node>>>),     /* DB, DW, DD and DQ with size of each item in bytes */
+    Reserve(u32, u32, u32, Box<AMD64Node>),            /* RESB, RESW, RESD and RESQ with size in bytes and count */
+    Repeat(u32, u32, Box<AMD64Node>, Box<AMD64Node>),  /* TIMES count line */
+    Align(u32, u32, Box<AMD64Node>),
+    Origin(u32, u32, Box<AMD64Node>),
+    Absolute(u32, u32, Box<AMD64Node>),
+    Bits(u32, u32, u32),
+    Cpu(u32, u32, CpuFlags),
+    Masked(u32, u32, Box<AMD64Node>, u8, bool),        /* Operand with '{K1}' opmask and '{Z}' zeroing */
+    Broadcast(u32, u32, Box<AMD64Node>, u32)           /* Memory operand with '{1TO16}' broadcast */
+}
+
+/// Start and end position of node inside assembler text
+fn node_span(node: &AMD64Node) -> (u32, u32) {
+    match node {
+        AMD64Node::Number(s, e, _) |
+        AMD64Node::String(s, e, _) |
+        AMD64Node::Ident(s, e, _) |
+        AMD64Node::Times(s, e, _ , _ , _ ) |
+        AMD64Node::Div(s, e, _ , _ , _ ) |
+        AMD64Node::Modulo(s, e, _ , _ , _ ) |
+        AMD64Node::Minus(s, e, _ , _ , _ ) |
+        AMD64Node::Plus(s, e, _ , _ , _ ) |
+        AMD64Node::UnaryMinus(s, e, _ , _ ) |
+        AMD64Node::UnaryPlus(s, e, _ , _ ) |
+        AMD64Node::Negate(s, e, _ , _ ) |
+        AMD64Node::Location(s, e) |
+        AMD64Node::Memory(s, e, _ , _ ) |
+        AMD64Node::Instruction(s, e, _ , _ ) |
+        AMD64Node::Label(s, e, _ ) |
+        AMD64Node::Data(s, e, _ , _ ) |
+        AMD64Node::Reserve(s, e, _ , _ ) |
+        AMD64Node::Repeat(s, e, _ , _ ) |
+        AMD64Node::Align(s, e, _ ) |
+        AMD64Node::Origin(s, e, _ ) |
+        AMD64Node::Absolute(s, e, _ ) |
+        AMD64Node::Bits(s, e, _ ) |
+        AMD64Node::Cpu(s, e, _ ) |
+        AMD64Node::Masked(s, e, _ , _ , _ ) |
+        AMD64Node::Broadcast(s, e, _ , _ ) => (*s, *e),
+        AMD64Node::None => (0, 0)
+    }
+}
+
+/// True when a register name is part of expression, only allowed inside memory operands
+fn has_register(node: &AMD64Node) -> bool {
+    match node {
+        AMD64Node::Ident( _ , _ , t ) => register_amd64(t.as_str()).is_some(),
+        AMD64Node::Times( _ , _ , l , _ , r ) |
+        AMD64Node::Div( _ , _ , l , _ , r ) |
+        AMD64Node::Modulo( _ , _ , l , _ , r ) |
+        AMD64Node::Minus( _ , _ , l , _ , r ) |
+        AMD64Node::Plus( _ , _ , l , _ , r ) => has_register(l) || has_register(r),
+        AMD64Node::UnaryMinus( _ , _ , _ , x ) |
+        AMD64Node::UnaryPlus( _ , _ , _ , x ) |
+        AMD64Node::Negate( _ , _ , _ , x ) => has_register(x),
+        _ => false
+    }
 }
 
 
@@ -55,36 +120,87 @@ pub trait AssemblerAMD64Methods {
     fn new(text: Vec<char>, offset_position: u32) -> Self;
     fn get_position(&self) -> u32;
     fn get_char(&mut self) -> char;
+    fn peek_char(&self) -> char;
     fn next_char(&mut self) -> ();
     fn skip_whitespace(&mut self) -> ();
     fn get_ident(&mut self) -> Box<String>;
-    fn get_number(&mut self) -> Result<i64, Box<String>>;
+    fn get_number(&mut self) -> Result<i64, Box<Diagnostic>>;
     fn get_string(&mut self) -> Box<String>;
-    fn get_symbol(&mut self) -> Result<Box<AMD64Symbols>, Box<String>>;
-    fn factor(&mut self) -> Result<Box<AMD64Node>, Box<String>>;
-    fn term(&mut self) -> Result<Box<AMD64Node>, Box<String>>;
-    fn expression(&mut self) -> Result<Box<AMD64Node>, Box<String>>;
+    fn get_symbol(&mut self) -> Result<Box<AMD64Symbols>, Box<Diagnostic>>;
+    fn factor(&mut self) -> Result<Box<AMD64Node>, Box<Diagnostic>>;
+    fn term(&mut self) -> Result<Box<AMD64Node>, Box<Diagnostic>>;
+    fn expression(&mut self) -> Result<Box<AMD64Node>, Box<Diagnostic>>;
+    fn operand(&mut self) -> Result<Box<AMD64Node>, Box<Diagnostic>>;
+    fn instruction(&mut self, start: u32, end: u32, mnemonic: Box<String>) -> Result<Box<AMD64Node>, Box<Diagnostic>>;
+    fn statement(&mut self, start: u32, end: u32, keyword: Box<String>) -> Result<Box<AMD64Node>, Box<Diagnostic>>;
+    fn end_of_line(&self, node: Box<AMD64Node>) -> Result<Box<AMD64Node>, Box<Diagnostic>>;
+    fn evaluate(&self, node: &AMD64Node) -> Result<i64, Box<Diagnostic>>;
+    fn linear(&self, node: &AMD64Node, sign: i64, registers: &mut Vec<(RegisterAMD64, i64)>) -> Result<i64, Box<Diagnostic>>;
+    fn resolve_operand(&self, node: &AMD64Node) -> Result<AMD64Operand, Box<Diagnostic>>;
+    fn define(&mut self, name: &str, value: i64, is_label: bool, start: u32, end: u32) -> Result<(), Box<Diagnostic>>;
+    fn qualified(&self, name: &str) -> Box<String>;
+    fn emit(&mut self, line: &AMD64Node, near: bool, code: &mut Vec<u8>) -> Result<(), Box<Diagnostic>>;
+    fn count(&self, node: &AMD64Node) -> Result<i64, Box<Diagnostic>>;
+    fn variable(&self, name: &str) -> Option<&OberonVariable>;
+    fn variables_in(&self, node: &AMD64Node, found: &mut Vec<(String, OberonVariable)>) -> ();
+    fn sized_variable(&self, node: &AMD64Node) -> Option<(String, u32)>;
 
-
-    fn assemble(&mut self) -> Result<Box<Vec<u8>>, Box<String>>;
     fn advance(&mut self) -> ();
-    fn skip_line(&mut self) -> Result<Box<AMD64Node>, Box<String>>;
+    fn error(&self, code: &'static str, message: String) -> Box<Diagnostic>;
+    fn error_at(&self, code: &'static str, message: String, start: u32, end: u32) -> Box<Diagnostic>;
 }
 
 pub struct AssemblerAMD64 {
     buffer: Vec<char>,	/* Sourcecode as a vector of chars */
+    offset_position: u32,	/* Position of code block in Oberon source file */
     start_pos: u32,		/* Start of current analyzed symbol */
     index: u32,			/* Position into vector */
-    symbol: Result<Box<AMD64Symbols>, Box<String>>
+    symbol: Result<Box<AMD64Symbols>, Box<Diagnostic>>,
+    location: i64,		/* Value of '$', offset of current instruction from start of code block */
+    labels: HashMap<String, i64>,
+    constants: HashMap<String, i64>,	/* Names defined with 'EQU' */
+    scope: Box<String>,		/* Last label not starting with '@', local labels are only visible until next such label */
+    pending: HashSet<String>,	/* Labels not yet reached in first pass, they are assumed to be at current location */
+    bits: u32,			/* Mode selected with 'BITS', 16, 32 or 64 */
+    flags: CpuFlags,		/* CPU flags of code block, changed with 'CPU' */
+    absolute: bool,		/* After 'ABSOLUTE' only space is reserved, no code is emitted */
+    variables: HashMap<String, OberonVariable>,	/* Oberon variables visible in code block */
+    relocations: Vec<Relocation>,
+    instructions: Vec<(i64, Box<String>, Vec<AMD64Operand>)>	/* Location, mnemonic and evaluated operands */
+}
+
+/// Characters of string literal without its delimiters, one ' or " at each end
+fn string_text(text: &str) -> &str {
+    let mut chars = text.chars();
+
+    match chars.next() {
+        Some( delimiter ) if delimiter == '\'' || delimiter == '"' => {
+            let rest = chars.as_str();
+            rest.strip_suffix(delimiter).unwrap_or(rest)
+        },
+        _ => text
+    }
 }
 
 impl AssemblerAMD64Methods for AssemblerAMD64 {
     fn new(text: Vec<char>, offset_position: u32) -> Self {
         AssemblerAMD64 {
             buffer: text,
-            start_pos: offset_position,
+            offset_position,
+            start_pos: 0,
             index: 0,
-            symbol: Ok(Box::new(AMD64Symbols::None))
+            symbol: Ok(Box::new(AMD64Symbols::None)),
+            location: 0,
+            labels: HashMap::new(),
+            constants: HashMap::new(),
+            scope: Box::new(String::new()),
+            pending: HashSet::new(),
+            bits: 64,
+            flags: CPU_AMD64 | CPU_PROTECTED,
+            absolute: false,
+            variables: HashMap::new(),
+            relocations: Vec::new(),
+            instructions: Vec::new()
         }
     }
 
@@ -101,6 +217,13 @@ impl AssemblerAMD64Methods for AssemblerAMD64 {
         }
     }
 
+    fn peek_char(&self) -> char {
+        match self.buffer.get(self.index as usize + 1) {
+            Some(x) => *x,
+            _ => '\0'
+        }
+    }
+
     fn next_char(&mut self) -> () {
         if self.index as usize <= self.buffer.len() - 1 {
             self.index += 1;
@@ -146,9 +269,12 @@ impl AssemblerAMD64Methods for AssemblerAMD64 {
         Box::new(text)
     }
 
-    fn get_number(&mut self) -> Result<i64, Box<String>> {
+    /// Decimal number or hexadecimal number like '1FH' or '0x1F'. Numbers up to 64 bits are accepted, those above
+    /// the largest signed number give their bit pattern, like '0FFFFFFFFFFFFFFFFH' for -1.
+    fn get_number(&mut self) -> Result<i64, Box<Diagnostic>> {
         let mut text = String::new();
-        let mut value : i64 = 0;
+        let mut value : u64 = 0;
+        let mut prefixed = false;
 
         loop {
             match self.get_char() {
@@ -156,121 +282,51 @@ impl AssemblerAMD64Methods for AssemblerAMD64 {
                     text.push(self.get_char());
                     self.next_char();
                 },
+                'x' | 'X' if text == "0" && !prefixed && self.peek_char().is_ascii_hexdigit() => {
+                    text.clear();
+                    prefixed = true;
+                    self.next_char();
+                },
                 _ => break
             }
         }
 
-        match self.get_char() {
-            'H' | 'X' => {
+        let radix = match ( prefixed , self.get_char() ) {
+            ( true , _ ) => 16,
+            ( _ , 'H' ) | ( _ , 'X' ) => {
                 self.next_char();
-                for el in text.chars() {
-                    match el {
-                        '0' => {
-                            value = value * 0x10 + 0
-                        },
-                        '1' => {
-                            value = value * 0x10 + 1
-                        },
-                        '2' => {
-                            value = value * 0x10 + 2
-                        },
-                        '3' => {
-                            value = value * 0x10 + 3
-                        },
-                        '4' => {
-                            value = value * 0x10 + 4
-                        },
-                        '5' => {
-                            value = value * 0x10 + 5
-                        },
-                        '6' => {
-                            value = value * 0x10 + 6
-                        },
-                        '7' => {
-                            value = value * 0x10 + 7
-                        },
-                        '8' => {
-                            value = value * 0x10 + 8
-                        },
-                        '9' => {
-                            value = value * 0x10 + 9
-                        },
-                        'a' | 'A' => {
-                            value = value * 0x10 + 10
-                        },
-                        'b' | 'B' => {
-                            value = value * 0x10 + 11
-                        },
-                        'c' | 'C' => {
-                            value = value * 0x10 + 12
-                        },
-                        'd' | 'D' => {
-                            value = value * 0x10 + 13
-                        },
-                        'e' | 'E' => {
-                            value = value * 0x10 + 14
-                        },
-                        'f' | 'F' => {
-                            value = value * 0x10 + 15
-                        },
-                        _ => ()
-                    }
-                }
+                16
             },
-            _ => {
-
-                for el in text.chars() {
-                    match el {
-                        '0' => {
-                            value = value * 10
-                        },
-                        '1' => {
-                            value = value * 10 + 1
-                        },
-                        '2' => {
-                            value = value * 10 + 2
-                        },
-                        '3' => {
-                            value = value * 10 + 3
-                        },
-                        '4' => {
-                            value = value * 10 + 4
-                        },
-                        '5' => {
-                            value = value * 10 + 5
-                        },
-                        '6' => {
-                            value = value * 10 + 6
-                        },
-                        '7' => {
-                            value = value * 10 + 7
-                        },
-                        '8' => {
-                            value = value * 10 + 8
-                        },
-                        '9' => {
-                            value = value * 10 + 9
-                        },
-                        _ => {
-                            return Err(Box::new(format!("Found hex digit in non hex number at position: '{}'", self.get_position())))
-                        }
-                    }
-                }
+            _ => 10
+        };
+
+        for el in text.chars() {
+            let digit = match el.to_digit(radix) {
+                Some( x ) => x as u64,
+                _ => return Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Found hex digit in non hex number")))
+            };
+
+            value = match value.checked_mul(radix as u64).and_then(|x| x.checked_add(digit)) {
+                Some( x ) => x,
+                _ => return Err(self.error(E_ASSEMBLER_OPERAND, format!("Number '{}' is out of range of 64 bits", text)))
             }
         }
 
-        Ok(value)
+        Ok(value as i64)
     }
 
+    /// String between ' or " delimiters, the delimiters are kept in text
     fn get_string(&mut self) -> Box<String> {
         let mut text = String::new();
-        text.push(self.get_char());
+        let delimiter = self.get_char();
+        text.push(delimiter);
         self.next_char();
 
         loop {
             match self.get_char() {
-                '\'' => {
+                x if x == delimiter => {
                     text.push(self.get_char());
+                    self.next_char();
                     break
                 },
                 '\0' => break,
@@ -285,9 +341,10 @@ impl AssemblerAMD64Methods for AssemblerAMD64 {
     }
 
     /// Assembler lexer for AMD64 Syntax
-    fn get_symbol(&mut self) -> Result<Box<AMD64Symbols>, Box<String>> {
+    fn get_symbol(&mut self) -> Result<Box<AMD64Symbols>, Box<Diagnostic>> {
         self.skip_whitespace();
-        let mut start_pos = self.get_position();
+        let start_pos = self.get_position();
+        self.start_pos = start_pos;
 
         return match self.get_char() {
             'a' ..= 'z' | 'A' ..= 'Z' | '_' => {
@@ -307,7 +364,7 @@ impl AssemblerAMD64Methods for AssemblerAMD64 {
                 let symbol = self.get_number()?;
                 Ok(Box::new(AMD64Symbols::Number(start_pos, self.get_position(), symbol)))
             },
-            '\'' => {
+            '\'' | '"' => {
                 let symbol = self.get_string();
                 Ok(Box::new(AMD64Symbols::String_(start_pos, self.get_position(), symbol)))
             },
@@ -373,7 +430,23 @@ impl AssemblerAMD64Methods for AssemblerAMD64 {
             },
             '@' => {
                 self.next_char();
-                Ok(Box::new(AMD64Symbols::At(start_pos, self.get_position())))
+                match self.get_char() {
+                    'a' ..= 'z' | 'A' ..= 'Z' | '_' => {
+                        /* Local label like '@loop' */
+                        let symbol = Box::new(format!("@{}", self.get_ident()));
+                        self.skip_whitespace();
+                        match self.get_char() {
+                            ':' => {
+                                self.next_char();
+                                Ok(Box::new(AMD64Symbols::Label(start_pos, self.get_position(), symbol)))
+                            },
+                            _ => {
+                                Ok(Box::new(AMD64Symbols::Ident(start_pos, self.get_position(), symbol)))
+                            }
+                        }
+                    },
+                    _ => Ok(Box::new(AMD64Symbols::At(start_pos, self.get_position())))
+                }
             },
             '$' => {
                 self.next_char();
@@ -396,24 +469,760 @@ impl AssemblerAMD64Methods for AssemblerAMD64 {
                 self.next_char();
                 Ok(Box::new(AMD64Symbols::NewLine(start_pos, self.get_position())))
             },
-            _ => Err(Box::new(format!("Invalid symbol in inline assembler at position: '{}'", self.get_position())))
+            _ => Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Invalid symbol in inline assembler")))
+        }
+    }
+
+    /// factor = number | string | '$' | ident | '(' expression ')' | ( '-' | '+' | '~' ) factor
+    fn factor(&mut self) -> Result<Box<AMD64Node>, Box<Diagnostic>> {
+        match *self.symbol.clone()? {
+            AMD64Symbols::Number(s, e, n) => {
+                self.advance();
+                Ok(Box::new(AMD64Node::Number(s, e, n)))
+            },
+            AMD64Symbols::String_(s, e, t) => {
+                self.advance();
+                Ok(Box::new(AMD64Node::String(s, e, t)))
+            },
+            AMD64Symbols::Ident(s, e, t) => {
+                self.advance();
+                Ok(Box::new(AMD64Node::Ident(s, e, self.qualified(t.as_str()))))
+            },
+            AMD64Symbols::Dollar(s, e) => {
+                self.advance();
+                Ok(Box::new(AMD64Node::Location(s, e)))
+            },
+            AMD64Symbols::LeftParen( _ , _ ) => {
+                self.advance();
+                let node = self.expression()?;
+                match *self.symbol.clone()? {
+                    AMD64Symbols::RightParen( _ , _ ) => {
+                        self.advance();
+                        Ok(node)
+                    },
+                    _ => Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Expecting ')' in expression")))
+                }
+            },
+            AMD64Symbols::Minus(s, _ ) => {
+                let symbol = self.symbol.clone()?;
+                self.advance();
+                let right = self.factor()?;
+                Ok(Box::new(AMD64Node::UnaryMinus(s, node_span(&right).1, symbol, right)))
+            },
+            AMD64Symbols::Plus(s, _ ) => {
+                let symbol = self.symbol.clone()?;
+                self.advance();
+                let right = self.factor()?;
+                Ok(Box::new(AMD64Node::UnaryPlus(s, node_span(&right).1, symbol, right)))
+            },
+            AMD64Symbols::Negate(s, _ ) => {
+                let symbol = self.symbol.clone()?;
+                self.advance();
+                let right = self.factor()?;
+                Ok(Box::new(AMD64Node::Negate(s, node_span(&right).1, symbol, right)))
+            },
+            _ => Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Expecting operand in expression")))
+        }
+    }
+
+    /// term = factor { ( '*' | '/' | '%' ) factor }
+    fn term(&mut self) -> Result<Box<AMD64Node>, Box<Diagnostic>> {
+        let mut left = self.factor()?;
+
+        loop {
+            let symbol = self.symbol.clone()?;
+            match *symbol {
+                AMD64Symbols::Times( _ , _ ) |
+                AMD64Symbols::Div( _ , _ ) |
+                AMD64Symbols::Modulo( _ , _ ) => {
+                    self.advance();
+                    let right = self.factor()?;
+                    let ( s , e ) = ( node_span(&left).0, node_span(&right).1 );
+                    left = match *symbol {
+                        AMD64Symbols::Times( _ , _ ) => Box::new(AMD64Node::Times(s, e, left, symbol, right)),
+                        AMD64Symbols::Div( _ , _ ) => Box::new(AMD64Node::Div(s, e, left, symbol, right)),
+                        _ => Box::new(AMD64Node::Modulo(s, e, left, symbol, right))
+                    }
+                },
+                _ => break
+            }
+        }
+
+        Ok(left)
+    }
+
+    /// expression = term { ( '+' | '-' ) term }
+    fn expression(&mut self) -> Result<Box<AMD64Node>, Box<Diagnostic>> {
+        let mut left = self.term()?;
+
+        loop {
+            let symbol = self.symbol.clone()?;
+            match *symbol {
+                AMD64Symbols::Plus( _ , _ ) |
+                AMD64Symbols::Minus( _ , _ ) => {
+                    self.advance();
+                    let right = self.term()?;
+                    let ( s , e ) = ( node_span(&left).0, node_span(&right).1 );
+                    left = match *symbol {
+                        AMD64Symbols::Plus( _ , _ ) => Box::new(AMD64Node::Plus(s, e, left, symbol, right)),
+                        _ => Box::new(AMD64Node::Minus(s, e, left, symbol, right))
+                    }
+                },
+                _ => break
+            }
+        }
+
+        Ok(left)
+    }
+
+    /// operand = ( [ ( 'BYTE' | 'WORD' | 'DWORD' | 'QWORD' | 'TWORD' | 'OWORD' | 'YWORD' | 'ZWORD' ) [ 'PTR' ] ] '[' expression ']' | expression ) { '{' decoration '}' }
+    fn operand(&mut self) -> Result<Box<AMD64Node>, Box<Diagnostic>> {
+        let start = self.start_pos;
+        let size = match *self.symbol.clone()? {
+            AMD64Symbols::Ident( _ , _ , t ) => {
+                match t.to_uppercase().as_str() {
+                    "BYTE" => Some( 8 ),
+                    "WORD" => Some( 16 ),
+                    "DWORD" => Some( 32 ),
+                    "QWORD" => Some( 64 ),
+                    "TWORD" => Some( 80 ),
+                    "OWORD" => Some( 128 ),
+                    "YWORD" => Some( 256 ),
+                    "ZWORD" => Some( 512 ),
+                    _ => None
+                }
+            },
+            _ => None
+        };
+
+        if size.is_some() {
+            self.advance();
+            match *self.symbol.clone()? {
+                AMD64Symbols::Ident( _ , _ , t ) if t.to_uppercase() == "PTR" => self.advance(),
+                _ => ()
+            }
+            match *self.symbol.clone()? {
+                AMD64Symbols::LeftBracket( _ , _ ) => (),
+                _ => return Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Expecting '[' after operand size")))
+            }
+        }
+
+        let mut node = match *self.symbol.clone()? {
+            AMD64Symbols::LeftBracket( _ , _ ) => {
+                self.advance();
+                let node = self.expression()?;
+                match *self.symbol.clone()? {
+                    AMD64Symbols::RightBracket( _ , e ) => {
+                        self.advance();
+                        Box::new(AMD64Node::Memory(start, e, size, node))
+                    },
+                    _ => return Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Expecting ']' in memory operand")))
+                }
+            },
+            _ => self.expression()?
+        };
+
+        /* AVX-512 decorations, opmask register like '{K1}', zeroing '{Z}' and broadcast like '{1TO16}' */
+        while let AMD64Symbols::LeftCurly( _ , _ ) = *self.symbol.clone()? {
+            self.skip_whitespace();
+            let text = self.get_ident().to_uppercase();
+            self.advance();
+            let e = match *self.symbol.clone()? {
+                AMD64Symbols::RightCurly( _ , e ) => e,
+                _ => return Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Expecting '}' after operand decoration")))
+            };
+            node = match ( *node , text.as_str() ) {
+                ( AMD64Node::Masked( _ , _ , x , k , _ ) , "Z" ) => Box::new(AMD64Node::Masked(start, e, x, k, true)),
+                ( x , "Z" ) => Box::new(AMD64Node::Masked(start, e, Box::new(x), 0, true)),
+                ( x @ AMD64Node::Memory( .. ) , t ) if t.starts_with("1TO") => {
+                    match t[3 ..].parse::<u32>() {
+                        Ok( n ) if [ 2, 4, 8, 16 ].contains(&n) => Box::new(AMD64Node::Broadcast(start, e, Box::new(x), n)),
+                        _ => return Err(self.error(E_ASSEMBLER_SYNTAX, format!("Invalid broadcast '{{{}}}', expecting '{{1TO2}}', '{{1TO4}}', '{{1TO8}}' or '{{1TO16}}'", t)))
+                    }
+                },
+                ( x , t ) => {
+                    let mask = match register_amd64(t) {
+                        Some( RegisterAMD64::Mask(k) ) if k != 0 => k,
+                        _ => return Err(self.error(E_ASSEMBLER_SYNTAX, format!("Invalid operand decoration '{{{}}}', expecting opmask register K1 - K7, 'Z' or broadcast", t)))
+                    };
+                    match x {
+                        AMD64Node::Masked( _ , _ , y , 0 , z ) => Box::new(AMD64Node::Masked(start, e, y, mask, z)),
+                        AMD64Node::Masked( .. ) => return Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Only one opmask register is allowed for operand"))),
+                        y => Box::new(AMD64Node::Masked(start, e, Box::new(y), mask, false))
+                    }
+                }
+            };
+            self.advance()
+        }
+
+        Ok(node)
+    }
+
+    /// instruction = mnemonic [ operand { ',' operand } ] ( newline | end of file ), called after mnemonic is read
+    fn instruction(&mut self, start: u32, end: u32, mnemonic: Box<String>) -> Result<Box<AMD64Node>, Box<Diagnostic>> {
+        let mut end = end;
+        let mut operands = Box::new(Vec::<Box<AMD64Node>>::new());
+        let mnemonic = Box::new(mnemonic.to_uppercase());
+
+        match *self.symbol.clone()? {
+            AMD64Symbols::NewLine( _ , _ ) | AMD64Symbols::EndOfFile( _ ) => (),
+            _ => {
+                loop {
+                    let node = self.operand()?;
+                    end = node_span(&node).1;
+                    operands.push(node);
+                    match *self.symbol.clone()? {
+                        AMD64Symbols::Comma( _ , _ ) => self.advance(),
+                        _ => break
+                    }
+                }
+            }
+        }
+
+        match *self.symbol.clone()? {
+            AMD64Symbols::NewLine( _ , _ ) | AMD64Symbols::EndOfFile( _ ) => Ok(Box::new(AMD64Node::Instruction(start, end, mnemonic, operands))),
+            _ => Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Expecting end of line after instruction")))
+        }
+    }
+
+    /// statement = directive | 'TIMES' expression statement | instruction, called after first identifier is read
+    fn statement(&mut self, start: u32, end: u32, keyword: Box<String>) -> Result<Box<AMD64Node>, Box<Diagnostic>> {
+        match keyword.to_uppercase().as_str() {
+            "BITS" => {
+                match *self.symbol.clone()? {
+                    AMD64Symbols::Number( _ , e , n ) if n == 16 || n == 32 || n == 64 => {
+                        self.advance();
+                        self.end_of_line(Box::new(AMD64Node::Bits(start, e, n as u32)))
+                    },
+                    AMD64Symbols::NewLine( _ , _ ) | AMD64Symbols::EndOfFile( _ ) => Ok(Box::new(AMD64Node::Bits(start, end, 64))),
+                    _ => Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Expecting 16, 32 or 64 after 'BITS'")))
+                }
+            },
+            "CPU" => {
+                let ( e , name ) = match *self.symbol.clone()? {
+                    AMD64Symbols::Number( _ , e , n ) => ( e , n.to_string() ),
+                    AMD64Symbols::Ident( _ , e , t ) => ( e , t.to_uppercase() ),
+                    _ => return Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Expecting processor name after 'CPU'")))
+                };
+                let cpu = match name.as_str() {
+                    "8086" => CPU_8086,
+                    "186" => CPU_186,
+                    "286" => CPU_286,
+                    "386" => CPU_386,
+                    "486" => CPU_486,
+                    "PENTIUM" | "586" => CPU_PENTIUM,
+                    "P6" | "686" => CPU_P6,
+                    "KATMAI" => CPU_KATMAI,
+                    "WILLAMETTE" => CPU_WILLAMETTE,
+                    "PRESCOTT" => CPU_PRESCOTT,
+                    "X64" | "AMD64" => CPU_AMD64,
+                    "SANDYBRIDGE" => CPU_AMD64 | CPU_AVX,
+                    "HASWELL" => CPU_AMD64 | CPU_AVX2 | CPU_FMA,
+                    "SKX" => CPU_AMD64 | CPU_AVX512,
+                    _ => return Err(self.error(E_ASSEMBLER_CPU_FLAG, format!("Unknown processor '{}' after 'CPU'", name)))
+                };
+                self.advance();
+                /* Processor selection keeps protected and privileged mode of code block */
+                let flags = cpu | (self.flags & (CPU_PROTECTED | CPU_PRIVILEGED));
+                self.end_of_line(Box::new(AMD64Node::Cpu(start, e, flags)))
+            },
+            "ALIGN" => {
+                let node = self.expression()?;
+                self.end_of_line(Box::new(AMD64Node::Align(start, node_span(&node).1, node)))
+            },
+            "ORG" => {
+                let node = self.expression()?;
+                self.end_of_line(Box::new(AMD64Node::Origin(start, node_span(&node).1, node)))
+            },
+            "ABSOLUTE" => {
+                let node = self.expression()?;
+                self.end_of_line(Box::new(AMD64Node::Absolute(start, node_span(&node).1, node)))
+            },
+            "RESB" | "RESW" | "RESD" | "RESQ" => {
+                let size = match keyword.to_uppercase().as_str() {
+                    "RESB" => 1,
+                    "RESW" => 2,
+                    "RESD" => 4,
+                    _ => 8
+                };
+                let node = self.expression()?;
+                self.end_of_line(Box::new(AMD64Node::Reserve(start, node_span(&node).1, size, node)))
+            },
+            "DB" | "DW" | "DD" | "DQ" => {
+                let size = match keyword.to_uppercase().as_str() {
+                    "DB" => 1,
+                    "DW" => 2,
+                    "DD" => 4,
+                    _ => 8
+                };
+                let mut items = Box::new(Vec::<Box<AMD64Node>>::new());
+                loop {
+                    items.push(self.expression()?);
+                    match *self.symbol.clone()? {
+                        AMD64Symbols::Comma( _ , _ ) => self.advance(),
+                        _ => break
+                    }
+                }
+                let end = node_span(&items[items.len() - 1]).1;
+                self.end_of_line(Box::new(AMD64Node::Data(start, end, size, items)))
+            },
+            "TIMES" => {
+                let count = self.expression()?;
+                match *self.symbol.clone()? {
+                    AMD64Symbols::Ident(s, e, t) => {
+                        self.advance();
+                        let line = self.statement(s, e, t)?;
+                        Ok(Box::new(AMD64Node::Repeat(start, node_span(&line).1, count, line)))
+                    },
+                    _ => Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Expecting instruction or data after 'TIMES' count")))
+                }
+            },
+            _ => self.instruction(start, end, keyword)
+        }
+    }
+
+    /// Directive node when it is followed by end of line, else a syntax error
+    fn end_of_line(&self, node: Box<AMD64Node>) -> Result<Box<AMD64Node>, Box<Diagnostic>> {
+        match *self.symbol.clone()? {
+            AMD64Symbols::NewLine( _ , _ ) | AMD64Symbols::EndOfFile( _ ) => Ok(node),
+            _ => Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Expecting end of line after directive")))
+        }
+    }
+
+    /// Value of expression, using current location for '$', labels and 'EQU' constants
+    fn evaluate(&self, node: &AMD64Node) -> Result<i64, Box<Diagnostic>> {
+        let ( s , e ) = node_span(node);
+
+        match node {
+            AMD64Node::Number( _ , _ , n ) => Ok(*n),
+            AMD64Node::String( _ , _ , t ) => {
+                let text : Vec<char> = string_text(t).chars().collect();
+                if text.len() > 8 {
+                    return Err(self.error_at(E_ASSEMBLER_OPERAND, String::from("String is too long for a numeric value"), s, e))
+                }
+                /* Characters are packed little endian, first character in lowest byte */
+                let mut value : i64 = 0;
+                for ( i , c ) in text.iter().enumerate() {
+                    value |= ((*c as u32 & 0xff) as i64) << (8 * i)
+                }
+                Ok(value)
+            },
+            AMD64Node::Location( _ , _ ) => Ok(self.location),
+            AMD64Node::Ident( _ , _ , t ) => {
+                if register_amd64(t.as_str()).is_some() {
+                    return Err(self.error_at(E_ASSEMBLER_OPERAND, format!("Register '{}' is not allowed in expression", t), s, e))
+                }
+                if let Some( x ) = self.constants.get(t.as_str()) {
+                    return Ok(*x)
+                }
+                if self.pending.contains(t.as_str()) {
+                    return Ok(self.location)
+                }
+                match self.labels.get(t.as_str()) {
+                    Some( x ) => Ok(*x),
+                    _ if self.variables.contains_key(t.as_str()) => {
+                        match self.variables.get(t.as_str()) {
+                            Some( OberonVariable::Frame( offset , _ ) ) => Ok(*offset),
+                            _ => Err(self.error_at(E_ASSEMBLER_OPERAND, format!("Module variable '{}' can only be used as memory operand", t), s, e))
+                        }
+                    },
+                    _ => {
+                        let name = match t.find('@') {
+                            Some( i ) => &t[i ..],
+                            None => t.as_str()
+                        };
+                        Err(self.error_at(E_ASSEMBLER_UNDEFINED, format!("Undefined symbol '{}' in assembler code", name), s, e))
+                    }
+                }
+            },
+            AMD64Node::Plus( _ , _ , l , _ , r ) => Ok(self.evaluate(l)?.wrapping_add(self.evaluate(r)?)),
+            AMD64Node::Minus( _ , _ , l , _ , r ) => Ok(self.evaluate(l)?.wrapping_sub(self.evaluate(r)?)),
+            AMD64Node::Times( _ , _ , l , _ , r ) => Ok(self.evaluate(l)?.wrapping_mul(self.evaluate(r)?)),
+            AMD64Node::Div( _ , _ , l , _ , r ) |
+            AMD64Node::Modulo( _ , _ , l , _ , r ) => {
+                let left = self.evaluate(l)?;
+                let right = self.evaluate(r)?;
+                match ( right , node ) {
+                    ( 0 , _ ) => Err(self.error_at(E_DIVISION_BY_ZERO, String::from("Division by zero in assembler expression"), s, e)),
+                    ( _ , AMD64Node::Div( .. ) ) => Ok(left.wrapping_div(right)),
+                    _ => Ok(left.wrapping_rem(right))
+                }
+            },
+            AMD64Node::UnaryMinus( _ , _ , _ , x ) => Ok(self.evaluate(x)?.wrapping_neg()),
+            AMD64Node::UnaryPlus( _ , _ , _ , x ) => self.evaluate(x),
+            AMD64Node::Negate( _ , _ , _ , x ) => Ok(!self.evaluate(x)?),
+            _ => Err(self.error_at(E_ASSEMBLER_OPERAND, String::from("Expecting constant expression"), s, e))
+        }
+    }
+
+    /// Splits address expression into registers with scale factors, returns the constant displacement
+    fn linear(&self, node: &AMD64Node, sign: i64, registers: &mut Vec<(RegisterAMD64, i64)>) -> Result<i64, Box<Diagnostic>> {
+        let ( s , e ) = node_span(node);
+
+        match node {
+            AMD64Node::Ident( _ , _ , t ) if register_amd64(t.as_str()).is_some() => {
+                if let Some( r ) = register_amd64(t.as_str()) {
+                    registers.push(( r , sign ))
+                }
+                Ok(0)
+            },
+            AMD64Node::Ident( _ , _ , t ) if matches!(self.variable(t.as_str()), Some( OberonVariable::Global( .. ) )) => {
+                /* Address of module variable is added by relocation */
+                match sign {
+                    1 => Ok(0),
+                    _ => Err(self.error_at(E_ASSEMBLER_OPERAND, format!("Module variable '{}' can only be added in memory operand", t), s, e))
+                }
+            },
+            AMD64Node::Plus( _ , _ , l , _ , r ) => Ok(self.linear(l, sign, registers)?.wrapping_add(self.linear(r, sign, registers)?)),
+            AMD64Node::Minus( _ , _ , l , _ , r ) => Ok(self.linear(l, sign, registers)?.wrapping_add(self.linear(r, sign.wrapping_neg(), registers)?)),
+            AMD64Node::UnaryMinus( _ , _ , _ , x ) => self.linear(x, sign.wrapping_neg(), registers),
+            AMD64Node::UnaryPlus( _ , _ , _ , x ) => self.linear(x, sign, registers),
+            AMD64Node::Times( _ , _ , l , _ , r ) if has_register(node) => {
+                match ( has_register(l) , has_register(r) ) {
+                    ( true , false ) => self.linear(l, sign.wrapping_mul(self.evaluate(r)?), registers),
+                    ( false , true ) => self.linear(r, sign.wrapping_mul(self.evaluate(l)?), registers),
+                    _ => Err(self.error_at(E_ASSEMBLER_OPERAND, String::from("Registers cannot be multiplied in memory operand"), s, e))
+                }
+            },
+            _ if has_register(node) => Err(self.error_at(E_ASSEMBLER_OPERAND, String::from("Invalid use of register in memory operand"), s, e)),
+            _ => Ok(sign.wrapping_mul(self.evaluate(node)?))
+        }
+    }
+
+    /// Register, immediate or memory operand with base, index, scale and displacement
+    fn resolve_operand(&self, node: &AMD64Node) -> Result<AMD64Operand, Box<Diagnostic>> {
+        match node {
+            AMD64Node::Ident( _ , _ , t ) if register_amd64(t.as_str()).is_some() => {
+                match register_amd64(t.as_str()) {
+                    Some( r ) => Ok(AMD64Operand::Register(r)),
+                    _ => Err(self.error_at(E_ASSEMBLER_OPERAND, String::from("Expecting register"), node_span(node).0, node_span(node).1))
+                }
+            },
+            AMD64Node::Ident(s, e, t) if self.variable(t.as_str()).is_some() => {
+                /* Oberon variable by name alone is a memory operand */
+                self.resolve_operand(&AMD64Node::Memory(*s, *e, None, Box::new(node.clone())))
+            },
+            AMD64Node::Memory(s, e, size, x) => {
+                let mut terms = Vec::<(RegisterAMD64, i64)>::new();
+                let displacement = self.linear(x, 1, &mut terms)?;
+
+                let mut variables = Vec::<(String, OberonVariable)>::new();
+                self.variables_in(x, &mut variables);
+                let global = variables.iter().any(|x| matches!(x.1, OberonVariable::Global( .. )));
+                match ( global , variables.len() , terms.is_empty() ) {
+                    ( true , 1 , true ) => (),
+                    ( true , _ , _ ) => return Err(self.error_at(E_ASSEMBLER_OPERAND, String::from("Module variable cannot be combined with registers or other variables in memory operand"), *s, *e)),
+                    ( _ , 0 , _ ) | ( _ , _ , false ) => (),
+                    _ => terms.push(( RegisterAMD64::General(5, 64) , 1 ))    /* Parameters and local variables are relative to RBP */
+                }
+                if global {
+                    terms.push(( RegisterAMD64::Rip , 1 ))
+                }
+
+                /* Operand size is taken from Oberon variable, or must agree with it */
+                let size = match ( size , self.sized_variable(x) ) {
+                    ( None , Some( ( _ , bytes ) ) ) => Some( bytes * 8 ),
+                    ( Some( bits ) , Some( ( name , bytes ) ) ) if *bits != bytes * 8 => {
+                        return Err(self.error_at(E_ASSEMBLER_OPERAND, format!("Operand size does not match '{}' of {} bytes", name, bytes), *s, *e))
+                    },
+                    _ => *size
+                };
+
+                /* Same register is only added together when there are more than base and index, like 'RBX + RAX - RAX' */
+                let mut registers = Vec::<(RegisterAMD64, i64)>::new();
+                match terms.len() {
+                    0 ..= 2 => registers = terms,
+                    _ => {
+                        for ( r , scale ) in terms {
+                            match registers.iter().position(|x| x.0 == r) {
+                                Some( i ) => registers[i].1 = registers[i].1.wrapping_add(scale),
+                                _ => registers.push(( r , scale ))
+                            }
+                        }
+                    }
+                }
+                registers.retain(|x| x.1 != 0);
+
+                let mut width = 0;
+                for ( r , scale ) in registers.iter() {
+                    match r {
+                        RegisterAMD64::General( _ , 16 | 32 | 64 ) if width == 0 || width == r.bits() => width = r.bits(),
+                        RegisterAMD64::General( _ , 16 | 32 | 64 ) => return Err(self.error_at(E_ASSEMBLER_OPERAND, String::from("Mixed 16, 32 and 64 bits registers in memory operand"), *s, *e)),
+                        RegisterAMD64::Rip if registers.len() == 1 && *scale == 1 => (),
+                        _ => return Err(self.error_at(E_ASSEMBLER_OPERAND, String::from("Invalid register in memory operand"), *s, *e))
+                    }
+                    match scale {
+                        1 | 2 | 4 | 8 => (),
+                        _ => return Err(self.error_at(E_ASSEMBLER_OPERAND, String::from("Scale factor must be 1, 2, 4 or 8 in memory operand"), *s, *e))
+                    }
+                }
+
+                let ( base , index ) = match registers.len() {
+                    0 => ( None , None ),
+                    1 if registers[0].1 == 1 => ( Some( registers[0].0.clone() ) , None ),
+                    1 => ( None , Some( ( registers[0].0.clone() , registers[0].1 as u8 ) ) ),
+                    2 if registers[1].1 == 1 && registers[0].1 != 1 => ( Some( registers[1].0.clone() ) , Some( ( registers[0].0.clone() , registers[0].1 as u8 ) ) ),
+                    2 if registers[0].1 == 1 && registers[1].1 == 1 && registers[1].0.number() == 4 => ( Some( registers[1].0.clone() ) , Some( ( registers[0].0.clone() , 1 ) ) ),
+                    2 if registers[0].1 == 1 => ( Some( registers[0].0.clone() ) , Some( ( registers[1].0.clone() , registers[1].1 as u8 ) ) ),
+                    _ => return Err(self.error_at(E_ASSEMBLER_OPERAND, String::from("Too many registers in memory operand"), *s, *e))
+                };
+
+                match &index {
+                    Some( ( r , _ ) ) if r.number() == 4 => return Err(self.error_at(E_ASSEMBLER_OPERAND, String::from("Stack pointer cannot be used as index register"), *s, *e)),
+                    _ => ()
+                }
+
+                Ok(AMD64Operand::Memory(size, base, index, displacement))
+            },
+            AMD64Node::Masked(s, e, x, k, z) => {
+                match ( *k , *z , &**x ) {
+                    ( 0 , _ , _ ) => Err(self.error_at(E_ASSEMBLER_OPERAND, String::from("Zeroing needs an opmask register, like '{K1}{Z}'"), *s, *e)),
+                    ( _ , _ , AMD64Node::Memory( .. ) | AMD64Node::Broadcast( .. ) ) if *z => Err(self.error_at(E_ASSEMBLER_OPERAND, String::from("Zeroing cannot be used with memory destination"), *s, *e)),
+                    _ => Ok(AMD64Operand::Masked(Box::new(self.resolve_operand(x)?), *k, *z))
+                }
+            },
+            AMD64Node::Broadcast( _ , _ , x , n ) => Ok(AMD64Operand::Broadcast(Box::new(self.resolve_operand(x)?), *n)),
+            _ => Ok(AMD64Operand::Immediate(self.evaluate(node)?))
+        }
+    }
+
+    /// Defines label or 'EQU' constant, each name can only be defined once
+    fn define(&mut self, name: &str, value: i64, is_label: bool, start: u32, end: u32) -> Result<(), Box<Diagnostic>> {
+        if register_amd64(name).is_some() {
+            return Err(self.error_at(E_ASSEMBLER_DUPLICATE, format!("Register name '{}' cannot be redefined", name), start, end))
+        }
+        if self.labels.contains_key(name) || self.constants.contains_key(name) {
+            return Err(self.error_at(E_ASSEMBLER_DUPLICATE, format!("Symbol '{}' is already defined in assembler code", name), start, end))
+        }
+        match is_label {
+            true => self.labels.insert(String::from(name), value),
+            _ => self.constants.insert(String::from(name), value)
+        };
+        Ok(())
+    }
+
+    /// Name of local label '@name' inside the scope of the last label before it, other names are unchanged
+    fn qualified(&self, name: &str) -> Box<String> {
+        match name.starts_with('@') {
+            true => Box::new(format!("{}{}", self.scope, name)),
+            _ => Box::new(String::from(name))
+        }
+    }
+
+    /// Encode line at current location into code, after 'ABSOLUTE' only the location moves
+    fn emit(&mut self, line: &AMD64Node, near: bool, code: &mut Vec<u8>) -> Result<(), Box<Diagnostic>> {
+        let ( s , e ) = node_span(line);
+
+        match line {
+            AMD64Node::Instruction( _ , _ , _ , _ ) | AMD64Node::Data( _ , _ , _ , _ ) if self.absolute => {
+                Err(self.error_at(E_ASSEMBLER_SYNTAX, String::from("Only labels and reserved space are allowed after 'ABSOLUTE'"), s, e))
+            },
+            AMD64Node::Instruction( _ , _ , mnemonic, operands) => {
+                let mut values = Vec::<AMD64Operand>::new();
+                for x in operands.iter() {
+                    values.push(self.resolve_operand(x)?)
+                }
+                let ( mut bytes , displacement ) = match encode_instruction_amd64_mode(mnemonic.clone(), Box::new(values.clone()), self.location, self.flags, self.bits, near) {
+                    Ok( x ) => x,
+                    Err( x ) => {
+                        /* Size of Oberon variable is the likely cause when operands do not match */
+                        let sized = operands.iter().find_map(|x| match &**x {
+                            AMD64Node::Memory( _ , _ , None , y ) => self.sized_variable(y),
+                            AMD64Node::Ident( .. ) => self.sized_variable(x),
+                            _ => None
+                        });
+                        return match ( x.code , sized ) {
+                            ( E_ASSEMBLER_OPERAND , Some( ( name , size ) ) ) => Err(self.error_at(x.code, format!("Operands of '{}' do not match size of '{}', which is {} bytes", mnemonic, name, size), s, e)),
+                            _ => Err(self.error_at(x.code, (*x.message).clone(), s, e))
+                        }
+                    }
+                };
+
+                /* Module variable is reached RIP relative, displacement holds offset inside variable until linker adds the address */
+                let mut variables = Vec::<(String, OberonVariable)>::new();
+                for x in operands.iter() {
+                    self.variables_in(x, &mut variables)
+                }
+                if let ( Some( ( _ , OberonVariable::Global( symbol , _ ) ) ) , Some( i ) ) = ( variables.iter().find(|x| matches!(x.1, OberonVariable::Global( .. ))) , displacement ) {
+                    let offset = i32::from_le_bytes([ bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3] ]) as i64;
+                    bytes[i .. i + 4].copy_from_slice(&[ 0, 0, 0, 0 ]);
+                    self.relocations.push(Relocation { offset: code.len() + i, symbol: symbol.clone(), addend: offset - (bytes.len() - i) as i64 })
+                }
+                self.instructions.push(( self.location , mnemonic.clone() , values ));
+                self.location += bytes.len() as i64;
+                code.extend_from_slice(&bytes);
+                Ok(())
+            },
+            AMD64Node::Data( _ , _ , size, items ) => {
+                let mut bytes = Vec::<u8>::new();
+                for item in items.iter() {
+                    match &**item {
+                        AMD64Node::String( _ , _ , t ) => {
+                            /* Strings are stored one character per byte, padded with zero to a whole item */
+                            for c in string_text(t).chars() {
+                                bytes.push((c as u32 & 0xff) as u8)
+                            }
+                            while !bytes.len().is_multiple_of(*size as usize) {
+                                bytes.push(0)
+                            }
+                        },
+                        _ => {
+                            let value = self.evaluate(item)?;
+                            let bits = 8 * *size;
+                            if bits < 64 && ( value < -(1 << (bits - 1)) || value >= 1 << bits ) {
+                                let ( a , b ) = node_span(item);
+                                return Err(self.error_at(E_ASSEMBLER_OPERAND, format!("Value does not fit in {} bits", bits), a, b))
+                            }
+                            bytes.extend_from_slice(&value.to_le_bytes()[.. *size as usize])
+                        }
+                    }
+                }
+                self.location += bytes.len() as i64;
+                code.extend_from_slice(&bytes);
+                Ok(())
+            },
+            AMD64Node::Reserve( _ , _ , size, count ) => {
+                let total = self.count(count)? * *size as i64;
+                if !self.absolute {
+                    code.resize(code.len() + total as usize, 0)
+                }
+                self.location += total;
+                Ok(())
+            },
+            AMD64Node::Repeat( _ , _ , count, line ) => {
+                for _ in 0 .. self.count(count)? {
+                    self.emit(line, near, code)?
+                }
+                Ok(())
+            },
+            AMD64Node::Align( _ , _ , x ) => {
+                let alignment = self.evaluate(x)?;
+                if alignment <= 0 || alignment & (alignment - 1) != 0 {
+                    return Err(self.error_at(E_ASSEMBLER_OPERAND, String::from("Alignment must be a power of two"), s, e))
+                }
+                /* Code is padded with NOP instructions */
+                let padding = (alignment - self.location.rem_euclid(alignment)) % alignment;
+                if !self.absolute {
+                    code.resize(code.len() + padding as usize, 0x90)
+                }
+                self.location += padding;
+                Ok(())
+            },
+            AMD64Node::Origin( _ , _ , x ) => {
+                if self.absolute || !code.is_empty() {
+                    return Err(self.error_at(E_ASSEMBLER_SYNTAX, String::from("'ORG' must come before any code or data"), s, e))
+                }
+                self.location = self.evaluate(x)?;
+                Ok(())
+            },
+            AMD64Node::Absolute( _ , _ , x ) => {
+                self.location = self.evaluate(x)?;
+                self.absolute = true;
+                Ok(())
+            },
+            AMD64Node::Bits( _ , _ , n ) => {
+                self.bits = *n;
+                Ok(())
+            },
+            AMD64Node::Cpu( _ , _ , x ) => {
+                self.flags = *x;
+                Ok(())
+            },
+            _ => Ok(())
+        }
+    }
+
+    /// Value of repeat or reserve count, it must not be negative
+    fn count(&self, node: &AMD64Node) -> Result<i64, Box<Diagnostic>> {
+        let ( s , e ) = node_span(node);
+
+        match self.evaluate(node)? {
+            x if !(0 ..= 0x10_0000).contains(&x) => Err(self.error_at(E_ASSEMBLER_OPERAND, format!("Count {} is out of range", x), s, e)),
+            x => Ok(x)
+        }
+    }
+
+    /// Oberon variable with name, names defined in assembler text hide variables
+    fn variable(&self, name: &str) -> Option<&OberonVariable> {
+        match self.labels.contains_key(name) || self.constants.contains_key(name) || self.pending.contains(name) {
+            true => None,
+            _ => self.variables.get(name)
+        }
+    }
+
+    /// Oberon variables named in expression
+    fn variables_in(&self, node: &AMD64Node, found: &mut Vec<(String, OberonVariable)>) {
+        match node {
+            AMD64Node::Ident( _ , _ , t ) => {
+                if let Some( x ) = self.variable(t.as_str()) {
+                    found.push(( (**t).clone() , x.clone() ))
+                }
+            },
+            AMD64Node::Times( _ , _ , l , _ , r ) |
+            AMD64Node::Div( _ , _ , l , _ , r ) |
+            AMD64Node::Modulo( _ , _ , l , _ , r ) |
+            AMD64Node::Minus( _ , _ , l , _ , r ) |
+            AMD64Node::Plus( _ , _ , l , _ , r ) => {
+                self.variables_in(l, found);
+                self.variables_in(r, found)
+            },
+            AMD64Node::UnaryMinus( _ , _ , _ , x ) |
+            AMD64Node::UnaryPlus( _ , _ , _ , x ) |
+            AMD64Node::Negate( _ , _ , _ , x ) |
+            AMD64Node::Memory( _ , _ , _ , x ) |
+            AMD64Node::Masked( _ , _ , x , _ , _ ) |
+            AMD64Node::Broadcast( _ , _ , x , _ ) => self.variables_in(x, found),
+            _ => ()
+        }
+    }
+
+    /// Name and size of Oberon variable that memory expression is exactly, like 'x' or 'RBP + x', when it has size of an operand
+    fn sized_variable(&self, node: &AMD64Node) -> Option<(String, u32)> {
+        let name = match node {
+            AMD64Node::Ident( _ , _ , t ) => t,
+            AMD64Node::Plus( _ , _ , l , _ , r ) => {
+                match ( &**l , &**r ) {
+                    ( AMD64Node::Ident( _ , _ , a ) , AMD64Node::Ident( _ , _ , b ) ) if a.to_uppercase() == "RBP" => b,
+                    ( AMD64Node::Ident( _ , _ , a ) , AMD64Node::Ident( _ , _ , b ) ) if b.to_uppercase() == "RBP" => a,
+                    _ => return None
+                }
+            },
+            _ => return None
+        };
+
+        match self.variable(name.as_str()) {
+            Some( OberonVariable::Frame( _ , size ) ) |
+            Some( OberonVariable::Global( _ , size ) ) if [ 1, 2, 4, 8, 10, 16, 32, 64 ].contains(size) => Some( ( (**name).clone() , *size ) ),
+            _ => None
         }
     }
 
-    fn factor(&mut self) -> Result<Box<AMD64Node>, Box<String>> {
-        todo!()
+    fn advance(&mut self) -> () {
+        self.symbol = self.get_symbol();
+    }
+
+    /// Error covering the current symbol, positioned in the Oberon source file
+    fn error(&self, code: &'static str, message: String) -> Box<Diagnostic> {
+        Diagnostic::error(code, message, self.offset_position + self.start_pos, self.offset_position + self.index)
+    }
+
+    /// Error covering part of assembler text, positioned in the Oberon source file
+    fn error_at(&self, code: &'static str, message: String, start: u32, end: u32) -> Box<Diagnostic> {
+        Diagnostic::error(code, message, self.offset_position + start, self.offset_position + end)
     }
+}
 
-    fn term(&mut self) -> Result<Box<AMD64Node>, Box<String>> {
-        todo!()
+impl InlineAssembler for AssemblerAMD64 {
+    fn set_variables(&mut self, variables: HashMap<String, OberonVariable>) {
+        self.variables = variables
     }
 
-    fn expression(&mut self) -> Result<Box<AMD64Node>, Box<String>> {
-        todo!()
+    fn get_relocations(&self) -> &Vec<Relocation> {
+        &self.relocations
     }
 
     /// Entry point for inline assemble of block of code in AMD64 instruction set
-    fn assemble(&mut self) -> Result<Box<Vec<u8>>, Box<String>> {
+    fn assemble(&mut self) -> Result<Box<Vec<u8>>, Box<Diagnostic>> {
         let mut flags : CpuFlags = 0;
 
         self.advance();
@@ -432,7 +1241,7 @@ impl AssemblerAMD64Methods for AssemblerAMD64 {
 
                                     match *self.symbol.clone()? {
                                         AMD64Symbols::Period( _ , _ )  => self.advance(),
-                                        _ => return Err(Box::new(format!("Identifier missing in assembler code at position: '{}'", self.get_position())))
+                                        _ => return Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Identifier missing in assembler code")))
                                     }
 
                                     match *self.symbol.clone()? {
@@ -456,7 +1265,11 @@ impl AssemblerAMD64Methods for AssemblerAMD64 {
                                                 "CPU_3DNOW" => flags |= CPU_3DNOW,
                                                 "CPU_MMX" => flags |= CPU_MMX,
                                                 "CPU_FPU" => flags |= CPU_FPU,
-                                                _ => return Err(Box::new(format!("Unknown CPU type flag in assembler code at position: '{}'", self.get_position())))
+                                                "CPU_AVX" => flags |= CPU_AVX,
+                                                "CPU_AVX2" => flags |= CPU_AVX2,
+                                                "CPU_FMA" => flags |= CPU_FMA,
+                                                "CPU_AVX512" => flags |= CPU_AVX512,
+                                                _ => return Err(self.error(E_ASSEMBLER_CPU_FLAG, String::from("Unknown CPU type flag in assembler code")))
                                             }
                                             self.advance();
                                         },
@@ -471,122 +1284,139 @@ impl AssemblerAMD64Methods for AssemblerAMD64 {
                                         AMD64Symbols::Comma( _ , _ ) => {
                                             self.advance()
                                         },
-                                        _ => return Err(Box::new(format!("Target identifier expected in assembler code at position: '{}'", self.get_position())))
+                                        _ => return Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Target identifier expected in assembler code")))
                                     }
                                 },
-                                _ => return Err(Box::new(format!("Unsupported target identifier in assembler code at position: '{}'", self.get_position())))
+                                _ => return Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Unsupported target identifier in assembler code")))
                             }
                         },
                         AMD64Symbols::RightCurly( _ , _ ) => {
                             self.advance();
                             break
                         }
-                        _ => return Err(Box::new(format!("Missing target identifier in assembler code at position: '{}'", self.get_position())))
+                        _ => return Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Missing target identifier in assembler code")))
                     }
                 }
             },
             _ => ()
         }
 
+        /* Code block without CPU flags may use all instructions of the user mode X86-64 base line */
+        let flags = match flags {
+            0 => CPU_AMD64 | CPU_PROTECTED,
+            x => x
+        };
+        self.flags = flags;
+
+        let mut lines = Vec::<Box<AMD64Node>>::new();
+
         // High level instructions in assembler language
         loop {
             match *self.symbol.clone()? {
                 AMD64Symbols::NewLine( _ , _ ) => {
                   self.advance();
                 },
+                AMD64Symbols::Label(s, e, t) => {
+                    self.advance();
+                    let name = self.qualified(t.as_str());
+                    match *self.symbol.clone()? {
+                        AMD64Symbols::Ident( _ , _ , x ) if x.as_str() == "EQU" => {
+                            self.advance();
+                            let node = self.expression()?;
+                            let value = self.evaluate(&node)?;
+                            self.define(name.as_str(), value, false, s, e)?
+                        },
+                        _ => {
+                            self.define(name.as_str(), 0, true, s, e)?;
+                            if !t.starts_with('@') {
+                                self.scope = t
+                            }
+                            lines.push(Box::new(AMD64Node::Label(s, e, name)))
+                        }
+                    }
+                },
                 AMD64Symbols::Ident(s, e, t) => {
                     match &*t.as_str() {
-                        "BITS" => {
+                        "EQU" => {
+                            return Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Missing name before 'EQU' in assembler code")))
+                        },
+                        "REP" | "LOCK" | "REPE" | "REPZ" | "REPNE" | "REPNZ" => {
+                            /* Prefix is encoded as its own instruction, followed by instruction on same line */
+                            lines.push(Box::new(AMD64Node::Instruction(s, e, t.clone(), Box::default())));
+                            self.advance()
+                        },
+                        _ => {
                             self.advance();
                             match *self.symbol.clone()? {
-                                AMD64Symbols::Number( _ , _ , n ) => {
+                                AMD64Symbols::Ident( _ , _ , x ) if x.as_str() == "EQU" => {
                                     self.advance();
-                                    // Set BITS to n later!
+                                    let node = self.expression()?;
+                                    let value = self.evaluate(&node)?;
+                                    let name = self.qualified(t.as_str());
+                                    self.define(name.as_str(), value, false, s, e)?
                                 },
-                                _ => ()
+                                _ => lines.push(self.statement(s, e, t)?)
                             }
-                        },
-                        "ALIGN" => {
-
-                        },
-                        "CPU" => {
-
-                        },
-                        "ABSOLUTE" => {
-
-                        },
-                        "ORG" => {
-
-                        },
-                        "RESB" => {
-
-                        },
-                        "RESW" => {
-
-                        },
-                        "RESD" => {
-
-                        },
-                        "EQU" => {
-
-                        },
-                        "TIMES" => {
-
-                        },
-                        "DB" => {
-
-                        },
-                        "DD" => {
-
-                        },
-                        "DQ" => {
-
-                        },
-                        "REP" => {
-
-                        },
-                        "LOCK" => {
-
-                        },
-                        "REPE" => {
-
-                        },
-                        "REPZ" => {
-
-                        },
-                        "REPNE" => {
-
-                        },
-                        "REPNZ" => {
-
-                        },
-                        _ => return Err(Box::new(format!("Expected identifier in assembler code at position: '{}'", self.get_position())))
+                        }
                     }
                 },
                 AMD64Symbols::EndOfFile( _ ) => break,
-                _ => return Err(Box::new(format!("Invalid symbol in inline assembler at position: '{}'", self.get_position())))
+                _ => return Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Invalid symbol in inline assembler")))
             }
         }
 
-        Ok(Box::new(Vec::<u8>::new()))
-    }
+        /* Code is encoded again until no label moves. Forward references are assumed to be short jumps in the first pass,
+           and a jump that grows keeps its near form in later passes, so labels only move forward and passes settle */
+        let mut code = Vec::<u8>::new();
+        let mut near = vec![ false; lines.len() ];
+        let mut sizes = vec![ 0; lines.len() ];
+        let mut passes = 0;
 
-    fn advance(&mut self) -> () {
-        self.symbol = self.get_symbol();
-    }
+        for line in lines.iter() {
+            if let AMD64Node::Label( _ , _ , t ) = &**line {
+                self.pending.insert((**t).clone());
+            }
+        }
 
-    fn skip_line(&mut self) -> Result<Box<AMD64Node>, Box<String>> {
         loop {
-            match *self.symbol.clone()? {
-                AMD64Symbols::NewLine( _ , _ ) => {
-                    self.advance();
-                    break
-                },
-                AMD64Symbols::EndOfFile( _ ) => break,
-                _ => self.advance()
+            let mut changed = false;
+            code.clear();
+            self.instructions.clear();
+            self.relocations.clear();
+            self.location = 0;
+            self.bits = 64;
+            self.flags = flags;
+            self.absolute = false;
+
+            for ( i , line ) in lines.iter().enumerate() {
+                match &**line {
+                    AMD64Node::Label( _ , _ , t ) => {
+                        self.pending.remove(t.as_str());
+                        match self.labels.insert((**t).clone(), self.location) {
+                            Some( x ) if x == self.location && passes > 0 => (),
+                            _ => changed = true
+                        }
+                    },
+                    x => {
+                        let size = code.len();
+                        self.emit(x, near[i], &mut code)?;
+                        if passes > 0 && code.len() - size > sizes[i] {
+                            near[i] = true
+                        }
+                        sizes[i] = code.len() - size
+                    }
+                }
+            }
+
+            passes += 1;
+            match ( changed , passes ) {
+                ( false , _ ) => break,
+                ( _ , x ) if x > lines.len() + 2 => return Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Labels in assembler code do not settle"))),
+                _ => ()
             }
         }
-        Ok(Box::new(AMD64Node::None))
+
+        Ok(Box::new(code))
     }
 }
 
@@ -595,6 +1425,10 @@ impl AssemblerAMD64Methods for AssemblerAMD64 {
 mod tests {
 
     use crate::amd64_assembler::{ AssemblerAMD64, AssemblerAMD64Methods, AMD64Symbols };
+    use crate::amd64_instruction_set_neo::{ AMD64Operand, RegisterAMD64 };
+    use crate::diagnostics::{ E_ASSEMBLER_SYNTAX, E_ASSEMBLER_UNDEFINED, E_ASSEMBLER_DUPLICATE, E_ASSEMBLER_OPERAND, E_ASSEMBLER_INSTRUCTION, E_ASSEMBLER_CPU_FLAG, E_DIVISION_BY_ZERO };
+    use crate::inline_assembler::{ InlineAssembler, OberonVariable, Relocation };
+    use std::collections::HashMap;
 
     #[test]
     fn test_assembler_amd64_lexer_ident() {
@@ -643,7 +1477,7 @@ mod tests {
         match res {
             Ok( x ) => {
                 match *x {
-                    AMD64Symbols::String_( 0, 14, t ) => {
+                    AMD64Symbols::String_( 0, 15, t ) => {
                         assert_eq!(*t, String::from("'Hello, World!'"))
                     },
                     _ => { assert!(false) }
@@ -982,6 +1816,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_assembler_amd64_lexer_local_label() {
+        let source = "@loop: ".chars().collect();
+        let mut lexer = AssemblerAMD64::new(source, 0);
+        let res = lexer.get_symbol();
+
+        match res {
+            Ok( x ) => {
+                match *x {
+                    AMD64Symbols::Label( 0, 6, t ) => assert_eq!(*t, String::from("@loop")),
+                    _ => { assert!(false) }
+                }
+            },
+            _ => assert!(false)
+        }
+    }
+
     #[test]
     fn test_assembler_amd64_lexer_dollar() {
         let source = "  $  ".chars().collect();
@@ -1057,4 +1908,449 @@ mod tests {
             Err( e ) => assert!(false)
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_assembler_amd64_expression_precedence() {
+        let source = "2 + 3 * (4 - 1) % 5 - -1".chars().collect();
+        let mut assembler = AssemblerAMD64::new(source, 0);
+        assembler.advance();
+        let node = assembler.expression().unwrap();
+        assert_eq!(assembler.evaluate(&node), Ok( 7 ))
+    }
+
+    #[test]
+    fn test_assembler_amd64_expression_string_and_negate() {
+        let source = "'AB' + ~0FFH".chars().collect();
+        let mut assembler = AssemblerAMD64::new(source, 0);
+        assembler.advance();
+        let node = assembler.expression().unwrap();
+        assert_eq!(assembler.evaluate(&node), Ok( 0x4241 + !0xff ))
+    }
+
+    #[test]
+    fn test_assembler_amd64_expression_division_by_zero() {
+        let source = "10 / (2 - 2)".chars().collect();
+        let mut assembler = AssemblerAMD64::new(source, 7);
+        assembler.advance();
+        let node = assembler.expression().unwrap();
+        match assembler.evaluate(&node) {
+            Err( e ) => {
+                assert_eq!(e.code, E_DIVISION_BY_ZERO);
+                assert_eq!(e.span.start, 7)
+            },
+            _ => assert!(false)
+        }
+    }
+
+    #[test]
+    fn test_assembler_amd64_equ_labels_and_location() {
+        let source = "size EQU 4 * 8\nstart:\nMOV RAX, size + 2\nJMP $\nJMP finish\nfinish:".chars().collect();
+        let mut assembler = AssemblerAMD64::new(source, 0);
+        assert!(assembler.assemble().is_ok());
+
+        assert_eq!(assembler.constants.get("size"), Some( &32 ));
+        assert_eq!(assembler.labels.get("start"), Some( &0 ));
+        assert_eq!(assembler.instructions.len(), 3);
+        assert_eq!(assembler.instructions[0].2, vec![ AMD64Operand::Register(RegisterAMD64::General(0, 64)), AMD64Operand::Immediate(34) ]);
+        assert_eq!(assembler.instructions[1].2, vec![ AMD64Operand::Immediate(7) ]);
+        assert_eq!(*assembler.instructions[2].1, String::from("JMP"));
+        assert_eq!(assembler.labels.get("finish"), Some( &11 ))
+    }
+
+    #[test]
+    fn test_assembler_amd64_encode_block() {
+        let source = "{ SYSTEM.CPU_AMD64 }\nloop: ADD RAX, [RBP + 8*RCX - 16]\nREP STOSB\nJNZ loop\nRET".chars().collect();
+        let mut assembler = AssemblerAMD64::new(source, 0);
+        let pattern = Box::new(vec![ 0x48, 0x03, 0x44, 0xcd, 0xf0, 0xf3, 0xaa, 0x75, 0xf7, 0xc3 ]);
+
+        assert_eq!(assembler.assemble(), Ok( pattern ))
+    }
+
+    #[test]
+    fn test_assembler_amd64_encode_errors_positioned() {
+        let mut assembler = AssemblerAMD64::new(" FOO RAX".chars().collect(), 20);
+        match assembler.assemble() {
+            Err( e ) => {
+                assert_eq!(e.code, E_ASSEMBLER_INSTRUCTION);
+                assert_eq!(e.span.start, 21)
+            },
+            _ => assert!(false)
+        }
+
+        let mut assembler = AssemblerAMD64::new("{ SYSTEM.CPU_AMD64 }\nHADDPS XMM0, XMM1".chars().collect(), 0);
+        match assembler.assemble() {
+            Err( e ) => assert_eq!(e.code, E_ASSEMBLER_CPU_FLAG),
+            _ => assert!(false)
+        }
+    }
+
+    #[test]
+    fn test_assembler_amd64_memory_operand() {
+        let source = "offset EQU 16\nMOV RAX, QWORD PTR [RBP + 8*RCX - offset]\nLEA RDX, [RSI*4 + R12 + (2 + 3)]".chars().collect();
+        let mut assembler = AssemblerAMD64::new(source, 0);
+        assert!(assembler.assemble().is_ok());
+
+        assert_eq!(assembler.instructions[0].2[1], AMD64Operand::Memory(Some( 64 ), Some( RegisterAMD64::General(5, 64) ), Some( ( RegisterAMD64::General(1, 64), 8 ) ), -16));
+        assert_eq!(assembler.instructions[1].2[1], AMD64Operand::Memory(None, Some( RegisterAMD64::General(12, 64) ), Some( ( RegisterAMD64::General(6, 64), 4 ) ), 5))
+    }
+
+    #[test]
+    fn test_assembler_amd64_memory_operand_errors() {
+        let cases = [ "MOV RAX, [RAX * 3]", "MOV RAX, [RAX + RBX + RCX]", "MOV RAX, [RAX * RBX]", "MOV RAX, [EAX + RBX]", "MOV RAX, RBX + 1" ];
+        for case in cases.iter() {
+            let mut assembler = AssemblerAMD64::new(case.chars().collect(), 0);
+            match assembler.assemble() {
+                Err( e ) => assert_eq!(e.code, E_ASSEMBLER_OPERAND),
+                _ => assert!(false)
+            }
+        }
+    }
+
+    #[test]
+    fn test_assembler_amd64_undefined_and_duplicate_symbols() {
+        let mut assembler = AssemblerAMD64::new("MOV RAX, missing".chars().collect(), 10);
+        match assembler.assemble() {
+            Err( e ) => {
+                assert_eq!(e.code, E_ASSEMBLER_UNDEFINED);
+                assert_eq!(e.span.start, 19)
+            },
+            _ => assert!(false)
+        }
+
+        let mut assembler = AssemblerAMD64::new("a:\na EQU 1".chars().collect(), 0);
+        match assembler.assemble() {
+            Err( e ) => assert_eq!(e.code, E_ASSEMBLER_DUPLICATE),
+            _ => assert!(false)
+        }
+    }
+
+    #[test]
+    fn test_assembler_amd64_jump_relaxation() {
+        let source = format!("JMP done\n{}done: RET", "NOP\n".repeat(130));
+        let mut assembler = AssemblerAMD64::new(source.chars().collect(), 0);
+        let code = assembler.assemble().unwrap();
+        assert_eq!(code[0 .. 5], [ 0xe9, 0x82, 0x00, 0x00, 0x00 ]);
+        assert_eq!(code.len(), 136);
+
+        let mut assembler = AssemblerAMD64::new("top: NOP\nJMP top".chars().collect(), 0);
+        assert_eq!(assembler.assemble(), Ok( Box::new(vec![ 0x90, 0xeb, 0xfd ]) ));
+
+        /* Jump growing to near form moves the target of the first jump out of short range */
+        let source = format!("JZ target\nJMP far\n{}target: NOP\n{}far: RET", "NOP\n".repeat(123), "NOP\n".repeat(130));
+        let mut assembler = AssemblerAMD64::new(source.chars().collect(), 0);
+        let code = assembler.assemble().unwrap();
+        assert_eq!(code[0 .. 11], [ 0x0f, 0x84, 0x80, 0x00, 0x00, 0x00, 0xe9, 0xfe, 0x00, 0x00, 0x00 ]);
+        assert_eq!(assembler.labels.get("target"), Some( &134 ));
+        assert_eq!(assembler.labels.get("far"), Some( &265 ))
+    }
+
+    #[test]
+    fn test_assembler_amd64_local_labels() {
+        let source = "first:\n@loop: DEC RCX\nJNZ @loop\nsecond:\n@loop: DEC RDX\nJNZ @loop\nJMP first\n".chars().collect();
+        let mut assembler = AssemblerAMD64::new(source, 0);
+        let pattern = Box::new(vec![ 0x48, 0xff, 0xc9, 0x75, 0xfb, 0x48, 0xff, 0xca, 0x75, 0xfb, 0xeb, 0xf4 ]);
+
+        assert_eq!(assembler.assemble(), Ok( pattern ));
+        assert_eq!(assembler.labels.get("first@loop"), Some( &0 ));
+        assert_eq!(assembler.labels.get("second@loop"), Some( &5 ))
+    }
+
+    #[test]
+    fn test_assembler_amd64_label_errors_positioned() {
+        let mut assembler = AssemblerAMD64::new("a:\n@x: NOP\nb:\nJMP @x".chars().collect(), 100);
+        match assembler.assemble() {
+            Err( e ) => {
+                assert_eq!(e.code, E_ASSEMBLER_UNDEFINED);
+                assert_eq!(*e.message, String::from("Undefined symbol '@x' in assembler code"));
+                assert_eq!(e.span.start, 118)
+            },
+            _ => assert!(false)
+        }
+
+        let mut assembler = AssemblerAMD64::new("start: NOP\nstart: RET".chars().collect(), 100);
+        match assembler.assemble() {
+            Err( e ) => {
+                assert_eq!(e.code, E_ASSEMBLER_DUPLICATE);
+                assert_eq!(e.span.start, 111)
+            },
+            _ => assert!(false)
+        }
+
+        let mut assembler = AssemblerAMD64::new("JRCXZ far\n{}far:".replace("{}", "NOP\n".repeat(200).as_str()).chars().collect(), 0);
+        match assembler.assemble() {
+            Err( e ) => assert_eq!(e.code, E_ASSEMBLER_OPERAND),
+            _ => assert!(false)
+        }
+    }
+
+    #[test]
+    fn test_assembler_amd64_data_directives() {
+        let source = "DB 'AB', 0, -1, 255\nDW 1234H\nDD 'AB'\nstart: DQ start".chars().collect();
+        let mut assembler = AssemblerAMD64::new(source, 0);
+        let pattern = Box::new(vec![ 0x41, 0x42, 0x00, 0xff, 0xff, 0x34, 0x12, 0x41, 0x42, 0x00, 0x00, 0x0b, 0, 0, 0, 0, 0, 0, 0 ]);
+
+        assert_eq!(assembler.assemble(), Ok( pattern ));
+
+        let mut assembler = AssemblerAMD64::new("DB 1, 256".chars().collect(), 10);
+        match assembler.assemble() {
+            Err( e ) => {
+                assert_eq!(e.code, E_ASSEMBLER_OPERAND);
+                assert_eq!(e.span.start, 16)
+            },
+            _ => assert!(false)
+        }
+    }
+
+    #[test]
+    fn test_assembler_amd64_number_range() {
+        let mut assembler = AssemblerAMD64::new("DQ 0FFFFFFFFFFFFFFFFH, 18446744073709551615".chars().collect(), 0);
+        assert_eq!(assembler.assemble(), Ok( Box::new(vec![ 0xff; 16 ]) ));
+
+        let mut assembler = AssemblerAMD64::new("MOV RAX, 99999999999999999999".chars().collect(), 10);
+        match assembler.assemble() {
+            Err( e ) => {
+                assert_eq!(e.code, E_ASSEMBLER_OPERAND);
+                assert_eq!(e.span.start, 19);
+                assert_eq!(e.span.end, 39)
+            },
+            _ => assert!(false)
+        }
+
+        let mut assembler = AssemblerAMD64::new("DQ 10000000000000000H".chars().collect(), 0);
+        assert!(matches!(assembler.assemble(), Err( e ) if e.code == E_ASSEMBLER_OPERAND))
+    }
+
+    #[test]
+    fn test_assembler_amd64_strings_and_hex_prefix() {
+        let source = "DB 'a\"b', \"c'd\", ''\nDD \"AB\"\nMOV AL, 0x7F\nMOV AL, 7FH\nDB 0X".chars().collect();
+        let mut assembler = AssemblerAMD64::new(source, 0);
+        let pattern = Box::new(vec![ 0x61, 0x22, 0x62, 0x63, 0x27, 0x64, 0x41, 0x42, 0x00, 0x00, 0xb0, 0x7f, 0xb0, 0x7f, 0x00 ]);
+
+        assert_eq!(assembler.assemble(), Ok( pattern ))
+    }
+
+    #[test]
+    fn test_assembler_amd64_times_align_and_reserve() {
+        let source = "start: TIMES 2 NOP\nTIMES 3 DB 1\nALIGN 8\nRESW 2\nRET\nTIMES 16 - ($ - start) DB 0CCH".chars().collect();
+        let mut assembler = AssemblerAMD64::new(source, 0);
+        let pattern = Box::new(vec![ 0x90, 0x90, 0x01, 0x01, 0x01, 0x90, 0x90, 0x90, 0x00, 0x00, 0x00, 0x00, 0xc3, 0xcc, 0xcc, 0xcc ]);
+
+        assert_eq!(assembler.assemble(), Ok( pattern ));
+
+        let cases = [ "ALIGN 3", "TIMES -1 NOP", "RESB 2 3" ];
+        for case in cases.iter() {
+            let mut assembler = AssemblerAMD64::new(case.chars().collect(), 0);
+            assert!(assembler.assemble().is_err())
+        }
+    }
+
+    #[test]
+    fn test_assembler_amd64_origin_and_absolute() {
+        let source = "ORG 1000H\nstart: JMP start\nDQ start\nABSOLUTE 0\nfield1: RESD 1\nALIGN 8\nfield2: RESQ 1\nsize:".chars().collect();
+        let mut assembler = AssemblerAMD64::new(source, 0);
+        let pattern = Box::new(vec![ 0xeb, 0xfe, 0x00, 0x10, 0, 0, 0, 0, 0, 0 ]);
+
+        assert_eq!(assembler.assemble(), Ok( pattern ));
+        assert_eq!(assembler.labels.get("start"), Some( &0x1000 ));
+        assert_eq!(assembler.labels.get("field2"), Some( &8 ));
+        assert_eq!(assembler.labels.get("size"), Some( &16 ));
+
+        let cases = [ "NOP\nORG 10H", "ABSOLUTE 0\nNOP", "ABSOLUTE 0\nDB 1" ];
+        for case in cases.iter() {
+            let mut assembler = AssemblerAMD64::new(case.chars().collect(), 0);
+            match assembler.assemble() {
+                Err( e ) => assert_eq!(e.code, E_ASSEMBLER_SYNTAX),
+                _ => assert!(false)
+            }
+        }
+    }
+
+    #[test]
+    fn test_assembler_amd64_cpu_directive() {
+        let mut assembler = AssemblerAMD64::new("CPU PRESCOTT\nHADDPS XMM0, XMM1".chars().collect(), 0);
+        assert_eq!(assembler.assemble(), Ok( Box::new(vec![ 0xf2, 0x0f, 0x7c, 0xc1 ]) ));
+
+        let cases = [ "CPU 386\nHADDPS XMM0, XMM1", "CPU Z80" ];
+        for case in cases.iter() {
+            let mut assembler = AssemblerAMD64::new(case.chars().collect(), 0);
+            match assembler.assemble() {
+                Err( e ) => assert_eq!(e.code, E_ASSEMBLER_CPU_FLAG),
+                _ => assert!(false)
+            }
+        }
+    }
+
+    #[test]
+    fn test_assembler_amd64_bits_modes() {
+        let source = "BITS 32\nMOV EAX, [EBX + 4]\nMOV AX, 1\nADD EAX, [1000H]\nBITS 16\nMOV AX, 1\nMOV EAX, 1\nMOV AX, [EBX]\nPUSH 1234H\nBITS 64\nMOV EAX, [EBX]".chars().collect();
+        let mut assembler = AssemblerAMD64::new(source, 0);
+        let pattern = Box::new(vec![
+            0x8b, 0x43, 0x04,
+            0x66, 0xb8, 0x01, 0x00,
+            0x03, 0x05, 0x00, 0x10, 0x00, 0x00,
+            0xb8, 0x01, 0x00,
+            0x66, 0xb8, 0x01, 0x00, 0x00, 0x00,
+            0x67, 0x8b, 0x03,
+            0x68, 0x34, 0x12,
+            0x67, 0x8b, 0x03
+        ]);
+
+        assert_eq!(assembler.assemble(), Ok( pattern ));
+
+        let mut assembler = AssemblerAMD64::new("BITS 16\nstart: NOP\nTIMES 200 NOP\nJMP start".chars().collect(), 0);
+        let code = assembler.assemble().unwrap();
+        assert_eq!(code[201 ..], [ 0xe9, 0x34, 0xff ]);
+
+        let cases = [ "BITS 32\nMOV RAX, 1", "BITS 32\nMOV EAX, [RBX]", "BITS 32\nMOV SIL, 1", "BITS 16\nMOV EAX, [RIP + 4]", "BITS 8" ];
+        for case in cases.iter() {
+            let mut assembler = AssemblerAMD64::new(case.chars().collect(), 0);
+            assert!(assembler.assemble().is_err())
+        }
+    }
+
+    #[test]
+    fn test_assembler_amd64_bits_stack_and_16_bits_addressing() {
+        let source = "BITS 32\nPUSH EAX\nPOP EBX\nJMP EAX\nCALL EBX\nPUSH DWORD [ESI]\nMOV AX, [BX]\nBITS 16\nMOV AX, [BX + SI]\nMOV AX, [BP]\nMOV CX, [DI + BX + 1234H]\nMOV [SI - 2], DL\nPUSH EAX\nJMP AX".chars().collect();
+        let mut assembler = AssemblerAMD64::new(source, 0);
+        let pattern = Box::new(vec![
+            0x50,
+            0x5b,
+            0xff, 0xe0,
+            0xff, 0xd3,
+            0xff, 0x36,
+            0x66, 0x67, 0x8b, 0x07,
+            0x8b, 0x00,
+            0x8b, 0x46, 0x00,
+            0x8b, 0x89, 0x34, 0x12,
+            0x88, 0x54, 0xfe,
+            0x66, 0x50,
+            0xff, 0xe0
+        ]);
+
+        assert_eq!(assembler.assemble(), Ok( pattern ));
+
+        let cases = [ "PUSH EAX", "JMP EAX", "MOV AX, [BX]", "BITS 16\nMOV AX, [BX + BP]", "BITS 16\nMOV AX, [SI * 2]", "BITS 16\nMOV AX, [BX + 10000H]", "BITS 16\nMOV AX, [BX + EAX]" ];
+        for case in cases.iter() {
+            let mut assembler = AssemblerAMD64::new(case.chars().collect(), 0);
+            assert!(assembler.assemble().is_err(), "{}", case)
+        }
+
+        let mut assembler = AssemblerAMD64::new("PUSH EAX".chars().collect(), 0);
+        match assembler.assemble() {
+            Err( e ) => assert_eq!(*e.message, "Operands of 'PUSH' are only available in 16 and 32 bits mode"),
+            _ => assert!(false)
+        }
+    }
+
+    fn with_variables(source: &str) -> AssemblerAMD64 {
+        let mut assembler = AssemblerAMD64::new(source.chars().collect(), 0);
+        assembler.set_variables(HashMap::from([
+            ( String::from("a"), OberonVariable::Frame(16, 8) ),
+            ( String::from("x"), OberonVariable::Frame(-8, 8) ),
+            ( String::from("y"), OberonVariable::Frame(-10, 2) ),
+            ( String::from("g"), OberonVariable::Global(String::from("Test.g"), 4) )
+        ]));
+        assembler
+    }
+
+    #[test]
+    fn test_assembler_amd64_oberon_variables() {
+        let mut assembler = with_variables("MOV RAX, [RBP + a]\nMOV x, RAX\nMOV AX, y\nMOV EAX, g\nADD g, 1\nMOV EDX, DWORD [g + 4]");
+        let pattern = Box::new(vec![
+            0x48, 0x8b, 0x45, 0x10,
+            0x48, 0x89, 0x45, 0xf8,
+            0x66, 0x8b, 0x45, 0xf6,
+            0x8b, 0x05, 0x00, 0x00, 0x00, 0x00,
+            0x83, 0x05, 0x00, 0x00, 0x00, 0x00, 0x01,
+            0x8b, 0x15, 0x00, 0x00, 0x00, 0x00
+        ]);
+
+        assert_eq!(assembler.assemble(), Ok( pattern ));
+        assert_eq!(*assembler.get_relocations(), vec![
+            Relocation { offset: 14, symbol: String::from("Test.g"), addend: -4 },
+            Relocation { offset: 20, symbol: String::from("Test.g"), addend: -5 },
+            Relocation { offset: 27, symbol: String::from("Test.g"), addend: 0 }
+        ]);
+
+        /* Names defined in assembler text hide Oberon variables */
+        let mut assembler = with_variables("x EQU 3\nMOV RAX, x");
+        assert_eq!(assembler.assemble(), Ok( Box::new(vec![ 0x48, 0xc7, 0xc0, 0x03, 0x00, 0x00, 0x00 ]) ))
+    }
+
+    #[test]
+    fn test_assembler_amd64_oberon_variable_errors() {
+        let mut assembler = with_variables("MOV EAX, x");
+        match assembler.assemble() {
+            Err( e ) => {
+                assert_eq!(e.code, E_ASSEMBLER_OPERAND);
+                assert_eq!(*e.message, String::from("Operands of 'MOV' do not match size of 'x', which is 8 bytes"))
+            },
+            _ => assert!(false)
+        }
+
+        let mut assembler = with_variables("MOV AX, DWORD [RBP + y]");
+        match assembler.assemble() {
+            Err( e ) => assert_eq!(*e.message, String::from("Operand size does not match 'y' of 2 bytes")),
+            _ => assert!(false)
+        }
+
+        let cases = [ "MOV RAX, [RBX + g]", "MOV RAX, g + 1", "MOV EAX, [g + x]", "MOV EAX, [RAX - g]" ];
+        for case in cases.iter() {
+            let mut assembler = with_variables(case);
+            match assembler.assemble() {
+                Err( e ) => assert_eq!(e.code, E_ASSEMBLER_OPERAND),
+                _ => assert!(false)
+            }
+        }
+
+        let mut assembler = with_variables("MOV RAX, z");
+        match assembler.assemble() {
+            Err( e ) => assert_eq!(e.code, E_ASSEMBLER_UNDEFINED),
+            _ => assert!(false)
+        }
+    }
+
+    #[test]
+    fn test_assembler_amd64_avx() {
+        let source = "CPU HASWELL\nVADDPS YMM1, YMM2, YMM3\nVFMADD231PD YMM1, YMM2, YWORD [RAX]\nVZEROUPPER".chars().collect();
+        let mut assembler = AssemblerAMD64::new(source, 0);
+        assert_eq!(assembler.assemble(), Ok( Box::new(vec![ 0xc5, 0xec, 0x58, 0xcb, 0xc4, 0xe2, 0xed, 0xb8, 0x08, 0xc5, 0xf8, 0x77 ]) ));
+
+        let mut assembler = AssemblerAMD64::new("VADDPS YMM1, YMM2, YMM3".chars().collect(), 0);
+        match assembler.assemble() {
+            Err( e ) => assert_eq!(e.code, E_ASSEMBLER_CPU_FLAG),
+            _ => assert!(false)
+        }
+    }
+
+    #[test]
+    fn test_assembler_amd64_avx512_decorations() {
+        let source = "CPU SKX\nVADDPS ZMM1 {K1}{Z}, ZMM2, ZMM3\nVADDPS ZMM1, ZMM2, DWORD [RAX + 8] {1TO16}\nVMOVDQU32 ZWORD [RBP - 128] {K2}, ZMM17\nKMOVW K1, EAX".chars().collect();
+        let mut assembler = AssemblerAMD64::new(source, 0);
+        let pattern = Box::new(vec![
+            0x62, 0xf1, 0x6c, 0xc9, 0x58, 0xcb,
+            0x62, 0xf1, 0x6c, 0x58, 0x58, 0x48, 0x02,
+            0x62, 0xe1, 0x7e, 0x4a, 0x7f, 0x4d, 0xfe,
+            0xc5, 0xf8, 0x92, 0xc8
+        ]);
+        assert_eq!(assembler.assemble(), Ok( pattern ));
+
+        let cases = [
+            "CPU SKX\nVADDPS ZMM1 {Z}, ZMM2, ZMM3",
+            "CPU SKX\nVADDPS ZMM1, ZMM2, DWORD [RAX] {1TO8}",
+            "CPU SKX\nVADDPS ZMM1 {K1}{K2}, ZMM2, ZMM3",
+            "CPU SKX\nVADDPS ZMM1 {K1, ZMM2, ZMM3",
+            "CPU SKX\nVADDPS ZMM1 {Q}, ZMM2, ZMM3",
+            "CPU SKX\nVMOVUPS ZWORD [RAX] {K1}{Z}, ZMM1",
+            "CPU HASWELL\nVADDPS YMM1 {K1}, YMM2, YMM3",
+            "CPU SKX\nVZEROUPPER XMM16"
+        ];
+        for case in cases.iter() {
+            let mut assembler = AssemblerAMD64::new(case.chars().collect(), 0);
+            match assembler.assemble() {
+                Err( e ) => assert!(e.code == E_ASSEMBLER_OPERAND || e.code == E_ASSEMBLER_SYNTAX || e.code == E_ASSEMBLER_INSTRUCTION, "{}", case),
+                _ => assert!(false, "{}", case)
+            }
+        }
+    }
+}
@@ -3,6 +3,7 @@
 // Written by Richard Magnor Stenbro. Licensed under GPL v3
 // Instruction set for X86-64 module for compiling and linking of projects written in ActiveOberon language
 
+use crate::diagnostics::{Diagnostic, DiagnosticMethods, E_ASSEMBLER_CPU_FLAG, E_ASSEMBLER_OPERAND, E_ASSEMBLER_INSTRUCTION};
 
 pub const CPU_8086 : u32 = 0;
 pub const CPU_186 : u32 = 1;
@@ -23,1032 +24,2975 @@ pub const CPU_SSE3 : u32 = 16384;
 pub const CPU_3DNOW : u32 = 32768;
 pub const CPU_MMX : u32 = 65536;
 pub const CPU_FPU : u32 = 131072;
+pub const CPU_AVX : u32 = 262144;
+pub const CPU_AVX2 : u32 = 524288;
+pub const CPU_FMA : u32 = 1048576;
+pub const CPU_AVX512 : u32 = 2097152;     /* AVX-512 foundation with 128 and 256 bits vector lengths */
 
 pub type CpuFlags = u32;
 
+/// Bytes of encoded instruction and position of displacement in RIP relative memory operand
+pub type EncodedAMD64 = ( Box<Vec<u8>>, Option<usize> );
 
-/// Encode a single assembler instructions with operands
-fn encode_instruction_amd64(instruction: Box<String>, operands: Box<Vec<Box<String>>>, flags: CpuFlags) -> Result<Box<Vec<u8>>, Box<String>> {
-
-    match &*instruction.as_str() {
-        "AAA" => {},
-        "AAD" => {},
-        "AAM" => {},
-        "AAS" => {},
-        "ADC" => {},
-        "ADD" => {},
-        "ADDPD" => {},
-        "ADDPS" => {},
-        "ADDSD" => {},
-        "ADDSS" => {},
-        "ADDSUBPD" => {},
-        "ADDSUBPS" => {},
-        "AND" => {},
-        "ANDNPD" => {},
-        "ANDNPS" => {},
-        "ANDPD" => {},
-        "ANDPS" => {},
-        "ARPL" => {},
-        "BOUND" => {},
-        "BSF" => {},
-        "BSR" => {},
-        "BSWAP" => {},
-        "BT" => {},
-        "BTC" => {},
-        "BTR" => {},
-        "BTS" => {},
-        "CALL" => {},
-        "CALLFAR" => {},
-        "CBW" => {},
-        "CDQ" => {},
-        "CDQE" => {},
-        "CLC" => {},
-        "CLD" => {},
-        "CLFLUSH" => {},
-        "CLGI" => {},
-        "CLI" => {},
-        "CLTS" => {},
-        "CMC" => {},
-        "CMOVA" => {},
-        "CMOVAE" => {},
-        "CMOVB" => {},
-        "CMOVBE" => {},
-        "CMOVC" => {},
-        "CMOVE" => {},
-        "CMOVG" => {},
-        "CMOVGE" => {},
-        "CMOVL" => {},
-        "CMOVLE" => {},
-        "CMOVNA" => {},
-        "CMOVNAE" => {},
-        "CMOVNB" => {},
-        "CMOVNBE" => {},
-        "CMOVNC" => {},
-        "CMOVNE" => {},
-        "CMOVNG" => {},
-        "CMOVNGE" => {},
-        "CMOVNL" => {},
-        "CMOVNLE" => {},
-        "CMOVNO" => {},
-        "CMOVNP" => {},
-        "CMOVNS" => {},
-        "CMOVNZ" => {},
-        "CMOVO" => {},
-        "CMOVP" => {},
-        "CMOVPE" => {},
-        "CMOVPO" => {},
-        "CMOVS" => {},
-        "CMOVZ" => {},
-        "CMP" => {},
-        "CMPPD" => {},
-        "CMPPS" => {},
-        "CMPS" => {},
-        "CMPSB" => {},
-        "CMPSD" => {},
-        "CMPSQ" => {},
-        "CMPSS" => {},
-        "CMPSW" => {},
-        "CMPXCHG" => {},
-        "CMPXCHG16B" => {},
-        "CMPXCHG8B" => {},
-        "COMISD" => {},
-        "COMISS" => {},
-        "CPUID" => {},
-        "CQO" => {},
-        "CVTDQ2PD" => {},
-        "CVTDQ2PS" => {},
-        "CVTPD2DQ" => {},
-        "CVTPD2PI" => {},
-        "CVTPD2PS" => {},
-        "CVTPI2PD" => {},
-        "CVTPI2PS" => {},
-        "CVTPS2DQ" => {},
-        "CVTPS2PD" => {},
-        "CVTPS2PI" => {},
-        "CVTSD2SI" => {},
-        "CVTSD2SS" => {},
-        "CVTSI2SD" => {},
-        "CVTSI2SS" => {},
-        "CVTSS2SD" => {},
-        "CVTSS2SI" => {},
-        "CVTTPD2DQ" => {},
-        "CVTTPD2PI" => {},
-        "CVTTPS2DQ" => {},
-        "CVTTPS2PI" => {},
-        "CVTTSD2SI" => {},
-        "CVTTSS2SI" => {},
-        "CWD" => {},
-        "CWDE" => {},
-        "DAA" => {},
-        "DAS" => {},
-        "DEC" => {},
-        "DIV" => {},
-        "DIVPD" => {},
-        "DIVPS" => {},
-        "DIVSD" => {},
-        "DIVSS" => {},
-        "EMMS" => {},
-        "ENTER" => {},
-        "F2XM1" => {},
-        "FABS" => {},
-        "FADD" => {},
-        "FADDP" => {},
-        "FBLD" => {},
-        "FBSTP" => {},
-        "FCHS" => {},
-        "FCLEX" => {},
-        "FCMOVB" => {},
-        "FCMOVBE" => {},
-        "FCMOVE" => {},
-        "FCMOVNB" => {},
-        "FCMOVNBE" => {},
-        "FCMOVNE" => {},
-        "FCMOVNU" => {},
-        "FCMOVU" => {},
-        "FCOM" => {},
-        "FCOMI" => {},
-        "FCOMIP" => {},
-        "FCOMP" => {},
-        "FCOMPP" => {},
-        "FCOS" => {},
-        "FDECSTP" => {},
-        "FDIV" => {},
-        "FDIVP" => {},
-        "FDIVR" => {},
-        "FDIVRP" => {},
-        "FEMMS" => {},
-        "FFREE" => {},
-        "FIADD" => {},
-        "FICOM" => {},
-        "FICOMP" => {},
-        "FIDIV" => {},
-        "FIDIVR" => {},
-        "FILD" => {},
-        "FIMUL" => {},
-        "FINCSTP" => {},
-        "FINIT" => {},
-        "FIST" => {},
-        "FISTP" => {},
-        "FISTTP" => {},
-        "FISUB" => {},
-        "FISUBR" => {},
-        "FLD" => {},
-        "FLD1" => {},
-        "FLDCW" => {},
-        "FLDENV" => {},
-        "FLDL2E" => {},
-        "FLDL2T" => {},
-        "FLDLG2" => {},
-        "FLDLN2" => {},
-        "FLDPI" => {},
-        "FLDZ" => {},
-        "FMUL" => {},
-        "FMULP" => {},
-        "FNCLEX" => {},
-        "FNINIT" => {},
-        "FN" => {},
-        "FNSAVE" => {},
-        "FNSTCW" => {},
-        "FNSTENV" => {},
-        "FNSTSW" => {},
-        "FPATAN" => {},
-        "FPREM" => {},
-        "FPREM1" => {},
-        "FPTAN" => {},
-        "FRNDINT" => {},
-        "FRSTOR" => {},
-        "FSAVE" => {},
-        "FSCALE" => {},
-        "FSIN" => {},
-        "FSINCOS" => {},
-        "FSQRT" => {},
-        "FST" => {},
-        "FSTCW" => {},
-        "FSTENV" => {},
-        "FSTP" => {},
-        "FSTSW" => {},
-        "FSUB" => {},
-        "FSUBP" => {},
-        "FSUBR" => {},
-        "FSUBRP" => {},
-        "FTST" => {},
-        "FUCOM" => {},
-        "FUCOMI" => {},
-        "FUCOMIP" => {},
-        "FUCOMP" => {},
-        "FUCOMPP" => {},
-        "FWAIT" => {},
-        "FXAM" => {},
-        "FXCH" => {},
-        "FXRSTOR" => {},
-        "FXSAVE" => {},
-        "FXTRACT" => {},
-        "FYL2X" => {},
-        "FYL2XP1" => {},
-        "HADDPD" => {},
-        "HADDPS" => {},
-        "HLT" => {},
-        "HSUBPD" => {},
-        "HSUBPS" => {},
-        "IDIV" => {},
-        "IMUL" => {},
-        "IN" => {},
-        "INC" => {},
-        "INS" => {},
-        "INSB" => {},
-        "INSD" => {},
-        "INSW" => {},
-        "INT" => {},
-        "INT3" => {},
-        "INTO" => {},
-        "INVD" => {},
-        "INVLPG" => {},
-        "INVLPGA" => {},
-        "IRET" => {},
-        "IRETD" => {},
-        "IRETQ" => {},
-        "JA" => {},
-        "JAE" => {},
-        "JB" => {},
-        "JBE" => {},
-        "JC" => {},
-        "JCXZ" => {},
-        "JE" => {},
-        "JECXZ" => {},
-        "JG" => {},
-        "JGE" => {},
-        "JL" => {},
-        "JLE" => {},
-        "JMP" => {},
-        "JMPFAR" => {},
-        "JNA" => {},
-        "JNAE" => {},
-        "JNB" => {},
-        "JNBE" => {},
-        "JNC" => {},
-        "JNE" => {},
-        "JNG" => {},
-        "JNGE" => {},
-        "JNL" => {},
-        "JNLE" => {},
-        "JNO" => {},
-        "JNP" => {},
-        "JNS" => {},
-        "JNZ" => {},
-        "JO" => {},
-        "JP" => {},
-        "JPE" => {},
-        "JPO" => {},
-        "JRCXZ" => {},
-        "JS" => {},
-        "JZ" => {},
-        "LAHF" => {},
-        "LAR" => {},
-        "LDDQU" => {},
-        "LDMXCSR" => {},
-        "LDS" => {},
-        "LEA" => {},
-        "LEAVE" => {},
-        "LES" => {},
-        "LFENCE" => {},
-        "LFS" => {},
-        "LGDT" => {},
-        "LGS" => {},
-        "LIDT" => {},
-        "LLDT" => {},
-        "LMSW" => {},
-        "LODS" => {},
-        "LODSB" => {},
-        "LODSD" => {},
-        "LODSQ" => {},
-        "LODSW" => {},
-        "LO" => {},
-        "LOE" => {},
-        "LONE" => {},
-        "LONZ" => {},
-        "LOZ" => {},
-        "LSL" => {},
-        "LSS" => {},
-        "LTR" => {},
-        "MASKMOVDQU" => {},
-        "MASKMOVQ" => {},
-        "MAXPD" => {},
-        "MAXPS" => {},
-        "MAXSD" => {},
-        "MAXSS" => {},
-        "MFENCE" => {},
-        "MINPD" => {},
-        "MINPS" => {},
-        "MINSD" => {},
-        "MINSS" => {},
-        "MOV" => {},
-        "MOVAPD" => {},
-        "MOVAPS" => {},
-        "MOVD" => {},
-        "MOVDDUP" => {},
-        "MOVDQ2Q" => {},
-        "MOVDQA" => {},
-        "MOVDQU" => {},
-        "MOVHLPS" => {},
-        "MOVHPD" => {},
-        "MOVHPS" => {},
-        "MOVLHPS" => {},
-        "MOVLPD" => {},
-        "MOVLPS" => {},
-        "MOVMSKPD" => {},
-        "MOVMSKPS" => {},
-        "MOVNTDQ" => {},
-        "MOVNTI" => {},
-        "MOVNTPD" => {},
-        "MOVNTPS" => {},
-        "MOVNTQ" => {},
-        "MOVQ" => {},
-        "MOVQ2DQ" => {},
-        "MOVS" => {},
-        "MOVSB" => {},
-        "MOVSD" => {},
-        "MOVSHDUP" => {},
-        "MOVSLDUP" => {},
-        "MOVSQ" => {},
-        "MOVSS" => {},
-        "MOVSW" => {},
-        "MOVSX" => {},
-        "MOVSXD" => {},
-        "MOVUPD" => {},
-        "MOVUPS" => {},
-        "MOVZX" => {},
-        "MUL" => {},
-        "MULPD" => {},
-        "MULPS" => {},
-        "MULSD" => {},
-        "MULSS" => {},
-        "NEG" => {},
-        "N" => {},
-        "NOT" => {},
-        "OR" => {},
-        "ORPD" => {},
-        "ORPS" => {},
-        "OUT" => {},
-        "OUTS" => {},
-        "OUTSB" => {},
-        "OUTSD" => {},
-        "OUTSW" => {},
-        "PACKSSDW" => {},
-        "PACKSSWB" => {},
-        "PACKUSWB" => {},
-        "PADDB" => {},
-        "PADDD" => {},
-        "PADDQ" => {},
-        "PADDSB" => {},
-        "PADDSW" => {},
-        "PADDUSB" => {},
-        "PADDUSW" => {},
-        "PADDW" => {},
-        "PAND" => {},
-        "PANDN" => {},
-        "PAUSE" => {},
-        "PAVGB" => {},
-        "PAVGUSB" => {},
-        "PAVGW" => {},
-        "PCMPEQB" => {},
-        "PCMPEQD" => {},
-        "PCMPEQW" => {},
-        "PCMPGTB" => {},
-        "PCMPGTD" => {},
-        "PCMPGTW" => {},
-        "PEXTRW" => {},
-        "PF2ID" => {},
-        "PF2IW" => {},
-        "PFACC" => {},
-        "PFADD" => {},
-        "PFCMPEQ" => {},
-        "PFCMPGE" => {},
-        "PFCMPGT" => {},
-        "PFMAX" => {},
-        "PFMIN" => {},
-        "PFMUL" => {},
-        "PFNACC" => {},
-        "PFPNACC" => {},
-        "PFRCP" => {},
-        "PFRCPIT1" => {},
-        "PFRCPIT2" => {},
-        "PFRSQIT1" => {},
-        "PFRSQRT" => {},
-        "PFSUB" => {},
-        "PFSUBR" => {},
-        "PI2FD" => {},
-        "PI2FW" => {},
-        "PINSRW" => {},
-        "PMADDWD" => {},
-        "PMAXSW" => {},
-        "PMAXUB" => {},
-        "PMINSW" => {},
-        "PMINUB" => {},
-        "PMOVMSKB" => {},
-        "PMULHRW" => {},
-        "PMULHUW" => {},
-        "PMULHW" => {},
-        "PMULLW" => {},
-        "PMULUDQ" => {},
-        "P" => {},
-        "PA" => {},
-        "PAD" => {},
-        "PAW" => {},
-        "PF" => {},
-        "PFD" => {},
-        "PFQ" => {},
-        "POR" => {},
-        "PREFETCH" => {},
-        "PREFETCHNTA" => {},
-        "PREFETCHT0" => {},
-        "PREFETCHT1" => {},
-        "PREFETCHT2" => {},
-        "PREFETCHW" => {},
-        "PSADBW" => {},
-        "PSHUFD" => {},
-        "PSHUFHW" => {},
-        "PSHUFLW" => {},
-        "PSHUFW" => {},
-        "PSLLD" => {},
-        "PSLLDQ" => {},
-        "PSLLQ" => {},
-        "PSLLW" => {},
-        "PSRAD" => {},
-        "PSRAW" => {},
-        "PSRLD" => {},
-        "PSRLDQ" => {},
-        "PSRLQ" => {},
-        "PSRLW" => {},
-        "PSUBB" => {},
-        "PSUBD" => {},
-        "PSUBQ" => {},
-        "PSUBSB" => {},
-        "PSUBSW" => {},
-        "PSUBUSB" => {},
-        "PSUBUSW" => {},
-        "PSUBW" => {},
-        "PSWAPD" => {},
-        "PUNPCKHBW" => {},
-        "PUNPCKHDQ" => {},
-        "PUNPCKHQDQ" => {},
-        "PUNPCKHWD" => {},
-        "PUNPCKLBW" => {},
-        "PUNPCKLDQ" => {},
-        "PUNPCKLQDQ" => {},
-        "PUNPCKLWD" => {},
-        "PUSH" => {},
-        "PUSHA" => {},
-        "PUSHAD" => {},
-        "PUSHF" => {},
-        "PUSHFD" => {},
-        "PUSHFQ" => {},
-        "PXOR" => {},
-        "RCL" => {},
-        "RCPPS" => {},
-        "RCPSS" => {},
-        "RCR" => {},
-        "RDMSR" => {},
-        "RDPMC" => {},
-        "RDTSC" => {},
-        "RDTSCP" => {},
-        "RET" => {},
-        "RETF" => {},
-        "ROL" => {},
-        "ROR" => {},
-        "RSM" => {},
-        "RSQRTPS" => {},
-        "RSQRTSS" => {},
-        "SAHF" => {},
-        "SAL" => {},
-        "SAR" => {},
-        "SBB" => {},
-        "SCAS" => {},
-        "SCASB" => {},
-        "SCASD" => {},
-        "SCASQ" => {},
-        "SCASW" => {},
-        "SETA" => {},
-        "SETAE" => {},
-        "SETB" => {},
-        "SETBE" => {},
-        "SETC" => {},
-        "SETE" => {},
-        "SETG" => {},
-        "SETGE" => {},
-        "SETL" => {},
-        "SETLE" => {},
-        "SETNA" => {},
-        "SETNAE" => {},
-        "SETNB" => {},
-        "SETNBE" => {},
-        "SETNC" => {},
-        "SETNE" => {},
-        "SETNG" => {},
-        "SETNGE" => {},
-        "SETNL" => {},
-        "SETNLE" => {},
-        "SETNO" => {},
-        "SETNP" => {},
-        "SETNS" => {},
-        "SETNZ" => {},
-        "SETO" => {},
-        "SETP" => {},
-        "SETPE" => {},
-        "SETPO" => {},
-        "SETS" => {},
-        "SETZ" => {},
-        "SFENCE" => {},
-        "SGDT" => {},
-        "SHL" => {},
-        "SHLD" => {},
-        "SHR" => {},
-        "SHRD" => {},
-        "SHUFPD" => {},
-        "SHUFPS" => {},
-        "SIDT" => {},
-        "SKINIT" => {},
-        "SLDT" => {},
-        "SMSW" => {},
-        "SQRTPD" => {},
-        "SQRTPS" => {},
-        "SQRTSD" => {},
-        "SQRTSS" => {},
-        "STC" => {},
-        "STD" => {},
-        "STGI" => {},
-        "STI" => {},
-        "STMXCSR" => {},
-        "STOS" => {},
-        "STOSB" => {},
-        "STOSD" => {},
-        "STOSQ" => {},
-        "STOSW" => {},
-        "STR" => {},
-        "SUB" => {},
-        "SUBPD" => {},
-        "SUBPS" => {},
-        "SUBSD" => {},
-        "SUBSS" => {},
-        "SWAPGS" => {},
-        "SYSCALL" => {},
-        "SYSENTER" => {},
-        "SYSEXIT" => {},
-        "SYSRET" => {},
-        "TEST" => {},
-        "UCOMISD" => {},
-        "UCOMISS" => {},
-        "UD2" => {},
-        "UNPCKHPD" => {},
-        "UNPCKHPS" => {},
-        "UNPCKLPD" => {},
-        "UNPCKLPS" => {},
-        "VERR" => {},
-        "VERW" => {},
-        "VMLOAD" => {},
-        "VMMCALL" => {},
-        "VMRUN" => {},
-        "VMSAVE" => {},
-        "WBINVD" => {},
-        "WRMSR" => {},
-        "XADD" => {},
-        "XCHG" => {},
-        "XLAT" => {},
-        "XLATB" => {},
-        "XOR" => {},
-        "XORPD" => {},
-        "XORPS" => {},
-        "VAESKEYGENASSIST" => {},
-        "VPMADCSWD" => {},
-        "VADDPD" => {},
-        "VADDPS" => {},
-        "VADDSD" => {},
-        "VADDSS" => {},
-        "VADDSUBPD" => {},
-        "VADDSUBPS" => {},
-        "VAESDEC" => {},
-        "VAESDECLAST" => {},
-        "VAESENC" => {},
-        "VAESENCLAST" => {},
-        "VAESIMC" => {},
-        "VANDNPD" => {},
-        "VANDNPS" => {},
-        "VANDPD" => {},
-        "VANDPS" => {},
-        "VBLENDPD" => {},
-        "VBLENDPS" => {},
-        "VBLENDVPD" => {},
-        "VBLENDVPS" => {},
-        "VBROADCASTF128" => {},
-        "VBROADCASTI128" => {},
-        "VBROADCASTSD" => {},
-        "VBROADCASTSS" => {},
-        "VCMPPD" => {},
-        "VCMPPS" => {},
-        "VCMPSD" => {},
-        "VCMPSS" => {},
-        "VCOMISD" => {},
-        "VCOMISS" => {},
-        "VCVTDQ2PD" => {},
-        "VCVTDQ2PS" => {},
-        "VCVTPD2DQ" => {},
-        "VCVTPD2PS" => {},
-        "VCVTPH2PS" => {},
-        "VCVTPS2DQ" => {},
-        "VCVTPS2PD" => {},
-        "VCVTPS2PH" => {},
-        "VCVTSD2SI" => {},
-        "VCVTSD2SS" => {},
-        "VCVTSI2SD" => {},
-        "VCVTSI2SS" => {},
-        "VCVTSS2SD" => {},
-        "VCVTSS2SI" => {},
-        "VCVTTPD2DQ" => {},
-        "VCVTTPS2DQ" => {},
-        "VCVTTSD2SI" => {},
-        "VCVTTSS2SI" => {},
-        "VDIVPD" => {},
-        "VDIVPS" => {},
-        "VDIVSD" => {},
-        "VDIVSS" => {},
-        "VDPPD" => {},
-        "VDPPS" => {},
-        "VEXTRACTF128" => {},
-        "VEXTRACTI128" => {},
-        "VEXTRACTPS" => {},
-        "VFMADD132PD" => {},
-        "VFMADD132PS" => {},
-        "VFMADD132SD" => {},
-        "VFMADD132SS" => {},
-        "VFMADD213PD" => {},
-        "VFMADD213PS" => {},
-        "VFMADD213SD" => {},
-        "VFMADD213SS" => {},
-        "VFMADD231PD" => {},
-        "VFMADD231PS" => {},
-        "VFMADD231SD" => {},
-        "VFMADD231SS" => {},
-        "VFMADDPD" => {},
-        "VFMADDPS" => {},
-        "VFMADDSD" => {},
-        "VFMADDSS" => {},
-        "VFMADDSUB132PD" => {},
-        "VFMADDSUB132PS" => {},
-        "VFMADDSUB213PD" => {},
-        "VFMADDSUB213PS" => {},
-        "VFMADDSUB231PD" => {},
-        "VFMADDSUB231PS" => {},
-        "VFMADDSUBPD" => {},
-        "VFMADDSUBPS" => {},
-        "VFMSUB132PD" => {},
-        "VFMSUB132PS" => {},
-        "VFMSUB132SD" => {},
-        "VFMSUB132SS" => {},
-        "VFMSUB213PD" => {},
-        "VFMSUB213PS" => {},
-        "VFMSUB213SD" => {},
-        "VFMSUB213SS" => {},
-        "VFMSUB231PD" => {},
-        "VFMSUB231PS" => {},
-        "VFMSUB231SD" => {},
-        "VFMSUB231SS" => {},
-        "VFMSUBADD132PD" => {},
-        "VFMSUBADD132PS" => {},
-        "VFMSUBADD213PD" => {},
-        "VFMSUBADD213PS" => {},
-        "VFMSUBADD231PD" => {},
-        "VFMSUBADD231PS" => {},
-        "VFMSUBADDPD" => {},
-        "VFMSUBADDPS" => {},
-        "VFMSUBPD" => {},
-        "VFMSUBPS" => {},
-        "VFMSUBSD" => {},
-        "VFMSUBSS" => {},
-        "VFNMADD132PD" => {},
-        "VFNMADD132PS" => {},
-        "VFNMADD132SD" => {},
-        "VFNMADD132SS" => {},
-        "VFNMADD213PD" => {},
-        "VFNMADD213PS" => {},
-        "VFNMADD213SD" => {},
-        "VFNMADD213SS" => {},
-        "VFNMADD231PD" => {},
-        "VFNMADD231PS" => {},
-        "VFNMADD231SD" => {},
-        "VFNMADD231SS" => {},
-        "VFNMADDPD" => {},
-        "VFNMADDPS" => {},
-        "VFNMADDSD" => {},
-        "VFNMADDSS" => {},
-        "VFNMSUB132PD" => {},
-        "VFNMSUB132PS" => {},
-        "VFNMSUB132SD" => {},
-        "VFNMSUB132SS" => {},
-        "VFNMSUB213PD" => {},
-        "VFNMSUB213PS" => {},
-        "VFNMSUB213SD" => {},
-        "VFNMSUB213SS" => {},
-        "VFNMSUB231PD" => {},
-        "VFNMSUB231PS" => {},
-        "VFNMSUB231SD" => {},
-        "VFNMSUB231SS" => {},
-        "VFNMSUBPD" => {},
-        "VFNMSUBPS" => {},
-        "VFNMSUBSD" => {},
-        "VFNMSUBSS" => {},
-        "VFRCZPD" => {},
-        "VFRCZPS" => {},
-        "VFRCZSD" => {},
-        "VFRCZSS" => {},
-        "VGATHERDPD" => {},
-        "VGATHERDPS" => {},
-        "VGATHERQPD" => {},
-        "VGATHERQPS" => {},
-        "VHADDPD" => {},
-        "VHADDPS" => {},
-        "VHSUBPD" => {},
-        "VHSUBPS" => {},
-        "VINSERTF128" => {},
-        "VINSERTI128" => {},
-        "VINSERTPS" => {},
-        "VLDDQU" => {},
-        "VLDMXCSR" => {},
-        "VMASKMOVDQU" => {},
-        "VMASKMOVPD" => {},
-        "VMASKMOVPS" => {},
-        "VMAXPD" => {},
-        "VMAXPS" => {},
-        "VMAXSD" => {},
-        "VMAXSS" => {},
-        "VMINPD" => {},
-        "VMINPS" => {},
-        "VMINSD" => {},
-        "VMINSS" => {},
-        "VMOVAPD" => {},
-        "VMOVAPS" => {},
-        "VMOVD" => {},
-        "VMOVDDUP" => {},
-        "VMOVDQA" => {},
-        "VMOVDQU" => {},
-        "VMOVHLPS" => {},
-        "VMOVHPD" => {},
-        "VMOVHPS" => {},
-        "VMOVLHPS" => {},
-        "VMOVLPD" => {},
-        "VMOVLPS" => {},
-        "VPMOVMSKB" => {},
-        "VMOVMSKPD" => {},
-        "VMOVMSKPS" => {},
-        "VMOVNTDQ" => {},
-        "VMOVNTDQA" => {},
-        "VMOVNTPD" => {},
-        "VMOVNTPS" => {},
-        "VMOVQ" => {},
-        "VMOVSD" => {},
-        "VMOVSHDUP" => {},
-        "VMOVSLDUP" => {},
-        "VMOVSS" => {},
-        "VMOVUPD" => {},
-        "VMOVUPS" => {},
-        "VMPSADBW" => {},
-        "VMULPD" => {},
-        "VMULPS" => {},
-        "VMULSD" => {},
-        "VMULSS" => {},
-        "VORPD" => {},
-        "VORPS" => {},
-        "VPABSB" => {},
-        "VPABSD" => {},
-        "VPABSW" => {},
-        "VPACKSSDW" => {},
-        "VPACKSSWB" => {},
-        "VPACKUSDW" => {},
-        "VPACKUSWB" => {},
-        "VPADDB" => {},
-        "VPADDD" => {},
-        "VPADDQ" => {},
-        "VPADDSB" => {},
-        "VPADDSW" => {},
-        "VPADDUSB" => {},
-        "VPADDUSW" => {},
-        "VPADDW" => {},
-        "VPALIGNR" => {},
-        "VPAND" => {},
-        "VPANDN" => {},
-        "VPAVGB" => {},
-        "VPAVGW" => {},
-        "VPBLENDD" => {},
-        "VPBLENDVB" => {},
-        "VPBLENDW" => {},
-        "VPBROADCASTB" => {},
-        "VPBROADCASTD" => {},
-        "VPBROADCASTQ" => {},
-        "VPBROADCASTW" => {},
-        "VPCLMULQDQ" => {},
-        "VPCMOV" => {},
-        "VPCMPEQB" => {},
-        "VPCMPEQD" => {},
-        "VPCMPEQQ" => {},
-        "VPCMPEQW" => {},
-        "VPCMPESTRI" => {},
-        "VPCMPESTRM" => {},
-        "VPCMPGTB" => {},
-        "VPCMPGTD" => {},
-        "VPCMPGTQ" => {},
-        "VPCMPGTW" => {},
-        "VPCMPISTRI" => {},
-        "VPCMPISTRM" => {},
-        "VPCOMB" => {},
-        "VPCOMD" => {},
-        "VPCOMQ" => {},
-        "VPCOMUB" => {},
-        "VPCOMUD" => {},
-        "VPCOMUQ" => {},
-        "VPCOMUW" => {},
-        "VPCOMW" => {},
-        "VPERM2F128" => {},
-        "VPERM2I128" => {},
-        "VPERMD" => {},
-        "VPERMIL2PD" => {},
-        "VPERMIL2PS" => {},
-        "VPERMILPD" => {},
-        "VPERMILPS" => {},
-        "VPERMPD" => {},
-        "VPERMPS" => {},
-        "VPERMQ" => {},
-        "VPEXTRB" => {},
-        "VPEXTRD" => {},
-        "VPEXTRQ" => {},
-        "VPEXTRW" => {},
-        "VPGATHERDD" => {},
-        "VPGATHERDQ" => {},
-        "VPGATHERQD" => {},
-        "VPGATHERQQ" => {},
-        "VPHADDBD" => {},
-        "VPHADDBQ" => {},
-        "VPHADDBW" => {},
-        "VPHADDD" => {},
-        "VPHADDDQ" => {},
-        "VPHADDSW" => {},
-        "VPHADDUBD" => {},
-        "VPHADDUBQ" => {},
-        "VPHADDUBW" => {},
-        "VPHADDUDQ" => {},
-        "VPHADDUWD" => {},
-        "VPHADDUWQ" => {},
-        "VPHADDW" => {},
-        "VPHADDWD" => {},
-        "VPHADDWQ" => {},
-        "VPHMINPOSUW" => {},
-        "VPHSUBBW" => {},
-        "VPHSUBD" => {},
-        "VPHSUBDQ" => {},
-        "VPHSUBSW" => {},
-        "VPHSUBW" => {},
-        "VPHSUBWD" => {},
-        "VPINSRB" => {},
-        "VPINSRD" => {},
-        "VPINSRQ" => {},
-        "VPINSRW" => {},
-        "VPMACSDD" => {},
-        "VPMACSDQH" => {},
-        "VPMACSDQL" => {},
-        "VPMACSSDD" => {},
-        "VPMACSSDQH" => {},
-        "VPMACSSDQL" => {},
-        "VPMACSSWD" => {},
-        "VPMACSSWW" => {},
-        "VPMACSWD" => {},
-        "VPMACSWW" => {},
-        "VPMADCSSWD" => {},
-        "VPMADDUBSW" => {},
-        "VPMADDWD" => {},
-        "VPMASKMOVD" => {},
-        "VPMASKMOVQ" => {},
-        "VPMAXSB" => {},
-        "VPMAXSD" => {},
-        "VPMAXSW" => {},
-        "VPMAXUB" => {},
-        "VPMAXUD" => {},
-        "VPMAXUW" => {},
-        "VPMINSB" => {},
-        "VPMINSD" => {},
-        "VPMINSW" => {},
-        "VPMINUB" => {},
-        "VPMINUD" => {},
-        "VPMINUW" => {},
-        "VPMOVSXBD" => {},
-        "VPMOVSXBQ" => {},
-        "VPMOVSXBW" => {},
-        "VPMOVSXDQ" => {},
-        "VPMOVSXWD" => {},
-        "VPMOVSXWQ" => {},
-        "VPMOVZXBD" => {},
-        "VPMOVZXBQ" => {},
-        "VPMOVZXBW" => {},
-        "VPMOVZXDQ" => {},
-        "VPMOVZXWD" => {},
-        "VPMOVZXWQ" => {},
-        "VPMULDQ" => {},
-        "VPMULHRSW" => {},
-        "VPMULHUW" => {},
-        "VPMULHW" => {},
-        "VPMULLD" => {},
-        "VPMULLW" => {},
-        "VPMULUDQ" => {},
-        "VPOR" => {},
-        "VPPERM" => {},
-        "VPROTB" => {},
-        "VPROTD" => {},
-        "VPROTQ" => {},
-        "VPROTW" => {},
-        "VPSADBW" => {},
-        "VPSHAB" => {},
-        "VPSHAD" => {},
-        "VPSHAQ" => {},
-        "VPSHAW" => {},
-        "VPSHLB" => {},
-        "VPSHLD" => {},
-        "VPSHLQ" => {},
-        "VPSHLW" => {},
-        "VPSHUFB" => {},
-        "VPSHUFD" => {},
-        "VPSHUFHW" => {},
-        "VPSHUFLW" => {},
-        "VPSIGNB" => {},
-        "VPSIGND" => {},
-        "VPSIGNW" => {},
-        "VPSLLD" => {},
-        "VPSLLDQ" => {},
-        "VPSLLQ" => {},
-        "VPSLLVD" => {},
-        "VPSLLVQ" => {},
-        "VPSLLW" => {},
-        "VPSRAD" => {},
-        "VPSRAVD" => {},
-        "VPSRAW" => {},
-        "VPSRLD" => {},
-        "VPSRLDQ" => {},
-        "VPSRLQ" => {},
-        "VPSRLVD" => {},
-        "VPSRLVQ" => {},
-        "VPSRLW" => {},
-        "VPSUBB" => {},
-        "VPSUBD" => {},
-        "VPSUBQ" => {},
-        "VPSUBSB" => {},
-        "VPSUBSW" => {},
-        "VPSUBUSB" => {},
-        "VPSUBUSW" => {},
-        "VPSUBW" => {},
-        "VPTEST" => {},
-        "VPUNPCKHBW" => {},
-        "VPUNPCKHDQ" => {},
-        "VPUNPCKHQDQ" => {},
-        "VPUNPCKHWD" => {},
-        "VPUNPCKLBW" => {},
-        "VPUNPCKLDQ" => {},
-        "VPUNPCKLQDQ" => {},
-        "VPUNPCKLWD" => {},
-        "VPXOR" => {},
-        "VRCPPS" => {},
-        "VRCPSS" => {},
-        "VROUNDPD" => {},
-        "VROUNDPS" => {},
-        "VROUNDSD" => {},
-        "VROUNDSS" => {},
-        "VRSQRTPS" => {},
-        "VRSQRTSS" => {},
-        "VSHUFPD" => {},
-        "VSHUFPS" => {},
-        "VSQRTPD" => {},
-        "VSQRTPS" => {},
-        "VSQRTSD" => {},
-        "VSQRTSS" => {},
-        "VSTMXCSR" => {},
-        "VSUBPD" => {},
-        "VSUBPS" => {},
-        "VSUBSD" => {},
-        "VSUBSS" => {},
-        "VTESTPD" => {},
-        "VTESTPS" => {},
-        "VUCOMISD" => {},
-        "VUCOMISS" => {},
-        "VUNPCKHPD" => {},
-        "VUNPCKHPS" => {},
-        "VUNPCKLPD" => {},
-        "VUNPCKLPS" => {},
-        "VXORPD" => {},
-        "VXORPS" => {},
-        "VZEROALL" => {},
-        "VZEROUPPER" => {},
-
-        _ => return Err(Box::new(String::from("Illegal instruction!")))
-    }
-
-    Ok(Box::new(Vec::<u8>::new()))
+/// Registers of X86-64, general purpose registers have number 0 - 15 and size in bits
+#[derive(Clone, PartialEq, Debug)]
+pub enum RegisterAMD64 {
+    General(u8, u32),
+    HighByte(u8),       /* AH, CH, DH and BH, cannot be used together with REX prefix */
+    Segment(u8),
+    Control(u8),
+    Debug(u8),
+    X87(u8),
+    Mmx(u8),
+    Xmm(u8),            /* XMM16 - XMM31 are only reachable with EVEX prefix, like all of YMM16 - YMM31 and ZMM0 - ZMM31 */
+    Ymm(u8),
+    Zmm(u8),
+    Mask(u8),           /* Opmask registers K0 - K7 of AVX-512 */
+    Rip
 }
 
-/// Decode a single array of bytes to assembler instruction with operands
-fn decode_instruction_amd64(code: Box<Vec<u8>>, flags: CpuFlags) -> Result<Box<String>, Box<String>>{
-    Ok(Box::new(String::new()))
-}
\ No newline at end of file
+pub trait RegisterAMD64Methods {
+    fn number(&self) -> u8;
+    fn bits(&self) -> u32;
+}
+
+impl RegisterAMD64Methods for RegisterAMD64 {
+    /// Register number as encoded in ModRM, SIB and REX bits
+    fn number(&self) -> u8 {
+        match self {
+            RegisterAMD64::General(n, _ ) |
+            RegisterAMD64::HighByte(n) |
+            RegisterAMD64::Segment(n) |
+            RegisterAMD64::Control(n) |
+            RegisterAMD64::Debug(n) |
+            RegisterAMD64::X87(n) |
+            RegisterAMD64::Mmx(n) |
+            RegisterAMD64::Xmm(n) |
+            RegisterAMD64::Ymm(n) |
+            RegisterAMD64::Zmm(n) |
+            RegisterAMD64::Mask(n) => *n,
+            RegisterAMD64::Rip => 5
+        }
+    }
+
+    /// Size of register in bits
+    fn bits(&self) -> u32 {
+        match self {
+            RegisterAMD64::General( _ , b ) => *b,
+            RegisterAMD64::HighByte( _ ) => 8,
+            RegisterAMD64::Segment( _ ) => 16,
+            RegisterAMD64::X87( _ ) => 80,
+            RegisterAMD64::Mmx( _ ) => 64,
+            RegisterAMD64::Xmm( _ ) => 128,
+            RegisterAMD64::Ymm( _ ) => 256,
+            RegisterAMD64::Zmm( _ ) => 512,
+            _ => 64
+        }
+    }
+}
+
+/// Operand of X86-64 instruction after expressions are evaluated
+#[derive(Clone, PartialEq, Debug)]
+pub enum AMD64Operand {
+    Register(RegisterAMD64),
+    Immediate(i64),
+    Memory(Option<u32>, Option<RegisterAMD64>, Option<(RegisterAMD64, u8)>, i64),   /* Size in bits, base, index with scale and displacement */
+    Masked(Box<AMD64Operand>, u8, bool),        /* Destination with opmask register K1 - K7 and zeroing, like 'ZMM0{K1}{Z}' */
+    Broadcast(Box<AMD64Operand>, u32)           /* Memory element repeated the given number of times, like '[RAX]{1TO16}' */
+}
+
+/// Register with given name, upper or lower case, or none when name is not a register
+pub fn register_amd64(name: &str) -> Option<RegisterAMD64> {
+    let upper = name.to_uppercase();
+
+    match upper.as_str() {
+        "AL" => return Some( RegisterAMD64::General(0, 8) ),
+        "CL" => return Some( RegisterAMD64::General(1, 8) ),
+        "DL" => return Some( RegisterAMD64::General(2, 8) ),
+        "BL" => return Some( RegisterAMD64::General(3, 8) ),
+        "SPL" => return Some( RegisterAMD64::General(4, 8) ),
+        "BPL" => return Some( RegisterAMD64::General(5, 8) ),
+        "SIL" => return Some( RegisterAMD64::General(6, 8) ),
+        "DIL" => return Some( RegisterAMD64::General(7, 8) ),
+        "AH" => return Some( RegisterAMD64::HighByte(4) ),
+        "CH" => return Some( RegisterAMD64::HighByte(5) ),
+        "DH" => return Some( RegisterAMD64::HighByte(6) ),
+        "BH" => return Some( RegisterAMD64::HighByte(7) ),
+        "ES" => return Some( RegisterAMD64::Segment(0) ),
+        "CS" => return Some( RegisterAMD64::Segment(1) ),
+        "SS" => return Some( RegisterAMD64::Segment(2) ),
+        "DS" => return Some( RegisterAMD64::Segment(3) ),
+        "FS" => return Some( RegisterAMD64::Segment(4) ),
+        "GS" => return Some( RegisterAMD64::Segment(5) ),
+        "RIP" => return Some( RegisterAMD64::Rip ),
+        _ => ()
+    }
+
+    /* Registers AX - DI in their 16, 32 and 64 bits forms */
+    let legacy = [ "AX", "CX", "DX", "BX", "SP", "BP", "SI", "DI" ];
+    for ( number , x ) in legacy.iter().enumerate() {
+        match upper.as_str() {
+            y if y == *x => return Some( RegisterAMD64::General(number as u8, 16) ),
+            y if y.len() == 3 && y.starts_with('E') && &y[1 ..] == *x => return Some( RegisterAMD64::General(number as u8, 32) ),
+            y if y.len() == 3 && y.starts_with('R') && &y[1 ..] == *x => return Some( RegisterAMD64::General(number as u8, 64) ),
+            _ => ()
+        }
+    }
+
+    /* Numbered registers like R8D, ST0, MM7, XMM15, YMM31, K7, CR0 and DR7 */
+    let numbered = [ ( "XMM", 31 ), ( "YMM", 31 ), ( "ZMM", 31 ), ( "MM", 7 ), ( "ST", 7 ), ( "CR", 15 ), ( "DR", 15 ), ( "K", 7 ), ( "R", 15 ) ];
+    for ( prefix , max ) in numbered.iter() {
+        if let Some( rest ) = upper.strip_prefix(prefix) {
+            let digits : String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+            let suffix = &rest[digits.len() ..];
+            let number = match digits.parse::<u8>() {
+                Ok( x ) if x <= *max && ( digits.len() == 1 || !digits.starts_with('0') ) => x,
+                _ => continue
+            };
+
+            return match ( *prefix , suffix ) {
+                ( "XMM" , "" ) => Some( RegisterAMD64::Xmm(number) ),
+                ( "YMM" , "" ) => Some( RegisterAMD64::Ymm(number) ),
+                ( "ZMM" , "" ) => Some( RegisterAMD64::Zmm(number) ),
+                ( "K" , "" ) => Some( RegisterAMD64::Mask(number) ),
+                ( "MM" , "" ) => Some( RegisterAMD64::Mmx(number) ),
+                ( "ST" , "" ) => Some( RegisterAMD64::X87(number) ),
+                ( "CR" , "" ) => Some( RegisterAMD64::Control(number) ),
+                ( "DR" , "" ) => Some( RegisterAMD64::Debug(number) ),
+                ( "R" , "" ) if number >= 8 => Some( RegisterAMD64::General(number, 64) ),
+                ( "R" , "D" ) if number >= 8 => Some( RegisterAMD64::General(number, 32) ),
+                ( "R" , "W" ) if number >= 8 => Some( RegisterAMD64::General(number, 16) ),
+                ( "R" , "B" ) if number >= 8 => Some( RegisterAMD64::General(number, 8) ),
+                _ => None
+            }
+        }
+    }
+
+    None
+}
+
+
+/// Kind of operand accepted by an instruction form
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum OperandKind {
+    Al, Ax, Eax, Rax, Cl, Dx, One, St0,     /* Fixed operands, given by the opcode and not encoded */
+    R8, R16, R32, R64,                      /* General register in ModRM reg field or added to opcode */
+    RM8, RM16, RM32, RM64,                  /* General register or memory in ModRM rm field */
+    B32, B64,                               /* General register in ModRM rm field, memory not allowed */
+    Xmm, XmmB, XmmM32, XmmM64, XmmM128,
+    Ymm, YmmM256, Zmm, ZmmM512,
+    K, KB, KM16,                            /* Opmask register in reg field, in rm field and opmask register or memory */
+    Mem, M8, M16, M32, M64, M80, M128,      /* Memory only, 'Mem' is memory of any size */
+    St, Sreg, Creg, Dreg,
+    I8, SI8, I16, I32, I64,                 /* Immediates, 'SI8' is sign extended to operand size */
+    Rel8, Rel32                             /* Target address, encoded relative to end of instruction */
+}
+
+/// How the operands of an instruction form are placed in the instruction
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Encoding {
+    Opcode,         /* Opcode bytes only, operands are fixed or immediate */
+    ModRM,          /* ModRM byte with register operand in reg field */
+    Digit(u8),      /* ModRM byte with opcode extension in reg field */
+    PlusReg,        /* Register number added to last opcode byte */
+    ModRMV          /* Like 'ModRM' with second operand in 'vvvv' field of VEX or EVEX prefix */
+}
+
+/// Prefix that carries REX bits, opcode map and mandatory prefix of the instruction form
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum VectorPrefix {
+    Legacy,         /* Mandatory prefix and REX as separate bytes */
+    Vex,            /* Two or three bytes VEX prefix of AVX instructions */
+    Evex(u32)       /* Four bytes EVEX prefix of AVX-512 instructions, with element size in bits when memory can be broadcast */
+}
+
+/// One encoding of a mnemonic, shared by encoder and decoder
+pub struct InstructionForm {
+    pub mnemonic: &'static str,
+    pub operands: &'static [OperandKind],
+    pub prefix: u8,                 /* Mandatory prefix 0x66, 0xF2, 0xF3 or 0x9B, zero when none */
+    pub opcode: &'static [u8],
+    pub encoding: Encoding,
+    pub size: u32,                  /* Operand size, 16 gives 0x66 prefix and 64 gives REX.W or VEX.W, 256 gives VEX.L without vector operands */
+    pub flags: CpuFlags,            /* CPU type or extension required */
+    pub vector: VectorPrefix,
+    pub long_mode: bool             /* False for forms only encodable in 16 and 32 bits mode */
+}
+
+const fn form(mnemonic: &'static str, operands: &'static [OperandKind], prefix: u8, opcode: &'static [u8], encoding: Encoding, size: u32, flags: CpuFlags) -> InstructionForm {
+    InstructionForm { mnemonic, operands, prefix, opcode, encoding, size, flags, vector: VectorPrefix::Legacy, long_mode: true }
+}
+
+/// Instruction form of 16 and 32 bits mode, its opcode has another operand size or meaning in 64 bits mode
+const fn legacy(mnemonic: &'static str, operands: &'static [OperandKind], prefix: u8, opcode: &'static [u8], encoding: Encoding, size: u32, flags: CpuFlags) -> InstructionForm {
+    InstructionForm { mnemonic, operands, prefix, opcode, encoding, size, flags, vector: VectorPrefix::Legacy, long_mode: false }
+}
+
+/// Instruction form with VEX prefix, the opcode bytes start with the opcode map 0F, 0F 38 or 0F 3A
+const fn vex(mnemonic: &'static str, operands: &'static [OperandKind], prefix: u8, opcode: &'static [u8], encoding: Encoding, size: u32, flags: CpuFlags) -> InstructionForm {
+    InstructionForm { mnemonic, operands, prefix, opcode, encoding, size, flags, vector: VectorPrefix::Vex, long_mode: true }
+}
+
+/// Instruction form with EVEX prefix, 'broadcast' is element size in bits for '{1TOn}' memory operands or zero when not allowed
+const fn evex(mnemonic: &'static str, operands: &'static [OperandKind], prefix: u8, opcode: &'static [u8], encoding: Encoding, size: u32, broadcast: u32) -> InstructionForm {
+    InstructionForm { mnemonic, operands, prefix, opcode, encoding, size, flags: CPU_AVX512, vector: VectorPrefix::Evex(broadcast), long_mode: true }
+}
+
+use OperandKind::*;
+use Encoding::*;
+use VectorPrefix::*;
+
+/// Supported instruction forms, first matching form is used so shorter encodings are listed first
+pub static INSTRUCTIONS_AMD64 : &[InstructionForm] = &[
+    form("ADD", &[ RM8, R8 ], 0, &[ 0x00 ], ModRM, 0, CPU_8086),
+    form("ADD", &[ RM16, R16 ], 0, &[ 0x01 ], ModRM, 16, CPU_8086),
+    form("ADD", &[ RM32, R32 ], 0, &[ 0x01 ], ModRM, 32, CPU_8086),
+    form("ADD", &[ RM64, R64 ], 0, &[ 0x01 ], ModRM, 64, CPU_8086),
+    form("ADD", &[ R8, RM8 ], 0, &[ 0x02 ], ModRM, 0, CPU_8086),
+    form("ADD", &[ R16, RM16 ], 0, &[ 0x03 ], ModRM, 16, CPU_8086),
+    form("ADD", &[ R32, RM32 ], 0, &[ 0x03 ], ModRM, 32, CPU_8086),
+    form("ADD", &[ R64, RM64 ], 0, &[ 0x03 ], ModRM, 64, CPU_8086),
+    form("ADD", &[ RM16, SI8 ], 0, &[ 0x83 ], Digit(0), 16, CPU_8086),
+    form("ADD", &[ RM32, SI8 ], 0, &[ 0x83 ], Digit(0), 32, CPU_8086),
+    form("ADD", &[ RM64, SI8 ], 0, &[ 0x83 ], Digit(0), 64, CPU_8086),
+    form("ADD", &[ Al, I8 ], 0, &[ 0x04 ], Opcode, 0, CPU_8086),
+    form("ADD", &[ Ax, I16 ], 0, &[ 0x05 ], Opcode, 16, CPU_8086),
+    form("ADD", &[ Eax, I32 ], 0, &[ 0x05 ], Opcode, 32, CPU_8086),
+    form("ADD", &[ Rax, I32 ], 0, &[ 0x05 ], Opcode, 64, CPU_8086),
+    form("ADD", &[ RM8, I8 ], 0, &[ 0x80 ], Digit(0), 0, CPU_8086),
+    form("ADD", &[ RM16, I16 ], 0, &[ 0x81 ], Digit(0), 16, CPU_8086),
+    form("ADD", &[ RM32, I32 ], 0, &[ 0x81 ], Digit(0), 32, CPU_8086),
+    form("ADD", &[ RM64, I32 ], 0, &[ 0x81 ], Digit(0), 64, CPU_8086),
+
+    form("OR", &[ RM8, R8 ], 0, &[ 0x08 ], ModRM, 0, CPU_8086),
+    form("OR", &[ RM16, R16 ], 0, &[ 0x09 ], ModRM, 16, CPU_8086),
+    form("OR", &[ RM32, R32 ], 0, &[ 0x09 ], ModRM, 32, CPU_8086),
+    form("OR", &[ RM64, R64 ], 0, &[ 0x09 ], ModRM, 64, CPU_8086),
+    form("OR", &[ R8, RM8 ], 0, &[ 0x0A ], ModRM, 0, CPU_8086),
+    form("OR", &[ R16, RM16 ], 0, &[ 0x0B ], ModRM, 16, CPU_8086),
+    form("OR", &[ R32, RM32 ], 0, &[ 0x0B ], ModRM, 32, CPU_8086),
+    form("OR", &[ R64, RM64 ], 0, &[ 0x0B ], ModRM, 64, CPU_8086),
+    form("OR", &[ RM16, SI8 ], 0, &[ 0x83 ], Digit(1), 16, CPU_8086),
+    form("OR", &[ RM32, SI8 ], 0, &[ 0x83 ], Digit(1), 32, CPU_8086),
+    form("OR", &[ RM64, SI8 ], 0, &[ 0x83 ], Digit(1), 64, CPU_8086),
+    form("OR", &[ Al, I8 ], 0, &[ 0x0C ], Opcode, 0, CPU_8086),
+    form("OR", &[ Ax, I16 ], 0, &[ 0x0D ], Opcode, 16, CPU_8086),
+    form("OR", &[ Eax, I32 ], 0, &[ 0x0D ], Opcode, 32, CPU_8086),
+    form("OR", &[ Rax, I32 ], 0, &[ 0x0D ], Opcode, 64, CPU_8086),
+    form("OR", &[ RM8, I8 ], 0, &[ 0x80 ], Digit(1), 0, CPU_8086),
+    form("OR", &[ RM16, I16 ], 0, &[ 0x81 ], Digit(1), 16, CPU_8086),
+    form("OR", &[ RM32, I32 ], 0, &[ 0x81 ], Digit(1), 32, CPU_8086),
+    form("OR", &[ RM64, I32 ], 0, &[ 0x81 ], Digit(1), 64, CPU_8086),
+
+    form("ADC", &[ RM8, R8 ], 0, &[ 0x10 ], ModRM, 0, CPU_8086),
+    form("ADC", &[ RM16, R16 ], 0, &[ 0x11 ], ModRM, 16, CPU_8086),
+    form("ADC", &[ RM32, R32 ], 0, &[ 0x11 ], ModRM, 32, CPU_8086),
+    form("ADC", &[ RM64, R64 ], 0, &[ 0x11 ], ModRM, 64, CPU_8086),
+    form("ADC", &[ R8, RM8 ], 0, &[ 0x12 ], ModRM, 0, CPU_8086),
+    form("ADC", &[ R16, RM16 ], 0, &[ 0x13 ], ModRM, 16, CPU_8086),
+    form("ADC", &[ R32, RM32 ], 0, &[ 0x13 ], ModRM, 32, CPU_8086),
+    form("ADC", &[ R64, RM64 ], 0, &[ 0x13 ], ModRM, 64, CPU_8086),
+    form("ADC", &[ RM16, SI8 ], 0, &[ 0x83 ], Digit(2), 16, CPU_8086),
+    form("ADC", &[ RM32, SI8 ], 0, &[ 0x83 ], Digit(2), 32, CPU_8086),
+    form("ADC", &[ RM64, SI8 ], 0, &[ 0x83 ], Digit(2), 64, CPU_8086),
+    form("ADC", &[ Al, I8 ], 0, &[ 0x14 ], Opcode, 0, CPU_8086),
+    form("ADC", &[ Ax, I16 ], 0, &[ 0x15 ], Opcode, 16, CPU_8086),
+    form("ADC", &[ Eax, I32 ], 0, &[ 0x15 ], Opcode, 32, CPU_8086),
+    form("ADC", &[ Rax, I32 ], 0, &[ 0x15 ], Opcode, 64, CPU_8086),
+    form("ADC", &[ RM8, I8 ], 0, &[ 0x80 ], Digit(2), 0, CPU_8086),
+    form("ADC", &[ RM16, I16 ], 0, &[ 0x81 ], Digit(2), 16, CPU_8086),
+    form("ADC", &[ RM32, I32 ], 0, &[ 0x81 ], Digit(2), 32, CPU_8086),
+    form("ADC", &[ RM64, I32 ], 0, &[ 0x81 ], Digit(2), 64, CPU_8086),
+
+    form("SBB", &[ RM8, R8 ], 0, &[ 0x18 ], ModRM, 0, CPU_8086),
+    form("SBB", &[ RM16, R16 ], 0, &[ 0x19 ], ModRM, 16, CPU_8086),
+    form("SBB", &[ RM32, R32 ], 0, &[ 0x19 ], ModRM, 32, CPU_8086),
+    form("SBB", &[ RM64, R64 ], 0, &[ 0x19 ], ModRM, 64, CPU_8086),
+    form("SBB", &[ R8, RM8 ], 0, &[ 0x1A ], ModRM, 0, CPU_8086),
+    form("SBB", &[ R16, RM16 ], 0, &[ 0x1B ], ModRM, 16, CPU_8086),
+    form("SBB", &[ R32, RM32 ], 0, &[ 0x1B ], ModRM, 32, CPU_8086),
+    form("SBB", &[ R64, RM64 ], 0, &[ 0x1B ], ModRM, 64, CPU_8086),
+    form("SBB", &[ RM16, SI8 ], 0, &[ 0x83 ], Digit(3), 16, CPU_8086),
+    form("SBB", &[ RM32, SI8 ], 0, &[ 0x83 ], Digit(3), 32, CPU_8086),
+    form("SBB", &[ RM64, SI8 ], 0, &[ 0x83 ], Digit(3), 64, CPU_8086),
+    form("SBB", &[ Al, I8 ], 0, &[ 0x1C ], Opcode, 0, CPU_8086),
+    form("SBB", &[ Ax, I16 ], 0, &[ 0x1D ], Opcode, 16, CPU_8086),
+    form("SBB", &[ Eax, I32 ], 0, &[ 0x1D ], Opcode, 32, CPU_8086),
+    form("SBB", &[ Rax, I32 ], 0, &[ 0x1D ], Opcode, 64, CPU_8086),
+    form("SBB", &[ RM8, I8 ], 0, &[ 0x80 ], Digit(3), 0, CPU_8086),
+    form("SBB", &[ RM16, I16 ], 0, &[ 0x81 ], Digit(3), 16, CPU_8086),
+    form("SBB", &[ RM32, I32 ], 0, &[ 0x81 ], Digit(3), 32, CPU_8086),
+    form("SBB", &[ RM64, I32 ], 0, &[ 0x81 ], Digit(3), 64, CPU_8086),
+
+    form("AND", &[ RM8, R8 ], 0, &[ 0x20 ], ModRM, 0, CPU_8086),
+    form("AND", &[ RM16, R16 ], 0, &[ 0x21 ], ModRM, 16, CPU_8086),
+    form("AND", &[ RM32, R32 ], 0, &[ 0x21 ], ModRM, 32, CPU_8086),
+    form("AND", &[ RM64, R64 ], 0, &[ 0x21 ], ModRM, 64, CPU_8086),
+    form("AND", &[ R8, RM8 ], 0, &[ 0x22 ], ModRM, 0, CPU_8086),
+    form("AND", &[ R16, RM16 ], 0, &[ 0x23 ], ModRM, 16, CPU_8086),
+    form("AND", &[ R32, RM32 ], 0, &[ 0x23 ], ModRM, 32, CPU_8086),
+    form("AND", &[ R64, RM64 ], 0, &[ 0x23 ], ModRM, 64, CPU_8086),
+    form("AND", &[ RM16, SI8 ], 0, &[ 0x83 ], Digit(4), 16, CPU_8086),
+    form("AND", &[ RM32, SI8 ], 0, &[ 0x83 ], Digit(4), 32, CPU_8086),
+    form("AND", &[ RM64, SI8 ], 0, &[ 0x83 ], Digit(4), 64, CPU_8086),
+    form("AND", &[ Al, I8 ], 0, &[ 0x24 ], Opcode, 0, CPU_8086),
+    form("AND", &[ Ax, I16 ], 0, &[ 0x25 ], Opcode, 16, CPU_8086),
+    form("AND", &[ Eax, I32 ], 0, &[ 0x25 ], Opcode, 32, CPU_8086),
+    form("AND", &[ Rax, I32 ], 0, &[ 0x25 ], Opcode, 64, CPU_8086),
+    form("AND", &[ RM8, I8 ], 0, &[ 0x80 ], Digit(4), 0, CPU_8086),
+    form("AND", &[ RM16, I16 ], 0, &[ 0x81 ], Digit(4), 16, CPU_8086),
+    form("AND", &[ RM32, I32 ], 0, &[ 0x81 ], Digit(4), 32, CPU_8086),
+    form("AND", &[ RM64, I32 ], 0, &[ 0x81 ], Digit(4), 64, CPU_8086),
+
+    form("SUB", &[ RM8, R8 ], 0, &[ 0x28 ], ModRM, 0, CPU_8086),
+    form("SUB", &[ RM16, R16 ], 0, &[ 0x29 ], ModRM, 16, CPU_8086),
+    form("SUB", &[ RM32, R32 ], 0, &[ 0x29 ], ModRM, 32, CPU_8086),
+    form("SUB", &[ RM64, R64 ], 0, &[ 0x29 ], ModRM, 64, CPU_8086),
+    form("SUB", &[ R8, RM8 ], 0, &[ 0x2A ], ModRM, 0, CPU_8086),
+    form("SUB", &[ R16, RM16 ], 0, &[ 0x2B ], ModRM, 16, CPU_8086),
+    form("SUB", &[ R32, RM32 ], 0, &[ 0x2B ], ModRM, 32, CPU_8086),
+    form("SUB", &[ R64, RM64 ], 0, &[ 0x2B ], ModRM, 64, CPU_8086),
+    form("SUB", &[ RM16, SI8 ], 0, &[ 0x83 ], Digit(5), 16, CPU_8086),
+    form("SUB", &[ RM32, SI8 ], 0, &[ 0x83 ], Digit(5), 32, CPU_8086),
+    form("SUB", &[ RM64, SI8 ], 0, &[ 0x83 ], Digit(5), 64, CPU_8086),
+    form("SUB", &[ Al, I8 ], 0, &[ 0x2C ], Opcode, 0, CPU_8086),
+    form("SUB", &[ Ax, I16 ], 0, &[ 0x2D ], Opcode, 16, CPU_8086),
+    form("SUB", &[ Eax, I32 ], 0, &[ 0x2D ], Opcode, 32, CPU_8086),
+    form("SUB", &[ Rax, I32 ], 0, &[ 0x2D ], Opcode, 64, CPU_8086),
+    form("SUB", &[ RM8, I8 ], 0, &[ 0x80 ], Digit(5), 0, CPU_8086),
+    form("SUB", &[ RM16, I16 ], 0, &[ 0x81 ], Digit(5), 16, CPU_8086),
+    form("SUB", &[ RM32, I32 ], 0, &[ 0x81 ], Digit(5), 32, CPU_8086),
+    form("SUB", &[ RM64, I32 ], 0, &[ 0x81 ], Digit(5), 64, CPU_8086),
+
+    form("XOR", &[ RM8, R8 ], 0, &[ 0x30 ], ModRM, 0, CPU_8086),
+    form("XOR", &[ RM16, R16 ], 0, &[ 0x31 ], ModRM, 16, CPU_8086),
+    form("XOR", &[ RM32, R32 ], 0, &[ 0x31 ], ModRM, 32, CPU_8086),
+    form("XOR", &[ RM64, R64 ], 0, &[ 0x31 ], ModRM, 64, CPU_8086),
+    form("XOR", &[ R8, RM8 ], 0, &[ 0x32 ], ModRM, 0, CPU_8086),
+    form("XOR", &[ R16, RM16 ], 0, &[ 0x33 ], ModRM, 16, CPU_8086),
+    form("XOR", &[ R32, RM32 ], 0, &[ 0x33 ], ModRM, 32, CPU_8086),
+    form("XOR", &[ R64, RM64 ], 0, &[ 0x33 ], ModRM, 64, CPU_8086),
+    form("XOR", &[ RM16, SI8 ], 0, &[ 0x83 ], Digit(6), 16, CPU_8086),
+    form("XOR", &[ RM32, SI8 ], 0, &[ 0x83 ], Digit(6), 32, CPU_8086),
+    form("XOR", &[ RM64, SI8 ], 0, &[ 0x83 ], Digit(6), 64, CPU_8086),
+    form("XOR", &[ Al, I8 ], 0, &[ 0x34 ], Opcode, 0, CPU_8086),
+    form("XOR", &[ Ax, I16 ], 0, &[ 0x35 ], Opcode, 16, CPU_8086),
+    form("XOR", &[ Eax, I32 ], 0, &[ 0x35 ], Opcode, 32, CPU_8086),
+    form("XOR", &[ Rax, I32 ], 0, &[ 0x35 ], Opcode, 64, CPU_8086),
+    form("XOR", &[ RM8, I8 ], 0, &[ 0x80 ], Digit(6), 0, CPU_8086),
+    form("XOR", &[ RM16, I16 ], 0, &[ 0x81 ], Digit(6), 16, CPU_8086),
+    form("XOR", &[ RM32, I32 ], 0, &[ 0x81 ], Digit(6), 32, CPU_8086),
+    form("XOR", &[ RM64, I32 ], 0, &[ 0x81 ], Digit(6), 64, CPU_8086),
+
+    form("CMP", &[ RM8, R8 ], 0, &[ 0x38 ], ModRM, 0, CPU_8086),
+    form("CMP", &[ RM16, R16 ], 0, &[ 0x39 ], ModRM, 16, CPU_8086),
+    form("CMP", &[ RM32, R32 ], 0, &[ 0x39 ], ModRM, 32, CPU_8086),
+    form("CMP", &[ RM64, R64 ], 0, &[ 0x39 ], ModRM, 64, CPU_8086),
+    form("CMP", &[ R8, RM8 ], 0, &[ 0x3A ], ModRM, 0, CPU_8086),
+    form("CMP", &[ R16, RM16 ], 0, &[ 0x3B ], ModRM, 16, CPU_8086),
+    form("CMP", &[ R32, RM32 ], 0, &[ 0x3B ], ModRM, 32, CPU_8086),
+    form("CMP", &[ R64, RM64 ], 0, &[ 0x3B ], ModRM, 64, CPU_8086),
+    form("CMP", &[ RM16, SI8 ], 0, &[ 0x83 ], Digit(7), 16, CPU_8086),
+    form("CMP", &[ RM32, SI8 ], 0, &[ 0x83 ], Digit(7), 32, CPU_8086),
+    form("CMP", &[ RM64, SI8 ], 0, &[ 0x83 ], Digit(7), 64, CPU_8086),
+    form("CMP", &[ Al, I8 ], 0, &[ 0x3C ], Opcode, 0, CPU_8086),
+    form("CMP", &[ Ax, I16 ], 0, &[ 0x3D ], Opcode, 16, CPU_8086),
+    form("CMP", &[ Eax, I32 ], 0, &[ 0x3D ], Opcode, 32, CPU_8086),
+    form("CMP", &[ Rax, I32 ], 0, &[ 0x3D ], Opcode, 64, CPU_8086),
+    form("CMP", &[ RM8, I8 ], 0, &[ 0x80 ], Digit(7), 0, CPU_8086),
+    form("CMP", &[ RM16, I16 ], 0, &[ 0x81 ], Digit(7), 16, CPU_8086),
+    form("CMP", &[ RM32, I32 ], 0, &[ 0x81 ], Digit(7), 32, CPU_8086),
+    form("CMP", &[ RM64, I32 ], 0, &[ 0x81 ], Digit(7), 64, CPU_8086),
+
+    form("TEST", &[ RM8, R8 ], 0, &[ 0x84 ], ModRM, 0, CPU_8086),
+    form("TEST", &[ RM16, R16 ], 0, &[ 0x85 ], ModRM, 16, CPU_8086),
+    form("TEST", &[ RM32, R32 ], 0, &[ 0x85 ], ModRM, 32, CPU_8086),
+    form("TEST", &[ RM64, R64 ], 0, &[ 0x85 ], ModRM, 64, CPU_8086),
+    form("TEST", &[ Al, I8 ], 0, &[ 0xA8 ], Opcode, 0, CPU_8086),
+    form("TEST", &[ Ax, I16 ], 0, &[ 0xA9 ], Opcode, 16, CPU_8086),
+    form("TEST", &[ Eax, I32 ], 0, &[ 0xA9 ], Opcode, 32, CPU_8086),
+    form("TEST", &[ Rax, I32 ], 0, &[ 0xA9 ], Opcode, 64, CPU_8086),
+    form("TEST", &[ RM8, I8 ], 0, &[ 0xF6 ], Digit(0), 0, CPU_8086),
+    form("TEST", &[ RM16, I16 ], 0, &[ 0xF7 ], Digit(0), 16, CPU_8086),
+    form("TEST", &[ RM32, I32 ], 0, &[ 0xF7 ], Digit(0), 32, CPU_8086),
+    form("TEST", &[ RM64, I32 ], 0, &[ 0xF7 ], Digit(0), 64, CPU_8086),
+
+    form("MOV", &[ RM8, R8 ], 0, &[ 0x88 ], ModRM, 0, CPU_8086),
+    form("MOV", &[ RM16, R16 ], 0, &[ 0x89 ], ModRM, 16, CPU_8086),
+    form("MOV", &[ RM32, R32 ], 0, &[ 0x89 ], ModRM, 32, CPU_8086),
+    form("MOV", &[ RM64, R64 ], 0, &[ 0x89 ], ModRM, 64, CPU_8086),
+    form("MOV", &[ R8, RM8 ], 0, &[ 0x8A ], ModRM, 0, CPU_8086),
+    form("MOV", &[ R16, RM16 ], 0, &[ 0x8B ], ModRM, 16, CPU_8086),
+    form("MOV", &[ R32, RM32 ], 0, &[ 0x8B ], ModRM, 32, CPU_8086),
+    form("MOV", &[ R64, RM64 ], 0, &[ 0x8B ], ModRM, 64, CPU_8086),
+    form("MOV", &[ RM16, Sreg ], 0, &[ 0x8C ], ModRM, 0, CPU_8086),
+    form("MOV", &[ Sreg, RM16 ], 0, &[ 0x8E ], ModRM, 0, CPU_8086),
+    form("MOV", &[ R8, I8 ], 0, &[ 0xB0 ], PlusReg, 0, CPU_8086),
+    form("MOV", &[ R16, I16 ], 0, &[ 0xB8 ], PlusReg, 16, CPU_8086),
+    form("MOV", &[ R32, I32 ], 0, &[ 0xB8 ], PlusReg, 32, CPU_8086),
+    form("MOV", &[ RM64, I32 ], 0, &[ 0xC7 ], Digit(0), 64, CPU_8086),
+    form("MOV", &[ R64, I64 ], 0, &[ 0xB8 ], PlusReg, 64, CPU_8086),
+    form("MOV", &[ RM8, I8 ], 0, &[ 0xC6 ], Digit(0), 0, CPU_8086),
+    form("MOV", &[ RM16, I16 ], 0, &[ 0xC7 ], Digit(0), 16, CPU_8086),
+    form("MOV", &[ RM32, I32 ], 0, &[ 0xC7 ], Digit(0), 32, CPU_8086),
+    form("MOV", &[ B64, Creg ], 0, &[ 0x0F, 0x20 ], ModRM, 0, CPU_PRIVILEGED),
+    form("MOV", &[ Creg, B64 ], 0, &[ 0x0F, 0x22 ], ModRM, 0, CPU_PRIVILEGED),
+    form("MOV", &[ B64, Dreg ], 0, &[ 0x0F, 0x21 ], ModRM, 0, CPU_PRIVILEGED),
+    form("MOV", &[ Dreg, B64 ], 0, &[ 0x0F, 0x23 ], ModRM, 0, CPU_PRIVILEGED),
+
+    form("MOVZX", &[ R16, RM8 ], 0, &[ 0x0F, 0xB6 ], ModRM, 16, CPU_386),
+    form("MOVZX", &[ R32, RM8 ], 0, &[ 0x0F, 0xB6 ], ModRM, 32, CPU_386),
+    form("MOVZX", &[ R64, RM8 ], 0, &[ 0x0F, 0xB6 ], ModRM, 64, CPU_386),
+    form("MOVZX", &[ R32, RM16 ], 0, &[ 0x0F, 0xB7 ], ModRM, 32, CPU_386),
+    form("MOVZX", &[ R64, RM16 ], 0, &[ 0x0F, 0xB7 ], ModRM, 64, CPU_386),
+    form("MOVSX", &[ R16, RM8 ], 0, &[ 0x0F, 0xBE ], ModRM, 16, CPU_386),
+    form("MOVSX", &[ R32, RM8 ], 0, &[ 0x0F, 0xBE ], ModRM, 32, CPU_386),
+    form("MOVSX", &[ R64, RM8 ], 0, &[ 0x0F, 0xBE ], ModRM, 64, CPU_386),
+    form("MOVSX", &[ R32, RM16 ], 0, &[ 0x0F, 0xBF ], ModRM, 32, CPU_386),
+    form("MOVSX", &[ R64, RM16 ], 0, &[ 0x0F, 0xBF ], ModRM, 64, CPU_386),
+    form("MOVSXD", &[ R64, RM32 ], 0, &[ 0x63 ], ModRM, 64, CPU_AMD64),
+    form("LEA", &[ R16, Mem ], 0, &[ 0x8D ], ModRM, 16, CPU_8086),
+    form("LEA", &[ R32, Mem ], 0, &[ 0x8D ], ModRM, 32, CPU_8086),
+    form("LEA", &[ R64, Mem ], 0, &[ 0x8D ], ModRM, 64, CPU_8086),
+    form("XCHG", &[ RM8, R8 ], 0, &[ 0x86 ], ModRM, 0, CPU_8086),
+    form("XCHG", &[ R8, RM8 ], 0, &[ 0x86 ], ModRM, 0, CPU_8086),
+    form("XCHG", &[ RM16, R16 ], 0, &[ 0x87 ], ModRM, 16, CPU_8086),
+    form("XCHG", &[ R16, RM16 ], 0, &[ 0x87 ], ModRM, 16, CPU_8086),
+    form("XCHG", &[ RM32, R32 ], 0, &[ 0x87 ], ModRM, 32, CPU_8086),
+    form("XCHG", &[ R32, RM32 ], 0, &[ 0x87 ], ModRM, 32, CPU_8086),
+    form("XCHG", &[ RM64, R64 ], 0, &[ 0x87 ], ModRM, 64, CPU_8086),
+    form("XCHG", &[ R64, RM64 ], 0, &[ 0x87 ], ModRM, 64, CPU_8086),
+
+    form("INC", &[ RM8 ], 0, &[ 0xFE ], Digit(0), 0, CPU_8086),
+    form("INC", &[ RM16 ], 0, &[ 0xFF ], Digit(0), 16, CPU_8086),
+    form("INC", &[ RM32 ], 0, &[ 0xFF ], Digit(0), 32, CPU_8086),
+    form("INC", &[ RM64 ], 0, &[ 0xFF ], Digit(0), 64, CPU_8086),
+    form("DEC", &[ RM8 ], 0, &[ 0xFE ], Digit(1), 0, CPU_8086),
+    form("DEC", &[ RM16 ], 0, &[ 0xFF ], Digit(1), 16, CPU_8086),
+    form("DEC", &[ RM32 ], 0, &[ 0xFF ], Digit(1), 32, CPU_8086),
+    form("DEC", &[ RM64 ], 0, &[ 0xFF ], Digit(1), 64, CPU_8086),
+    form("NOT", &[ RM8 ], 0, &[ 0xF6 ], Digit(2), 0, CPU_8086),
+    form("NOT", &[ RM16 ], 0, &[ 0xF7 ], Digit(2), 16, CPU_8086),
+    form("NOT", &[ RM32 ], 0, &[ 0xF7 ], Digit(2), 32, CPU_8086),
+    form("NOT", &[ RM64 ], 0, &[ 0xF7 ], Digit(2), 64, CPU_8086),
+    form("NEG", &[ RM8 ], 0, &[ 0xF6 ], Digit(3), 0, CPU_8086),
+    form("NEG", &[ RM16 ], 0, &[ 0xF7 ], Digit(3), 16, CPU_8086),
+    form("NEG", &[ RM32 ], 0, &[ 0xF7 ], Digit(3), 32, CPU_8086),
+    form("NEG", &[ RM64 ], 0, &[ 0xF7 ], Digit(3), 64, CPU_8086),
+    form("MUL", &[ RM8 ], 0, &[ 0xF6 ], Digit(4), 0, CPU_8086),
+    form("MUL", &[ RM16 ], 0, &[ 0xF7 ], Digit(4), 16, CPU_8086),
+    form("MUL", &[ RM32 ], 0, &[ 0xF7 ], Digit(4), 32, CPU_8086),
+    form("MUL", &[ RM64 ], 0, &[ 0xF7 ], Digit(4), 64, CPU_8086),
+    form("IMUL", &[ RM8 ], 0, &[ 0xF6 ], Digit(5), 0, CPU_8086),
+    form("IMUL", &[ RM16 ], 0, &[ 0xF7 ], Digit(5), 16, CPU_8086),
+    form("IMUL", &[ RM32 ], 0, &[ 0xF7 ], Digit(5), 32, CPU_8086),
+    form("IMUL", &[ RM64 ], 0, &[ 0xF7 ], Digit(5), 64, CPU_8086),
+    form("DIV", &[ RM8 ], 0, &[ 0xF6 ], Digit(6), 0, CPU_8086),
+    form("DIV", &[ RM16 ], 0, &[ 0xF7 ], Digit(6), 16, CPU_8086),
+    form("DIV", &[ RM32 ], 0, &[ 0xF7 ], Digit(6), 32, CPU_8086),
+    form("DIV", &[ RM64 ], 0, &[ 0xF7 ], Digit(6), 64, CPU_8086),
+    form("IDIV", &[ RM8 ], 0, &[ 0xF6 ], Digit(7), 0, CPU_8086),
+    form("IDIV", &[ RM16 ], 0, &[ 0xF7 ], Digit(7), 16, CPU_8086),
+    form("IDIV", &[ RM32 ], 0, &[ 0xF7 ], Digit(7), 32, CPU_8086),
+    form("IDIV", &[ RM64 ], 0, &[ 0xF7 ], Digit(7), 64, CPU_8086),
+    form("IMUL", &[ R16, RM16 ], 0, &[ 0x0F, 0xAF ], ModRM, 16, CPU_386),
+    form("IMUL", &[ R32, RM32 ], 0, &[ 0x0F, 0xAF ], ModRM, 32, CPU_386),
+    form("IMUL", &[ R64, RM64 ], 0, &[ 0x0F, 0xAF ], ModRM, 64, CPU_386),
+    form("IMUL", &[ R16, RM16, SI8 ], 0, &[ 0x6B ], ModRM, 16, CPU_186),
+    form("IMUL", &[ R32, RM32, SI8 ], 0, &[ 0x6B ], ModRM, 32, CPU_186),
+    form("IMUL", &[ R64, RM64, SI8 ], 0, &[ 0x6B ], ModRM, 64, CPU_186),
+    form("IMUL", &[ R16, RM16, I16 ], 0, &[ 0x69 ], ModRM, 16, CPU_186),
+    form("IMUL", &[ R32, RM32, I32 ], 0, &[ 0x69 ], ModRM, 32, CPU_186),
+    form("IMUL", &[ R64, RM64, I32 ], 0, &[ 0x69 ], ModRM, 64, CPU_186),
+
+    form("ROL", &[ RM8, One ], 0, &[ 0xD0 ], Digit(0), 0, CPU_8086),
+    form("ROL", &[ RM8, Cl ], 0, &[ 0xD2 ], Digit(0), 0, CPU_8086),
+    form("ROL", &[ RM8, I8 ], 0, &[ 0xC0 ], Digit(0), 0, CPU_186),
+    form("ROL", &[ RM16, One ], 0, &[ 0xD1 ], Digit(0), 16, CPU_8086),
+    form("ROL", &[ RM16, Cl ], 0, &[ 0xD3 ], Digit(0), 16, CPU_8086),
+    form("ROL", &[ RM16, I8 ], 0, &[ 0xC1 ], Digit(0), 16, CPU_186),
+    form("ROL", &[ RM32, One ], 0, &[ 0xD1 ], Digit(0), 32, CPU_8086),
+    form("ROL", &[ RM32, Cl ], 0, &[ 0xD3 ], Digit(0), 32, CPU_8086),
+    form("ROL", &[ RM32, I8 ], 0, &[ 0xC1 ], Digit(0), 32, CPU_186),
+    form("ROL", &[ RM64, One ], 0, &[ 0xD1 ], Digit(0), 64, CPU_8086),
+    form("ROL", &[ RM64, Cl ], 0, &[ 0xD3 ], Digit(0), 64, CPU_8086),
+    form("ROL", &[ RM64, I8 ], 0, &[ 0xC1 ], Digit(0), 64, CPU_186),
+    form("ROR", &[ RM8, One ], 0, &[ 0xD0 ], Digit(1), 0, CPU_8086),
+    form("ROR", &[ RM8, Cl ], 0, &[ 0xD2 ], Digit(1), 0, CPU_8086),
+    form("ROR", &[ RM8, I8 ], 0, &[ 0xC0 ], Digit(1), 0, CPU_186),
+    form("ROR", &[ RM16, One ], 0, &[ 0xD1 ], Digit(1), 16, CPU_8086),
+    form("ROR", &[ RM16, Cl ], 0, &[ 0xD3 ], Digit(1), 16, CPU_8086),
+    form("ROR", &[ RM16, I8 ], 0, &[ 0xC1 ], Digit(1), 16, CPU_186),
+    form("ROR", &[ RM32, One ], 0, &[ 0xD1 ], Digit(1), 32, CPU_8086),
+    form("ROR", &[ RM32, Cl ], 0, &[ 0xD3 ], Digit(1), 32, CPU_8086),
+    form("ROR", &[ RM32, I8 ], 0, &[ 0xC1 ], Digit(1), 32, CPU_186),
+    form("ROR", &[ RM64, One ], 0, &[ 0xD1 ], Digit(1), 64, CPU_8086),
+    form("ROR", &[ RM64, Cl ], 0, &[ 0xD3 ], Digit(1), 64, CPU_8086),
+    form("ROR", &[ RM64, I8 ], 0, &[ 0xC1 ], Digit(1), 64, CPU_186),
+    form("RCL", &[ RM8, One ], 0, &[ 0xD0 ], Digit(2), 0, CPU_8086),
+    form("RCL", &[ RM8, Cl ], 0, &[ 0xD2 ], Digit(2), 0, CPU_8086),
+    form("RCL", &[ RM8, I8 ], 0, &[ 0xC0 ], Digit(2), 0, CPU_186),
+    form("RCL", &[ RM16, One ], 0, &[ 0xD1 ], Digit(2), 16, CPU_8086),
+    form("RCL", &[ RM16, Cl ], 0, &[ 0xD3 ], Digit(2), 16, CPU_8086),
+    form("RCL", &[ RM16, I8 ], 0, &[ 0xC1 ], Digit(2), 16, CPU_186),
+    form("RCL", &[ RM32, One ], 0, &[ 0xD1 ], Digit(2), 32, CPU_8086),
+    form("RCL", &[ RM32, Cl ], 0, &[ 0xD3 ], Digit(2), 32, CPU_8086),
+    form("RCL", &[ RM32, I8 ], 0, &[ 0xC1 ], Digit(2), 32, CPU_186),
+    form("RCL", &[ RM64, One ], 0, &[ 0xD1 ], Digit(2), 64, CPU_8086),
+    form("RCL", &[ RM64, Cl ], 0, &[ 0xD3 ], Digit(2), 64, CPU_8086),
+    form("RCL", &[ RM64, I8 ], 0, &[ 0xC1 ], Digit(2), 64, CPU_186),
+    form("RCR", &[ RM8, One ], 0, &[ 0xD0 ], Digit(3), 0, CPU_8086),
+    form("RCR", &[ RM8, Cl ], 0, &[ 0xD2 ], Digit(3), 0, CPU_8086),
+    form("RCR", &[ RM8, I8 ], 0, &[ 0xC0 ], Digit(3), 0, CPU_186),
+    form("RCR", &[ RM16, One ], 0, &[ 0xD1 ], Digit(3), 16, CPU_8086),
+    form("RCR", &[ RM16, Cl ], 0, &[ 0xD3 ], Digit(3), 16, CPU_8086),
+    form("RCR", &[ RM16, I8 ], 0, &[ 0xC1 ], Digit(3), 16, CPU_186),
+    form("RCR", &[ RM32, One ], 0, &[ 0xD1 ], Digit(3), 32, CPU_8086),
+    form("RCR", &[ RM32, Cl ], 0, &[ 0xD3 ], Digit(3), 32, CPU_8086),
+    form("RCR", &[ RM32, I8 ], 0, &[ 0xC1 ], Digit(3), 32, CPU_186),
+    form("RCR", &[ RM64, One ], 0, &[ 0xD1 ], Digit(3), 64, CPU_8086),
+    form("RCR", &[ RM64, Cl ], 0, &[ 0xD3 ], Digit(3), 64, CPU_8086),
+    form("RCR", &[ RM64, I8 ], 0, &[ 0xC1 ], Digit(3), 64, CPU_186),
+    form("SHL", &[ RM8, One ], 0, &[ 0xD0 ], Digit(4), 0, CPU_8086),
+    form("SHL", &[ RM8, Cl ], 0, &[ 0xD2 ], Digit(4), 0, CPU_8086),
+    form("SHL", &[ RM8, I8 ], 0, &[ 0xC0 ], Digit(4), 0, CPU_186),
+    form("SHL", &[ RM16, One ], 0, &[ 0xD1 ], Digit(4), 16, CPU_8086),
+    form("SHL", &[ RM16, Cl ], 0, &[ 0xD3 ], Digit(4), 16, CPU_8086),
+    form("SHL", &[ RM16, I8 ], 0, &[ 0xC1 ], Digit(4), 16, CPU_186),
+    form("SHL", &[ RM32, One ], 0, &[ 0xD1 ], Digit(4), 32, CPU_8086),
+    form("SHL", &[ RM32, Cl ], 0, &[ 0xD3 ], Digit(4), 32, CPU_8086),
+    form("SHL", &[ RM32, I8 ], 0, &[ 0xC1 ], Digit(4), 32, CPU_186),
+    form("SHL", &[ RM64, One ], 0, &[ 0xD1 ], Digit(4), 64, CPU_8086),
+    form("SHL", &[ RM64, Cl ], 0, &[ 0xD3 ], Digit(4), 64, CPU_8086),
+    form("SHL", &[ RM64, I8 ], 0, &[ 0xC1 ], Digit(4), 64, CPU_186),
+    form("SAL", &[ RM8, One ], 0, &[ 0xD0 ], Digit(4), 0, CPU_8086),
+    form("SAL", &[ RM8, Cl ], 0, &[ 0xD2 ], Digit(4), 0, CPU_8086),
+    form("SAL", &[ RM8, I8 ], 0, &[ 0xC0 ], Digit(4), 0, CPU_186),
+    form("SAL", &[ RM16, One ], 0, &[ 0xD1 ], Digit(4), 16, CPU_8086),
+    form("SAL", &[ RM16, Cl ], 0, &[ 0xD3 ], Digit(4), 16, CPU_8086),
+    form("SAL", &[ RM16, I8 ], 0, &[ 0xC1 ], Digit(4), 16, CPU_186),
+    form("SAL", &[ RM32, One ], 0, &[ 0xD1 ], Digit(4), 32, CPU_8086),
+    form("SAL", &[ RM32, Cl ], 0, &[ 0xD3 ], Digit(4), 32, CPU_8086),
+    form("SAL", &[ RM32, I8 ], 0, &[ 0xC1 ], Digit(4), 32, CPU_186),
+    form("SAL", &[ RM64, One ], 0, &[ 0xD1 ], Digit(4), 64, CPU_8086),
+    form("SAL", &[ RM64, Cl ], 0, &[ 0xD3 ], Digit(4), 64, CPU_8086),
+    form("SAL", &[ RM64, I8 ], 0, &[ 0xC1 ], Digit(4), 64, CPU_186),
+    form("SHR", &[ RM8, One ], 0, &[ 0xD0 ], Digit(5), 0, CPU_8086),
+    form("SHR", &[ RM8, Cl ], 0, &[ 0xD2 ], Digit(5), 0, CPU_8086),
+    form("SHR", &[ RM8, I8 ], 0, &[ 0xC0 ], Digit(5), 0, CPU_186),
+    form("SHR", &[ RM16, One ], 0, &[ 0xD1 ], Digit(5), 16, CPU_8086),
+    form("SHR", &[ RM16, Cl ], 0, &[ 0xD3 ], Digit(5), 16, CPU_8086),
+    form("SHR", &[ RM16, I8 ], 0, &[ 0xC1 ], Digit(5), 16, CPU_186),
+    form("SHR", &[ RM32, One ], 0, &[ 0xD1 ], Digit(5), 32, CPU_8086),
+    form("SHR", &[ RM32, Cl ], 0, &[ 0xD3 ], Digit(5), 32, CPU_8086),
+    form("SHR", &[ RM32, I8 ], 0, &[ 0xC1 ], Digit(5), 32, CPU_186),
+    form("SHR", &[ RM64, One ], 0, &[ 0xD1 ], Digit(5), 64, CPU_8086),
+    form("SHR", &[ RM64, Cl ], 0, &[ 0xD3 ], Digit(5), 64, CPU_8086),
+    form("SHR", &[ RM64, I8 ], 0, &[ 0xC1 ], Digit(5), 64, CPU_186),
+    form("SAR", &[ RM8, One ], 0, &[ 0xD0 ], Digit(7), 0, CPU_8086),
+    form("SAR", &[ RM8, Cl ], 0, &[ 0xD2 ], Digit(7), 0, CPU_8086),
+    form("SAR", &[ RM8, I8 ], 0, &[ 0xC0 ], Digit(7), 0, CPU_186),
+    form("SAR", &[ RM16, One ], 0, &[ 0xD1 ], Digit(7), 16, CPU_8086),
+    form("SAR", &[ RM16, Cl ], 0, &[ 0xD3 ], Digit(7), 16, CPU_8086),
+    form("SAR", &[ RM16, I8 ], 0, &[ 0xC1 ], Digit(7), 16, CPU_186),
+    form("SAR", &[ RM32, One ], 0, &[ 0xD1 ], Digit(7), 32, CPU_8086),
+    form("SAR", &[ RM32, Cl ], 0, &[ 0xD3 ], Digit(7), 32, CPU_8086),
+    form("SAR", &[ RM32, I8 ], 0, &[ 0xC1 ], Digit(7), 32, CPU_186),
+    form("SAR", &[ RM64, One ], 0, &[ 0xD1 ], Digit(7), 64, CPU_8086),
+    form("SAR", &[ RM64, Cl ], 0, &[ 0xD3 ], Digit(7), 64, CPU_8086),
+    form("SAR", &[ RM64, I8 ], 0, &[ 0xC1 ], Digit(7), 64, CPU_186),
+    form("SHLD", &[ RM16, R16, I8 ], 0, &[ 0x0F, 0xA4 ], ModRM, 16, CPU_386),
+    form("SHLD", &[ RM16, R16, Cl ], 0, &[ 0x0F, 0xA5 ], ModRM, 16, CPU_386),
+    form("SHLD", &[ RM32, R32, I8 ], 0, &[ 0x0F, 0xA4 ], ModRM, 32, CPU_386),
+    form("SHLD", &[ RM32, R32, Cl ], 0, &[ 0x0F, 0xA5 ], ModRM, 32, CPU_386),
+    form("SHLD", &[ RM64, R64, I8 ], 0, &[ 0x0F, 0xA4 ], ModRM, 64, CPU_386),
+    form("SHLD", &[ RM64, R64, Cl ], 0, &[ 0x0F, 0xA5 ], ModRM, 64, CPU_386),
+    form("SHRD", &[ RM16, R16, I8 ], 0, &[ 0x0F, 0xAC ], ModRM, 16, CPU_386),
+    form("SHRD", &[ RM16, R16, Cl ], 0, &[ 0x0F, 0xAD ], ModRM, 16, CPU_386),
+    form("SHRD", &[ RM32, R32, I8 ], 0, &[ 0x0F, 0xAC ], ModRM, 32, CPU_386),
+    form("SHRD", &[ RM32, R32, Cl ], 0, &[ 0x0F, 0xAD ], ModRM, 32, CPU_386),
+    form("SHRD", &[ RM64, R64, I8 ], 0, &[ 0x0F, 0xAC ], ModRM, 64, CPU_386),
+    form("SHRD", &[ RM64, R64, Cl ], 0, &[ 0x0F, 0xAD ], ModRM, 64, CPU_386),
+
+    form("BT", &[ RM16, R16 ], 0, &[ 0x0F, 0xA3 ], ModRM, 16, CPU_386),
+    form("BT", &[ RM32, R32 ], 0, &[ 0x0F, 0xA3 ], ModRM, 32, CPU_386),
+    form("BT", &[ RM64, R64 ], 0, &[ 0x0F, 0xA3 ], ModRM, 64, CPU_386),
+    form("BT", &[ RM16, I8 ], 0, &[ 0x0F, 0xBA ], Digit(4), 16, CPU_386),
+    form("BT", &[ RM32, I8 ], 0, &[ 0x0F, 0xBA ], Digit(4), 32, CPU_386),
+    form("BT", &[ RM64, I8 ], 0, &[ 0x0F, 0xBA ], Digit(4), 64, CPU_386),
+    form("BTS", &[ RM16, R16 ], 0, &[ 0x0F, 0xAB ], ModRM, 16, CPU_386),
+    form("BTS", &[ RM32, R32 ], 0, &[ 0x0F, 0xAB ], ModRM, 32, CPU_386),
+    form("BTS", &[ RM64, R64 ], 0, &[ 0x0F, 0xAB ], ModRM, 64, CPU_386),
+    form("BTS", &[ RM16, I8 ], 0, &[ 0x0F, 0xBA ], Digit(5), 16, CPU_386),
+    form("BTS", &[ RM32, I8 ], 0, &[ 0x0F, 0xBA ], Digit(5), 32, CPU_386),
+    form("BTS", &[ RM64, I8 ], 0, &[ 0x0F, 0xBA ], Digit(5), 64, CPU_386),
+    form("BTR", &[ RM16, R16 ], 0, &[ 0x0F, 0xB3 ], ModRM, 16, CPU_386),
+    form("BTR", &[ RM32, R32 ], 0, &[ 0x0F, 0xB3 ], ModRM, 32, CPU_386),
+    form("BTR", &[ RM64, R64 ], 0, &[ 0x0F, 0xB3 ], ModRM, 64, CPU_386),
+    form("BTR", &[ RM16, I8 ], 0, &[ 0x0F, 0xBA ], Digit(6), 16, CPU_386),
+    form("BTR", &[ RM32, I8 ], 0, &[ 0x0F, 0xBA ], Digit(6), 32, CPU_386),
+    form("BTR", &[ RM64, I8 ], 0, &[ 0x0F, 0xBA ], Digit(6), 64, CPU_386),
+    form("BTC", &[ RM16, R16 ], 0, &[ 0x0F, 0xBB ], ModRM, 16, CPU_386),
+    form("BTC", &[ RM32, R32 ], 0, &[ 0x0F, 0xBB ], ModRM, 32, CPU_386),
+    form("BTC", &[ RM64, R64 ], 0, &[ 0x0F, 0xBB ], ModRM, 64, CPU_386),
+    form("BTC", &[ RM16, I8 ], 0, &[ 0x0F, 0xBA ], Digit(7), 16, CPU_386),
+    form("BTC", &[ RM32, I8 ], 0, &[ 0x0F, 0xBA ], Digit(7), 32, CPU_386),
+    form("BTC", &[ RM64, I8 ], 0, &[ 0x0F, 0xBA ], Digit(7), 64, CPU_386),
+    form("BSF", &[ R16, RM16 ], 0, &[ 0x0F, 0xBC ], ModRM, 16, CPU_386),
+    form("BSF", &[ R32, RM32 ], 0, &[ 0x0F, 0xBC ], ModRM, 32, CPU_386),
+    form("BSF", &[ R64, RM64 ], 0, &[ 0x0F, 0xBC ], ModRM, 64, CPU_386),
+    form("BSR", &[ R16, RM16 ], 0, &[ 0x0F, 0xBD ], ModRM, 16, CPU_386),
+    form("BSR", &[ R32, RM32 ], 0, &[ 0x0F, 0xBD ], ModRM, 32, CPU_386),
+    form("BSR", &[ R64, RM64 ], 0, &[ 0x0F, 0xBD ], ModRM, 64, CPU_386),
+    form("BSWAP", &[ R32 ], 0, &[ 0x0F, 0xC8 ], PlusReg, 32, CPU_486),
+    form("BSWAP", &[ R64 ], 0, &[ 0x0F, 0xC8 ], PlusReg, 64, CPU_486),
+    form("CMPXCHG", &[ RM8, R8 ], 0, &[ 0x0F, 0xB0 ], ModRM, 0, CPU_486),
+    form("CMPXCHG", &[ RM16, R16 ], 0, &[ 0x0F, 0xB1 ], ModRM, 16, CPU_486),
+    form("CMPXCHG", &[ RM32, R32 ], 0, &[ 0x0F, 0xB1 ], ModRM, 32, CPU_486),
+    form("CMPXCHG", &[ RM64, R64 ], 0, &[ 0x0F, 0xB1 ], ModRM, 64, CPU_486),
+    form("XADD", &[ RM8, R8 ], 0, &[ 0x0F, 0xC0 ], ModRM, 0, CPU_486),
+    form("XADD", &[ RM16, R16 ], 0, &[ 0x0F, 0xC1 ], ModRM, 16, CPU_486),
+    form("XADD", &[ RM32, R32 ], 0, &[ 0x0F, 0xC1 ], ModRM, 32, CPU_486),
+    form("XADD", &[ RM64, R64 ], 0, &[ 0x0F, 0xC1 ], ModRM, 64, CPU_486),
+    form("CMPXCHG8B", &[ M64 ], 0, &[ 0x0F, 0xC7 ], Digit(1), 0, CPU_PENTIUM),
+    form("CMPXCHG16B", &[ M128 ], 0, &[ 0x0F, 0xC7 ], Digit(1), 64, CPU_AMD64),
+
+    form("PUSH", &[ R64 ], 0, &[ 0x50 ], PlusReg, 0, CPU_8086),
+    form("PUSH", &[ R16 ], 0, &[ 0x50 ], PlusReg, 16, CPU_8086),
+    form("PUSH", &[ RM64 ], 0, &[ 0xFF ], Digit(6), 0, CPU_8086),
+    form("PUSH", &[ SI8 ], 0, &[ 0x6A ], Opcode, 0, CPU_186),
+    form("PUSH", &[ I32 ], 0, &[ 0x68 ], Opcode, 0, CPU_186),
+    legacy("PUSH", &[ R32 ], 0, &[ 0x50 ], PlusReg, 32, CPU_8086),
+    legacy("PUSH", &[ RM32 ], 0, &[ 0xFF ], Digit(6), 32, CPU_8086),
+    form("POP", &[ R64 ], 0, &[ 0x58 ], PlusReg, 0, CPU_8086),
+    form("POP", &[ R16 ], 0, &[ 0x58 ], PlusReg, 16, CPU_8086),
+    form("POP", &[ RM64 ], 0, &[ 0x8F ], Digit(0), 0, CPU_8086),
+    legacy("POP", &[ R32 ], 0, &[ 0x58 ], PlusReg, 32, CPU_8086),
+    legacy("POP", &[ RM32 ], 0, &[ 0x8F ], Digit(0), 32, CPU_8086),
+    form("ENTER", &[ I16, I8 ], 0, &[ 0xC8 ], Opcode, 0, CPU_186),
+    form("RET", &[ I16 ], 0, &[ 0xC2 ], Opcode, 0, CPU_8086),
+    form("INT", &[ I8 ], 0, &[ 0xCD ], Opcode, 0, CPU_8086),
+    form("IN", &[ Al, I8 ], 0, &[ 0xE4 ], Opcode, 0, CPU_8086),
+    form("IN", &[ Eax, I8 ], 0, &[ 0xE5 ], Opcode, 0, CPU_8086),
+    form("IN", &[ Al, Dx ], 0, &[ 0xEC ], Opcode, 0, CPU_8086),
+    form("IN", &[ Eax, Dx ], 0, &[ 0xED ], Opcode, 0, CPU_8086),
+    form("OUT", &[ I8, Al ], 0, &[ 0xE6 ], Opcode, 0, CPU_8086),
+    form("OUT", &[ I8, Eax ], 0, &[ 0xE7 ], Opcode, 0, CPU_8086),
+    form("OUT", &[ Dx, Al ], 0, &[ 0xEE ], Opcode, 0, CPU_8086),
+    form("OUT", &[ Dx, Eax ], 0, &[ 0xEF ], Opcode, 0, CPU_8086),
+
+    form("NOP", &[  ], 0, &[ 0x90 ], Opcode, 0, CPU_8086),
+    form("PAUSE", &[  ], 0xF3, &[ 0x90 ], Opcode, 0, CPU_8086),
+    form("HLT", &[  ], 0, &[ 0xF4 ], Opcode, 0, CPU_PRIVILEGED),
+    form("CLC", &[  ], 0, &[ 0xF8 ], Opcode, 0, CPU_8086),
+    form("STC", &[  ], 0, &[ 0xF9 ], Opcode, 0, CPU_8086),
+    form("CMC", &[  ], 0, &[ 0xF5 ], Opcode, 0, CPU_8086),
+    form("CLD", &[  ], 0, &[ 0xFC ], Opcode, 0, CPU_8086),
+    form("STD", &[  ], 0, &[ 0xFD ], Opcode, 0, CPU_8086),
+    form("CLI", &[  ], 0, &[ 0xFA ], Opcode, 0, CPU_PROTECTED),
+    form("STI", &[  ], 0, &[ 0xFB ], Opcode, 0, CPU_PROTECTED),
+    form("LEAVE", &[  ], 0, &[ 0xC9 ], Opcode, 0, CPU_186),
+    form("RET", &[  ], 0, &[ 0xC3 ], Opcode, 0, CPU_8086),
+    form("INT3", &[  ], 0, &[ 0xCC ], Opcode, 0, CPU_8086),
+    form("CBW", &[  ], 0, &[ 0x98 ], Opcode, 16, CPU_8086),
+    form("CWDE", &[  ], 0, &[ 0x98 ], Opcode, 32, CPU_386),
+    form("CDQE", &[  ], 0, &[ 0x98 ], Opcode, 64, CPU_AMD64),
+    form("CWD", &[  ], 0, &[ 0x99 ], Opcode, 16, CPU_8086),
+    form("CDQ", &[  ], 0, &[ 0x99 ], Opcode, 32, CPU_386),
+    form("CQO", &[  ], 0, &[ 0x99 ], Opcode, 64, CPU_AMD64),
+    form("PUSHF", &[  ], 0, &[ 0x9C ], Opcode, 0, CPU_8086),
+    form("PUSHFQ", &[  ], 0, &[ 0x9C ], Opcode, 0, CPU_AMD64),
+    form("POPF", &[  ], 0, &[ 0x9D ], Opcode, 0, CPU_8086),
+    form("POPFQ", &[  ], 0, &[ 0x9D ], Opcode, 0, CPU_AMD64),
+    form("SAHF", &[  ], 0, &[ 0x9E ], Opcode, 0, CPU_8086),
+    form("LAHF", &[  ], 0, &[ 0x9F ], Opcode, 0, CPU_8086),
+    form("CPUID", &[  ], 0, &[ 0x0F, 0xA2 ], Opcode, 0, CPU_PENTIUM),
+    form("RDTSC", &[  ], 0, &[ 0x0F, 0x31 ], Opcode, 0, CPU_PENTIUM),
+    form("UD2", &[  ], 0, &[ 0x0F, 0x0B ], Opcode, 0, CPU_P6),
+    form("SYSCALL", &[  ], 0, &[ 0x0F, 0x05 ], Opcode, 0, CPU_AMD64),
+    form("SYSRET", &[  ], 0, &[ 0x0F, 0x07 ], Opcode, 64, CPU_PRIVILEGED),
+    form("IRETQ", &[  ], 0, &[ 0xCF ], Opcode, 64, CPU_PRIVILEGED),
+    form("SWAPGS", &[  ], 0, &[ 0x0F, 0x01, 0xF8 ], Opcode, 0, CPU_PRIVILEGED),
+    form("RDMSR", &[  ], 0, &[ 0x0F, 0x32 ], Opcode, 0, CPU_PRIVILEGED),
+    form("WRMSR", &[  ], 0, &[ 0x0F, 0x30 ], Opcode, 0, CPU_PRIVILEGED),
+    form("INVD", &[  ], 0, &[ 0x0F, 0x08 ], Opcode, 0, CPU_PRIVILEGED),
+    form("WBINVD", &[  ], 0, &[ 0x0F, 0x09 ], Opcode, 0, CPU_PRIVILEGED),
+    form("CLTS", &[  ], 0, &[ 0x0F, 0x06 ], Opcode, 0, CPU_PRIVILEGED),
+    form("SFENCE", &[  ], 0, &[ 0x0F, 0xAE, 0xF8 ], Opcode, 0, CPU_SSE),
+    form("LFENCE", &[  ], 0, &[ 0x0F, 0xAE, 0xE8 ], Opcode, 0, CPU_SSE2),
+    form("MFENCE", &[  ], 0, &[ 0x0F, 0xAE, 0xF0 ], Opcode, 0, CPU_SSE2),
+    form("MOVSB", &[  ], 0, &[ 0xA4 ], Opcode, 0, CPU_8086),
+    form("MOVSW", &[  ], 0, &[ 0xA5 ], Opcode, 16, CPU_8086),
+    form("MOVSD", &[  ], 0, &[ 0xA5 ], Opcode, 32, CPU_386),
+    form("MOVSQ", &[  ], 0, &[ 0xA5 ], Opcode, 64, CPU_AMD64),
+    form("CMPSB", &[  ], 0, &[ 0xA6 ], Opcode, 0, CPU_8086),
+    form("CMPSW", &[  ], 0, &[ 0xA7 ], Opcode, 16, CPU_8086),
+    form("CMPSD", &[  ], 0, &[ 0xA7 ], Opcode, 32, CPU_386),
+    form("CMPSQ", &[  ], 0, &[ 0xA7 ], Opcode, 64, CPU_AMD64),
+    form("STOSB", &[  ], 0, &[ 0xAA ], Opcode, 0, CPU_8086),
+    form("STOSW", &[  ], 0, &[ 0xAB ], Opcode, 16, CPU_8086),
+    form("STOSD", &[  ], 0, &[ 0xAB ], Opcode, 32, CPU_386),
+    form("STOSQ", &[  ], 0, &[ 0xAB ], Opcode, 64, CPU_AMD64),
+    form("LODSB", &[  ], 0, &[ 0xAC ], Opcode, 0, CPU_8086),
+    form("LODSW", &[  ], 0, &[ 0xAD ], Opcode, 16, CPU_8086),
+    form("LODSD", &[  ], 0, &[ 0xAD ], Opcode, 32, CPU_386),
+    form("LODSQ", &[  ], 0, &[ 0xAD ], Opcode, 64, CPU_AMD64),
+    form("SCASB", &[  ], 0, &[ 0xAE ], Opcode, 0, CPU_8086),
+    form("SCASW", &[  ], 0, &[ 0xAF ], Opcode, 16, CPU_8086),
+    form("SCASD", &[  ], 0, &[ 0xAF ], Opcode, 32, CPU_386),
+    form("SCASQ", &[  ], 0, &[ 0xAF ], Opcode, 64, CPU_AMD64),
+    form("LOCK", &[  ], 0, &[ 0xF0 ], Opcode, 0, CPU_8086),
+    form("REP", &[  ], 0, &[ 0xF3 ], Opcode, 0, CPU_8086),
+    form("REPE", &[  ], 0, &[ 0xF3 ], Opcode, 0, CPU_8086),
+    form("REPZ", &[  ], 0, &[ 0xF3 ], Opcode, 0, CPU_8086),
+    form("REPNE", &[  ], 0, &[ 0xF2 ], Opcode, 0, CPU_8086),
+    form("REPNZ", &[  ], 0, &[ 0xF2 ], Opcode, 0, CPU_8086),
+    form("LGDT", &[ Mem ], 0, &[ 0x0F, 0x01 ], Digit(2), 0, CPU_PRIVILEGED),
+    form("LIDT", &[ Mem ], 0, &[ 0x0F, 0x01 ], Digit(3), 0, CPU_PRIVILEGED),
+    form("SGDT", &[ Mem ], 0, &[ 0x0F, 0x01 ], Digit(0), 0, CPU_PROTECTED),
+    form("SIDT", &[ Mem ], 0, &[ 0x0F, 0x01 ], Digit(1), 0, CPU_PROTECTED),
+    form("LTR", &[ RM16 ], 0, &[ 0x0F, 0x00 ], Digit(3), 0, CPU_PRIVILEGED),
+    form("INVLPG", &[ Mem ], 0, &[ 0x0F, 0x01 ], Digit(7), 0, CPU_PRIVILEGED),
+
+    form("JMP", &[ Rel8 ], 0, &[ 0xEB ], Opcode, 0, CPU_8086),
+    form("JMP", &[ Rel32 ], 0, &[ 0xE9 ], Opcode, 0, CPU_8086),
+    form("JMP", &[ RM64 ], 0, &[ 0xFF ], Digit(4), 0, CPU_8086),
+    legacy("JMP", &[ RM32 ], 0, &[ 0xFF ], Digit(4), 32, CPU_8086),
+    legacy("JMP", &[ RM16 ], 0, &[ 0xFF ], Digit(4), 16, CPU_8086),
+    form("CALL", &[ Rel32 ], 0, &[ 0xE8 ], Opcode, 0, CPU_8086),
+    form("CALL", &[ RM64 ], 0, &[ 0xFF ], Digit(2), 0, CPU_8086),
+    legacy("CALL", &[ RM32 ], 0, &[ 0xFF ], Digit(2), 32, CPU_8086),
+    legacy("CALL", &[ RM16 ], 0, &[ 0xFF ], Digit(2), 16, CPU_8086),
+    form("JRCXZ", &[ Rel8 ], 0, &[ 0xE3 ], Opcode, 0, CPU_8086),
+    form("LOOP", &[ Rel8 ], 0, &[ 0xE2 ], Opcode, 0, CPU_8086),
+    form("LOOPE", &[ Rel8 ], 0, &[ 0xE1 ], Opcode, 0, CPU_8086),
+    form("LOOPNE", &[ Rel8 ], 0, &[ 0xE0 ], Opcode, 0, CPU_8086),
+    form("JO", &[ Rel8 ], 0, &[ 0x70 ], Opcode, 0, CPU_8086),
+    form("JO", &[ Rel32 ], 0, &[ 0x0F, 0x80 ], Opcode, 0, CPU_386),
+    form("JNO", &[ Rel8 ], 0, &[ 0x71 ], Opcode, 0, CPU_8086),
+    form("JNO", &[ Rel32 ], 0, &[ 0x0F, 0x81 ], Opcode, 0, CPU_386),
+    form("JB", &[ Rel8 ], 0, &[ 0x72 ], Opcode, 0, CPU_8086),
+    form("JB", &[ Rel32 ], 0, &[ 0x0F, 0x82 ], Opcode, 0, CPU_386),
+    form("JC", &[ Rel8 ], 0, &[ 0x72 ], Opcode, 0, CPU_8086),
+    form("JC", &[ Rel32 ], 0, &[ 0x0F, 0x82 ], Opcode, 0, CPU_386),
+    form("JNAE", &[ Rel8 ], 0, &[ 0x72 ], Opcode, 0, CPU_8086),
+    form("JNAE", &[ Rel32 ], 0, &[ 0x0F, 0x82 ], Opcode, 0, CPU_386),
+    form("JAE", &[ Rel8 ], 0, &[ 0x73 ], Opcode, 0, CPU_8086),
+    form("JAE", &[ Rel32 ], 0, &[ 0x0F, 0x83 ], Opcode, 0, CPU_386),
+    form("JNB", &[ Rel8 ], 0, &[ 0x73 ], Opcode, 0, CPU_8086),
+    form("JNB", &[ Rel32 ], 0, &[ 0x0F, 0x83 ], Opcode, 0, CPU_386),
+    form("JNC", &[ Rel8 ], 0, &[ 0x73 ], Opcode, 0, CPU_8086),
+    form("JNC", &[ Rel32 ], 0, &[ 0x0F, 0x83 ], Opcode, 0, CPU_386),
+    form("JE", &[ Rel8 ], 0, &[ 0x74 ], Opcode, 0, CPU_8086),
+    form("JE", &[ Rel32 ], 0, &[ 0x0F, 0x84 ], Opcode, 0, CPU_386),
+    form("JZ", &[ Rel8 ], 0, &[ 0x74 ], Opcode, 0, CPU_8086),
+    form("JZ", &[ Rel32 ], 0, &[ 0x0F, 0x84 ], Opcode, 0, CPU_386),
+    form("JNE", &[ Rel8 ], 0, &[ 0x75 ], Opcode, 0, CPU_8086),
+    form("JNE", &[ Rel32 ], 0, &[ 0x0F, 0x85 ], Opcode, 0, CPU_386),
+    form("JNZ", &[ Rel8 ], 0, &[ 0x75 ], Opcode, 0, CPU_8086),
+    form("JNZ", &[ Rel32 ], 0, &[ 0x0F, 0x85 ], Opcode, 0, CPU_386),
+    form("JBE", &[ Rel8 ], 0, &[ 0x76 ], Opcode, 0, CPU_8086),
+    form("JBE", &[ Rel32 ], 0, &[ 0x0F, 0x86 ], Opcode, 0, CPU_386),
+    form("JNA", &[ Rel8 ], 0, &[ 0x76 ], Opcode, 0, CPU_8086),
+    form("JNA", &[ Rel32 ], 0, &[ 0x0F, 0x86 ], Opcode, 0, CPU_386),
+    form("JA", &[ Rel8 ], 0, &[ 0x77 ], Opcode, 0, CPU_8086),
+    form("JA", &[ Rel32 ], 0, &[ 0x0F, 0x87 ], Opcode, 0, CPU_386),
+    form("JNBE", &[ Rel8 ], 0, &[ 0x77 ], Opcode, 0, CPU_8086),
+    form("JNBE", &[ Rel32 ], 0, &[ 0x0F, 0x87 ], Opcode, 0, CPU_386),
+    form("JS", &[ Rel8 ], 0, &[ 0x78 ], Opcode, 0, CPU_8086),
+    form("JS", &[ Rel32 ], 0, &[ 0x0F, 0x88 ], Opcode, 0, CPU_386),
+    form("JNS", &[ Rel8 ], 0, &[ 0x79 ], Opcode, 0, CPU_8086),
+    form("JNS", &[ Rel32 ], 0, &[ 0x0F, 0x89 ], Opcode, 0, CPU_386),
+    form("JP", &[ Rel8 ], 0, &[ 0x7A ], Opcode, 0, CPU_8086),
+    form("JP", &[ Rel32 ], 0, &[ 0x0F, 0x8A ], Opcode, 0, CPU_386),
+    form("JPE", &[ Rel8 ], 0, &[ 0x7A ], Opcode, 0, CPU_8086),
+    form("JPE", &[ Rel32 ], 0, &[ 0x0F, 0x8A ], Opcode, 0, CPU_386),
+    form("JNP", &[ Rel8 ], 0, &[ 0x7B ], Opcode, 0, CPU_8086),
+    form("JNP", &[ Rel32 ], 0, &[ 0x0F, 0x8B ], Opcode, 0, CPU_386),
+    form("JPO", &[ Rel8 ], 0, &[ 0x7B ], Opcode, 0, CPU_8086),
+    form("JPO", &[ Rel32 ], 0, &[ 0x0F, 0x8B ], Opcode, 0, CPU_386),
+    form("JL", &[ Rel8 ], 0, &[ 0x7C ], Opcode, 0, CPU_8086),
+    form("JL", &[ Rel32 ], 0, &[ 0x0F, 0x8C ], Opcode, 0, CPU_386),
+    form("JNGE", &[ Rel8 ], 0, &[ 0x7C ], Opcode, 0, CPU_8086),
+    form("JNGE", &[ Rel32 ], 0, &[ 0x0F, 0x8C ], Opcode, 0, CPU_386),
+    form("JGE", &[ Rel8 ], 0, &[ 0x7D ], Opcode, 0, CPU_8086),
+    form("JGE", &[ Rel32 ], 0, &[ 0x0F, 0x8D ], Opcode, 0, CPU_386),
+    form("JNL", &[ Rel8 ], 0, &[ 0x7D ], Opcode, 0, CPU_8086),
+    form("JNL", &[ Rel32 ], 0, &[ 0x0F, 0x8D ], Opcode, 0, CPU_386),
+    form("JLE", &[ Rel8 ], 0, &[ 0x7E ], Opcode, 0, CPU_8086),
+    form("JLE", &[ Rel32 ], 0, &[ 0x0F, 0x8E ], Opcode, 0, CPU_386),
+    form("JNG", &[ Rel8 ], 0, &[ 0x7E ], Opcode, 0, CPU_8086),
+    form("JNG", &[ Rel32 ], 0, &[ 0x0F, 0x8E ], Opcode, 0, CPU_386),
+    form("JG", &[ Rel8 ], 0, &[ 0x7F ], Opcode, 0, CPU_8086),
+    form("JG", &[ Rel32 ], 0, &[ 0x0F, 0x8F ], Opcode, 0, CPU_386),
+    form("JNLE", &[ Rel8 ], 0, &[ 0x7F ], Opcode, 0, CPU_8086),
+    form("JNLE", &[ Rel32 ], 0, &[ 0x0F, 0x8F ], Opcode, 0, CPU_386),
+    form("SETO", &[ RM8 ], 0, &[ 0x0F, 0x90 ], Digit(0), 0, CPU_386),
+    form("SETNO", &[ RM8 ], 0, &[ 0x0F, 0x91 ], Digit(0), 0, CPU_386),
+    form("SETB", &[ RM8 ], 0, &[ 0x0F, 0x92 ], Digit(0), 0, CPU_386),
+    form("SETC", &[ RM8 ], 0, &[ 0x0F, 0x92 ], Digit(0), 0, CPU_386),
+    form("SETNAE", &[ RM8 ], 0, &[ 0x0F, 0x92 ], Digit(0), 0, CPU_386),
+    form("SETAE", &[ RM8 ], 0, &[ 0x0F, 0x93 ], Digit(0), 0, CPU_386),
+    form("SETNB", &[ RM8 ], 0, &[ 0x0F, 0x93 ], Digit(0), 0, CPU_386),
+    form("SETNC", &[ RM8 ], 0, &[ 0x0F, 0x93 ], Digit(0), 0, CPU_386),
+    form("SETE", &[ RM8 ], 0, &[ 0x0F, 0x94 ], Digit(0), 0, CPU_386),
+    form("SETZ", &[ RM8 ], 0, &[ 0x0F, 0x94 ], Digit(0), 0, CPU_386),
+    form("SETNE", &[ RM8 ], 0, &[ 0x0F, 0x95 ], Digit(0), 0, CPU_386),
+    form("SETNZ", &[ RM8 ], 0, &[ 0x0F, 0x95 ], Digit(0), 0, CPU_386),
+    form("SETBE", &[ RM8 ], 0, &[ 0x0F, 0x96 ], Digit(0), 0, CPU_386),
+    form("SETNA", &[ RM8 ], 0, &[ 0x0F, 0x96 ], Digit(0), 0, CPU_386),
+    form("SETA", &[ RM8 ], 0, &[ 0x0F, 0x97 ], Digit(0), 0, CPU_386),
+    form("SETNBE", &[ RM8 ], 0, &[ 0x0F, 0x97 ], Digit(0), 0, CPU_386),
+    form("SETS", &[ RM8 ], 0, &[ 0x0F, 0x98 ], Digit(0), 0, CPU_386),
+    form("SETNS", &[ RM8 ], 0, &[ 0x0F, 0x99 ], Digit(0), 0, CPU_386),
+    form("SETP", &[ RM8 ], 0, &[ 0x0F, 0x9A ], Digit(0), 0, CPU_386),
+    form("SETPE", &[ RM8 ], 0, &[ 0x0F, 0x9A ], Digit(0), 0, CPU_386),
+    form("SETNP", &[ RM8 ], 0, &[ 0x0F, 0x9B ], Digit(0), 0, CPU_386),
+    form("SETPO", &[ RM8 ], 0, &[ 0x0F, 0x9B ], Digit(0), 0, CPU_386),
+    form("SETL", &[ RM8 ], 0, &[ 0x0F, 0x9C ], Digit(0), 0, CPU_386),
+    form("SETNGE", &[ RM8 ], 0, &[ 0x0F, 0x9C ], Digit(0), 0, CPU_386),
+    form("SETGE", &[ RM8 ], 0, &[ 0x0F, 0x9D ], Digit(0), 0, CPU_386),
+    form("SETNL", &[ RM8 ], 0, &[ 0x0F, 0x9D ], Digit(0), 0, CPU_386),
+    form("SETLE", &[ RM8 ], 0, &[ 0x0F, 0x9E ], Digit(0), 0, CPU_386),
+    form("SETNG", &[ RM8 ], 0, &[ 0x0F, 0x9E ], Digit(0), 0, CPU_386),
+    form("SETG", &[ RM8 ], 0, &[ 0x0F, 0x9F ], Digit(0), 0, CPU_386),
+    form("SETNLE", &[ RM8 ], 0, &[ 0x0F, 0x9F ], Digit(0), 0, CPU_386),
+    form("CMOVO", &[ R16, RM16 ], 0, &[ 0x0F, 0x40 ], ModRM, 16, CPU_P6),
+    form("CMOVO", &[ R32, RM32 ], 0, &[ 0x0F, 0x40 ], ModRM, 32, CPU_P6),
+    form("CMOVO", &[ R64, RM64 ], 0, &[ 0x0F, 0x40 ], ModRM, 64, CPU_P6),
+    form("CMOVNO", &[ R16, RM16 ], 0, &[ 0x0F, 0x41 ], ModRM, 16, CPU_P6),
+    form("CMOVNO", &[ R32, RM32 ], 0, &[ 0x0F, 0x41 ], ModRM, 32, CPU_P6),
+    form("CMOVNO", &[ R64, RM64 ], 0, &[ 0x0F, 0x41 ], ModRM, 64, CPU_P6),
+    form("CMOVB", &[ R16, RM16 ], 0, &[ 0x0F, 0x42 ], ModRM, 16, CPU_P6),
+    form("CMOVB", &[ R32, RM32 ], 0, &[ 0x0F, 0x42 ], ModRM, 32, CPU_P6),
+    form("CMOVB", &[ R64, RM64 ], 0, &[ 0x0F, 0x42 ], ModRM, 64, CPU_P6),
+    form("CMOVC", &[ R16, RM16 ], 0, &[ 0x0F, 0x42 ], ModRM, 16, CPU_P6),
+    form("CMOVC", &[ R32, RM32 ], 0, &[ 0x0F, 0x42 ], ModRM, 32, CPU_P6),
+    form("CMOVC", &[ R64, RM64 ], 0, &[ 0x0F, 0x42 ], ModRM, 64, CPU_P6),
+    form("CMOVNAE", &[ R16, RM16 ], 0, &[ 0x0F, 0x42 ], ModRM, 16, CPU_P6),
+    form("CMOVNAE", &[ R32, RM32 ], 0, &[ 0x0F, 0x42 ], ModRM, 32, CPU_P6),
+    form("CMOVNAE", &[ R64, RM64 ], 0, &[ 0x0F, 0x42 ], ModRM, 64, CPU_P6),
+    form("CMOVAE", &[ R16, RM16 ], 0, &[ 0x0F, 0x43 ], ModRM, 16, CPU_P6),
+    form("CMOVAE", &[ R32, RM32 ], 0, &[ 0x0F, 0x43 ], ModRM, 32, CPU_P6),
+    form("CMOVAE", &[ R64, RM64 ], 0, &[ 0x0F, 0x43 ], ModRM, 64, CPU_P6),
+    form("CMOVNB", &[ R16, RM16 ], 0, &[ 0x0F, 0x43 ], ModRM, 16, CPU_P6),
+    form("CMOVNB", &[ R32, RM32 ], 0, &[ 0x0F, 0x43 ], ModRM, 32, CPU_P6),
+    form("CMOVNB", &[ R64, RM64 ], 0, &[ 0x0F, 0x43 ], ModRM, 64, CPU_P6),
+    form("CMOVNC", &[ R16, RM16 ], 0, &[ 0x0F, 0x43 ], ModRM, 16, CPU_P6),
+    form("CMOVNC", &[ R32, RM32 ], 0, &[ 0x0F, 0x43 ], ModRM, 32, CPU_P6),
+    form("CMOVNC", &[ R64, RM64 ], 0, &[ 0x0F, 0x43 ], ModRM, 64, CPU_P6),
+    form("CMOVE", &[ R16, RM16 ], 0, &[ 0x0F, 0x44 ], ModRM, 16, CPU_P6),
+    form("CMOVE", &[ R32, RM32 ], 0, &[ 0x0F, 0x44 ], ModRM, 32, CPU_P6),
+    form("CMOVE", &[ R64, RM64 ], 0, &[ 0x0F, 0x44 ], ModRM, 64, CPU_P6),
+    form("CMOVZ", &[ R16, RM16 ], 0, &[ 0x0F, 0x44 ], ModRM, 16, CPU_P6),
+    form("CMOVZ", &[ R32, RM32 ], 0, &[ 0x0F, 0x44 ], ModRM, 32, CPU_P6),
+    form("CMOVZ", &[ R64, RM64 ], 0, &[ 0x0F, 0x44 ], ModRM, 64, CPU_P6),
+    form("CMOVNE", &[ R16, RM16 ], 0, &[ 0x0F, 0x45 ], ModRM, 16, CPU_P6),
+    form("CMOVNE", &[ R32, RM32 ], 0, &[ 0x0F, 0x45 ], ModRM, 32, CPU_P6),
+    form("CMOVNE", &[ R64, RM64 ], 0, &[ 0x0F, 0x45 ], ModRM, 64, CPU_P6),
+    form("CMOVNZ", &[ R16, RM16 ], 0, &[ 0x0F, 0x45 ], ModRM, 16, CPU_P6),
+    form("CMOVNZ", &[ R32, RM32 ], 0, &[ 0x0F, 0x45 ], ModRM, 32, CPU_P6),
+    form("CMOVNZ", &[ R64, RM64 ], 0, &[ 0x0F, 0x45 ], ModRM, 64, CPU_P6),
+    form("CMOVBE", &[ R16, RM16 ], 0, &[ 0x0F, 0x46 ], ModRM, 16, CPU_P6),
+    form("CMOVBE", &[ R32, RM32 ], 0, &[ 0x0F, 0x46 ], ModRM, 32, CPU_P6),
+    form("CMOVBE", &[ R64, RM64 ], 0, &[ 0x0F, 0x46 ], ModRM, 64, CPU_P6),
+    form("CMOVNA", &[ R16, RM16 ], 0, &[ 0x0F, 0x46 ], ModRM, 16, CPU_P6),
+    form("CMOVNA", &[ R32, RM32 ], 0, &[ 0x0F, 0x46 ], ModRM, 32, CPU_P6),
+    form("CMOVNA", &[ R64, RM64 ], 0, &[ 0x0F, 0x46 ], ModRM, 64, CPU_P6),
+    form("CMOVA", &[ R16, RM16 ], 0, &[ 0x0F, 0x47 ], ModRM, 16, CPU_P6),
+    form("CMOVA", &[ R32, RM32 ], 0, &[ 0x0F, 0x47 ], ModRM, 32, CPU_P6),
+    form("CMOVA", &[ R64, RM64 ], 0, &[ 0x0F, 0x47 ], ModRM, 64, CPU_P6),
+    form("CMOVNBE", &[ R16, RM16 ], 0, &[ 0x0F, 0x47 ], ModRM, 16, CPU_P6),
+    form("CMOVNBE", &[ R32, RM32 ], 0, &[ 0x0F, 0x47 ], ModRM, 32, CPU_P6),
+    form("CMOVNBE", &[ R64, RM64 ], 0, &[ 0x0F, 0x47 ], ModRM, 64, CPU_P6),
+    form("CMOVS", &[ R16, RM16 ], 0, &[ 0x0F, 0x48 ], ModRM, 16, CPU_P6),
+    form("CMOVS", &[ R32, RM32 ], 0, &[ 0x0F, 0x48 ], ModRM, 32, CPU_P6),
+    form("CMOVS", &[ R64, RM64 ], 0, &[ 0x0F, 0x48 ], ModRM, 64, CPU_P6),
+    form("CMOVNS", &[ R16, RM16 ], 0, &[ 0x0F, 0x49 ], ModRM, 16, CPU_P6),
+    form("CMOVNS", &[ R32, RM32 ], 0, &[ 0x0F, 0x49 ], ModRM, 32, CPU_P6),
+    form("CMOVNS", &[ R64, RM64 ], 0, &[ 0x0F, 0x49 ], ModRM, 64, CPU_P6),
+    form("CMOVP", &[ R16, RM16 ], 0, &[ 0x0F, 0x4A ], ModRM, 16, CPU_P6),
+    form("CMOVP", &[ R32, RM32 ], 0, &[ 0x0F, 0x4A ], ModRM, 32, CPU_P6),
+    form("CMOVP", &[ R64, RM64 ], 0, &[ 0x0F, 0x4A ], ModRM, 64, CPU_P6),
+    form("CMOVPE", &[ R16, RM16 ], 0, &[ 0x0F, 0x4A ], ModRM, 16, CPU_P6),
+    form("CMOVPE", &[ R32, RM32 ], 0, &[ 0x0F, 0x4A ], ModRM, 32, CPU_P6),
+    form("CMOVPE", &[ R64, RM64 ], 0, &[ 0x0F, 0x4A ], ModRM, 64, CPU_P6),
+    form("CMOVNP", &[ R16, RM16 ], 0, &[ 0x0F, 0x4B ], ModRM, 16, CPU_P6),
+    form("CMOVNP", &[ R32, RM32 ], 0, &[ 0x0F, 0x4B ], ModRM, 32, CPU_P6),
+    form("CMOVNP", &[ R64, RM64 ], 0, &[ 0x0F, 0x4B ], ModRM, 64, CPU_P6),
+    form("CMOVPO", &[ R16, RM16 ], 0, &[ 0x0F, 0x4B ], ModRM, 16, CPU_P6),
+    form("CMOVPO", &[ R32, RM32 ], 0, &[ 0x0F, 0x4B ], ModRM, 32, CPU_P6),
+    form("CMOVPO", &[ R64, RM64 ], 0, &[ 0x0F, 0x4B ], ModRM, 64, CPU_P6),
+    form("CMOVL", &[ R16, RM16 ], 0, &[ 0x0F, 0x4C ], ModRM, 16, CPU_P6),
+    form("CMOVL", &[ R32, RM32 ], 0, &[ 0x0F, 0x4C ], ModRM, 32, CPU_P6),
+    form("CMOVL", &[ R64, RM64 ], 0, &[ 0x0F, 0x4C ], ModRM, 64, CPU_P6),
+    form("CMOVNGE", &[ R16, RM16 ], 0, &[ 0x0F, 0x4C ], ModRM, 16, CPU_P6),
+    form("CMOVNGE", &[ R32, RM32 ], 0, &[ 0x0F, 0x4C ], ModRM, 32, CPU_P6),
+    form("CMOVNGE", &[ R64, RM64 ], 0, &[ 0x0F, 0x4C ], ModRM, 64, CPU_P6),
+    form("CMOVGE", &[ R16, RM16 ], 0, &[ 0x0F, 0x4D ], ModRM, 16, CPU_P6),
+    form("CMOVGE", &[ R32, RM32 ], 0, &[ 0x0F, 0x4D ], ModRM, 32, CPU_P6),
+    form("CMOVGE", &[ R64, RM64 ], 0, &[ 0x0F, 0x4D ], ModRM, 64, CPU_P6),
+    form("CMOVNL", &[ R16, RM16 ], 0, &[ 0x0F, 0x4D ], ModRM, 16, CPU_P6),
+    form("CMOVNL", &[ R32, RM32 ], 0, &[ 0x0F, 0x4D ], ModRM, 32, CPU_P6),
+    form("CMOVNL", &[ R64, RM64 ], 0, &[ 0x0F, 0x4D ], ModRM, 64, CPU_P6),
+    form("CMOVLE", &[ R16, RM16 ], 0, &[ 0x0F, 0x4E ], ModRM, 16, CPU_P6),
+    form("CMOVLE", &[ R32, RM32 ], 0, &[ 0x0F, 0x4E ], ModRM, 32, CPU_P6),
+    form("CMOVLE", &[ R64, RM64 ], 0, &[ 0x0F, 0x4E ], ModRM, 64, CPU_P6),
+    form("CMOVNG", &[ R16, RM16 ], 0, &[ 0x0F, 0x4E ], ModRM, 16, CPU_P6),
+    form("CMOVNG", &[ R32, RM32 ], 0, &[ 0x0F, 0x4E ], ModRM, 32, CPU_P6),
+    form("CMOVNG", &[ R64, RM64 ], 0, &[ 0x0F, 0x4E ], ModRM, 64, CPU_P6),
+    form("CMOVG", &[ R16, RM16 ], 0, &[ 0x0F, 0x4F ], ModRM, 16, CPU_P6),
+    form("CMOVG", &[ R32, RM32 ], 0, &[ 0x0F, 0x4F ], ModRM, 32, CPU_P6),
+    form("CMOVG", &[ R64, RM64 ], 0, &[ 0x0F, 0x4F ], ModRM, 64, CPU_P6),
+    form("CMOVNLE", &[ R16, RM16 ], 0, &[ 0x0F, 0x4F ], ModRM, 16, CPU_P6),
+    form("CMOVNLE", &[ R32, RM32 ], 0, &[ 0x0F, 0x4F ], ModRM, 32, CPU_P6),
+    form("CMOVNLE", &[ R64, RM64 ], 0, &[ 0x0F, 0x4F ], ModRM, 64, CPU_P6),
+
+    form("ADDPS", &[ Xmm, XmmM128 ], 0, &[ 0x0F, 0x58 ], ModRM, 0, CPU_SSE),
+    form("ADDSS", &[ Xmm, XmmM32 ], 0xF3, &[ 0x0F, 0x58 ], ModRM, 0, CPU_SSE),
+    form("ADDPD", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x58 ], ModRM, 0, CPU_SSE2),
+    form("ADDSD", &[ Xmm, XmmM64 ], 0xF2, &[ 0x0F, 0x58 ], ModRM, 0, CPU_SSE2),
+    form("MULPS", &[ Xmm, XmmM128 ], 0, &[ 0x0F, 0x59 ], ModRM, 0, CPU_SSE),
+    form("MULSS", &[ Xmm, XmmM32 ], 0xF3, &[ 0x0F, 0x59 ], ModRM, 0, CPU_SSE),
+    form("MULPD", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x59 ], ModRM, 0, CPU_SSE2),
+    form("MULSD", &[ Xmm, XmmM64 ], 0xF2, &[ 0x0F, 0x59 ], ModRM, 0, CPU_SSE2),
+    form("SUBPS", &[ Xmm, XmmM128 ], 0, &[ 0x0F, 0x5C ], ModRM, 0, CPU_SSE),
+    form("SUBSS", &[ Xmm, XmmM32 ], 0xF3, &[ 0x0F, 0x5C ], ModRM, 0, CPU_SSE),
+    form("SUBPD", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x5C ], ModRM, 0, CPU_SSE2),
+    form("SUBSD", &[ Xmm, XmmM64 ], 0xF2, &[ 0x0F, 0x5C ], ModRM, 0, CPU_SSE2),
+    form("MINPS", &[ Xmm, XmmM128 ], 0, &[ 0x0F, 0x5D ], ModRM, 0, CPU_SSE),
+    form("MINSS", &[ Xmm, XmmM32 ], 0xF3, &[ 0x0F, 0x5D ], ModRM, 0, CPU_SSE),
+    form("MINPD", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x5D ], ModRM, 0, CPU_SSE2),
+    form("MINSD", &[ Xmm, XmmM64 ], 0xF2, &[ 0x0F, 0x5D ], ModRM, 0, CPU_SSE2),
+    form("DIVPS", &[ Xmm, XmmM128 ], 0, &[ 0x0F, 0x5E ], ModRM, 0, CPU_SSE),
+    form("DIVSS", &[ Xmm, XmmM32 ], 0xF3, &[ 0x0F, 0x5E ], ModRM, 0, CPU_SSE),
+    form("DIVPD", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x5E ], ModRM, 0, CPU_SSE2),
+    form("DIVSD", &[ Xmm, XmmM64 ], 0xF2, &[ 0x0F, 0x5E ], ModRM, 0, CPU_SSE2),
+    form("MAXPS", &[ Xmm, XmmM128 ], 0, &[ 0x0F, 0x5F ], ModRM, 0, CPU_SSE),
+    form("MAXSS", &[ Xmm, XmmM32 ], 0xF3, &[ 0x0F, 0x5F ], ModRM, 0, CPU_SSE),
+    form("MAXPD", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x5F ], ModRM, 0, CPU_SSE2),
+    form("MAXSD", &[ Xmm, XmmM64 ], 0xF2, &[ 0x0F, 0x5F ], ModRM, 0, CPU_SSE2),
+    form("SQRTPS", &[ Xmm, XmmM128 ], 0, &[ 0x0F, 0x51 ], ModRM, 0, CPU_SSE),
+    form("SQRTSS", &[ Xmm, XmmM32 ], 0xF3, &[ 0x0F, 0x51 ], ModRM, 0, CPU_SSE),
+    form("SQRTPD", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x51 ], ModRM, 0, CPU_SSE2),
+    form("SQRTSD", &[ Xmm, XmmM64 ], 0xF2, &[ 0x0F, 0x51 ], ModRM, 0, CPU_SSE2),
+    form("RSQRTPS", &[ Xmm, XmmM128 ], 0, &[ 0x0F, 0x52 ], ModRM, 0, CPU_SSE),
+    form("RSQRTSS", &[ Xmm, XmmM32 ], 0xF3, &[ 0x0F, 0x52 ], ModRM, 0, CPU_SSE),
+    form("RCPPS", &[ Xmm, XmmM128 ], 0, &[ 0x0F, 0x53 ], ModRM, 0, CPU_SSE),
+    form("RCPSS", &[ Xmm, XmmM32 ], 0xF3, &[ 0x0F, 0x53 ], ModRM, 0, CPU_SSE),
+    form("ANDPS", &[ Xmm, XmmM128 ], 0, &[ 0x0F, 0x54 ], ModRM, 0, CPU_SSE),
+    form("ANDPD", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x54 ], ModRM, 0, CPU_SSE2),
+    form("ANDNPS", &[ Xmm, XmmM128 ], 0, &[ 0x0F, 0x55 ], ModRM, 0, CPU_SSE),
+    form("ANDNPD", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x55 ], ModRM, 0, CPU_SSE2),
+    form("ORPS", &[ Xmm, XmmM128 ], 0, &[ 0x0F, 0x56 ], ModRM, 0, CPU_SSE),
+    form("ORPD", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x56 ], ModRM, 0, CPU_SSE2),
+    form("XORPS", &[ Xmm, XmmM128 ], 0, &[ 0x0F, 0x57 ], ModRM, 0, CPU_SSE),
+    form("XORPD", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x57 ], ModRM, 0, CPU_SSE2),
+    form("UNPCKLPS", &[ Xmm, XmmM128 ], 0, &[ 0x0F, 0x14 ], ModRM, 0, CPU_SSE),
+    form("UNPCKLPD", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x14 ], ModRM, 0, CPU_SSE2),
+    form("UNPCKHPS", &[ Xmm, XmmM128 ], 0, &[ 0x0F, 0x15 ], ModRM, 0, CPU_SSE),
+    form("UNPCKHPD", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x15 ], ModRM, 0, CPU_SSE2),
+    form("MOVAPS", &[ Xmm, XmmM128 ], 0, &[ 0x0F, 0x28 ], ModRM, 0, CPU_SSE),
+    form("MOVAPS", &[ XmmM128, Xmm ], 0, &[ 0x0F, 0x29 ], ModRM, 0, CPU_SSE),
+    form("MOVAPD", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x28 ], ModRM, 0, CPU_SSE2),
+    form("MOVAPD", &[ XmmM128, Xmm ], 0x66, &[ 0x0F, 0x29 ], ModRM, 0, CPU_SSE2),
+    form("MOVUPS", &[ Xmm, XmmM128 ], 0, &[ 0x0F, 0x10 ], ModRM, 0, CPU_SSE),
+    form("MOVUPS", &[ XmmM128, Xmm ], 0, &[ 0x0F, 0x11 ], ModRM, 0, CPU_SSE),
+    form("MOVUPD", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x10 ], ModRM, 0, CPU_SSE2),
+    form("MOVUPD", &[ XmmM128, Xmm ], 0x66, &[ 0x0F, 0x11 ], ModRM, 0, CPU_SSE2),
+    form("MOVSS", &[ Xmm, XmmM32 ], 0xF3, &[ 0x0F, 0x10 ], ModRM, 0, CPU_SSE),
+    form("MOVSS", &[ XmmM32, Xmm ], 0xF3, &[ 0x0F, 0x11 ], ModRM, 0, CPU_SSE),
+    form("MOVSD", &[ Xmm, XmmM64 ], 0xF2, &[ 0x0F, 0x10 ], ModRM, 0, CPU_SSE2),
+    form("MOVSD", &[ XmmM64, Xmm ], 0xF2, &[ 0x0F, 0x11 ], ModRM, 0, CPU_SSE2),
+    form("MOVDQA", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x6F ], ModRM, 0, CPU_SSE2),
+    form("MOVDQA", &[ XmmM128, Xmm ], 0x66, &[ 0x0F, 0x7F ], ModRM, 0, CPU_SSE2),
+    form("MOVDQU", &[ Xmm, XmmM128 ], 0xF3, &[ 0x0F, 0x6F ], ModRM, 0, CPU_SSE2),
+    form("MOVDQU", &[ XmmM128, Xmm ], 0xF3, &[ 0x0F, 0x7F ], ModRM, 0, CPU_SSE2),
+    form("MOVD", &[ Xmm, RM32 ], 0x66, &[ 0x0F, 0x6E ], ModRM, 0, CPU_SSE2),
+    form("MOVD", &[ RM32, Xmm ], 0x66, &[ 0x0F, 0x7E ], ModRM, 0, CPU_SSE2),
+    form("MOVQ", &[ Xmm, XmmM64 ], 0xF3, &[ 0x0F, 0x7E ], ModRM, 0, CPU_SSE2),
+    form("MOVQ", &[ XmmM64, Xmm ], 0x66, &[ 0x0F, 0xD6 ], ModRM, 0, CPU_SSE2),
+    form("MOVQ", &[ Xmm, RM64 ], 0x66, &[ 0x0F, 0x6E ], ModRM, 64, CPU_SSE2),
+    form("MOVQ", &[ RM64, Xmm ], 0x66, &[ 0x0F, 0x7E ], ModRM, 64, CPU_SSE2),
+    form("MOVHLPS", &[ Xmm, XmmB ], 0, &[ 0x0F, 0x12 ], ModRM, 0, CPU_SSE),
+    form("MOVLHPS", &[ Xmm, XmmB ], 0, &[ 0x0F, 0x16 ], ModRM, 0, CPU_SSE),
+    form("MOVLPS", &[ Xmm, M64 ], 0, &[ 0x0F, 0x12 ], ModRM, 0, CPU_SSE),
+    form("MOVLPS", &[ M64, Xmm ], 0, &[ 0x0F, 0x13 ], ModRM, 0, CPU_SSE),
+    form("MOVHPS", &[ Xmm, M64 ], 0, &[ 0x0F, 0x16 ], ModRM, 0, CPU_SSE),
+    form("MOVHPS", &[ M64, Xmm ], 0, &[ 0x0F, 0x17 ], ModRM, 0, CPU_SSE),
+    form("MOVMSKPS", &[ R32, XmmB ], 0, &[ 0x0F, 0x50 ], ModRM, 0, CPU_SSE),
+    form("MOVMSKPD", &[ R32, XmmB ], 0x66, &[ 0x0F, 0x50 ], ModRM, 0, CPU_SSE2),
+
+    form("CVTSI2SS", &[ Xmm, RM32 ], 0xF3, &[ 0x0F, 0x2A ], ModRM, 32, CPU_SSE),
+    form("CVTSI2SS", &[ Xmm, RM64 ], 0xF3, &[ 0x0F, 0x2A ], ModRM, 64, CPU_SSE),
+    form("CVTSI2SD", &[ Xmm, RM32 ], 0xF2, &[ 0x0F, 0x2A ], ModRM, 32, CPU_SSE2),
+    form("CVTSI2SD", &[ Xmm, RM64 ], 0xF2, &[ 0x0F, 0x2A ], ModRM, 64, CPU_SSE2),
+    form("CVTTSS2SI", &[ R32, XmmM32 ], 0xF3, &[ 0x0F, 0x2C ], ModRM, 32, CPU_SSE),
+    form("CVTTSS2SI", &[ R64, XmmM32 ], 0xF3, &[ 0x0F, 0x2C ], ModRM, 64, CPU_SSE),
+    form("CVTSS2SI", &[ R32, XmmM32 ], 0xF3, &[ 0x0F, 0x2D ], ModRM, 32, CPU_SSE),
+    form("CVTSS2SI", &[ R64, XmmM32 ], 0xF3, &[ 0x0F, 0x2D ], ModRM, 64, CPU_SSE),
+    form("CVTTSD2SI", &[ R32, XmmM64 ], 0xF2, &[ 0x0F, 0x2C ], ModRM, 32, CPU_SSE2),
+    form("CVTTSD2SI", &[ R64, XmmM64 ], 0xF2, &[ 0x0F, 0x2C ], ModRM, 64, CPU_SSE2),
+    form("CVTSD2SI", &[ R32, XmmM64 ], 0xF2, &[ 0x0F, 0x2D ], ModRM, 32, CPU_SSE2),
+    form("CVTSD2SI", &[ R64, XmmM64 ], 0xF2, &[ 0x0F, 0x2D ], ModRM, 64, CPU_SSE2),
+    form("CVTSS2SD", &[ Xmm, XmmM32 ], 0xF3, &[ 0x0F, 0x5A ], ModRM, 0, CPU_SSE2),
+    form("CVTSD2SS", &[ Xmm, XmmM64 ], 0xF2, &[ 0x0F, 0x5A ], ModRM, 0, CPU_SSE2),
+    form("CVTPS2PD", &[ Xmm, XmmM64 ], 0, &[ 0x0F, 0x5A ], ModRM, 0, CPU_SSE2),
+    form("CVTPD2PS", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x5A ], ModRM, 0, CPU_SSE2),
+    form("CVTDQ2PS", &[ Xmm, XmmM128 ], 0, &[ 0x0F, 0x5B ], ModRM, 0, CPU_SSE2),
+    form("CVTPS2DQ", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x5B ], ModRM, 0, CPU_SSE2),
+    form("CVTTPS2DQ", &[ Xmm, XmmM128 ], 0xF3, &[ 0x0F, 0x5B ], ModRM, 0, CPU_SSE2),
+    form("UCOMISS", &[ Xmm, XmmM32 ], 0, &[ 0x0F, 0x2E ], ModRM, 0, CPU_SSE),
+    form("COMISS", &[ Xmm, XmmM32 ], 0, &[ 0x0F, 0x2F ], ModRM, 0, CPU_SSE),
+    form("UCOMISD", &[ Xmm, XmmM64 ], 0x66, &[ 0x0F, 0x2E ], ModRM, 0, CPU_SSE2),
+    form("COMISD", &[ Xmm, XmmM64 ], 0x66, &[ 0x0F, 0x2F ], ModRM, 0, CPU_SSE2),
+    form("CMPPS", &[ Xmm, XmmM128, I8 ], 0, &[ 0x0F, 0xC2 ], ModRM, 0, CPU_SSE),
+    form("CMPSS", &[ Xmm, XmmM32, I8 ], 0xF3, &[ 0x0F, 0xC2 ], ModRM, 0, CPU_SSE),
+    form("CMPPD", &[ Xmm, XmmM128, I8 ], 0x66, &[ 0x0F, 0xC2 ], ModRM, 0, CPU_SSE2),
+    form("CMPSD", &[ Xmm, XmmM64, I8 ], 0xF2, &[ 0x0F, 0xC2 ], ModRM, 0, CPU_SSE2),
+    form("SHUFPS", &[ Xmm, XmmM128, I8 ], 0, &[ 0x0F, 0xC6 ], ModRM, 0, CPU_SSE),
+    form("SHUFPD", &[ Xmm, XmmM128, I8 ], 0x66, &[ 0x0F, 0xC6 ], ModRM, 0, CPU_SSE2),
+
+    form("PADDB", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0xFC ], ModRM, 0, CPU_SSE2),
+    form("PADDW", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0xFD ], ModRM, 0, CPU_SSE2),
+    form("PADDD", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0xFE ], ModRM, 0, CPU_SSE2),
+    form("PADDQ", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0xD4 ], ModRM, 0, CPU_SSE2),
+    form("PSUBB", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0xF8 ], ModRM, 0, CPU_SSE2),
+    form("PSUBW", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0xF9 ], ModRM, 0, CPU_SSE2),
+    form("PSUBD", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0xFA ], ModRM, 0, CPU_SSE2),
+    form("PSUBQ", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0xFB ], ModRM, 0, CPU_SSE2),
+    form("PAND", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0xDB ], ModRM, 0, CPU_SSE2),
+    form("PANDN", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0xDF ], ModRM, 0, CPU_SSE2),
+    form("POR", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0xEB ], ModRM, 0, CPU_SSE2),
+    form("PXOR", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0xEF ], ModRM, 0, CPU_SSE2),
+    form("PCMPEQB", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x74 ], ModRM, 0, CPU_SSE2),
+    form("PCMPEQW", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x75 ], ModRM, 0, CPU_SSE2),
+    form("PCMPEQD", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x76 ], ModRM, 0, CPU_SSE2),
+    form("PCMPGTB", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x64 ], ModRM, 0, CPU_SSE2),
+    form("PCMPGTW", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x65 ], ModRM, 0, CPU_SSE2),
+    form("PCMPGTD", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x66 ], ModRM, 0, CPU_SSE2),
+    form("PMULLW", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0xD5 ], ModRM, 0, CPU_SSE2),
+    form("PMULUDQ", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0xF4 ], ModRM, 0, CPU_SSE2),
+    form("PUNPCKLBW", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x60 ], ModRM, 0, CPU_SSE2),
+    form("PUNPCKLWD", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x61 ], ModRM, 0, CPU_SSE2),
+    form("PUNPCKLDQ", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x62 ], ModRM, 0, CPU_SSE2),
+    form("PUNPCKLQDQ", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x6C ], ModRM, 0, CPU_SSE2),
+    form("PUNPCKHQDQ", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x6D ], ModRM, 0, CPU_SSE2),
+    form("PSLLW", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0xF1 ], ModRM, 0, CPU_SSE2),
+    form("PSLLD", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0xF2 ], ModRM, 0, CPU_SSE2),
+    form("PSLLQ", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0xF3 ], ModRM, 0, CPU_SSE2),
+    form("PSRLW", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0xD1 ], ModRM, 0, CPU_SSE2),
+    form("PSRLD", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0xD2 ], ModRM, 0, CPU_SSE2),
+    form("PSRLQ", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0xD3 ], ModRM, 0, CPU_SSE2),
+    form("PSRAW", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0xE1 ], ModRM, 0, CPU_SSE2),
+    form("PSRAD", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0xE2 ], ModRM, 0, CPU_SSE2),
+    form("PSHUFD", &[ Xmm, XmmM128, I8 ], 0x66, &[ 0x0F, 0x70 ], ModRM, 0, CPU_SSE2),
+    form("PSLLW", &[ XmmB, I8 ], 0x66, &[ 0x0F, 0x71 ], Digit(6), 0, CPU_SSE2),
+    form("PSRLW", &[ XmmB, I8 ], 0x66, &[ 0x0F, 0x71 ], Digit(2), 0, CPU_SSE2),
+    form("PSRAW", &[ XmmB, I8 ], 0x66, &[ 0x0F, 0x71 ], Digit(4), 0, CPU_SSE2),
+    form("PSLLD", &[ XmmB, I8 ], 0x66, &[ 0x0F, 0x72 ], Digit(6), 0, CPU_SSE2),
+    form("PSRLD", &[ XmmB, I8 ], 0x66, &[ 0x0F, 0x72 ], Digit(2), 0, CPU_SSE2),
+    form("PSRAD", &[ XmmB, I8 ], 0x66, &[ 0x0F, 0x72 ], Digit(4), 0, CPU_SSE2),
+    form("PSLLQ", &[ XmmB, I8 ], 0x66, &[ 0x0F, 0x73 ], Digit(6), 0, CPU_SSE2),
+    form("PSRLQ", &[ XmmB, I8 ], 0x66, &[ 0x0F, 0x73 ], Digit(2), 0, CPU_SSE2),
+    form("PSLLDQ", &[ XmmB, I8 ], 0x66, &[ 0x0F, 0x73 ], Digit(7), 0, CPU_SSE2),
+    form("PSRLDQ", &[ XmmB, I8 ], 0x66, &[ 0x0F, 0x73 ], Digit(3), 0, CPU_SSE2),
+    form("LDMXCSR", &[ M32 ], 0, &[ 0x0F, 0xAE ], Digit(2), 0, CPU_SSE),
+    form("STMXCSR", &[ M32 ], 0, &[ 0x0F, 0xAE ], Digit(3), 0, CPU_SSE),
+    form("FXSAVE", &[ Mem ], 0, &[ 0x0F, 0xAE ], Digit(0), 0, CPU_SSE),
+    form("FXRSTOR", &[ Mem ], 0, &[ 0x0F, 0xAE ], Digit(1), 0, CPU_SSE),
+    form("CLFLUSH", &[ M8 ], 0, &[ 0x0F, 0xAE ], Digit(7), 0, CPU_SSE2),
+    form("PREFETCHNTA", &[ M8 ], 0, &[ 0x0F, 0x18 ], Digit(0), 0, CPU_SSE),
+    form("PREFETCHT0", &[ M8 ], 0, &[ 0x0F, 0x18 ], Digit(1), 0, CPU_SSE),
+    form("PREFETCHT1", &[ M8 ], 0, &[ 0x0F, 0x18 ], Digit(2), 0, CPU_SSE),
+    form("PREFETCHT2", &[ M8 ], 0, &[ 0x0F, 0x18 ], Digit(3), 0, CPU_SSE),
+
+    form("ADDSUBPS", &[ Xmm, XmmM128 ], 0xF2, &[ 0x0F, 0xD0 ], ModRM, 0, CPU_SSE3),
+    form("ADDSUBPD", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0xD0 ], ModRM, 0, CPU_SSE3),
+    form("HADDPS", &[ Xmm, XmmM128 ], 0xF2, &[ 0x0F, 0x7C ], ModRM, 0, CPU_SSE3),
+    form("HADDPD", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x7C ], ModRM, 0, CPU_SSE3),
+    form("HSUBPS", &[ Xmm, XmmM128 ], 0xF2, &[ 0x0F, 0x7D ], ModRM, 0, CPU_SSE3),
+    form("HSUBPD", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x7D ], ModRM, 0, CPU_SSE3),
+    form("MOVSLDUP", &[ Xmm, XmmM128 ], 0xF3, &[ 0x0F, 0x12 ], ModRM, 0, CPU_SSE3),
+    form("MOVSHDUP", &[ Xmm, XmmM128 ], 0xF3, &[ 0x0F, 0x16 ], ModRM, 0, CPU_SSE3),
+    form("MOVDDUP", &[ Xmm, XmmM64 ], 0xF2, &[ 0x0F, 0x12 ], ModRM, 0, CPU_SSE3),
+    form("LDDQU", &[ Xmm, M128 ], 0xF2, &[ 0x0F, 0xF0 ], ModRM, 0, CPU_SSE3),
+    form("MONITOR", &[  ], 0, &[ 0x0F, 0x01, 0xC8 ], Opcode, 0, CPU_SSE3),
+    form("MWAIT", &[  ], 0, &[ 0x0F, 0x01, 0xC9 ], Opcode, 0, CPU_SSE3),
+    form("FISTTP", &[ M16 ], 0, &[ 0xDF ], Digit(1), 0, CPU_SSE3),
+    form("FISTTP", &[ M32 ], 0, &[ 0xDB ], Digit(1), 0, CPU_SSE3),
+    form("FISTTP", &[ M64 ], 0, &[ 0xDD ], Digit(1), 0, CPU_SSE3),
+
+    form("FNOP", &[  ], 0, &[ 0xD9, 0xD0 ], Opcode, 0, CPU_FPU),
+    form("FCHS", &[  ], 0, &[ 0xD9, 0xE0 ], Opcode, 0, CPU_FPU),
+    form("FABS", &[  ], 0, &[ 0xD9, 0xE1 ], Opcode, 0, CPU_FPU),
+    form("FTST", &[  ], 0, &[ 0xD9, 0xE4 ], Opcode, 0, CPU_FPU),
+    form("FXAM", &[  ], 0, &[ 0xD9, 0xE5 ], Opcode, 0, CPU_FPU),
+    form("FLD1", &[  ], 0, &[ 0xD9, 0xE8 ], Opcode, 0, CPU_FPU),
+    form("FLDL2T", &[  ], 0, &[ 0xD9, 0xE9 ], Opcode, 0, CPU_FPU),
+    form("FLDL2E", &[  ], 0, &[ 0xD9, 0xEA ], Opcode, 0, CPU_FPU),
+    form("FLDPI", &[  ], 0, &[ 0xD9, 0xEB ], Opcode, 0, CPU_FPU),
+    form("FLDLG2", &[  ], 0, &[ 0xD9, 0xEC ], Opcode, 0, CPU_FPU),
+    form("FLDLN2", &[  ], 0, &[ 0xD9, 0xED ], Opcode, 0, CPU_FPU),
+    form("FLDZ", &[  ], 0, &[ 0xD9, 0xEE ], Opcode, 0, CPU_FPU),
+    form("F2XM1", &[  ], 0, &[ 0xD9, 0xF0 ], Opcode, 0, CPU_FPU),
+    form("FYL2X", &[  ], 0, &[ 0xD9, 0xF1 ], Opcode, 0, CPU_FPU),
+    form("FPTAN", &[  ], 0, &[ 0xD9, 0xF2 ], Opcode, 0, CPU_FPU),
+    form("FPATAN", &[  ], 0, &[ 0xD9, 0xF3 ], Opcode, 0, CPU_FPU),
+    form("FXTRACT", &[  ], 0, &[ 0xD9, 0xF4 ], Opcode, 0, CPU_FPU),
+    form("FPREM1", &[  ], 0, &[ 0xD9, 0xF5 ], Opcode, 0, CPU_FPU),
+    form("FDECSTP", &[  ], 0, &[ 0xD9, 0xF6 ], Opcode, 0, CPU_FPU),
+    form("FINCSTP", &[  ], 0, &[ 0xD9, 0xF7 ], Opcode, 0, CPU_FPU),
+    form("FPREM", &[  ], 0, &[ 0xD9, 0xF8 ], Opcode, 0, CPU_FPU),
+    form("FYL2XP1", &[  ], 0, &[ 0xD9, 0xF9 ], Opcode, 0, CPU_FPU),
+    form("FSQRT", &[  ], 0, &[ 0xD9, 0xFA ], Opcode, 0, CPU_FPU),
+    form("FSINCOS", &[  ], 0, &[ 0xD9, 0xFB ], Opcode, 0, CPU_FPU),
+    form("FRNDINT", &[  ], 0, &[ 0xD9, 0xFC ], Opcode, 0, CPU_FPU),
+    form("FSCALE", &[  ], 0, &[ 0xD9, 0xFD ], Opcode, 0, CPU_FPU),
+    form("FSIN", &[  ], 0, &[ 0xD9, 0xFE ], Opcode, 0, CPU_FPU),
+    form("FCOS", &[  ], 0, &[ 0xD9, 0xFF ], Opcode, 0, CPU_FPU),
+    form("FCOMPP", &[  ], 0, &[ 0xDE, 0xD9 ], Opcode, 0, CPU_FPU),
+    form("FUCOMPP", &[  ], 0, &[ 0xDA, 0xE9 ], Opcode, 0, CPU_FPU),
+    form("FNINIT", &[  ], 0, &[ 0xDB, 0xE3 ], Opcode, 0, CPU_FPU),
+    form("FNCLEX", &[  ], 0, &[ 0xDB, 0xE2 ], Opcode, 0, CPU_FPU),
+    form("FWAIT", &[  ], 0, &[ 0x9B ], Opcode, 0, CPU_FPU),
+    form("WAIT", &[  ], 0, &[ 0x9B ], Opcode, 0, CPU_FPU),
+    form("FXCH", &[  ], 0, &[ 0xD9, 0xC9 ], Opcode, 0, CPU_FPU),
+    form("FADDP", &[  ], 0, &[ 0xDE, 0xC1 ], Opcode, 0, CPU_FPU),
+    form("FMULP", &[  ], 0, &[ 0xDE, 0xC9 ], Opcode, 0, CPU_FPU),
+    form("FSUBP", &[  ], 0, &[ 0xDE, 0xE9 ], Opcode, 0, CPU_FPU),
+    form("FSUBRP", &[  ], 0, &[ 0xDE, 0xE1 ], Opcode, 0, CPU_FPU),
+    form("FDIVP", &[  ], 0, &[ 0xDE, 0xF9 ], Opcode, 0, CPU_FPU),
+    form("FDIVRP", &[  ], 0, &[ 0xDE, 0xF1 ], Opcode, 0, CPU_FPU),
+    form("FINIT", &[  ], 0x9B, &[ 0xDB, 0xE3 ], Opcode, 0, CPU_FPU),
+    form("FCLEX", &[  ], 0x9B, &[ 0xDB, 0xE2 ], Opcode, 0, CPU_FPU),
+    form("FLD", &[ M32 ], 0, &[ 0xD9 ], Digit(0), 0, CPU_FPU),
+    form("FLD", &[ M64 ], 0, &[ 0xDD ], Digit(0), 0, CPU_FPU),
+    form("FLD", &[ M80 ], 0, &[ 0xDB ], Digit(5), 0, CPU_FPU),
+    form("FLD", &[ St ], 0, &[ 0xD9, 0xC0 ], PlusReg, 0, CPU_FPU),
+    form("FST", &[ M32 ], 0, &[ 0xD9 ], Digit(2), 0, CPU_FPU),
+    form("FST", &[ M64 ], 0, &[ 0xDD ], Digit(2), 0, CPU_FPU),
+    form("FST", &[ St ], 0, &[ 0xDD, 0xD0 ], PlusReg, 0, CPU_FPU),
+    form("FSTP", &[ M32 ], 0, &[ 0xD9 ], Digit(3), 0, CPU_FPU),
+    form("FSTP", &[ M64 ], 0, &[ 0xDD ], Digit(3), 0, CPU_FPU),
+    form("FSTP", &[ M80 ], 0, &[ 0xDB ], Digit(7), 0, CPU_FPU),
+    form("FSTP", &[ St ], 0, &[ 0xDD, 0xD8 ], PlusReg, 0, CPU_FPU),
+    form("FILD", &[ M16 ], 0, &[ 0xDF ], Digit(0), 0, CPU_FPU),
+    form("FILD", &[ M32 ], 0, &[ 0xDB ], Digit(0), 0, CPU_FPU),
+    form("FILD", &[ M64 ], 0, &[ 0xDF ], Digit(5), 0, CPU_FPU),
+    form("FIST", &[ M16 ], 0, &[ 0xDF ], Digit(2), 0, CPU_FPU),
+    form("FIST", &[ M32 ], 0, &[ 0xDB ], Digit(2), 0, CPU_FPU),
+    form("FISTP", &[ M16 ], 0, &[ 0xDF ], Digit(3), 0, CPU_FPU),
+    form("FISTP", &[ M32 ], 0, &[ 0xDB ], Digit(3), 0, CPU_FPU),
+    form("FISTP", &[ M64 ], 0, &[ 0xDF ], Digit(7), 0, CPU_FPU),
+    form("FADD", &[ M32 ], 0, &[ 0xD8 ], Digit(0), 0, CPU_FPU),
+    form("FADD", &[ M64 ], 0, &[ 0xDC ], Digit(0), 0, CPU_FPU),
+    form("FADD", &[ St0, St ], 0, &[ 0xD8, 0xC0 ], PlusReg, 0, CPU_FPU),
+    form("FADD", &[ St, St0 ], 0, &[ 0xDC, 0xC0 ], PlusReg, 0, CPU_FPU),
+    form("FMUL", &[ M32 ], 0, &[ 0xD8 ], Digit(1), 0, CPU_FPU),
+    form("FMUL", &[ M64 ], 0, &[ 0xDC ], Digit(1), 0, CPU_FPU),
+    form("FMUL", &[ St0, St ], 0, &[ 0xD8, 0xC8 ], PlusReg, 0, CPU_FPU),
+    form("FMUL", &[ St, St0 ], 0, &[ 0xDC, 0xC8 ], PlusReg, 0, CPU_FPU),
+    form("FCOM", &[ M32 ], 0, &[ 0xD8 ], Digit(2), 0, CPU_FPU),
+    form("FCOM", &[ M64 ], 0, &[ 0xDC ], Digit(2), 0, CPU_FPU),
+    form("FCOM", &[ St0, St ], 0, &[ 0xD8, 0xD0 ], PlusReg, 0, CPU_FPU),
+    form("FCOM", &[ St ], 0, &[ 0xD8, 0xD0 ], PlusReg, 0, CPU_FPU),
+    form("FCOMP", &[ M32 ], 0, &[ 0xD8 ], Digit(3), 0, CPU_FPU),
+    form("FCOMP", &[ M64 ], 0, &[ 0xDC ], Digit(3), 0, CPU_FPU),
+    form("FCOMP", &[ St0, St ], 0, &[ 0xD8, 0xD8 ], PlusReg, 0, CPU_FPU),
+    form("FCOMP", &[ St ], 0, &[ 0xD8, 0xD8 ], PlusReg, 0, CPU_FPU),
+    form("FSUB", &[ M32 ], 0, &[ 0xD8 ], Digit(4), 0, CPU_FPU),
+    form("FSUB", &[ M64 ], 0, &[ 0xDC ], Digit(4), 0, CPU_FPU),
+    form("FSUB", &[ St0, St ], 0, &[ 0xD8, 0xE0 ], PlusReg, 0, CPU_FPU),
+    form("FSUB", &[ St, St0 ], 0, &[ 0xDC, 0xE8 ], PlusReg, 0, CPU_FPU),
+    form("FSUBR", &[ M32 ], 0, &[ 0xD8 ], Digit(5), 0, CPU_FPU),
+    form("FSUBR", &[ M64 ], 0, &[ 0xDC ], Digit(5), 0, CPU_FPU),
+    form("FSUBR", &[ St0, St ], 0, &[ 0xD8, 0xE8 ], PlusReg, 0, CPU_FPU),
+    form("FSUBR", &[ St, St0 ], 0, &[ 0xDC, 0xE0 ], PlusReg, 0, CPU_FPU),
+    form("FDIV", &[ M32 ], 0, &[ 0xD8 ], Digit(6), 0, CPU_FPU),
+    form("FDIV", &[ M64 ], 0, &[ 0xDC ], Digit(6), 0, CPU_FPU),
+    form("FDIV", &[ St0, St ], 0, &[ 0xD8, 0xF0 ], PlusReg, 0, CPU_FPU),
+    form("FDIV", &[ St, St0 ], 0, &[ 0xDC, 0xF8 ], PlusReg, 0, CPU_FPU),
+    form("FDIVR", &[ M32 ], 0, &[ 0xD8 ], Digit(7), 0, CPU_FPU),
+    form("FDIVR", &[ M64 ], 0, &[ 0xDC ], Digit(7), 0, CPU_FPU),
+    form("FDIVR", &[ St0, St ], 0, &[ 0xD8, 0xF8 ], PlusReg, 0, CPU_FPU),
+    form("FDIVR", &[ St, St0 ], 0, &[ 0xDC, 0xF0 ], PlusReg, 0, CPU_FPU),
+    form("FADDP", &[ St, St0 ], 0, &[ 0xDE, 0xC0 ], PlusReg, 0, CPU_FPU),
+    form("FMULP", &[ St, St0 ], 0, &[ 0xDE, 0xC8 ], PlusReg, 0, CPU_FPU),
+    form("FSUBP", &[ St, St0 ], 0, &[ 0xDE, 0xE8 ], PlusReg, 0, CPU_FPU),
+    form("FSUBRP", &[ St, St0 ], 0, &[ 0xDE, 0xE0 ], PlusReg, 0, CPU_FPU),
+    form("FDIVP", &[ St, St0 ], 0, &[ 0xDE, 0xF8 ], PlusReg, 0, CPU_FPU),
+    form("FDIVRP", &[ St, St0 ], 0, &[ 0xDE, 0xF0 ], PlusReg, 0, CPU_FPU),
+    form("FIADD", &[ M32 ], 0, &[ 0xDA ], Digit(0), 0, CPU_FPU),
+    form("FIADD", &[ M16 ], 0, &[ 0xDE ], Digit(0), 0, CPU_FPU),
+    form("FIMUL", &[ M32 ], 0, &[ 0xDA ], Digit(1), 0, CPU_FPU),
+    form("FIMUL", &[ M16 ], 0, &[ 0xDE ], Digit(1), 0, CPU_FPU),
+    form("FISUB", &[ M32 ], 0, &[ 0xDA ], Digit(4), 0, CPU_FPU),
+    form("FISUB", &[ M16 ], 0, &[ 0xDE ], Digit(4), 0, CPU_FPU),
+    form("FIDIV", &[ M32 ], 0, &[ 0xDA ], Digit(6), 0, CPU_FPU),
+    form("FIDIV", &[ M16 ], 0, &[ 0xDE ], Digit(6), 0, CPU_FPU),
+    form("FXCH", &[ St ], 0, &[ 0xD9, 0xC8 ], PlusReg, 0, CPU_FPU),
+    form("FFREE", &[ St ], 0, &[ 0xDD, 0xC0 ], PlusReg, 0, CPU_FPU),
+    form("FUCOM", &[ St ], 0, &[ 0xDD, 0xE0 ], PlusReg, 0, CPU_FPU),
+    form("FUCOMP", &[ St ], 0, &[ 0xDD, 0xE8 ], PlusReg, 0, CPU_FPU),
+    form("FCOMI", &[ St0, St ], 0, &[ 0xDB, 0xF0 ], PlusReg, 0, CPU_P6),
+    form("FUCOMI", &[ St0, St ], 0, &[ 0xDB, 0xE8 ], PlusReg, 0, CPU_P6),
+    form("FCOMIP", &[ St0, St ], 0, &[ 0xDF, 0xF0 ], PlusReg, 0, CPU_P6),
+    form("FUCOMIP", &[ St0, St ], 0, &[ 0xDF, 0xE8 ], PlusReg, 0, CPU_P6),
+    form("FLDCW", &[ M16 ], 0, &[ 0xD9 ], Digit(5), 0, CPU_FPU),
+    form("FNSTCW", &[ M16 ], 0, &[ 0xD9 ], Digit(7), 0, CPU_FPU),
+    form("FSTCW", &[ M16 ], 0x9B, &[ 0xD9 ], Digit(7), 0, CPU_FPU),
+    form("FNSTSW", &[ M16 ], 0, &[ 0xDD ], Digit(7), 0, CPU_FPU),
+    form("FNSTSW", &[ Ax ], 0, &[ 0xDF, 0xE0 ], Opcode, 0, CPU_FPU),
+    form("FSTSW", &[ M16 ], 0x9B, &[ 0xDD ], Digit(7), 0, CPU_FPU),
+    form("FSTSW", &[ Ax ], 0x9B, &[ 0xDF, 0xE0 ], Opcode, 0, CPU_FPU),
+    form("FLDENV", &[ Mem ], 0, &[ 0xD9 ], Digit(4), 0, CPU_FPU),
+    form("FNSTENV", &[ Mem ], 0, &[ 0xD9 ], Digit(6), 0, CPU_FPU),
+    form("FRSTOR", &[ Mem ], 0, &[ 0xDD ], Digit(4), 0, CPU_FPU),
+    form("FNSAVE", &[ Mem ], 0, &[ 0xDD ], Digit(6), 0, CPU_FPU),
+    vex("VZEROUPPER", &[  ], 0, &[ 0x0F, 0x77 ], Opcode, 0, CPU_AVX),
+    vex("VZEROALL", &[  ], 0, &[ 0x0F, 0x77 ], Opcode, 256, CPU_AVX),
+    vex("VADDPS", &[ Xmm, Xmm, XmmM128 ], 0, &[ 0x0F, 0x58 ], ModRMV, 0, CPU_AVX),
+    vex("VADDPS", &[ Ymm, Ymm, YmmM256 ], 0, &[ 0x0F, 0x58 ], ModRMV, 0, CPU_AVX),
+    vex("VADDPD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x58 ], ModRMV, 0, CPU_AVX),
+    vex("VADDPD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x58 ], ModRMV, 0, CPU_AVX),
+    vex("VADDSS", &[ Xmm, Xmm, XmmM32 ], 0xF3, &[ 0x0F, 0x58 ], ModRMV, 0, CPU_AVX),
+    vex("VADDSD", &[ Xmm, Xmm, XmmM64 ], 0xF2, &[ 0x0F, 0x58 ], ModRMV, 0, CPU_AVX),
+    vex("VMULPS", &[ Xmm, Xmm, XmmM128 ], 0, &[ 0x0F, 0x59 ], ModRMV, 0, CPU_AVX),
+    vex("VMULPS", &[ Ymm, Ymm, YmmM256 ], 0, &[ 0x0F, 0x59 ], ModRMV, 0, CPU_AVX),
+    vex("VMULPD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x59 ], ModRMV, 0, CPU_AVX),
+    vex("VMULPD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x59 ], ModRMV, 0, CPU_AVX),
+    vex("VMULSS", &[ Xmm, Xmm, XmmM32 ], 0xF3, &[ 0x0F, 0x59 ], ModRMV, 0, CPU_AVX),
+    vex("VMULSD", &[ Xmm, Xmm, XmmM64 ], 0xF2, &[ 0x0F, 0x59 ], ModRMV, 0, CPU_AVX),
+    vex("VSUBPS", &[ Xmm, Xmm, XmmM128 ], 0, &[ 0x0F, 0x5C ], ModRMV, 0, CPU_AVX),
+    vex("VSUBPS", &[ Ymm, Ymm, YmmM256 ], 0, &[ 0x0F, 0x5C ], ModRMV, 0, CPU_AVX),
+    vex("VSUBPD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x5C ], ModRMV, 0, CPU_AVX),
+    vex("VSUBPD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x5C ], ModRMV, 0, CPU_AVX),
+    vex("VSUBSS", &[ Xmm, Xmm, XmmM32 ], 0xF3, &[ 0x0F, 0x5C ], ModRMV, 0, CPU_AVX),
+    vex("VSUBSD", &[ Xmm, Xmm, XmmM64 ], 0xF2, &[ 0x0F, 0x5C ], ModRMV, 0, CPU_AVX),
+    vex("VMINPS", &[ Xmm, Xmm, XmmM128 ], 0, &[ 0x0F, 0x5D ], ModRMV, 0, CPU_AVX),
+    vex("VMINPS", &[ Ymm, Ymm, YmmM256 ], 0, &[ 0x0F, 0x5D ], ModRMV, 0, CPU_AVX),
+    vex("VMINPD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x5D ], ModRMV, 0, CPU_AVX),
+    vex("VMINPD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x5D ], ModRMV, 0, CPU_AVX),
+    vex("VMINSS", &[ Xmm, Xmm, XmmM32 ], 0xF3, &[ 0x0F, 0x5D ], ModRMV, 0, CPU_AVX),
+    vex("VMINSD", &[ Xmm, Xmm, XmmM64 ], 0xF2, &[ 0x0F, 0x5D ], ModRMV, 0, CPU_AVX),
+    vex("VDIVPS", &[ Xmm, Xmm, XmmM128 ], 0, &[ 0x0F, 0x5E ], ModRMV, 0, CPU_AVX),
+    vex("VDIVPS", &[ Ymm, Ymm, YmmM256 ], 0, &[ 0x0F, 0x5E ], ModRMV, 0, CPU_AVX),
+    vex("VDIVPD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x5E ], ModRMV, 0, CPU_AVX),
+    vex("VDIVPD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x5E ], ModRMV, 0, CPU_AVX),
+    vex("VDIVSS", &[ Xmm, Xmm, XmmM32 ], 0xF3, &[ 0x0F, 0x5E ], ModRMV, 0, CPU_AVX),
+    vex("VDIVSD", &[ Xmm, Xmm, XmmM64 ], 0xF2, &[ 0x0F, 0x5E ], ModRMV, 0, CPU_AVX),
+    vex("VMAXPS", &[ Xmm, Xmm, XmmM128 ], 0, &[ 0x0F, 0x5F ], ModRMV, 0, CPU_AVX),
+    vex("VMAXPS", &[ Ymm, Ymm, YmmM256 ], 0, &[ 0x0F, 0x5F ], ModRMV, 0, CPU_AVX),
+    vex("VMAXPD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x5F ], ModRMV, 0, CPU_AVX),
+    vex("VMAXPD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x5F ], ModRMV, 0, CPU_AVX),
+    vex("VMAXSS", &[ Xmm, Xmm, XmmM32 ], 0xF3, &[ 0x0F, 0x5F ], ModRMV, 0, CPU_AVX),
+    vex("VMAXSD", &[ Xmm, Xmm, XmmM64 ], 0xF2, &[ 0x0F, 0x5F ], ModRMV, 0, CPU_AVX),
+    vex("VSQRTPS", &[ Xmm, XmmM128 ], 0, &[ 0x0F, 0x51 ], ModRM, 0, CPU_AVX),
+    vex("VSQRTPS", &[ Ymm, YmmM256 ], 0, &[ 0x0F, 0x51 ], ModRM, 0, CPU_AVX),
+    vex("VSQRTPD", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x51 ], ModRM, 0, CPU_AVX),
+    vex("VSQRTPD", &[ Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x51 ], ModRM, 0, CPU_AVX),
+    vex("VSQRTSS", &[ Xmm, Xmm, XmmM32 ], 0xF3, &[ 0x0F, 0x51 ], ModRMV, 0, CPU_AVX),
+    vex("VSQRTSD", &[ Xmm, Xmm, XmmM64 ], 0xF2, &[ 0x0F, 0x51 ], ModRMV, 0, CPU_AVX),
+    vex("VANDPS", &[ Xmm, Xmm, XmmM128 ], 0, &[ 0x0F, 0x54 ], ModRMV, 0, CPU_AVX),
+    vex("VANDPS", &[ Ymm, Ymm, YmmM256 ], 0, &[ 0x0F, 0x54 ], ModRMV, 0, CPU_AVX),
+    vex("VANDPD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x54 ], ModRMV, 0, CPU_AVX),
+    vex("VANDPD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x54 ], ModRMV, 0, CPU_AVX),
+    vex("VANDNPS", &[ Xmm, Xmm, XmmM128 ], 0, &[ 0x0F, 0x55 ], ModRMV, 0, CPU_AVX),
+    vex("VANDNPS", &[ Ymm, Ymm, YmmM256 ], 0, &[ 0x0F, 0x55 ], ModRMV, 0, CPU_AVX),
+    vex("VANDNPD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x55 ], ModRMV, 0, CPU_AVX),
+    vex("VANDNPD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x55 ], ModRMV, 0, CPU_AVX),
+    vex("VORPS", &[ Xmm, Xmm, XmmM128 ], 0, &[ 0x0F, 0x56 ], ModRMV, 0, CPU_AVX),
+    vex("VORPS", &[ Ymm, Ymm, YmmM256 ], 0, &[ 0x0F, 0x56 ], ModRMV, 0, CPU_AVX),
+    vex("VORPD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x56 ], ModRMV, 0, CPU_AVX),
+    vex("VORPD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x56 ], ModRMV, 0, CPU_AVX),
+    vex("VXORPS", &[ Xmm, Xmm, XmmM128 ], 0, &[ 0x0F, 0x57 ], ModRMV, 0, CPU_AVX),
+    vex("VXORPS", &[ Ymm, Ymm, YmmM256 ], 0, &[ 0x0F, 0x57 ], ModRMV, 0, CPU_AVX),
+    vex("VXORPD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x57 ], ModRMV, 0, CPU_AVX),
+    vex("VXORPD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x57 ], ModRMV, 0, CPU_AVX),
+    vex("VUNPCKLPS", &[ Xmm, Xmm, XmmM128 ], 0, &[ 0x0F, 0x14 ], ModRMV, 0, CPU_AVX),
+    vex("VUNPCKLPS", &[ Ymm, Ymm, YmmM256 ], 0, &[ 0x0F, 0x14 ], ModRMV, 0, CPU_AVX),
+    vex("VUNPCKLPD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x14 ], ModRMV, 0, CPU_AVX),
+    vex("VUNPCKLPD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x14 ], ModRMV, 0, CPU_AVX),
+    vex("VUNPCKHPS", &[ Xmm, Xmm, XmmM128 ], 0, &[ 0x0F, 0x15 ], ModRMV, 0, CPU_AVX),
+    vex("VUNPCKHPS", &[ Ymm, Ymm, YmmM256 ], 0, &[ 0x0F, 0x15 ], ModRMV, 0, CPU_AVX),
+    vex("VUNPCKHPD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x15 ], ModRMV, 0, CPU_AVX),
+    vex("VUNPCKHPD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x15 ], ModRMV, 0, CPU_AVX),
+    vex("VHADDPS", &[ Xmm, Xmm, XmmM128 ], 0xF2, &[ 0x0F, 0x7C ], ModRMV, 0, CPU_AVX),
+    vex("VHADDPS", &[ Ymm, Ymm, YmmM256 ], 0xF2, &[ 0x0F, 0x7C ], ModRMV, 0, CPU_AVX),
+    vex("VHADDPD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x7C ], ModRMV, 0, CPU_AVX),
+    vex("VHADDPD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x7C ], ModRMV, 0, CPU_AVX),
+    vex("VCMPPS", &[ Xmm, Xmm, XmmM128, I8 ], 0, &[ 0x0F, 0xC2 ], ModRMV, 0, CPU_AVX),
+    vex("VCMPPS", &[ Ymm, Ymm, YmmM256, I8 ], 0, &[ 0x0F, 0xC2 ], ModRMV, 0, CPU_AVX),
+    vex("VCMPPD", &[ Xmm, Xmm, XmmM128, I8 ], 0x66, &[ 0x0F, 0xC2 ], ModRMV, 0, CPU_AVX),
+    vex("VCMPPD", &[ Ymm, Ymm, YmmM256, I8 ], 0x66, &[ 0x0F, 0xC2 ], ModRMV, 0, CPU_AVX),
+    vex("VSHUFPS", &[ Xmm, Xmm, XmmM128, I8 ], 0, &[ 0x0F, 0xC6 ], ModRMV, 0, CPU_AVX),
+    vex("VSHUFPS", &[ Ymm, Ymm, YmmM256, I8 ], 0, &[ 0x0F, 0xC6 ], ModRMV, 0, CPU_AVX),
+    vex("VSHUFPD", &[ Xmm, Xmm, XmmM128, I8 ], 0x66, &[ 0x0F, 0xC6 ], ModRMV, 0, CPU_AVX),
+    vex("VSHUFPD", &[ Ymm, Ymm, YmmM256, I8 ], 0x66, &[ 0x0F, 0xC6 ], ModRMV, 0, CPU_AVX),
+    vex("VDPPS", &[ Xmm, Xmm, XmmM128, I8 ], 0x66, &[ 0x0F, 0x3A, 0x40 ], ModRMV, 0, CPU_AVX),
+    vex("VDPPS", &[ Ymm, Ymm, YmmM256, I8 ], 0x66, &[ 0x0F, 0x3A, 0x40 ], ModRMV, 0, CPU_AVX),
+    vex("VMOVAPS", &[ Xmm, XmmM128 ], 0, &[ 0x0F, 0x28 ], ModRM, 0, CPU_AVX),
+    vex("VMOVAPS", &[ XmmM128, Xmm ], 0, &[ 0x0F, 0x29 ], ModRM, 0, CPU_AVX),
+    vex("VMOVAPS", &[ Ymm, YmmM256 ], 0, &[ 0x0F, 0x28 ], ModRM, 0, CPU_AVX),
+    vex("VMOVAPS", &[ YmmM256, Ymm ], 0, &[ 0x0F, 0x29 ], ModRM, 0, CPU_AVX),
+    vex("VMOVAPD", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x28 ], ModRM, 0, CPU_AVX),
+    vex("VMOVAPD", &[ XmmM128, Xmm ], 0x66, &[ 0x0F, 0x29 ], ModRM, 0, CPU_AVX),
+    vex("VMOVAPD", &[ Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x28 ], ModRM, 0, CPU_AVX),
+    vex("VMOVAPD", &[ YmmM256, Ymm ], 0x66, &[ 0x0F, 0x29 ], ModRM, 0, CPU_AVX),
+    vex("VMOVUPS", &[ Xmm, XmmM128 ], 0, &[ 0x0F, 0x10 ], ModRM, 0, CPU_AVX),
+    vex("VMOVUPS", &[ XmmM128, Xmm ], 0, &[ 0x0F, 0x11 ], ModRM, 0, CPU_AVX),
+    vex("VMOVUPS", &[ Ymm, YmmM256 ], 0, &[ 0x0F, 0x10 ], ModRM, 0, CPU_AVX),
+    vex("VMOVUPS", &[ YmmM256, Ymm ], 0, &[ 0x0F, 0x11 ], ModRM, 0, CPU_AVX),
+    vex("VMOVUPD", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x10 ], ModRM, 0, CPU_AVX),
+    vex("VMOVUPD", &[ XmmM128, Xmm ], 0x66, &[ 0x0F, 0x11 ], ModRM, 0, CPU_AVX),
+    vex("VMOVUPD", &[ Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x10 ], ModRM, 0, CPU_AVX),
+    vex("VMOVUPD", &[ YmmM256, Ymm ], 0x66, &[ 0x0F, 0x11 ], ModRM, 0, CPU_AVX),
+    vex("VMOVDQA", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x6F ], ModRM, 0, CPU_AVX),
+    vex("VMOVDQA", &[ XmmM128, Xmm ], 0x66, &[ 0x0F, 0x7F ], ModRM, 0, CPU_AVX),
+    vex("VMOVDQA", &[ Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x6F ], ModRM, 0, CPU_AVX),
+    vex("VMOVDQA", &[ YmmM256, Ymm ], 0x66, &[ 0x0F, 0x7F ], ModRM, 0, CPU_AVX),
+    vex("VMOVDQU", &[ Xmm, XmmM128 ], 0xF3, &[ 0x0F, 0x6F ], ModRM, 0, CPU_AVX),
+    vex("VMOVDQU", &[ XmmM128, Xmm ], 0xF3, &[ 0x0F, 0x7F ], ModRM, 0, CPU_AVX),
+    vex("VMOVDQU", &[ Ymm, YmmM256 ], 0xF3, &[ 0x0F, 0x6F ], ModRM, 0, CPU_AVX),
+    vex("VMOVDQU", &[ YmmM256, Ymm ], 0xF3, &[ 0x0F, 0x7F ], ModRM, 0, CPU_AVX),
+    vex("VMOVSS", &[ Xmm, M32 ], 0xF3, &[ 0x0F, 0x10 ], ModRM, 0, CPU_AVX),
+    vex("VMOVSS", &[ M32, Xmm ], 0xF3, &[ 0x0F, 0x11 ], ModRM, 0, CPU_AVX),
+    vex("VMOVSS", &[ Xmm, Xmm, XmmB ], 0xF3, &[ 0x0F, 0x10 ], ModRMV, 0, CPU_AVX),
+    vex("VMOVSD", &[ Xmm, M64 ], 0xF2, &[ 0x0F, 0x10 ], ModRM, 0, CPU_AVX),
+    vex("VMOVSD", &[ M64, Xmm ], 0xF2, &[ 0x0F, 0x11 ], ModRM, 0, CPU_AVX),
+    vex("VMOVSD", &[ Xmm, Xmm, XmmB ], 0xF2, &[ 0x0F, 0x10 ], ModRMV, 0, CPU_AVX),
+    vex("VCVTDQ2PS", &[ Xmm, XmmM128 ], 0, &[ 0x0F, 0x5B ], ModRM, 0, CPU_AVX),
+    vex("VCVTDQ2PS", &[ Ymm, YmmM256 ], 0, &[ 0x0F, 0x5B ], ModRM, 0, CPU_AVX),
+    vex("VCVTPS2DQ", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x5B ], ModRM, 0, CPU_AVX),
+    vex("VCVTPS2DQ", &[ Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x5B ], ModRM, 0, CPU_AVX),
+    vex("VCVTTPS2DQ", &[ Xmm, XmmM128 ], 0xF3, &[ 0x0F, 0x5B ], ModRM, 0, CPU_AVX),
+    vex("VCVTTPS2DQ", &[ Ymm, YmmM256 ], 0xF3, &[ 0x0F, 0x5B ], ModRM, 0, CPU_AVX),
+    vex("VBROADCASTSS", &[ Xmm, M32 ], 0x66, &[ 0x0F, 0x38, 0x18 ], ModRM, 0, CPU_AVX),
+    vex("VBROADCASTSS", &[ Ymm, M32 ], 0x66, &[ 0x0F, 0x38, 0x18 ], ModRM, 0, CPU_AVX),
+    vex("VBROADCASTSS", &[ Xmm, XmmB ], 0x66, &[ 0x0F, 0x38, 0x18 ], ModRM, 0, CPU_AVX2),
+    vex("VBROADCASTSS", &[ Ymm, XmmB ], 0x66, &[ 0x0F, 0x38, 0x18 ], ModRM, 0, CPU_AVX2),
+    vex("VBROADCASTSD", &[ Ymm, M64 ], 0x66, &[ 0x0F, 0x38, 0x19 ], ModRM, 0, CPU_AVX),
+    vex("VBROADCASTSD", &[ Ymm, XmmB ], 0x66, &[ 0x0F, 0x38, 0x19 ], ModRM, 0, CPU_AVX2),
+    vex("VBROADCASTF128", &[ Ymm, M128 ], 0x66, &[ 0x0F, 0x38, 0x1A ], ModRM, 0, CPU_AVX),
+    vex("VINSERTF128", &[ Ymm, Ymm, XmmM128, I8 ], 0x66, &[ 0x0F, 0x3A, 0x18 ], ModRMV, 0, CPU_AVX),
+    vex("VEXTRACTF128", &[ XmmM128, Ymm, I8 ], 0x66, &[ 0x0F, 0x3A, 0x19 ], ModRM, 0, CPU_AVX),
+    vex("VPERM2F128", &[ Ymm, Ymm, YmmM256, I8 ], 0x66, &[ 0x0F, 0x3A, 0x06 ], ModRMV, 0, CPU_AVX),
+
+    vex("VPADDD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0xFE ], ModRMV, 0, CPU_AVX),
+    vex("VPADDD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0xFE ], ModRMV, 0, CPU_AVX2),
+    vex("VPADDQ", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0xD4 ], ModRMV, 0, CPU_AVX),
+    vex("VPADDQ", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0xD4 ], ModRMV, 0, CPU_AVX2),
+    vex("VPSUBD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0xFA ], ModRMV, 0, CPU_AVX),
+    vex("VPSUBD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0xFA ], ModRMV, 0, CPU_AVX2),
+    vex("VPSUBQ", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0xFB ], ModRMV, 0, CPU_AVX),
+    vex("VPSUBQ", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0xFB ], ModRMV, 0, CPU_AVX2),
+    vex("VPMULLD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0x40 ], ModRMV, 0, CPU_AVX),
+    vex("VPMULLD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0x40 ], ModRMV, 0, CPU_AVX2),
+    vex("VPCMPEQD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x76 ], ModRMV, 0, CPU_AVX),
+    vex("VPCMPEQD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x76 ], ModRMV, 0, CPU_AVX2),
+    vex("VPAND", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0xDB ], ModRMV, 0, CPU_AVX),
+    vex("VPAND", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0xDB ], ModRMV, 0, CPU_AVX2),
+    vex("VPANDN", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0xDF ], ModRMV, 0, CPU_AVX),
+    vex("VPANDN", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0xDF ], ModRMV, 0, CPU_AVX2),
+    vex("VPOR", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0xEB ], ModRMV, 0, CPU_AVX),
+    vex("VPOR", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0xEB ], ModRMV, 0, CPU_AVX2),
+    vex("VPXOR", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0xEF ], ModRMV, 0, CPU_AVX),
+    vex("VPXOR", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0xEF ], ModRMV, 0, CPU_AVX2),
+    vex("VPBROADCASTD", &[ Xmm, XmmM32 ], 0x66, &[ 0x0F, 0x38, 0x58 ], ModRM, 0, CPU_AVX2),
+    vex("VPBROADCASTD", &[ Ymm, XmmM32 ], 0x66, &[ 0x0F, 0x38, 0x58 ], ModRM, 0, CPU_AVX2),
+    vex("VPBROADCASTQ", &[ Xmm, XmmM64 ], 0x66, &[ 0x0F, 0x38, 0x59 ], ModRM, 0, CPU_AVX2),
+    vex("VPBROADCASTQ", &[ Ymm, XmmM64 ], 0x66, &[ 0x0F, 0x38, 0x59 ], ModRM, 0, CPU_AVX2),
+    vex("VPERMD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0x36 ], ModRMV, 0, CPU_AVX2),
+    vex("VPERMPS", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0x16 ], ModRMV, 0, CPU_AVX2),
+    vex("VPERMQ", &[ Ymm, YmmM256, I8 ], 0x66, &[ 0x0F, 0x3A, 0x00 ], ModRM, 64, CPU_AVX2),
+    vex("VPERMPD", &[ Ymm, YmmM256, I8 ], 0x66, &[ 0x0F, 0x3A, 0x01 ], ModRM, 64, CPU_AVX2),
+
+    vex("VFMADD132PS", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0x98 ], ModRMV, 0, CPU_FMA),
+    vex("VFMADD132PS", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0x98 ], ModRMV, 0, CPU_FMA),
+    vex("VFMADD132PD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0x98 ], ModRMV, 64, CPU_FMA),
+    vex("VFMADD132PD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0x98 ], ModRMV, 64, CPU_FMA),
+    vex("VFMADD132SS", &[ Xmm, Xmm, XmmM32 ], 0x66, &[ 0x0F, 0x38, 0x99 ], ModRMV, 0, CPU_FMA),
+    vex("VFMADD132SD", &[ Xmm, Xmm, XmmM64 ], 0x66, &[ 0x0F, 0x38, 0x99 ], ModRMV, 64, CPU_FMA),
+    vex("VFMADD213PS", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0xA8 ], ModRMV, 0, CPU_FMA),
+    vex("VFMADD213PS", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0xA8 ], ModRMV, 0, CPU_FMA),
+    vex("VFMADD213PD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0xA8 ], ModRMV, 64, CPU_FMA),
+    vex("VFMADD213PD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0xA8 ], ModRMV, 64, CPU_FMA),
+    vex("VFMADD213SS", &[ Xmm, Xmm, XmmM32 ], 0x66, &[ 0x0F, 0x38, 0xA9 ], ModRMV, 0, CPU_FMA),
+    vex("VFMADD213SD", &[ Xmm, Xmm, XmmM64 ], 0x66, &[ 0x0F, 0x38, 0xA9 ], ModRMV, 64, CPU_FMA),
+    vex("VFMADD231PS", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0xB8 ], ModRMV, 0, CPU_FMA),
+    vex("VFMADD231PS", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0xB8 ], ModRMV, 0, CPU_FMA),
+    vex("VFMADD231PD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0xB8 ], ModRMV, 64, CPU_FMA),
+    vex("VFMADD231PD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0xB8 ], ModRMV, 64, CPU_FMA),
+    vex("VFMADD231SS", &[ Xmm, Xmm, XmmM32 ], 0x66, &[ 0x0F, 0x38, 0xB9 ], ModRMV, 0, CPU_FMA),
+    vex("VFMADD231SD", &[ Xmm, Xmm, XmmM64 ], 0x66, &[ 0x0F, 0x38, 0xB9 ], ModRMV, 64, CPU_FMA),
+    vex("VFMSUB132PS", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0x9A ], ModRMV, 0, CPU_FMA),
+    vex("VFMSUB132PS", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0x9A ], ModRMV, 0, CPU_FMA),
+    vex("VFMSUB132PD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0x9A ], ModRMV, 64, CPU_FMA),
+    vex("VFMSUB132PD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0x9A ], ModRMV, 64, CPU_FMA),
+    vex("VFMSUB132SS", &[ Xmm, Xmm, XmmM32 ], 0x66, &[ 0x0F, 0x38, 0x9B ], ModRMV, 0, CPU_FMA),
+    vex("VFMSUB132SD", &[ Xmm, Xmm, XmmM64 ], 0x66, &[ 0x0F, 0x38, 0x9B ], ModRMV, 64, CPU_FMA),
+    vex("VFMSUB213PS", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0xAA ], ModRMV, 0, CPU_FMA),
+    vex("VFMSUB213PS", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0xAA ], ModRMV, 0, CPU_FMA),
+    vex("VFMSUB213PD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0xAA ], ModRMV, 64, CPU_FMA),
+    vex("VFMSUB213PD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0xAA ], ModRMV, 64, CPU_FMA),
+    vex("VFMSUB213SS", &[ Xmm, Xmm, XmmM32 ], 0x66, &[ 0x0F, 0x38, 0xAB ], ModRMV, 0, CPU_FMA),
+    vex("VFMSUB213SD", &[ Xmm, Xmm, XmmM64 ], 0x66, &[ 0x0F, 0x38, 0xAB ], ModRMV, 64, CPU_FMA),
+    vex("VFMSUB231PS", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0xBA ], ModRMV, 0, CPU_FMA),
+    vex("VFMSUB231PS", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0xBA ], ModRMV, 0, CPU_FMA),
+    vex("VFMSUB231PD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0xBA ], ModRMV, 64, CPU_FMA),
+    vex("VFMSUB231PD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0xBA ], ModRMV, 64, CPU_FMA),
+    vex("VFMSUB231SS", &[ Xmm, Xmm, XmmM32 ], 0x66, &[ 0x0F, 0x38, 0xBB ], ModRMV, 0, CPU_FMA),
+    vex("VFMSUB231SD", &[ Xmm, Xmm, XmmM64 ], 0x66, &[ 0x0F, 0x38, 0xBB ], ModRMV, 64, CPU_FMA),
+    vex("VFNMADD132PS", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0x9C ], ModRMV, 0, CPU_FMA),
+    vex("VFNMADD132PS", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0x9C ], ModRMV, 0, CPU_FMA),
+    vex("VFNMADD132PD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0x9C ], ModRMV, 64, CPU_FMA),
+    vex("VFNMADD132PD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0x9C ], ModRMV, 64, CPU_FMA),
+    vex("VFNMADD132SS", &[ Xmm, Xmm, XmmM32 ], 0x66, &[ 0x0F, 0x38, 0x9D ], ModRMV, 0, CPU_FMA),
+    vex("VFNMADD132SD", &[ Xmm, Xmm, XmmM64 ], 0x66, &[ 0x0F, 0x38, 0x9D ], ModRMV, 64, CPU_FMA),
+    vex("VFNMADD213PS", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0xAC ], ModRMV, 0, CPU_FMA),
+    vex("VFNMADD213PS", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0xAC ], ModRMV, 0, CPU_FMA),
+    vex("VFNMADD213PD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0xAC ], ModRMV, 64, CPU_FMA),
+    vex("VFNMADD213PD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0xAC ], ModRMV, 64, CPU_FMA),
+    vex("VFNMADD213SS", &[ Xmm, Xmm, XmmM32 ], 0x66, &[ 0x0F, 0x38, 0xAD ], ModRMV, 0, CPU_FMA),
+    vex("VFNMADD213SD", &[ Xmm, Xmm, XmmM64 ], 0x66, &[ 0x0F, 0x38, 0xAD ], ModRMV, 64, CPU_FMA),
+    vex("VFNMADD231PS", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0xBC ], ModRMV, 0, CPU_FMA),
+    vex("VFNMADD231PS", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0xBC ], ModRMV, 0, CPU_FMA),
+    vex("VFNMADD231PD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0xBC ], ModRMV, 64, CPU_FMA),
+    vex("VFNMADD231PD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0xBC ], ModRMV, 64, CPU_FMA),
+    vex("VFNMADD231SS", &[ Xmm, Xmm, XmmM32 ], 0x66, &[ 0x0F, 0x38, 0xBD ], ModRMV, 0, CPU_FMA),
+    vex("VFNMADD231SD", &[ Xmm, Xmm, XmmM64 ], 0x66, &[ 0x0F, 0x38, 0xBD ], ModRMV, 64, CPU_FMA),
+    vex("VFNMSUB132PS", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0x9E ], ModRMV, 0, CPU_FMA),
+    vex("VFNMSUB132PS", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0x9E ], ModRMV, 0, CPU_FMA),
+    vex("VFNMSUB132PD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0x9E ], ModRMV, 64, CPU_FMA),
+    vex("VFNMSUB132PD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0x9E ], ModRMV, 64, CPU_FMA),
+    vex("VFNMSUB132SS", &[ Xmm, Xmm, XmmM32 ], 0x66, &[ 0x0F, 0x38, 0x9F ], ModRMV, 0, CPU_FMA),
+    vex("VFNMSUB132SD", &[ Xmm, Xmm, XmmM64 ], 0x66, &[ 0x0F, 0x38, 0x9F ], ModRMV, 64, CPU_FMA),
+    vex("VFNMSUB213PS", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0xAE ], ModRMV, 0, CPU_FMA),
+    vex("VFNMSUB213PS", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0xAE ], ModRMV, 0, CPU_FMA),
+    vex("VFNMSUB213PD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0xAE ], ModRMV, 64, CPU_FMA),
+    vex("VFNMSUB213PD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0xAE ], ModRMV, 64, CPU_FMA),
+    vex("VFNMSUB213SS", &[ Xmm, Xmm, XmmM32 ], 0x66, &[ 0x0F, 0x38, 0xAF ], ModRMV, 0, CPU_FMA),
+    vex("VFNMSUB213SD", &[ Xmm, Xmm, XmmM64 ], 0x66, &[ 0x0F, 0x38, 0xAF ], ModRMV, 64, CPU_FMA),
+    vex("VFNMSUB231PS", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0xBE ], ModRMV, 0, CPU_FMA),
+    vex("VFNMSUB231PS", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0xBE ], ModRMV, 0, CPU_FMA),
+    vex("VFNMSUB231PD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0xBE ], ModRMV, 64, CPU_FMA),
+    vex("VFNMSUB231PD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0xBE ], ModRMV, 64, CPU_FMA),
+    vex("VFNMSUB231SS", &[ Xmm, Xmm, XmmM32 ], 0x66, &[ 0x0F, 0x38, 0xBF ], ModRMV, 0, CPU_FMA),
+    vex("VFNMSUB231SD", &[ Xmm, Xmm, XmmM64 ], 0x66, &[ 0x0F, 0x38, 0xBF ], ModRMV, 64, CPU_FMA),
+
+    vex("KMOVW", &[ K, KM16 ], 0, &[ 0x0F, 0x90 ], ModRM, 0, CPU_AVX512),
+    vex("KMOVW", &[ M16, K ], 0, &[ 0x0F, 0x91 ], ModRM, 0, CPU_AVX512),
+    vex("KMOVW", &[ K, B32 ], 0, &[ 0x0F, 0x92 ], ModRM, 0, CPU_AVX512),
+    vex("KMOVW", &[ R32, KB ], 0, &[ 0x0F, 0x93 ], ModRM, 0, CPU_AVX512),
+    vex("KNOTW", &[ K, KB ], 0, &[ 0x0F, 0x44 ], ModRM, 0, CPU_AVX512),
+    vex("KORTESTW", &[ K, KB ], 0, &[ 0x0F, 0x98 ], ModRM, 0, CPU_AVX512),
+    vex("KANDW", &[ K, K, KB ], 0, &[ 0x0F, 0x41 ], ModRMV, 256, CPU_AVX512),
+    vex("KANDNW", &[ K, K, KB ], 0, &[ 0x0F, 0x42 ], ModRMV, 256, CPU_AVX512),
+    vex("KORW", &[ K, K, KB ], 0, &[ 0x0F, 0x45 ], ModRMV, 256, CPU_AVX512),
+    vex("KXNORW", &[ K, K, KB ], 0, &[ 0x0F, 0x46 ], ModRMV, 256, CPU_AVX512),
+    vex("KXORW", &[ K, K, KB ], 0, &[ 0x0F, 0x47 ], ModRMV, 256, CPU_AVX512),
+
+    evex("VADDPS", &[ Xmm, Xmm, XmmM128 ], 0, &[ 0x0F, 0x58 ], ModRMV, 0, 32),
+    evex("VADDPS", &[ Ymm, Ymm, YmmM256 ], 0, &[ 0x0F, 0x58 ], ModRMV, 0, 32),
+    evex("VADDPS", &[ Zmm, Zmm, ZmmM512 ], 0, &[ 0x0F, 0x58 ], ModRMV, 0, 32),
+    evex("VADDPD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x58 ], ModRMV, 64, 64),
+    evex("VADDPD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x58 ], ModRMV, 64, 64),
+    evex("VADDPD", &[ Zmm, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0x58 ], ModRMV, 64, 64),
+    evex("VADDSS", &[ Xmm, Xmm, XmmM32 ], 0xF3, &[ 0x0F, 0x58 ], ModRMV, 0, 0),
+    evex("VADDSD", &[ Xmm, Xmm, XmmM64 ], 0xF2, &[ 0x0F, 0x58 ], ModRMV, 64, 0),
+    evex("VMULPS", &[ Xmm, Xmm, XmmM128 ], 0, &[ 0x0F, 0x59 ], ModRMV, 0, 32),
+    evex("VMULPS", &[ Ymm, Ymm, YmmM256 ], 0, &[ 0x0F, 0x59 ], ModRMV, 0, 32),
+    evex("VMULPS", &[ Zmm, Zmm, ZmmM512 ], 0, &[ 0x0F, 0x59 ], ModRMV, 0, 32),
+    evex("VMULPD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x59 ], ModRMV, 64, 64),
+    evex("VMULPD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x59 ], ModRMV, 64, 64),
+    evex("VMULPD", &[ Zmm, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0x59 ], ModRMV, 64, 64),
+    evex("VMULSS", &[ Xmm, Xmm, XmmM32 ], 0xF3, &[ 0x0F, 0x59 ], ModRMV, 0, 0),
+    evex("VMULSD", &[ Xmm, Xmm, XmmM64 ], 0xF2, &[ 0x0F, 0x59 ], ModRMV, 64, 0),
+    evex("VSUBPS", &[ Xmm, Xmm, XmmM128 ], 0, &[ 0x0F, 0x5C ], ModRMV, 0, 32),
+    evex("VSUBPS", &[ Ymm, Ymm, YmmM256 ], 0, &[ 0x0F, 0x5C ], ModRMV, 0, 32),
+    evex("VSUBPS", &[ Zmm, Zmm, ZmmM512 ], 0, &[ 0x0F, 0x5C ], ModRMV, 0, 32),
+    evex("VSUBPD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x5C ], ModRMV, 64, 64),
+    evex("VSUBPD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x5C ], ModRMV, 64, 64),
+    evex("VSUBPD", &[ Zmm, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0x5C ], ModRMV, 64, 64),
+    evex("VSUBSS", &[ Xmm, Xmm, XmmM32 ], 0xF3, &[ 0x0F, 0x5C ], ModRMV, 0, 0),
+    evex("VSUBSD", &[ Xmm, Xmm, XmmM64 ], 0xF2, &[ 0x0F, 0x5C ], ModRMV, 64, 0),
+    evex("VMINPS", &[ Xmm, Xmm, XmmM128 ], 0, &[ 0x0F, 0x5D ], ModRMV, 0, 32),
+    evex("VMINPS", &[ Ymm, Ymm, YmmM256 ], 0, &[ 0x0F, 0x5D ], ModRMV, 0, 32),
+    evex("VMINPS", &[ Zmm, Zmm, ZmmM512 ], 0, &[ 0x0F, 0x5D ], ModRMV, 0, 32),
+    evex("VMINPD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x5D ], ModRMV, 64, 64),
+    evex("VMINPD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x5D ], ModRMV, 64, 64),
+    evex("VMINPD", &[ Zmm, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0x5D ], ModRMV, 64, 64),
+    evex("VMINSS", &[ Xmm, Xmm, XmmM32 ], 0xF3, &[ 0x0F, 0x5D ], ModRMV, 0, 0),
+    evex("VMINSD", &[ Xmm, Xmm, XmmM64 ], 0xF2, &[ 0x0F, 0x5D ], ModRMV, 64, 0),
+    evex("VDIVPS", &[ Xmm, Xmm, XmmM128 ], 0, &[ 0x0F, 0x5E ], ModRMV, 0, 32),
+    evex("VDIVPS", &[ Ymm, Ymm, YmmM256 ], 0, &[ 0x0F, 0x5E ], ModRMV, 0, 32),
+    evex("VDIVPS", &[ Zmm, Zmm, ZmmM512 ], 0, &[ 0x0F, 0x5E ], ModRMV, 0, 32),
+    evex("VDIVPD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x5E ], ModRMV, 64, 64),
+    evex("VDIVPD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x5E ], ModRMV, 64, 64),
+    evex("VDIVPD", &[ Zmm, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0x5E ], ModRMV, 64, 64),
+    evex("VDIVSS", &[ Xmm, Xmm, XmmM32 ], 0xF3, &[ 0x0F, 0x5E ], ModRMV, 0, 0),
+    evex("VDIVSD", &[ Xmm, Xmm, XmmM64 ], 0xF2, &[ 0x0F, 0x5E ], ModRMV, 64, 0),
+    evex("VMAXPS", &[ Xmm, Xmm, XmmM128 ], 0, &[ 0x0F, 0x5F ], ModRMV, 0, 32),
+    evex("VMAXPS", &[ Ymm, Ymm, YmmM256 ], 0, &[ 0x0F, 0x5F ], ModRMV, 0, 32),
+    evex("VMAXPS", &[ Zmm, Zmm, ZmmM512 ], 0, &[ 0x0F, 0x5F ], ModRMV, 0, 32),
+    evex("VMAXPD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x5F ], ModRMV, 64, 64),
+    evex("VMAXPD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x5F ], ModRMV, 64, 64),
+    evex("VMAXPD", &[ Zmm, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0x5F ], ModRMV, 64, 64),
+    evex("VMAXSS", &[ Xmm, Xmm, XmmM32 ], 0xF3, &[ 0x0F, 0x5F ], ModRMV, 0, 0),
+    evex("VMAXSD", &[ Xmm, Xmm, XmmM64 ], 0xF2, &[ 0x0F, 0x5F ], ModRMV, 64, 0),
+    evex("VSQRTPS", &[ Xmm, XmmM128 ], 0, &[ 0x0F, 0x51 ], ModRM, 0, 32),
+    evex("VSQRTPS", &[ Ymm, YmmM256 ], 0, &[ 0x0F, 0x51 ], ModRM, 0, 32),
+    evex("VSQRTPS", &[ Zmm, ZmmM512 ], 0, &[ 0x0F, 0x51 ], ModRM, 0, 32),
+    evex("VSQRTPD", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x51 ], ModRM, 64, 64),
+    evex("VSQRTPD", &[ Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x51 ], ModRM, 64, 64),
+    evex("VSQRTPD", &[ Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0x51 ], ModRM, 64, 64),
+    evex("VSQRTSS", &[ Xmm, Xmm, XmmM32 ], 0xF3, &[ 0x0F, 0x51 ], ModRMV, 0, 0),
+    evex("VSQRTSD", &[ Xmm, Xmm, XmmM64 ], 0xF2, &[ 0x0F, 0x51 ], ModRMV, 64, 0),
+    evex("VCMPPS", &[ K, Xmm, XmmM128, I8 ], 0, &[ 0x0F, 0xC2 ], ModRMV, 0, 32),
+    evex("VCMPPS", &[ K, Ymm, YmmM256, I8 ], 0, &[ 0x0F, 0xC2 ], ModRMV, 0, 32),
+    evex("VCMPPS", &[ K, Zmm, ZmmM512, I8 ], 0, &[ 0x0F, 0xC2 ], ModRMV, 0, 32),
+    evex("VCMPPD", &[ K, Xmm, XmmM128, I8 ], 0x66, &[ 0x0F, 0xC2 ], ModRMV, 64, 64),
+    evex("VCMPPD", &[ K, Ymm, YmmM256, I8 ], 0x66, &[ 0x0F, 0xC2 ], ModRMV, 64, 64),
+    evex("VCMPPD", &[ K, Zmm, ZmmM512, I8 ], 0x66, &[ 0x0F, 0xC2 ], ModRMV, 64, 64),
+    evex("VMOVAPS", &[ Xmm, XmmM128 ], 0, &[ 0x0F, 0x28 ], ModRM, 0, 0),
+    evex("VMOVAPS", &[ XmmM128, Xmm ], 0, &[ 0x0F, 0x29 ], ModRM, 0, 0),
+    evex("VMOVAPS", &[ Ymm, YmmM256 ], 0, &[ 0x0F, 0x28 ], ModRM, 0, 0),
+    evex("VMOVAPS", &[ YmmM256, Ymm ], 0, &[ 0x0F, 0x29 ], ModRM, 0, 0),
+    evex("VMOVAPS", &[ Zmm, ZmmM512 ], 0, &[ 0x0F, 0x28 ], ModRM, 0, 0),
+    evex("VMOVAPS", &[ ZmmM512, Zmm ], 0, &[ 0x0F, 0x29 ], ModRM, 0, 0),
+    evex("VMOVAPD", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x28 ], ModRM, 64, 0),
+    evex("VMOVAPD", &[ XmmM128, Xmm ], 0x66, &[ 0x0F, 0x29 ], ModRM, 64, 0),
+    evex("VMOVAPD", &[ Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x28 ], ModRM, 64, 0),
+    evex("VMOVAPD", &[ YmmM256, Ymm ], 0x66, &[ 0x0F, 0x29 ], ModRM, 64, 0),
+    evex("VMOVAPD", &[ Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0x28 ], ModRM, 64, 0),
+    evex("VMOVAPD", &[ ZmmM512, Zmm ], 0x66, &[ 0x0F, 0x29 ], ModRM, 64, 0),
+    evex("VMOVUPS", &[ Xmm, XmmM128 ], 0, &[ 0x0F, 0x10 ], ModRM, 0, 0),
+    evex("VMOVUPS", &[ XmmM128, Xmm ], 0, &[ 0x0F, 0x11 ], ModRM, 0, 0),
+    evex("VMOVUPS", &[ Ymm, YmmM256 ], 0, &[ 0x0F, 0x10 ], ModRM, 0, 0),
+    evex("VMOVUPS", &[ YmmM256, Ymm ], 0, &[ 0x0F, 0x11 ], ModRM, 0, 0),
+    evex("VMOVUPS", &[ Zmm, ZmmM512 ], 0, &[ 0x0F, 0x10 ], ModRM, 0, 0),
+    evex("VMOVUPS", &[ ZmmM512, Zmm ], 0, &[ 0x0F, 0x11 ], ModRM, 0, 0),
+    evex("VMOVUPD", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x10 ], ModRM, 64, 0),
+    evex("VMOVUPD", &[ XmmM128, Xmm ], 0x66, &[ 0x0F, 0x11 ], ModRM, 64, 0),
+    evex("VMOVUPD", &[ Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x10 ], ModRM, 64, 0),
+    evex("VMOVUPD", &[ YmmM256, Ymm ], 0x66, &[ 0x0F, 0x11 ], ModRM, 64, 0),
+    evex("VMOVUPD", &[ Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0x10 ], ModRM, 64, 0),
+    evex("VMOVUPD", &[ ZmmM512, Zmm ], 0x66, &[ 0x0F, 0x11 ], ModRM, 64, 0),
+    evex("VMOVDQA32", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x6F ], ModRM, 0, 0),
+    evex("VMOVDQA32", &[ XmmM128, Xmm ], 0x66, &[ 0x0F, 0x7F ], ModRM, 0, 0),
+    evex("VMOVDQA32", &[ Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x6F ], ModRM, 0, 0),
+    evex("VMOVDQA32", &[ YmmM256, Ymm ], 0x66, &[ 0x0F, 0x7F ], ModRM, 0, 0),
+    evex("VMOVDQA32", &[ Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0x6F ], ModRM, 0, 0),
+    evex("VMOVDQA32", &[ ZmmM512, Zmm ], 0x66, &[ 0x0F, 0x7F ], ModRM, 0, 0),
+    evex("VMOVDQA64", &[ Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x6F ], ModRM, 64, 0),
+    evex("VMOVDQA64", &[ XmmM128, Xmm ], 0x66, &[ 0x0F, 0x7F ], ModRM, 64, 0),
+    evex("VMOVDQA64", &[ Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x6F ], ModRM, 64, 0),
+    evex("VMOVDQA64", &[ YmmM256, Ymm ], 0x66, &[ 0x0F, 0x7F ], ModRM, 64, 0),
+    evex("VMOVDQA64", &[ Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0x6F ], ModRM, 64, 0),
+    evex("VMOVDQA64", &[ ZmmM512, Zmm ], 0x66, &[ 0x0F, 0x7F ], ModRM, 64, 0),
+    evex("VMOVDQU32", &[ Xmm, XmmM128 ], 0xF3, &[ 0x0F, 0x6F ], ModRM, 0, 0),
+    evex("VMOVDQU32", &[ XmmM128, Xmm ], 0xF3, &[ 0x0F, 0x7F ], ModRM, 0, 0),
+    evex("VMOVDQU32", &[ Ymm, YmmM256 ], 0xF3, &[ 0x0F, 0x6F ], ModRM, 0, 0),
+    evex("VMOVDQU32", &[ YmmM256, Ymm ], 0xF3, &[ 0x0F, 0x7F ], ModRM, 0, 0),
+    evex("VMOVDQU32", &[ Zmm, ZmmM512 ], 0xF3, &[ 0x0F, 0x6F ], ModRM, 0, 0),
+    evex("VMOVDQU32", &[ ZmmM512, Zmm ], 0xF3, &[ 0x0F, 0x7F ], ModRM, 0, 0),
+    evex("VMOVDQU64", &[ Xmm, XmmM128 ], 0xF3, &[ 0x0F, 0x6F ], ModRM, 64, 0),
+    evex("VMOVDQU64", &[ XmmM128, Xmm ], 0xF3, &[ 0x0F, 0x7F ], ModRM, 64, 0),
+    evex("VMOVDQU64", &[ Ymm, YmmM256 ], 0xF3, &[ 0x0F, 0x6F ], ModRM, 64, 0),
+    evex("VMOVDQU64", &[ YmmM256, Ymm ], 0xF3, &[ 0x0F, 0x7F ], ModRM, 64, 0),
+    evex("VMOVDQU64", &[ Zmm, ZmmM512 ], 0xF3, &[ 0x0F, 0x6F ], ModRM, 64, 0),
+    evex("VMOVDQU64", &[ ZmmM512, Zmm ], 0xF3, &[ 0x0F, 0x7F ], ModRM, 64, 0),
+    evex("VMOVSS", &[ Xmm, M32 ], 0xF3, &[ 0x0F, 0x10 ], ModRM, 0, 0),
+    evex("VMOVSS", &[ M32, Xmm ], 0xF3, &[ 0x0F, 0x11 ], ModRM, 0, 0),
+    evex("VMOVSD", &[ Xmm, M64 ], 0xF2, &[ 0x0F, 0x10 ], ModRM, 64, 0),
+    evex("VMOVSD", &[ M64, Xmm ], 0xF2, &[ 0x0F, 0x11 ], ModRM, 64, 0),
+    evex("VPADDD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0xFE ], ModRMV, 0, 32),
+    evex("VPADDD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0xFE ], ModRMV, 0, 32),
+    evex("VPADDD", &[ Zmm, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0xFE ], ModRMV, 0, 32),
+    evex("VPADDQ", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0xD4 ], ModRMV, 64, 64),
+    evex("VPADDQ", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0xD4 ], ModRMV, 64, 64),
+    evex("VPADDQ", &[ Zmm, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0xD4 ], ModRMV, 64, 64),
+    evex("VPSUBD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0xFA ], ModRMV, 0, 32),
+    evex("VPSUBD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0xFA ], ModRMV, 0, 32),
+    evex("VPSUBD", &[ Zmm, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0xFA ], ModRMV, 0, 32),
+    evex("VPSUBQ", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0xFB ], ModRMV, 64, 64),
+    evex("VPSUBQ", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0xFB ], ModRMV, 64, 64),
+    evex("VPSUBQ", &[ Zmm, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0xFB ], ModRMV, 64, 64),
+    evex("VPMULLD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0x40 ], ModRMV, 0, 32),
+    evex("VPMULLD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0x40 ], ModRMV, 0, 32),
+    evex("VPMULLD", &[ Zmm, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0x38, 0x40 ], ModRMV, 0, 32),
+    evex("VPANDD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0xDB ], ModRMV, 0, 32),
+    evex("VPANDD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0xDB ], ModRMV, 0, 32),
+    evex("VPANDD", &[ Zmm, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0xDB ], ModRMV, 0, 32),
+    evex("VPANDQ", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0xDB ], ModRMV, 64, 64),
+    evex("VPANDQ", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0xDB ], ModRMV, 64, 64),
+    evex("VPANDQ", &[ Zmm, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0xDB ], ModRMV, 64, 64),
+    evex("VPORD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0xEB ], ModRMV, 0, 32),
+    evex("VPORD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0xEB ], ModRMV, 0, 32),
+    evex("VPORD", &[ Zmm, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0xEB ], ModRMV, 0, 32),
+    evex("VPORQ", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0xEB ], ModRMV, 64, 64),
+    evex("VPORQ", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0xEB ], ModRMV, 64, 64),
+    evex("VPORQ", &[ Zmm, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0xEB ], ModRMV, 64, 64),
+    evex("VPXORD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0xEF ], ModRMV, 0, 32),
+    evex("VPXORD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0xEF ], ModRMV, 0, 32),
+    evex("VPXORD", &[ Zmm, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0xEF ], ModRMV, 0, 32),
+    evex("VPXORQ", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0xEF ], ModRMV, 64, 64),
+    evex("VPXORQ", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0xEF ], ModRMV, 64, 64),
+    evex("VPXORQ", &[ Zmm, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0xEF ], ModRMV, 64, 64),
+    evex("VPCMPEQD", &[ K, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x76 ], ModRMV, 0, 32),
+    evex("VPCMPEQD", &[ K, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x76 ], ModRMV, 0, 32),
+    evex("VPCMPEQD", &[ K, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0x76 ], ModRMV, 0, 32),
+    evex("VBROADCASTSS", &[ Xmm, XmmM32 ], 0x66, &[ 0x0F, 0x38, 0x18 ], ModRM, 0, 0),
+    evex("VBROADCASTSS", &[ Ymm, XmmM32 ], 0x66, &[ 0x0F, 0x38, 0x18 ], ModRM, 0, 0),
+    evex("VBROADCASTSS", &[ Zmm, XmmM32 ], 0x66, &[ 0x0F, 0x38, 0x18 ], ModRM, 0, 0),
+    evex("VBROADCASTSD", &[ Ymm, XmmM64 ], 0x66, &[ 0x0F, 0x38, 0x19 ], ModRM, 64, 0),
+    evex("VBROADCASTSD", &[ Zmm, XmmM64 ], 0x66, &[ 0x0F, 0x38, 0x19 ], ModRM, 64, 0),
+    evex("VPBROADCASTD", &[ Xmm, XmmM32 ], 0x66, &[ 0x0F, 0x38, 0x58 ], ModRM, 0, 0),
+    evex("VPBROADCASTQ", &[ Xmm, XmmM64 ], 0x66, &[ 0x0F, 0x38, 0x59 ], ModRM, 64, 0),
+    evex("VPBROADCASTD", &[ Ymm, XmmM32 ], 0x66, &[ 0x0F, 0x38, 0x58 ], ModRM, 0, 0),
+    evex("VPBROADCASTQ", &[ Ymm, XmmM64 ], 0x66, &[ 0x0F, 0x38, 0x59 ], ModRM, 64, 0),
+    evex("VPBROADCASTD", &[ Zmm, XmmM32 ], 0x66, &[ 0x0F, 0x38, 0x58 ], ModRM, 0, 0),
+    evex("VPBROADCASTQ", &[ Zmm, XmmM64 ], 0x66, &[ 0x0F, 0x38, 0x59 ], ModRM, 64, 0),
+    evex("VFMADD132PS", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0x98 ], ModRMV, 0, 32),
+    evex("VFMADD132PS", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0x98 ], ModRMV, 0, 32),
+    evex("VFMADD132PS", &[ Zmm, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0x38, 0x98 ], ModRMV, 0, 32),
+    evex("VFMADD132PD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0x98 ], ModRMV, 64, 64),
+    evex("VFMADD132PD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0x98 ], ModRMV, 64, 64),
+    evex("VFMADD132PD", &[ Zmm, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0x38, 0x98 ], ModRMV, 64, 64),
+    evex("VFMADD132SS", &[ Xmm, Xmm, XmmM32 ], 0x66, &[ 0x0F, 0x38, 0x99 ], ModRMV, 0, 0),
+    evex("VFMADD132SD", &[ Xmm, Xmm, XmmM64 ], 0x66, &[ 0x0F, 0x38, 0x99 ], ModRMV, 64, 0),
+    evex("VFMADD213PS", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0xA8 ], ModRMV, 0, 32),
+    evex("VFMADD213PS", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0xA8 ], ModRMV, 0, 32),
+    evex("VFMADD213PS", &[ Zmm, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0x38, 0xA8 ], ModRMV, 0, 32),
+    evex("VFMADD213PD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0xA8 ], ModRMV, 64, 64),
+    evex("VFMADD213PD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0xA8 ], ModRMV, 64, 64),
+    evex("VFMADD213PD", &[ Zmm, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0x38, 0xA8 ], ModRMV, 64, 64),
+    evex("VFMADD213SS", &[ Xmm, Xmm, XmmM32 ], 0x66, &[ 0x0F, 0x38, 0xA9 ], ModRMV, 0, 0),
+    evex("VFMADD213SD", &[ Xmm, Xmm, XmmM64 ], 0x66, &[ 0x0F, 0x38, 0xA9 ], ModRMV, 64, 0),
+    evex("VFMADD231PS", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0xB8 ], ModRMV, 0, 32),
+    evex("VFMADD231PS", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0xB8 ], ModRMV, 0, 32),
+    evex("VFMADD231PS", &[ Zmm, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0x38, 0xB8 ], ModRMV, 0, 32),
+    evex("VFMADD231PD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0xB8 ], ModRMV, 64, 64),
+    evex("VFMADD231PD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0xB8 ], ModRMV, 64, 64),
+    evex("VFMADD231PD", &[ Zmm, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0x38, 0xB8 ], ModRMV, 64, 64),
+    evex("VFMADD231SS", &[ Xmm, Xmm, XmmM32 ], 0x66, &[ 0x0F, 0x38, 0xB9 ], ModRMV, 0, 0),
+    evex("VFMADD231SD", &[ Xmm, Xmm, XmmM64 ], 0x66, &[ 0x0F, 0x38, 0xB9 ], ModRMV, 64, 0),
+    evex("VFMSUB132PS", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0x9A ], ModRMV, 0, 32),
+    evex("VFMSUB132PS", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0x9A ], ModRMV, 0, 32),
+    evex("VFMSUB132PS", &[ Zmm, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0x38, 0x9A ], ModRMV, 0, 32),
+    evex("VFMSUB132PD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0x9A ], ModRMV, 64, 64),
+    evex("VFMSUB132PD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0x9A ], ModRMV, 64, 64),
+    evex("VFMSUB132PD", &[ Zmm, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0x38, 0x9A ], ModRMV, 64, 64),
+    evex("VFMSUB132SS", &[ Xmm, Xmm, XmmM32 ], 0x66, &[ 0x0F, 0x38, 0x9B ], ModRMV, 0, 0),
+    evex("VFMSUB132SD", &[ Xmm, Xmm, XmmM64 ], 0x66, &[ 0x0F, 0x38, 0x9B ], ModRMV, 64, 0),
+    evex("VFMSUB213PS", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0xAA ], ModRMV, 0, 32),
+    evex("VFMSUB213PS", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0xAA ], ModRMV, 0, 32),
+    evex("VFMSUB213PS", &[ Zmm, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0x38, 0xAA ], ModRMV, 0, 32),
+    evex("VFMSUB213PD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0xAA ], ModRMV, 64, 64),
+    evex("VFMSUB213PD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0xAA ], ModRMV, 64, 64),
+    evex("VFMSUB213PD", &[ Zmm, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0x38, 0xAA ], ModRMV, 64, 64),
+    evex("VFMSUB213SS", &[ Xmm, Xmm, XmmM32 ], 0x66, &[ 0x0F, 0x38, 0xAB ], ModRMV, 0, 0),
+    evex("VFMSUB213SD", &[ Xmm, Xmm, XmmM64 ], 0x66, &[ 0x0F, 0x38, 0xAB ], ModRMV, 64, 0),
+    evex("VFMSUB231PS", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0xBA ], ModRMV, 0, 32),
+    evex("VFMSUB231PS", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0xBA ], ModRMV, 0, 32),
+    evex("VFMSUB231PS", &[ Zmm, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0x38, 0xBA ], ModRMV, 0, 32),
+    evex("VFMSUB231PD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0xBA ], ModRMV, 64, 64),
+    evex("VFMSUB231PD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0xBA ], ModRMV, 64, 64),
+    evex("VFMSUB231PD", &[ Zmm, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0x38, 0xBA ], ModRMV, 64, 64),
+    evex("VFMSUB231SS", &[ Xmm, Xmm, XmmM32 ], 0x66, &[ 0x0F, 0x38, 0xBB ], ModRMV, 0, 0),
+    evex("VFMSUB231SD", &[ Xmm, Xmm, XmmM64 ], 0x66, &[ 0x0F, 0x38, 0xBB ], ModRMV, 64, 0),
+    evex("VFNMADD132PS", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0x9C ], ModRMV, 0, 32),
+    evex("VFNMADD132PS", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0x9C ], ModRMV, 0, 32),
+    evex("VFNMADD132PS", &[ Zmm, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0x38, 0x9C ], ModRMV, 0, 32),
+    evex("VFNMADD132PD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0x9C ], ModRMV, 64, 64),
+    evex("VFNMADD132PD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0x9C ], ModRMV, 64, 64),
+    evex("VFNMADD132PD", &[ Zmm, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0x38, 0x9C ], ModRMV, 64, 64),
+    evex("VFNMADD132SS", &[ Xmm, Xmm, XmmM32 ], 0x66, &[ 0x0F, 0x38, 0x9D ], ModRMV, 0, 0),
+    evex("VFNMADD132SD", &[ Xmm, Xmm, XmmM64 ], 0x66, &[ 0x0F, 0x38, 0x9D ], ModRMV, 64, 0),
+    evex("VFNMADD213PS", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0xAC ], ModRMV, 0, 32),
+    evex("VFNMADD213PS", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0xAC ], ModRMV, 0, 32),
+    evex("VFNMADD213PS", &[ Zmm, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0x38, 0xAC ], ModRMV, 0, 32),
+    evex("VFNMADD213PD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0xAC ], ModRMV, 64, 64),
+    evex("VFNMADD213PD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0xAC ], ModRMV, 64, 64),
+    evex("VFNMADD213PD", &[ Zmm, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0x38, 0xAC ], ModRMV, 64, 64),
+    evex("VFNMADD213SS", &[ Xmm, Xmm, XmmM32 ], 0x66, &[ 0x0F, 0x38, 0xAD ], ModRMV, 0, 0),
+    evex("VFNMADD213SD", &[ Xmm, Xmm, XmmM64 ], 0x66, &[ 0x0F, 0x38, 0xAD ], ModRMV, 64, 0),
+    evex("VFNMADD231PS", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0xBC ], ModRMV, 0, 32),
+    evex("VFNMADD231PS", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0xBC ], ModRMV, 0, 32),
+    evex("VFNMADD231PS", &[ Zmm, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0x38, 0xBC ], ModRMV, 0, 32),
+    evex("VFNMADD231PD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0xBC ], ModRMV, 64, 64),
+    evex("VFNMADD231PD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0xBC ], ModRMV, 64, 64),
+    evex("VFNMADD231PD", &[ Zmm, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0x38, 0xBC ], ModRMV, 64, 64),
+    evex("VFNMADD231SS", &[ Xmm, Xmm, XmmM32 ], 0x66, &[ 0x0F, 0x38, 0xBD ], ModRMV, 0, 0),
+    evex("VFNMADD231SD", &[ Xmm, Xmm, XmmM64 ], 0x66, &[ 0x0F, 0x38, 0xBD ], ModRMV, 64, 0),
+    evex("VFNMSUB132PS", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0x9E ], ModRMV, 0, 32),
+    evex("VFNMSUB132PS", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0x9E ], ModRMV, 0, 32),
+    evex("VFNMSUB132PS", &[ Zmm, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0x38, 0x9E ], ModRMV, 0, 32),
+    evex("VFNMSUB132PD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0x9E ], ModRMV, 64, 64),
+    evex("VFNMSUB132PD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0x9E ], ModRMV, 64, 64),
+    evex("VFNMSUB132PD", &[ Zmm, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0x38, 0x9E ], ModRMV, 64, 64),
+    evex("VFNMSUB132SS", &[ Xmm, Xmm, XmmM32 ], 0x66, &[ 0x0F, 0x38, 0x9F ], ModRMV, 0, 0),
+    evex("VFNMSUB132SD", &[ Xmm, Xmm, XmmM64 ], 0x66, &[ 0x0F, 0x38, 0x9F ], ModRMV, 64, 0),
+    evex("VFNMSUB213PS", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0xAE ], ModRMV, 0, 32),
+    evex("VFNMSUB213PS", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0xAE ], ModRMV, 0, 32),
+    evex("VFNMSUB213PS", &[ Zmm, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0x38, 0xAE ], ModRMV, 0, 32),
+    evex("VFNMSUB213PD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0xAE ], ModRMV, 64, 64),
+    evex("VFNMSUB213PD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0xAE ], ModRMV, 64, 64),
+    evex("VFNMSUB213PD", &[ Zmm, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0x38, 0xAE ], ModRMV, 64, 64),
+    evex("VFNMSUB213SS", &[ Xmm, Xmm, XmmM32 ], 0x66, &[ 0x0F, 0x38, 0xAF ], ModRMV, 0, 0),
+    evex("VFNMSUB213SD", &[ Xmm, Xmm, XmmM64 ], 0x66, &[ 0x0F, 0x38, 0xAF ], ModRMV, 64, 0),
+    evex("VFNMSUB231PS", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0xBE ], ModRMV, 0, 32),
+    evex("VFNMSUB231PS", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0xBE ], ModRMV, 0, 32),
+    evex("VFNMSUB231PS", &[ Zmm, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0x38, 0xBE ], ModRMV, 0, 32),
+    evex("VFNMSUB231PD", &[ Xmm, Xmm, XmmM128 ], 0x66, &[ 0x0F, 0x38, 0xBE ], ModRMV, 64, 64),
+    evex("VFNMSUB231PD", &[ Ymm, Ymm, YmmM256 ], 0x66, &[ 0x0F, 0x38, 0xBE ], ModRMV, 64, 64),
+    evex("VFNMSUB231PD", &[ Zmm, Zmm, ZmmM512 ], 0x66, &[ 0x0F, 0x38, 0xBE ], ModRMV, 64, 64),
+    evex("VFNMSUB231SS", &[ Xmm, Xmm, XmmM32 ], 0x66, &[ 0x0F, 0x38, 0xBF ], ModRMV, 0, 0),
+    evex("VFNMSUB231SD", &[ Xmm, Xmm, XmmM64 ], 0x66, &[ 0x0F, 0x38, 0xBF ], ModRMV, 64, 0),
+];
+
+/// Flags implied by the CPU types given in code block, newer CPU types include older instruction sets
+pub fn implied_flags(flags: CpuFlags) -> CpuFlags {
+    let rules = [
+        ( CPU_AVX512, CPU_AVX2 | CPU_FMA ),
+        ( CPU_AVX2, CPU_AVX ),
+        ( CPU_FMA, CPU_AVX ),
+        ( CPU_AVX, CPU_PRESCOTT ),
+        ( CPU_AMD64, CPU_WILLAMETTE ),
+        ( CPU_PRESCOTT, CPU_WILLAMETTE | CPU_SSE3 ),
+        ( CPU_WILLAMETTE, CPU_KATMAI | CPU_SSE2 ),
+        ( CPU_KATMAI, CPU_P6 | CPU_SSE ),
+        ( CPU_P6, CPU_PENTIUM | CPU_MMX ),
+        ( CPU_PENTIUM, CPU_486 ),
+        ( CPU_486, CPU_386 | CPU_FPU ),
+        ( CPU_386, CPU_286 ),
+        ( CPU_286, CPU_186 ),
+        ( CPU_PRIVILEGED, CPU_PROTECTED )
+    ];
+    let mut result = flags;
+
+    for ( cpu , implied ) in rules.iter() {
+        if result & cpu != 0 {
+            result |= implied
+        }
+    }
+
+    result
+}
+
+/// Size in bits of memory accepted by operand kind, none when any size is accepted
+fn memory_size(kind: OperandKind) -> Option<u32> {
+    match kind {
+        RM8 | M8 => Some( 8 ),
+        RM16 | KM16 | M16 => Some( 16 ),
+        RM32 | XmmM32 | M32 => Some( 32 ),
+        RM64 | XmmM64 | M64 => Some( 64 ),
+        M80 => Some( 80 ),
+        XmmM128 | M128 => Some( 128 ),
+        YmmM256 => Some( 256 ),
+        ZmmM512 => Some( 512 ),
+        _ => None
+    }
+}
+
+/// True when operand can be used where instruction form expects the kind
+fn operand_matches(kind: OperandKind, operand: &AMD64Operand, size: u32) -> bool {
+    match ( kind , operand ) {
+        ( _ , AMD64Operand::Masked(x, _ , _ ) ) => operand_matches(kind, x, size),
+        ( XmmM128 | YmmM256 | ZmmM512 , AMD64Operand::Broadcast(x, _ ) ) => matches!(**x, AMD64Operand::Memory( .. )),
+        ( Al , AMD64Operand::Register(RegisterAMD64::General(0, 8)) ) |
+        ( Ax , AMD64Operand::Register(RegisterAMD64::General(0, 16)) ) |
+        ( Eax , AMD64Operand::Register(RegisterAMD64::General(0, 32)) ) |
+        ( Rax , AMD64Operand::Register(RegisterAMD64::General(0, 64)) ) |
+        ( Cl , AMD64Operand::Register(RegisterAMD64::General(1, 8)) ) |
+        ( Dx , AMD64Operand::Register(RegisterAMD64::General(2, 16)) ) |
+        ( St0 , AMD64Operand::Register(RegisterAMD64::X87(0)) ) |
+        ( One , AMD64Operand::Immediate(1) ) => true,
+        ( R8 , AMD64Operand::Register(RegisterAMD64::General( _ , 8)) ) |
+        ( R8 , AMD64Operand::Register(RegisterAMD64::HighByte( _ )) ) |
+        ( RM8 , AMD64Operand::Register(RegisterAMD64::General( _ , 8)) ) |
+        ( RM8 , AMD64Operand::Register(RegisterAMD64::HighByte( _ )) ) |
+        ( R16 , AMD64Operand::Register(RegisterAMD64::General( _ , 16)) ) |
+        ( RM16 , AMD64Operand::Register(RegisterAMD64::General( _ , 16)) ) |
+        ( R32 , AMD64Operand::Register(RegisterAMD64::General( _ , 32)) ) |
+        ( RM32 , AMD64Operand::Register(RegisterAMD64::General( _ , 32)) ) |
+        ( R64 , AMD64Operand::Register(RegisterAMD64::General( _ , 64)) ) |
+        ( RM64 , AMD64Operand::Register(RegisterAMD64::General( _ , 64)) ) |
+        ( B32 , AMD64Operand::Register(RegisterAMD64::General( _ , 32)) ) |
+        ( B64 , AMD64Operand::Register(RegisterAMD64::General( _ , 64)) ) => true,
+        ( Xmm , AMD64Operand::Register(RegisterAMD64::Xmm( _ )) ) |
+        ( XmmB , AMD64Operand::Register(RegisterAMD64::Xmm( _ )) ) |
+        ( XmmM32 , AMD64Operand::Register(RegisterAMD64::Xmm( _ )) ) |
+        ( XmmM64 , AMD64Operand::Register(RegisterAMD64::Xmm( _ )) ) |
+        ( XmmM128 , AMD64Operand::Register(RegisterAMD64::Xmm( _ )) ) |
+        ( Ymm , AMD64Operand::Register(RegisterAMD64::Ymm( _ )) ) |
+        ( YmmM256 , AMD64Operand::Register(RegisterAMD64::Ymm( _ )) ) |
+        ( Zmm , AMD64Operand::Register(RegisterAMD64::Zmm( _ )) ) |
+        ( ZmmM512 , AMD64Operand::Register(RegisterAMD64::Zmm( _ )) ) |
+        ( K , AMD64Operand::Register(RegisterAMD64::Mask( _ )) ) |
+        ( KB , AMD64Operand::Register(RegisterAMD64::Mask( _ )) ) |
+        ( KM16 , AMD64Operand::Register(RegisterAMD64::Mask( _ )) ) => true,
+        ( St , AMD64Operand::Register(RegisterAMD64::X87( _ )) ) |
+        ( Sreg , AMD64Operand::Register(RegisterAMD64::Segment( _ )) ) |
+        ( Creg , AMD64Operand::Register(RegisterAMD64::Control( _ )) ) |
+        ( Dreg , AMD64Operand::Register(RegisterAMD64::Debug( _ )) ) => true,
+        ( Mem , AMD64Operand::Memory( .. ) ) => true,
+        ( _ , AMD64Operand::Memory(bits, _ , _ , _ ) ) => {
+            match ( memory_size(kind) , bits ) {
+                ( Some( _ ) , None ) => true,
+                ( Some( x ) , Some( y ) ) => x == *y,
+                _ => false
+            }
+        },
+        ( I8 , AMD64Operand::Immediate(x) ) => *x >= -128 && *x <= 255,
+        ( SI8 , AMD64Operand::Immediate(x) ) => *x >= -128 && *x <= 127,
+        ( I16 , AMD64Operand::Immediate(x) ) => *x >= -32768 && *x <= 65535,
+        ( I32 , AMD64Operand::Immediate(x) ) if size == 64 || size == 0 => *x >= i32::MIN as i64 && *x <= i32::MAX as i64,
+        ( I32 , AMD64Operand::Immediate(x) ) => *x >= i32::MIN as i64 && *x <= u32::MAX as i64,
+        ( I64 , AMD64Operand::Immediate( _ ) ) |
+        ( Rel8 , AMD64Operand::Immediate( _ ) ) |
+        ( Rel32 , AMD64Operand::Immediate( _ ) ) => true,
+        _ => false
+    }
+}
+
+/// Opcode map number of VEX and EVEX prefix and the opcode bytes following the map
+fn opcode_map(opcode: &[u8]) -> (u8, &[u8]) {
+    match opcode {
+        [ 0x0F , 0x38 , rest @ .. ] => ( 2 , rest ),
+        [ 0x0F , 0x3A , rest @ .. ] => ( 3 , rest ),
+        [ 0x0F , rest @ .. ] => ( 1 , rest ),
+        _ => ( 0 , opcode )
+    }
+}
+
+/// Mandatory prefix as 'pp' field of VEX and EVEX prefix
+fn prefix_pp(prefix: u8) -> u8 {
+    match prefix {
+        0x66 => 1,
+        0xF3 => 2,
+        0xF2 => 3,
+        _ => 0
+    }
+}
+
+/// Vector length as 'L' field, 0 for 128 bits, 1 for 256 bits and 2 for 512 bits
+fn vector_length(form: &InstructionForm) -> u8 {
+    let mut length = if form.size == 256 { 1 } else { 0 };
+    for kind in form.operands.iter() {
+        match kind {
+            Ymm | YmmM256 => length = length.max(1),
+            Zmm | ZmmM512 => length = 2,
+            _ => ()
+        }
+    }
+    length
+}
+
+/// Multiplier of 8 bits displacement with EVEX prefix, the size of the memory operand or of the broadcast element
+fn displacement_scale(form: &InstructionForm, broadcast: bool) -> i64 {
+    match ( form.vector , broadcast ) {
+        ( Evex( element ) , true ) => element as i64 / 8,
+        ( Evex( _ ) , _ ) => form.operands.iter().find_map(|x| memory_size(*x)).map(|x| x as i64 / 8).unwrap_or(1),
+        _ => 1
+    }
+}
+
+/// ModRM byte and displacement of memory operand with 16 bits addressing, BX or BP as base with SI or DI as index
+fn address_16_bits(base: &Option<RegisterAMD64>, index: &Option<(RegisterAMD64, u8)>, displacement: i64) -> Result<Vec<u8>, String> {
+    let registers : Vec<&RegisterAMD64> = base.iter().chain(index.iter().map(|x| &x.0)).collect();
+    let mut numbers : Vec<u8> = registers.iter().map(|x| x.number()).collect();
+    numbers.sort();
+
+    let rm : u8 = match ( numbers.as_slice() , registers.iter().all(|x| x.bits() == 16) && index.as_ref().is_none_or(|x| x.1 == 1) ) {
+        ( [ 3 , 6 ] , true ) => 0,
+        ( [ 3 , 7 ] , true ) => 1,
+        ( [ 5 , 6 ] , true ) => 2,
+        ( [ 5 , 7 ] , true ) => 3,
+        ( [ 6 ] , true ) => 4,
+        ( [ 7 ] , true ) => 5,
+        ( [ 5 ] , true ) => 6,
+        ( [ 3 ] , true ) => 7,
+        _ => return Err(String::from("16 bits memory operand must be BX or BP and SI or DI, without scale"))
+    };
+    if displacement < i16::MIN as i64 || displacement > u16::MAX as i64 {
+        return Err(String::from("Displacement does not fit in 16 bits"))
+    }
+
+    /* BP alone has no form without displacement, that encoding is an absolute address */
+    let mode : u8 = match displacement {
+        0 if rm != 6 => 0x00,
+        x if (-128 ..= 127).contains(&x) => 0x40,
+        _ => 0x80
+    };
+    let mut bytes = vec![ mode | rm ];
+    match mode {
+        0x40 => bytes.push(displacement as u8),
+        0x80 => bytes.extend_from_slice(&(displacement as u16).to_le_bytes()),
+        _ => ()
+    }
+    Ok(bytes)
+}
+
+/// Bytes of instruction form with operands in 16, 32 or 64 bits mode, error when a relative target or displacement does not fit.
+/// Memory operands with BX, BP, SI or DI use 16 bits addressing, others use 32 or 64 bits addressing. Address size prefix is
+/// added when the addressing differs from the mode. Also gives position of displacement in RIP relative memory operand, needed for relocations.
+fn encode_form(form: &InstructionForm, operands: &[AMD64Operand], location: i64, bits: u32) -> Result<(Vec<u8>, Option<usize>), String> {
+    /* Opmask, zeroing and broadcast are taken from operands and placed in EVEX prefix */
+    let mut mask : u8 = 0;
+    let mut zeroing = false;
+    let mut broadcast : Option<(u32, Option<u32>)> = None;
+    let mut plain = Vec::<AMD64Operand>::new();
+    for operand in operands.iter() {
+        match operand {
+            AMD64Operand::Masked(x, k, z) => {
+                if *z && matches!(**x, AMD64Operand::Memory( .. )) {
+                    return Err(String::from("Zeroing cannot be used with memory destination"))
+                }
+                mask = *k;
+                zeroing = *z;
+                plain.push((**x).clone())
+            },
+            AMD64Operand::Broadcast(x, n) => {
+                match &**x {
+                    AMD64Operand::Memory(size, _ , _ , _ ) => broadcast = Some( ( *n , *size ) ),
+                    _ => return Err(String::from("Only memory operand can be broadcast"))
+                }
+                plain.push((**x).clone())
+            },
+            x => plain.push(x.clone())
+        }
+    }
+    let operands = plain.as_slice();
+    let length = vector_length(form);
+
+    match ( form.vector , broadcast ) {
+        ( Evex( _ ) , None ) => (),
+        ( Evex( element ) , Some( ( count , size ) ) ) if element != 0 => {
+            if count * element != 128 << length || size.is_some_and(|x| x != element) {
+                return Err(format!("Broadcast must repeat {} bits element {} times", element, (128 << length) / element))
+            }
+        },
+        ( _ , None ) if mask == 0 && !zeroing => (),
+        _ => return Err(String::from("Opmask, zeroing and broadcast are only available for AVX-512 instructions"))
+    }
+
+    let evex = matches!(form.vector, Evex( _ ));
+    let upper = operands.iter().any(|x| match x {
+        AMD64Operand::Register(RegisterAMD64::Xmm(n) | RegisterAMD64::Ymm(n)) => *n >= 16,
+        _ => false
+    });
+    if upper && !evex {
+        return Err(String::from("Registers XMM16 - XMM31 and YMM16 - YMM31 are only available for AVX-512 instructions"))
+    }
+
+    let mut bytes = Vec::<u8>::new();
+    let mut rex : u8 = if form.size == 64 && form.vector == Legacy { 0x08 } else { 0x00 };
+    let mut force_rex = false;      /* SPL, BPL, SIL and DIL are only reachable with REX prefix */
+    let mut high_byte = false;
+    let mut address32 = false;
+    let mut address16 = false;
+    let mut memory = false;
+    let mut long_only = false;      /* 64 bits registers and RIP relative addressing */
+    let word = bits == 16 && form.size == 0;    /* Default sized immediates and targets are 16 bits in 16 bits mode */
+    let mut reg_field : u8 = match form.encoding { Digit(n) => n, _ => 0 };
+    let mut reg_upper = false;      /* Register 16 - 31 in reg field, EVEX.R' */
+    let mut vvvv : u8 = 0;
+    let mut plus_reg : u8 = 0;
+    let mut address = Vec::<u8>::new();    /* ModRM, SIB and displacement */
+    let mut relative = false;
+    let compression = displacement_scale(form, broadcast.is_some());
+
+    for ( i , ( kind , operand ) ) in form.operands.iter().zip(operands.iter()).enumerate() {
+        match operand {
+            AMD64Operand::Register(RegisterAMD64::General(n, 8)) if *n >= 4 && *n < 8 => force_rex = true,
+            AMD64Operand::Register(RegisterAMD64::HighByte( _ )) => high_byte = true,
+            AMD64Operand::Register(RegisterAMD64::General( _ , 64 )) => long_only = true,
+            _ => ()
+        }
+
+        match ( kind , operand ) {
+            ( _ , AMD64Operand::Register(r) ) if form.encoding == ModRMV && i == 1 => vvvv = r.number(),
+            ( R8 | R16 | R32 | R64 | St , AMD64Operand::Register(r) ) if form.encoding == PlusReg => {
+                plus_reg = r.number() & 7;
+                if r.number() >= 8 { rex |= 0x01 }
+            },
+            ( R8 | R16 | R32 | R64 | Xmm | Ymm | Zmm | K | Sreg | Creg | Dreg , AMD64Operand::Register(r) ) => {
+                reg_field = r.number() & 7;
+                if r.number() & 8 != 0 { rex |= 0x04 }
+                reg_upper = r.number() >= 16
+            },
+            ( RM8 | RM16 | RM32 | RM64 | B32 | B64 | XmmB | XmmM32 | XmmM64 | XmmM128 | YmmM256 | ZmmM512 | KB | KM16 , AMD64Operand::Register(r) ) => {
+                address.push(0xc0 | (r.number() & 7));
+                if r.number() & 8 != 0 { rex |= 0x01 }
+                if r.number() >= 16 { rex |= 0x02 }     /* EVEX.X extends register in rm field */
+            },
+            ( _ , AMD64Operand::Memory( _ , base, index, displacement ) ) => {
+                memory = true;
+                if *displacement < i32::MIN as i64 || *displacement > i32::MAX as i64 {
+                    return Err(String::from("Displacement does not fit in 32 bits"))
+                }
+                let disp32 = (*displacement as i32).to_le_bytes();
+                let word_registers = base.iter().chain(index.iter().map(|x| &x.0)).any(|x| x.bits() == 16);
+
+                match ( base , index ) {
+                    _ if word_registers => {
+                        match ( bits , form.vector ) {
+                            ( 64 , _ ) => return Err(String::from("16 bits addressing is not available in 64 bits mode")),
+                            ( _ , Legacy ) => (),
+                            _ => return Err(String::from("16 bits addressing is not available for VEX and EVEX encoded instructions"))
+                        }
+                        address16 = true;
+                        address.extend(address_16_bits(base, index, *displacement)?)
+                    },
+                    ( Some( RegisterAMD64::Rip ) , _ ) => {
+                        long_only = true;
+                        relative = true;
+                        address.push(0x05);
+                        address.extend_from_slice(&disp32)
+                    },
+                    ( None , None ) if bits != 64 => {
+                        address.push(0x05);
+                        address.extend_from_slice(&disp32)
+                    },
+                    ( None , None ) => {
+                        address.push(0x04);
+                        address.push(0x25);
+                        address.extend_from_slice(&disp32)
+                    },
+                    _ => {
+                        let ( index_number , scale ) = match index {
+                            Some( ( r , s ) ) => {
+                                match r.bits() {
+                                    32 => address32 = true,
+                                    _ => long_only = true
+                                }
+                                ( r.number() , match s { 1 => 0, 2 => 1, 4 => 2, _ => 3 } )
+                            },
+                            _ => ( 4 , 0 )
+                        };
+                        if index_number >= 8 { rex |= 0x02 }
+
+                        match base {
+                            Some( r ) => {
+                                match r.bits() {
+                                    32 => address32 = true,
+                                    _ => long_only = true
+                                }
+                                if r.number() >= 8 { rex |= 0x01 }
+                                /* With EVEX prefix 8 bits displacement is scaled by size of memory operand */
+                                let mode : u8 = match *displacement {
+                                    0 if r.number() & 7 != 5 => 0x00,
+                                    x if x % compression == 0 && (-128 ..= 127).contains(&(x / compression)) => 0x40,
+                                    _ => 0x80
+                                };
+                                match index.is_some() || r.number() & 7 == 4 {
+                                    true => {
+                                        address.push(mode | 0x04);
+                                        address.push((scale << 6) | ((index_number & 7) << 3) | (r.number() & 7))
+                                    },
+                                    _ => address.push(mode | (r.number() & 7))
+                                }
+                                match mode {
+                                    0x40 => address.push((*displacement / compression) as u8),
+                                    0x80 => address.extend_from_slice(&disp32),
+                                    _ => ()
+                                }
+                            },
+                            _ => {
+                                address.push(0x04);
+                                address.push((scale << 6) | ((index_number & 7) << 3) | 0x05);
+                                address.extend_from_slice(&disp32)
+                            }
+                        }
+                    }
+                }
+            },
+            _ => ()
+        }
+    }
+
+    if high_byte && ( rex != 0 || force_rex ) {
+        return Err(String::from("AH, CH, DH and BH cannot be used in instruction requiring REX prefix"))
+    }
+
+    if bits != 64 && ( long_only || rex != 0 || force_rex || reg_upper || vvvv >= 8 ) {
+        return Err(String::from("64 bits registers and operand size are only available in 64 bits mode"))
+    }
+
+    match ( form.size , bits ) {
+        ( 16 , 32 | 64 ) | ( 32 , 16 ) => bytes.push(0x66),
+        _ => ()
+    }
+    match bits {
+        64 if address32 => bytes.push(0x67),
+        32 if address16 => bytes.push(0x67),
+        16 if memory && !address16 => bytes.push(0x67),
+        _ => ()
+    }
+    let ( map , opcode ) = opcode_map(form.opcode);
+    let w : u8 = if form.size == 64 { 0x80 } else { 0x00 };
+    let pp = prefix_pp(form.prefix);
+    match form.vector {
+        Legacy => {
+            if form.prefix != 0 { bytes.push(form.prefix) }
+            if rex != 0 || force_rex { bytes.push(0x40 | rex) }
+            bytes.extend_from_slice(form.opcode)
+        },
+        Vex => {
+            /* REX bits are stored inverted, two bytes form when only REX.R is needed */
+            match ( rex & 0x03 , w , map ) {
+                ( 0 , 0 , 1 ) => {
+                    bytes.push(0xC5);
+                    bytes.push(((!rex & 0x04) << 5) | ((!vvvv & 0x0f) << 3) | (length << 2) | pp)
+                },
+                _ => {
+                    bytes.push(0xC4);
+                    bytes.push(((!rex & 0x07) << 5) | map);
+                    bytes.push(w | ((!vvvv & 0x0f) << 3) | (length << 2) | pp)
+                }
+            }
+            bytes.extend_from_slice(opcode)
+        },
+        Evex( _ ) => {
+            /* R' and V' are inverted like the REX bits, they give registers 16 - 31 in reg and vvvv fields */
+            let r = if reg_upper { 0x00 } else { 0x10 };
+            let v = if vvvv >= 16 { 0x00 } else { 0x08 };
+            let z = if zeroing { 0x80 } else { 0x00 };
+            let b = if broadcast.is_some() { 0x10 } else { 0x00 };
+            bytes.push(0x62);
+            bytes.push(((!rex & 0x07) << 5) | r | map);
+            bytes.push(w | ((!vvvv & 0x0f) << 3) | 0x04 | pp);
+            bytes.push(z | (length << 5) | b | v | mask);
+            bytes.extend_from_slice(opcode)
+        }
+    }
+    let mut displacement = None;
+    match form.encoding {
+        PlusReg => {
+            let last = bytes.len() - 1;
+            bytes[last] += plus_reg
+        },
+        ModRM | ModRMV | Digit( _ ) => {
+            address[0] |= reg_field << 3;
+            if relative { displacement = Some( bytes.len() + 1 ) }
+            bytes.extend_from_slice(&address)
+        },
+        Opcode => ()
+    }
+
+    for ( kind , operand ) in form.operands.iter().zip(operands.iter()) {
+        match ( kind , operand ) {
+            ( I8 | SI8 , AMD64Operand::Immediate(x) ) => bytes.push(*x as u8),
+            ( I16 , AMD64Operand::Immediate(x) ) => bytes.extend_from_slice(&(*x as u16).to_le_bytes()),
+            ( I32 , AMD64Operand::Immediate(x) ) if word => bytes.extend_from_slice(&(*x as u16).to_le_bytes()),
+            ( I32 , AMD64Operand::Immediate(x) ) => bytes.extend_from_slice(&(*x as u32).to_le_bytes()),
+            ( I64 , AMD64Operand::Immediate(x) ) => bytes.extend_from_slice(&x.to_le_bytes()),
+            ( Rel8 , AMD64Operand::Immediate(x) ) => {
+                let distance = x.wrapping_sub(location + bytes.len() as i64 + 1);
+                if !(-128 ..= 127).contains(&distance) {
+                    return Err(String::from("Jump target out of range for short jump"))
+                }
+                bytes.push(distance as u8)
+            },
+            ( Rel32 , AMD64Operand::Immediate(x) ) if word => {
+                let distance = x.wrapping_sub(location + bytes.len() as i64 + 2);
+                if distance < i16::MIN as i64 || distance > i16::MAX as i64 {
+                    return Err(String::from("Jump target out of range"))
+                }
+                bytes.extend_from_slice(&(distance as i16).to_le_bytes())
+            },
+            ( Rel32 , AMD64Operand::Immediate(x) ) => {
+                let distance = x.wrapping_sub(location + bytes.len() as i64 + 4);
+                if distance < i32::MIN as i64 || distance > i32::MAX as i64 {
+                    return Err(String::from("Jump target out of range"))
+                }
+                bytes.extend_from_slice(&(distance as i32).to_le_bytes())
+            },
+            _ => ()
+        }
+    }
+
+    Ok(( bytes , displacement ))
+}
+
+/// Encode a single assembler instructions with operands for 16, 32 or 64 bits mode, location is address of instruction
+/// for relative jumps and with 'near' jumps never use the short form. Position of displacement in RIP relative memory
+/// operand is given as well.
+pub fn encode_instruction_amd64_mode(instruction: Box<String>, operands: Box<Vec<AMD64Operand>>, location: i64, flags: CpuFlags, bits: u32, near: bool) -> Result<EncodedAMD64, Box<Diagnostic>> {
+    let mnemonic = instruction.to_uppercase();
+    let flags = implied_flags(flags);
+    let mut known = false;
+    let mut legacy_only = false;
+    let mut matches = Vec::<&InstructionForm>::new();
+
+    for form in INSTRUCTIONS_AMD64.iter() {
+        if form.mnemonic != mnemonic.as_str() {
+            continue
+        }
+        known = true;
+        if form.operands.len() == operands.len() && form.operands.iter().zip(operands.iter()).all(|( k , o )| operand_matches(*k, o, form.size)) {
+            match bits == 64 && !form.long_mode {
+                true => legacy_only = true,
+                _ => matches.push(form)
+            }
+        }
+    }
+
+    if !known {
+        return Err(Diagnostic::error(E_ASSEMBLER_INSTRUCTION, format!("Unknown instruction '{}'", mnemonic), 0, 0))
+    }
+    if matches.is_empty() && legacy_only {
+        return Err(Diagnostic::error(E_ASSEMBLER_OPERAND, format!("Operands of '{}' are only available in 16 and 32 bits mode", mnemonic), 0, 0))
+    }
+    if matches.is_empty() {
+        return Err(Diagnostic::error(E_ASSEMBLER_OPERAND, format!("Invalid combination of operands for '{}'", mnemonic), 0, 0))
+    }
+    if near && matches.iter().any(|x| !x.operands.contains(&Rel8)) {
+        matches.retain(|x| !x.operands.contains(&Rel8))
+    }
+
+    /* Memory operand without size is only accepted when all matching forms agree on the size */
+    for ( i , operand ) in operands.iter().enumerate() {
+        if let AMD64Operand::Memory(None, _ , _ , _ ) = operand {
+            let first = memory_size(matches[0].operands[i]);
+            if matches.iter().any(|x| memory_size(x.operands[i]) != first) {
+                return Err(Diagnostic::error(E_ASSEMBLER_OPERAND, format!("Operation size not specified for '{}'", mnemonic), 0, 0))
+            }
+        }
+    }
+
+    let mut error = Diagnostic::error(E_ASSEMBLER_CPU_FLAG, format!("Instruction '{}' is not enabled by CPU flags of code block", mnemonic), 0, 0);
+    for form in matches.iter() {
+        if form.flags & !flags != 0 {
+            continue
+        }
+        match encode_form(form, &operands, location, bits) {
+            Ok( ( x , displacement ) ) => return Ok(( Box::new(x) , displacement )),
+            Err( x ) => error = Diagnostic::error(E_ASSEMBLER_OPERAND, x, 0, 0)
+        }
+    }
+
+    Err(error)
+}
+
+/// Register operand of kind from register number in instruction, 'rex' tells if REX prefix was present
+fn decoded_register(kind: OperandKind, number: u8, rex: bool) -> Option<RegisterAMD64> {
+    match kind {
+        R8 | RM8 if !rex && ( 4 .. 8 ).contains(&number) => Some( RegisterAMD64::HighByte(number) ),
+        R8 | RM8 => Some( RegisterAMD64::General(number, 8) ),
+        R16 | RM16 => Some( RegisterAMD64::General(number, 16) ),
+        R32 | RM32 => Some( RegisterAMD64::General(number, 32) ),
+        R64 | RM64 | B64 => Some( RegisterAMD64::General(number, 64) ),
+        B32 => Some( RegisterAMD64::General(number, 32) ),
+        Xmm | XmmB | XmmM32 | XmmM64 | XmmM128 => Some( RegisterAMD64::Xmm(number) ),
+        Ymm | YmmM256 => Some( RegisterAMD64::Ymm(number) ),
+        Zmm | ZmmM512 => Some( RegisterAMD64::Zmm(number) ),
+        K | KB | KM16 if number < 8 => Some( RegisterAMD64::Mask(number) ),
+        St => Some( RegisterAMD64::X87(number & 7) ),
+        Sreg if number < 6 => Some( RegisterAMD64::Segment(number) ),
+        Creg => Some( RegisterAMD64::Control(number) ),
+        Dreg => Some( RegisterAMD64::Debug(number) ),
+        _ => None
+    }
+}
+
+/// Operands and length of instruction when bytes are an encoding of the instruction form
+fn decode_form(form: &InstructionForm, code: &[u8], location: i64) -> Option<(Vec<AMD64Operand>, usize)> {
+    let mut i = 0;
+    let mut address32 = false;
+    let mut rex : u8 = 0;
+    let mut has_rex = false;
+
+    if form.size == 16 {
+        if *code.get(i)? != 0x66 { return None }
+        i += 1
+    }
+    if *code.get(i)? == 0x67 {
+        address32 = true;
+        i += 1
+    }
+    let mut opcode = form.opcode;
+    let mut reg_upper : u8 = 0;
+    let mut vvvv : u8 = 0;
+    let mut mask : u8 = 0;
+    let mut zeroing = false;
+    let mut broadcast = false;
+    match form.vector {
+        Legacy => {
+            if form.prefix != 0 {
+                if *code.get(i)? != form.prefix { return None }
+                i += 1
+            }
+            if ( 0x40 ..= 0x4f ).contains(code.get(i)?) {
+                rex = code[i];
+                has_rex = true;
+                i += 1
+            }
+            if ( rex & 0x08 != 0 ) != ( form.size == 64 ) {
+                return None
+            }
+        },
+        _ => {
+            /* VEX and EVEX prefix with inverted REX bits, map, W, vvvv, L and pp fields */
+            let ( map , rest ) = opcode_map(form.opcode);
+            opcode = rest;
+            let ( fields , w , length ) = match ( form.vector , *code.get(i)? ) {
+                ( Vex , 0xC5 ) => {
+                    let x = *code.get(i + 1)?;
+                    i += 2;
+                    rex = (!x >> 5) & 0x04;
+                    ( ( 1 , x ) , false , (x >> 2) & 1 )
+                },
+                ( Vex , 0xC4 ) => {
+                    let ( x , y ) = ( *code.get(i + 1)? , *code.get(i + 2)? );
+                    i += 3;
+                    rex = (!x >> 5) & 0x07;
+                    ( ( x & 0x1f , y ) , y & 0x80 != 0 , (y >> 2) & 1 )
+                },
+                ( Evex( _ ) , 0x62 ) => {
+                    let ( x , y , z ) = ( *code.get(i + 1)? , *code.get(i + 2)? , *code.get(i + 3)? );
+                    if x & 0x0c != 0 || y & 0x04 == 0 { return None }
+                    i += 4;
+                    rex = (!x >> 5) & 0x07;
+                    reg_upper = if x & 0x10 == 0 { 16 } else { 0 };
+                    vvvv = if z & 0x08 == 0 { 16 } else { 0 };
+                    mask = z & 0x07;
+                    zeroing = z & 0x80 != 0;
+                    broadcast = z & 0x10 != 0;
+                    ( ( x & 0x03 , y ) , y & 0x80 != 0 , (z >> 5) & 3 )
+                },
+                _ => return None
+            };
+            vvvv |= (!fields.1 >> 3) & 0x0f;
+            if fields.0 != map || fields.1 & 3 != prefix_pp(form.prefix) || w != ( form.size == 64 ) || length != vector_length(form) {
+                return None
+            }
+            if form.encoding != ModRMV && vvvv != 0 {
+                return None
+            }
+            has_rex = true
+        }
+    }
+
+    /* Opcode bytes, register may be added to last byte */
+    let mut plus_reg : u8 = 0;
+    for ( n , byte ) in opcode.iter().enumerate() {
+        let value = *code.get(i)?;
+        match form.encoding {
+            PlusReg if n == opcode.len() - 1 => {
+                if value & 0xf8 != *byte { return None }
+                plus_reg = (value & 7) | ((rex & 1) << 3)
+            },
+            _ => if value != *byte { return None }
+        }
+        i += 1
+    }
+
+    /* ModRM, SIB and displacement */
+    let mut reg_field : u8 = 0;
+    let mut rm : Option<AMD64Operand> = None;
+    let mut rm_number : u8 = 0;
+    let mut is_register = false;
+    match form.encoding {
+        ModRM | ModRMV | Digit( _ ) => {
+            let modrm = *code.get(i)?;
+            i += 1;
+            let mode = modrm >> 6;
+            reg_field = ((modrm >> 3) & 7) | ((rex & 4) << 1) | reg_upper;
+            match form.encoding {
+                Digit(n) if (modrm >> 3) & 7 != n => return None,
+                _ => ()
+            }
+            let width = if address32 { 32 } else { 64 };
+
+            match ( mode , modrm & 7 ) {
+                ( 3 , x ) => {
+                    is_register = true;
+                    rm_number = x | ((rex & 1) << 3);
+                    if matches!(form.vector, Evex( _ )) { rm_number |= (rex & 2) << 3 }
+                },
+                ( 0 , 5 ) => {
+                    let displacement = i32::from_le_bytes(code.get(i .. i + 4)?.try_into().ok()?) as i64;
+                    i += 4;
+                    rm = Some( AMD64Operand::Memory(None, Some( RegisterAMD64::Rip ), None, displacement) )
+                },
+                ( _ , x ) => {
+                    let ( base , index ) = match x {
+                        4 => {
+                            let sib = *code.get(i)?;
+                            i += 1;
+                            let index_number = ((sib >> 3) & 7) | ((rex & 2) << 2);
+                            let index = match index_number {
+                                4 => None,
+                                n => Some( ( RegisterAMD64::General(n, width), 1u8 << (sib >> 6) ) )
+                            };
+                            match ( mode , sib & 7 ) {
+                                ( 0 , 5 ) => ( None , index ),
+                                ( _ , b ) => ( Some( RegisterAMD64::General(b | ((rex & 1) << 3), width) ) , index )
+                            }
+                        },
+                        b => ( Some( RegisterAMD64::General(b | ((rex & 1) << 3), width) ) , None )
+                    };
+                    let displacement = match ( mode , &base ) {
+                        ( 1 , _ ) => {
+                            let x = *code.get(i)? as i8 as i64;
+                            i += 1;
+                            x * displacement_scale(form, broadcast)
+                        },
+                        ( 2 , _ ) | ( 0 , None ) => {
+                            let x = i32::from_le_bytes(code.get(i .. i + 4)?.try_into().ok()?) as i64;
+                            i += 4;
+                            x
+                        },
+                        _ => 0
+                    };
+                    rm = Some( AMD64Operand::Memory(None, base, index, displacement) )
+                }
+            }
+        },
+        _ => ()
+    }
+
+    if address32 && rm.is_none() {
+        return None
+    }
+
+    /* Operands in order of instruction form, immediates follow the address */
+    let mut operands = Vec::<AMD64Operand>::new();
+    for ( n , kind ) in form.operands.iter().enumerate() {
+        let operand = match kind {
+            _ if form.encoding == ModRMV && n == 1 => AMD64Operand::Register(decoded_register(*kind, vvvv, true)?),
+            Al => AMD64Operand::Register(RegisterAMD64::General(0, 8)),
+            Ax => AMD64Operand::Register(RegisterAMD64::General(0, 16)),
+            Eax => AMD64Operand::Register(RegisterAMD64::General(0, 32)),
+            Rax => AMD64Operand::Register(RegisterAMD64::General(0, 64)),
+            Cl => AMD64Operand::Register(RegisterAMD64::General(1, 8)),
+            Dx => AMD64Operand::Register(RegisterAMD64::General(2, 16)),
+            St0 => AMD64Operand::Register(RegisterAMD64::X87(0)),
+            One => AMD64Operand::Immediate(1),
+            R8 | R16 | R32 | R64 | St if form.encoding == PlusReg => AMD64Operand::Register(decoded_register(*kind, plus_reg, has_rex)?),
+            R8 | R16 | R32 | R64 | St | Xmm | Ymm | Zmm | K | Sreg | Creg | Dreg => AMD64Operand::Register(decoded_register(*kind, reg_field, has_rex)?),
+            RM8 | RM16 | RM32 | RM64 | B32 | B64 | XmmB | XmmM32 | XmmM64 | XmmM128 | YmmM256 | ZmmM512 | KB | KM16 if is_register => AMD64Operand::Register(decoded_register(*kind, rm_number, has_rex)?),
+            Mem | M8 | M16 | M32 | M64 | M80 | M128 if is_register => return None,
+            B32 | B64 | XmmB | KB => return None,
+            XmmM128 | YmmM256 | ZmmM512 if broadcast => {
+                let element = match form.vector { Evex( x ) if x != 0 => x, _ => return None };
+                match rm.clone()? {
+                    AMD64Operand::Memory( _ , base, index, displacement ) => AMD64Operand::Broadcast(Box::new(AMD64Operand::Memory(Some( element ), base, index, displacement)), memory_size(*kind)? / element),
+                    _ => return None
+                }
+            },
+            RM8 | RM16 | RM32 | RM64 | XmmM32 | XmmM64 | XmmM128 | YmmM256 | ZmmM512 | KM16 | Mem | M8 | M16 | M32 | M64 | M80 | M128 => {
+                match rm.clone()? {
+                    AMD64Operand::Memory( _ , base, index, displacement ) => AMD64Operand::Memory(memory_size(*kind), base, index, displacement),
+                    x => x
+                }
+            },
+            I8 => {
+                i += 1;
+                AMD64Operand::Immediate(*code.get(i - 1)? as i64)
+            },
+            SI8 => {
+                i += 1;
+                AMD64Operand::Immediate(*code.get(i - 1)? as i8 as i64)
+            },
+            I16 => {
+                i += 2;
+                AMD64Operand::Immediate(u16::from_le_bytes(code.get(i - 2 .. i)?.try_into().ok()?) as i64)
+            },
+            I32 if form.size == 64 || form.size == 0 => {
+                i += 4;
+                AMD64Operand::Immediate(i32::from_le_bytes(code.get(i - 4 .. i)?.try_into().ok()?) as i64)
+            },
+            I32 => {
+                i += 4;
+                AMD64Operand::Immediate(u32::from_le_bytes(code.get(i - 4 .. i)?.try_into().ok()?) as i64)
+            },
+            I64 => {
+                i += 8;
+                AMD64Operand::Immediate(i64::from_le_bytes(code.get(i - 8 .. i)?.try_into().ok()?))
+            },
+            Rel8 => {
+                i += 1;
+                AMD64Operand::Immediate(location + i as i64 + *code.get(i - 1)? as i8 as i64)
+            },
+            Rel32 => {
+                i += 4;
+                AMD64Operand::Immediate(location + i as i64 + i32::from_le_bytes(code.get(i - 4 .. i)?.try_into().ok()?) as i64)
+            }
+        };
+        operands.push(operand)
+    }
+
+    /* Broadcast bit of register operands selects rounding, which is not supported */
+    if broadcast && !operands.iter().any(|x| matches!(x, AMD64Operand::Broadcast( .. ))) {
+        return None
+    }
+    if mask != 0 || zeroing {
+        operands[0] = AMD64Operand::Masked(Box::new(operands[0].clone()), mask, zeroing)
+    }
+
+    Some( ( operands , i ) )
+}
+
+/// Number in assembler syntax, hexadecimal with 'H' suffix above nine
+fn number_text(value: i64) -> String {
+    match value {
+        x if x < 0 => format!("-{}", number_text_unsigned(x.unsigned_abs())),
+        x => number_text_unsigned(x as u64)
+    }
+}
+
+fn number_text_unsigned(value: u64) -> String {
+    match value {
+        0 ..= 9 => format!("{}", value),
+        _ => {
+            let text = format!("{:X}", value);
+            match text.starts_with(|c: char| c.is_ascii_alphabetic()) {
+                true => format!("0{}H", text),
+                _ => format!("{}H", text)
+            }
+        }
+    }
+}
+
+/// Name of register as accepted by the inline assembler
+pub fn register_name_amd64(register: &RegisterAMD64) -> String {
+    let legacy = [ "AX", "CX", "DX", "BX", "SP", "BP", "SI", "DI" ];
+    let low = [ "AL", "CL", "DL", "BL", "SPL", "BPL", "SIL", "DIL" ];
+
+    match register {
+        RegisterAMD64::General(n, 8) if *n < 8 => String::from(low[*n as usize]),
+        RegisterAMD64::General(n, 16) if *n < 8 => String::from(legacy[*n as usize]),
+        RegisterAMD64::General(n, 32) if *n < 8 => format!("E{}", legacy[*n as usize]),
+        RegisterAMD64::General(n, 64) if *n < 8 => format!("R{}", legacy[*n as usize]),
+        RegisterAMD64::General(n, 8) => format!("R{}B", n),
+        RegisterAMD64::General(n, 16) => format!("R{}W", n),
+        RegisterAMD64::General(n, 32) => format!("R{}D", n),
+        RegisterAMD64::General(n, _ ) => format!("R{}", n),
+        RegisterAMD64::HighByte(n) => String::from([ "AH", "CH", "DH", "BH" ][(*n & 3) as usize]),
+        RegisterAMD64::Segment(n) => String::from([ "ES", "CS", "SS", "DS", "FS", "GS", "?S", "?S" ][(*n & 7) as usize]),
+        RegisterAMD64::Control(n) => format!("CR{}", n),
+        RegisterAMD64::Debug(n) => format!("DR{}", n),
+        RegisterAMD64::X87(n) => format!("ST{}", n),
+        RegisterAMD64::Mmx(n) => format!("MM{}", n),
+        RegisterAMD64::Xmm(n) => format!("XMM{}", n),
+        RegisterAMD64::Ymm(n) => format!("YMM{}", n),
+        RegisterAMD64::Zmm(n) => format!("ZMM{}", n),
+        RegisterAMD64::Mask(n) => format!("K{}", n),
+        RegisterAMD64::Rip => String::from("RIP")
+    }
+}
+
+/// Operand in Intel syntax, memory operands with size gets 'PTR'
+pub fn operand_text_amd64(operand: &AMD64Operand) -> String {
+    match operand {
+        AMD64Operand::Register(r) => register_name_amd64(r),
+        AMD64Operand::Immediate(x) => number_text(*x),
+        AMD64Operand::Memory(size, base, index, displacement) => {
+            let mut text = String::new();
+            if let Some( r ) = base {
+                text.push_str(register_name_amd64(r).as_str())
+            }
+            if let Some( ( r , scale ) ) = index {
+                if !text.is_empty() {
+                    text.push_str(" + ")
+                }
+                text.push_str(register_name_amd64(r).as_str());
+                text.push_str(format!("*{}", scale).as_str())
+            }
+            match ( text.is_empty() , *displacement ) {
+                ( true , x ) => text.push_str(number_text(x).as_str()),
+                ( _ , 0 ) => (),
+                ( _ , x ) if x < 0 => text.push_str(format!(" - {}", number_text_unsigned(x.unsigned_abs())).as_str()),
+                ( _ , x ) => text.push_str(format!(" + {}", number_text(x)).as_str())
+            }
+            let prefix = match size {
+                Some( 8 ) => "BYTE PTR ",
+                Some( 16 ) => "WORD PTR ",
+                Some( 32 ) => "DWORD PTR ",
+                Some( 64 ) => "QWORD PTR ",
+                Some( 80 ) => "TWORD PTR ",
+                Some( 128 ) => "OWORD PTR ",
+                Some( 256 ) => "YWORD PTR ",
+                Some( 512 ) => "ZWORD PTR ",
+                _ => ""
+            };
+            format!("{}[{}]", prefix, text)
+        },
+        AMD64Operand::Masked(x, 0, _ ) => format!("{}{{Z}}", operand_text_amd64(x)),
+        AMD64Operand::Masked(x, k, false) => format!("{}{{K{}}}", operand_text_amd64(x), k),
+        AMD64Operand::Masked(x, k, true) => format!("{}{{K{}}}{{Z}}", operand_text_amd64(x), k),
+        AMD64Operand::Broadcast(x, n) => format!("{}{{1TO{}}}", operand_text_amd64(x), n)
+    }
+}
+
+/// Decode instruction at start of bytes to Intel syntax and its length. Location is address of instruction for relative jumps.
+/// The longest matching form is used, so prefixes are only decoded alone when no instruction follows that uses them.
+pub fn decode_instruction_amd64(code: &[u8], location: i64, flags: CpuFlags) -> Result<(Box<String>, usize), Box<String>> {
+    let enabled = implied_flags(flags);
+    let mut best : Option<(&InstructionForm, Vec<AMD64Operand>, usize)> = None;
+
+    for form in INSTRUCTIONS_AMD64.iter() {
+        if ( flags != 0 && form.flags & !enabled != 0 ) || !form.long_mode {
+            continue
+        }
+        if let Some( ( operands , length ) ) = decode_form(form, code, location) {
+            match &best {
+                Some( ( _ , _ , x ) ) if *x >= length => (),
+                _ => best = Some( ( form , operands , length ) )
+            }
+        }
+    }
+
+    match best {
+        Some( ( form , operands , length ) ) => {
+            let text : Vec<String> = operands.iter().map(operand_text_amd64).collect();
+            match text.is_empty() {
+                true => Ok( ( Box::new(String::from(form.mnemonic)) , length ) ),
+                _ => Ok( ( Box::new(format!("{} {}", form.mnemonic, text.join(", "))) , length ) )
+            }
+        },
+        _ => Err(Box::new(format!("Unknown instruction at byte {:02X}", code.first().copied().unwrap_or(0))))
+    }
+}
+
+/// Listing of machine code with address, bytes and instruction on each line. Unknown bytes are listed as 'DB'.
+pub fn listing_amd64(code: &[u8], location: i64) -> String {
+    let mut text = String::new();
+    let mut index = 0;
+
+    while index < code.len() {
+        let ( instruction , length ) = match decode_instruction_amd64(&code[index ..], location + index as i64, 0) {
+            Ok( x ) => ( *x.0 , x.1 ),
+            _ => ( format!("DB {}", number_text(code[index] as i64)) , 1 )
+        };
+        let bytes : Vec<String> = code[index .. index + length].iter().map(|x| format!("{:02X}", x)).collect();
+        text.push_str(format!("{:08X}  {:<30} {}\r\n", location + index as i64, bytes.join(" "), instruction).as_str());
+        index += length
+    }
+
+    text
+}
+
+// Unittests for X86-64 instruction encoding
+
+#[cfg(test)]
+mod tests {
+    use crate::amd64_instruction_set_neo::{ AMD64Operand, RegisterAMD64, InstructionForm, OperandKind, OperandKind::*, VectorPrefix::*, memory_size, vector_length, register_amd64, encode_instruction_amd64_mode, decode_instruction_amd64, CpuFlags, listing_amd64, implied_flags, CPU_AMD64, CPU_PRESCOTT, CPU_PRIVILEGED, CPU_SSE2, CPU_SSE3, CPU_FPU, CPU_AVX, CPU_AVX2, CPU_FMA, CPU_AVX512 };
+    use crate::diagnostics::{ Diagnostic, E_ASSEMBLER_CPU_FLAG, E_ASSEMBLER_OPERAND, E_ASSEMBLER_INSTRUCTION };
+
+    fn reg(name: &str) -> AMD64Operand {
+        AMD64Operand::Register(register_amd64(name).unwrap())
+    }
+
+    fn mem(size: Option<u32>, base: Option<&str>, index: Option<(&str, u8)>, displacement: i64) -> AMD64Operand {
+        AMD64Operand::Memory(size, base.map(|x| register_amd64(x).unwrap()), index.map(|( x , s )| ( register_amd64(x).unwrap() , s )), displacement)
+    }
+
+    fn encode_instruction_amd64(instruction: Box<String>, operands: Box<Vec<AMD64Operand>>, location: i64, flags: CpuFlags) -> Result<Box<Vec<u8>>, Box<Diagnostic>> {
+        encode_instruction_amd64_mode(instruction, operands, location, flags, 64, false).map(|x| x.0)
+    }
+
+    fn encode(mnemonic: &str, operands: Vec<AMD64Operand>) -> Vec<u8> {
+        *encode_instruction_amd64(Box::new(String::from(mnemonic)), Box::new(operands), 0, CPU_PRESCOTT | CPU_AMD64 | CPU_PRIVILEGED).unwrap()
+    }
+
+    #[test]
+    fn register_names() {
+        assert_eq!(register_amd64("rax"), Some( RegisterAMD64::General(0, 64) ));
+        assert_eq!(register_amd64("R10D"), Some( RegisterAMD64::General(10, 32) ));
+        assert_eq!(register_amd64("SIL"), Some( RegisterAMD64::General(6, 8) ));
+        assert_eq!(register_amd64("AH"), Some( RegisterAMD64::HighByte(4) ));
+        assert_eq!(register_amd64("XMM15"), Some( RegisterAMD64::Xmm(15) ));
+        assert_eq!(register_amd64("XMM31"), Some( RegisterAMD64::Xmm(31) ));
+        assert_eq!(register_amd64("ymm7"), Some( RegisterAMD64::Ymm(7) ));
+        assert_eq!(register_amd64("ZMM0"), Some( RegisterAMD64::Zmm(0) ));
+        assert_eq!(register_amd64("K7"), Some( RegisterAMD64::Mask(7) ));
+        assert_eq!(register_amd64("K8"), None);
+        assert_eq!(register_amd64("R16"), None);
+        assert_eq!(register_amd64("offset"), None)
+    }
+
+    #[test]
+    fn cpu_flags_implied() {
+        assert_ne!(implied_flags(CPU_AMD64) & CPU_SSE2, 0);
+        assert_ne!(implied_flags(CPU_AMD64) & CPU_FPU, 0);
+        assert_eq!(implied_flags(CPU_AMD64) & CPU_SSE3, 0);
+        assert_ne!(implied_flags(CPU_AVX512) & CPU_FMA, 0);
+        assert_ne!(implied_flags(CPU_AVX512) & CPU_AVX, 0);
+        assert_ne!(implied_flags(CPU_AVX512) & CPU_SSE3, 0);
+        assert_eq!(implied_flags(CPU_AVX2) & CPU_FMA, 0)
+    }
+
+    #[test]
+    fn encode_integer_instructions() {
+        assert_eq!(encode("ADD", vec![ reg("RAX"), reg("RBX") ]), vec![ 0x48, 0x01, 0xd8 ]);
+        assert_eq!(encode("ADD", vec![ reg("EAX"), AMD64Operand::Immediate(1) ]), vec![ 0x83, 0xc0, 0x01 ]);
+        assert_eq!(encode("ADD", vec![ reg("RAX"), AMD64Operand::Immediate(1000) ]), vec![ 0x48, 0x05, 0xe8, 0x03, 0x00, 0x00 ]);
+        assert_eq!(encode("MOV", vec![ reg("SIL"), AMD64Operand::Immediate(1) ]), vec![ 0x40, 0xb6, 0x01 ]);
+        assert_eq!(encode("MOV", vec![ reg("RAX"), AMD64Operand::Immediate(0x123456789) ]), vec![ 0x48, 0xb8, 0x89, 0x67, 0x45, 0x23, 0x01, 0x00, 0x00, 0x00 ]);
+        assert_eq!(encode("MOV", vec![ reg("AX"), reg("R9W") ]), vec![ 0x66, 0x44, 0x89, 0xc8 ]);
+        assert_eq!(encode("PUSH", vec![ reg("R15") ]), vec![ 0x41, 0x57 ]);
+        assert_eq!(encode("SHL", vec![ reg("RAX"), AMD64Operand::Immediate(1) ]), vec![ 0x48, 0xd1, 0xe0 ]);
+        assert_eq!(encode("SAR", vec![ reg("ECX"), reg("CL") ]), vec![ 0xd3, 0xf9 ]);
+        assert_eq!(encode("SHR", vec![ reg("EDX"), AMD64Operand::Immediate(4) ]), vec![ 0xc1, 0xea, 0x04 ]);
+        assert_eq!(encode("MOV", vec![ reg("CR0"), reg("RAX") ]), vec![ 0x0f, 0x22, 0xc0 ]);
+        assert_eq!(encode("CQO", vec![ ]), vec![ 0x48, 0x99 ])
+    }
+
+    #[test]
+    fn encode_memory_operands() {
+        assert_eq!(encode("MOV", vec![ reg("R8D"), mem(None, Some( "RSP" ), None, 8) ]), vec![ 0x44, 0x8b, 0x44, 0x24, 0x08 ]);
+        assert_eq!(encode("MOV", vec![ mem(Some( 64 ), Some( "RBP" ), None, -16), reg("R12") ]), vec![ 0x4c, 0x89, 0x65, 0xf0 ]);
+        assert_eq!(encode("LEA", vec![ reg("RAX"), mem(None, Some( "RBX" ), Some( ( "RCX", 4 ) ), 0x100) ]), vec![ 0x48, 0x8d, 0x84, 0x8b, 0x00, 0x01, 0x00, 0x00 ]);
+        assert_eq!(encode("MOV", vec![ reg("AL"), mem(None, Some( "R13" ), None, 0) ]), vec![ 0x41, 0x8a, 0x45, 0x00 ]);
+        assert_eq!(encode("MOV", vec![ reg("RAX"), mem(None, None, None, 0x1000) ]), vec![ 0x48, 0x8b, 0x04, 0x25, 0x00, 0x10, 0x00, 0x00 ]);
+        assert_eq!(encode("MOV", vec![ reg("EAX"), mem(None, Some( "RIP" ), None, 8) ]), vec![ 0x8b, 0x05, 0x08, 0x00, 0x00, 0x00 ]);
+        assert_eq!(encode("MOV", vec![ reg("EAX"), mem(None, Some( "EBX" ), None, 0) ]), vec![ 0x67, 0x8b, 0x03 ]);
+        assert_eq!(encode("INC", vec![ mem(Some( 32 ), None, Some( ( "R9", 8 ) ), 0) ]), vec![ 0x42, 0xff, 0x04, 0xcd, 0x00, 0x00, 0x00, 0x00 ])
+    }
+
+    #[test]
+    fn encode_relative_jumps() {
+        assert_eq!(encode("JMP", vec![ AMD64Operand::Immediate(0x10) ]), vec![ 0xeb, 0x0e ]);
+        assert_eq!(encode("JMP", vec![ AMD64Operand::Immediate(0x1000) ]), vec![ 0xe9, 0xfb, 0x0f, 0x00, 0x00 ]);
+        assert_eq!(encode("JE", vec![ AMD64Operand::Immediate(-0x100) ]), vec![ 0x0f, 0x84, 0xfa, 0xfe, 0xff, 0xff ]);
+        assert_eq!(encode("CALL", vec![ AMD64Operand::Immediate(5) ]), vec![ 0xe8, 0x00, 0x00, 0x00, 0x00 ])
+    }
+
+    #[test]
+    fn encode_sse_and_x87_instructions() {
+        assert_eq!(encode("ADDSD", vec![ reg("XMM1"), reg("XMM9") ]), vec![ 0xf2, 0x41, 0x0f, 0x58, 0xc9 ]);
+        assert_eq!(encode("MOVQ", vec![ reg("XMM0"), reg("RAX") ]), vec![ 0x66, 0x48, 0x0f, 0x6e, 0xc0 ]);
+        assert_eq!(encode("MOVAPS", vec![ mem(None, Some( "RDI" ), None, 0), reg("XMM2") ]), vec![ 0x0f, 0x29, 0x17 ]);
+        assert_eq!(encode("ADDSUBPS", vec![ reg("XMM0"), reg("XMM1") ]), vec![ 0xf2, 0x0f, 0xd0, 0xc1 ]);
+        assert_eq!(encode("FLD", vec![ mem(Some( 64 ), Some( "RAX" ), None, 0) ]), vec![ 0xdd, 0x00 ]);
+        assert_eq!(encode("FADDP", vec![ reg("ST1"), reg("ST0") ]), vec![ 0xde, 0xc1 ]);
+        assert_eq!(encode("FSTSW", vec![ reg("AX") ]), vec![ 0x9b, 0xdf, 0xe0 ])
+    }
+
+    #[test]
+    fn encode_errors() {
+        let sse2 = encode_instruction_amd64(Box::new(String::from("ADDSUBPS")), Box::new(vec![ reg("XMM0"), reg("XMM1") ]), 0, CPU_AMD64);
+        match sse2 {
+            Err( e ) => assert_eq!(e.code, E_ASSEMBLER_CPU_FLAG),
+            _ => assert!(false)
+        }
+
+        let no_size = encode_instruction_amd64(Box::new(String::from("INC")), Box::new(vec![ mem(None, Some( "RAX" ), None, 0) ]), 0, CPU_AMD64);
+        match no_size {
+            Err( e ) => assert_eq!(e.code, E_ASSEMBLER_OPERAND),
+            _ => assert!(false)
+        }
+
+        let high_byte = encode_instruction_amd64(Box::new(String::from("MOV")), Box::new(vec![ reg("AH"), reg("SIL") ]), 0, CPU_AMD64);
+        match high_byte {
+            Err( e ) => assert_eq!(e.code, E_ASSEMBLER_OPERAND),
+            _ => assert!(false)
+        }
+
+        let unknown = encode_instruction_amd64(Box::new(String::from("FOO")), Box::default(), 0, CPU_AMD64);
+        match unknown {
+            Err( e ) => assert_eq!(e.code, E_ASSEMBLER_INSTRUCTION),
+            _ => assert!(false)
+        }
+    }
+
+    fn encode_avx(mnemonic: &str, operands: Vec<AMD64Operand>) -> Vec<u8> {
+        *encode_instruction_amd64(Box::new(String::from(mnemonic)), Box::new(operands), 0, CPU_AMD64 | CPU_AVX512).unwrap()
+    }
+
+    fn masked(operand: AMD64Operand, mask: u8, zeroing: bool) -> AMD64Operand {
+        AMD64Operand::Masked(Box::new(operand), mask, zeroing)
+    }
+
+    #[test]
+    fn encode_vex_and_evex_instructions() {
+        assert_eq!(encode_avx("VADDPS", vec![ reg("YMM1"), reg("YMM2"), reg("YMM3") ]), vec![ 0xc5, 0xec, 0x58, 0xcb ]);
+        assert_eq!(encode_avx("VPADDD", vec![ reg("XMM1"), reg("XMM2"), reg("XMM3") ]), vec![ 0xc5, 0xe9, 0xfe, 0xcb ]);
+        assert_eq!(encode_avx("VFMADD231PD", vec![ reg("YMM1"), reg("YMM2"), mem(Some( 256 ), Some( "RAX" ), None, 0) ]), vec![ 0xc4, 0xe2, 0xed, 0xb8, 0x08 ]);
+        assert_eq!(encode_avx("KMOVW", vec![ reg("K1"), reg("EAX") ]), vec![ 0xc5, 0xf8, 0x92, 0xc8 ]);
+        assert_eq!(encode_avx("VZEROUPPER", vec![]), vec![ 0xc5, 0xf8, 0x77 ]);
+        assert_eq!(encode_avx("VMOVAPS", vec![ reg("XMM16"), reg("XMM1") ]), vec![ 0x62, 0xe1, 0x7c, 0x08, 0x28, 0xc1 ]);
+        assert_eq!(encode_avx("VADDPS", vec![ masked(reg("ZMM1"), 1, true), reg("ZMM2"), reg("ZMM3") ]), vec![ 0x62, 0xf1, 0x6c, 0xc9, 0x58, 0xcb ]);
+        assert_eq!(encode_avx("VADDPS", vec![ reg("ZMM1"), reg("ZMM2"), AMD64Operand::Broadcast(Box::new(mem(Some( 32 ), Some( "RAX" ), None, 8)), 16) ]), vec![ 0x62, 0xf1, 0x6c, 0x58, 0x58, 0x48, 0x02 ]);
+        assert_eq!(encode_avx("VMOVDQU32", vec![ masked(mem(None, Some( "RBP" ), None, -128), 2, false), reg("ZMM17") ]), vec![ 0x62, 0xe1, 0x7e, 0x4a, 0x7f, 0x4d, 0xfe ]);
+        assert_eq!(encode_avx("VCMPPS", vec![ reg("K1"), reg("ZMM0"), reg("ZMM1"), AMD64Operand::Immediate(0) ]), vec![ 0x62, 0xf1, 0x7c, 0x48, 0xc2, 0xc9, 0x00 ])
+    }
+
+    #[test]
+    fn encode_vector_errors() {
+        let res = encode_instruction_amd64(Box::new(String::from("VADDPS")), Box::new(vec![ reg("YMM1"), reg("YMM2"), reg("YMM3") ]), 0, CPU_PRESCOTT | CPU_AMD64);
+        assert_eq!(res.unwrap_err().code, E_ASSEMBLER_CPU_FLAG);
+        let res = encode_instruction_amd64(Box::new(String::from("VADDPS")), Box::new(vec![ masked(reg("YMM1"), 1, false), reg("YMM2"), reg("YMM3") ]), 0, CPU_AMD64 | CPU_AVX2);
+        assert_eq!(res.unwrap_err().code, E_ASSEMBLER_OPERAND);
+        let res = encode_instruction_amd64(Box::new(String::from("VZEROUPPER")), Box::new(vec![ reg("XMM16") ]), 0, CPU_AMD64 | CPU_AVX512);
+        assert!(res.is_err());
+        let res = encode_instruction_amd64(Box::new(String::from("VADDPS")), Box::new(vec![ reg("ZMM1"), reg("ZMM2"), AMD64Operand::Broadcast(Box::new(mem(Some( 32 ), Some( "RAX" ), None, 0)), 8) ]), 0, CPU_AMD64 | CPU_AVX512);
+        assert!(res.is_err())
+    }
+
+    #[test]
+    fn decode_vex_and_evex_instructions() {
+        assert_eq!(decode(&[ 0xc5, 0xec, 0x58, 0xcb ]), ( String::from("VADDPS YMM1, YMM2, YMM3") , 4 ));
+        assert_eq!(decode(&[ 0xc4, 0xe2, 0xed, 0xb8, 0x08 ]), ( String::from("VFMADD231PD YMM1, YMM2, YWORD PTR [RAX]") , 5 ));
+        assert_eq!(decode(&[ 0x62, 0xf1, 0x6c, 0xc9, 0x58, 0xcb ]), ( String::from("VADDPS ZMM1{K1}{Z}, ZMM2, ZMM3") , 6 ));
+        assert_eq!(decode(&[ 0x62, 0xf1, 0x6c, 0x58, 0x58, 0x48, 0x02 ]), ( String::from("VADDPS ZMM1, ZMM2, DWORD PTR [RAX + 8]{1TO16}") , 7 ));
+        assert_eq!(decode(&[ 0x62, 0xe1, 0x7e, 0x4a, 0x7f, 0x4d, 0xfe ]), ( String::from("VMOVDQU32 ZWORD PTR [RBP - 80H]{K2}, ZMM17") , 7 ));
+        assert_eq!(decode(&[ 0xc5, 0xf8, 0x92, 0xc8 ]), ( String::from("KMOVW K1, EAX") , 4 ))
+    }
+
+    fn decode(code: &[u8]) -> (String, usize) {
+        let ( text , length ) = decode_instruction_amd64(code, 0, 0).unwrap();
+        ( *text , length )
+    }
+
+    #[test]
+    fn decode_instructions() {
+        assert_eq!(decode(&[ 0x48, 0x01, 0xd8 ]), ( String::from("ADD RAX, RBX") , 3 ));
+        assert_eq!(decode(&[ 0x4c, 0x89, 0x65, 0xf0, 0x90 ]), ( String::from("MOV QWORD PTR [RBP - 10H], R12") , 4 ));
+        assert_eq!(decode(&[ 0x48, 0x8d, 0x84, 0x8b, 0x00, 0x01, 0x00, 0x00 ]), ( String::from("LEA RAX, [RBX + RCX*4 + 100H]") , 8 ));
+        assert_eq!(decode(&[ 0x83, 0xc0, 0xff ]), ( String::from("ADD EAX, -1") , 3 ));
+        assert_eq!(decode(&[ 0x40, 0xb6, 0x01 ]), ( String::from("MOV SIL, 1") , 3 ));
+        assert_eq!(decode(&[ 0xb4, 0x01 ]), ( String::from("MOV AH, 1") , 2 ));
+        assert_eq!(decode(&[ 0xf2, 0x41, 0x0f, 0x58, 0xc9 ]), ( String::from("ADDSD XMM1, XMM9") , 5 ));
+        assert_eq!(decode(&[ 0xf3, 0x90 ]), ( String::from("PAUSE") , 2 ));
+        assert_eq!(decode(&[ 0xf3, 0xaa ]), ( String::from("REP") , 1 ));
+        assert_eq!(decode(&[ 0x9b, 0xdf, 0xe0 ]), ( String::from("FSTSW AX") , 3 ));
+        assert_eq!(decode(&[ 0xeb, 0xfe ]), ( String::from("JMP 0") , 2 ));
+        assert!(decode_instruction_amd64(&[ 0x0f, 0xff ], 0, 0).is_err())
+    }
+
+    #[test]
+    fn decode_relative_to_location() {
+        let ( text , length ) = decode_instruction_amd64(&[ 0xe8, 0x00, 0x01, 0x00, 0x00 ], 0x20, 0).unwrap();
+        assert_eq!(*text, String::from("CALL 125H"));
+        assert_eq!(length, 5)
+    }
+
+    #[test]
+    fn listing_with_unknown_bytes() {
+        let text = listing_amd64(&[ 0x55, 0x0f, 0xc3 ], 0x10);
+        assert_eq!(text, format!("{:08X}  {:<30} {}\r\n{:08X}  {:<30} {}\r\n{:08X}  {:<30} {}\r\n", 0x10, "55", "PUSH RBP", 0x11, "0F", "DB 0FH", 0x12, "C3", "RET"))
+    }
+
+    /// Sample operand of kind, picked by pseudo random seed
+    fn sample_operand(kind: OperandKind, size: u32, seed: &mut u64) -> AMD64Operand {
+        *seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        let random = *seed >> 33;
+        let number = (random % 16) as u8;
+        let memory = |bits: Option<u32>| {
+            let base = match random % 5 { 0 => None, 1 => Some( RegisterAMD64::General(number, 32) ), _ => Some( RegisterAMD64::General(number, 64) ) };
+            let width = match &base { Some( RegisterAMD64::General( _ , 32 ) ) => 32, _ => 64 };
+            /* Index without base and scale one is written as base register in assembler text */
+            let index = match ( ( random >> 4 ) % 3 , &base ) {
+                ( 0 , _ ) => None,
+                ( _ , None ) => Some( ( RegisterAMD64::General(number & 7 | 8, width), 8 ) ),
+                _ => Some( ( RegisterAMD64::General([ 0, 1, 2, 3, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15 ][(random >> 6) as usize % 15], width), [ 1u8, 2, 4, 8 ][(random >> 8) as usize % 4] ) )
+            };
+            let displacement = [ 0i64, 8, -128, 0x1000, -0x12345 ][(random >> 10) as usize % 5];
+            AMD64Operand::Memory(bits, base, index, displacement)
+        };
+
+        match kind {
+            Al => AMD64Operand::Register(RegisterAMD64::General(0, 8)),
+            Ax => AMD64Operand::Register(RegisterAMD64::General(0, 16)),
+            Eax => AMD64Operand::Register(RegisterAMD64::General(0, 32)),
+            Rax => AMD64Operand::Register(RegisterAMD64::General(0, 64)),
+            Cl => AMD64Operand::Register(RegisterAMD64::General(1, 8)),
+            Dx => AMD64Operand::Register(RegisterAMD64::General(2, 16)),
+            St0 => AMD64Operand::Register(RegisterAMD64::X87(0)),
+            One => AMD64Operand::Immediate(1),
+            R8 => AMD64Operand::Register(RegisterAMD64::General(number, 8)),
+            R16 => AMD64Operand::Register(RegisterAMD64::General(number, 16)),
+            R32 | B32 => AMD64Operand::Register(RegisterAMD64::General(number, 32)),
+            R64 | B64 => AMD64Operand::Register(RegisterAMD64::General(number, 64)),
+            RM8 | RM16 | RM32 | RM64 if random & 1 == 0 => AMD64Operand::Register(RegisterAMD64::General(number, memory_size(kind).unwrap())),
+            Xmm | XmmB => AMD64Operand::Register(RegisterAMD64::Xmm(number)),
+            XmmM32 | XmmM64 | XmmM128 if random & 1 == 0 => AMD64Operand::Register(RegisterAMD64::Xmm(number)),
+            Ymm => AMD64Operand::Register(RegisterAMD64::Ymm(number)),
+            YmmM256 if random & 1 == 0 => AMD64Operand::Register(RegisterAMD64::Ymm(number)),
+            Zmm => AMD64Operand::Register(RegisterAMD64::Zmm(number)),
+            ZmmM512 if random & 1 == 0 => AMD64Operand::Register(RegisterAMD64::Zmm(number)),
+            K | KB => AMD64Operand::Register(RegisterAMD64::Mask(number & 7)),
+            KM16 if random & 1 == 0 => AMD64Operand::Register(RegisterAMD64::Mask(number & 7)),
+            St => AMD64Operand::Register(RegisterAMD64::X87(number & 7)),
+            Sreg => AMD64Operand::Register(RegisterAMD64::Segment(number % 6)),
+            Creg => AMD64Operand::Register(RegisterAMD64::Control([ 0, 2, 3, 4, 8 ][number as usize % 5])),
+            Dreg => AMD64Operand::Register(RegisterAMD64::Debug(number & 7)),
+            I8 => AMD64Operand::Immediate((random % 256) as i64),
+            SI8 => AMD64Operand::Immediate((random % 256) as i64 - 128),
+            I16 => AMD64Operand::Immediate((random % 65536) as i64),
+            I32 if size == 64 || size == 0 => AMD64Operand::Immediate(random as i32 as i64),
+            I32 => AMD64Operand::Immediate(random as u32 as i64),
+            I64 => AMD64Operand::Immediate(0x1234_5678_9abc_0000 + random as i64),
+            Rel8 => AMD64Operand::Immediate(0x20 + (random % 64) as i64),
+            Rel32 => AMD64Operand::Immediate(0x10_0000 + random as i64 % 0x1000),
+            _ => memory(memory_size(kind))
+        }
+    }
+
+    /// Registers 16 - 31, opmask, zeroing and broadcast for sample operands of AVX-512 instruction form
+    fn sample_evex(form: &InstructionForm, operands: &mut [AMD64Operand], seed: &mut u64) {
+        let broadcast = match form.vector { Evex( x ) => x, _ => return };
+        *seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        let random = *seed >> 33;
+
+        for ( i , operand ) in operands.iter_mut().enumerate() {
+            match operand {
+                AMD64Operand::Register(RegisterAMD64::Xmm(n) | RegisterAMD64::Ymm(n) | RegisterAMD64::Zmm(n)) if random & (1 << i) != 0 => *n += 16,
+                AMD64Operand::Memory( _ , base, index, displacement ) if broadcast != 0 && random & 8 != 0 && memory_size(form.operands[i]) != Some( broadcast ) => {
+                    let count = (128 << vector_length(form)) / broadcast;
+                    *operand = AMD64Operand::Broadcast(Box::new(AMD64Operand::Memory(Some( broadcast ), base.clone(), index.clone(), *displacement)), count)
+                },
+                _ => ()
+            }
+        }
+        let zeroing = random & 16 != 0 && matches!(operands[0], AMD64Operand::Register(RegisterAMD64::Xmm( _ ) | RegisterAMD64::Ymm( _ ) | RegisterAMD64::Zmm( _ )));
+        match ( random >> 5 ) % 8 {
+            0 => (),
+            k => operands[0] = AMD64Operand::Masked(Box::new(operands[0].clone()), k as u8, zeroing)
+        }
+    }
+
+    /// Every instruction form encoded with sample operands, decoded and assembled again gives the same bytes
+    #[test]
+    fn round_trip_encode_decode_assemble() {
+        use crate::amd64_assembler::{ AssemblerAMD64, AssemblerAMD64Methods };
+        use crate::inline_assembler::InlineAssembler;
+        use crate::amd64_instruction_set_neo::{ INSTRUCTIONS_AMD64, CPU_PROTECTED };
+
+        let flags = CPU_PRESCOTT | CPU_AMD64 | CPU_PRIVILEGED | CPU_PROTECTED | CPU_AVX512;
+        let mut seed : u64 = 1;
+        let mut checked = 0;
+
+        for form in INSTRUCTIONS_AMD64.iter() {
+            for _ in 0 .. 4 {
+                let mut operands : Vec<AMD64Operand> = form.operands.iter().map(|k| sample_operand(*k, form.size, &mut seed)).collect();
+                sample_evex(form, &mut operands, &mut seed);
+                let code = match encode_instruction_amd64(Box::new(String::from(form.mnemonic)), Box::new(operands.clone()), 0, flags) {
+                    Ok( x ) => x,
+                    _ => continue
+                };
+                let ( text , length ) = match decode_instruction_amd64(&code, 0, 0) {
+                    Ok( x ) => x,
+                    Err( e ) => panic!("{} {:?} gives {:02X?} which does not decode: {}", form.mnemonic, operands, code, e)
+                };
+                assert_eq!(length, code.len(), "{} {:?} decoded as '{}'", form.mnemonic, operands, text);
+
+                let source = format!("{{ SYSTEM.CPU_PRESCOTT, SYSTEM.CPU_AMD64, SYSTEM.PRIVILEGED, SYSTEM.CPU_AVX512 }}\n{}", text);
+                let mut assembler = AssemblerAMD64::new(source.chars().collect(), 0);
+                match assembler.assemble() {
+                    Ok( x ) => assert_eq!(*x, *code, "'{}' from {} {:?}", text, form.mnemonic, operands),
+                    Err( e ) => panic!("'{}' from {} {:?} does not assemble: {}", text, form.mnemonic, operands, e.message)
+                }
+                checked += 1
+            }
+        }
+
+        assert!(checked > INSTRUCTIONS_AMD64.len() * 2)
+    }
+}
@@ -0,0 +1,1396 @@
+// ActiveOberon Compiler, a native ARM v8 & X86-64 compiler & Risc V / linker / builder utility.
+// Written by Richard Magnor Stenbro. Licensed under GPL v3
+// Inline assembler for ARM v8 module for compiling and linking of projects written in ActiveOberon language
+
+use std::collections::{HashMap, HashSet};
+use crate::diagnostics::{Diagnostic, DiagnosticMethods, E_ASSEMBLER_SYNTAX, E_ASSEMBLER_CPU_FLAG, E_ASSEMBLER_UNDEFINED, E_ASSEMBLER_DUPLICATE, E_ASSEMBLER_OPERAND, E_DIVISION_BY_ZERO};
+use crate::inline_assembler::{InlineAssembler, OberonVariable, Relocation};
+use crate::arm64_instruction_set_neo::{ARM64Operand, RegisterARM64, RegisterARM64Methods, ModifierARM64, IndexingARM64, register_arm64, condition_arm64, modifier_arm64, barrier_arm64, system_register_arm64, encode_instruction_arm64, CpuFlags, CPU_ARMV8_1, CPU_ARMV8_2, CPU_FP, CPU_NEON, CPU_SVE};
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum ARM64Symbols {
+    Ident(u32, u32, Box<String>),
+    Label(u32, u32, Box<String>),
+    Number(u32, u32, i64),
+    Float(u32, u32, f64),
+    String_(u32, u32, Box<String>),
+    Period(u32, u32),
+    Comma(u32, u32),
+    Plus(u32, u32),
+    Minus(u32, u32),
+    Times(u32, u32),
+    Div(u32, u32),
+    Modulo(u32, u32),
+    Negate(u32, u32),
+    LeftParen(u32, u32),
+    RightParen(u32, u32),
+    LeftBracket(u32, u32),
+    RightBracket(u32, u32),
+    LeftCurly(u32, u32),
+    RightCurly(u32, u32),
+    Hash(u32, u32),
+    Exclamation(u32, u32),
+    Dollar(u32, u32),
+    NewLine(u32, u32),
+    EndOfFile(u32),
+    None
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum ARM64Node {
+    None,
+    Number(u32, u32, i64),
+    Float(u32, u32, f64),
+    String(u32, u32, Box<String>),
+    Ident(u32, u32, Box<String>),
+    Times(u32, u32, Box<ARM64Node>, Box<ARM64Symbols>, Box<ARM64Node>),
+    Div(u32, u32, Box<ARM64Node>, Box<ARM64Symbols>, Box<ARM64Node>),
+    Modulo(u32, u32, Box<ARM64Node>, Box<ARM64Symbols>, Box<ARM64Node>),
+    Minus(u32, u32, Box<ARM64Node>, Box<ARM64Symbols>, Box<ARM64Node>),
+    Plus(u32, u32, Box<ARM64Node>, Box<ARM64Symbols>, Box<ARM64Node>),
+    UnaryMinus(u32, u32, Box<ARM64Symbols>, Box<ARM64Node>),
+    UnaryPlus(u32, u32, Box<ARM64Symbols>, Box<ARM64Node>),
+    Negate(u32, u32, Box<ARM64Symbols>, Box<ARM64Node>),
+    Location(u32, u32),
+    Immediate(u32, u32, Box<ARM64Node>),                                      /* '#' followed by expression */
+    Modifier(u32, u32, ModifierARM64, Box<ARM64Node>),                        /* Shift or extend with amount, like 'LSL #3' */
+    Memory(u32, u32, Box<ARM64Node>, Box<ARM64Node>, IndexingARM64),          /* Base register and offset, like '[X1, #8]!' */
+    MemoryRegister(u32, u32, Box<ARM64Node>, Box<ARM64Node>, Box<ARM64Node>), /* Base and index register with modifier, like '[X1, X2, LSL #3]' */
+    Instruction(u32, u32, Box<String>, Box<Vec<Box<ARM64Node>>>),
+    Label(u32, u32, Box<String>),
+    Data(u32, u32, u32, Box<Vec<Box<ARM64Node>>>),     /* DB, DW, DD and DQ with size of each item in bytes */
+    Repeat(u32, u32, Box<ARM64Node>, Box<ARM64Node>),  /* TIMES count line */
+    Align(u32, u32, Box<ARM64Node>)
+}
+
+/// Start and end position of node inside assembler text
+fn node_span(node: &ARM64Node) -> (u32, u32) {
+    match node {
+        ARM64Node::Number(s, e, _) |
+        ARM64Node::Float(s, e, _) |
+        ARM64Node::String(s, e, _) |
+        ARM64Node::Ident(s, e, _) |
+        ARM64Node::Times(s, e, _ , _ , _ ) |
+        ARM64Node::Div(s, e, _ , _ , _ ) |
+        ARM64Node::Modulo(s, e, _ , _ , _ ) |
+        ARM64Node::Minus(s, e, _ , _ , _ ) |
+        ARM64Node::Plus(s, e, _ , _ , _ ) |
+        ARM64Node::UnaryMinus(s, e, _ , _ ) |
+        ARM64Node::UnaryPlus(s, e, _ , _ ) |
+        ARM64Node::Negate(s, e, _ , _ ) |
+        ARM64Node::Location(s, e) |
+        ARM64Node::Immediate(s, e, _ ) |
+        ARM64Node::Modifier(s, e, _ , _ ) |
+        ARM64Node::Memory(s, e, _ , _ , _ ) |
+        ARM64Node::MemoryRegister(s, e, _ , _ , _ ) |
+        ARM64Node::Instruction(s, e, _ , _ ) |
+        ARM64Node::Label(s, e, _ ) |
+        ARM64Node::Data(s, e, _ , _ ) |
+        ARM64Node::Repeat(s, e, _ , _ ) |
+        ARM64Node::Align(s, e, _ ) => (*s, *e),
+        ARM64Node::None => (0, 0)
+    }
+}
+
+/// Value of floating point immediate like '#1.5' or '#-0.25', none when expression is not a floating point number
+fn float_value(node: &ARM64Node) -> Option<f64> {
+    match node {
+        ARM64Node::Float( _ , _ , x ) => Some( *x ),
+        ARM64Node::UnaryMinus( _ , _ , _ , x ) => float_value(x).map(|y| -y),
+        ARM64Node::UnaryPlus( _ , _ , _ , x ) => float_value(x),
+        _ => None
+    }
+}
+
+/// Bytes read or written by load and store, taken from mnemonic or size of transfer register
+fn access_size(mnemonic: &str, operands: &[ARM64Operand]) -> Option<u32> {
+    match mnemonic {
+        "LDRB" | "STRB" | "LDRSB" | "LDURB" | "STURB" | "LDURSB" => Some( 1 ),
+        "LDRH" | "STRH" | "LDRSH" | "LDURH" | "STURH" | "LDURSH" => Some( 2 ),
+        "LDRSW" | "LDURSW" => Some( 4 ),
+        "LDR" | "STR" | "LDUR" | "STUR" => {
+            match operands.first() {
+                Some( ARM64Operand::Register( r ) ) => Some( r.bits() / 8 ),
+                _ => None
+            }
+        },
+        _ => None
+    }
+}
+
+
+
+pub trait AssemblerARM64Methods {
+    fn new(text: Vec<char>, offset_position: u32) -> Self;
+    fn get_position(&self) -> u32;
+    fn get_char(&mut self) -> char;
+    fn peek_char(&self) -> char;
+    fn next_char(&mut self) -> ();
+    fn skip_whitespace(&mut self) -> ();
+    fn get_ident(&mut self) -> Box<String>;
+    fn get_number(&mut self, start: u32) -> Result<Box<ARM64Symbols>, Box<Diagnostic>>;
+    fn get_string(&mut self) -> Box<String>;
+    fn get_symbol(&mut self) -> Result<Box<ARM64Symbols>, Box<Diagnostic>>;
+    fn factor(&mut self) -> Result<Box<ARM64Node>, Box<Diagnostic>>;
+    fn term(&mut self) -> Result<Box<ARM64Node>, Box<Diagnostic>>;
+    fn expression(&mut self) -> Result<Box<ARM64Node>, Box<Diagnostic>>;
+    fn modifier(&mut self) -> Result<Box<ARM64Node>, Box<Diagnostic>>;
+    fn memory(&mut self) -> Result<Box<ARM64Node>, Box<Diagnostic>>;
+    fn operand(&mut self) -> Result<Box<ARM64Node>, Box<Diagnostic>>;
+    fn instruction(&mut self, start: u32, end: u32, mnemonic: Box<String>) -> Result<Box<ARM64Node>, Box<Diagnostic>>;
+    fn statement(&mut self, start: u32, end: u32, keyword: Box<String>) -> Result<Box<ARM64Node>, Box<Diagnostic>>;
+    fn end_of_line(&self, node: Box<ARM64Node>) -> Result<Box<ARM64Node>, Box<Diagnostic>>;
+    fn evaluate(&self, node: &ARM64Node) -> Result<i64, Box<Diagnostic>>;
+    fn register(&self, node: &ARM64Node) -> Result<RegisterARM64, Box<Diagnostic>>;
+    fn resolve_operand(&self, node: &ARM64Node) -> Result<ARM64Operand, Box<Diagnostic>>;
+    fn define(&mut self, name: &str, value: i64, is_label: bool, start: u32, end: u32) -> Result<(), Box<Diagnostic>>;
+    fn qualified(&self, name: &str) -> Box<String>;
+    fn emit(&mut self, line: &ARM64Node, code: &mut Vec<u8>) -> Result<(), Box<Diagnostic>>;
+    fn count(&self, node: &ARM64Node) -> Result<i64, Box<Diagnostic>>;
+    fn is_defined(&self, name: &str) -> bool;
+    fn variable(&self, name: &str) -> Option<&OberonVariable>;
+    fn sized_variable(&self, node: &ARM64Node) -> Option<(String, u32)>;
+
+    fn advance(&mut self) -> ();
+    fn error(&self, code: &'static str, message: String) -> Box<Diagnostic>;
+    fn error_at(&self, code: &'static str, message: String, start: u32, end: u32) -> Box<Diagnostic>;
+}
+
+pub struct AssemblerARM64 {
+    buffer: Vec<char>,	/* Sourcecode as a vector of chars */
+    offset_position: u32,	/* Position of code block in Oberon source file */
+    start_pos: u32,		/* Start of current analyzed symbol */
+    index: u32,			/* Position into vector */
+    symbol: Result<Box<ARM64Symbols>, Box<Diagnostic>>,
+    location: i64,		/* Value of '$', offset of current instruction from start of code block */
+    labels: HashMap<String, i64>,
+    constants: HashMap<String, i64>,	/* Names defined with 'EQU' */
+    scope: Box<String>,		/* Last label not starting with '@', local labels are only visible until next such label */
+    pending: HashSet<String>,	/* Labels not yet reached in first pass, they are assumed to be at current location */
+    flags: CpuFlags,		/* CPU flags of code block */
+    variables: HashMap<String, OberonVariable>,	/* Oberon variables visible in code block */
+    relocations: Vec<Relocation>
+}
+
+impl AssemblerARM64Methods for AssemblerARM64 {
+    fn new(text: Vec<char>, offset_position: u32) -> Self {
+        AssemblerARM64 {
+            buffer: text,
+            offset_position,
+            start_pos: 0,
+            index: 0,
+            symbol: Ok(Box::new(ARM64Symbols::None)),
+            location: 0,
+            labels: HashMap::new(),
+            constants: HashMap::new(),
+            scope: Box::new(String::new()),
+            pending: HashSet::new(),
+            flags: CPU_NEON,
+            variables: HashMap::new(),
+            relocations: Vec::new()
+        }
+    }
+
+    fn get_position(&self) -> u32 {
+        self.index
+    }
+
+    fn get_char(&mut self) -> char {
+        match self.buffer.get(self.index as usize) {
+            Some(x) => *x,
+            _ => '\0'
+        }
+    }
+
+    /// Character after the current one, used for '//' comments and fractions of numbers
+    fn peek_char(&self) -> char {
+        match self.buffer.get(self.index as usize + 1) {
+            Some(x) => *x,
+            _ => '\0'
+        }
+    }
+
+    fn next_char(&mut self) {
+        if (self.index as usize) < self.buffer.len() {
+            self.index += 1;
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        loop {
+            match self.get_char() {
+                ' ' | '\t' => {
+                    self.next_char();
+                    continue
+                }, /* Remove whitespace */
+                '/' if self.peek_char() != '/' => break,
+                ';' | '/' => { /* Remove comments, starting with ';' or '//' */
+                    loop {
+                        match self.get_char() {
+                            '\r' | '\n' | '\0' => break,
+                            _ => self.next_char()
+                        }
+                    }
+                    break
+                },
+                _ => break
+            }
+        }
+    }
+
+    fn get_ident(&mut self) -> Box<String> {
+        let mut text = String::new();
+        while let '0' ..= '9' | 'a' ..= 'z' | 'A' ..= 'Z' | '_' = self.get_char() {
+            text.push(self.get_char());
+            self.next_char();
+        }
+
+        Box::new(text)
+    }
+
+    /// Decimal number, hexadecimal number like '0x1F' or '1FH', or floating point number like '1.5'
+    fn get_number(&mut self, start: u32) -> Result<Box<ARM64Symbols>, Box<Diagnostic>> {
+        let mut text = String::new();
+
+        while let '0' ..= '9' | 'a' ..= 'z' | 'A' ..= 'Z' | '_' = self.get_char() {
+            text.push(self.get_char());
+            self.next_char();
+        }
+
+        /* Fraction and exponent of floating point number */
+        if self.get_char() == '.' && self.peek_char().is_ascii_digit() && text.chars().all(|c| c.is_ascii_digit()) {
+            text.push('.');
+            self.next_char();
+            loop {
+                match self.get_char() {
+                    '0' ..= '9' | 'e' | 'E' => {
+                        text.push(self.get_char());
+                        self.next_char();
+                    },
+                    '+' | '-' if text.ends_with(['e', 'E']) => {
+                        text.push(self.get_char());
+                        self.next_char();
+                    },
+                    _ => break
+                }
+            }
+            return match text.parse::<f64>() {
+                Ok( x ) => Ok(Box::new(ARM64Symbols::Float(start, self.get_position(), x))),
+                _ => Err(self.error(E_ASSEMBLER_SYNTAX, format!("Invalid floating point number '{}' in assembler code", text)))
+            }
+        }
+
+        let upper = text.to_uppercase();
+        let value = match ( upper.strip_prefix("0X") , upper.strip_suffix('H') ) {
+            ( Some( x ) , _ ) | ( _ , Some( x ) ) => u64::from_str_radix(x, 16),
+            _ => upper.parse::<u64>()
+        };
+
+        match value {
+            Ok( x ) => Ok(Box::new(ARM64Symbols::Number(start, self.get_position(), x as i64))),
+            _ => Err(self.error(E_ASSEMBLER_SYNTAX, format!("Invalid number '{}' in assembler code", text)))
+        }
+    }
+
+    fn get_string(&mut self) -> Box<String> {
+        let mut text = String::new();
+        text.push(self.get_char());
+        self.next_char();
+
+        loop {
+            match self.get_char() {
+                '\'' => {
+                    text.push(self.get_char());
+                    self.next_char();
+                    break
+                },
+                '\0' => break,
+                _ => {
+                    text.push(self.get_char())
+                }
+            }
+            self.next_char()
+        }
+
+        Box::new(text)
+    }
+
+    /// Assembler lexer for A64 Syntax
+    fn get_symbol(&mut self) -> Result<Box<ARM64Symbols>, Box<Diagnostic>> {
+        self.skip_whitespace();
+        let start_pos = self.get_position();
+        self.start_pos = start_pos;
+
+        match self.get_char() {
+            'a' ..= 'z' | 'A' ..= 'Z' | '_' | '@' => {
+                /* Local label like '@loop' starts with '@' */
+                let local = self.get_char() == '@';
+                if local {
+                    self.next_char()
+                }
+                let symbol = match local {
+                    true => Box::new(format!("@{}", self.get_ident())),
+                    _ => self.get_ident()
+                };
+                if symbol.as_str() == "@" {
+                    return Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Expecting name of local label after '@'")))
+                }
+                let end = self.get_position();
+                self.skip_whitespace();
+                match self.get_char() {
+                    ':' => {
+                        self.next_char();
+                        Ok(Box::new(ARM64Symbols::Label(start_pos, self.get_position(), symbol)))
+                    },
+                    _ => Ok(Box::new(ARM64Symbols::Ident(start_pos, end, symbol)))
+                }
+            },
+            '0' ..= '9' => self.get_number(start_pos),
+            '\'' => {
+                let symbol = self.get_string();
+                Ok(Box::new(ARM64Symbols::String_(start_pos, self.get_position(), symbol)))
+            },
+            '\0' => Ok(Box::new(ARM64Symbols::EndOfFile(self.get_position()))),
+            '\r' => {
+                self.next_char();
+                if self.get_char() == '\n' {
+                    self.next_char()
+                }
+                Ok(Box::new(ARM64Symbols::NewLine(start_pos, self.get_position())))
+            },
+            '.' => {
+                self.next_char();
+                Ok(Box::new(ARM64Symbols::Period(start_pos, self.get_position())))
+            },
+            ',' => {
+                self.next_char();
+                Ok(Box::new(ARM64Symbols::Comma(start_pos, self.get_position())))
+            },
+            '+' => {
+                self.next_char();
+                Ok(Box::new(ARM64Symbols::Plus(start_pos, self.get_position())))
+            },
+            '-' => {
+                self.next_char();
+                Ok(Box::new(ARM64Symbols::Minus(start_pos, self.get_position())))
+            },
+            '*' => {
+                self.next_char();
+                Ok(Box::new(ARM64Symbols::Times(start_pos, self.get_position())))
+            },
+            '/' => {
+                self.next_char();
+                Ok(Box::new(ARM64Symbols::Div(start_pos, self.get_position())))
+            },
+            '%' => {
+                self.next_char();
+                Ok(Box::new(ARM64Symbols::Modulo(start_pos, self.get_position())))
+            },
+            '~' => {
+                self.next_char();
+                Ok(Box::new(ARM64Symbols::Negate(start_pos, self.get_position())))
+            },
+            '(' => {
+                self.next_char();
+                Ok(Box::new(ARM64Symbols::LeftParen(start_pos, self.get_position())))
+            },
+            ')' => {
+                self.next_char();
+                Ok(Box::new(ARM64Symbols::RightParen(start_pos, self.get_position())))
+            },
+            '[' => {
+                self.next_char();
+                Ok(Box::new(ARM64Symbols::LeftBracket(start_pos, self.get_position())))
+            },
+            ']' => {
+                self.next_char();
+                Ok(Box::new(ARM64Symbols::RightBracket(start_pos, self.get_position())))
+            },
+            '{' => {
+                self.next_char();
+                Ok(Box::new(ARM64Symbols::LeftCurly(start_pos, self.get_position())))
+            },
+            '}' => {
+                self.next_char();
+                Ok(Box::new(ARM64Symbols::RightCurly(start_pos, self.get_position())))
+            },
+            '#' => {
+                self.next_char();
+                Ok(Box::new(ARM64Symbols::Hash(start_pos, self.get_position())))
+            },
+            '!' => {
+                self.next_char();
+                Ok(Box::new(ARM64Symbols::Exclamation(start_pos, self.get_position())))
+            },
+            '$' => {
+                self.next_char();
+                Ok(Box::new(ARM64Symbols::Dollar(start_pos, self.get_position())))
+            },
+            '\n' => {
+                self.next_char();
+                Ok(Box::new(ARM64Symbols::NewLine(start_pos, self.get_position())))
+            },
+            _ => Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Invalid symbol in inline assembler")))
+        }
+    }
+
+    /// factor = number | float | string | '$' | ident | '(' expression ')' | ( '-' | '+' | '~' ) factor
+    fn factor(&mut self) -> Result<Box<ARM64Node>, Box<Diagnostic>> {
+        match *self.symbol.clone()? {
+            ARM64Symbols::Number(s, e, n) => {
+                self.advance();
+                Ok(Box::new(ARM64Node::Number(s, e, n)))
+            },
+            ARM64Symbols::Float(s, e, x) => {
+                self.advance();
+                Ok(Box::new(ARM64Node::Float(s, e, x)))
+            },
+            ARM64Symbols::String_(s, e, t) => {
+                self.advance();
+                Ok(Box::new(ARM64Node::String(s, e, t)))
+            },
+            ARM64Symbols::Ident(s, e, t) => {
+                self.advance();
+                Ok(Box::new(ARM64Node::Ident(s, e, self.qualified(t.as_str()))))
+            },
+            ARM64Symbols::Dollar(s, e) => {
+                self.advance();
+                Ok(Box::new(ARM64Node::Location(s, e)))
+            },
+            ARM64Symbols::LeftParen( _ , _ ) => {
+                self.advance();
+                let node = self.expression()?;
+                match *self.symbol.clone()? {
+                    ARM64Symbols::RightParen( _ , _ ) => {
+                        self.advance();
+                        Ok(node)
+                    },
+                    _ => Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Expecting ')' in expression")))
+                }
+            },
+            ARM64Symbols::Minus(s, _ ) => {
+                let symbol = self.symbol.clone()?;
+                self.advance();
+                let right = self.factor()?;
+                Ok(Box::new(ARM64Node::UnaryMinus(s, node_span(&right).1, symbol, right)))
+            },
+            ARM64Symbols::Plus(s, _ ) => {
+                let symbol = self.symbol.clone()?;
+                self.advance();
+                let right = self.factor()?;
+                Ok(Box::new(ARM64Node::UnaryPlus(s, node_span(&right).1, symbol, right)))
+            },
+            ARM64Symbols::Negate(s, _ ) => {
+                let symbol = self.symbol.clone()?;
+                self.advance();
+                let right = self.factor()?;
+                Ok(Box::new(ARM64Node::Negate(s, node_span(&right).1, symbol, right)))
+            },
+            _ => Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Expecting operand in expression")))
+        }
+    }
+
+    /// term = factor { ( '*' | '/' | '%' ) factor }
+    fn term(&mut self) -> Result<Box<ARM64Node>, Box<Diagnostic>> {
+        let mut left = self.factor()?;
+
+        loop {
+            let symbol = self.symbol.clone()?;
+            match *symbol {
+                ARM64Symbols::Times( _ , _ ) |
+                ARM64Symbols::Div( _ , _ ) |
+                ARM64Symbols::Modulo( _ , _ ) => {
+                    self.advance();
+                    let right = self.factor()?;
+                    let ( s , e ) = ( node_span(&left).0, node_span(&right).1 );
+                    left = match *symbol {
+                        ARM64Symbols::Times( _ , _ ) => Box::new(ARM64Node::Times(s, e, left, symbol, right)),
+                        ARM64Symbols::Div( _ , _ ) => Box::new(ARM64Node::Div(s, e, left, symbol, right)),
+                        _ => Box::new(ARM64Node::Modulo(s, e, left, symbol, right))
+                    }
+                },
+                _ => break
+            }
+        }
+
+        Ok(left)
+    }
+
+    /// expression = term { ( '+' | '-' ) term }
+    fn expression(&mut self) -> Result<Box<ARM64Node>, Box<Diagnostic>> {
+        let mut left = self.term()?;
+
+        loop {
+            let symbol = self.symbol.clone()?;
+            match *symbol {
+                ARM64Symbols::Plus( _ , _ ) |
+                ARM64Symbols::Minus( _ , _ ) => {
+                    self.advance();
+                    let right = self.term()?;
+                    let ( s , e ) = ( node_span(&left).0, node_span(&right).1 );
+                    left = match *symbol {
+                        ARM64Symbols::Plus( _ , _ ) => Box::new(ARM64Node::Plus(s, e, left, symbol, right)),
+                        _ => Box::new(ARM64Node::Minus(s, e, left, symbol, right))
+                    }
+                },
+                _ => break
+            }
+        }
+
+        Ok(left)
+    }
+
+    /// modifier = ( 'LSL' | 'LSR' | 'ASR' | 'ROR' | 'UXTB' | ... | 'SXTX' ) [ [ '#' ] expression ], called at name of shift or extend
+    fn modifier(&mut self) -> Result<Box<ARM64Node>, Box<Diagnostic>> {
+        let ( s , e , kind ) = match *self.symbol.clone()? {
+            ARM64Symbols::Ident( s , e , t ) => {
+                match modifier_arm64(t.as_str()) {
+                    Some( x ) => ( s , e , x ),
+                    _ => return Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Expecting shift or extend in operand")))
+                }
+            },
+            _ => return Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Expecting shift or extend in operand")))
+        };
+        self.advance();
+
+        match *self.symbol.clone()? {
+            ARM64Symbols::Hash( _ , _ ) => {
+                self.advance();
+                let amount = self.expression()?;
+                Ok(Box::new(ARM64Node::Modifier(s, node_span(&amount).1, kind, amount)))
+            },
+            ARM64Symbols::Number( .. ) | ARM64Symbols::LeftParen( .. ) | ARM64Symbols::Ident( .. ) => {
+                let amount = self.expression()?;
+                Ok(Box::new(ARM64Node::Modifier(s, node_span(&amount).1, kind, amount)))
+            },
+            _ => Ok(Box::new(ARM64Node::Modifier(s, e, kind, Box::new(ARM64Node::None))))
+        }
+    }
+
+    /// memory = '[' register [ ',' ( [ '#' ] expression | register [ ',' modifier ] ) ] ']' [ '!' | ',' '#' expression ]
+    fn memory(&mut self) -> Result<Box<ARM64Node>, Box<Diagnostic>> {
+        let start = self.start_pos;
+        self.advance();
+        let base = self.expression()?;
+        let mut index = None;
+        let mut offset = Box::new(ARM64Node::None);
+
+        if let ARM64Symbols::Comma( _ , _ ) = *self.symbol.clone()? {
+            self.advance();
+            match *self.symbol.clone()? {
+                ARM64Symbols::Hash( _ , _ ) => {
+                    self.advance();
+                    offset = self.expression()?
+                },
+                _ => {
+                    let node = self.expression()?;
+                    match &*node {
+                        ARM64Node::Ident( _ , _ , t ) if register_arm64(t.as_str()).is_some() => {
+                            let modifier = match *self.symbol.clone()? {
+                                ARM64Symbols::Comma( _ , _ ) => {
+                                    self.advance();
+                                    self.modifier()?
+                                },
+                                _ => Box::new(ARM64Node::None)
+                            };
+                            index = Some( ( node , modifier ) )
+                        },
+                        _ => offset = node
+                    }
+                }
+            }
+        }
+
+        let end = match *self.symbol.clone()? {
+            ARM64Symbols::RightBracket( _ , e ) => {
+                self.advance();
+                e
+            },
+            _ => return Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Expecting ']' in memory operand")))
+        };
+
+        match ( index , *self.symbol.clone()? ) {
+            ( Some( ( x , modifier ) ) , _ ) => Ok(Box::new(ARM64Node::MemoryRegister(start, end, base, x, modifier))),
+            ( None , ARM64Symbols::Exclamation( _ , e ) ) => {
+                self.advance();
+                Ok(Box::new(ARM64Node::Memory(start, e, base, offset, IndexingARM64::PreIndex)))
+            },
+            ( None , ARM64Symbols::Comma( _ , _ ) ) if *offset == ARM64Node::None => {
+                /* Immediate after memory operand without offset is post index, like '[X1], #16', else it is next operand */
+                let saved = ( self.index , self.start_pos , self.symbol.clone() );
+                self.advance();
+                match *self.symbol.clone()? {
+                    ARM64Symbols::Hash( _ , _ ) => {
+                        self.advance();
+                        let amount = self.expression()?;
+                        Ok(Box::new(ARM64Node::Memory(start, node_span(&amount).1, base, amount, IndexingARM64::PostIndex)))
+                    },
+                    _ => {
+                        ( self.index , self.start_pos , self.symbol ) = saved;
+                        Ok(Box::new(ARM64Node::Memory(start, end, base, offset, IndexingARM64::Offset)))
+                    }
+                }
+            },
+            _ => Ok(Box::new(ARM64Node::Memory(start, end, base, offset, IndexingARM64::Offset)))
+        }
+    }
+
+    /// operand = '#' expression | memory | modifier | expression
+    fn operand(&mut self) -> Result<Box<ARM64Node>, Box<Diagnostic>> {
+        match *self.symbol.clone()? {
+            ARM64Symbols::Hash( s , _ ) => {
+                self.advance();
+                let node = self.expression()?;
+                Ok(Box::new(ARM64Node::Immediate(s, node_span(&node).1, node)))
+            },
+            ARM64Symbols::LeftBracket( _ , _ ) => self.memory(),
+            ARM64Symbols::Ident( _ , _ , t ) if modifier_arm64(t.as_str()).is_some() => self.modifier(),
+            _ => self.expression()
+        }
+    }
+
+    /// instruction = mnemonic [ '.' condition ] [ operand { ',' operand } ] ( newline | end of file ), called after mnemonic is read
+    fn instruction(&mut self, start: u32, end: u32, mnemonic: Box<String>) -> Result<Box<ARM64Node>, Box<Diagnostic>> {
+        let mut end = end;
+        let mut operands = Box::new(Vec::<Box<ARM64Node>>::new());
+        let mut mnemonic = Box::new(mnemonic.to_uppercase());
+
+        /* Conditional branch like 'B.NE', condition follows mnemonic without space */
+        match *self.symbol.clone()? {
+            ARM64Symbols::Period( s , _ ) if s == end => {
+                self.advance();
+                match *self.symbol.clone()? {
+                    ARM64Symbols::Ident( s , e , t ) if s == end + 1 => {
+                        mnemonic = Box::new(format!("{}.{}", mnemonic, t.to_uppercase()));
+                        end = e;
+                        self.advance()
+                    },
+                    _ => return Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Expecting condition after '.' in mnemonic")))
+                }
+            },
+            _ => ()
+        }
+
+        match *self.symbol.clone()? {
+            ARM64Symbols::NewLine( _ , _ ) | ARM64Symbols::EndOfFile( _ ) => (),
+            _ => {
+                loop {
+                    let node = self.operand()?;
+                    end = node_span(&node).1;
+                    operands.push(node);
+                    match *self.symbol.clone()? {
+                        ARM64Symbols::Comma( _ , _ ) => self.advance(),
+                        _ => break
+                    }
+                }
+            }
+        }
+
+        match *self.symbol.clone()? {
+            ARM64Symbols::NewLine( _ , _ ) | ARM64Symbols::EndOfFile( _ ) => Ok(Box::new(ARM64Node::Instruction(start, end, mnemonic, operands))),
+            _ => Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Expecting end of line after instruction")))
+        }
+    }
+
+    /// statement = directive | 'TIMES' expression statement | instruction, called after first identifier is read
+    fn statement(&mut self, start: u32, end: u32, keyword: Box<String>) -> Result<Box<ARM64Node>, Box<Diagnostic>> {
+        match keyword.to_uppercase().as_str() {
+            "ALIGN" => {
+                let node = self.expression()?;
+                self.end_of_line(Box::new(ARM64Node::Align(start, node_span(&node).1, node)))
+            },
+            "DB" | "DW" | "DD" | "DQ" => {
+                let size = match keyword.to_uppercase().as_str() {
+                    "DB" => 1,
+                    "DW" => 2,
+                    "DD" => 4,
+                    _ => 8
+                };
+                let mut items = Box::new(Vec::<Box<ARM64Node>>::new());
+                loop {
+                    items.push(self.expression()?);
+                    match *self.symbol.clone()? {
+                        ARM64Symbols::Comma( _ , _ ) => self.advance(),
+                        _ => break
+                    }
+                }
+                let end = node_span(&items[items.len() - 1]).1;
+                self.end_of_line(Box::new(ARM64Node::Data(start, end, size, items)))
+            },
+            "TIMES" => {
+                let count = self.expression()?;
+                match *self.symbol.clone()? {
+                    ARM64Symbols::Ident(s, e, t) => {
+                        self.advance();
+                        let line = self.statement(s, e, t)?;
+                        Ok(Box::new(ARM64Node::Repeat(start, node_span(&line).1, count, line)))
+                    },
+                    _ => Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Expecting instruction or data after 'TIMES' count")))
+                }
+            },
+            _ => self.instruction(start, end, keyword)
+        }
+    }
+
+    /// Directive node when it is followed by end of line, else a syntax error
+    fn end_of_line(&self, node: Box<ARM64Node>) -> Result<Box<ARM64Node>, Box<Diagnostic>> {
+        match *self.symbol.clone()? {
+            ARM64Symbols::NewLine( _ , _ ) | ARM64Symbols::EndOfFile( _ ) => Ok(node),
+            _ => Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Expecting end of line after directive")))
+        }
+    }
+
+    /// Value of expression, using current location for '$', labels and 'EQU' constants
+    fn evaluate(&self, node: &ARM64Node) -> Result<i64, Box<Diagnostic>> {
+        let ( s , e ) = node_span(node);
+
+        match node {
+            ARM64Node::Number( _ , _ , n ) => Ok(*n),
+            ARM64Node::String( _ , _ , t ) => {
+                let text : Vec<char> = t.trim_matches('\'').chars().collect();
+                if text.len() > 8 {
+                    return Err(self.error_at(E_ASSEMBLER_OPERAND, String::from("String is too long for a numeric value"), s, e))
+                }
+                /* Characters are packed little endian, first character in lowest byte */
+                let mut value : i64 = 0;
+                for ( i , c ) in text.iter().enumerate() {
+                    value |= ((*c as u32 & 0xff) as i64) << (8 * i)
+                }
+                Ok(value)
+            },
+            ARM64Node::Location( _ , _ ) => Ok(self.location),
+            ARM64Node::Ident( _ , _ , t ) => {
+                if register_arm64(t.as_str()).is_some() {
+                    return Err(self.error_at(E_ASSEMBLER_OPERAND, format!("Register '{}' is not allowed in expression", t), s, e))
+                }
+                if let Some( x ) = self.constants.get(t.as_str()) {
+                    return Ok(*x)
+                }
+                if self.pending.contains(t.as_str()) {
+                    return Ok(self.location)
+                }
+                match self.labels.get(t.as_str()) {
+                    Some( x ) => Ok(*x),
+                    _ if self.variables.contains_key(t.as_str()) => {
+                        match self.variables.get(t.as_str()) {
+                            Some( OberonVariable::Frame( offset , _ ) ) => Ok(*offset),
+                            _ => Err(self.error_at(E_ASSEMBLER_OPERAND, format!("Module variable '{}' cannot be reached from ARM v8 inline assembler", t), s, e))
+                        }
+                    },
+                    _ => {
+                        let name = match t.find('@') {
+                            Some( i ) => &t[i ..],
+                            None => t.as_str()
+                        };
+                        Err(self.error_at(E_ASSEMBLER_UNDEFINED, format!("Undefined symbol '{}' in assembler code", name), s, e))
+                    }
+                }
+            },
+            ARM64Node::Plus( _ , _ , l , _ , r ) => Ok(self.evaluate(l)?.wrapping_add(self.evaluate(r)?)),
+            ARM64Node::Minus( _ , _ , l , _ , r ) => Ok(self.evaluate(l)?.wrapping_sub(self.evaluate(r)?)),
+            ARM64Node::Times( _ , _ , l , _ , r ) => Ok(self.evaluate(l)?.wrapping_mul(self.evaluate(r)?)),
+            ARM64Node::Div( _ , _ , l , _ , r ) |
+            ARM64Node::Modulo( _ , _ , l , _ , r ) => {
+                let left = self.evaluate(l)?;
+                let right = self.evaluate(r)?;
+                match ( right , node ) {
+                    ( 0 , _ ) => Err(self.error_at(E_DIVISION_BY_ZERO, String::from("Division by zero in assembler expression"), s, e)),
+                    ( _ , ARM64Node::Div( .. ) ) => Ok(left.wrapping_div(right)),
+                    _ => Ok(left.wrapping_rem(right))
+                }
+            },
+            ARM64Node::UnaryMinus( _ , _ , _ , x ) => Ok(self.evaluate(x)?.wrapping_neg()),
+            ARM64Node::UnaryPlus( _ , _ , _ , x ) => self.evaluate(x),
+            ARM64Node::Negate( _ , _ , _ , x ) => Ok(!self.evaluate(x)?),
+            ARM64Node::Float( .. ) => Err(self.error_at(E_ASSEMBLER_OPERAND, String::from("Floating point number is only allowed as immediate operand"), s, e)),
+            _ => Err(self.error_at(E_ASSEMBLER_OPERAND, String::from("Expecting constant expression"), s, e))
+        }
+    }
+
+    /// Register named by node, used for base and index of memory operands
+    fn register(&self, node: &ARM64Node) -> Result<RegisterARM64, Box<Diagnostic>> {
+        let ( s , e ) = node_span(node);
+
+        match node {
+            ARM64Node::Ident( _ , _ , t ) => {
+                match register_arm64(t.as_str()) {
+                    Some( r ) => Ok(r),
+                    _ => Err(self.error_at(E_ASSEMBLER_OPERAND, format!("Expecting register in memory operand, found '{}'", t), s, e))
+                }
+            },
+            _ => Err(self.error_at(E_ASSEMBLER_OPERAND, String::from("Expecting register in memory operand"), s, e))
+        }
+    }
+
+    /// Register, immediate, condition, shift or memory operand, names not defined in assembler text may be
+    /// Oberon variables, conditions, barrier options or system registers
+    fn resolve_operand(&self, node: &ARM64Node) -> Result<ARM64Operand, Box<Diagnostic>> {
+        let ( s , e ) = node_span(node);
+
+        match node {
+            ARM64Node::Ident( _ , _ , t ) if register_arm64(t.as_str()).is_some() => Ok(ARM64Operand::Register(self.register(node)?)),
+            ARM64Node::Ident( _ , _ , t ) if self.variable(t.as_str()).is_some() => {
+                /* Parameter or local variable by name alone is a memory operand relative to frame pointer */
+                match self.variable(t.as_str()) {
+                    Some( OberonVariable::Frame( offset , _ ) ) => Ok(ARM64Operand::Memory(RegisterARM64::General(29, 64), *offset, IndexingARM64::Offset)),
+                    _ => Err(self.error_at(E_ASSEMBLER_OPERAND, format!("Module variable '{}' cannot be reached from ARM v8 inline assembler", t), s, e))
+                }
+            },
+            ARM64Node::Ident( _ , _ , t ) if !self.is_defined(t.as_str()) => {
+                match ( condition_arm64(t.as_str()) , barrier_arm64(t.as_str()) , system_register_arm64(t.as_str()) ) {
+                    ( Some( x ) , _ , _ ) => Ok(ARM64Operand::Condition(x)),
+                    ( _ , Some( x ) , _ ) => Ok(ARM64Operand::Immediate(x as i64)),
+                    ( _ , _ , Some( x ) ) => Ok(ARM64Operand::SystemRegister(x)),
+                    _ => Ok(ARM64Operand::Immediate(self.evaluate(node)?))
+                }
+            },
+            ARM64Node::Immediate( _ , _ , x ) => {
+                match float_value(x) {
+                    Some( f ) => Ok(ARM64Operand::Float(f)),
+                    _ => Ok(ARM64Operand::Immediate(self.evaluate(x)?))
+                }
+            },
+            ARM64Node::Modifier( _ , _ , kind , x ) => {
+                let amount = match &**x {
+                    ARM64Node::None => 0,
+                    _ => self.evaluate(x)?
+                };
+                match amount {
+                    0 ..= 63 => Ok(ARM64Operand::Modifier(*kind, amount as u8)),
+                    _ => Err(self.error_at(E_ASSEMBLER_OPERAND, format!("Shift amount {} is out of range", amount), s, e))
+                }
+            },
+            ARM64Node::Memory( _ , _ , base , offset , indexing ) => {
+                let offset = match &**offset {
+                    ARM64Node::None => 0,
+                    x => self.evaluate(x)?
+                };
+                Ok(ARM64Operand::Memory(self.register(base)?, offset, *indexing))
+            },
+            ARM64Node::MemoryRegister( _ , _ , base , index , modifier ) => {
+                let ( kind , amount ) = match &**modifier {
+                    ARM64Node::None => ( ModifierARM64::Lsl , 0 ),
+                    x => {
+                        match self.resolve_operand(x)? {
+                            ARM64Operand::Modifier( kind , amount ) => ( kind , amount ),
+                            _ => return Err(self.error_at(E_ASSEMBLER_OPERAND, String::from("Expecting shift or extend of index register"), s, e))
+                        }
+                    }
+                };
+                Ok(ARM64Operand::MemoryRegister(self.register(base)?, self.register(index)?, kind, amount))
+            },
+            _ => {
+                match float_value(node) {
+                    Some( f ) => Ok(ARM64Operand::Float(f)),
+                    _ => Ok(ARM64Operand::Immediate(self.evaluate(node)?))
+                }
+            }
+        }
+    }
+
+    /// Defines label or 'EQU' constant, each name can only be defined once
+    fn define(&mut self, name: &str, value: i64, is_label: bool, start: u32, end: u32) -> Result<(), Box<Diagnostic>> {
+        if register_arm64(name).is_some() {
+            return Err(self.error_at(E_ASSEMBLER_DUPLICATE, format!("Register name '{}' cannot be redefined", name), start, end))
+        }
+        if self.labels.contains_key(name) || self.constants.contains_key(name) {
+            return Err(self.error_at(E_ASSEMBLER_DUPLICATE, format!("Symbol '{}' is already defined in assembler code", name), start, end))
+        }
+        match is_label {
+            true => self.labels.insert(String::from(name), value),
+            _ => self.constants.insert(String::from(name), value)
+        };
+        Ok(())
+    }
+
+    /// Name of local label '@name' inside the scope of the last label before it, other names are unchanged
+    fn qualified(&self, name: &str) -> Box<String> {
+        match name.starts_with('@') {
+            true => Box::new(format!("{}{}", self.scope, name)),
+            _ => Box::new(String::from(name))
+        }
+    }
+
+    /// Encode line at current location into code
+    fn emit(&mut self, line: &ARM64Node, code: &mut Vec<u8>) -> Result<(), Box<Diagnostic>> {
+        let ( s , e ) = node_span(line);
+
+        match line {
+            ARM64Node::Instruction( _ , _ , mnemonic, operands) => {
+                if self.location % 4 != 0 {
+                    return Err(self.error_at(E_ASSEMBLER_OPERAND, String::from("Instruction is not aligned to 4 bytes, use 'ALIGN 4' after data"), s, e))
+                }
+                let mut values = Vec::<ARM64Operand>::new();
+                for x in operands.iter() {
+                    values.push(self.resolve_operand(x)?)
+                }
+
+                /* Load or store of Oberon variable must transfer as many bytes as the variable has */
+                let sized = operands.iter().find_map(|x| self.sized_variable(x));
+                match ( sized , access_size(mnemonic.as_str(), &values) ) {
+                    ( Some( ( name , size ) ) , Some( bytes ) ) if bytes != size => {
+                        return Err(self.error_at(E_ASSEMBLER_OPERAND, format!("Operands of '{}' do not match size of '{}', which is {} bytes", mnemonic, name, size), s, e))
+                    },
+                    _ => ()
+                }
+
+                let bytes = match encode_instruction_arm64(mnemonic.clone(), Box::new(values), self.location, self.flags) {
+                    Ok( x ) => x,
+                    Err( x ) => return Err(self.error_at(x.code, (*x.message).clone(), s, e))
+                };
+                self.location += bytes.len() as i64;
+                code.extend_from_slice(&bytes);
+                Ok(())
+            },
+            ARM64Node::Data( _ , _ , size, items ) => {
+                let mut bytes = Vec::<u8>::new();
+                for item in items.iter() {
+                    match &**item {
+                        ARM64Node::String( _ , _ , t ) => {
+                            /* Strings are stored one character per byte, padded with zero to a whole item */
+                            for c in t.trim_matches('\'').chars() {
+                                bytes.push((c as u32 & 0xff) as u8)
+                            }
+                            while !bytes.len().is_multiple_of(*size as usize) {
+                                bytes.push(0)
+                            }
+                        },
+                        _ => {
+                            let value = self.evaluate(item)?;
+                            let bits = 8 * *size;
+                            if bits < 64 && ( value < -(1 << (bits - 1)) || value >= 1 << bits ) {
+                                let ( a , b ) = node_span(item);
+                                return Err(self.error_at(E_ASSEMBLER_OPERAND, format!("Value does not fit in {} bits", bits), a, b))
+                            }
+                            bytes.extend_from_slice(&value.to_le_bytes()[.. *size as usize])
+                        }
+                    }
+                }
+                self.location += bytes.len() as i64;
+                code.extend_from_slice(&bytes);
+                Ok(())
+            },
+            ARM64Node::Repeat( _ , _ , count, line ) => {
+                for _ in 0 .. self.count(count)? {
+                    self.emit(line, code)?
+                }
+                Ok(())
+            },
+            ARM64Node::Align( _ , _ , x ) => {
+                let alignment = self.evaluate(x)?;
+                if alignment <= 0 || alignment & (alignment - 1) != 0 {
+                    return Err(self.error_at(E_ASSEMBLER_OPERAND, String::from("Alignment must be a power of two"), s, e))
+                }
+                /* Padding is zero bytes up to a whole instruction, then NOP instructions */
+                let padding = (alignment - self.location.rem_euclid(alignment)) % alignment;
+                let mut filled = 0;
+                while filled < padding {
+                    match ( ( self.location + filled ) % 4 , padding - filled ) {
+                        ( 0 , x ) if x >= 4 => {
+                            code.extend_from_slice(&0xd503_201fu32.to_le_bytes());
+                            filled += 4
+                        },
+                        _ => {
+                            code.push(0);
+                            filled += 1
+                        }
+                    }
+                }
+                self.location += padding;
+                Ok(())
+            },
+            _ => Ok(())
+        }
+    }
+
+    /// Value of repeat count, it must not be negative
+    fn count(&self, node: &ARM64Node) -> Result<i64, Box<Diagnostic>> {
+        let ( s , e ) = node_span(node);
+
+        match self.evaluate(node)? {
+            x if !(0 ..= 0x10_0000).contains(&x) => Err(self.error_at(E_ASSEMBLER_OPERAND, format!("Count {} is out of range", x), s, e)),
+            x => Ok(x)
+        }
+    }
+
+    /// True when name is label or 'EQU' constant of assembler text
+    fn is_defined(&self, name: &str) -> bool {
+        self.labels.contains_key(name) || self.constants.contains_key(name) || self.pending.contains(name)
+    }
+
+    /// Oberon variable with name, names defined in assembler text hide variables
+    fn variable(&self, name: &str) -> Option<&OberonVariable> {
+        match self.is_defined(name) {
+            true => None,
+            _ => self.variables.get(name)
+        }
+    }
+
+    /// Name and size of Oberon variable that memory operand is exactly, like 'x' or '[FP, #x]', when it has size of a register
+    fn sized_variable(&self, node: &ARM64Node) -> Option<(String, u32)> {
+        let name = match node {
+            ARM64Node::Ident( _ , _ , t ) => t,
+            ARM64Node::Memory( _ , _ , base , offset , IndexingARM64::Offset ) => {
+                match ( &**base , &**offset ) {
+                    ( ARM64Node::Ident( _ , _ , a ) , ARM64Node::Ident( _ , _ , b ) ) if register_arm64(a.as_str()) == Some( RegisterARM64::General(29, 64) ) => b,
+                    _ => return None
+                }
+            },
+            _ => return None
+        };
+
+        match self.variable(name.as_str()) {
+            Some( OberonVariable::Frame( _ , size ) ) if [ 1, 2, 4, 8, 16 ].contains(size) => Some( ( (**name).clone() , *size ) ),
+            _ => None
+        }
+    }
+
+    fn advance(&mut self) {
+        self.symbol = self.get_symbol();
+    }
+
+    /// Error covering the current symbol, positioned in the Oberon source file
+    fn error(&self, code: &'static str, message: String) -> Box<Diagnostic> {
+        Diagnostic::error(code, message, self.offset_position + self.start_pos, self.offset_position + self.index)
+    }
+
+    /// Error covering part of assembler text, positioned in the Oberon source file
+    fn error_at(&self, code: &'static str, message: String, start: u32, end: u32) -> Box<Diagnostic> {
+        Diagnostic::error(code, message, self.offset_position + start, self.offset_position + end)
+    }
+}
+
+impl InlineAssembler for AssemblerARM64 {
+    fn set_variables(&mut self, variables: HashMap<String, OberonVariable>) {
+        self.variables = variables
+    }
+
+    fn get_relocations(&self) -> &Vec<Relocation> {
+        &self.relocations
+    }
+
+    /// Entry point for inline assemble of block of code in A64 instruction set
+    fn assemble(&mut self) -> Result<Box<Vec<u8>>, Box<Diagnostic>> {
+        let mut flags : CpuFlags = 0;
+        let mut selected = false;
+
+        self.advance();
+
+        // Flags handling for setting architecture version and extensions
+        if let ARM64Symbols::LeftCurly( _ , _ ) = *self.symbol.clone()? {
+            self.advance();
+
+            loop {
+                match *self.symbol.clone()? {
+                    ARM64Symbols::Ident( _ , _ , t ) => {
+                        match t.as_str() {
+                            "SYSTEM" => {
+                                self.advance();
+
+                                match *self.symbol.clone()? {
+                                    ARM64Symbols::Period( _ , _ )  => self.advance(),
+                                    _ => return Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Identifier missing in assembler code")))
+                                }
+
+                                if let ARM64Symbols::Ident( _ , _ , t ) = *self.symbol.clone()? {
+                                    match t.as_str() {
+                                        "ARMV8" => (),
+                                        "ARMV8_1" => flags |= CPU_ARMV8_1,
+                                        "ARMV8_2" => flags |= CPU_ARMV8_2,
+                                        "FP" => flags |= CPU_FP,
+                                        "NEON" => flags |= CPU_NEON,
+                                        "SVE" => flags |= CPU_SVE,
+                                        _ => return Err(self.error(E_ASSEMBLER_CPU_FLAG, String::from("Unknown CPU type flag in assembler code")))
+                                    }
+                                    selected = true;
+                                    self.advance();
+                                }
+
+                                match *self.symbol.clone()? {
+                                    ARM64Symbols::RightCurly( _ , _ ) => {
+                                        self.advance();
+                                        break;
+                                    },
+                                    ARM64Symbols::Comma( _ , _ ) => {
+                                        self.advance()
+                                    },
+                                    _ => return Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Target identifier expected in assembler code")))
+                                }
+                            },
+                            _ => return Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Unsupported target identifier in assembler code")))
+                        }
+                    },
+                    ARM64Symbols::RightCurly( _ , _ ) => {
+                        self.advance();
+                        break
+                    }
+                    _ => return Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Missing target identifier in assembler code")))
+                }
+            }
+        }
+
+        /* Code block without CPU flags may use floating point and Advanced SIMD, which every A64 processor has */
+        self.flags = match selected {
+            false => CPU_NEON,
+            _ => flags
+        };
+
+        let mut lines = Vec::<Box<ARM64Node>>::new();
+
+        // High level instructions in assembler language
+        loop {
+            match *self.symbol.clone()? {
+                ARM64Symbols::NewLine( _ , _ ) => {
+                    self.advance();
+                },
+                ARM64Symbols::Label(s, e, t) => {
+                    self.advance();
+                    let name = self.qualified(t.as_str());
+                    match *self.symbol.clone()? {
+                        ARM64Symbols::Ident( _ , _ , x ) if x.as_str() == "EQU" => {
+                            self.advance();
+                            let node = self.expression()?;
+                            let value = self.evaluate(&node)?;
+                            self.define(name.as_str(), value, false, s, e)?
+                        },
+                        _ => {
+                            self.define(name.as_str(), 0, true, s, e)?;
+                            if !t.starts_with('@') {
+                                self.scope = t
+                            }
+                            lines.push(Box::new(ARM64Node::Label(s, e, name)))
+                        }
+                    }
+                },
+                ARM64Symbols::Ident(s, e, t) => {
+                    match t.as_str() {
+                        "EQU" => {
+                            return Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Missing name before 'EQU' in assembler code")))
+                        },
+                        _ => {
+                            self.advance();
+                            match *self.symbol.clone()? {
+                                ARM64Symbols::Ident( _ , _ , x ) if x.as_str() == "EQU" => {
+                                    self.advance();
+                                    let node = self.expression()?;
+                                    let value = self.evaluate(&node)?;
+                                    let name = self.qualified(t.as_str());
+                                    self.define(name.as_str(), value, false, s, e)?
+                                },
+                                _ => lines.push(self.statement(s, e, t)?)
+                            }
+                        }
+                    }
+                },
+                ARM64Symbols::EndOfFile( _ ) => break,
+                _ => return Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Invalid symbol in inline assembler")))
+            }
+        }
+
+        /* Instructions have fixed size, so a second pass with all labels known gives the final code */
+        let mut code = Vec::<u8>::new();
+        let mut passes = 0;
+
+        for line in lines.iter() {
+            if let ARM64Node::Label( _ , _ , t ) = &**line {
+                self.pending.insert((**t).clone());
+            }
+        }
+
+        loop {
+            let mut changed = false;
+            code.clear();
+            self.relocations.clear();
+            self.location = 0;
+
+            for line in lines.iter() {
+                match &**line {
+                    ARM64Node::Label( _ , _ , t ) => {
+                        self.pending.remove(t.as_str());
+                        match self.labels.insert((**t).clone(), self.location) {
+                            Some( x ) if x == self.location && passes > 0 => (),
+                            _ => changed = true
+                        }
+                    },
+                    x => self.emit(x, &mut code)?
+                }
+            }
+
+            passes += 1;
+            match ( changed , passes ) {
+                ( false , _ ) => break,
+                ( _ , x ) if x > 2 => return Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Labels in assembler code do not settle"))),
+                _ => ()
+            }
+        }
+
+        Ok(Box::new(code))
+    }
+}
+
+// Unittests for inline assembler for ARM v8 module
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use crate::arm64_assembler::{ AssemblerARM64, AssemblerARM64Methods, ARM64Symbols };
+    use crate::diagnostics::{ E_ASSEMBLER_SYNTAX, E_ASSEMBLER_UNDEFINED, E_ASSEMBLER_DUPLICATE, E_ASSEMBLER_OPERAND, E_ASSEMBLER_INSTRUCTION, E_ASSEMBLER_CPU_FLAG };
+    use crate::inline_assembler::{ InlineAssembler, OberonVariable };
+
+    /// Machine code as instruction words, or code of first error
+    fn assemble(text: &str) -> Result<Vec<u32>, &'static str> {
+        let mut assembler = AssemblerARM64::new(text.chars().collect(), 0);
+        match assembler.assemble() {
+            Ok( x ) => Ok( x.chunks(4).map(|w| u32::from_le_bytes([ w[0], w[1], w[2], w[3] ])).collect() ),
+            Err( e ) => Err( e.code )
+        }
+    }
+
+    #[test]
+    fn test_lexer_symbols() {
+        let mut assembler = AssemblerARM64::new("ldr x0, [sp, #0x10]! // comment\r\n@loop: 1.5 1FH".chars().collect(), 0);
+        let mut symbols = Vec::<ARM64Symbols>::new();
+        loop {
+            match *assembler.get_symbol().unwrap() {
+                ARM64Symbols::EndOfFile( _ ) => break,
+                x => symbols.push(x)
+            }
+        }
+
+        assert_eq!(symbols, vec![
+            ARM64Symbols::Ident(0, 3, Box::new(String::from("ldr"))),
+            ARM64Symbols::Ident(4, 6, Box::new(String::from("x0"))),
+            ARM64Symbols::Comma(6, 7),
+            ARM64Symbols::LeftBracket(8, 9),
+            ARM64Symbols::Ident(9, 11, Box::new(String::from("sp"))),
+            ARM64Symbols::Comma(11, 12),
+            ARM64Symbols::Hash(13, 14),
+            ARM64Symbols::Number(14, 18, 16),
+            ARM64Symbols::RightBracket(18, 19),
+            ARM64Symbols::Exclamation(19, 20),
+            ARM64Symbols::NewLine(31, 33),
+            ARM64Symbols::Label(33, 39, Box::new(String::from("@loop"))),
+            ARM64Symbols::Float(40, 43, 1.5),
+            ARM64Symbols::Number(44, 47, 31)
+        ])
+    }
+
+    #[test]
+    fn test_assembler_arm64_instructions() {
+        let source = "MOV X0, X1\nadd x0, x1, #16\nLDR X0, [X1, #8]\nSTP X29, X30, [SP, #-16]!\nLDP X29, X30, [SP], #16\nLDR X0, [X1, X2, LSL #3]\nADD X0, X1, W2, SXTW #2\nRET";
+        assert_eq!(assemble(source), Ok( vec![ 0xaa01_03e0, 0x9100_4020, 0xf940_0420, 0xa9bf_7bfd, 0xa8c1_7bfd, 0xf862_7820, 0x8b22_c820, 0xd65f_03c0 ] ));
+
+        let source = "CSEL X0, X1, X2, EQ\nDMB ISH\nMRS X0, NZCV\nFMOV D0, #1.0\nFMOV S0, #-0.5\nLDRH W0, [X1, W2, UXTW #1]";
+        assert_eq!(assemble(source), Ok( vec![ 0x9a82_0020, 0xd503_3bbf, 0xd53b_4200, 0x1e6e_1000, 0x1e3c_1000, 0x7862_5820 ] ))
+    }
+
+    #[test]
+    fn test_assembler_arm64_labels_and_branches() {
+        let source = "loop: SUBS X0, X0, #1\nB.NE loop\nCBZ X1, done\nB loop\ndone: RET";
+        assert_eq!(assemble(source), Ok( vec![ 0xf100_0400, 0x54ff_ffe1, 0xb400_0041, 0x17ff_fffd, 0xd65f_03c0 ] ));
+
+        /* Local labels belong to the label before them, literal is loaded relative to instruction */
+        let source = "first:\n@again: CBNZ X0, @again\nsecond:\n@again: B @again\nLDR X1, value\nvalue: DQ 1";
+        assert_eq!(assemble(source), Ok( vec![ 0xb500_0000, 0x1400_0000, 0x5800_0021, 0x0000_0001, 0x0000_0000 ] ))
+    }
+
+    #[test]
+    fn test_assembler_arm64_expressions_and_data() {
+        let source = "size EQU 4 * 2\nADD X0, X1, #size + 1\nMOV X2, #$\nDB 1, 'A'\nALIGN 8\nDD 12345678H, 0x10\nTIMES 2 NOP";
+        assert_eq!(assemble(source), Ok( vec![ 0x9100_2420, 0xd280_0082, 0x0000_4101, 0xd503_201f, 0x1234_5678, 0x0000_0010, 0xd503_201f, 0xd503_201f ] ));
+
+        assert_eq!(assemble("NOP\nALIGN 16\nRET"), Ok( vec![ 0xd503_201f, 0xd503_201f, 0xd503_201f, 0xd503_201f, 0xd65f_03c0 ] ))
+    }
+
+    #[test]
+    fn test_assembler_arm64_cpu_flags() {
+        assert_eq!(assemble("FADD D0, D1, D2"), Ok( vec![ 0x1e62_2820 ] ));
+        assert_eq!(assemble("{ SYSTEM.ARMV8_1 }\nLDADD X0, X1, [X2]"), Ok( vec![ 0xf820_0041 ] ));
+        assert_eq!(assemble("{ SYSTEM.SVE }\nFDIV H0, H1, H2"), Ok( vec![ 0x1ee2_1820 ] ));
+        assert_eq!(assemble("{ SYSTEM.ARMV8_2, SYSTEM.NEON }\nFDIV H0, H1, H2"), Ok( vec![ 0x1ee2_1820 ] ));
+        assert_eq!(assemble("LDADD X0, X1, [X2]"), Err( E_ASSEMBLER_CPU_FLAG ));
+        assert_eq!(assemble("{ SYSTEM.ARMV8 }\nFADD D0, D1, D2"), Err( E_ASSEMBLER_CPU_FLAG ));
+        assert_eq!(assemble("{ SYSTEM.CPU_AMD64 }\nNOP"), Err( E_ASSEMBLER_CPU_FLAG ));
+        assert_eq!(assemble("{ SYSTEM.NEON \nNOP"), Err( E_ASSEMBLER_SYNTAX ))
+    }
+
+    #[test]
+    fn test_assembler_arm64_errors() {
+        let cases = [
+            ( "LDR X0, [X1" , E_ASSEMBLER_SYNTAX ),
+            ( "ADD X0, X1, #" , E_ASSEMBLER_SYNTAX ),
+            ( "B.", E_ASSEMBLER_SYNTAX ),
+            ( "MOV X0, #0x" , E_ASSEMBLER_SYNTAX ),
+            ( "NOP )" , E_ASSEMBLER_SYNTAX ),
+            ( "B nowhere" , E_ASSEMBLER_UNDEFINED ),
+            ( "here: NOP\nhere: NOP" , E_ASSEMBLER_DUPLICATE ),
+            ( "X0: NOP" , E_ASSEMBLER_DUPLICATE ),
+            ( "DB 1\nNOP" , E_ASSEMBLER_OPERAND ),
+            ( "ADD X0, X1, X2 + 1" , E_ASSEMBLER_OPERAND ),
+            ( "LDR X0, [8]" , E_ASSEMBLER_OPERAND ),
+            ( "ADD X0, X1, #1.5" , E_ASSEMBLER_OPERAND ),
+            ( "ALIGN 3" , E_ASSEMBLER_OPERAND ),
+            ( "BLX X0" , E_ASSEMBLER_INSTRUCTION )
+        ];
+
+        for ( text , code ) in cases.iter() {
+            assert_eq!(assemble(text), Err( *code ), "{}", text)
+        }
+
+        let mut assembler = AssemblerARM64::new("NOP\nADD X0, X1, #4097".chars().collect(), 100);
+        match assembler.assemble() {
+            Err( e ) => {
+                assert_eq!(e.code, E_ASSEMBLER_OPERAND);
+                assert_eq!(e.span.start, 104);
+                assert_eq!(e.span.end, 121)
+            },
+            _ => assert!(false)
+        }
+    }
+
+    fn with_variables(text: &str) -> AssemblerARM64 {
+        let mut assembler = AssemblerARM64::new(text.chars().collect(), 0);
+        assembler.set_variables(HashMap::from([
+            ( String::from("a"), OberonVariable::Frame(16, 8) ),
+            ( String::from("x"), OberonVariable::Frame(-8, 8) ),
+            ( String::from("y"), OberonVariable::Frame(-12, 4) ),
+            ( String::from("g"), OberonVariable::Global(String::from("Test.g"), 4) )
+        ]));
+        assembler
+    }
+
+    #[test]
+    fn test_assembler_arm64_oberon_variables() {
+        let mut assembler = with_variables("LDR X0, a\nSTUR X0, x\nLDUR W1, y\nLDR X2, [FP, #a]\nADD X3, X29, #a");
+        let pattern = Box::new(vec![
+            0xa0, 0x0b, 0x40, 0xf9,
+            0xa0, 0x83, 0x1f, 0xf8,
+            0xa1, 0x43, 0x5f, 0xb8,
+            0xa2, 0x0b, 0x40, 0xf9,
+            0xa3, 0x43, 0x00, 0x91
+        ]);
+        assert_eq!(assembler.assemble(), Ok( pattern ));
+
+        let mut assembler = with_variables("LDR W0, a");
+        match assembler.assemble() {
+            Err( e ) => {
+                assert_eq!(e.code, E_ASSEMBLER_OPERAND);
+                assert_eq!(*e.message, String::from("Operands of 'LDR' do not match size of 'a', which is 8 bytes"))
+            },
+            _ => assert!(false)
+        }
+
+        let cases = [ ( "LDR W0, g" , E_ASSEMBLER_OPERAND ), ( "ADD X0, X1, #g" , E_ASSEMBLER_OPERAND ), ( "LDR X0, z" , E_ASSEMBLER_UNDEFINED ) ];
+        for ( text , code ) in cases.iter() {
+            let mut assembler = with_variables(text);
+            match assembler.assemble() {
+                Err( e ) => assert_eq!(e.code, *code, "{}", text),
+                _ => assert!(false, "{}", text)
+            }
+        }
+
+        /* Label of assembler text hides Oberon variable with same name */
+        let mut assembler = with_variables("x: B x");
+        assert_eq!(assembler.assemble(), Ok( Box::new(vec![ 0x00, 0x00, 0x00, 0x14 ]) ))
+    }
+}
@@ -1,197 +1,1897 @@
-
 // ActiveOberon Compiler, a native ARM v8 & X86-64 compiler & Risc V / linker / builder utility.
 // Written by Richard Magnor Stenbro. Licensed under GPL v3
 // Instruction set for ARM v8 module for compiling and linking of projects written in ActiveOberon language
 
-type CpuFlags = u32;
-
-fn encode_instruction_arm64(instruction: Box<String>, operands: Box<Vec<Box<String>>>, flags: CpuFlags) -> Result<Box<Vec<u8>>, Box<String>> {
-
-    match &*instruction.as_str() {
-        "ADC" => {},
-        "ADD" => {},
-        "AND" => {},
-        "B" => {},
-        "BIC" => {},
-        "BKPT" => {},
-        "BL" => {},
-        "BLX" => {},
-        "BX" => {},
-        "CDP" => {},
-        "CLZ" => {},
-        "CMN" => {},
-        "CMP" => {},
-        "EOR" => {},
-        "FABSD" => {},
-        "FABSS" => {},
-        "FADDD" => {},
-        "FADDS" => {},
-        "FCMPD" => {},
-        "FCMPED" => {},
-        "FCMPES" => {},
-        "FCMPEZD" => {},
-        "FCMPEZS" => {},
-        "FCMPS" => {},
-        "FCMPZD" => {},
-        "FCMPZS" => {},
-        "FCPYD" => {},
-        "FCPYS" => {},
-        "FCVTDS" => {},
-        "FCVTSD" => {},
-        "FDIVD" => {},
-        "FDIVS" => {},
-        "FLDD" => {},
-        "FLDMIAD" => {},
-        "FLDMIAS" => {},
-        "FLDMIAX" => {},
-        "FLDMDBD" => {},
-        "FLDMDBS" => {},
-        "FLDMDBX" => {},
-        "FLDS" => {},
-        "FMACD" => {},
-        "FMACS" => {},
-        "FMDHR" => {},
-        "FMDLR" => {},
-        "FMRDH" => {},
-        "FMRDL" => {},
-        "FMRS" => {},
-        "FMRX" => {},
-        "FMSCD" => {},
-        "FMSCS" => {},
-        "FMSR" => {},
-        "FMSTAT" => {},
-        "FMULD" => {},
-        "FMULS" => {},
-        "FMXR" => {},
-        "FNEGD" => {},
-        "FNEGS" => {},
-        "FNMACD" => {},
-        "FNMACS" => {},
-        "FNMSCD" => {},
-        "FNMSCS" => {},
-        "FNMULD" => {},
-        "FNMULS" => {},
-        "FSITOD" => {},
-        "FSITOS" => {},
-        "FSQRTD" => {},
-        "FSQRTS" => {},
-        "FSTD" => {},
-        "FSTMIAD" => {},
-        "FSTMIAS" => {},
-        "FSTMIAX" => {},
-        "FSTMDBD" => {},
-        "FSTMDBS" => {},
-        "FSTMDBX" => {},
-        "FSTS" => {},
-        "FSUBD" => {},
-        "FSUBS" => {},
-        "FTOSID" => {},
-        "FTOSIZD" => {},
-        "FTOSIS" => {},
-        "FTOSIZS" => {},
-        "FTOUID" => {},
-        "FTOUIZD" => {},
-        "FTOUIS" => {},
-        "FTOUIZS" => {},
-        "FUITOD" => {},
-        "FUITOS" => {},
-        "LDC" => {},
-        "LDM" => {},
-        "LDR" => {},
-        "MCR" => {},
-        "MCR" => {},
-        "MCRR" => {},
-        "MLA" => {},
-        "MOV" => {},
-        "MRC" => {},
-        "MRC" => {},
-        "MRRC" => {},
-        "MRS" => {},
-        "MSR" => {},
-        "MUL" => {},
-        "MVN" => {},
-        "ORR" => {},
-        "PLD" => {},
-        "QADD" => {},
-        "QDADD" => {},
-        "QDSUB" => {},
-        "QSUB" => {},
-        "RSB" => {},
-        "RSC" => {},
-        "SBC" => {},
-        "SMLABB" => {},
-        "SMLABT" => {},
-        "SMLAL" => {},
-        "SMLATB" => {},
-        "SMLATT" => {},
-        "SMLALBB" => {},
-        "SMLALBT" => {},
-        "SMLALTB" => {},
-        "SMLALTT" => {},
-        "SMLAWB" => {},
-        "SMLAWT" => {},
-        "SMULBB" => {},
-        "SMULBT" => {},
-        "SMULTB" => {},
-        "SMULTT" => {},
-        "SMULWB" => {},
-        "SMULWT" => {},
-        "SMULL" => {},
-        "STC" => {},
-        "STM" => {},
-        "STR" => {},
-        "SUB" => {},
-        "SWI" => {},
-        "SWP" => {},
-        "TEQ" => {},
-        "TST" => {},
-        "UMLAL" => {},
-        "UMULL" => {},
-        "ISB" => {},
-        "VADD" => {},
-        "VADDL" => {},
-        "VADDW" => {},
-        "VMUL" => {},
-        "VMULL" => {},
-        "VMSR" => {},
-        "VMRS" => {},
-        "VLDR" => {},
-        "VSTR" => {},
-        "VDIV" => {},
-        "VMLA" => {},
-        "VMLS" => {},
-        "VMIN" => {},
-        "VMAX" => {},
-        "VSUB" => {},
-        "VABS" => {},
-        "VABD" => {},
-        "LSL" => {},
-        "LSR" => {},
-        "VLD" => {},
-        "VST" => {},
-        "VPADD" => {},
-        "VMOV" => {},
-        "SEV" => {},
-        "DSB" => {},
-        "LDREX" => {},
-        "STREX" => {},
-        "ADR" => {},
-        "LDREXB" => {},
-        "STREXB" => {},
-        "DMB" => {},
-        "CLREX" => {},
-        "REV" => {},
-        "UXTH" => {},
-        "WFE" => {},
-        "WFI" => {},
-        "MOVW" => {},
-        "UDF" => {},
-        _ => return Err(Box::new(String::from("Illegal instruction!")))
-    }
-
-    Ok(Box::new(Vec::<u8>::new()))
+use crate::diagnostics::{Diagnostic, DiagnosticMethods, E_ASSEMBLER_CPU_FLAG, E_ASSEMBLER_OPERAND, E_ASSEMBLER_INSTRUCTION};
+
+pub const CPU_ARMV8 : u32 = 0;
+pub const CPU_ARMV8_1 : u32 = 1;        /* Large system extension, atomic memory operations and compare and swap */
+pub const CPU_ARMV8_2 : u32 = 2;        /* Half precision floating point arithmetic */
+pub const CPU_FP : u32 = 4;
+pub const CPU_NEON : u32 = 8;
+pub const CPU_SVE : u32 = 16;
+
+pub type CpuFlags = u32;
+
+/// Registers of A64, general purpose registers have number 0 - 31 and size in bits
+#[derive(Clone, PartialEq, Debug)]
+pub enum RegisterARM64 {
+    General(u8, u32),   /* X0 - X30 and W0 - W30, number 31 is zero register XZR or WZR */
+    Stack(u32),         /* SP or WSP, encoded as number 31 where instruction accepts stack pointer */
+    Float(u8, u32)      /* SIMD & FP register as B, H, S, D or Q by size in bits */
+}
+
+pub trait RegisterARM64Methods {
+    fn number(&self) -> u8;
+    fn bits(&self) -> u32;
+}
+
+impl RegisterARM64Methods for RegisterARM64 {
+    /// Register number as encoded in instruction word
+    fn number(&self) -> u8 {
+        match self {
+            RegisterARM64::General( n , _ ) |
+            RegisterARM64::Float( n , _ ) => *n,
+            RegisterARM64::Stack( _ ) => 31
+        }
+    }
+
+    /// Size of register in bits
+    fn bits(&self) -> u32 {
+        match self {
+            RegisterARM64::General( _ , b ) |
+            RegisterARM64::Stack( b ) |
+            RegisterARM64::Float( _ , b ) => *b
+        }
+    }
+}
+
+/// Shift or extend applied to a register operand
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ModifierARM64 {
+    Lsl, Lsr, Asr, Ror,
+    Uxtb, Uxth, Uxtw, Uxtx, Sxtb, Sxth, Sxtw, Sxtx
+}
+
+/// How base register of memory operand is updated
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum IndexingARM64 {
+    Offset,         /* [Xn, #imm] */
+    PreIndex,       /* [Xn, #imm]! */
+    PostIndex       /* [Xn], #imm */
+}
+
+/// Operand of A64 instruction after expressions are evaluated, branch and literal targets are absolute addresses
+#[derive(Clone, PartialEq, Debug)]
+pub enum ARM64Operand {
+    Register(RegisterARM64),
+    Immediate(i64),
+    Float(f64),
+    Modifier(ModifierARM64, u8),                                        /* Shift or extend of register before it, like LSL #3 */
+    Condition(u8),
+    SystemRegister(u16),                                                /* op0, op1, CRn, CRm and op2 as encoded in MRS and MSR */
+    Memory(RegisterARM64, i64, IndexingARM64),                          /* Base and immediate offset */
+    MemoryRegister(RegisterARM64, RegisterARM64, ModifierARM64, u8)     /* Base and index register with shift or extend */
+}
+
+/// Register with given name, upper or lower case, or none when name is not a register
+pub fn register_arm64(name: &str) -> Option<RegisterARM64> {
+    let upper = name.to_uppercase();
+
+    match upper.as_str() {
+        "SP" => return Some( RegisterARM64::Stack(64) ),
+        "WSP" => return Some( RegisterARM64::Stack(32) ),
+        "XZR" => return Some( RegisterARM64::General(31, 64) ),
+        "WZR" => return Some( RegisterARM64::General(31, 32) ),
+        "FP" => return Some( RegisterARM64::General(29, 64) ),
+        "LR" => return Some( RegisterARM64::General(30, 64) ),
+        _ => ()
+    }
+
+    /* Numbered registers like X0, W30, S31 and Q7 */
+    let first = upper.chars().next()?;
+    let digits = &upper[first.len_utf8() ..];
+    let number = match digits.parse::<u8>() {
+        Ok( x ) if digits.chars().all(|c| c.is_ascii_digit()) && ( digits.len() == 1 || !digits.starts_with('0') ) => x,
+        _ => return None
+    };
+
+    match ( first , number ) {
+        ( 'X' , 0 ..= 30 ) => Some( RegisterARM64::General(number, 64) ),
+        ( 'W' , 0 ..= 30 ) => Some( RegisterARM64::General(number, 32) ),
+        ( 'B' , 0 ..= 31 ) => Some( RegisterARM64::Float(number, 8) ),
+        ( 'H' , 0 ..= 31 ) => Some( RegisterARM64::Float(number, 16) ),
+        ( 'S' , 0 ..= 31 ) => Some( RegisterARM64::Float(number, 32) ),
+        ( 'D' , 0 ..= 31 ) => Some( RegisterARM64::Float(number, 64) ),
+        ( 'Q' , 0 ..= 31 ) => Some( RegisterARM64::Float(number, 128) ),
+        _ => None
+    }
+}
+
+/// Condition code with given name, 'HS' and 'LO' are the same as 'CS' and 'CC'
+pub fn condition_arm64(name: &str) -> Option<u8> {
+    let conditions = [ "EQ", "NE", "CS", "CC", "MI", "PL", "VS", "VC", "HI", "LS", "GE", "LT", "GT", "LE", "AL", "NV" ];
+
+    match name.to_uppercase().as_str() {
+        "HS" => Some( 2 ),
+        "LO" => Some( 3 ),
+        x => conditions.iter().position(|c| *c == x).map(|c| c as u8)
+    }
+}
+
+/// Shift or extend with given name
+pub fn modifier_arm64(name: &str) -> Option<ModifierARM64> {
+    match name.to_uppercase().as_str() {
+        "LSL" => Some( ModifierARM64::Lsl ),
+        "LSR" => Some( ModifierARM64::Lsr ),
+        "ASR" => Some( ModifierARM64::Asr ),
+        "ROR" => Some( ModifierARM64::Ror ),
+        "UXTB" => Some( ModifierARM64::Uxtb ),
+        "UXTH" => Some( ModifierARM64::Uxth ),
+        "UXTW" => Some( ModifierARM64::Uxtw ),
+        "UXTX" => Some( ModifierARM64::Uxtx ),
+        "SXTB" => Some( ModifierARM64::Sxtb ),
+        "SXTH" => Some( ModifierARM64::Sxth ),
+        "SXTW" => Some( ModifierARM64::Sxtw ),
+        "SXTX" => Some( ModifierARM64::Sxtx ),
+        _ => None
+    }
+}
+
+/// Option of 'DMB' and 'DSB' barriers with given name
+pub fn barrier_arm64(name: &str) -> Option<u8> {
+    match name.to_uppercase().as_str() {
+        "OSHLD" => Some( 1 ),
+        "OSHST" => Some( 2 ),
+        "OSH" => Some( 3 ),
+        "NSHLD" => Some( 5 ),
+        "NSHST" => Some( 6 ),
+        "NSH" => Some( 7 ),
+        "ISHLD" => Some( 9 ),
+        "ISHST" => Some( 10 ),
+        "ISH" => Some( 11 ),
+        "LD" => Some( 13 ),
+        "ST" => Some( 14 ),
+        "SY" => Some( 15 ),
+        _ => None
+    }
+}
+
+/// System register for 'MRS' and 'MSR' by name or in generic form 'S3_3_C4_C2_0'
+pub fn system_register_arm64(name: &str) -> Option<u16> {
+    let encode = | op0 : u16, op1 : u16, crn : u16, crm : u16, op2 : u16 | op0 << 14 | op1 << 11 | crn << 7 | crm << 3 | op2;
+    let upper = name.to_uppercase();
+
+    match upper.as_str() {
+        "NZCV" => Some( encode(3, 3, 4, 2, 0) ),
+        "DAIF" => Some( encode(3, 3, 4, 2, 1) ),
+        "FPCR" => Some( encode(3, 3, 4, 4, 0) ),
+        "FPSR" => Some( encode(3, 3, 4, 4, 1) ),
+        "CURRENTEL" => Some( encode(3, 0, 4, 2, 2) ),
+        "SPSEL" => Some( encode(3, 0, 4, 2, 0) ),
+        "SPSR_EL1" => Some( encode(3, 0, 4, 0, 0) ),
+        "ELR_EL1" => Some( encode(3, 0, 4, 0, 1) ),
+        "SP_EL0" => Some( encode(3, 0, 4, 1, 0) ),
+        "MIDR_EL1" => Some( encode(3, 0, 0, 0, 0) ),
+        "MPIDR_EL1" => Some( encode(3, 0, 0, 0, 5) ),
+        "SCTLR_EL1" => Some( encode(3, 0, 1, 0, 0) ),
+        "ESR_EL1" => Some( encode(3, 0, 5, 2, 0) ),
+        "FAR_EL1" => Some( encode(3, 0, 6, 0, 0) ),
+        "VBAR_EL1" => Some( encode(3, 0, 12, 0, 0) ),
+        "TPIDR_EL1" => Some( encode(3, 0, 13, 0, 4) ),
+        "CTR_EL0" => Some( encode(3, 3, 0, 0, 1) ),
+        "DCZID_EL0" => Some( encode(3, 3, 0, 0, 7) ),
+        "TPIDR_EL0" => Some( encode(3, 3, 13, 0, 2) ),
+        "TPIDRRO_EL0" => Some( encode(3, 3, 13, 0, 3) ),
+        "CNTFRQ_EL0" => Some( encode(3, 3, 14, 0, 0) ),
+        "CNTPCT_EL0" => Some( encode(3, 3, 14, 0, 1) ),
+        "CNTVCT_EL0" => Some( encode(3, 3, 14, 0, 2) ),
+        _ => {
+            /* Generic form with fields op0, op1, CRn, CRm and op2 */
+            let fields : Vec<&str> = upper.split('_').collect();
+            match fields.as_slice() {
+                [ op0 , op1 , crn , crm , op2 ] => {
+                    let field = | text : Option<&str>, max : u16 | -> Option<u16> {
+                        match text.map(|x| x.parse::<u16>()) {
+                            Some( Ok( x ) ) if x <= max => Some( x ),
+                            _ => None
+                        }
+                    };
+                    Some( encode(field(op0.strip_prefix('S'), 3)?, field(Some( *op1 ), 7)?, field(crn.strip_prefix('C'), 15)?, field(crm.strip_prefix('C'), 15)?, field(Some( *op2 ), 7)?) )
+                },
+                _ => None
+            }
+        }
+    }
+}
+
+/// Flags implied by the CPU types given in code block, newer architecture versions include older extensions
+pub fn implied_flags(flags: CpuFlags) -> CpuFlags {
+    let rules = [
+        ( CPU_SVE, CPU_ARMV8_2 | CPU_NEON ),
+        ( CPU_ARMV8_2, CPU_ARMV8_1 ),
+        ( CPU_NEON, CPU_FP )
+    ];
+    let mut result = flags;
+
+    for ( cpu , implied ) in rules.iter() {
+        if result & cpu != 0 {
+            result |= implied
+        }
+    }
+
+    result
+}
+
+fn operand_error(mnemonic: &str) -> Box<Diagnostic> {
+    Diagnostic::error(E_ASSEMBLER_OPERAND, format!("Invalid combination of operands for '{}'", mnemonic), 0, 0)
+}
+
+fn range_error(mnemonic: &str, what: &str, value: i64) -> Box<Diagnostic> {
+    Diagnostic::error(E_ASSEMBLER_OPERAND, format!("{} {} out of range for '{}'", what, value, mnemonic), 0, 0)
+}
+
+/// Error unless all flags needed by instruction are given for code block
+fn require(mnemonic: &str, flags: CpuFlags, needed: CpuFlags) -> Result<(), Box<Diagnostic>> {
+    match needed & !flags {
+        0 => Ok(()),
+        _ => Err(Diagnostic::error(E_ASSEMBLER_CPU_FLAG, format!("Instruction '{}' is not enabled by CPU flags of code block", mnemonic), 0, 0))
+    }
+}
+
+/// Flags needed by SIMD & FP registers in operands, half precision arithmetic needs ARM v8.2
+fn float_flags(operands: &[ARM64Operand], arithmetic: bool) -> CpuFlags {
+    let mut needed = CPU_ARMV8;
+
+    for operand in operands.iter() {
+        match operand {
+            ARM64Operand::Register(RegisterARM64::Float( _ , 16 )) if arithmetic => needed |= CPU_FP | CPU_ARMV8_2,
+            ARM64Operand::Register(RegisterARM64::Float( _ , _ )) => needed |= CPU_FP,
+            _ => ()
+        }
+    }
+
+    needed
+}
+
+fn register_at(operands: &[ARM64Operand], index: usize) -> Option<&RegisterARM64> {
+    match operands.get(index) {
+        Some( ARM64Operand::Register( x ) ) => Some( x ),
+        _ => None
+    }
+}
+
+/// Number and size of general register, number 31 is zero register
+fn general_at(operands: &[ARM64Operand], index: usize) -> Option<(u32, u32)> {
+    match register_at(operands, index) {
+        Some( RegisterARM64::General( n , b ) ) => Some( ( *n as u32 , *b ) ),
+        _ => None
+    }
+}
+
+/// Number and size of general register or stack pointer, number 31 is stack pointer so zero register is not accepted
+fn general_or_stack_at(operands: &[ARM64Operand], index: usize) -> Option<(u32, u32)> {
+    match register_at(operands, index) {
+        Some( RegisterARM64::General( n , b ) ) if *n != 31 => Some( ( *n as u32 , *b ) ),
+        Some( RegisterARM64::Stack( b ) ) => Some( ( 31 , *b ) ),
+        _ => None
+    }
+}
+
+/// Number and size of SIMD & FP register
+fn float_at(operands: &[ARM64Operand], index: usize) -> Option<(u32, u32)> {
+    match register_at(operands, index) {
+        Some( RegisterARM64::Float( n , b ) ) => Some( ( *n as u32 , *b ) ),
+        _ => None
+    }
+}
+
+fn immediate_at(operands: &[ARM64Operand], index: usize) -> Option<i64> {
+    match operands.get(index) {
+        Some( ARM64Operand::Immediate( x ) ) => Some( *x ),
+        _ => None
+    }
+}
+
+fn condition_at(operands: &[ARM64Operand], index: usize) -> Option<u32> {
+    match operands.get(index) {
+        Some( ARM64Operand::Condition( x ) ) => Some( *x as u32 ),
+        _ => None
+    }
+}
+
+/// Base register of memory operand, must be 64 bits register or stack pointer
+fn base_number(base: &RegisterARM64) -> Option<u32> {
+    match base {
+        RegisterARM64::General( n , 64 ) if *n != 31 => Some( *n as u32 ),
+        RegisterARM64::Stack( 64 ) => Some( 31 ),
+        _ => None
+    }
+}
+
+/// Base register of memory operand without offset, as used by exclusive and atomic instructions
+fn plain_base_at(operands: &[ARM64Operand], index: usize) -> Option<u32> {
+    match operands.get(index) {
+        Some( ARM64Operand::Memory( base , 0 , IndexingARM64::Offset ) ) => base_number(base),
+        _ => None
+    }
+}
+
+/// Bit 31 of instruction word, set for 64 bits operation
+fn size_bit(bits: u32) -> u32 {
+    match bits {
+        64 => 1 << 31,
+        _ => 0
+    }
+}
+
+/// Type field of floating point instruction for register size
+fn float_type(bits: u32) -> Option<u32> {
+    match bits {
+        32 => Some( 0 ),
+        64 => Some( 1 ),
+        16 => Some( 3 ),
+        _ => None
+    }
+}
+
+/// Option field of extended register for extend modifier
+fn extend_option(modifier: ModifierARM64) -> Option<u32> {
+    match modifier {
+        ModifierARM64::Uxtb => Some( 0 ),
+        ModifierARM64::Uxth => Some( 1 ),
+        ModifierARM64::Uxtw => Some( 2 ),
+        ModifierARM64::Uxtx => Some( 3 ),
+        ModifierARM64::Sxtb => Some( 4 ),
+        ModifierARM64::Sxth => Some( 5 ),
+        ModifierARM64::Sxtw => Some( 6 ),
+        ModifierARM64::Sxtx => Some( 7 ),
+        _ => None
+    }
+}
+
+/// Optional shift or extend given as last operand
+fn modifier_at(operands: &[ARM64Operand], index: usize) -> Option<Option<(ModifierARM64, u32)>> {
+    match operands.get(index) {
+        Some( ARM64Operand::Modifier( m , a ) ) if operands.len() == index + 1 => Some( Some( ( *m , *a as u32 ) ) ),
+        None if operands.len() == index => Some( None ),
+        _ => None
+    }
+}
+
+/// Fields N, immr and imms of logical immediate, none when value is not a rotated run of ones repeated over the register
+pub fn bitmask_immediate(value: u64, bits: u32) -> Option<(u32, u32, u32)> {
+    let value = match bits {
+        32 => ( value & 0xffff_ffff ) | ( value << 32 ),
+        _ => value
+    };
+    if value == 0 || value == u64::MAX {
+        return None
+    }
+
+    /* Smallest element size that repeats over the register */
+    let mut size = 64u32;
+    while size > 2 {
+        let half = size / 2;
+        let mask = ( 1u64 << half ) - 1;
+        if value & mask != ( value >> half ) & mask {
+            break
+        }
+        size = half
+    }
+
+    let mask = match size {
+        64 => u64::MAX,
+        _ => ( 1u64 << size ) - 1
+    };
+    let element = value & mask;
+    let ones = element.count_ones();
+    let run = ( 1u64 << ones ) - 1;
+
+    for rotation in 0 .. size {
+        let rotated = match rotation {
+            0 => element,
+            _ => ( ( element >> rotation ) | ( element << ( size - rotation ) ) ) & mask
+        };
+        if rotated == run {
+            let immr = ( size - rotation ) % size;
+            let imms = ( ( !( size - 1 ) << 1 ) & 0x3f ) | ( ones - 1 );
+            return Some( ( ( size == 64 ) as u32 , immr , imms ) )
+        }
+    }
+
+    None
+}
+
+/// Eight bits immediate of 'FMOV' for value, none when value is not (-1)^s * (1 + f/16) * 2^e with e in -3 to 4
+fn float_immediate(value: f64) -> Option<u32> {
+    for imm8 in 0u32 .. 256 {
+        let sign = match imm8 >> 7 {
+            0 => 1.0,
+            _ => -1.0
+        };
+        let exponent = match ( imm8 >> 6 ) & 1 {
+            0 => 1 + ( ( imm8 >> 4 ) & 3 ) as i32,
+            _ => ( ( imm8 >> 4 ) & 3 ) as i32 - 3
+        };
+        let fraction = 1.0 + ( imm8 & 15 ) as f64 / 16.0;
+        if sign * fraction * 2f64.powi(exponent) == value {
+            return Some( imm8 )
+        }
+    }
+
+    None
+}
+
+/// Distance to target in words as field of given bits, target must be aligned to instruction words
+fn branch_field(mnemonic: &str, target: i64, location: i64, bits: u32) -> Result<u32, Box<Diagnostic>> {
+    let distance = target.wrapping_sub(location);
+    if distance & 3 != 0 {
+        return Err(Diagnostic::error(E_ASSEMBLER_OPERAND, format!("Target of '{}' is not aligned to instruction word", mnemonic), 0, 0))
+    }
+
+    let words = distance >> 2;
+    let limit = 1i64 << ( bits - 1 );
+    if !( -limit .. limit ).contains(&words) {
+        return Err(range_error(mnemonic, "Target distance", distance))
+    }
+
+    Ok( ( words as u32 ) & ( ( 1u32 << bits ) - 1 ) )
+}
+
+/// ADD, ADDS, SUB and SUBS with immediate, shifted register or extended register
+fn arithmetic(mnemonic: &str, subtract: bool, set_flags: bool, operands: &[ARM64Operand]) -> Result<u32, Box<Diagnostic>> {
+    let modifier = match modifier_at(operands, 3) {
+        Some( x ) => x,
+        None => return Err(operand_error(mnemonic))
+    };
+    let ( d , n ) = match ( register_at(operands, 0) , register_at(operands, 1) ) {
+        ( Some( d ) , Some( n ) ) if !matches!(d, RegisterARM64::Float( .. )) && !matches!(n, RegisterARM64::Float( .. )) && d.bits() == n.bits() => ( d , n ),
+        _ => return Err(operand_error(mnemonic))
+    };
+    let bits = d.bits();
+    let operation = size_bit(bits) | ( subtract as u32 ) << 30 | ( set_flags as u32 ) << 29;
+
+    /* Destination of immediate and extended forms is stack pointer, except when flags are set where number 31 is zero register */
+    let destination = match ( set_flags , d ) {
+        ( true , RegisterARM64::General( x , _ ) ) => Some( *x as u32 ),
+        ( false , RegisterARM64::General( x , _ ) ) if *x != 31 => Some( *x as u32 ),
+        ( false , RegisterARM64::Stack( _ ) ) => Some( 31 ),
+        _ => None
+    };
+
+    match operands.get(2) {
+        Some( ARM64Operand::Immediate( value ) ) => {
+            let ( source , destination ) = match ( general_or_stack_at(operands, 1) , destination ) {
+                ( Some( ( x , _ ) ) , Some( y ) ) => ( x , y ),
+                _ => return Err(operand_error(mnemonic))
+            };
+            /* Negative immediate uses the opposite operation */
+            let ( value , operation ) = match *value < 0 {
+                true => ( value.wrapping_neg() , operation ^ 1 << 30 ),
+                false => ( *value , operation )
+            };
+            let shift = match modifier {
+                None => 0,
+                Some( ( ModifierARM64::Lsl , x ) ) if x == 0 || x == 12 => x,
+                _ => return Err(operand_error(mnemonic))
+            };
+            let ( immediate , shifted ) = match ( value , shift ) {
+                ( 0 ..= 4095 , _ ) => ( value as u32 , shift / 12 ),
+                ( _ , 0 ) if value & 0xfff == 0 && ( 0 ..= 0xfff_000 ).contains(&value) => ( ( value >> 12 ) as u32 , 1 ),
+                _ => return Err(range_error(mnemonic, "Immediate", value))
+            };
+
+            Ok( operation | 0x1100_0000 | shifted << 22 | immediate << 10 | source << 5 | destination )
+        },
+        Some( ARM64Operand::Register( m @ RegisterARM64::General( .. ) ) ) => {
+            let extended = matches!(d, RegisterARM64::Stack( _ )) || matches!(n, RegisterARM64::Stack( _ )) || matches!(modifier, Some( ( x , _ ) ) if extend_option(x).is_some());
+
+            match extended {
+                true => {
+                    let ( source , destination ) = match ( general_or_stack_at(operands, 1) , destination ) {
+                        ( Some( ( x , _ ) ) , Some( y ) ) => ( x , y ),
+                        _ => return Err(operand_error(mnemonic))
+                    };
+                    let ( option , amount ) = match modifier {
+                        None => ( 2 | ( bits == 64 ) as u32 , 0 ),
+                        Some( ( ModifierARM64::Lsl , x ) ) => ( 2 | ( bits == 64 ) as u32 , x ),
+                        Some( ( x , a ) ) => match extend_option(x) {
+                            Some( o ) => ( o , a ),
+                            None => return Err(operand_error(mnemonic))
+                        }
+                    };
+                    if amount > 4 {
+                        return Err(range_error(mnemonic, "Extend amount", amount as i64))
+                    }
+                    /* Index register is 64 bits only for UXTX and SXTX of 64 bits operation */
+                    let wide = bits == 64 && option & 3 == 3;
+                    if m.bits() != if wide { 64 } else { 32 } {
+                        return Err(operand_error(mnemonic))
+                    }
+
+                    Ok( operation | 0x0b20_0000 | ( m.number() as u32 ) << 16 | option << 13 | amount << 10 | source << 5 | destination )
+                },
+                _ => {
+                    let shift = match modifier {
+                        None => ( 0 , 0 ),
+                        Some( ( ModifierARM64::Lsl , x ) ) => ( 0 , x ),
+                        Some( ( ModifierARM64::Lsr , x ) ) => ( 1 , x ),
+                        Some( ( ModifierARM64::Asr , x ) ) => ( 2 , x ),
+                        _ => return Err(operand_error(mnemonic))
+                    };
+                    if m.bits() != bits {
+                        return Err(operand_error(mnemonic))
+                    }
+                    if shift.1 >= bits {
+                        return Err(range_error(mnemonic, "Shift amount", shift.1 as i64))
+                    }
+
+                    Ok( operation | 0x0b00_0000 | shift.0 << 22 | ( m.number() as u32 ) << 16 | shift.1 << 10 | ( n.number() as u32 ) << 5 | ( d.number() as u32 ) )
+                }
+            }
+        },
+        _ => Err(operand_error(mnemonic))
+    }
+}
+
+/// AND, ORR, EOR and ANDS with bitmask immediate or shifted register, 'negate' gives BIC, ORN, EON and BICS
+fn logical(mnemonic: &str, opc: u32, negate: bool, operands: &[ARM64Operand]) -> Result<u32, Box<Diagnostic>> {
+    let modifier = match modifier_at(operands, 3) {
+        Some( x ) => x,
+        None => return Err(operand_error(mnemonic))
+    };
+    let ( n , bits ) = match general_at(operands, 1) {
+        Some( x ) => x,
+        None => return Err(operand_error(mnemonic))
+    };
+    let operation = size_bit(bits) | opc << 29;
+
+    match operands.get(2) {
+        Some( ARM64Operand::Immediate( value ) ) if modifier.is_none() => {
+            /* Destination is stack pointer, except for ANDS where number 31 is zero register */
+            let d = match opc {
+                3 => general_at(operands, 0),
+                _ => general_or_stack_at(operands, 0)
+            };
+            let d = match d {
+                Some( ( x , b ) ) if b == bits => x,
+                _ => return Err(operand_error(mnemonic))
+            };
+            let value = match negate {
+                true => !*value,
+                false => *value
+            };
+            let pattern = match bits {
+                32 if ( i32::MIN as i64 ..= u32::MAX as i64 ).contains(&value) => value as u64 & 0xffff_ffff,
+                32 => return Err(range_error(mnemonic, "Immediate", value)),
+                _ => value as u64
+            };
+
+            match bitmask_immediate(pattern, bits) {
+                Some( ( wide , immr , imms ) ) => Ok( operation | 0x1200_0000 | wide << 22 | immr << 16 | imms << 10 | n << 5 | d ),
+                None => Err(Diagnostic::error(E_ASSEMBLER_OPERAND, format!("Immediate {} is not a valid bitmask for '{}'", value, mnemonic), 0, 0))
+            }
+        },
+        Some( ARM64Operand::Register( RegisterARM64::General( m , b ) ) ) if *b == bits => {
+            let d = match general_at(operands, 0) {
+                Some( ( x , b ) ) if b == bits => x,
+                _ => return Err(operand_error(mnemonic))
+            };
+            let ( shift , amount ) = match modifier {
+                None => ( 0 , 0 ),
+                Some( ( ModifierARM64::Lsl , x ) ) => ( 0 , x ),
+                Some( ( ModifierARM64::Lsr , x ) ) => ( 1 , x ),
+                Some( ( ModifierARM64::Asr , x ) ) => ( 2 , x ),
+                Some( ( ModifierARM64::Ror , x ) ) => ( 3 , x ),
+                _ => return Err(operand_error(mnemonic))
+            };
+            if amount >= bits {
+                return Err(range_error(mnemonic, "Shift amount", amount as i64))
+            }
+
+            Ok( operation | 0x0a00_0000 | shift << 22 | ( negate as u32 ) << 21 | ( *m as u32 ) << 16 | amount << 10 | n << 5 | d )
+        },
+        _ => Err(operand_error(mnemonic))
+    }
+}
+
+/// MOV of immediate as MOVZ or MOVN when possible, otherwise as ORR with bitmask immediate
+fn move_immediate(mnemonic: &str, operands: &[ARM64Operand], value: i64) -> Result<u32, Box<Diagnostic>> {
+    let register = match register_at(operands, 0) {
+        Some( x ) => x.clone(),
+        None => return Err(operand_error(mnemonic))
+    };
+    let bits = register.bits();
+    let ( pattern , mask ) = match bits {
+        32 if ( i32::MIN as i64 ..= u32::MAX as i64 ).contains(&value) => ( value as u64 & 0xffff_ffff , 0xffff_ffffu64 ),
+        32 => return Err(range_error(mnemonic, "Immediate", value)),
+        _ => ( value as u64 , u64::MAX )
+    };
+
+    if let Some( ( d , _ ) ) = general_at(operands, 0) {
+        for ( opc , wide ) in [ ( 2u32 , pattern ), ( 0u32 , !pattern & mask ) ] {
+            for hw in 0 .. bits / 16 {
+                if wide & !( 0xffffu64 << ( hw * 16 ) ) == 0 {
+                    return Ok( size_bit(bits) | opc << 29 | 0x1280_0000 | hw << 21 | ( ( ( wide >> ( hw * 16 ) ) & 0xffff ) as u32 ) << 5 | d )
+                }
+            }
+        }
+    }
+
+    logical(mnemonic, 1, false, &[ ARM64Operand::Register(register), ARM64Operand::Register(RegisterARM64::General(31, bits)), ARM64Operand::Immediate(value) ])
+}
+
+/// MOVN, MOVZ and MOVK of 16 bits immediate with optional shift
+fn wide_move(mnemonic: &str, opc: u32, operands: &[ARM64Operand]) -> Result<u32, Box<Diagnostic>> {
+    let ( d , bits ) = match general_at(operands, 0) {
+        Some( x ) => x,
+        None => return Err(operand_error(mnemonic))
+    };
+    let value = match immediate_at(operands, 1) {
+        Some( x ) => x,
+        None => return Err(operand_error(mnemonic))
+    };
+    let shift = match modifier_at(operands, 2) {
+        Some( None ) => 0,
+        Some( Some( ( ModifierARM64::Lsl , x ) ) ) if x % 16 == 0 && x < bits => x,
+        _ => return Err(operand_error(mnemonic))
+    };
+    if !( 0 ..= 0xffff ).contains(&value) {
+        return Err(range_error(mnemonic, "Immediate", value))
+    }
+
+    Ok( size_bit(bits) | opc << 29 | 0x1280_0000 | ( shift / 16 ) << 21 | ( value as u32 ) << 5 | d )
+}
+
+/// SBFM, BFM and UBFM with fields immr and imms
+fn bitfield(opc: u32, bits: u32, d: u32, n: u32, immr: u32, imms: u32) -> u32 {
+    size_bit(bits) | opc << 29 | 0x1300_0000 | ( ( bits == 64 ) as u32 ) << 22 | immr << 16 | imms << 10 | n << 5 | d
+}
+
+/// Two general registers of same size followed by number of immediates, each less than register size
+fn registers_and_immediates(mnemonic: &str, operands: &[ARM64Operand], count: usize) -> Result<(u32, u32, u32, Vec<u32>), Box<Diagnostic>> {
+    let ( d , n , bits ) = match ( general_at(operands, 0) , general_at(operands, 1) ) {
+        ( Some( ( d , b ) ) , Some( ( n , c ) ) ) if b == c && operands.len() == 2 + count => ( d , n , b ),
+        _ => return Err(operand_error(mnemonic))
+    };
+    let mut values = Vec::<u32>::new();
+
+    for i in 0 .. count {
+        match immediate_at(operands, 2 + i) {
+            Some( x ) if ( 0 .. bits as i64 ).contains(&x) => values.push(x as u32),
+            Some( x ) => return Err(range_error(mnemonic, "Immediate", x)),
+            None => return Err(operand_error(mnemonic))
+        }
+    }
+
+    Ok( ( d , n , bits , values ) )
+}
+
+/// Aliases of bitfield move with least significant bit and width, 'insert' places field at lsb instead of extracting it
+fn bitfield_alias(mnemonic: &str, opc: u32, insert: bool, operands: &[ARM64Operand]) -> Result<u32, Box<Diagnostic>> {
+    let ( d , n , bits , values ) = match ( general_at(operands, 0) , general_at(operands, 1) , immediate_at(operands, 2) , immediate_at(operands, 3) ) {
+        ( Some( ( d , b ) ) , Some( ( n , c ) ) , Some( lsb ) , Some( width ) ) if b == c && operands.len() == 4 => ( d , n , b , ( lsb , width ) ),
+        _ => return Err(operand_error(mnemonic))
+    };
+    let ( lsb , width ) = values;
+    if !( 0 .. bits as i64 ).contains(&lsb) {
+        return Err(range_error(mnemonic, "Least significant bit", lsb))
+    }
+    if width < 1 || width > bits as i64 - lsb {
+        return Err(range_error(mnemonic, "Width", width))
+    }
+
+    let ( lsb , width ) = ( lsb as u32 , width as u32 );
+    match insert {
+        true => Ok( bitfield(opc, bits, d, n, ( bits - lsb ) % bits, width - 1) ),
+        false => Ok( bitfield(opc, bits, d, n, lsb, lsb + width - 1) )
+    }
+}
+
+/// Three general registers of same size, as used by data processing with two sources
+fn three_registers(mnemonic: &str, operands: &[ARM64Operand]) -> Result<(u32, u32, u32, u32), Box<Diagnostic>> {
+    match ( general_at(operands, 0) , general_at(operands, 1) , general_at(operands, 2) ) {
+        ( Some( ( d , b ) ) , Some( ( n , c ) ) , Some( ( m , e ) ) ) if b == c && b == e && operands.len() == 3 => Ok( ( d , n , m , b ) ),
+        _ => Err(operand_error(mnemonic))
+    }
+}
+
+/// Shifts by immediate as bitfield move or extract, by register as variable shift
+fn shift(mnemonic: &str, kind: u32, operands: &[ARM64Operand]) -> Result<u32, Box<Diagnostic>> {
+    match operands.get(2) {
+        Some( ARM64Operand::Immediate( _ ) ) => {
+            let ( d , n , bits , values ) = registers_and_immediates(mnemonic, operands, 1)?;
+            let amount = values[0];
+            match kind {
+                0 => Ok( bitfield(2, bits, d, n, ( bits - amount ) % bits, bits - 1 - amount) ),
+                1 => Ok( bitfield(2, bits, d, n, amount, bits - 1) ),
+                2 => Ok( bitfield(0, bits, d, n, amount, bits - 1) ),
+                _ => Ok( size_bit(bits) | 0x1380_0000 | ( ( bits == 64 ) as u32 ) << 22 | n << 16 | amount << 10 | n << 5 | d )
+            }
+        },
+        _ => {
+            let ( d , n , m , bits ) = three_registers(mnemonic, operands)?;
+            Ok( size_bit(bits) | 0x1ac0_0000 | m << 16 | ( 8 + kind ) << 10 | n << 5 | d )
+        }
+    }
+}
+
+/// Data processing with one source like REV and CLZ
+fn single_source(mnemonic: &str, opcode: u32, operands: &[ARM64Operand]) -> Result<u32, Box<Diagnostic>> {
+    match ( general_at(operands, 0) , general_at(operands, 1) ) {
+        ( Some( ( d , b ) ) , Some( ( n , c ) ) ) if b == c && operands.len() == 2 => Ok( size_bit(b) | 0x5ac0_0000 | opcode << 10 | n << 5 | d ),
+        _ => Err(operand_error(mnemonic))
+    }
+}
+
+/// MADD and MSUB family with accumulator, 'accumulate' is false for MUL and MNEG that use zero register
+fn multiply(mnemonic: &str, base: u32, accumulate: bool, operands: &[ARM64Operand]) -> Result<u32, Box<Diagnostic>> {
+    let ( d , n , m , bits ) = three_registers(mnemonic, &operands[.. operands.len().min(3)])?;
+    let a = match ( accumulate , general_at(operands, 3) ) {
+        ( true , Some( ( a , b ) ) ) if b == bits && operands.len() == 4 => a,
+        ( false , None ) if operands.len() == 3 => 31,
+        _ => return Err(operand_error(mnemonic))
+    };
+
+    Ok( size_bit(bits) | base | m << 16 | a << 10 | n << 5 | d )
+}
+
+/// SMADDL family with 64 bits destination and accumulator from 32 bits sources
+fn multiply_long(mnemonic: &str, base: u32, accumulate: bool, operands: &[ARM64Operand]) -> Result<u32, Box<Diagnostic>> {
+    let a = match ( accumulate , general_at(operands, 3) ) {
+        ( true , Some( ( a , 64 ) ) ) if operands.len() == 4 => a,
+        ( false , None ) if operands.len() == 3 => 31,
+        _ => return Err(operand_error(mnemonic))
+    };
+
+    match ( general_at(operands, 0) , general_at(operands, 1) , general_at(operands, 2) ) {
+        ( Some( ( d , 64 ) ) , Some( ( n , 32 ) ) , Some( ( m , 32 ) ) ) => Ok( base | m << 16 | a << 10 | n << 5 | d ),
+        _ => Err(operand_error(mnemonic))
+    }
+}
+
+/// CSEL, CSINC, CSINV and CSNEG
+fn conditional_select(mnemonic: &str, base: u32, operands: &[ARM64Operand]) -> Result<u32, Box<Diagnostic>> {
+    let ( d , n , m , bits ) = three_registers(mnemonic, &operands[.. operands.len().min(3)])?;
+
+    match condition_at(operands, 3) {
+        Some( c ) if operands.len() == 4 => Ok( size_bit(bits) | base | m << 16 | c << 12 | n << 5 | d ),
+        _ => Err(operand_error(mnemonic))
+    }
+}
+
+/// Aliases of conditional select with inverted condition, 'CSET' and 'CSETM' have one register and 'CINC' family two
+fn conditional_alias(mnemonic: &str, base: u32, operands: &[ARM64Operand]) -> Result<u32, Box<Diagnostic>> {
+    let ( registers , condition ) = match operands.split_last() {
+        Some( ( ARM64Operand::Condition( c ) , rest ) ) if *c < 14 => ( rest , *c ^ 1 ),
+        Some( ( ARM64Operand::Condition( _ ) , _ ) ) => return Err(Diagnostic::error(E_ASSEMBLER_OPERAND, format!("Condition 'AL' and 'NV' are not allowed for '{}'", mnemonic), 0, 0)),
+        _ => return Err(operand_error(mnemonic))
+    };
+    let source = match ( registers.len() , register_at(registers, 0) ) {
+        ( 1 , Some( d ) ) => ARM64Operand::Register(RegisterARM64::General(31, d.bits())),
+        ( 2 , Some( _ ) ) => registers[1].clone(),
+        _ => return Err(operand_error(mnemonic))
+    };
+
+    conditional_select(mnemonic, base, &[ registers[0].clone(), source.clone(), source, ARM64Operand::Condition(condition) ])
+}
+
+/// CCMP and CCMN with register or five bits immediate
+fn conditional_compare(mnemonic: &str, base: u32, operands: &[ARM64Operand]) -> Result<u32, Box<Diagnostic>> {
+    let ( n , bits ) = match general_at(operands, 0) {
+        Some( x ) => x,
+        None => return Err(operand_error(mnemonic))
+    };
+    let ( second , immediate ) = match operands.get(1) {
+        Some( ARM64Operand::Register( RegisterARM64::General( m , b ) ) ) if *b == bits => ( *m as u32 , 0 ),
+        Some( ARM64Operand::Immediate( x ) ) if ( 0 ..= 31 ).contains(x) => ( *x as u32 , 1 ),
+        Some( ARM64Operand::Immediate( x ) ) => return Err(range_error(mnemonic, "Immediate", *x)),
+        _ => return Err(operand_error(mnemonic))
+    };
+
+    match ( immediate_at(operands, 2) , condition_at(operands, 3) ) {
+        ( Some( nzcv ) , Some( c ) ) if ( 0 ..= 15 ).contains(&nzcv) && operands.len() == 4 => Ok( size_bit(bits) | base | second << 16 | c << 12 | immediate << 11 | n << 5 | nzcv as u32 ),
+        _ => Err(operand_error(mnemonic))
+    }
+}
+
+/// Size, opc and vector bit of single register load or store, mnemonic without 'U' of unscaled forms
+fn load_store_class(mnemonic: &str, register: &RegisterARM64) -> Option<(u32, u32, u32)> {
+    let size = | bits : u32 | bits.trailing_zeros() - 3;
+
+    match ( mnemonic , register ) {
+        ( "STRB" , RegisterARM64::General( _ , 32 ) ) => Some( ( 0 , 0 , 0 ) ),
+        ( "LDRB" , RegisterARM64::General( _ , 32 ) ) => Some( ( 0 , 1 , 0 ) ),
+        ( "LDRSB" , RegisterARM64::General( _ , 64 ) ) => Some( ( 0 , 2 , 0 ) ),
+        ( "LDRSB" , RegisterARM64::General( _ , 32 ) ) => Some( ( 0 , 3 , 0 ) ),
+        ( "STRH" , RegisterARM64::General( _ , 32 ) ) => Some( ( 1 , 0 , 0 ) ),
+        ( "LDRH" , RegisterARM64::General( _ , 32 ) ) => Some( ( 1 , 1 , 0 ) ),
+        ( "LDRSH" , RegisterARM64::General( _ , 64 ) ) => Some( ( 1 , 2 , 0 ) ),
+        ( "LDRSH" , RegisterARM64::General( _ , 32 ) ) => Some( ( 1 , 3 , 0 ) ),
+        ( "LDRSW" , RegisterARM64::General( _ , 64 ) ) => Some( ( 2 , 2 , 0 ) ),
+        ( "STR" , RegisterARM64::General( _ , b ) ) => Some( ( size(*b) , 0 , 0 ) ),
+        ( "LDR" , RegisterARM64::General( _ , b ) ) => Some( ( size(*b) , 1 , 0 ) ),
+        ( "STR" , RegisterARM64::Float( _ , 128 ) ) => Some( ( 0 , 2 , 1 ) ),
+        ( "LDR" , RegisterARM64::Float( _ , 128 ) ) => Some( ( 0 , 3 , 1 ) ),
+        ( "STR" , RegisterARM64::Float( _ , b ) ) => Some( ( size(*b) , 0 , 1 ) ),
+        ( "LDR" , RegisterARM64::Float( _ , b ) ) => Some( ( size(*b) , 1 , 1 ) ),
+        _ => None
+    }
+}
+
+/// Single register load or store with immediate offset, register offset or literal, 'unscaled' only allows LDUR and STUR forms
+fn load_store(mnemonic: &str, class: &str, unscaled: bool, operands: &[ARM64Operand], location: i64) -> Result<u32, Box<Diagnostic>> {
+    let register = match register_at(operands, 0) {
+        Some( x ) if operands.len() == 2 => x,
+        _ => return Err(operand_error(mnemonic))
+    };
+    let ( size , opc , vector ) = match load_store_class(class, register) {
+        Some( x ) => x,
+        None => return Err(operand_error(mnemonic))
+    };
+    let t = register.number() as u32;
+    let shift = match vector == 1 && opc >= 2 {
+        true => 4,
+        false => size
+    };
+    let operation = size << 30 | vector << 26 | opc << 22 | t;
+
+    match &operands[1] {
+        ARM64Operand::Memory( base , offset , indexing ) => {
+            let n = match base_number(base) {
+                Some( x ) => x,
+                None => return Err(operand_error(mnemonic))
+            };
+            let scaled = offset >> shift;
+            match indexing {
+                IndexingARM64::Offset if !unscaled && *offset >= 0 && scaled << shift == *offset && scaled <= 4095 => Ok( operation | 0x3900_0000 | ( scaled as u32 ) << 10 | n << 5 ),
+                _ if ( -256 ..= 255 ).contains(offset) => {
+                    let index = match indexing {
+                        IndexingARM64::Offset => 0,
+                        IndexingARM64::PostIndex if !unscaled => 1,
+                        IndexingARM64::PreIndex if !unscaled => 3,
+                        _ => return Err(operand_error(mnemonic))
+                    };
+                    Ok( operation | 0x3800_0000 | ( ( *offset as u32 ) & 0x1ff ) << 12 | index << 10 | n << 5 )
+                },
+                _ => Err(range_error(mnemonic, "Offset", *offset))
+            }
+        },
+        ARM64Operand::MemoryRegister( base , index , modifier , amount ) if !unscaled => {
+            let n = match base_number(base) {
+                Some( x ) => x,
+                None => return Err(operand_error(mnemonic))
+            };
+            let option = match ( modifier , index ) {
+                ( ModifierARM64::Lsl , RegisterARM64::General( _ , 64 ) ) => 3,
+                ( ModifierARM64::Uxtw , RegisterARM64::General( _ , 32 ) ) => 2,
+                ( ModifierARM64::Sxtw , RegisterARM64::General( _ , 32 ) ) => 6,
+                ( ModifierARM64::Sxtx , RegisterARM64::General( _ , 64 ) ) => 7,
+                _ => return Err(operand_error(mnemonic))
+            };
+            let scaled = match *amount as u32 {
+                0 => 0,
+                x if x == shift => 1,
+                x => return Err(range_error(mnemonic, "Index shift", x as i64))
+            };
+            Ok( operation | 0x3820_0800 | ( index.number() as u32 ) << 16 | option << 13 | scaled << 12 | n << 5 )
+        },
+        ARM64Operand::Immediate( target ) if !unscaled => {
+            /* Literal is only loaded into 32 and 64 bits registers */
+            let literal = match ( class , register ) {
+                ( "LDR" , RegisterARM64::General( _ , 32 ) ) => 0x1800_0000,
+                ( "LDR" , RegisterARM64::General( _ , 64 ) ) => 0x5800_0000,
+                ( "LDRSW" , _ ) => 0x9800_0000,
+                ( "LDR" , RegisterARM64::Float( _ , 32 ) ) => 0x1c00_0000,
+                ( "LDR" , RegisterARM64::Float( _ , 64 ) ) => 0x5c00_0000,
+                ( "LDR" , RegisterARM64::Float( _ , 128 ) ) => 0x9c00_0000,
+                _ => return Err(operand_error(mnemonic))
+            };
+            Ok( literal | branch_field(mnemonic, *target, location, 19)? << 5 | t )
+        },
+        _ => Err(operand_error(mnemonic))
+    }
+}
+
+/// LDP, STP, LDPSW, LDNP and STNP of two registers with scaled seven bits offset
+fn load_store_pair(mnemonic: &str, load: bool, non_temporal: bool, operands: &[ARM64Operand]) -> Result<u32, Box<Diagnostic>> {
+    let ( first , second ) = match ( register_at(operands, 0) , register_at(operands, 1) ) {
+        ( Some( x ) , Some( y ) ) if operands.len() == 3 => ( x , y ),
+        _ => return Err(operand_error(mnemonic))
+    };
+    let ( opc , vector , shift ) = match ( mnemonic , first , second ) {
+        ( "LDPSW" , RegisterARM64::General( _ , 64 ) , RegisterARM64::General( _ , 64 ) ) => ( 1 , 0 , 2 ),
+        ( "LDPSW" , _ , _ ) => return Err(operand_error(mnemonic)),
+        ( _ , RegisterARM64::General( _ , 32 ) , RegisterARM64::General( _ , 32 ) ) => ( 0 , 0 , 2 ),
+        ( _ , RegisterARM64::General( _ , 64 ) , RegisterARM64::General( _ , 64 ) ) => ( 2 , 0 , 3 ),
+        ( _ , RegisterARM64::Float( _ , 32 ) , RegisterARM64::Float( _ , 32 ) ) => ( 0 , 1 , 2 ),
+        ( _ , RegisterARM64::Float( _ , 64 ) , RegisterARM64::Float( _ , 64 ) ) => ( 1 , 1 , 3 ),
+        ( _ , RegisterARM64::Float( _ , 128 ) , RegisterARM64::Float( _ , 128 ) ) => ( 2 , 1 , 4 ),
+        _ => return Err(operand_error(mnemonic))
+    };
+    let ( n , offset , index ) = match &operands[2] {
+        ARM64Operand::Memory( base , offset , indexing ) => {
+            let index = match ( indexing , non_temporal ) {
+                ( IndexingARM64::Offset , true ) => 0,
+                ( IndexingARM64::PostIndex , false ) => 1,
+                ( IndexingARM64::Offset , false ) => 2,
+                ( IndexingARM64::PreIndex , false ) => 3,
+                _ => return Err(operand_error(mnemonic))
+            };
+            match base_number(base) {
+                Some( n ) => ( n , *offset , index ),
+                None => return Err(operand_error(mnemonic))
+            }
+        },
+        _ => return Err(operand_error(mnemonic))
+    };
+    let scaled = offset >> shift;
+    if scaled << shift != offset || !( -64 ..= 63 ).contains(&scaled) {
+        return Err(range_error(mnemonic, "Offset", offset))
+    }
+
+    Ok( opc << 30 | 0x2800_0000 | vector << 26 | index << 23 | ( load as u32 ) << 22 | ( ( scaled as u32 ) & 0x7f ) << 15 | ( second.number() as u32 ) << 10 | n << 5 | ( first.number() as u32 ) )
+}
+
+/// Size field from byte or halfword suffix of mnemonic or from size of register, suffixed forms use 32 bits registers
+fn access_size(suffix: Option<u32>, bits: u32) -> Option<u32> {
+    match ( suffix , bits ) {
+        ( Some( x ) , 32 ) => Some( x ),
+        ( None , 32 ) => Some( 2 ),
+        ( None , 64 ) => Some( 3 ),
+        _ => None
+    }
+}
+
+/// Exclusive and acquire / release loads and stores, 'status' tells if first operand is status register of store exclusive
+fn exclusive(mnemonic: &str, base: u32, suffix: Option<u32>, status: bool, pair: bool, operands: &[ARM64Operand]) -> Result<u32, Box<Diagnostic>> {
+    let registers = match ( status , pair ) {
+        ( true , true ) => 3,
+        ( true , false ) | ( false , true ) => 2,
+        _ => 1
+    };
+    let n = match plain_base_at(operands, registers) {
+        Some( x ) if operands.len() == registers + 1 => x,
+        _ => return Err(operand_error(mnemonic))
+    };
+    let s = match status {
+        true => match general_at(operands, 0) {
+            Some( ( x , 32 ) ) => x,
+            _ => return Err(operand_error(mnemonic))
+        },
+        false => 31
+    };
+    let first = status as usize;
+    let ( t , bits ) = match general_at(operands, first) {
+        Some( x ) => x,
+        None => return Err(operand_error(mnemonic))
+    };
+    let t2 = match ( pair , general_at(operands, first + 1) ) {
+        ( true , Some( ( x , b ) ) ) if b == bits => x,
+        ( false , _ ) => 31,
+        _ => return Err(operand_error(mnemonic))
+    };
+    let size = match ( pair , access_size(suffix, bits) ) {
+        ( true , Some( x ) ) if x >= 2 => x,
+        ( false , Some( x ) ) => x,
+        _ => return Err(operand_error(mnemonic))
+    };
+
+    Ok( size << 30 | base | s << 16 | t2 << 10 | n << 5 | t )
+}
+
+/// Split mnemonic of atomic memory operation into operation, acquire, release and byte or halfword size
+fn atomic_parts(mnemonic: &str) -> Option<(&str, bool, bool, Option<u32>)> {
+    let operations = [ "LDADD", "LDCLR", "LDEOR", "LDSET", "LDSMAX", "LDSMIN", "LDUMAX", "LDUMIN", "SWP", "CAS",
+                       "STADD", "STCLR", "STEOR", "STSET", "STSMAX", "STSMIN", "STUMAX", "STUMIN" ];
+    let ( rest , size ) = match ( mnemonic.strip_suffix('B') , mnemonic.strip_suffix('H') ) {
+        ( Some( x ) , _ ) => ( x , Some( 0 ) ),
+        ( _ , Some( x ) ) => ( x , Some( 1 ) ),
+        _ => ( mnemonic , None )
+    };
+
+    for ( suffix , acquire , release ) in [ ( "AL" , true , true ), ( "A" , true , false ), ( "L" , false , true ), ( "" , false , false ) ] {
+        match rest.strip_suffix(suffix) {
+            Some( operation ) if operations.contains(&operation) => {
+                /* Atomic stores have no result so they can not acquire */
+                if operation.starts_with("ST") && acquire {
+                    return None
+                }
+                return Some( ( operation , acquire , release , size ) )
+            },
+            _ => ()
+        }
+    }
+
+    None
+}
+
+/// Atomic memory operations and compare and swap of large system extension
+fn atomic(mnemonic: &str, operation: &str, acquire: bool, release: bool, suffix: Option<u32>, operands: &[ARM64Operand]) -> Result<u32, Box<Diagnostic>> {
+    /* Atomic stores are loads into zero register */
+    let store = operation.starts_with("ST");
+    let registers = match store {
+        true => 1,
+        false => 2
+    };
+    let n = match plain_base_at(operands, registers) {
+        Some( x ) if operands.len() == registers + 1 => x,
+        _ => return Err(operand_error(mnemonic))
+    };
+    let ( s , bits ) = match general_at(operands, 0) {
+        Some( x ) => x,
+        None => return Err(operand_error(mnemonic))
+    };
+    let t = match ( store , general_at(operands, 1) ) {
+        ( true , _ ) => 31,
+        ( false , Some( ( x , b ) ) ) if b == bits => x,
+        _ => return Err(operand_error(mnemonic))
+    };
+    let size = match access_size(suffix, bits) {
+        Some( x ) => x,
+        None => return Err(operand_error(mnemonic))
+    };
+
+    match operation {
+        "CAS" => Ok( size << 30 | 0x08a0_7c00 | ( acquire as u32 ) << 22 | s << 16 | ( release as u32 ) << 15 | n << 5 | t ),
+        _ => {
+            let ( o3 , opc ) = match &operation[2 ..] {
+                "ADD" => ( 0 , 0 ),
+                "CLR" => ( 0 , 1 ),
+                "EOR" => ( 0 , 2 ),
+                "SET" => ( 0 , 3 ),
+                "SMAX" => ( 0 , 4 ),
+                "SMIN" => ( 0 , 5 ),
+                "UMAX" => ( 0 , 6 ),
+                "UMIN" => ( 0 , 7 ),
+                _ => ( 1 , 0 )
+            };
+            Ok( size << 30 | 0x3820_0000 | ( acquire as u32 ) << 23 | ( release as u32 ) << 22 | s << 16 | o3 << 15 | opc << 12 | n << 5 | t )
+        }
+    }
+}
+
+/// SIMD & FP registers of same size with type field, given number of registers and nothing more
+fn float_registers(mnemonic: &str, operands: &[ARM64Operand], count: usize) -> Result<(Vec<u32>, u32), Box<Diagnostic>> {
+    let mut numbers = Vec::<u32>::new();
+    let mut size = 0;
+
+    if operands.len() != count {
+        return Err(operand_error(mnemonic))
+    }
+    for i in 0 .. count {
+        match float_at(operands, i) {
+            Some( ( n , b ) ) if i == 0 || b == size => {
+                numbers.push(n);
+                size = b
+            },
+            _ => return Err(operand_error(mnemonic))
+        }
+    }
+
+    match float_type(size) {
+        Some( x ) => Ok( ( numbers , x ) ),
+        None => Err(operand_error(mnemonic))
+    }
+}
+
+/// Conversion between general register and floating point register
+fn float_conversion(mnemonic: &str, to_float: bool, rmode: u32, opcode: u32, operands: &[ARM64Operand]) -> Result<u32, Box<Diagnostic>> {
+    let registers = match to_float {
+        true => ( float_at(operands, 0) , general_at(operands, 1) ),
+        false => ( float_at(operands, 1) , general_at(operands, 0) )
+    };
+
+    match registers {
+        ( Some( ( f , fb ) ) , Some( ( g , gb ) ) ) if operands.len() == 2 => {
+            let kind = match float_type(fb) {
+                Some( x ) => x,
+                None => return Err(operand_error(mnemonic))
+            };
+            let ( d , n ) = match to_float {
+                true => ( f , g ),
+                false => ( g , f )
+            };
+            Ok( size_bit(gb) | 0x1e20_0000 | kind << 22 | rmode << 19 | opcode << 16 | n << 5 | d )
+        },
+        _ => Err(operand_error(mnemonic))
+    }
+}
+
+/// FMOV between registers, between general and floating point registers or of immediate
+fn float_move(mnemonic: &str, operands: &[ARM64Operand]) -> Result<u32, Box<Diagnostic>> {
+    match ( operands.first() , operands.get(1) ) {
+        ( Some( ARM64Operand::Register( RegisterARM64::Float( .. ) ) ) , Some( ARM64Operand::Register( RegisterARM64::Float( .. ) ) ) ) => {
+            let ( numbers , kind ) = float_registers(mnemonic, operands, 2)?;
+            Ok( 0x1e20_4000 | kind << 22 | numbers[1] << 5 | numbers[0] )
+        },
+        ( Some( ARM64Operand::Register( RegisterARM64::Float( _ , b ) ) ) , Some( ARM64Operand::Register( RegisterARM64::General( _ , c ) ) ) ) if b == c || *b == 16 => float_conversion(mnemonic, true, 0, 7, operands),
+        ( Some( ARM64Operand::Register( RegisterARM64::General( _ , c ) ) ) , Some( ARM64Operand::Register( RegisterARM64::Float( _ , b ) ) ) ) if b == c || *b == 16 => float_conversion(mnemonic, false, 0, 6, operands),
+        ( Some( ARM64Operand::Register( RegisterARM64::Float( d , b ) ) ) , Some( ARM64Operand::Float( _ ) | ARM64Operand::Immediate( _ ) ) ) if operands.len() == 2 => {
+            let kind = match float_type(*b) {
+                Some( x ) => x,
+                None => return Err(operand_error(mnemonic))
+            };
+            let value = match &operands[1] {
+                ARM64Operand::Float( x ) => *x,
+                ARM64Operand::Immediate( x ) => *x as f64,
+                _ => return Err(operand_error(mnemonic))
+            };
+            match float_immediate(value) {
+                Some( x ) => Ok( 0x1e20_1000 | kind << 22 | x << 13 | *d as u32 ),
+                None => Err(Diagnostic::error(E_ASSEMBLER_OPERAND, format!("Floating point value {} can not be encoded as immediate of '{}'", value, mnemonic), 0, 0))
+            }
+        },
+        _ => Err(operand_error(mnemonic))
+    }
+}
+
+/// FCMP and FCMPE with register or zero
+fn float_compare(mnemonic: &str, signaling: u32, operands: &[ARM64Operand]) -> Result<u32, Box<Diagnostic>> {
+    let zero = match operands.get(1) {
+        Some( ARM64Operand::Immediate( 0 ) ) => true,
+        Some( ARM64Operand::Float( x ) ) => *x == 0.0,
+        _ => false
+    };
+
+    match zero {
+        true => {
+            let ( numbers , kind ) = float_registers(mnemonic, &operands[.. 1], 1)?;
+            match operands.len() {
+                2 => Ok( 0x1e20_2008 | kind << 22 | numbers[0] << 5 | signaling << 4 ),
+                _ => Err(operand_error(mnemonic))
+            }
+        },
+        _ => {
+            let ( numbers , kind ) = float_registers(mnemonic, operands, 2)?;
+            Ok( 0x1e20_2000 | kind << 22 | numbers[1] << 16 | numbers[0] << 5 | signaling << 4 )
+        }
+    }
+}
+
+/// Encode single instruction word, flags are already expanded with implied flags
+fn encode_word(mnemonic: &str, operands: &[ARM64Operand], location: i64, flags: CpuFlags) -> Result<u32, Box<Diagnostic>> {
+    let zero = | index : usize | match register_at(operands, index) {
+        Some( x ) => ARM64Operand::Register(RegisterARM64::General(31, x.bits())),
+        None => ARM64Operand::Register(RegisterARM64::General(31, 64))
+    };
+    let with_zero = | at : usize, index : usize | {
+        let mut result = operands.to_vec();
+        result.insert(at, zero(index));
+        result
+    };
+
+    match mnemonic {
+        /* Data processing */
+        "ADD" | "SUB" if float_at(operands, 0).is_some() => {
+            require(mnemonic, flags, CPU_NEON)?;
+            match float_registers(mnemonic, operands, 3)? {
+                ( numbers , 1 ) => Ok( ( ( mnemonic == "SUB" ) as u32 ) << 29 | 0x5ee0_8400 | numbers[2] << 16 | numbers[1] << 5 | numbers[0] ),
+                _ => Err(operand_error(mnemonic))
+            }
+        },
+        "ADD" => arithmetic(mnemonic, false, false, operands),
+        "ADDS" => arithmetic(mnemonic, false, true, operands),
+        "SUB" => arithmetic(mnemonic, true, false, operands),
+        "SUBS" => arithmetic(mnemonic, true, true, operands),
+        "CMP" => arithmetic(mnemonic, true, true, &with_zero(0, 0)),
+        "CMN" => arithmetic(mnemonic, false, true, &with_zero(0, 0)),
+        "NEG" => arithmetic(mnemonic, true, false, &with_zero(1, 0)),
+        "NEGS" => arithmetic(mnemonic, true, true, &with_zero(1, 0)),
+        "AND" => logical(mnemonic, 0, false, operands),
+        "ORR" => logical(mnemonic, 1, false, operands),
+        "EOR" => logical(mnemonic, 2, false, operands),
+        "ANDS" => logical(mnemonic, 3, false, operands),
+        "BIC" => logical(mnemonic, 0, true, operands),
+        "ORN" => logical(mnemonic, 1, true, operands),
+        "EON" => logical(mnemonic, 2, true, operands),
+        "BICS" => logical(mnemonic, 3, true, operands),
+        "TST" => logical(mnemonic, 3, false, &with_zero(0, 0)),
+        "MVN" => logical(mnemonic, 1, true, &with_zero(1, 0)),
+        "MOV" => {
+            match ( register_at(operands, 0) , operands.get(1) , operands.len() ) {
+                ( Some( RegisterARM64::Float( .. ) ) , _ , _ ) => float_move(mnemonic, operands),
+                ( Some( _ ) , Some( ARM64Operand::Immediate( x ) ) , 2 ) => move_immediate(mnemonic, operands, *x),
+                ( Some( RegisterARM64::Stack( _ ) ) , _ , 2 ) | ( Some( _ ) , Some( ARM64Operand::Register( RegisterARM64::Stack( _ ) ) ) , 2 ) => {
+                    arithmetic(mnemonic, false, false, &[ operands[0].clone(), operands[1].clone(), ARM64Operand::Immediate(0) ])
+                },
+                ( Some( _ ) , _ , 2 ) => logical(mnemonic, 1, false, &with_zero(1, 0)),
+                _ => Err(operand_error(mnemonic))
+            }
+        },
+        "MOVN" => wide_move(mnemonic, 0, operands),
+        "MOVZ" => wide_move(mnemonic, 2, operands),
+        "MOVK" => wide_move(mnemonic, 3, operands),
+        "LSL" => shift(mnemonic, 0, operands),
+        "LSR" => shift(mnemonic, 1, operands),
+        "ASR" => shift(mnemonic, 2, operands),
+        "ROR" => shift(mnemonic, 3, operands),
+        "LSLV" | "LSRV" | "ASRV" | "RORV" => {
+            let ( d , n , m , bits ) = three_registers(mnemonic, operands)?;
+            let kind = [ "LSLV", "LSRV", "ASRV", "RORV" ].iter().position(|x| *x == mnemonic).unwrap_or(0) as u32;
+            Ok( size_bit(bits) | 0x1ac0_0000 | m << 16 | ( 8 + kind ) << 10 | n << 5 | d )
+        },
+        "UDIV" | "SDIV" => {
+            let ( d , n , m , bits ) = three_registers(mnemonic, operands)?;
+            Ok( size_bit(bits) | 0x1ac0_0000 | m << 16 | ( 2 + ( mnemonic == "SDIV" ) as u32 ) << 10 | n << 5 | d )
+        },
+        "SBFM" | "BFM" | "UBFM" => {
+            let ( d , n , bits , values ) = registers_and_immediates(mnemonic, operands, 2)?;
+            let opc = [ "SBFM", "BFM", "UBFM" ].iter().position(|x| *x == mnemonic).unwrap_or(0) as u32;
+            Ok( bitfield(opc, bits, d, n, values[0], values[1]) )
+        },
+        "SBFX" => bitfield_alias(mnemonic, 0, false, operands),
+        "BFXIL" => bitfield_alias(mnemonic, 1, false, operands),
+        "UBFX" => bitfield_alias(mnemonic, 2, false, operands),
+        "SBFIZ" => bitfield_alias(mnemonic, 0, true, operands),
+        "BFI" => bitfield_alias(mnemonic, 1, true, operands),
+        "UBFIZ" => bitfield_alias(mnemonic, 2, true, operands),
+        "SXTB" | "SXTH" | "SXTW" | "UXTB" | "UXTH" => {
+            let ( opc , top ) = match mnemonic {
+                "SXTB" => ( 0 , 7 ),
+                "SXTH" => ( 0 , 15 ),
+                "SXTW" => ( 0 , 31 ),
+                "UXTB" => ( 2 , 7 ),
+                _ => ( 2 , 15 )
+            };
+            /* Source is always 32 bits register, only sign extension has 64 bits destination */
+            match ( general_at(operands, 0) , general_at(operands, 1) ) {
+                ( Some( ( d , 64 ) ) , Some( ( n , 32 ) ) ) if opc == 0 && operands.len() == 2 => Ok( bitfield(opc, 64, d, n, 0, top) ),
+                ( Some( ( d , 32 ) ) , Some( ( n , 32 ) ) ) if top < 31 && operands.len() == 2 => Ok( bitfield(opc, 32, d, n, 0, top) ),
+                _ => Err(operand_error(mnemonic))
+            }
+        },
+        "EXTR" => {
+            let ( d , n , m , bits ) = three_registers(mnemonic, &operands[.. operands.len().min(3)])?;
+            match immediate_at(operands, 3) {
+                Some( x ) if ( 0 .. bits as i64 ).contains(&x) && operands.len() == 4 => Ok( size_bit(bits) | 0x1380_0000 | ( ( bits == 64 ) as u32 ) << 22 | m << 16 | ( x as u32 ) << 10 | n << 5 | d ),
+                Some( x ) => Err(range_error(mnemonic, "Immediate", x)),
+                None => Err(operand_error(mnemonic))
+            }
+        },
+        "RBIT" => single_source(mnemonic, 0, operands),
+        "REV16" => single_source(mnemonic, 1, operands),
+        "REV32" => {
+            match general_at(operands, 0) {
+                Some( ( _ , 64 ) ) => single_source(mnemonic, 2, operands),
+                _ => Err(operand_error(mnemonic))
+            }
+        },
+        "REV" => single_source(mnemonic, 2 + matches!(general_at(operands, 0), Some( ( _ , 64 ) )) as u32, operands),
+        "CLZ" => single_source(mnemonic, 4, operands),
+        "CLS" => single_source(mnemonic, 5, operands),
+        "MADD" => multiply(mnemonic, 0x1b00_0000, true, operands),
+        "MSUB" => multiply(mnemonic, 0x1b00_8000, true, operands),
+        "MUL" => multiply(mnemonic, 0x1b00_0000, false, operands),
+        "MNEG" => multiply(mnemonic, 0x1b00_8000, false, operands),
+        "SMADDL" => multiply_long(mnemonic, 0x9b20_0000, true, operands),
+        "SMSUBL" => multiply_long(mnemonic, 0x9b20_8000, true, operands),
+        "UMADDL" => multiply_long(mnemonic, 0x9ba0_0000, true, operands),
+        "UMSUBL" => multiply_long(mnemonic, 0x9ba0_8000, true, operands),
+        "SMULL" => multiply_long(mnemonic, 0x9b20_0000, false, operands),
+        "UMULL" => multiply_long(mnemonic, 0x9ba0_0000, false, operands),
+        "SMULH" | "UMULH" => {
+            match three_registers(mnemonic, operands)? {
+                ( d , n , m , 64 ) => Ok( ( ( mnemonic == "UMULH" ) as u32 ) << 23 | 0x9b40_7c00 | m << 16 | n << 5 | d ),
+                _ => Err(operand_error(mnemonic))
+            }
+        },
+        "ADC" | "ADCS" | "SBC" | "SBCS" => {
+            let ( d , n , m , bits ) = three_registers(mnemonic, operands)?;
+            let base = [ 0x1a00_0000, 0x3a00_0000, 0x5a00_0000, 0x7a00_0000 ][ [ "ADC", "ADCS", "SBC", "SBCS" ].iter().position(|x| *x == mnemonic).unwrap_or(0) ];
+            Ok( size_bit(bits) | base | m << 16 | n << 5 | d )
+        },
+        "NGC" | "NGCS" => {
+            let ( d , n , m , bits ) = three_registers(mnemonic, &with_zero(1, 0))?;
+            Ok( size_bit(bits) | if mnemonic == "NGC" { 0x5a00_0000 } else { 0x7a00_0000 } | m << 16 | n << 5 | d )
+        },
+        "CSEL" => conditional_select(mnemonic, 0x1a80_0000, operands),
+        "CSINC" => conditional_select(mnemonic, 0x1a80_0400, operands),
+        "CSINV" => conditional_select(mnemonic, 0x5a80_0000, operands),
+        "CSNEG" => conditional_select(mnemonic, 0x5a80_0400, operands),
+        "CSET" | "CINC" => conditional_alias(mnemonic, 0x1a80_0400, operands),
+        "CSETM" | "CINV" => conditional_alias(mnemonic, 0x5a80_0000, operands),
+        "CNEG" => conditional_alias(mnemonic, 0x5a80_0400, operands),
+        "CCMN" => conditional_compare(mnemonic, 0x3a40_0000, operands),
+        "CCMP" => conditional_compare(mnemonic, 0x7a40_0000, operands),
+        "ADR" | "ADRP" => {
+            match ( general_at(operands, 0) , immediate_at(operands, 1) ) {
+                ( Some( ( d , 64 ) ) , Some( target ) ) if operands.len() == 2 => {
+                    let ( base , distance ) = match mnemonic {
+                        "ADR" => ( 0x1000_0000 , target.wrapping_sub(location) ),
+                        _ => ( 0x9000_0000 , ( target >> 12 ) - ( location >> 12 ) )
+                    };
+                    if !( -( 1 << 20 ) .. 1 << 20 ).contains(&distance) {
+                        return Err(range_error(mnemonic, "Target distance", distance))
+                    }
+                    Ok( base | ( ( distance as u32 ) & 3 ) << 29 | ( ( ( distance >> 2 ) as u32 ) & 0x7ffff ) << 5 | d )
+                },
+                _ => Err(operand_error(mnemonic))
+            }
+        },
+
+        /* Branches */
+        "B" | "BL" => {
+            match immediate_at(operands, 0) {
+                Some( target ) if operands.len() == 1 => Ok( if mnemonic == "BL" { 0x9400_0000 } else { 0x1400_0000 } | branch_field(mnemonic, target, location, 26)? ),
+                _ => Err(operand_error(mnemonic))
+            }
+        },
+        "CBZ" | "CBNZ" => {
+            match ( general_at(operands, 0) , immediate_at(operands, 1) ) {
+                ( Some( ( t , bits ) ) , Some( target ) ) if operands.len() == 2 => Ok( size_bit(bits) | ( ( mnemonic == "CBNZ" ) as u32 ) << 24 | 0x3400_0000 | branch_field(mnemonic, target, location, 19)? << 5 | t ),
+                _ => Err(operand_error(mnemonic))
+            }
+        },
+        "TBZ" | "TBNZ" => {
+            match ( general_at(operands, 0) , immediate_at(operands, 1) , immediate_at(operands, 2) ) {
+                ( Some( ( t , bits ) ) , Some( bit ) , Some( target ) ) if operands.len() == 3 => {
+                    if !( 0 .. bits as i64 ).contains(&bit) {
+                        return Err(range_error(mnemonic, "Bit number", bit))
+                    }
+                    let bit = bit as u32;
+                    Ok( ( bit >> 5 ) << 31 | ( ( mnemonic == "TBNZ" ) as u32 ) << 24 | 0x3600_0000 | ( bit & 31 ) << 19 | branch_field(mnemonic, target, location, 14)? << 5 | t )
+                },
+                _ => Err(operand_error(mnemonic))
+            }
+        },
+        "BR" | "BLR" | "RET" => {
+            let base = match mnemonic {
+                "BR" => 0xd61f_0000,
+                "BLR" => 0xd63f_0000,
+                _ => 0xd65f_0000
+            };
+            match ( general_at(operands, 0) , operands.len() ) {
+                ( Some( ( n , 64 ) ) , 1 ) => Ok( base | n << 5 ),
+                ( None , 0 ) if mnemonic == "RET" => Ok( base | 30 << 5 ),
+                _ => Err(operand_error(mnemonic))
+            }
+        },
+
+        /* System */
+        "NOP" | "YIELD" | "WFE" | "WFI" | "SEV" | "SEVL" => {
+            let hint = [ "NOP", "YIELD", "WFE", "WFI", "SEV", "SEVL" ].iter().position(|x| *x == mnemonic).unwrap_or(0) as u32;
+            match operands.len() {
+                0 => Ok( 0xd503_201f | hint << 5 ),
+                _ => Err(operand_error(mnemonic))
+            }
+        },
+        "DMB" | "DSB" | "ISB" | "CLREX" => {
+            let option = match ( operands.len() , immediate_at(operands, 0) ) {
+                ( 0 , _ ) if mnemonic == "ISB" || mnemonic == "CLREX" => 15,
+                ( 1 , Some( x ) ) if ( 0 ..= 15 ).contains(&x) => x as u32,
+                _ => return Err(operand_error(mnemonic))
+            };
+            let base = match mnemonic {
+                "CLREX" => 0xd503_305f,
+                "DSB" => 0xd503_309f,
+                "DMB" => 0xd503_30bf,
+                _ => 0xd503_30df
+            };
+            Ok( base | option << 8 )
+        },
+        "SVC" | "HVC" | "SMC" | "BRK" | "HLT" | "UDF" => {
+            let base = match mnemonic {
+                "SVC" => 0xd400_0001,
+                "HVC" => 0xd400_0002,
+                "SMC" => 0xd400_0003,
+                "BRK" => 0xd420_0000,
+                "HLT" => 0xd440_0000,
+                _ => 0
+            };
+            let shift = match mnemonic {
+                "UDF" => 0,
+                _ => 5
+            };
+            match immediate_at(operands, 0) {
+                Some( x ) if ( 0 ..= 0xffff ).contains(&x) && operands.len() == 1 => Ok( base | ( x as u32 ) << shift ),
+                Some( x ) => Err(range_error(mnemonic, "Immediate", x)),
+                None => Err(operand_error(mnemonic))
+            }
+        },
+        "MRS" => {
+            match ( general_at(operands, 0) , operands.get(1) ) {
+                ( Some( ( t , 64 ) ) , Some( ARM64Operand::SystemRegister( x ) ) ) if operands.len() == 2 && *x >= 0x8000 => Ok( 0xd520_0000 | ( *x as u32 ) << 5 | t ),
+                _ => Err(operand_error(mnemonic))
+            }
+        },
+        "MSR" => {
+            match ( operands.first() , general_at(operands, 1) ) {
+                ( Some( ARM64Operand::SystemRegister( x ) ) , Some( ( t , 64 ) ) ) if operands.len() == 2 && *x >= 0x8000 => Ok( 0xd500_0000 | ( *x as u32 ) << 5 | t ),
+                _ => Err(operand_error(mnemonic))
+            }
+        },
+
+        /* Loads and stores */
+        "LDR" | "STR" | "LDRB" | "STRB" | "LDRH" | "STRH" | "LDRSB" | "LDRSH" | "LDRSW" => {
+            require(mnemonic, flags, float_flags(operands, false))?;
+            load_store(mnemonic, mnemonic, false, operands, location)
+        },
+        "LDUR" | "STUR" | "LDURB" | "STURB" | "LDURH" | "STURH" | "LDURSB" | "LDURSH" | "LDURSW" => {
+            require(mnemonic, flags, float_flags(operands, false))?;
+            load_store(mnemonic, mnemonic.replacen("UR", "R", 1).as_str(), true, operands, location)
+        },
+        "LDP" | "STP" | "LDPSW" | "LDNP" | "STNP" => {
+            require(mnemonic, flags, float_flags(operands, false))?;
+            load_store_pair(mnemonic, mnemonic.starts_with("LD"), mnemonic.ends_with("NP"), operands)
+        },
+        "LDXR" | "LDXRB" | "LDXRH" | "LDAXR" | "LDAXRB" | "LDAXRH" |
+        "STXR" | "STXRB" | "STXRH" | "STLXR" | "STLXRB" | "STLXRH" |
+        "LDAR" | "LDARB" | "LDARH" | "STLR" | "STLRB" | "STLRH" |
+        "LDXP" | "LDAXP" | "STXP" | "STLXP" => {
+            let ( operation , suffix ) = match ( mnemonic.strip_suffix('B') , mnemonic.strip_suffix('H') ) {
+                ( Some( x ) , _ ) => ( x , Some( 0 ) ),
+                ( _ , Some( x ) ) => ( x , Some( 1 ) ),
+                _ => ( mnemonic , None )
+            };
+            /* Fields o2, L, o1 and o0 select the operation */
+            let ( base , status , pair ) = match operation {
+                "LDXR" => ( 0x0840_0000 , false , false ),
+                "LDAXR" => ( 0x0840_8000 , false , false ),
+                "STXR" => ( 0x0800_0000 , true , false ),
+                "STLXR" => ( 0x0800_8000 , true , false ),
+                "LDAR" => ( 0x08c0_8000 , false , false ),
+                "STLR" => ( 0x0880_8000 , false , false ),
+                "LDXP" => ( 0x0860_0000 , false , true ),
+                "LDAXP" => ( 0x0860_8000 , false , true ),
+                "STXP" => ( 0x0820_0000 , true , true ),
+                _ => ( 0x0820_8000 , true , true )
+            };
+            exclusive(mnemonic, base, suffix, status, pair, operands)
+        },
+
+        /* Floating point */
+        "FADD" | "FSUB" | "FMUL" | "FDIV" | "FMAX" | "FMIN" | "FMAXNM" | "FMINNM" | "FNMUL" => {
+            require(mnemonic, flags, float_flags(operands, true))?;
+            let opcode = [ "FMUL", "FDIV", "FADD", "FSUB", "FMAX", "FMIN", "FMAXNM", "FMINNM", "FNMUL" ].iter().position(|x| *x == mnemonic).unwrap_or(0) as u32;
+            let ( numbers , kind ) = float_registers(mnemonic, operands, 3)?;
+            Ok( 0x1e20_0800 | kind << 22 | numbers[2] << 16 | opcode << 12 | numbers[1] << 5 | numbers[0] )
+        },
+        "FABS" | "FNEG" | "FSQRT" | "FRINTN" | "FRINTP" | "FRINTM" | "FRINTZ" | "FRINTA" | "FRINTX" | "FRINTI" => {
+            require(mnemonic, flags, float_flags(operands, true))?;
+            let opcode = match mnemonic {
+                "FABS" => 1,
+                "FNEG" => 2,
+                "FSQRT" => 3,
+                "FRINTN" => 8,
+                "FRINTP" => 9,
+                "FRINTM" => 10,
+                "FRINTZ" => 11,
+                "FRINTA" => 12,
+                "FRINTX" => 14,
+                _ => 15
+            };
+            let ( numbers , kind ) = float_registers(mnemonic, operands, 2)?;
+            Ok( 0x1e20_4000 | kind << 22 | opcode << 15 | numbers[1] << 5 | numbers[0] )
+        },
+        "FMOV" => {
+            require(mnemonic, flags, float_flags(operands, true))?;
+            float_move(mnemonic, operands)
+        },
+        "FCVT" => {
+            require(mnemonic, flags, float_flags(operands, false))?;
+            match ( float_at(operands, 0) , float_at(operands, 1) ) {
+                ( Some( ( d , db ) ) , Some( ( n , nb ) ) ) if db != nb && operands.len() == 2 => {
+                    match ( float_type(db) , float_type(nb) ) {
+                        ( Some( to ) , Some( from ) ) => Ok( 0x1e20_4000 | from << 22 | ( 4 | to ) << 15 | n << 5 | d ),
+                        _ => Err(operand_error(mnemonic))
+                    }
+                },
+                _ => Err(operand_error(mnemonic))
+            }
+        },
+        "FCMP" | "FCMPE" => {
+            require(mnemonic, flags, float_flags(operands, true))?;
+            float_compare(mnemonic, ( mnemonic == "FCMPE" ) as u32, operands)
+        },
+        "FMADD" | "FMSUB" | "FNMADD" | "FNMSUB" => {
+            require(mnemonic, flags, float_flags(operands, true))?;
+            let ( numbers , kind ) = float_registers(mnemonic, operands, 4)?;
+            let ( o1 , o0 ) = match mnemonic {
+                "FMADD" => ( 0 , 0 ),
+                "FMSUB" => ( 0 , 1 ),
+                "FNMADD" => ( 1 , 0 ),
+                _ => ( 1 , 1 )
+            };
+            Ok( 0x1f00_0000 | kind << 22 | o1 << 21 | numbers[2] << 16 | o0 << 15 | numbers[3] << 10 | numbers[1] << 5 | numbers[0] )
+        },
+        "FCSEL" => {
+            require(mnemonic, flags, float_flags(operands, true))?;
+            let ( numbers , kind ) = float_registers(mnemonic, &operands[.. operands.len().min(3)], 3)?;
+            match condition_at(operands, 3) {
+                Some( c ) if operands.len() == 4 => Ok( 0x1e20_0c00 | kind << 22 | numbers[2] << 16 | c << 12 | numbers[1] << 5 | numbers[0] ),
+                _ => Err(operand_error(mnemonic))
+            }
+        },
+        "SCVTF" | "UCVTF" => {
+            require(mnemonic, flags, float_flags(operands, true))?;
+            float_conversion(mnemonic, true, 0, 2 + ( mnemonic == "UCVTF" ) as u32, operands)
+        },
+        "FCVTNS" | "FCVTNU" | "FCVTPS" | "FCVTPU" | "FCVTMS" | "FCVTMU" | "FCVTZS" | "FCVTZU" | "FCVTAS" | "FCVTAU" => {
+            require(mnemonic, flags, float_flags(operands, true))?;
+            let ( rmode , opcode ) = match &mnemonic[4 .. 5] {
+                "N" => ( 0 , 0 ),
+                "P" => ( 1 , 0 ),
+                "M" => ( 2 , 0 ),
+                "Z" => ( 3 , 0 ),
+                _ => ( 0 , 4 )
+            };
+            float_conversion(mnemonic, false, rmode, opcode + mnemonic.ends_with('U') as u32, operands)
+        },
+
+        /* Conditional branch 'B.cond' and atomic memory operations */
+        _ => {
+            if let Some( c ) = mnemonic.strip_prefix("B.").and_then(condition_arm64) {
+                return match immediate_at(operands, 0) {
+                    Some( target ) if operands.len() == 1 => Ok( 0x5400_0000 | branch_field(mnemonic, target, location, 19)? << 5 | c as u32 ),
+                    _ => Err(operand_error(mnemonic))
+                }
+            }
+
+            match atomic_parts(mnemonic) {
+                Some( ( operation , acquire , release , suffix ) ) => {
+                    require(mnemonic, flags, CPU_ARMV8_1)?;
+                    atomic(mnemonic, operation, acquire, release, suffix, operands)
+                },
+                None => Err(Diagnostic::error(E_ASSEMBLER_INSTRUCTION, format!("Unknown instruction '{}'", mnemonic), 0, 0))
+            }
+        }
+    }
+}
+
+/// Encode a single A64 instruction as 32 bits little endian word, location is address of instruction for branches and literals
+pub fn encode_instruction_arm64(instruction: Box<String>, operands: Box<Vec<ARM64Operand>>, location: i64, flags: CpuFlags) -> Result<Box<Vec<u8>>, Box<Diagnostic>> {
+    let mnemonic = instruction.to_uppercase();
+    let word = encode_word(mnemonic.as_str(), &operands, location, implied_flags(flags))?;
+
+    Ok(Box::new(word.to_le_bytes().to_vec()))
 }
 
 fn decode_instruction_arm64(code: Box<Vec<u8>>, flags: CpuFlags) -> Result<Box<String>, Box<String>>{
     Ok(Box::new(String::new()))
-}
\ No newline at end of file
+}
+
+// Unittests for instruction set for ARM v8 module
+
+#[cfg(test)]
+mod tests {
+    use crate::arm64_instruction_set_neo::{ ARM64Operand, RegisterARM64, IndexingARM64, register_arm64, condition_arm64, modifier_arm64, barrier_arm64, system_register_arm64, bitmask_immediate, encode_instruction_arm64, implied_flags, CpuFlags, CPU_ARMV8, CPU_ARMV8_1, CPU_ARMV8_2, CPU_FP, CPU_NEON, CPU_SVE };
+    use crate::diagnostics::{ E_ASSEMBLER_CPU_FLAG, E_ASSEMBLER_OPERAND, E_ASSEMBLER_INSTRUCTION };
+
+    /// Operand from assembler text like the inline assembler gives after evaluating expressions
+    fn operand(text: &str) -> ARM64Operand {
+        let text = text.trim();
+
+        match text.strip_prefix('#') {
+            Some( x ) if x.contains('.') => return ARM64Operand::Float(x.parse::<f64>().unwrap()),
+            Some( x ) => {
+                return match x.strip_prefix("0x") {
+                    Some( h ) => ARM64Operand::Immediate(u64::from_str_radix(h, 16).unwrap() as i64),
+                    None => ARM64Operand::Immediate(x.parse::<i64>().unwrap())
+                }
+            },
+            None => ()
+        }
+
+        match ( register_arm64(text) , condition_arm64(text) , barrier_arm64(text) , system_register_arm64(text) ) {
+            ( Some( x ) , _ , _ , _ ) => ARM64Operand::Register(x),
+            ( _ , Some( x ) , _ , _ ) => ARM64Operand::Condition(x),
+            ( _ , _ , Some( x ) , _ ) => ARM64Operand::Immediate(x as i64),
+            ( _ , _ , _ , Some( x ) ) => ARM64Operand::SystemRegister(x),
+            _ => {
+                let words : Vec<&str> = text.split_whitespace().collect();
+                let amount = match words.get(1) {
+                    Some( x ) => x.trim_start_matches('#').parse::<u8>().unwrap(),
+                    None => 0
+                };
+                ARM64Operand::Modifier(modifier_arm64(words[0]).unwrap(), amount)
+            }
+        }
+    }
+
+    /// Mnemonic and operands of assembler text, memory operands in brackets with optional writeback or post index
+    fn parse(text: &str) -> ( String , Vec<ARM64Operand> ) {
+        let ( mnemonic , rest ) = match text.find(' ') {
+            Some( i ) => ( &text[.. i] , &text[i + 1 ..] ),
+            None => ( text , "" )
+        };
+        let mut parts = Vec::<String>::new();
+        let mut depth = 0;
+        let mut current = String::new();
+
+        for c in rest.chars() {
+            match c {
+                '[' => depth += 1,
+                ']' => depth -= 1,
+                ',' if depth == 0 => {
+                    parts.push(current.clone());
+                    current.clear();
+                    continue
+                },
+                _ => ()
+            }
+            current.push(c)
+        }
+        if !current.trim().is_empty() {
+            parts.push(current)
+        }
+
+        let mut operands = Vec::<ARM64Operand>::new();
+        let mut post_index = false;
+        for part in parts.iter() {
+            let part = part.trim();
+            match ( part.strip_prefix('[') , post_index ) {
+                ( Some( inner ) , _ ) => {
+                    let writeback = inner.ends_with('!');
+                    let inner : Vec<&str> = inner.trim_end_matches('!').trim_end_matches(']').split(',').collect();
+                    let base = register_arm64(inner[0].trim()).unwrap();
+                    post_index = inner.len() == 1;
+                    match inner.get(1).map(|x| operand(x)) {
+                        Some( ARM64Operand::Register( index ) ) => {
+                            match inner.get(2).map(|x| operand(x)) {
+                                Some( ARM64Operand::Modifier( m , a ) ) => operands.push(ARM64Operand::MemoryRegister(base, index, m, a)),
+                                _ => operands.push(ARM64Operand::MemoryRegister(base, index, modifier_arm64("LSL").unwrap(), 0))
+                            }
+                        },
+                        Some( ARM64Operand::Immediate( x ) ) if writeback => operands.push(ARM64Operand::Memory(base, x, IndexingARM64::PreIndex)),
+                        Some( ARM64Operand::Immediate( x ) ) => operands.push(ARM64Operand::Memory(base, x, IndexingARM64::Offset)),
+                        _ => operands.push(ARM64Operand::Memory(base, 0, IndexingARM64::Offset))
+                    }
+                },
+                ( None , true ) => {
+                    match ( operands.pop() , operand(part) ) {
+                        ( Some( ARM64Operand::Memory( base , 0 , _ ) ) , ARM64Operand::Immediate( x ) ) => operands.push(ARM64Operand::Memory(base, x, IndexingARM64::PostIndex)),
+                        _ => assert!(false)
+                    }
+                },
+                _ => operands.push(operand(part))
+            }
+        }
+
+        ( String::from(mnemonic) , operands )
+    }
+
+    fn assemble(text: &str, location: i64, flags: CpuFlags) -> Result<u32, String> {
+        let ( mnemonic , operands ) = parse(text);
+        match encode_instruction_arm64(Box::new(mnemonic), Box::new(operands), location, flags) {
+            Ok( x ) => {
+                assert_eq!(x.len(), 4);
+                Ok( u32::from_le_bytes([ x[0], x[1], x[2], x[3] ]) )
+            },
+            Err( e ) => Err( String::from(e.code) )
+        }
+    }
+
+    fn check(cases: &[( &str , u32 )]) {
+        for ( text , word ) in cases.iter() {
+            assert_eq!(assemble(text, 0, CPU_ARMV8_2 | CPU_NEON), Ok( *word ), "{}", text)
+        }
+    }
+
+    #[test]
+    fn register_names() {
+        assert_eq!(register_arm64("x0"), Some( RegisterARM64::General(0, 64) ));
+        assert_eq!(register_arm64("W30"), Some( RegisterARM64::General(30, 32) ));
+        assert_eq!(register_arm64("XZR"), Some( RegisterARM64::General(31, 64) ));
+        assert_eq!(register_arm64("wsp"), Some( RegisterARM64::Stack(32) ));
+        assert_eq!(register_arm64("LR"), Some( RegisterARM64::General(30, 64) ));
+        assert_eq!(register_arm64("Q31"), Some( RegisterARM64::Float(31, 128) ));
+        assert_eq!(register_arm64("X31"), None);
+        assert_eq!(register_arm64("D01"), None);
+        assert_eq!(register_arm64("count"), None);
+        assert_eq!(condition_arm64("hs"), Some( 2 ));
+        assert_eq!(system_register_arm64("S3_3_C4_C2_0"), system_register_arm64("NZCV"))
+    }
+
+    #[test]
+    fn cpu_flags_implied() {
+        assert_ne!(implied_flags(CPU_SVE) & CPU_ARMV8_1, 0);
+        assert_ne!(implied_flags(CPU_NEON) & CPU_FP, 0);
+        assert_eq!(implied_flags(CPU_ARMV8) & CPU_FP, 0)
+    }
+
+    #[test]
+    fn bitmask_immediates() {
+        assert_eq!(bitmask_immediate(0xff, 64), Some( ( 1 , 0 , 7 ) ));
+        assert_eq!(bitmask_immediate(0x5555_5555, 32), Some( ( 0 , 0 , 0x3c ) ));
+        assert_eq!(bitmask_immediate(0x8000_0000_0000_0001, 64), Some( ( 1 , 1 , 1 ) ));
+        assert_eq!(bitmask_immediate(0, 64), None);
+        assert_eq!(bitmask_immediate(0x1234, 64), None)
+    }
+
+    #[test]
+    fn encode_data_processing() {
+        check(&[
+            ( "ADD X0, X1, #16" , 0x9100_4020 ),
+            ( "ADD SP, SP, #0x1000" , 0x9140_07ff ),
+            ( "SUB W2, W3, #4095" , 0x513f_fc62 ),
+            ( "ADDS X0, X1, X2, LSL #3" , 0xab02_0c20 ),
+            ( "ADD X0, SP, X1" , 0x8b21_63e0 ),
+            ( "ADD X0, X1, W2, SXTW #2" , 0x8b22_c820 ),
+            ( "CMP X1, #0" , 0xf100_003f ),
+            ( "CMN W0, W1" , 0x2b01_001f ),
+            ( "NEG X0, X1, ASR #2" , 0xcb81_0be0 ),
+            ( "SUB X0, X1, #1" , 0xd100_0420 ),
+            ( "AND X0, X1, #0xFF" , 0x9240_1c20 ),
+            ( "ORR W0, WZR, #0x55555555" , 0x3200_f3e0 ),
+            ( "EOR X3, X4, X5, ROR #7" , 0xcac5_1c83 ),
+            ( "BIC X0, X1, X2" , 0x8a22_0020 ),
+            ( "TST W0, #1" , 0x7200_001f ),
+            ( "MVN X0, X1" , 0xaa21_03e0 ),
+            ( "MOV X0, X1" , 0xaa01_03e0 ),
+            ( "MOV SP, X29" , 0x9100_03bf ),
+            ( "MOV X0, #0x10000" , 0xd2a0_0020 ),
+            ( "MOV W0, #-1" , 0x1280_0000 ),
+            ( "MOV X0, #-2" , 0x9280_0020 ),
+            ( "MOV X0, #0xFF00FF00FF00FF00" , 0xb208_9fe0 ),
+            ( "MOVK X1, #0x1234, LSL #48" , 0xf2e2_4681 ),
+            ( "LSL X0, X1, #3" , 0xd37d_f020 ),
+            ( "LSR W0, W1, #31" , 0x531f_7c20 ),
+            ( "ASR X2, X3, X4" , 0x9ac4_2862 ),
+            ( "ROR X0, X1, #8" , 0x93c1_2020 ),
+            ( "UBFX X0, X1, #4, #8" , 0xd344_2c20 ),
+            ( "BFI W0, W1, #3, #4" , 0x331d_0c20 ),
+            ( "SXTW X0, W1" , 0x9340_7c20 ),
+            ( "UXTB W0, W1" , 0x5300_1c20 ),
+            ( "MADD X0, X1, X2, X3" , 0x9b02_0c20 ),
+            ( "MUL W0, W1, W2" , 0x1b02_7c20 ),
+            ( "SMULL X0, W1, W2" , 0x9b22_7c20 ),
+            ( "UMULH X0, X1, X2" , 0x9bc2_7c20 ),
+            ( "SDIV X0, X1, X2" , 0x9ac2_0c20 ),
+            ( "REV X0, X1" , 0xdac0_0c20 ),
+            ( "REV W0, W1" , 0x5ac0_0820 ),
+            ( "CLZ X0, X1" , 0xdac0_1020 ),
+            ( "ADC X0, X1, X2" , 0x9a02_0020 ),
+            ( "CSEL X0, X1, X2, EQ" , 0x9a82_0020 ),
+            ( "CSET W0, NE" , 0x1a9f_07e0 ),
+            ( "CNEG X0, X1, LT" , 0xda81_a420 ),
+            ( "CCMP X0, #3, #4, GE" , 0xfa43_a804 )
+        ])
+    }
+
+    #[test]
+    fn encode_loads_and_stores() {
+        check(&[
+            ( "LDR X0, [X1, #8]" , 0xf940_0420 ),
+            ( "LDR W0, [SP]" , 0xb940_03e0 ),
+            ( "LDR X0, [X1, #-8]" , 0xf85f_8020 ),
+            ( "LDR X0, [X1, #16]!" , 0xf841_0c20 ),
+            ( "STR X0, [X1], #16" , 0xf801_0420 ),
+            ( "LDRB W0, [X1, X2]" , 0x3862_6820 ),
+            ( "LDRH W0, [X1, W2, UXTW #1]" , 0x7862_5820 ),
+            ( "LDR X0, [X1, X2, LSL #3]" , 0xf862_7820 ),
+            ( "LDRSW X0, [X1, #4]" , 0xb980_0420 ),
+            ( "LDRSB W0, [X1]" , 0x39c0_0020 ),
+            ( "LDUR X0, [X1, #1]" , 0xf840_1020 ),
+            ( "STRB WZR, [SP, #4095]" , 0x393f_ffff ),
+            ( "LDR Q0, [X0, #32]" , 0x3dc0_0800 ),
+            ( "STR D1, [SP, #8]" , 0xfd00_07e1 ),
+            ( "LDP X29, X30, [SP], #16" , 0xa8c1_7bfd ),
+            ( "STP X29, X30, [SP, #-16]!" , 0xa9bf_7bfd ),
+            ( "LDP S0, S1, [X0, #8]" , 0x2d41_0400 ),
+            ( "LDPSW X0, X1, [X2]" , 0x6940_0440 )
+        ])
+    }
+
+    #[test]
+    fn encode_exclusive_and_atomic() {
+        check(&[
+            ( "LDXR X0, [X1]" , 0xc85f_7c20 ),
+            ( "STXR W2, X0, [X1]" , 0xc802_7c20 ),
+            ( "LDAXRB W0, [X1]" , 0x085f_fc20 ),
+            ( "STLXR W3, W4, [SP]" , 0x8803_ffe4 ),
+            ( "LDAR X0, [X1]" , 0xc8df_fc20 ),
+            ( "STLR W0, [X1]" , 0x889f_fc20 ),
+            ( "LDXP X0, X1, [X2]" , 0xc87f_0440 ),
+            ( "STLXP W5, X0, X1, [X2]" , 0xc825_8440 ),
+            ( "LDADD X0, X1, [X2]" , 0xf820_0041 ),
+            ( "LDADDAL W0, W1, [X2]" , 0xb8e0_0041 ),
+            ( "SWPB W0, W1, [X2]" , 0x3820_8041 ),
+            ( "CAS X0, X1, [X2]" , 0xc8a0_7c41 ),
+            ( "CASAL W1, W2, [SP]" , 0x88e1_ffe2 ),
+            ( "STADD X0, [X1]" , 0xf820_003f ),
+            ( "LDSMAXLH W0, W1, [X2]" , 0x7860_4041 )
+        ])
+    }
+
+    #[test]
+    fn encode_floating_point() {
+        check(&[
+            ( "FADD D0, D1, D2" , 0x1e62_2820 ),
+            ( "FMUL S0, S1, S2" , 0x1e22_0820 ),
+            ( "FDIV H0, H1, H2" , 0x1ee2_1820 ),
+            ( "FSQRT D0, D1" , 0x1e61_c020 ),
+            ( "FNEG S3, S4" , 0x1e21_4083 ),
+            ( "FMOV D0, D1" , 0x1e60_4020 ),
+            ( "FMOV X0, D1" , 0x9e66_0020 ),
+            ( "FMOV S0, W1" , 0x1e27_0020 ),
+            ( "FMOV D0, #1.0" , 0x1e6e_1000 ),
+            ( "FMOV S0, #-0.5" , 0x1e3c_1000 ),
+            ( "FMOV D0, XZR" , 0x9e67_03e0 ),
+            ( "FCVT D0, S1" , 0x1e22_c020 ),
+            ( "FCVT S0, D1" , 0x1e62_4020 ),
+            ( "FCMP D0, D1" , 0x1e61_2000 ),
+            ( "FCMPE S0, #0.0" , 0x1e20_2018 ),
+            ( "FMADD D0, D1, D2, D3" , 0x1f42_0c20 ),
+            ( "FNMSUB S0, S1, S2, S3" , 0x1f22_8c20 ),
+            ( "FCSEL D0, D1, D2, GT" , 0x1e62_cc20 ),
+            ( "SCVTF D0, X1" , 0x9e62_0020 ),
+            ( "UCVTF S0, W1" , 0x1e23_0020 ),
+            ( "FCVTZS X0, D1" , 0x9e78_0020 ),
+            ( "FCVTZU W0, S1" , 0x1e39_0020 ),
+            ( "FCVTMS X0, D1" , 0x9e70_0020 ),
+            ( "FRINTZ D0, D1" , 0x1e65_c020 ),
+            ( "ADD D0, D1, D2" , 0x5ee2_8420 )
+        ])
+    }
+
+    #[test]
+    fn encode_system_instructions() {
+        check(&[
+            ( "NOP" , 0xd503_201f ),
+            ( "DMB ISH" , 0xd503_3bbf ),
+            ( "DSB SY" , 0xd503_3f9f ),
+            ( "ISB" , 0xd503_3fdf ),
+            ( "CLREX" , 0xd503_3f5f ),
+            ( "SVC #0" , 0xd400_0001 ),
+            ( "BRK #1" , 0xd420_0020 ),
+            ( "MRS X0, NZCV" , 0xd53b_4200 ),
+            ( "MSR FPCR, X1" , 0xd51b_4401 ),
+            ( "MRS X2, TPIDR_EL0" , 0xd53b_d042 ),
+            ( "MRS X3, CNTVCT_EL0" , 0xd53b_e043 ),
+            ( "RET" , 0xd65f_03c0 ),
+            ( "RET X1" , 0xd65f_0020 ),
+            ( "BR X16" , 0xd61f_0200 ),
+            ( "BLR X2" , 0xd63f_0040 )
+        ])
+    }
+
+    #[test]
+    fn encode_branches_and_literals() {
+        /* Targets are absolute addresses, instruction is at 0x1000 */
+        let cases = [
+            ( "B #0xf80" , 0x17ff_ffe0 ),
+            ( "BL #0x3000" , 0x9400_0800 ),
+            ( "B.NE #0x1008" , 0x5400_0041 ),
+            ( "CBNZ W3, #0xffc" , 0x35ff_ffe3 ),
+            ( "TBZ X5, #40, #0x1040" , 0xb640_0205 ),
+            ( "TBNZ W1, #3, #0xff8" , 0x371f_ffc1 ),
+            ( "ADR X0, #0xfff" , 0x70ff_ffe0 ),
+            ( "ADRP X1, #0x4000" , 0xf000_0001 ),
+            ( "LDR X0, #0x1010" , 0x5800_0080 ),
+            ( "LDR D2, #0xff8" , 0x5cff_ffc2 ),
+            ( "LDRSW X3, #0x1004" , 0x9800_0023 )
+        ];
+
+        for ( text , word ) in cases.iter() {
+            assert_eq!(assemble(text, 0x1000, CPU_FP), Ok( *word ), "{}", text)
+        }
+        assert_eq!(assemble("B #0x1002", 0x1000, CPU_ARMV8), Err( String::from(E_ASSEMBLER_OPERAND) ));
+        assert_eq!(assemble("B.EQ #0x200000", 0, CPU_ARMV8), Err( String::from(E_ASSEMBLER_OPERAND) ));
+        assert_eq!(assemble("TBZ W0, #32, #0", 0, CPU_ARMV8), Err( String::from(E_ASSEMBLER_OPERAND) ))
+    }
+
+    #[test]
+    fn encode_errors() {
+        let error = | text : &str, flags : CpuFlags | assemble(text, 0, flags).unwrap_err();
+
+        assert_eq!(error("BLX X0", CPU_NEON), E_ASSEMBLER_INSTRUCTION);
+        assert_eq!(error("FADDD D0, D1, D2", CPU_NEON), E_ASSEMBLER_INSTRUCTION);
+        assert_eq!(error("ADD X0, W1, X2", CPU_NEON), E_ASSEMBLER_OPERAND);
+        assert_eq!(error("ADD X0, X1, #4097", CPU_NEON), E_ASSEMBLER_OPERAND);
+        assert_eq!(error("AND X0, X1, #0x1234", CPU_NEON), E_ASSEMBLER_OPERAND);
+        assert_eq!(error("ADDS SP, X1, #1", CPU_NEON), E_ASSEMBLER_OPERAND);
+        assert_eq!(error("LDR X0, [X1, #32768]", CPU_NEON), E_ASSEMBLER_OPERAND);
+        assert_eq!(error("LDR X0, [W1]", CPU_NEON), E_ASSEMBLER_OPERAND);
+        assert_eq!(error("LDRB X0, [X1]", CPU_NEON), E_ASSEMBLER_OPERAND);
+        assert_eq!(error("LDP X0, X1, [X2, #4]", CPU_NEON), E_ASSEMBLER_OPERAND);
+        assert_eq!(error("STXR X2, X0, [X1]", CPU_NEON), E_ASSEMBLER_OPERAND);
+        assert_eq!(error("CSET W0, AL", CPU_NEON), E_ASSEMBLER_OPERAND);
+        assert_eq!(error("FMOV D0, #0.1", CPU_NEON), E_ASSEMBLER_OPERAND);
+        assert_eq!(error("LDADD X0, X1, [X2]", CPU_NEON), E_ASSEMBLER_CPU_FLAG);
+        assert_eq!(error("FADD D0, D1, D2", CPU_ARMV8), E_ASSEMBLER_CPU_FLAG);
+        assert_eq!(error("FADD H0, H1, H2", CPU_NEON), E_ASSEMBLER_CPU_FLAG);
+        assert_eq!(assemble("LDADD X0, X1, [X2]", 0, CPU_ARMV8_1), Ok( 0xf820_0041 ))
+    }
+}
@@ -1,101 +1,254 @@
-
 // ActiveOberon Compiler, a native ARM v8 & X86-64 compiler & Risc V / linker / builder utility.
 // Written by Richard Magnor Stenbro. Licensed under GPL v3
 // Compiler module for compiling and linking of projects written in ActiveOberon language
 
 
-use std::fs::{File, read_to_string};
+use std::collections::HashMap;
+use std::fs::File;
 use std::io::Read;
 use console::style;
+use crate::diagnostics::{Diagnostic, DiagnosticMethods, SourceMap, SourceMapMethods, E_SOURCE_FILE};
 use crate::parser::{Parser as ActiveOberonParser, ParserMethods, BlockRules, Node};
-use crate::scanner::{Scanner as ActiveOberonScanner, ScannerMethods };
+use crate::scanner::{Scanner as ActiveOberonScanner, ScannerMethods, Symbols};
+use crate::type_checker::{TypeChecker, TypeCheckerMethods};
+use crate::ir::IrModule;
+use crate::ir_lowering::lower_module;
+use crate::ir_text::{write_module, read_module};
+use crate::ir_verifier::verify_module;
+use crate::ir_optimizer::{PassManager, PassManagerMethods};
+use crate::resolver::ResolverMethods;
+use crate::symbol_table::identifier_definition;
+use crate::frame_layout::deferred_code_variables;
 use crate::traverse_abstract_syntax_tree::{TraverseAST, TraverseASTMethods};
+use crate::traverse_abstract_syntax_tree::children;
+use crate::inline_assembler::{Target, Relocation, assemble_code_block};
+use crate::amd64_instruction_set_neo::listing_amd64;
+use crate::riscv_instruction_set_neo::listing_risc_v;
 
 
 pub trait CompilerMethods {
     fn new() -> Self;
-    fn compile_module(&mut self, file_name: &String) -> bool;
-    /// Present Syntax Error messages correctly with position and source line
-    fn present_error_message(&mut self, msg: &String, file_name: &String);
-    fn parse_from_file(&mut self, file_name: String) -> Result<Box<Node>, Box<String>>;
+    fn set_target(&mut self, target: Target) -> ();
+    fn set_emit(&mut self, emit: Option<String>) -> ();
+    fn set_verify_ir(&mut self, verify: bool) -> ();
+    fn set_optimization(&mut self, level: u8) -> ();
+    fn compile_modules(&mut self, file_names: &[String]) -> bool;
+    /// Present errors and warnings correctly with file, line, column and source line
+    fn present_diagnostic(&mut self, diagnostic: &Diagnostic, source: &SourceMap);
+    /// Present all errors found in file with count of them, always returns false
+    fn present_errors(&mut self, errors: &[Box<Diagnostic>], source: &SourceMap, file_name: &str) -> bool;
+    fn read_source_file(&mut self, file_name: &str) -> Result<Box<String>, Box<Diagnostic>>;
+    fn parse_source(&mut self, source: Box<String>) -> Result<Box<Node>, Box<Vec<Box<Diagnostic>>>>;
+    fn generate_module(&mut self, checker: &TypeChecker, module: &ParsedModule) -> bool;
+    fn compile_ir_file(&mut self, file_name: &str) -> bool;
+    fn optimize_module(&mut self, ir: &mut IrModule, file_name: &str, stage: &str) -> Option<PassManager>;
+    fn emit_disassembly(&mut self, root: &Node, source: &SourceMap, code: &HashMap<u32, Box<Vec<u8>>>, relocations: &HashMap<u32, Vec<Relocation>>);
+    fn emit_ir(&mut self, ir: &IrModule, file_name: &str);
 }
 
-pub struct Compiler {
+/// Module of run parsed without errors, kept until code is generated for it
+pub struct ParsedModule {
+    file_name: String,
+    source_map: SourceMap,
+    root: Box<Node>,
+    deferred_code: Vec<(u32, Vec<char>)>,           /* 'CODE' blocks naming Oberon variables, by start of assembler text */
+    code_relocations: HashMap<u32, Vec<Relocation>>
+}
+
+/// Name of module and names of modules it imports, aliases are replaced by the module they stand for
+fn module_imports(root: &Node) -> Option<(String, Vec<String>)> {
+    match root {
+        Node::Module( _ , _ , _ , _ , id , _ , _ , imports , .. ) => {
+            let ( name , .. ) = identifier_definition(id)?;
+            let mut names = Vec::<String>::new();
+
+            for import_list in imports.iter().flat_map(|x| x.iter()) {
+                if let Node::ImportList( _ , _ , _ , nodes , _ , _ ) = &**import_list {
+                    for import in nodes.iter() {
+                        if let Node::Import( _ , _ , id , alias , _ , _ ) = &**import {
+                            let module = match alias {
+                                Some( ( _ , x ) ) => identifier_definition(x),
+                                _ => identifier_definition(id)
+                            };
+                            if let Some( ( x , .. ) ) = module {
+                                names.push(x)
+                            }
+                        }
+                    }
+                }
+            }
 
+            Some( ( name, names ) )
+        },
+        _ => None
+    }
 }
 
-impl CompilerMethods for Compiler {
-    fn new() -> Self {
-        Compiler {
+/// Indexes of modules ordered so that modules come after the modules they import, otherwise in given order.
+/// Each module has its name and the names it imports, modules in cycles are placed where the cycle is entered.
+fn import_order(modules: &Vec<Option<(String, Vec<String>)>>) -> Vec<usize> {
+    fn visit(index: usize, modules: &Vec<Option<(String, Vec<String>)>>, visited: &mut Vec<bool>, order: &mut Vec<usize>) {
+        if visited[index] {
+            return
+        }
+        visited[index] = true;
 
+        if let Some( ( _ , imports ) ) = &modules[index] {
+            for import in imports.iter() {
+                if let Some( x ) = modules.iter().position(|x| matches!(x, Some( ( name , _ ) ) if name == import)) {
+                    visit(x, modules, visited, order)
+                }
+            }
         }
+
+        order.push(index)
     }
 
-    fn compile_module(&mut self, file_name: &String) -> bool {
-        let res = self.parse_from_file(String::from(file_name));
+    let mut visited = vec![ false; modules.len() ];
+    let mut order = Vec::<usize>::new();
+    for index in 0 .. modules.len() {
+        visit(index, modules, &mut visited, &mut order)
+    }
 
-        match res {
-            Ok( root ) => {
-                println!("\r\nSuccess parsing statement!\r\n");
+    order
+}
+
+/// Start position, start of assembler text and machine code of all 'CODE' statements and procedure bodies in source order
+fn code_blocks<'a>(node: &'a Node, blocks: &mut Vec<(u32, u32, &'a Vec<u8>)>) {
+    match node {
+        Node::Code( s , _ , symbol , code , _ ) => {
+            if let Symbols::Code( _ , e ) = &**symbol {
+                blocks.push(( *s , *e , &**code ))
+            }
+        },
+        Node::CodeBlock( s , _ , code ) => blocks.push(( *s , *s , &**code )),
+        _ => ()
+    }
 
-                let mut tree_walker = TraverseAST::new();
+    for child in children(node).iter() {
+        code_blocks(child, blocks)
+    }
+}
 
-                tree_walker.traverse(root);
+pub struct Compiler {
+    target: Target,         /* CPU to generate code for, also used for inline assembler */
+    emit: Option<String>,   /* Intermediate output to print, given by '--emit' */
+    verify_ir: bool,        /* Check intermediate representation after each stage */
+    optimization: u8,       /* Level given by '-O', selects passes run over intermediate representation */
+    inline_modules: Vec<IrModule>,                  /* Modules compiled before exporting procedures marked inline */
+    deferred_code: Vec<(u32, Vec<char>)>,           /* 'CODE' blocks naming Oberon variables in module parsed last */
+    code_relocations: HashMap<u32, Vec<Relocation>>
+}
 
-                true
-            },
-            Err( s ) => {
-                self.present_error_message(&s, file_name);
-                false
-            }
+impl CompilerMethods for Compiler {
+    fn new() -> Self {
+        Compiler {
+            target: Target::X86_64,
+            emit: None,
+            verify_ir: true,
+            optimization: 0,
+            inline_modules: Vec::new(),
+            deferred_code: Vec::new(),
+            code_relocations: HashMap::new()
         }
     }
 
-    /// Present Syntax Error messages correctly with position and source line
-    fn present_error_message(&mut self, msg: &String, file_name: &String) {
-        let parts = msg.split("position: '").collect::<Vec<&str>>();
+    fn set_target(&mut self, target: Target) {
+        self.target = target
+    }
 
-        if parts.len() < 2 {
-            println!("\r\n{} in file: {}", style("SyntaxError").red(), file_name);
-            println!("{}", msg);
-            return
+    fn set_emit(&mut self, emit: Option<String>) {
+        self.emit = emit
+    }
+
+    fn set_verify_ir(&mut self, verify: bool) {
+        self.verify_ir = verify
+    }
+
+    fn set_optimization(&mut self, level: u8) {
+        self.optimization = level
+    }
+
+    /// Compile modules of one run, each after the modules it imports. Every file is parsed once and all modules
+    /// are type checked together, so procedures marked inline in a module can be inlined in the modules importing it.
+    /// Files with extension '.ir' hold intermediate representation and are read, verified and optimized before
+    /// the modules in source. Returns false if any module has errors.
+    fn compile_modules(&mut self, file_names: &[String]) -> bool {
+        let mut modules = Vec::<Option<ParsedModule>>::new();
+        let mut success = true;
+
+        for file_name in file_names.iter().filter(|x| x.ends_with(".ir")) {
+            success = self.compile_ir_file(file_name) && success
         }
 
-        let t : String = parts[1].chars().filter(|char| char.is_digit(10)).collect();
-        let pos : usize = t.parse::<u32>().unwrap() as usize;
+        for file_name in file_names.iter().filter(|x| !x.ends_with(".ir")) {
+            let source = match self.read_source_file(file_name) {
+                Ok( x ) => x,
+                Err( e ) => {
+                    self.present_diagnostic(&e, &SourceMap::new(file_name, ""));
+                    success = false;
+                    modules.push(None);
+                    continue
+                }
+            };
+
+            let source_map = SourceMap::new(file_name, source.as_str());
+            match self.parse_source(source) {
+                Ok( root ) => {
+                    println!("\r\nSuccess parsing statement!\r\n");
+                    modules.push(Some( ParsedModule {
+                        file_name: file_name.clone(),
+                        source_map,
+                        root,
+                        deferred_code: std::mem::take(&mut self.deferred_code),
+                        code_relocations: std::mem::take(&mut self.code_relocations)
+                    } ))
+                },
+                Err( errors ) => {
+                    success = self.present_errors(&errors, &source_map, file_name);
+                    modules.push(None)
+                }
+            }
+        }
 
-        let mut line : usize = 0;
-        let mut col : usize = 0;
-        let mut cur_pos : usize = 0;
-        let mut syntax_line = String::new();
+        let imports : Vec<Option<(String, Vec<String>)>> = modules.iter().map(|x| x.as_ref().and_then(|y| module_imports(&y.root))).collect();
+        let order : Vec<&ParsedModule> = import_order(&imports).iter().filter_map(|x| modules[*x].as_ref()).collect();
 
-        let lines : Vec<String> = read_to_string(file_name)
-            .unwrap()
-            .lines()
-            .map(String::from)
-            .collect();
+        /* Name resolution and type checking are done once for all modules, imported modules come before the modules importing them */
+        let mut checker = TypeChecker::new();
+        let nodes : Vec<&Node> = order.iter().map(|x| &*x.root).collect();
+        let results = checker.check_modules(&nodes);
 
-        for source_line in lines {
-            let start_of_line = cur_pos;
-            cur_pos += source_line.len();
-            line += 1;
-            if pos > cur_pos {
-                continue
+        for ( module , errors ) in order.iter().zip(results) {
+            if let Some( ( name , _ ) ) = module_imports(&module.root) {
+                println!("  Compiling module: '{}'", style(name).green())
             }
-            col = (pos - start_of_line) + 1;
-            syntax_line = source_line.clone();
-            break
+
+            success = match errors.is_empty() {
+                true => self.generate_module(&checker, module),
+                _ => self.present_errors(&errors, &module.source_map, &module.file_name)
+            } && success
         }
 
-        println!("\r\n{} in file: {}", style("SyntaxError").red(), file_name);
-        println!("Line: {}, Col: {} => {}\r\n", style(line).yellow(), style(col).yellow(), msg);
-        println!("{}", syntax_line);
-        println!("{}{}", (0..col).map(|_| " ").collect::<String>(), style("^").red())
+        success
     }
 
-    fn parse_from_file(&mut self, file_name: String) -> Result<Box<Node>, Box<String>> {
+    /// Present errors and warnings correctly with file, line, column and source line
+    fn present_diagnostic(&mut self, diagnostic: &Diagnostic, source: &SourceMap) {
+        println!("\r\n{}\r\n", source.render(diagnostic))
+    }
+
+    /// Present all errors found in file with count of them, always returns false
+    fn present_errors(&mut self, errors: &[Box<Diagnostic>], source: &SourceMap, file_name: &str) -> bool {
+        for e in errors.iter() {
+            self.present_diagnostic(e, source)
+        }
+        println!("{} error(s) found in '{}'\r\n", errors.len(), style(file_name).red());
+        false
+    }
 
-        let mut file = File::open(file_name.as_str());
+    fn read_source_file(&mut self, file_name: &str) -> Result<Box<String>, Box<Diagnostic>> {
+        let mut file = File::open(file_name);
 
         return match &mut file {
             Ok( f ) => {
@@ -105,18 +258,279 @@ impl CompilerMethods for Compiler {
                 match &size {
                     Ok(s) => {
                         match s {
-                            0 => Err(Box::new(format!("File '{}' is empty!", style(file_name).red()))),
-                            _ => {
-                                let mut parser = Box::new( ActiveOberonParser::new( Box::new( ActiveOberonScanner::new( Box::leak(contents.into_boxed_str() ) ) ) ) );
-                                let res =  parser.parse_module()?;
-                                Ok( res )
-                            }
+                            0 => Err(Diagnostic::error(E_SOURCE_FILE, format!("File '{}' is empty!", style(file_name).red()), 0, 0)),
+                            _ => Ok( Box::new(contents) )
                         }
                     },
-                    _ => Err(Box::new(format!("File '{}' is empty or i am unable to find size!", style(file_name).red())))
+                    _ => Err(Diagnostic::error(E_SOURCE_FILE, format!("File '{}' is empty or i am unable to find size!", style(file_name).red()), 0, 0))
                 }
             },
-            _ => Err(Box::new(format!("Unable to find or open '{}' file.", style(file_name).red())))
+            _ => Err(Diagnostic::error(E_SOURCE_FILE, format!("Unable to find or open '{}' file.", style(file_name).red()), 0, 0))
         }
     }
+
+    /// Parse module and collect all syntax errors found, not only the first one
+    fn parse_source(&mut self, source: Box<String>) -> Result<Box<Node>, Box<Vec<Box<Diagnostic>>>> {
+        let mut parser = Box::new( ActiveOberonParser::new( Box::new( ActiveOberonScanner::new( Box::leak(source.into_boxed_str() ) ) ) ) );
+        parser.set_target(self.target.clone());
+        let res =  parser.parse_module();
+        let mut errors = Box::new( parser.get_errors().clone() );
+        self.deferred_code = parser.get_deferred_code().clone();
+        self.code_relocations = parser.get_code_relocations().clone();
+
+        match res {
+            Ok( x ) => {
+                match errors.is_empty() {
+                    true => Ok( x ),
+                    _ => Err( errors )
+                }
+            },
+            Err( e ) => {
+                errors.push(e);
+                Err( errors )
+            }
+        }
+    }
+
+    /// Lower module checked without errors to intermediate representation, optimize it and emit what is asked for.
+    /// 'CODE' blocks naming Oberon variables are assembled here, now that variables have types and frame offsets.
+    fn generate_module(&mut self, checker: &TypeChecker, module: &ParsedModule) -> bool {
+        let mut code = HashMap::<u32, Box<Vec<u8>>>::new();
+        let mut relocations = module.code_relocations.clone();
+
+        match module.deferred_code.is_empty() {
+            true => (),
+            _ => {
+                let scope = match &*module.root {
+                    Node::Module( _ , _ , _ , _ , id , .. ) => identifier_definition(id).and_then(|x| checker.get_resolver().get_module_scope(x.0.as_str())),
+                    _ => None
+                };
+                let positions : Vec<u32> = module.deferred_code.iter().map(|x| x.0).collect();
+                let variables = scope.map(|x| deferred_code_variables(checker, x, &positions)).unwrap_or_default();
+                let mut errors = Vec::<Box<Diagnostic>>::new();
+
+                for ( position , text ) in module.deferred_code.iter() {
+                    match assemble_code_block(&self.target, text.clone(), *position, Some( &variables.get(position).cloned().unwrap_or_default() )) {
+                        Ok( x ) => {
+                            if !x.relocations.is_empty() {
+                                relocations.insert(*position, x.relocations);
+                            }
+                            code.insert(*position, x.code);
+                        },
+                        Err( e ) => errors.push(e)
+                    }
+                }
+
+                if !errors.is_empty() {
+                    return self.present_errors(&errors, &module.source_map, &module.file_name)
+                }
+            }
+        }
+
+        let mut ir = match lower_module(checker, &module.root, &code) {
+            Ok( x ) => x,
+            Err( errors ) => return self.present_errors(&errors, &module.source_map, &module.file_name)
+        };
+
+        let pass_manager = match self.optimize_module(&mut ir, &module.file_name, "lowering") {
+            Some( x ) => x,
+            _ => return false
+        };
+
+        match self.emit.as_deref() {
+            Some( "disasm" ) => self.emit_disassembly(&module.root, &module.source_map, &code, &relocations),
+            Some( "ir" ) => self.emit_ir(&ir, &module.file_name),
+            Some( "stats" ) => println!("{}", pass_manager.report()),
+            _ => ()
+        }
+
+        let mut tree_walker = TraverseAST::new();
+
+        tree_walker.traverse(module.root.clone());
+
+        true
+    }
+
+    /// Intermediate representation read from file, then verified, optimized and emitted as a module lowered from source
+    fn compile_ir_file(&mut self, file_name: &str) -> bool {
+        let text = match self.read_source_file(file_name) {
+            Ok( x ) => x,
+            Err( e ) => {
+                self.present_diagnostic(&e, &SourceMap::new(file_name, ""));
+                return false
+            }
+        };
+
+        let mut ir = match read_module(text.as_str()) {
+            Ok( x ) => x,
+            Err( e ) => return self.present_errors(&[ e ], &SourceMap::new(file_name, text.as_str()), file_name)
+        };
+
+        let pass_manager = match self.optimize_module(&mut ir, file_name, "reading") {
+            Some( x ) => x,
+            _ => return false
+        };
+
+        match self.emit.as_deref() {
+            Some( "ir" ) => self.emit_ir(&ir, file_name),
+            Some( "stats" ) => println!("{}", pass_manager.report()),
+            _ => ()
+        }
+
+        true
+    }
+
+    /// Verify module when asked for and run optimization passes over it, procedures marked inline in modules
+    /// compiled before are inlined, 'stage' names where module came from in errors. Returns passes run, or None when errors are found.
+    fn optimize_module(&mut self, ir: &mut IrModule, file_name: &str, stage: &str) -> Option<PassManager> {
+        let no_source = SourceMap::new(file_name, "");
+        if self.verify_ir {
+            let errors = verify_module(ir, stage);
+            if !errors.is_empty() {
+                self.present_errors(&errors, &no_source, file_name);
+                return None
+            }
+        }
+
+        let mut pass_manager = PassManager::new(self.optimization, self.verify_ir);
+        for x in self.inline_modules.iter() {
+            pass_manager.import(x)
+        }
+        match pass_manager.run(ir) {
+            Ok( _ ) => (),
+            Err( errors ) => {
+                self.present_errors(&errors, &no_source, file_name);
+                return None
+            }
+        }
+
+        if ir.functions.iter().any(|x| x.export && x.inline) {
+            self.inline_modules.push(ir.clone())
+        }
+
+        Some( pass_manager )
+    }
+
+    /// Listing of machine code in 'CODE' blocks, each block headed by its position in source file
+    fn emit_disassembly(&mut self, root: &Node, source: &SourceMap, code: &HashMap<u32, Box<Vec<u8>>>, relocations: &HashMap<u32, Vec<Relocation>>) {
+        let mut blocks = Vec::<(u32, u32, &Vec<u8>)>::new();
+        code_blocks(root, &mut blocks);
+
+        for ( position , text , bytes ) in blocks.iter() {
+            let bytes = code.get(text).map(|x| &**x).unwrap_or(*bytes);
+            let ( line , column ) = source.line_column(*position);
+            println!("{}:{}:{}: CODE, {} bytes\r\n", source.file_name(), line, column, bytes.len());
+            match self.target {
+                Target::X86_64 => println!("{}", listing_amd64(bytes, 0)),
+                Target::RiscV => println!("{}", listing_risc_v(bytes, 0)),
+                _ => ()
+            }
+            if let Some( relocations ) = relocations.get(text) {
+                for x in relocations.iter() {
+                    println!("{:08X}  relocation {} {:+}\r", x.offset, x.symbol, x.addend)
+                }
+                println!()
+            }
+        }
+    }
+
+    /// Text of intermediate representation written next to module file, with extension '.ir', or '.opt.ir' when
+    /// the module was read from intermediate representation
+    fn emit_ir(&mut self, ir: &IrModule, file_name: &str) {
+        let path = match file_name.ends_with(".ir") {
+            true => std::path::Path::new(file_name).with_extension("opt.ir"),
+            _ => std::path::Path::new(file_name).with_extension("ir")
+        };
+        match std::fs::write(&path, write_module(ir)) {
+            Ok( _ ) => println!("Intermediate representation written to '{}'\r\n", path.display()),
+            Err( e ) => println!("{} Unable to write '{}': {}\r\n", style("error:").red(), path.display(), e)
+        }
+    }
+}
+
+// Unittests for compiler module
+
+#[cfg(test)]
+mod tests {
+    use crate::compiler::{Compiler, CompilerMethods, import_order};
+
+    #[test]
+    fn modules_after_their_imports() {
+        let modules = vec![
+            Some( ( String::from("Main"), vec![ String::from("Out"), String::from("Lib") ] ) ),
+            Some( ( String::from("Out"), Vec::new() ) ),
+            None,
+            Some( ( String::from("Lib"), vec![ String::from("Out"), String::from("Main") ] ) )
+        ];
+
+        assert_eq!(import_order(&modules), vec![ 1, 3, 0, 2 ])
+    }
+
+    #[test]
+    fn inline_procedure_of_module_compiled_before() {
+        let directory = std::env::temp_dir().join(format!("active_oberon_compile_{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let main = directory.join("Main.Mod");
+        let library = directory.join("Lib.Mod");
+        std::fs::write(&main, "MODULE Main; IMPORT Lib; VAR g: INTEGER; BEGIN g := Lib.Add(2) END Main.").unwrap();
+        std::fs::write(&library, "MODULE Lib; VAR count*: INTEGER; PROCEDURE -Add*(x: INTEGER): INTEGER; BEGIN count := count + 1; RETURN x + count END Add; END Lib.").unwrap();
+
+        let mut compiler = Compiler::new();
+        compiler.set_optimization(1);
+        compiler.set_emit(Some( String::from("ir") ));
+        let success = compiler.compile_modules(&[ main.to_string_lossy().to_string(), library.to_string_lossy().to_string() ]);
+        let text = std::fs::read_to_string(directory.join("Main.ir")).unwrap_or_default();
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        assert!(success);
+        assert!(!text.contains("call Lib.Add"));
+        assert!(text.contains("globaladdr Lib.count"))
+    }
+
+    #[test]
+    fn code_naming_variables_of_imported_module() {
+        let directory = std::env::temp_dir().join(format!("active_oberon_code_{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let main = directory.join("Main.Mod");
+        let library = directory.join("Lib.Mod");
+        std::fs::write(&main, "MODULE Main; IMPORT Lib; PROCEDURE P(x: HUGEINT); BEGIN CODE MOV RAX, x END END P; BEGIN P(Lib.Get()) END Main.").unwrap();
+        std::fs::write(&library, "MODULE Lib; PROCEDURE Get*(): HUGEINT; BEGIN RETURN 1 END Get; END Lib.").unwrap();
+
+        let mut compiler = Compiler::new();
+        compiler.set_emit(Some( String::from("ir") ));
+        let success = compiler.compile_modules(&[ main.to_string_lossy().to_string(), library.to_string_lossy().to_string() ]);
+        let text = std::fs::read_to_string(directory.join("Main.ir")).unwrap_or_default();
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        assert!(success);
+        assert!(text.contains("call Lib.Get"));
+        assert!(text.contains("code \"488b4510\""))
+    }
+
+    #[test]
+    fn intermediate_representation_file() {
+        let directory = std::env::temp_dir().join(format!("active_oberon_ir_{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let good = directory.join("Good.ir");
+        let syntax = directory.join("Syntax.ir");
+        let invalid = directory.join("Invalid.ir");
+        std::fs::write(&good, "module Good\nfunction export Good.P() -> i32 {\nb0:\n  %0: i32 = iconst 2\n  %1: i32 = iconst 3\n  %2: i32 = add %0, %1\n  return %2\n}\n").unwrap();
+        std::fs::write(&syntax, "module Syntax\nfunction Syntax.P() -> i32 {\nb0:\n  %0: i32 = frobnicate %1\n}\n").unwrap();
+        std::fs::write(&invalid, "module Invalid\nfunction Invalid.P() -> i32 {\nb0:\n  %0: i32 = iconst 1\n}\n").unwrap();
+
+        let mut compiler = Compiler::new();
+        compiler.set_optimization(1);
+        compiler.set_emit(Some( String::from("ir") ));
+        let success = compiler.compile_modules(&[ good.to_string_lossy().to_string() ]);
+        let text = std::fs::read_to_string(directory.join("Good.opt.ir")).unwrap_or_default();
+        let syntax_error = compiler.compile_modules(&[ syntax.to_string_lossy().to_string() ]);
+        let verify_error = compiler.compile_modules(&[ invalid.to_string_lossy().to_string() ]);
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        assert!(success);
+        assert!(text.contains("iconst 5"));
+        assert!(!text.contains("add"));
+        assert!(!syntax_error);
+        assert!(!verify_error)
+    }
 }
@@ -0,0 +1,421 @@
+// ActiveOberon Compiler, a native ARM v8 & X86-64 compiler & Risc V / linker / builder utility.
+// Written by Richard Magnor Stenbro. Licensed under GPL v3
+// Constant evaluator module for folding of compile time expressions in ActiveOberon language
+
+use crate::diagnostics::{Diagnostic, DiagnosticMethods, E_NOT_CONSTANT, E_DIVISION_BY_ZERO, E_OUT_OF_RANGE, E_TYPE_MISMATCH};
+use crate::parser::Node;
+use crate::scanner::Symbols;
+
+/// Number of elements in SET, elements are 0 .. MAX(SET)
+pub const SET_ELEMENTS : i64 = 32;
+
+/// Value of constant expression, integers have the range of HUGEINT
+#[derive(Clone, PartialEq, Debug)]
+pub enum ConstValue {
+    Integer(i64),
+    Real(f64),
+    Boolean(bool),
+    Char(u32),
+    String(Box<String>),    /* Without quotes */
+    Set(u32),
+    Nil
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum ConstError {
+    NotConstant(u32, u32),      /* Span of part that has no value at compile time */
+    Invalid(Box<Diagnostic>),   /* Overflow, division by zero or value out of range */
+    Propagated                  /* Depends on constant with error reported at its declaration */
+}
+
+pub type Folded = Result<ConstValue, ConstError>;
+
+fn invalid(code: &'static str, message: String, start: u32, end: u32) -> Folded {
+    Err(ConstError::Invalid(Diagnostic::error(code, message, start, end)))
+}
+
+fn overflow(start: u32, end: u32) -> Folded {
+    invalid(E_OUT_OF_RANGE, String::from("Integer overflow in constant expression"), start, end)
+}
+
+fn mismatch(operator: &str, start: u32, end: u32) -> Folded {
+    invalid(E_TYPE_MISMATCH, format!("Invalid operand(s) for '{}' in constant expression", operator), start, end)
+}
+
+/// Real value must stay finite, infinity and NaN from folding are reported as out of range
+fn real(value: f64, start: u32, end: u32) -> Folded {
+    match value.is_finite() {
+        true => Ok( ConstValue::Real(value) ),
+        _ => invalid(E_OUT_OF_RANGE, String::from("Real value out of range in constant expression"), start, end)
+    }
+}
+
+/// Single character strings are compatible with characters
+fn as_char(value: &ConstValue) -> Option<u32> {
+    match value {
+        ConstValue::Char( x ) => Some( *x ),
+        ConstValue::String( x ) if x.chars().count() == 1 => x.chars().next().map(|c| c as u32),
+        _ => None
+    }
+}
+
+fn as_real(value: &ConstValue) -> Option<f64> {
+    match value {
+        ConstValue::Integer( x ) => Some( *x as f64 ),
+        ConstValue::Real( x ) => Some( *x ),
+        _ => None
+    }
+}
+
+/// Value of literal node
+pub fn literal(node: &Node) -> Folded {
+    match node {
+        Node::Integer( s , e , symbol ) => {
+            match &**symbol {
+                Symbols::Integer( _ , _ , text ) => {
+                    match crate::type_checker::integer_value(text.as_str()).map(i64::try_from) {
+                        Some( Ok( x ) ) => Ok( ConstValue::Integer(x) ),
+                        _ => invalid(E_OUT_OF_RANGE, format!("Integer '{}' is out of range of HUGEINT", text), *s, *e)
+                    }
+                },
+                _ => Err(ConstError::NotConstant(*s, *e))
+            }
+        },
+        Node::Real( s , e , symbol ) => {
+            match &**symbol {
+                Symbols::Real( _ , _ , text ) => {
+                    match text.replace('D', "E").parse::<f64>() {
+                        Ok( x ) => real(x, *s, *e),
+                        _ => invalid(E_OUT_OF_RANGE, format!("Real '{}' is out of range", text), *s, *e)
+                    }
+                },
+                _ => Err(ConstError::NotConstant(*s, *e))
+            }
+        },
+        Node::Character( s , e , symbol ) => {
+            match &**symbol {
+                Symbols::Character( _ , _ , text ) => {
+                    match text.ends_with('X') {
+                        true => {
+                            match u32::from_str_radix(&text[.. text.len() - 1], 16) {
+                                Ok( x ) if char::from_u32(x).is_some() => Ok( ConstValue::Char(x) ),
+                                _ => invalid(E_OUT_OF_RANGE, format!("Character '{}' is out of range", text), *s, *e)
+                            }
+                        },
+                        _ => Ok( ConstValue::Char(text.chars().nth(1).map(|c| c as u32).unwrap_or(0)) )
+                    }
+                },
+                _ => Err(ConstError::NotConstant(*s, *e))
+            }
+        },
+        Node::String( s , e , symbol ) => {
+            match &**symbol {
+                Symbols::String( _ , _ , text ) => {
+                    let characters : Vec<char> = text.chars().collect();
+                    Ok( ConstValue::String(Box::new(characters[1 .. characters.len() - 1].iter().collect())) )
+                },
+                _ => Err(ConstError::NotConstant(*s, *e))
+            }
+        },
+        Node::True( .. ) => Ok( ConstValue::Boolean(true) ),
+        Node::False( .. ) => Ok( ConstValue::Boolean(false) ),
+        Node::Nil( .. ) => Ok( ConstValue::Nil ),
+        Node::Error( s , e ) => Err(ConstError::NotConstant(*s, *e)),
+        _ => Err(ConstError::Propagated)
+    }
+}
+
+/// Prefix operators '+', '-' and '~'
+pub fn unary(operator: &str, value: ConstValue, start: u32, end: u32) -> Folded {
+    match ( operator , value ) {
+        ( "+" , ConstValue::Integer( x ) ) => Ok( ConstValue::Integer(x) ),
+        ( "+" , ConstValue::Real( x ) ) => Ok( ConstValue::Real(x) ),
+        ( "+" , ConstValue::Set( x ) ) => Ok( ConstValue::Set(x) ),
+        ( "-" , ConstValue::Integer( x ) ) => {
+            match x.checked_neg() {
+                Some( y ) => Ok( ConstValue::Integer(y) ),
+                _ => overflow(start, end)
+            }
+        },
+        ( "-" , ConstValue::Real( x ) ) => Ok( ConstValue::Real(-x) ),
+        ( "-" , ConstValue::Set( x ) ) => Ok( ConstValue::Set(!x) ),
+        ( "~" , ConstValue::Boolean( x ) ) => Ok( ConstValue::Boolean(!x) ),
+        _ => mismatch(operator, start, end)
+    }
+}
+
+/// Integer division rounding towards negative infinity, modulus has sign of divisor
+fn divide(operator: &str, left: i64, right: i64, start: u32, end: u32) -> Folded {
+    if right == 0 {
+        return invalid(E_DIVISION_BY_ZERO, String::from("Division by zero in constant expression"), start, end)
+    }
+
+    let ( quotient , remainder ) = match ( left.checked_div(right) , left.checked_rem(right) ) {
+        ( Some( q ) , Some( r ) ) => ( q , r ),
+        _ => return overflow(start, end)
+    };
+
+    let floor = remainder != 0 && ( ( remainder < 0 ) != ( right < 0 ) );
+
+    match ( operator , floor ) {
+        ( "DIV" , true ) => Ok( ConstValue::Integer(quotient - 1) ),
+        ( "DIV" , _ ) => Ok( ConstValue::Integer(quotient) ),
+        ( _ , true ) => Ok( ConstValue::Integer(remainder + right) ),
+        _ => Ok( ConstValue::Integer(remainder) )
+    }
+}
+
+/// Infix operators, integers are widened to reals when mixed with reals
+pub fn binary(operator: &str, left: ConstValue, right: ConstValue, start: u32, end: u32) -> Folded {
+    match ( &left , &right ) {
+        ( ConstValue::Integer( l ) , ConstValue::Integer( r ) ) => {
+            let ( l , r ) = ( *l , *r );
+            let result = match operator {
+                "+" => l.checked_add(r),
+                "-" => l.checked_sub(r),
+                "*" => l.checked_mul(r),
+                "DIV" | "MOD" => return divide(operator, l, r, start, end),
+                "/" => {
+                    return match r {
+                        0 => invalid(E_DIVISION_BY_ZERO, String::from("Division by zero in constant expression"), start, end),
+                        _ => real(l as f64 / r as f64, start, end)
+                    }
+                },
+                "=" => return Ok( ConstValue::Boolean(l == r) ),
+                "#" => return Ok( ConstValue::Boolean(l != r) ),
+                "<" => return Ok( ConstValue::Boolean(l < r) ),
+                "<=" => return Ok( ConstValue::Boolean(l <= r) ),
+                ">" => return Ok( ConstValue::Boolean(l > r) ),
+                ">=" => return Ok( ConstValue::Boolean(l >= r) ),
+                _ => return mismatch(operator, start, end)
+            };
+
+            match result {
+                Some( x ) => Ok( ConstValue::Integer(x) ),
+                _ => overflow(start, end)
+            }
+        },
+        ( ConstValue::Integer( _ ) , ConstValue::Set( s ) ) if operator == "IN" => {
+            match &left {
+                ConstValue::Integer( x ) if *x >= 0 && *x < SET_ELEMENTS => Ok( ConstValue::Boolean(s & ( 1 << x ) != 0) ),
+                _ => invalid(E_OUT_OF_RANGE, format!("Set element must be in range 0 .. {}", SET_ELEMENTS - 1), start, end)
+            }
+        },
+        ( ConstValue::Integer( _ ) , ConstValue::Real( _ ) ) |
+        ( ConstValue::Real( _ ) , ConstValue::Integer( _ ) ) |
+        ( ConstValue::Real( _ ) , ConstValue::Real( _ ) ) => {
+            let ( l , r ) = ( as_real(&left).unwrap_or(0.0) , as_real(&right).unwrap_or(0.0) );
+            match operator {
+                "+" => real(l + r, start, end),
+                "-" => real(l - r, start, end),
+                "*" => real(l * r, start, end),
+                "/" if r == 0.0 => invalid(E_DIVISION_BY_ZERO, String::from("Division by zero in constant expression"), start, end),
+                "/" => real(l / r, start, end),
+                "=" => Ok( ConstValue::Boolean(l == r) ),
+                "#" => Ok( ConstValue::Boolean(l != r) ),
+                "<" => Ok( ConstValue::Boolean(l < r) ),
+                "<=" => Ok( ConstValue::Boolean(l <= r) ),
+                ">" => Ok( ConstValue::Boolean(l > r) ),
+                ">=" => Ok( ConstValue::Boolean(l >= r) ),
+                _ => mismatch(operator, start, end)
+            }
+        },
+        ( ConstValue::Set( l ) , ConstValue::Set( r ) ) => {
+            match operator {
+                "+" => Ok( ConstValue::Set(l | r) ),
+                "-" => Ok( ConstValue::Set(l & !r) ),
+                "*" => Ok( ConstValue::Set(l & r) ),
+                "/" => Ok( ConstValue::Set(l ^ r) ),
+                "=" => Ok( ConstValue::Boolean(l == r) ),
+                "#" => Ok( ConstValue::Boolean(l != r) ),
+                "<=" => Ok( ConstValue::Boolean(l & !r == 0) ),
+                ">=" => Ok( ConstValue::Boolean(r & !l == 0) ),
+                _ => mismatch(operator, start, end)
+            }
+        },
+        ( ConstValue::Boolean( l ) , ConstValue::Boolean( r ) ) => {
+            match operator {
+                "&" => Ok( ConstValue::Boolean(*l && *r) ),
+                "OR" => Ok( ConstValue::Boolean(*l || *r) ),
+                "=" => Ok( ConstValue::Boolean(l == r) ),
+                "#" => Ok( ConstValue::Boolean(l != r) ),
+                _ => mismatch(operator, start, end)
+            }
+        },
+        ( ConstValue::Nil , ConstValue::Nil ) => {
+            match operator {
+                "=" => Ok( ConstValue::Boolean(true) ),
+                "#" => Ok( ConstValue::Boolean(false) ),
+                _ => mismatch(operator, start, end)
+            }
+        },
+        _ => {
+            /* Characters and strings, single character strings compare as characters */
+            let ( l , r ) = match ( as_char(&left) , as_char(&right) , &left , &right ) {
+                ( Some( l ) , Some( r ) , _ , _ ) => ( char::from_u32(l).unwrap_or('\0').to_string() , char::from_u32(r).unwrap_or('\0').to_string() ),
+                ( _ , _ , ConstValue::String( l ) , ConstValue::String( r ) ) => ( l.to_string() , r.to_string() ),
+                _ => return mismatch(operator, start, end)
+            };
+
+            match operator {
+                "=" => Ok( ConstValue::Boolean(l == r) ),
+                "#" => Ok( ConstValue::Boolean(l != r) ),
+                "<" => Ok( ConstValue::Boolean(l < r) ),
+                "<=" => Ok( ConstValue::Boolean(l <= r) ),
+                ">" => Ok( ConstValue::Boolean(l > r) ),
+                ">=" => Ok( ConstValue::Boolean(l >= r) ),
+                _ => mismatch(operator, start, end)
+            }
+        }
+    }
+}
+
+/// Set with elements from .. to, single elements have from equal to
+pub fn set_elements(from: i64, to: i64, start: u32, end: u32) -> Folded {
+    if !( 0 .. SET_ELEMENTS ).contains(&from) || !( 0 .. SET_ELEMENTS ).contains(&to) {
+        return invalid(E_OUT_OF_RANGE, format!("Set element must be in range 0 .. {}", SET_ELEMENTS - 1), start, end)
+    }
+
+    let mut bits : u32 = 0;
+    for element in from ..= to {
+        bits |= 1 << element
+    }
+
+    Ok( ConstValue::Set(bits) )
+}
+
+/// Predeclared functions with constant arguments
+pub fn builtin(name: &str, arguments: &Vec<ConstValue>, start: u32, end: u32) -> Folded {
+    match ( name , arguments.as_slice() ) {
+        ( "ABS" , [ ConstValue::Integer( x ) ] ) => {
+            match x.checked_abs() {
+                Some( y ) => Ok( ConstValue::Integer(y) ),
+                _ => overflow(start, end)
+            }
+        },
+        ( "ABS" , [ ConstValue::Real( x ) ] ) => Ok( ConstValue::Real(x.abs()) ),
+        ( "ODD" , [ ConstValue::Integer( x ) ] ) => Ok( ConstValue::Boolean(x & 1 == 1) ),
+        ( "ASH" , [ ConstValue::Integer( x ) , ConstValue::Integer( n ) ] ) => {
+            match *n >= 0 {
+                true => {
+                    /* Arithmetic shift left is multiplication, it overflows when bits are lost */
+                    match u32::try_from(*n).ok().and_then(|n| 1i64.checked_shl(n).filter(|_| n < 63)).and_then(|f| x.checked_mul(f)) {
+                        Some( y ) => Ok( ConstValue::Integer(y) ),
+                        _ if *x == 0 => Ok( ConstValue::Integer(0) ),
+                        _ => overflow(start, end)
+                    }
+                },
+                _ => Ok( ConstValue::Integer(x >> n.unsigned_abs().min(63)) )
+            }
+        },
+        ( "LSH" , [ ConstValue::Integer( x ) , ConstValue::Integer( n ) ] ) => {
+            let bits = *x as u64;
+            let shift = n.unsigned_abs();
+            let result = match ( *n >= 0 , shift >= 64 ) {
+                ( _ , true ) => 0,
+                ( true , _ ) => bits << shift,
+                _ => bits >> shift
+            };
+            Ok( ConstValue::Integer(result as i64) )
+        },
+        ( "ORD" , [ x ] ) => {
+            match as_char(x) {
+                Some( c ) => Ok( ConstValue::Integer(c as i64) ),
+                _ => mismatch(name, start, end)
+            }
+        },
+        ( "CHR" , [ ConstValue::Integer( x ) ] ) => {
+            match ( 0 ..= 0xff ).contains(x) {
+                true => Ok( ConstValue::Char(*x as u32) ),
+                _ => invalid(E_OUT_OF_RANGE, format!("CHR argument {} is out of range 0 .. 255", x), start, end)
+            }
+        },
+        ( "CAP" , [ x ] ) => {
+            match as_char(x).and_then(char::from_u32) {
+                Some( c ) => Ok( ConstValue::Char(c.to_ascii_uppercase() as u32) ),
+                _ => mismatch(name, start, end)
+            }
+        },
+        ( "MIN" , [ l , r ] ) | ( "MAX" , [ l , r ] ) => {
+            let less = binary("<", l.clone(), r.clone(), start, end)?;
+            match ( name , less ) {
+                ( "MIN" , ConstValue::Boolean( true ) ) | ( "MAX" , ConstValue::Boolean( false ) ) => {
+                    match ( l , r ) {
+                        ( ConstValue::Integer( _ ) , ConstValue::Real( _ ) ) => Ok( ConstValue::Real(as_real(l).unwrap_or(0.0)) ),
+                        _ => Ok( l.clone() )
+                    }
+                },
+                _ => {
+                    match ( l , r ) {
+                        ( ConstValue::Real( _ ) , ConstValue::Integer( _ ) ) => Ok( ConstValue::Real(as_real(r).unwrap_or(0.0)) ),
+                        _ => Ok( r.clone() )
+                    }
+                }
+            }
+        },
+        ( "ABS" , _ ) | ( "ODD" , _ ) | ( "ASH" , _ ) | ( "LSH" , _ ) | ( "CHR" , _ ) | ( "MIN" , _ ) | ( "MAX" , _ ) => mismatch(name, start, end),
+        _ => Err(ConstError::NotConstant(start, end))
+    }
+}
+
+/// Message for expression that must be constant but is not
+pub fn not_constant(start: u32, end: u32) -> Box<Diagnostic> {
+    Diagnostic::error(E_NOT_CONSTANT, String::from("Expression is not constant"), start, end)
+}
+
+// Unittests for constant evaluator module
+
+#[cfg(test)]
+mod tests {
+    use crate::const_evaluator::{ConstValue, ConstError, binary, unary, builtin, set_elements};
+    use crate::diagnostics::{E_DIVISION_BY_ZERO, E_OUT_OF_RANGE};
+
+    fn code(res: Result<ConstValue, ConstError>) -> &'static str {
+        match res {
+            Err( ConstError::Invalid( x ) ) => x.code,
+            _ => ""
+        }
+    }
+
+    #[test]
+    fn integer_arithmetic() {
+        assert_eq!(binary("+", ConstValue::Integer(2), ConstValue::Integer(3), 0, 1), Ok( ConstValue::Integer(5) ));
+        assert_eq!(binary("DIV", ConstValue::Integer(-7), ConstValue::Integer(2), 0, 1), Ok( ConstValue::Integer(-4) ));
+        assert_eq!(binary("MOD", ConstValue::Integer(-7), ConstValue::Integer(2), 0, 1), Ok( ConstValue::Integer(1) ));
+        assert_eq!(binary("MOD", ConstValue::Integer(7), ConstValue::Integer(-2), 0, 1), Ok( ConstValue::Integer(-1) ));
+        assert_eq!(binary("/", ConstValue::Integer(1), ConstValue::Integer(4), 0, 1), Ok( ConstValue::Real(0.25) ))
+    }
+
+    #[test]
+    fn integer_overflow_and_division_by_zero() {
+        assert_eq!(code(binary("*", ConstValue::Integer(i64::MAX), ConstValue::Integer(2), 0, 1)), E_OUT_OF_RANGE);
+        assert_eq!(code(binary("DIV", ConstValue::Integer(i64::MIN), ConstValue::Integer(-1), 0, 1)), E_OUT_OF_RANGE);
+        assert_eq!(code(unary("-", ConstValue::Integer(i64::MIN), 0, 1)), E_OUT_OF_RANGE);
+        assert_eq!(code(binary("MOD", ConstValue::Integer(1), ConstValue::Integer(0), 0, 1)), E_DIVISION_BY_ZERO);
+        assert_eq!(code(binary("/", ConstValue::Real(1.0), ConstValue::Integer(0), 0, 1)), E_DIVISION_BY_ZERO)
+    }
+
+    #[test]
+    fn sets_booleans_and_strings() {
+        assert_eq!(set_elements(1, 3, 0, 1), Ok( ConstValue::Set(0b1110) ));
+        assert_eq!(code(set_elements(0, 32, 0, 1)), E_OUT_OF_RANGE);
+        assert_eq!(binary("-", ConstValue::Set(0b1110), ConstValue::Set(0b0100), 0, 1), Ok( ConstValue::Set(0b1010) ));
+        assert_eq!(binary("IN", ConstValue::Integer(3), ConstValue::Set(0b1000), 0, 1), Ok( ConstValue::Boolean(true) ));
+        assert_eq!(binary("OR", ConstValue::Boolean(false), ConstValue::Boolean(true), 0, 1), Ok( ConstValue::Boolean(true) ));
+        assert_eq!(binary("<", ConstValue::String(Box::new(String::from("abc"))), ConstValue::String(Box::new(String::from("abd"))), 0, 1), Ok( ConstValue::Boolean(true) ));
+        assert_eq!(binary("=", ConstValue::Char(97), ConstValue::String(Box::new(String::from("a"))), 0, 1), Ok( ConstValue::Boolean(true) ))
+    }
+
+    #[test]
+    fn builtin_functions() {
+        assert_eq!(builtin("ASH", &vec![ ConstValue::Integer(-8), ConstValue::Integer(-2) ], 0, 1), Ok( ConstValue::Integer(-2) ));
+        assert_eq!(builtin("ASH", &vec![ ConstValue::Integer(3), ConstValue::Integer(4) ], 0, 1), Ok( ConstValue::Integer(48) ));
+        assert_eq!(code(builtin("ASH", &vec![ ConstValue::Integer(1), ConstValue::Integer(63) ], 0, 1)), E_OUT_OF_RANGE);
+        assert_eq!(builtin("LSH", &vec![ ConstValue::Integer(-1), ConstValue::Integer(-60) ], 0, 1), Ok( ConstValue::Integer(15) ));
+        assert_eq!(builtin("ORD", &vec![ ConstValue::Char(65) ], 0, 1), Ok( ConstValue::Integer(65) ));
+        assert_eq!(builtin("CHR", &vec![ ConstValue::Integer(66) ], 0, 1), Ok( ConstValue::Char(66) ));
+        assert_eq!(code(builtin("CHR", &vec![ ConstValue::Integer(256) ], 0, 1)), E_OUT_OF_RANGE);
+        assert_eq!(builtin("ABS", &vec![ ConstValue::Integer(-5) ], 0, 1), Ok( ConstValue::Integer(5) ));
+        assert_eq!(builtin("MAX", &vec![ ConstValue::Integer(2), ConstValue::Real(1.5) ], 0, 1), Ok( ConstValue::Real(2.0) ))
+    }
+}
@@ -0,0 +1,285 @@
+// ActiveOberon Compiler, a native ARM v8 & X86-64 compiler & Risc V / linker / builder utility.
+// Written by Richard Magnor Stenbro. Licensed under GPL v3
+// Diagnostics module for reporting errors and warnings from all stages of the compiler
+
+use console::style;
+
+/* Error codes for scanner */
+pub const E_INVALID_CHARACTER : &str = "E0001";
+pub const E_UNTERMINATED_COMMENT : &str = "E0002";
+pub const E_MALFORMED_NUMBER : &str = "E0003";
+pub const E_UNTERMINATED_STRING : &str = "E0004";
+
+/* Error codes for parser */
+pub const E_SYNTAX : &str = "E0100";
+pub const E_MODULE_NAME_MISMATCH : &str = "E0101";
+
+/* Error codes for inline assembler */
+pub const E_ASSEMBLER_SYNTAX : &str = "E0200";
+pub const E_ASSEMBLER_CPU_FLAG : &str = "E0201";
+pub const E_ASSEMBLER_UNDEFINED : &str = "E0203";
+pub const E_ASSEMBLER_DUPLICATE : &str = "E0204";
+pub const E_ASSEMBLER_OPERAND : &str = "E0205";
+pub const E_ASSEMBLER_INSTRUCTION : &str = "E0206";
+
+/* Error codes for semantic analysis */
+pub const E_DUPLICATE_DECLARATION : &str = "E0300";
+pub const E_INVALID_EXPORT : &str = "E0301";
+pub const E_NOT_EXPORTED : &str = "E0302";
+pub const E_UNDECLARED : &str = "E0303";
+pub const E_AMBIGUOUS_IMPORT : &str = "E0304";
+
+/* Error codes for type checker */
+pub const E_TYPE_MISMATCH : &str = "E0310";
+pub const E_INCOMPATIBLE_ASSIGNMENT : &str = "E0311";
+pub const E_NOT_ASSIGNABLE : &str = "E0312";
+pub const E_ARGUMENT : &str = "E0313";
+pub const E_NO_FIELD : &str = "E0314";
+pub const E_NOT_CALLABLE : &str = "E0315";
+pub const E_RESULT : &str = "E0316";
+pub const E_NOT_IMPLEMENTED : &str = "E0317";
+pub const E_SIGNATURE_MISMATCH : &str = "E0318";
+
+/* Error codes for constant evaluation */
+pub const E_NOT_CONSTANT : &str = "E0320";
+pub const E_DIVISION_BY_ZERO : &str = "E0321";
+pub const E_OUT_OF_RANGE : &str = "E0322";
+
+/* Error codes for intermediate representation */
+pub const E_LOWERING : &str = "E0400";
+pub const E_IR_SYNTAX : &str = "E0401";
+pub const E_IR_VERIFY : &str = "E0402";
+
+/* Error codes for driver */
+pub const E_SOURCE_FILE : &str = "E0900";
+
+/* Warning codes for linter */
+pub const W_UNUSED_IMPORT : &str = "W0001";
+pub const W_UNUSED_VARIABLE : &str = "W0002";
+pub const W_UNREACHABLE_CODE : &str = "W0003";
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Severity {
+	Error,
+	Warning
+}
+
+/// Range of characters in source file, start is inclusive and end is exclusive
+#[derive(Clone, PartialEq, Debug)]
+pub struct Span {
+	pub start: u32,
+	pub end: u32
+}
+
+/// Secondary position in source file with its own explanation
+#[derive(Clone, PartialEq, Debug)]
+pub struct Label {
+	pub span: Span,
+	pub message: Box<String>
+}
+
+/// Error or warning produced by any stage of the compiler
+#[derive(Clone, PartialEq, Debug)]
+pub struct Diagnostic {
+	pub severity: Severity,
+	pub code: &'static str,
+	pub message: Box<String>,
+	pub span: Span,
+	pub labels: Vec<Label>,
+	pub notes: Vec<Box<String>>
+}
+
+pub trait DiagnosticMethods {
+	fn new(severity: Severity, code: &'static str, message: String, start: u32, end: u32) -> Self;
+	fn error(code: &'static str, message: String, start: u32, end: u32) -> Box<Diagnostic>;
+	fn warning(code: &'static str, message: String, start: u32, end: u32) -> Box<Diagnostic>;
+	fn with_label(self: Box<Self>, message: String, start: u32, end: u32) -> Box<Diagnostic>;
+	fn with_note(self: Box<Self>, message: String) -> Box<Diagnostic>;
+	fn is_error(&self) -> bool;
+}
+
+impl DiagnosticMethods for Diagnostic {
+	fn new(severity: Severity, code: &'static str, message: String, start: u32, end: u32) -> Self {
+		Diagnostic {
+			severity,
+			code,
+			message: Box::new(message),
+			span: Span { start, end: if end < start { start } else { end } },
+			labels: Vec::new(),
+			notes: Vec::new()
+		}
+	}
+
+	fn error(code: &'static str, message: String, start: u32, end: u32) -> Box<Diagnostic> {
+		Box::new(Diagnostic::new(Severity::Error, code, message, start, end))
+	}
+
+	fn warning(code: &'static str, message: String, start: u32, end: u32) -> Box<Diagnostic> {
+		Box::new(Diagnostic::new(Severity::Warning, code, message, start, end))
+	}
+
+	fn with_label(mut self: Box<Self>, message: String, start: u32, end: u32) -> Box<Diagnostic> {
+		self.labels.push(Label { span: Span { start, end: if end < start { start } else { end } }, message: Box::new(message) });
+		self
+	}
+
+	fn with_note(mut self: Box<Self>, message: String) -> Box<Diagnostic> {
+		self.notes.push(Box::new(message));
+		self
+	}
+
+	fn is_error(&self) -> bool {
+		self.severity == Severity::Error
+	}
+}
+
+/// Source file with line table, used to turn character positions into line and column
+pub struct SourceMap {
+	file_name: String,
+	text: Vec<char>,		/* Same character vector as seen by the scanner */
+	line_starts: Vec<u32>	/* Character position of first character on each line */
+}
+
+pub trait SourceMapMethods {
+	fn new(file_name: &str, text: &str) -> Self;
+	fn file_name(&self) -> &str;
+	fn line_column(&self, position: u32) -> (u32, u32);
+	fn line_text(&self, line: u32) -> String;
+	fn render(&self, diagnostic: &Diagnostic) -> String;
+	fn render_short(&self, diagnostic: &Diagnostic) -> String;
+}
+
+impl SourceMapMethods for SourceMap {
+	fn new(file_name: &str, text: &str) -> Self {
+		let chars : Vec<char> = text.chars().collect();
+		let mut line_starts = vec![ 0 ];
+		let mut index = 0;
+
+		while index < chars.len() {
+			match chars[index] {
+				'\r' => {
+					if index + 1 < chars.len() && chars[index + 1] == '\n' {
+						index += 1
+					}
+					line_starts.push((index + 1) as u32)
+				},
+				'\n' => line_starts.push((index + 1) as u32),
+				_ => ()
+			}
+			index += 1
+		}
+
+		SourceMap {
+			file_name: String::from(file_name),
+			text: chars,
+			line_starts
+		}
+	}
+
+	fn file_name(&self) -> &str {
+		self.file_name.as_str()
+	}
+
+	/// Line and column of a character position, both starting at one and counted in characters
+	fn line_column(&self, position: u32) -> (u32, u32) {
+		let line = match self.line_starts.binary_search(&position) {
+			Ok(x) => x,
+			Err(x) => x - 1
+		};
+		(line as u32 + 1, position - self.line_starts[line] + 1)
+	}
+
+	fn line_text(&self, line: u32) -> String {
+		let index = (line - 1) as usize;
+		if index >= self.line_starts.len() {
+			return String::new()
+		}
+		let start = self.line_starts[index] as usize;
+		let end = match self.line_starts.get(index + 1) {
+			Some(x) => *x as usize,
+			_ => self.text.len()
+		};
+
+		self.text[start .. end].iter().filter(|c| **c != '\r' && **c != '\n').collect()
+	}
+
+	/// Full presentation with source line and marker under the primary span
+	fn render(&self, diagnostic: &Diagnostic) -> String {
+		let mut text = self.render_short(diagnostic);
+		let (line, col) = self.line_column(diagnostic.span.start);
+		let source_line = self.line_text(line);
+
+		if !self.text.is_empty() {
+			let (end_line, end_col) = self.line_column(diagnostic.span.end);
+			let width = match end_line == line && end_col > col {
+				true => end_col - col,
+				_ => 1
+			};
+			let padding : String = source_line.chars().take((col - 1) as usize).map(|c| if c == '\t' { '\t' } else { ' ' }).collect();
+
+			text.push_str(format!("\r\n{}\r\n{}{}", source_line, padding, style((0..width).map(|_| "^").collect::<String>()).red()).as_str());
+		}
+
+		for label in diagnostic.labels.iter() {
+			let (label_line, label_col) = self.line_column(label.span.start);
+			text.push_str(format!("\r\n{}:{}:{}: {}: {}", self.file_name, label_line, label_col, style("note").cyan(), label.message).as_str());
+		}
+
+		for note in diagnostic.notes.iter() {
+			text.push_str(format!("\r\n  = {}: {}", style("note").cyan(), note).as_str());
+		}
+
+		text
+	}
+
+	/// Single line presentation 'file:line:col: severity[code]: message' for editors and tools
+	fn render_short(&self, diagnostic: &Diagnostic) -> String {
+		let (line, col) = self.line_column(diagnostic.span.start);
+		let severity = match diagnostic.severity {
+			Severity::Error => style(format!("error[{}]", diagnostic.code)).red(),
+			Severity::Warning => style(format!("warning[{}]", diagnostic.code)).yellow()
+		};
+
+		format!("{}:{}:{}: {}: {}", self.file_name, line, col, severity, diagnostic.message)
+	}
+}
+
+// Unittests for diagnostics module
+
+#[cfg(test)]
+mod tests {
+	use crate::diagnostics::{Diagnostic, DiagnosticMethods, SourceMap, SourceMapMethods, E_SYNTAX};
+
+	#[test]
+	fn line_column_lf() {
+		let map = SourceMap::new("test.Mod", "MODULE a;\nEND a.");
+		assert_eq!(map.line_column(0), (1, 1));
+		assert_eq!(map.line_column(10), (2, 1));
+		assert_eq!(map.line_column(14), (2, 5));
+	}
+
+	#[test]
+	fn line_column_crlf() {
+		let map = SourceMap::new("test.Mod", "MODULE a;\r\nEND a.");
+		assert_eq!(map.line_column(9), (1, 10));
+		assert_eq!(map.line_column(11), (2, 1));
+		assert_eq!(map.line_column(15), (2, 5));
+		assert_eq!(map.line_text(1), String::from("MODULE a;"));
+		assert_eq!(map.line_text(2), String::from("END a."));
+	}
+
+	#[test]
+	fn line_column_multi_byte_characters() {
+		let map = SourceMap::new("test.Mod", "(* æøå *) x\r\n  ÆØÅ y");
+		assert_eq!(map.line_column(10), (1, 11));
+		assert_eq!(map.line_column(19), (2, 7));
+	}
+
+	#[test]
+	fn render_short_format() {
+		console::set_colors_enabled(false);
+		let map = SourceMap::new("test.Mod", "MODULE a;\r\nEN a.");
+		let diagnostic = Diagnostic::error(E_SYNTAX, String::from("Expecting 'END' in module"), 11, 13);
+		assert_eq!(map.render_short(&diagnostic), String::from("test.Mod:2:1: error[E0100]: Expecting 'END' in module"));
+	}
+}
@@ -0,0 +1,137 @@
+// ActiveOberon Compiler, a native ARM v8 & X86-64 compiler & Risc V / linker / builder utility.
+// Written by Richard Magnor Stenbro. Licensed under GPL v3
+// Frame layout module, places parameters and local variables of procedures relative to the frame pointer
+
+use std::collections::HashMap;
+use crate::symbol_table::{ScopeId, EntryId, ScopeKind, SymbolKind, SymbolTableMethods};
+use crate::resolver::ResolverMethods;
+use crate::type_checker::{TypeChecker, TypeCheckerMethods, ParameterMode, T_UNKNOWN};
+use crate::inline_assembler::OberonVariable;
+
+/// Offset of last parameter from frame pointer, saved frame pointer and return address are below it
+pub const FRAME_PARAMETERS : i64 = 16;
+
+/// Offset from frame pointer and size in bytes of parameters and local variables of procedure scope.
+/// Parameters are pushed from left to right in slots of 8 bytes, 'VAR' parameters and parameters
+/// larger than a slot are passed by address. Local variables are below frame pointer in order of
+/// declaration, aligned to their own size. Entries without known size are left out.
+pub fn frame_layout(checker: &TypeChecker, scope: ScopeId) -> Vec<(EntryId, i64, i64)> {
+    let table = checker.get_resolver().get_symbol_table();
+    let mut layout = Vec::<(EntryId, i64, i64)>::new();
+
+    match table.scope(scope).kind {
+        ScopeKind::Procedure | ScopeKind::Operator => (),
+        _ => return layout
+    }
+
+    let size = |entry: &EntryId| checker.type_size(checker.get_entry_type(*entry).unwrap_or(T_UNKNOWN));
+    let entries = &table.scope(scope).entries;
+
+    let mut offset = FRAME_PARAMETERS;
+    for entry in entries.iter().rev().filter(|x| table.entry(**x).kind == SymbolKind::Parameter) {
+        let bytes = match ( checker.get_parameter_mode(*entry) , size(entry) ) {
+            ( Some( ParameterMode::Var ) , _ ) | ( _ , None ) => 8,
+            ( _ , Some( x ) ) if x > 8 => 8,
+            ( _ , Some( x ) ) => x
+        };
+        layout.push(( *entry , offset , bytes ));
+        offset += 8
+    }
+
+    let mut locals = 0;
+    for entry in entries.iter().filter(|x| table.entry(**x).kind == SymbolKind::Variable) {
+        if let Some( bytes ) = size(entry) {
+            let align = bytes.clamp(1, 8);
+            locals = ( locals + bytes + align - 1 ) / align * align;
+            layout.push(( *entry , -locals , bytes ))
+        }
+    }
+
+    layout
+}
+
+/// Oberon variables that 'CODE' block in scope can name. These are parameters and local variables of the
+/// procedure and variables of the module, local variables of enclosing procedures are not reachable from frame.
+pub fn code_variables(checker: &TypeChecker, scope: ScopeId) -> HashMap<String, OberonVariable> {
+    let table = checker.get_resolver().get_symbol_table();
+    let module = table.enclosing_module(scope);
+    let mut variables = HashMap::<String, OberonVariable>::new();
+
+    for entry in table.scope(module).entries.iter() {
+        let symbol = table.entry(*entry);
+        if let ( SymbolKind::Variable , Some( size ) ) = ( &symbol.kind , checker.type_size(checker.get_entry_type(*entry).unwrap_or(T_UNKNOWN)) ) {
+            variables.insert(symbol.name.clone(), OberonVariable::Global(format!("{}.{}", table.scope(module).name, symbol.name), size as u32));
+        }
+    }
+
+    for ( entry , offset , size ) in frame_layout(checker, scope) {
+        variables.insert(table.entry(entry).name.clone(), OberonVariable::Frame(offset, size as u32));
+    }
+
+    variables
+}
+
+/// Oberon variables of each 'CODE' block in module deferred by parser, keyed by position of assembler text
+pub fn deferred_code_variables(checker: &TypeChecker, module: ScopeId, positions: &[u32]) -> HashMap<u32, HashMap<String, OberonVariable>> {
+    let mut variables = HashMap::<u32, HashMap<String, OberonVariable>>::new();
+
+    for position in positions.iter() {
+        if let Some( scope ) = checker.get_code_scope(module, *position) {
+            variables.insert(*position, code_variables(checker, scope));
+        }
+    }
+
+    variables
+}
+
+// Unittests for frame layout module
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{Parser, ParserMethods, BlockRules};
+    use crate::scanner::{Scanner, ScannerMethods};
+    use crate::resolver::ResolverMethods;
+    use crate::symbol_table::SymbolTableMethods;
+    use crate::type_checker::{TypeChecker, TypeCheckerMethods};
+    use crate::inline_assembler::OberonVariable;
+    use crate::frame_layout::code_variables;
+
+    fn variables(source: &'static str, procedure: &str) -> std::collections::HashMap<String, OberonVariable> {
+        let mut checker = TypeChecker::new();
+        let module = Parser::new(Box::new(Scanner::new(source))).parse_module().unwrap();
+        let res = checker.check_modules(&[ &*module ]);
+        assert_eq!(res[0].len(), 0);
+
+        let table = checker.get_resolver().get_symbol_table();
+        let scope = checker.get_resolver().get_module_scope("Test").unwrap();
+        let scope = match table.lookup_local(scope, procedure) {
+            Some( x ) => table.entry(x).inner.unwrap(),
+            _ => scope
+        };
+        code_variables(&checker, scope)
+    }
+
+    #[test]
+    fn parameters_and_locals() {
+        let res = variables("MODULE Test; VAR g: LONGINT; PROCEDURE P(a: INTEGER; VAR b: LONGINT; c: HUGEINT); VAR x: CHAR; y: LONGINT; z: ARRAY 3 OF HUGEINT; BEGIN END P; END Test.", "P");
+
+        assert_eq!(res.get("g"), Some( &OberonVariable::Global(String::from("Test.g"), 4) ));
+        assert_eq!(res.get("c"), Some( &OberonVariable::Frame(16, 8) ));
+        assert_eq!(res.get("b"), Some( &OberonVariable::Frame(24, 8) ));
+        assert_eq!(res.get("a"), Some( &OberonVariable::Frame(32, 2) ));
+        assert_eq!(res.get("x"), Some( &OberonVariable::Frame(-1, 1) ));
+        assert_eq!(res.get("y"), Some( &OberonVariable::Frame(-8, 4) ));
+        assert_eq!(res.get("z"), Some( &OberonVariable::Frame(-32, 24) ))
+    }
+
+    #[test]
+    fn module_scope_and_shadowing() {
+        let res = variables("MODULE Test; VAR g, h: SET; PROCEDURE P(g: HUGEINT); END P; END Test.", "P");
+        assert_eq!(res.get("g"), Some( &OberonVariable::Frame(16, 8) ));
+        assert_eq!(res.get("h"), Some( &OberonVariable::Global(String::from("Test.h"), 4) ));
+
+        let res = variables("MODULE Test; VAR g: SET; PROCEDURE P(g: HUGEINT); END P; END Test.", "Test");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res.get("g"), Some( &OberonVariable::Global(String::from("Test.g"), 4) ))
+    }
+}
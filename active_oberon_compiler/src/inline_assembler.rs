@@ -0,0 +1,162 @@
+
+// ActiveOberon Compiler, a native ARM v8 & X86-64 compiler & Risc V / linker / builder utility.
+// Written by Richard Magnor Stenbro. Licensed under GPL v3
+// Inline assembler selection module, picks the assembler for the target CPU of 'CODE' blocks
+
+use std::collections::HashMap;
+use crate::diagnostics::Diagnostic;
+use crate::amd64_assembler::{AssemblerAMD64, AssemblerAMD64Methods};
+use crate::arm64_assembler::{AssemblerARM64, AssemblerARM64Methods};
+use crate::riscv_assembler::{AssemblerRiscV, AssemblerRiscVMethods};
+
+/// CPU that code is generated for, selected by command line flags
+#[derive(Clone, PartialEq, Debug)]
+pub enum Target {
+    X86_64,
+    ArmV8,
+    RiscV
+}
+
+/// Oberon variable or parameter that 'CODE' block can name, with its size in bytes
+#[derive(Clone, PartialEq, Debug)]
+pub enum OberonVariable {
+    Frame(i64, u32),        /* Offset from frame pointer of parameter or local variable */
+    Global(String, u32)     /* Module variable, reached through relocation with qualified name */
+}
+
+/// Place in machine code of 'CODE' block where linker adds address of symbol relative to the place, like R_X86_64_PC32
+#[derive(Clone, PartialEq, Debug)]
+pub struct Relocation {
+    pub offset: usize,
+    pub symbol: String,
+    pub addend: i64
+}
+
+/// Shared by the inline assemblers of all back ends
+pub trait InlineAssembler {
+    /// Machine code of assembler text, errors are positioned in Oberon source file
+    fn assemble(&mut self) -> Result<Box<Vec<u8>>, Box<Diagnostic>>;
+    /// Oberon variables visible in 'CODE' block, used for names not defined in assembler text
+    fn set_variables(&mut self, variables: HashMap<String, OberonVariable>) -> ();
+    /// Places in machine code referring to module variables, valid after 'assemble'
+    fn get_relocations(&self) -> &Vec<Relocation>;
+}
+
+/// Target from command line flags, X86-64 when none is given. More than one flag is an error.
+pub fn select_target(x86_64: bool, arm_v8: bool, risc_v: bool) -> Result<Target, String> {
+    match ( x86_64 , arm_v8 , risc_v ) {
+        ( _ , false , false ) => Ok( Target::X86_64 ),
+        ( false , true , false ) => Ok( Target::ArmV8 ),
+        ( false , false , true ) => Ok( Target::RiscV ),
+        _ => Err( String::from("Only one of '--x86-64', '--arm-v8' and '--risc-v' can be given") )
+    }
+}
+
+/// Names of Oberon variables in assembler text of 'CODE' block, each name once in order of first use.
+/// Comments starting with ';' or '//' and quoted strings are skipped.
+pub fn referenced_variables(text: &[char], variables: &HashMap<String, OberonVariable>) -> Vec<String> {
+    let mut names = Vec::<String>::new();
+    let mut index = 0;
+
+    while index < text.len() {
+        match text[index] {
+            ';' => {
+                while index < text.len() && text[index] != '\n' {
+                    index += 1
+                }
+            },
+            '/' if text.get(index + 1) == Some( &'/' ) => {
+                while index < text.len() && text[index] != '\n' {
+                    index += 1
+                }
+            },
+            '\'' | '"' => {
+                let quote = text[index];
+                index += 1;
+                while index < text.len() && text[index] != quote {
+                    index += 1
+                }
+                index += 1
+            },
+            c if c.is_alphanumeric() || c == '_' || c == '@' => {
+                let start = index;
+                while index < text.len() && ( text[index].is_alphanumeric() || text[index] == '_' || text[index] == '@' ) {
+                    index += 1
+                }
+                let name : String = text[start .. index].iter().collect();
+                match c.is_ascii_digit() || names.contains(&name) || !variables.contains_key(&name) {
+                    true => (),     /* Numbers like '0FFH' are not names */
+                    _ => names.push(name)
+                }
+            },
+            _ => index += 1
+        }
+    }
+
+    names
+}
+
+/// Assembler for text of 'CODE' block starting at offset position in Oberon source file
+pub fn assembler_for(target: &Target, text: Vec<char>, offset_position: u32) -> Box<dyn InlineAssembler> {
+    match target {
+        Target::X86_64 => Box::new(AssemblerAMD64::new(text, offset_position)),
+        Target::ArmV8 => Box::new(AssemblerARM64::new(text, offset_position)),
+        Target::RiscV => Box::new(AssemblerRiscV::new(text, offset_position))
+    }
+}
+
+/// Machine code of 'CODE' block assembled with the Oberon variables visible in it, together with the places
+/// referring to module variables
+pub struct AssembledCode {
+    pub code: Box<Vec<u8>>,
+    pub relocations: Vec<Relocation>
+}
+
+/// Assemble text of 'CODE' block for target, names not defined in assembler text are looked up in variables
+pub fn assemble_code_block(target: &Target, text: Vec<char>, offset_position: u32, variables: Option<&HashMap<String, OberonVariable>>) -> Result<AssembledCode, Box<Diagnostic>> {
+    let mut assembler = assembler_for(target, text, offset_position);
+    if let Some( x ) = variables {
+        assembler.set_variables(x.clone())
+    }
+    let code = assembler.assemble()?;
+
+    Ok( AssembledCode { code, relocations: assembler.get_relocations().clone() } )
+}
+
+// Unittests for inline assembler selection module
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use crate::inline_assembler::{Target, OberonVariable, select_target, assembler_for, referenced_variables};
+
+    #[test]
+    fn target_from_flags() {
+        assert_eq!(select_target(false, false, false), Ok( Target::X86_64 ));
+        assert_eq!(select_target(true, false, false), Ok( Target::X86_64 ));
+        assert_eq!(select_target(false, true, false), Ok( Target::ArmV8 ));
+        assert_eq!(select_target(false, false, true), Ok( Target::RiscV ));
+        assert!(select_target(true, true, false).is_err())
+    }
+
+    #[test]
+    fn assembler_for_target() {
+        assert_eq!(assembler_for(&Target::X86_64, " BITS 64 ".chars().collect(), 4).assemble(), Ok( Box::new(Vec::<u8>::new()) ));
+        assert_eq!(assembler_for(&Target::ArmV8, " NOP ".chars().collect(), 4).assemble(), Ok( Box::new(vec![ 0x1f, 0x20, 0x03, 0xd5 ]) ));
+        assert_eq!(assembler_for(&Target::RiscV, " NOP ".chars().collect(), 4).assemble(), Ok( Box::new(vec![ 0x01, 0x00 ]) ));
+        assert_eq!(assembler_for(&Target::RiscV, " { SYSTEM.RV64I } NOP ".chars().collect(), 4).assemble(), Ok( Box::new(vec![ 0x13, 0x00, 0x00, 0x00 ]) ))
+    }
+
+    #[test]
+    fn variables_named_in_code() {
+        let variables = HashMap::from([
+            ( String::from("x"), OberonVariable::Frame(-8, 8) ),
+            ( String::from("y"), OberonVariable::Frame(-16, 8) ),
+            ( String::from("g"), OberonVariable::Global(String::from("Test.g"), 4) ),
+            ( String::from("FFH"), OberonVariable::Frame(-24, 8) )
+        ]);
+        let text : Vec<char> = "MOV RAX, x ; y\nADD g, 0FFH // y\nMOV [RBP + x], 'y'\nDB \"y\"".chars().collect();
+
+        assert_eq!(referenced_variables(&text, &variables), vec![ String::from("x"), String::from("g") ])
+    }
+}
@@ -0,0 +1,555 @@
+// ActiveOberon Compiler, a native ARM v8 & X86-64 compiler & Risc V / linker / builder utility.
+// Written by Richard Magnor Stenbro. Licensed under GPL v3
+// Intermediate representation module, target independent procedures of basic blocks and virtual registers shared by all back ends
+
+use std::collections::HashMap;
+
+pub type VReg = usize;
+pub type BlockId = usize;
+pub type SlotId = usize;
+
+/// Every function starts executing in its first block
+pub const ENTRY_BLOCK : BlockId = 0;
+
+/* Trap numbers, same as the runtime of the Active Oberon system */
+pub const TRAP_WITH : i64 = 1;
+pub const TRAP_CASE : i64 = 2;
+pub const TRAP_RETURN : i64 = 3;
+pub const TRAP_TYPE_GUARD : i64 = 6;
+pub const TRAP_INDEX : i64 = 7;
+pub const TRAP_ASSERT : i64 = 8;
+
+/// Type of virtual register. Records, arrays and other structured values stay in memory and are handled by address.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum IrType {
+    Void,
+    Bool,
+    I8,
+    I16,
+    I32,
+    I64,
+    U8,
+    U16,
+    U32,
+    U64,
+    F32,
+    F64,
+    Ptr
+}
+
+pub trait IrTypeMethods {
+    fn size(&self) -> i64;
+    fn is_integer(&self) -> bool;
+    fn is_float(&self) -> bool;
+}
+
+impl IrTypeMethods for IrType {
+    /// Size in bytes of value in memory
+    fn size(&self) -> i64 {
+        match self {
+            IrType::Void => 0,
+            IrType::Bool | IrType::I8 | IrType::U8 => 1,
+            IrType::I16 | IrType::U16 => 2,
+            IrType::I32 | IrType::U32 | IrType::F32 => 4,
+            IrType::I64 | IrType::U64 | IrType::F64 | IrType::Ptr => 8
+        }
+    }
+
+    fn is_integer(&self) -> bool {
+        matches!(self, IrType::I8 | IrType::I16 | IrType::I32 | IrType::I64 | IrType::U8 | IrType::U16 | IrType::U32 | IrType::U64)
+    }
+
+    fn is_float(&self) -> bool {
+        matches!(self, IrType::F32 | IrType::F64)
+    }
+}
+
+/// Operators with one operand, 'Not' is logical for BOOLEAN and complement of bits for integers and sets
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum UnaryOp {
+    Neg,
+    Not
+}
+
+/// Operators with two operands of the same type as the result. 'Div' and 'Mod' round towards minus infinity
+/// for integers like Oberon 'DIV' and 'MOD'. 'Ash', 'Lsh' and 'Rot' shift left for positive counts and right
+/// for negative counts, like the predeclared procedures of the same names. Their count operand is always I64.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    And,
+    Or,
+    Xor,
+    Ash,
+    Lsh,
+    Rot
+}
+
+/// Comparison giving BOOLEAN, signed or unsigned by the type of the operands
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge
+}
+
+/// Value of constant, BOOLEAN is 0 or 1 and NIL is 0
+#[derive(Clone, PartialEq, Debug)]
+pub enum Constant {
+    Integer(i64),
+    Real(f64)
+}
+
+/// Procedure called, methods are found at run time through the type of the receiver given as first argument
+#[derive(Clone, PartialEq, Debug)]
+pub enum Callee {
+    Direct(String),
+    Method(String),
+    Indirect(VReg)
+}
+
+/// Parameter and result types of runtime procedures called by lowered code
+pub fn runtime_signature(name: &str) -> Option<(Vec<IrType>, IrType)> {
+    match name {
+        "Runtime.New" => Some( ( vec![ IrType::Ptr, IrType::I64 ], IrType::Ptr ) ),
+        "Runtime.CompareStrings" => Some( ( vec![ IrType::Ptr, IrType::Ptr ], IrType::I32 ) ),
+        "Runtime.CopyString" => Some( ( vec![ IrType::Ptr, IrType::I64, IrType::Ptr, IrType::I64 ], IrType::Void ) ),
+        _ => None
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Operation {
+    Constant(Constant),
+    Copy(VReg),
+    Unary(UnaryOp, VReg),
+    Binary(BinaryOp, VReg, VReg),
+    Compare(CompareOp, VReg, VReg),
+    Convert(VReg),                      /* To type of result, floating point to integer rounds down like 'ENTIER' */
+    Phi(Vec<(BlockId, VReg)>),          /* Value from each predecessor block */
+    SlotAddress(SlotId),
+    OuterSlotAddress(u32, SlotId),      /* Slot of enclosing procedure, number of levels up the static chain */
+    GlobalAddress(String),
+    ProcedureAddress(String),
+    TypeDescriptor(String),             /* Address of run time description of named record or object type */
+    Offset(VReg, VReg),                 /* Address plus offset in bytes */
+    Load(VReg),
+    Store(VReg, VReg),                  /* Address and value */
+    CopyMemory(VReg, VReg, i64),        /* Destination address, source address and size in bytes */
+    Call(Callee, Vec<VReg>),
+    TypeTest(VReg, String),             /* Memory block referenced has named type or an extension of it */
+    Code(Vec<u8>)                       /* Machine code of 'CODE' block for the target */
+}
+
+pub trait OperationMethods {
+    fn operands(&self) -> Vec<VReg>;
+    fn operands_mut(&mut self) -> Vec<&mut VReg>;
+    fn is_pure(&self) -> bool;
+}
+
+impl OperationMethods for Operation {
+    /// Registers read by operation
+    fn operands(&self) -> Vec<VReg> {
+        match self {
+            Operation::Copy( x ) | Operation::Unary( _ , x ) | Operation::Convert( x ) | Operation::Load( x ) | Operation::TypeTest( x , _ ) => vec![ *x ],
+            Operation::Binary( _ , x , y ) | Operation::Compare( _ , x , y ) | Operation::Offset( x , y ) |
+            Operation::Store( x , y ) | Operation::CopyMemory( x , y , _ ) => vec![ *x, *y ],
+            Operation::Phi( values ) => values.iter().map(|x| x.1).collect(),
+            Operation::Call( Callee::Indirect( x ) , arguments ) => {
+                let mut registers = vec![ *x ];
+                registers.extend(arguments.iter());
+                registers
+            },
+            Operation::Call( _ , arguments ) => arguments.clone(),
+            _ => Vec::new()
+        }
+    }
+
+    fn operands_mut(&mut self) -> Vec<&mut VReg> {
+        match self {
+            Operation::Copy( x ) | Operation::Unary( _ , x ) | Operation::Convert( x ) | Operation::Load( x ) | Operation::TypeTest( x , _ ) => vec![ x ],
+            Operation::Binary( _ , x , y ) | Operation::Compare( _ , x , y ) | Operation::Offset( x , y ) |
+            Operation::Store( x , y ) | Operation::CopyMemory( x , y , _ ) => vec![ x, y ],
+            Operation::Phi( values ) => values.iter_mut().map(|x| &mut x.1).collect(),
+            Operation::Call( Callee::Indirect( x ) , arguments ) => {
+                let mut registers = vec![ x ];
+                registers.extend(arguments.iter_mut());
+                registers
+            },
+            Operation::Call( _ , arguments ) => arguments.iter_mut().collect(),
+            _ => Vec::new()
+        }
+    }
+
+    /// Operation without effect but its result, it can be removed when result is not used
+    fn is_pure(&self) -> bool {
+        !matches!(self, Operation::Store( .. ) | Operation::CopyMemory( .. ) | Operation::Call( .. ) | Operation::Code( _ ))
+    }
+}
+
+/// Operation with virtual register it defines, each register is defined by only one instruction
+#[derive(Clone, PartialEq, Debug)]
+pub struct Instruction {
+    pub result: Option<VReg>,
+    pub operation: Operation
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Terminator {
+    Jump(BlockId),
+    Branch(VReg, BlockId, BlockId),                 /* Condition, block when true and block when false */
+    Switch(VReg, Vec<(i64, i64, BlockId)>, BlockId),  /* Inclusive ranges of values with their blocks, and default block */
+    Return(Option<VReg>),
+    Trap(i64)
+}
+
+pub trait TerminatorMethods {
+    fn successors(&self) -> Vec<BlockId>;
+    fn operand(&self) -> Option<VReg>;
+    fn operand_mut(&mut self) -> Option<&mut VReg>;
+    fn replace_successor(&mut self, from: BlockId, to: BlockId) -> ();
+}
+
+impl TerminatorMethods for Terminator {
+    /// Blocks control can continue in, in order of appearance
+    fn successors(&self) -> Vec<BlockId> {
+        match self {
+            Terminator::Jump( x ) => vec![ *x ],
+            Terminator::Branch( _ , t , f ) => vec![ *t, *f ],
+            Terminator::Switch( _ , cases , default ) => {
+                let mut blocks : Vec<BlockId> = cases.iter().map(|x| x.2).collect();
+                blocks.push(*default);
+                blocks
+            },
+            Terminator::Return( _ ) | Terminator::Trap( _ ) => Vec::new()
+        }
+    }
+
+    /// Register read by terminator
+    fn operand(&self) -> Option<VReg> {
+        match self {
+            Terminator::Branch( x , _ , _ ) | Terminator::Switch( x , _ , _ ) | Terminator::Return( Some( x ) ) => Some( *x ),
+            _ => None
+        }
+    }
+
+    fn operand_mut(&mut self) -> Option<&mut VReg> {
+        match self {
+            Terminator::Branch( x , _ , _ ) | Terminator::Switch( x , _ , _ ) | Terminator::Return( Some( x ) ) => Some( x ),
+            _ => None
+        }
+    }
+
+    fn replace_successor(&mut self, from: BlockId, to: BlockId) {
+        let replace = |x: &mut BlockId| if *x == from { *x = to };
+        match self {
+            Terminator::Jump( x ) => replace(x),
+            Terminator::Branch( _ , t , f ) => {
+                replace(t);
+                replace(f)
+            },
+            Terminator::Switch( _ , cases , default ) => {
+                for case in cases.iter_mut() {
+                    replace(&mut case.2)
+                }
+                replace(default)
+            },
+            _ => ()
+        }
+    }
+}
+
+/// Instructions executed in sequence, ended by terminator. Terminator is missing only while block is built.
+#[derive(Clone, PartialEq, Debug)]
+pub struct BasicBlock {
+    pub instructions: Vec<Instruction>,
+    pub terminator: Option<Terminator>
+}
+
+/// Memory in frame of function for variables that are stored or have their address taken
+#[derive(Clone, PartialEq, Debug)]
+pub struct StackSlot {
+    pub name: String,
+    pub size: i64,
+    pub align: i64
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Function {
+    pub name: String,                   /* Qualified by module and enclosing procedures or types */
+    pub export: bool,
+    pub parent: Option<String>,         /* Enclosing procedure, reached through static link */
+    pub parameters: Vec<VReg>,
+    pub result: IrType,
+    pub registers: Vec<IrType>,         /* Type of each virtual register */
+    pub slots: Vec<StackSlot>,
+    pub blocks: Vec<BasicBlock>,
+    pub finally: Option<BlockId>,       /* Block continued in when a trap occurs in body, from 'FINALLY' */
+    pub inline: bool,                   /* Declared 'PROCEDURE -', body is inlined at calls, also from other modules when exported */
+    pub frameless: bool                 /* Leaf procedure needing no stack frame, only a mark as no back end reads it yet */
+}
+
+pub trait FunctionMethods {
+    fn new(name: &str, result: IrType) -> Self;
+    fn new_register(&mut self, ir_type: IrType) -> VReg;
+    fn new_block(&mut self) -> BlockId;
+    fn new_slot(&mut self, name: &str, size: i64, align: i64) -> SlotId;
+    fn successors(&self, block: BlockId) -> Vec<BlockId>;
+    fn predecessors(&self) -> Vec<Vec<BlockId>>;
+    fn reachable(&self) -> Vec<bool>;
+    fn remove_unreachable_blocks(&mut self) -> ();
+    fn replace_uses(&mut self, replacements: &HashMap<VReg, VReg>) -> usize;
+    fn dominators(&self) -> Vec<Vec<bool>>;
+    fn instruction_count(&self) -> usize;
+}
+
+impl FunctionMethods for Function {
+    fn new(name: &str, result: IrType) -> Self {
+        Function {
+            name: String::from(name),
+            export: false,
+            parent: None,
+            parameters: Vec::new(),
+            result,
+            registers: Vec::new(),
+            slots: Vec::new(),
+            blocks: Vec::new(),
+            finally: None,
+            inline: false,
+            frameless: false
+        }
+    }
+
+    fn new_register(&mut self, ir_type: IrType) -> VReg {
+        self.registers.push(ir_type);
+        self.registers.len() - 1
+    }
+
+    fn new_block(&mut self) -> BlockId {
+        self.blocks.push( BasicBlock { instructions: Vec::new(), terminator: None } );
+        self.blocks.len() - 1
+    }
+
+    fn new_slot(&mut self, name: &str, size: i64, align: i64) -> SlotId {
+        self.slots.push( StackSlot { name: String::from(name), size, align } );
+        self.slots.len() - 1
+    }
+
+    fn successors(&self, block: BlockId) -> Vec<BlockId> {
+        match &self.blocks[block].terminator {
+            Some( x ) => x.successors(),
+            _ => Vec::new()
+        }
+    }
+
+    /// Predecessors of every block, a block is listed once for each edge
+    fn predecessors(&self) -> Vec<Vec<BlockId>> {
+        let mut predecessors = vec![ Vec::<BlockId>::new(); self.blocks.len() ];
+        for block in 0 .. self.blocks.len() {
+            for successor in self.successors(block) {
+                predecessors[successor].push(block)
+            }
+        }
+        predecessors
+    }
+
+    /// Blocks reached from entry block or from 'FINALLY' block
+    fn reachable(&self) -> Vec<bool> {
+        let mut reached = vec![ false; self.blocks.len() ];
+        let mut work = vec![ ENTRY_BLOCK ];
+        if let Some( x ) = self.finally {
+            work.push(x)
+        }
+
+        loop {
+            match work.pop() {
+                Some( block ) if block < reached.len() && !reached[block] => {
+                    reached[block] = true;
+                    work.append(&mut self.successors(block))
+                },
+                Some( _ ) => (),
+                None => break
+            }
+        }
+
+        reached
+    }
+
+    /// Remove blocks nothing reaches and number the rest in order, phis lose values from blocks that are no longer predecessors
+    fn remove_unreachable_blocks(&mut self) {
+        let reached = self.reachable();
+        let mut numbers = vec![ usize::MAX; self.blocks.len() ];
+        let mut count = 0;
+        for block in 0 .. self.blocks.len() {
+            if reached[block] {
+                numbers[block] = count;
+                count += 1
+            }
+        }
+
+        let blocks = std::mem::take(&mut self.blocks);
+        for ( block , mut basic_block ) in blocks.into_iter().enumerate() {
+            match reached[block] {
+                true => (),
+                _ => continue
+            }
+
+            for instruction in basic_block.instructions.iter_mut() {
+                if let Operation::Phi( values ) = &mut instruction.operation {
+                    values.retain(|x| x.0 < reached.len() && reached[x.0]);
+                    for value in values.iter_mut() {
+                        value.0 = numbers[value.0]
+                    }
+                }
+            }
+
+            basic_block.terminator = match basic_block.terminator {
+                Some( Terminator::Jump( x ) ) => Some( Terminator::Jump(numbers[x]) ),
+                Some( Terminator::Branch( c , t , f ) ) => Some( Terminator::Branch(c, numbers[t], numbers[f]) ),
+                Some( Terminator::Switch( v , cases , default ) ) => {
+                    Some( Terminator::Switch(v, cases.into_iter().map(|x| ( x.0, x.1, numbers[x.2] )).collect(), numbers[default]) )
+                },
+                x => x
+            };
+
+            self.blocks.push(basic_block)
+        }
+
+        self.finally = self.finally.map(|x| numbers[x]);
+
+        let predecessors = self.predecessors();
+        for ( block , basic_block ) in self.blocks.iter_mut().enumerate() {
+            for instruction in basic_block.instructions.iter_mut() {
+                if let Operation::Phi( values ) = &mut instruction.operation {
+                    values.retain(|x| predecessors[block].contains(&x.0))
+                }
+            }
+        }
+    }
+
+    /// Replace registers read by instructions and terminators, following chains of replacements. Gives number of operands replaced.
+    fn replace_uses(&mut self, replacements: &HashMap<VReg, VReg>) -> usize {
+        let resolve = |register: VReg| {
+            let mut current = register;
+            for _ in 0 .. replacements.len() {
+                match replacements.get(&current) {
+                    Some( x ) if *x != current => current = *x,
+                    _ => break
+                }
+            }
+            current
+        };
+
+        let mut count = 0;
+        for block in self.blocks.iter_mut() {
+            let mut operands : Vec<&mut VReg> = block.instructions.iter_mut().flat_map(|x| x.operation.operands_mut()).collect();
+            if let Some( x ) = block.terminator.as_mut().and_then(|x| x.operand_mut()) {
+                operands.push(x)
+            }
+            for operand in operands {
+                let replacement = resolve(*operand);
+                match replacement == *operand {
+                    true => (),
+                    _ => {
+                        *operand = replacement;
+                        count += 1
+                    }
+                }
+            }
+        }
+        count
+    }
+
+    /// Blocks dominating each block, as row of flags. Entry block and 'FINALLY' block have no dominators but themselves,
+    /// blocks not reached are dominated by all blocks.
+    fn dominators(&self) -> Vec<Vec<bool>> {
+        let count = self.blocks.len();
+        let reached = self.reachable();
+        let predecessors = self.predecessors();
+        let roots = [ Some( ENTRY_BLOCK ), self.finally ];
+        let mut dominators = vec![ vec![ true; count ]; count ];
+
+        for root in roots.iter().flatten() {
+            if *root < count {
+                dominators[*root] = vec![ false; count ];
+                dominators[*root][*root] = true
+            }
+        }
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for block in 0 .. count {
+                match reached[block] && !roots.contains(&Some( block )) {
+                    true => (),
+                    _ => continue
+                }
+
+                let mut set = vec![ true; count ];
+                for predecessor in predecessors[block].iter().filter(|x| reached[**x]) {
+                    for ( index , flag ) in set.iter_mut().enumerate() {
+                        *flag = *flag && dominators[*predecessor][index]
+                    }
+                }
+                set[block] = true;
+
+                match set == dominators[block] {
+                    true => (),
+                    _ => {
+                        dominators[block] = set;
+                        changed = true
+                    }
+                }
+            }
+        }
+
+        dominators
+    }
+
+    fn instruction_count(&self) -> usize {
+        self.blocks.iter().map(|x| x.instructions.len()).sum()
+    }
+}
+
+/// Module variable or constant data, like string literals
+#[derive(Clone, PartialEq, Debug)]
+pub struct Global {
+    pub name: String,
+    pub export: bool,
+    pub size: i64,
+    pub align: i64,
+    pub data: Option<Vec<u8>>           /* Initial contents, zero when missing */
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct IrModule {
+    pub name: String,
+    pub globals: Vec<Global>,
+    pub functions: Vec<Function>
+}
+
+pub trait IrModuleMethods {
+    fn new(name: &str) -> Self;
+    fn function(&self, name: &str) -> Option<&Function>;
+}
+
+impl IrModuleMethods for IrModule {
+    fn new(name: &str) -> Self {
+        IrModule {
+            name: String::from(name),
+            globals: Vec::new(),
+            functions: Vec::new()
+        }
+    }
+
+    fn function(&self, name: &str) -> Option<&Function> {
+        self.functions.iter().find(|x| x.name == name)
+    }
+}
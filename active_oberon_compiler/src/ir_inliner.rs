@@ -0,0 +1,307 @@
+// ActiveOberon Compiler, a native ARM v8 & X86-64 compiler & Risc V / linker / builder utility.
+// Written by Richard Magnor Stenbro. Licensed under GPL v3
+// IR inliner module, inlines calls of small procedures and marks leaf procedures that need no stack frame
+
+use std::collections::{HashMap, HashSet};
+use crate::ir::{IrModule, Function, FunctionMethods, VReg, BlockId, BasicBlock, StackSlot, Instruction, Operation, OperationMethods, Terminator,
+                TerminatorMethods, Callee, ENTRY_BLOCK};
+
+/// Caller is not grown beyond this number of instructions by inlining
+pub const INLINE_GROWTH_LIMIT : usize = 2000;
+
+/// Procedures marked inline are inlined up to this cost, whatever the threshold
+pub const INLINE_MARKED_LIMIT : usize = 400;
+
+/// Cost of inlining procedure, its instructions and the jumps between its blocks
+fn cost(function: &Function) -> usize {
+    function.instruction_count() + function.blocks.len() - 1
+}
+
+/// Procedure can be copied into callers. Nested procedures and procedures with nested procedures use the
+/// static link, 'FINALLY' needs its own frame and 'CODE' reaches variables relative to the frame pointer.
+fn inlinable(function: &Function, parents: &HashSet<&String>) -> bool {
+    function.parent.is_none() && function.finally.is_none() && !function.blocks.is_empty() && !parents.contains(&function.name) &&
+    !function.blocks.iter().flat_map(|x| x.instructions.iter()).any(|x| matches!(x.operation, Operation::Code( _ ) | Operation::OuterSlotAddress( .. )))
+}
+
+/// Replace direct calls of procedures costing at most threshold, or marked inline, by their bodies. Procedures are
+/// inlined as they were before this pass, so each run inlines one level of calls. Gives number of calls inlined.
+pub fn inline_calls(module: &mut IrModule, imported: &[Function], threshold: usize) -> usize {
+    let parents : HashSet<&String> = module.functions.iter().filter_map(|x| x.parent.as_ref()).collect();
+    let callees : HashMap<String, Function> = module.functions.iter().chain(imported.iter())
+        .filter(|x| inlinable(x, &parents) && ( cost(x) <= threshold || ( x.inline && cost(x) <= INLINE_MARKED_LIMIT ) ))
+        .map(|x| ( x.name.clone(), x.clone() )).collect();
+
+    let mut count = 0;
+    for function in module.functions.iter_mut() {
+        let mut block = 0;
+        let mut copies = HashSet::<BlockId>::new();    /* Blocks copied from callees are not searched again */
+
+        while block < function.blocks.len() {
+            if copies.contains(&block) {
+                block += 1;
+                continue
+            }
+
+            let site = function.blocks[block].instructions.iter().enumerate().find_map(|( position , instruction )| {
+                match &instruction.operation {
+                    Operation::Call( Callee::Direct( name ) , arguments ) if *name != function.name => {
+                        match callees.get(name) {
+                            Some( callee ) if callee.parameters.len() == arguments.len() &&
+                                callee.parameters.iter().zip(arguments.iter()).all(|( x , y )| callee.registers.get(*x) == function.registers.get(*y)) &&
+                                instruction.result.map(|x| function.registers.get(x) == Some( &callee.result )) != Some( false ) &&
+                                function.instruction_count() + callee.instruction_count() <= INLINE_GROWTH_LIMIT => Some( ( position, callee ) ),
+                            _ => None
+                        }
+                    },
+                    _ => None
+                }
+            });
+
+            match site {
+                Some( ( position , callee ) ) => {
+                    let first = function.blocks.len();
+                    inline_call(function, block, position, callee);
+                    /* Continuation of block comes first and is searched, the copied blocks follow it */
+                    copies.extend(first + 1 .. function.blocks.len());
+                    count += 1
+                },
+                _ => block += 1
+            }
+        }
+
+        function.remove_unreachable_blocks()
+    }
+    count
+}
+
+/// Split block after call, and jump through a copy of callee to the rest of block. Values returned meet
+/// in a phi giving the result of call.
+fn inline_call(function: &mut Function, block: BlockId, position: usize, callee: &Function) {
+    let rest = function.blocks[block].instructions.split_off(position + 1);
+    let call = function.blocks[block].instructions.pop();
+    let ( result , arguments ) = match call {
+        Some( Instruction { result , operation: Operation::Call( _ , arguments ) } ) => ( result, arguments ),
+        _ => return
+    };
+
+    /* Successors now continue from the rest of block */
+    let continuation = function.new_block();
+    let terminator = function.blocks[block].terminator.take();
+    if let Some( x ) = &terminator {
+        for successor in x.successors().iter() {
+            for instruction in function.blocks[*successor].instructions.iter_mut() {
+                if let Operation::Phi( values ) = &mut instruction.operation {
+                    for value in values.iter_mut() {
+                        if value.0 == block {
+                            value.0 = continuation
+                        }
+                    }
+                }
+            }
+        }
+    }
+    function.blocks[continuation] = BasicBlock { instructions: rest, terminator };
+
+    /* Parameters become the arguments, all other registers and slots of callee get new numbers */
+    let mut registers = Vec::<VReg>::with_capacity(callee.registers.len());
+    for ( register , ir_type ) in callee.registers.iter().enumerate() {
+        match callee.parameters.iter().position(|x| *x == register) {
+            Some( x ) => registers.push(arguments[x]),
+            _ => registers.push(function.new_register(*ir_type))
+        }
+    }
+    let slots = function.slots.len();
+    for slot in callee.slots.iter() {
+        function.slots.push( StackSlot { name: format!("{}.{}", callee.name, slot.name), size: slot.size, align: slot.align } )
+    }
+    let blocks = function.blocks.len();
+
+    let mut returns = Vec::<(BlockId, VReg)>::new();
+    for ( index , source ) in callee.blocks.iter().enumerate() {
+        let mut instructions = source.instructions.clone();
+        for instruction in instructions.iter_mut() {
+            instruction.result = instruction.result.map(|x| registers[x]);
+            for operand in instruction.operation.operands_mut() {
+                *operand = registers[*operand]
+            }
+            match &mut instruction.operation {
+                Operation::SlotAddress( x ) => *x += slots,
+                Operation::Phi( values ) => {
+                    for value in values.iter_mut() {
+                        value.0 += blocks
+                    }
+                },
+                _ => ()
+            }
+        }
+
+        let terminator = match &source.terminator {
+            Some( Terminator::Return( value ) ) => {
+                if let Some( x ) = value {
+                    returns.push(( blocks + index, registers[*x] ))
+                }
+                Some( Terminator::Jump(continuation) )
+            },
+            Some( Terminator::Jump( x ) ) => Some( Terminator::Jump(x + blocks) ),
+            Some( Terminator::Branch( c , t , f ) ) => Some( Terminator::Branch(registers[*c], t + blocks, f + blocks) ),
+            Some( Terminator::Switch( v , cases , default ) ) => {
+                Some( Terminator::Switch(registers[*v], cases.iter().map(|x| ( x.0, x.1, x.2 + blocks )).collect(), default + blocks) )
+            },
+            Some( Terminator::Trap( x ) ) => Some( Terminator::Trap(*x) ),
+            _ => None
+        };
+        function.blocks.push( BasicBlock { instructions, terminator } )
+    }
+
+    function.blocks[block].terminator = Some( Terminator::Jump(blocks + ENTRY_BLOCK) );
+    if let Some( x ) = result {
+        function.blocks[continuation].instructions.insert(0, Instruction { result: Some( x ), operation: Operation::Phi(returns) })
+    }
+}
+
+/// Mark procedures that call nothing and keep nothing on the stack as frameless. The mark is kept in the text
+/// format for code generation to leave out the prologue later, nothing reads it yet. Gives number of procedures changing mark.
+pub fn elide_frames(module: &mut IrModule) -> usize {
+    let parents : HashSet<String> = module.functions.iter().filter_map(|x| x.parent.clone()).collect();
+    let mut count = 0;
+
+    for function in module.functions.iter_mut() {
+        let leaf = !function.blocks.iter().flat_map(|x| x.instructions.iter()).any(|x| {
+            matches!(x.operation, Operation::Call( .. ) | Operation::Code( _ ) | Operation::SlotAddress( _ ) | Operation::OuterSlotAddress( .. ))
+        });
+        let frameless = leaf && function.slots.is_empty() && function.finally.is_none() && !parents.contains(&function.name);
+        match frameless == function.frameless {
+            true => (),
+            _ => {
+                function.frameless = frameless;
+                count += 1
+            }
+        }
+    }
+    count
+}
+
+// Unittests for IR inliner module
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{Parser, ParserMethods, BlockRules};
+    use crate::scanner::{Scanner, ScannerMethods};
+    use crate::type_checker::{TypeChecker, TypeCheckerMethods};
+    use crate::ir::{IrModuleMethods, Operation, Callee};
+    use std::collections::HashMap;
+    use crate::ir_lowering::lower_module;
+    use crate::ir_text::{read_module, write_function};
+    use crate::ir_verifier::verify_module;
+    use crate::ir_inliner::{inline_calls, elide_frames};
+    use crate::ir_optimizer::{PassManager, PassManagerMethods};
+
+    fn calls(module: &crate::ir::IrModule, function: &str) -> Vec<String> {
+        module.function(function).unwrap().blocks.iter().flat_map(|x| x.instructions.iter()).filter_map(|x| match &x.operation {
+            Operation::Call( Callee::Direct( name ) , _ ) => Some( name.clone() ),
+            _ => None
+        }).collect()
+    }
+
+    #[test]
+    fn inline_call_with_result() {
+        let mut module = read_module("module Test
+            function Test.Max(%0: i32, %1: i32) -> i32 {
+            b0:
+              %2: bool = cmp gt %0, %1
+              branch %2, b1, b2
+            b1:
+              return %0
+            b2:
+              return %1
+            }
+            function Test.P(%0: i32) -> i32 {
+              slot s0 k size 4 align 4
+            b0:
+              %1: i32 = iconst 7
+              %2: i32 = call Test.Max(%0, %1)
+              %3: i32 = add %2, %1
+              jump b1
+            b1:
+              %4: i32 = phi [b0: %3]
+              return %4
+            }").unwrap();
+
+        assert_eq!(inline_calls(&mut module, &[], 8), 1);
+        assert_eq!(verify_module(&module, "inline").len(), 0);
+        assert!(calls(&module, "Test.P").is_empty());
+        let text = write_function(module.function("Test.P").unwrap());
+        assert!(text.contains("%2: i32 = phi [b4: %0], [b5: %1]"));
+        assert!(text.contains("%4: i32 = phi [b2: %3]"));
+        assert!(text.contains("b0:\n  %1: i32 = iconst 7\n  jump b3\n"))
+    }
+
+    #[test]
+    fn recursion_and_large_procedures_are_not_inlined() {
+        let mut module = read_module("module Test
+            function Test.R(%0: i32) -> i32 {
+            b0:
+              %1: i32 = call Test.R(%0)
+              %2: i32 = add %1, %0
+              %3: i32 = mul %2, %2
+              %4: i32 = sub %3, %0
+              return %4
+            }
+            function Test.P(%0: i32) -> i32 {
+            b0:
+              %1: i32 = call Test.R(%0)
+              return %1
+            }").unwrap();
+
+        assert_eq!(inline_calls(&mut module, &[], 2), 0);
+        module.functions[0].inline = true;
+        assert_eq!(inline_calls(&mut module, &[], 2), 1);
+        assert_eq!(calls(&module, "Test.R"), vec![ String::from("Test.R") ]);
+        assert_eq!(calls(&module, "Test.P"), vec![ String::from("Test.R") ]);
+        assert_eq!(verify_module(&module, "inline").len(), 0)
+    }
+
+    #[test]
+    fn inline_procedure_of_other_module() {
+        let sources = [ "MODULE Lib; VAR count*: INTEGER; PROCEDURE -Add*(x: INTEGER): INTEGER; BEGIN count := count + 1; RETURN x + count END Add; END Lib.",
+                            "MODULE Test; IMPORT Lib; VAR g: INTEGER; BEGIN g := Lib.Add(2) END Test." ];
+        let mut checker = TypeChecker::new();
+        let modules : Vec<_> = sources.iter().map(|x| Parser::new(Box::new(Scanner::new(x))).parse_module().unwrap()).collect();
+        checker.check_modules(&modules.iter().map(|x| &**x).collect::<Vec<_>>());
+        let library = lower_module(&checker, &modules[0], &HashMap::new()).unwrap();
+        let mut ir = lower_module(&checker, &modules[1], &HashMap::new()).unwrap();
+        assert!(library.function("Lib.Add").unwrap().inline);
+        assert_eq!(calls(&ir, "Test.$Body"), vec![ String::from("Lib.Add") ]);
+
+        let mut manager = PassManager::new(1, true);
+        manager.import(&library);
+        manager.run(&mut ir).unwrap();
+
+        assert!(calls(&ir, "Test.$Body").is_empty());
+        assert!(ir.function("Test.$Body").unwrap().blocks.iter().flat_map(|x| x.instructions.iter())
+            .any(|x| x.operation == Operation::GlobalAddress(String::from("Lib.count"))))
+    }
+
+    #[test]
+    fn leaf_procedures_are_frameless() {
+        let mut checker = TypeChecker::new();
+        let module = Parser::new(Box::new(Scanner::new("MODULE Test; VAR g: INTEGER; PROCEDURE Get(): INTEGER; BEGIN RETURN g END Get; PROCEDURE Square(x: INTEGER): INTEGER; VAR y: INTEGER; BEGIN y := x * x; RETURN y END Square; PROCEDURE Fill(VAR a: ARRAY 4 OF INTEGER); VAR i: INTEGER; BEGIN FOR i := 0 TO 3 DO a[i] := Get() END END Fill; PROCEDURE Outer(): INTEGER; VAR k: INTEGER; PROCEDURE Inner; BEGIN k := 1 END Inner; BEGIN Inner; RETURN k END Outer; END Test."))).parse_module().unwrap();
+        checker.check_modules(&[ &*module ]);
+        let mut ir = lower_module(&checker, &module, &HashMap::new()).unwrap();
+
+        elide_frames(&mut ir);
+        assert!(!ir.function("Test.Square").unwrap().frameless);
+
+        let mut manager = PassManager::new(2, true);
+        manager.run(&mut ir).unwrap();
+
+        assert!(ir.function("Test.Get").unwrap().frameless);
+        assert!(ir.function("Test.Square").unwrap().frameless);
+        assert!(ir.function("Test.Square").unwrap().slots.is_empty());
+        assert!(ir.function("Test.Fill").unwrap().frameless);
+        assert!(!ir.function("Test.Outer").unwrap().frameless);
+        assert!(!ir.function("Test.Outer.Inner").unwrap().frameless);
+        assert!(ir.function("Test.Outer").unwrap().slots.iter().any(|x| x.name == "k"))
+    }
+}
@@ -0,0 +1,2229 @@
+// ActiveOberon Compiler, a native ARM v8 & X86-64 compiler & Risc V / linker / builder utility.
+// Written by Richard Magnor Stenbro. Licensed under GPL v3
+// Lowering module, translates type checked syntax tree of module into target independent intermediate representation
+
+use std::collections::HashMap;
+use crate::diagnostics::{Diagnostic, DiagnosticMethods, E_LOWERING};
+use crate::parser::Node;
+use crate::scanner::Symbols;
+use crate::symbol_table::{ScopeId, EntryId, ScopeKind, SymbolKind, Export, SymbolTableMethods, identifier_definition, UNIVERSE_SCOPE};
+use crate::resolver::ResolverMethods;
+use crate::type_checker::{TypeChecker, TypeCheckerMethods, Type, TypeId, ParameterMode, expression_span, expression_list,
+                          T_UNKNOWN, T_NOTYPE, T_BOOLEAN, T_CHAR8, T_SIGNED16, T_SIGNED32, T_SIGNED64, T_SET, T_SIZE};
+use crate::const_evaluator::{ConstValue, literal};
+use crate::ir::{IrModule, IrModuleMethods, Function, FunctionMethods, Global, IrType, IrTypeMethods, VReg, BlockId, SlotId,
+                Instruction, Operation, Terminator, Constant, Callee, UnaryOp, BinaryOp, CompareOp,
+                TRAP_WITH, TRAP_CASE, TRAP_RETURN, TRAP_TYPE_GUARD, TRAP_INDEX, TRAP_ASSERT};
+
+/// Where parameter or local variable of procedure is kept
+#[derive(Clone, PartialEq, Debug)]
+enum Storage {
+    Value(SlotId),              /* Variable in stack slot */
+    Reference(SlotId),          /* Slot holds address of variable, for 'VAR' and structured parameters */
+    OpenArray(SlotId, SlotId)   /* Slots holding address of first element and number of elements */
+}
+
+/// What designator denotes after its selectors are applied
+#[derive(Clone, PartialEq, Debug)]
+enum Place {
+    Variable(VReg, TypeId, Option<VReg>),       /* Address of variable, with number of elements of open arrays */
+    Value(VReg, TypeId),                        /* Scalar value, or address of structured value */
+    Constant(ConstValue, TypeId),
+    Procedure(Callee, TypeId, Option<VReg>),    /* Procedure with its type and receiver of methods */
+    Builtin(String),
+    Type(TypeId, Option<EntryId>),              /* Type with its declaration when named */
+    Module(String),                             /* Imported module with its name */
+    NoValue                                     /* Call of proper procedure */
+}
+
+/// Function being lowered, functions of enclosing procedures stay below it on the stack
+struct Frame {
+    function: Function,
+    storage: HashMap<EntryId, Storage>,
+    receiver: Option<SlotId>,       /* Slot holding 'SELF' of methods and object bodies */
+    result: Option<SlotId>,
+    current: BlockId,
+    exits: Vec<BlockId>,            /* Blocks following enclosing 'LOOP' statements */
+    leave: BlockId                  /* Continued in by 'RETURN', start of 'FINALLY' part when there is one */
+}
+
+struct Lowering<'a> {
+    checker: &'a TypeChecker,
+    module: ScopeId,
+    name: String,
+    ir: IrModule,
+    frames: Vec<Frame>,
+    strings: HashMap<String, String>,   /* Global holding each string literal */
+    guards: HashMap<EntryId, TypeId>,   /* Variables narrowed to guard type by enclosing 'WITH' */
+    code: &'a HashMap<u32, Box<Vec<u8>>>,  /* Machine code of 'CODE' blocks assembled after type check, by start of assembler text */
+    errors: Vec<Box<Diagnostic>>
+}
+
+/// Intermediate representation of module checked without errors, with a function for every procedure,
+/// method and body. Constructs the back ends cannot handle yet are reported. Machine code of 'CODE' blocks assembled
+/// after type check, keyed by start of assembler text, replaces the code left empty by parser.
+pub fn lower_module(checker: &TypeChecker, module: &Node, code: &HashMap<u32, Box<Vec<u8>>>) -> Result<IrModule, Vec<Box<Diagnostic>>> {
+    let ( id , decl , body ) = match module {
+        Node::Module( _ , _ , _ , _ , id , _ , _ , _ , decl , body , _ , _ , _ ) => ( id, decl, body ),
+        _ => return Err( vec![ Diagnostic::error(E_LOWERING, String::from("Expecting module"), 0, 0) ] )
+    };
+
+    let ( name , scope ) = match identifier_definition(id) {
+        Some( ( name , _ , s , e ) ) => {
+            match checker.get_resolver().get_module_scope(name.as_str()) {
+                Some( x ) => ( name, x ),
+                _ => return Err( vec![ Diagnostic::error(E_LOWERING, format!("Module '{}' is not type checked", name), s, e) ] )
+            }
+        },
+        _ => return Err( vec![ Diagnostic::error(E_LOWERING, String::from("Expecting module name"), 0, 0) ] )
+    };
+
+    let mut lowering = Lowering {
+        checker,
+        module: scope,
+        name: name.clone(),
+        ir: IrModule::new(name.as_str()),
+        frames: Vec::new(),
+        strings: HashMap::new(),
+        guards: HashMap::new(),
+        code,
+        errors: Vec::new()
+    };
+
+    let table = checker.get_resolver().get_symbol_table();
+    for entry in table.scope(scope).entries.iter() {
+        let symbol = table.entry(*entry);
+        if symbol.kind == SymbolKind::Variable {
+            let type_id = checker.get_entry_type(*entry).unwrap_or(T_UNKNOWN);
+            match checker.type_size(type_id) {
+                Some( size ) => lowering.ir.globals.push( Global {
+                    name: format!("{}.{}", name, symbol.name),
+                    export: symbol.export != Export::None,
+                    size,
+                    align: size.clamp(1, 8),
+                    data: None
+                } ),
+                _ => lowering.unsupported(format!("Variable '{}' without known size", symbol.name).as_str(), symbol.start, symbol.end)
+            }
+        }
+    }
+
+    if let Some( x ) = decl {
+        lowering.declarations(x)
+    }
+
+    lowering.function(format!("{}.$Body", name).as_str(), scope, T_NOTYPE, true, body.as_deref(), None);
+
+    match lowering.errors.is_empty() {
+        true => Ok( lowering.ir ),
+        _ => Err( lowering.errors )
+    }
+}
+
+/// Procedures, methods and object bodies of declaration sequence
+impl<'a> Lowering<'a> {
+    fn declarations(&mut self, declarations: &Node) {
+        if let Node::DeclarationSequence( _ , _ , _ , types , _ , procedures , operators , _ , _ ) = declarations {
+            for type_declaration in types.iter() {
+                if let Node::TypeDeclaration( _ , _ , _ , nodes ) = &**type_declaration {
+                    for node in nodes.iter() {
+                        if let Node::TypeDeclarationElement( _ , _ , _ , _ , type_node , _ ) = &**node {
+                            self.type_procedures(type_node)
+                        }
+                    }
+                }
+            }
+
+            for procedure in procedures.iter() {
+                self.procedure(procedure)
+            }
+
+            for operator in operators.iter() {
+                self.procedure(operator)
+            }
+        }
+    }
+
+    /// Methods of records and objects, and bodies of objects
+    fn type_procedures(&mut self, type_node: &Node) {
+        match type_node {
+            Node::RecordType( _ , _ , _ , _ , _ , Some( ( nodes , _ ) ) , _ ) => {
+                for node in nodes.iter() {
+                    self.procedure(node)
+                }
+            },
+            Node::ObjectType( s , _ , _ , _ , _ , _ , decl , body , _ , _ ) => {
+                let scope = match self.checker.get_resolver().get_node_scope(self.module, *s) {
+                    Some( x ) => x,
+                    _ => return
+                };
+
+                if let Some( x ) = decl {
+                    self.declarations(x)
+                }
+
+                if let Some( x ) = body {
+                    let name = format!("{}.$Body", self.scope_path(scope));
+                    self.function(name.as_str(), scope, T_NOTYPE, false, Some( &**x ), None)
+                }
+            },
+            Node::PointerType( _ , _ , _ , _ , _ , target ) => self.type_procedures(target),
+            _ => ()
+        }
+    }
+
+    fn procedure(&mut self, procedure: &Node) {
+        /* Marked with '-' after 'PROCEDURE' or 'OPERATOR' to be inlined */
+        let ( start , name , inline , parameters , decl , body ) = match procedure {
+            Node::Procedure( s , _ , _ , marker , _ , id , parameters , _ , decl , body , _ , _ ) => {
+                let inline = matches!(marker, Some( ( _ , Some( x ) ) ) if matches!(**x, Symbols::Minus( .. )));
+                ( *s, identifier_definition(id), inline, parameters.as_deref(), decl.as_deref(), body.as_deref() )
+            },
+            Node::Operator( s , _ , _ , _ , marker , operator , _ , parameters , _ , decl , body , _ , _ ) => {
+                let name = match &**operator {
+                    Node::String( s , e , symbol ) => {
+                        match &**symbol {
+                            Symbols::String( _ , _ , text ) => Some( ( text.to_string(), Export::None, *s, *e ) ),
+                            _ => None
+                        }
+                    },
+                    _ => None
+                };
+                ( *s, name, marker.is_some(), Some( &**parameters ), decl.as_deref(), body.as_deref() )
+            },
+            _ => return
+        };
+
+        let checker = self.checker;
+        let table = checker.get_resolver().get_symbol_table();
+        let ( scope , ( name , export , _ , _ ) ) = match ( checker.get_resolver().get_node_scope(self.module, start) , name ) {
+            ( Some( x ) , Some( y ) ) => ( x, y ),
+            _ => return
+        };
+
+        /* Procedures bound by receiver and operators do not name their scope */
+        let parent = table.scope(scope).parent.unwrap_or(UNIVERSE_SCOPE);
+        let qualified = match table.scope(scope).name.is_empty() {
+            true => format!("{}.{}", self.scope_path(parent), name),
+            _ => self.scope_path(scope)
+        };
+
+        let result = match parameters {
+            Some( Node::FormalParameters( _ , _ , _ , _ , _ , _ , Some( ( _ , _ , x ) ) ) ) => self.type_of(x),
+            _ => T_NOTYPE
+        };
+
+        self.function(qualified.as_str(), scope, result, export != Export::None, body, decl);
+        if let ( true , Some( x ) ) = ( inline , self.ir.functions.iter_mut().find(|x| x.name == qualified) ) {
+            x.inline = true
+        }
+    }
+
+    /// Function for procedure or body with scope holding its parameters and local variables. Procedures
+    /// declared inside are lowered while the function is on the stack, so they can reach its variables.
+    /// Methods and object bodies get 'SELF' as first parameter.
+    fn function(&mut self, name: &str, scope: ScopeId, result: TypeId, export: bool, body: Option<&Node>, decl: Option<&Node>) {
+        let checker = self.checker;
+        let table = checker.get_resolver().get_symbol_table();
+        let parent = table.scope(scope).parent.unwrap_or(UNIVERSE_SCOPE);
+        let method = matches!(table.scope(scope).kind, ScopeKind::Object) || matches!(table.scope(parent).kind, ScopeKind::Record | ScopeKind::Object);
+
+        let result_type = match result {
+            T_NOTYPE => IrType::Void,
+            x => {
+                match self.ir_type(x) {
+                    Some( y ) => y,
+                    _ => {
+                        let ( s , e ) = self.body_span(body);
+                        self.unsupported(format!("Result of type '{}'", checker.type_name(x)).as_str(), s, e);
+                        IrType::Void
+                    }
+                }
+            }
+        };
+
+        let mut function = Function::new(name, result_type);
+        function.export = export;
+        function.parent = self.frames.last().map(|x| x.function.name.clone());
+        let entry = function.new_block();
+        let epilogue = function.new_block();
+
+        self.frames.push( Frame {
+            function,
+            storage: HashMap::new(),
+            receiver: None,
+            result: None,
+            current: entry,
+            exits: Vec::new(),
+            leave: epilogue
+        } );
+
+        if method {
+            let receiver = self.parameter(IrType::Ptr);
+            let slot = self.frame().function.new_slot("SELF", 8, 8);
+            self.store_slot(slot, receiver);
+            self.frame().receiver = Some( slot )
+        }
+
+        match table.scope(scope).kind {
+            ScopeKind::Procedure | ScopeKind::Operator => {
+                for entry in table.scope(scope).entries.clone().iter() {
+                    let symbol = table.entry(*entry);
+                    match symbol.kind {
+                        SymbolKind::Parameter => self.declare_parameter(*entry),
+                        SymbolKind::Variable => {
+                            let type_id = checker.get_entry_type(*entry).unwrap_or(T_UNKNOWN);
+                            match checker.type_size(type_id) {
+                                Some( size ) => {
+                                    let slot = self.frame().function.new_slot(symbol.name.as_str(), size, size.clamp(1, 8));
+                                    self.frame().storage.insert(*entry, Storage::Value(slot));
+                                },
+                                _ => self.unsupported(format!("Variable '{}' without known size", symbol.name).as_str(), symbol.start, symbol.end)
+                            }
+                        },
+                        _ => ()
+                    }
+                }
+            },
+            _ => ()
+        }
+
+        match result_type {
+            IrType::Void => (),
+            x => {
+                let slot = self.frame().function.new_slot("RESULT", x.size(), x.size());
+                self.frame().result = Some( slot )
+            }
+        }
+
+        match body {
+            Some( Node::Body( _ , _ , _ , _ , statements , finally ) ) => {
+                let finally_block = match finally {
+                    Some( _ ) => {
+                        let block = self.frame().function.new_block();
+                        self.frame().function.finally = Some( block );
+                        self.frame().leave = block;
+                        Some( block )
+                    },
+                    _ => None
+                };
+
+                self.statement(statements);
+
+                /* Function procedures must be left by 'RETURN' */
+                match result_type {
+                    IrType::Void => {
+                        let leave = self.frame().leave;
+                        self.jump(leave)
+                    },
+                    _ => self.terminate(Terminator::Trap(TRAP_RETURN))
+                }
+
+                if let ( Some( ( _ , x ) ) , Some( block ) ) = ( finally , finally_block ) {
+                    self.frame().leave = epilogue;
+                    self.start(block);
+                    self.statement(x);
+                    self.jump(epilogue)
+                }
+            },
+            Some( Node::BodyCode( _ , _ , _ , block ) ) => {
+                if let Node::CodeBlock( s , _ , code ) = &**block {
+                    let code = self.code.get(s).unwrap_or(code);
+                    self.emit_void(Operation::Code(code.to_vec()));
+                }
+                self.jump(epilogue)
+            },
+            _ => self.jump(epilogue)
+        }
+
+        self.start(epilogue);
+        let value = self.frame().result.map(|slot| self.load_slot(slot, result_type));
+        self.terminate(Terminator::Return(value));
+
+        /* Nested procedures follow their enclosing procedure */
+        let index = self.ir.functions.len();
+        if let Some( x ) = decl {
+            self.declarations(x)
+        }
+
+        if let Some( mut frame ) = self.frames.pop() {
+            frame.function.remove_unreachable_blocks();
+            self.ir.functions.insert(index, frame.function)
+        }
+    }
+
+    /// Parameter is passed in virtual registers and kept in stack slot, so that it can be assigned and its address taken.
+    /// Open arrays are passed as address and number of elements, 'VAR' and structured parameters by address.
+    fn declare_parameter(&mut self, entry: EntryId) {
+        let checker = self.checker;
+        let symbol = checker.get_resolver().get_symbol_table().entry(entry);
+        let name = symbol.name.as_str();
+        let type_id = checker.get_entry_type(entry).unwrap_or(T_UNKNOWN);
+        let mode = checker.get_parameter_mode(entry).unwrap_or(ParameterMode::Value);
+
+        let storage = match ( self.open_array(type_id) , &mode , self.ir_type(type_id) ) {
+            ( Some( element ) , _ , _ ) => {
+                if self.open_array(element).is_some() {
+                    self.unsupported("Open array with more than one open dimension", symbol.start, symbol.end);
+                }
+                let address = self.parameter(IrType::Ptr);
+                let length = self.parameter(IrType::I64);
+                let address_slot = self.frame().function.new_slot(name, 8, 8);
+                let length_slot = self.frame().function.new_slot(format!("{}$Length", name).as_str(), 8, 8);
+                self.store_slot(address_slot, address);
+                self.store_slot(length_slot, length);
+                Storage::OpenArray(address_slot, length_slot)
+            },
+            ( _ , ParameterMode::Var , _ ) | ( _ , ParameterMode::Const , None ) => {
+                let address = self.parameter(IrType::Ptr);
+                let slot = self.frame().function.new_slot(name, 8, 8);
+                self.store_slot(slot, address);
+                Storage::Reference(slot)
+            },
+            ( _ , _ , Some( x ) ) => {
+                let value = self.parameter(x);
+                let slot = self.frame().function.new_slot(name, x.size(), x.size());
+                self.store_slot(slot, value);
+                Storage::Value(slot)
+            },
+            _ => {
+                /* Structured value parameter is copied, so that assignments do not change argument */
+                let address = self.parameter(IrType::Ptr);
+                match checker.type_size(type_id) {
+                    Some( size ) => {
+                        let slot = self.frame().function.new_slot(name, size, size.clamp(1, 8));
+                        let target = self.emit(IrType::Ptr, Operation::SlotAddress(slot));
+                        self.emit_void(Operation::CopyMemory(target, address, size));
+                        Storage::Value(slot)
+                    },
+                    _ => {
+                        self.unsupported(format!("Parameter '{}' without known size", name).as_str(), symbol.start, symbol.end);
+                        Storage::Value(0)
+                    }
+                }
+            }
+        };
+
+        self.frame().storage.insert(entry, storage);
+    }
+
+    fn parameter(&mut self, ir_type: IrType) -> VReg {
+        let register = self.frame().function.new_register(ir_type);
+        self.frame().function.parameters.push(register);
+        register
+    }
+
+    fn body_span(&self, body: Option<&Node>) -> (u32, u32) {
+        match body {
+            Some( Node::Body( s , e , .. ) ) | Some( Node::BodyCode( s , e , .. ) ) => ( *s, *e ),
+            _ => ( 0, 0 )
+        }
+    }
+}
+
+/// Statements
+impl<'a> Lowering<'a> {
+    fn statement(&mut self, statement: &Node) {
+        match statement {
+            Node::StatementSequence( _ , _ , nodes , _ ) => {
+                for node in nodes.iter() {
+                    self.statement(node)
+                }
+            },
+            Node::StatementBlock( _ , _ , _ , _ , x , _ ) => self.statement(x),
+            Node::If( _ , _ , _ , condition , _ , x , elsif_nodes , else_node , _ ) => {
+                let end = self.new_block();
+                let mut next = self.new_block();
+                let then = self.new_block();
+                self.condition(condition, then, next);
+                self.start(then);
+                self.statement(x);
+                self.jump(end);
+
+                if let Some( nodes ) = elsif_nodes {
+                    for node in nodes.iter() {
+                        if let Node::Elsif( _ , _ , _ , condition , _ , x ) = &**node {
+                            self.start(next);
+                            next = self.new_block();
+                            let then = self.new_block();
+                            self.condition(condition, then, next);
+                            self.start(then);
+                            self.statement(x);
+                            self.jump(end)
+                        }
+                    }
+                }
+
+                self.start(next);
+                if let Some( node ) = else_node {
+                    self.statement(node)
+                }
+                self.jump(end);
+                self.start(end)
+            },
+            Node::Else( _ , _ , _ , x ) => self.statement(x),
+            Node::While( _ , _ , _ , condition , _ , x , _ ) => {
+                let header = self.new_block();
+                let body = self.new_block();
+                let exit = self.new_block();
+                self.jump(header);
+                self.start(header);
+                self.condition(condition, body, exit);
+                self.start(body);
+                self.statement(x);
+                self.jump(header);
+                self.start(exit)
+            },
+            Node::Repeat( _ , _ , _ , x , _ , condition ) => {
+                let body = self.new_block();
+                let exit = self.new_block();
+                self.jump(body);
+                self.start(body);
+                self.statement(x);
+                self.condition(condition, exit, body);
+                self.start(exit)
+            },
+            Node::Loop( _ , _ , _ , x , _ ) => {
+                let body = self.new_block();
+                let exit = self.new_block();
+                self.jump(body);
+                self.start(body);
+                self.frame().exits.push(exit);
+                self.statement(x);
+                self.frame().exits.pop();
+                self.jump(body);
+                self.start(exit)
+            },
+            Node::Exit( s , e , _ ) => {
+                match self.frame().exits.last().copied() {
+                    Some( x ) => self.jump(x),
+                    _ => self.unsupported("'EXIT' outside of 'LOOP'", *s, *e)
+                }
+            },
+            Node::For( _ , _ , _ , variable , _ , from , _ , to , by , _ , x , _ ) => self.for_statement(variable, from, to, by, x),
+            Node::Case( _ , _ , _ , selector , _ , nodes , else_node , _ ) => self.case_statement(selector, nodes, else_node),
+            Node::With( _ , _ , _ , designator , _ , nodes , else_node , _ ) => self.with_statement(designator, nodes, else_node),
+            Node::Return( s , e , _ , value ) => {
+                match ( value , self.frame().result ) {
+                    ( Some( x ) , Some( slot ) ) => {
+                        let result_type = self.frame().function.result;
+                        let value = self.expression_as(x, result_type);
+                        self.store_slot(slot, value)
+                    },
+                    ( None , None ) => (),
+                    _ => self.unsupported("'RETURN' not matching procedure", *s, *e)
+                }
+                let leave = self.frame().leave;
+                self.jump(leave)
+            },
+            Node::Code( _ , _ , symbol , code , _ ) => {
+                let code = match &**symbol {
+                    Symbols::Code( _ , e ) => self.code.get(e).unwrap_or(code),
+                    _ => code
+                };
+                self.emit_void(Operation::Code(code.to_vec()));
+            },
+            Node::Ignore( _ , _ , _ , x ) => {
+                self.expression(x);
+            },
+            Node::BecomesStatement( _ , _ , left , _ , right ) => self.assignment(left, right),
+            Node::Await( s , e , .. ) => self.unsupported("'AWAIT'", *s, *e),
+            Node::ExclaimMarkStatement( s , e , .. ) |
+            Node::QuestionmarkStatement( s , e , .. ) |
+            Node::LessLessStatement( s , e , .. ) |
+            Node::GreaterGreaterStatement( s , e , .. ) => self.unsupported("Port statement", *s, *e),
+            Node::Empty |
+            Node::Error( .. ) => (),
+            _ => {
+                /* Procedure call, procedure names without parameter list are called as well */
+                let ( s , e ) = expression_span(statement);
+                match self.designator(statement) {
+                    Place::Procedure( callee , type_id , receiver ) => {
+                        self.call(callee, type_id, receiver, &Vec::new(), s, e);
+                    },
+                    Place::Builtin( name ) => {
+                        self.builtin(name.as_str(), &Vec::new(), T_NOTYPE, s, e);
+                    },
+                    _ => ()
+                }
+            }
+        }
+    }
+
+    fn assignment(&mut self, left: &Node, right: &Node) {
+        let ( s , e ) = expression_span(left);
+
+        match self.designator(left) {
+            Place::Variable( address , type_id , _ ) => {
+                match self.ir_type(type_id) {
+                    Some( x ) => {
+                        let value = self.expression_as(right, x);
+                        self.emit_void(Operation::Store(address, value));
+                    },
+                    _ => {
+                        let ( source , size ) = match self.string_operand(right) {
+                            Some( x ) => x,
+                            _ => {
+                                let source = self.expression(right);
+                                let size = self.checker.type_size(self.node_type(right)).or(self.checker.type_size(type_id));
+                                match size {
+                                    Some( x ) => ( source, x ),
+                                    _ => {
+                                        self.unsupported("Assignment of value without known size", s, e);
+                                        return
+                                    }
+                                }
+                            }
+                        };
+                        self.emit_void(Operation::CopyMemory(address, source, size));
+                    }
+                }
+            },
+            _ => self.unsupported("Assignment to designator that is not a variable", s, e)
+        }
+    }
+
+    /// Limit is evaluated once before the loop, step is a constant deciding direction of the test
+    fn for_statement(&mut self, variable: &Node, from: &Node, to: &Node, by: &Option<(Box<Symbols>, Box<Node>)>, statements: &Node) {
+        let ( s , e ) = expression_span(variable);
+        let ( address , ir_type ) = match self.designator(variable) {
+            Place::Variable( address , type_id , _ ) => ( address, self.ir_type(type_id).unwrap_or(IrType::I64) ),
+            _ => {
+                self.unsupported("Control variable of 'FOR' that is not a variable", s, e);
+                return
+            }
+        };
+
+        let step = match by {
+            Some( ( _ , x ) ) => {
+                match self.constant_integer(x) {
+                    Some( y ) if y != 0 => y,
+                    _ => {
+                        let ( s , e ) = expression_span(x);
+                        self.unsupported("Step of 'FOR' that is not a constant other than zero", s, e);
+                        1
+                    }
+                }
+            },
+            _ => 1
+        };
+
+        let first = self.expression_as(from, ir_type);
+        let limit = self.expression_as(to, ir_type);
+        self.emit_void(Operation::Store(address, first));
+
+        let header = self.new_block();
+        let body = self.new_block();
+        let exit = self.new_block();
+        self.jump(header);
+
+        self.start(header);
+        let value = self.emit(ir_type, Operation::Load(address));
+        let operator = match step > 0 {
+            true => CompareOp::Le,
+            _ => CompareOp::Ge
+        };
+        let test = self.emit(IrType::Bool, Operation::Compare(operator, value, limit));
+        self.terminate(Terminator::Branch(test, body, exit));
+
+        self.start(body);
+        self.statement(statements);
+        let value = self.emit(ir_type, Operation::Load(address));
+        let increment = self.constant(ir_type, step);
+        let next = self.emit(ir_type, Operation::Binary(BinaryOp::Add, value, increment));
+        self.emit_void(Operation::Store(address, next));
+        self.jump(header);
+
+        self.start(exit)
+    }
+
+    /// Selector is compared against ranges of constant labels, without 'ELSE' a value not found traps
+    fn case_statement(&mut self, selector: &Node, nodes: &[Box<Node>], else_node: &Option<Box<Node>>) {
+        let value = self.expression(selector);
+        let end = self.new_block();
+        let mut cases = Vec::<(i64, i64, BlockId)>::new();
+        let mut bodies = Vec::<(BlockId, &Node)>::new();
+
+        for node in nodes.iter() {
+            if let Node::CaseElement( _ , _ , _ , labels , _ , _ , x ) = &**node {
+                let block = self.new_block();
+                for label in labels.iter() {
+                    let bounds = match &**label {
+                        Node::Range( _ , _ , Some( from ) , _ , Some( to ) , _ , _ ) => ( self.constant_integer(from), self.constant_integer(to) ),
+                        Node::Range( .. ) => ( None, None ),
+                        x => {
+                            let value = self.constant_integer(x);
+                            ( value, value )
+                        }
+                    };
+                    match bounds {
+                        ( Some( low ) , Some( high ) ) => cases.push(( low, high, block )),
+                        _ => {
+                            let ( s , e ) = expression_span(label);
+                            self.unsupported("Case label that is not a constant", s, e)
+                        }
+                    }
+                }
+                bodies.push(( block, &**x ))
+            }
+        }
+
+        let default = self.new_block();
+        self.terminate(Terminator::Switch(value, cases, default));
+
+        for ( block , statements ) in bodies.iter() {
+            self.start(*block);
+            self.statement(statements);
+            self.jump(end)
+        }
+
+        self.start(default);
+        match else_node {
+            Some( x ) => {
+                self.statement(x);
+                self.jump(end)
+            },
+            _ => self.terminate(Terminator::Trap(TRAP_CASE))
+        }
+
+        self.start(end)
+    }
+
+    /// Dynamic type of designator is tested against each guard in order, without 'ELSE' no matching guard traps.
+    /// Variable named by designator has the guard type inside the statements of the guard.
+    fn with_statement(&mut self, designator: &Node, nodes: &[Box<Node>], else_node: &Option<Box<Node>>) {
+        let reference = self.reference(designator);
+        let end = self.new_block();
+        let variable = match designator {
+            Node::Ident( s , .. ) => self.checker.get_resolver().get_binding(self.module, *s),
+            _ => None
+        };
+
+        for node in nodes.iter() {
+            if let Node::WithElement( _ , _ , _ , guard , _ , x ) = &**node {
+                let name = self.type_descriptor_name(guard);
+                let test = self.emit(IrType::Bool, Operation::TypeTest(reference, name));
+                let body = self.new_block();
+                let next = self.new_block();
+                self.terminate(Terminator::Branch(test, body, next));
+                self.start(body);
+                match variable {
+                    Some( entry ) => {
+                        let guard_type = self.type_of(guard);
+                        let previous = self.guards.insert(entry, guard_type);
+                        self.statement(x);
+                        match previous {
+                            Some( type_id ) => self.guards.insert(entry, type_id),
+                            _ => self.guards.remove(&entry)
+                        };
+                    },
+                    _ => self.statement(x)
+                }
+                self.jump(end);
+                self.start(next)
+            }
+        }
+
+        match else_node {
+            Some( x ) => {
+                self.statement(x);
+                self.jump(end)
+            },
+            _ => self.terminate(Terminator::Trap(TRAP_WITH))
+        }
+
+        self.start(end)
+    }
+
+    /// Branch to one of two blocks by condition, '&' and 'OR' only evaluate right operand when needed
+    fn condition(&mut self, condition: &Node, when_true: BlockId, when_false: BlockId) {
+        match condition {
+            Node::And( _ , _ , left , _ , right ) => {
+                let middle = self.new_block();
+                self.condition(left, middle, when_false);
+                self.start(middle);
+                self.condition(right, when_true, when_false)
+            },
+            Node::Or( _ , _ , left , _ , right ) => {
+                let middle = self.new_block();
+                self.condition(left, when_true, middle);
+                self.start(middle);
+                self.condition(right, when_true, when_false)
+            },
+            Node::UnaryNot( _ , _ , _ , x ) => self.condition(x, when_false, when_true),
+            Node::ParenthesisExpression( _ , _ , _ , x , _ ) => self.condition(x, when_true, when_false),
+            _ => {
+                let value = self.expression_as(condition, IrType::Bool);
+                self.terminate(Terminator::Branch(value, when_true, when_false))
+            }
+        }
+    }
+}
+
+/// Expressions
+impl<'a> Lowering<'a> {
+    /// Value of expression in virtual register, structured values give their address
+    fn expression(&mut self, node: &Node) -> VReg {
+        let type_id = self.node_type(node);
+
+        match node {
+            Node::Integer( s , e , .. ) |
+            Node::Real( s , e , .. ) |
+            Node::Character( s , e , .. ) |
+            Node::String( s , e , .. ) |
+            Node::Nil( s , e , .. ) |
+            Node::True( s , e , .. ) |
+            Node::False( s , e , .. ) => {
+                match literal(node) {
+                    Ok( x ) => self.constant_value(&x, type_id),
+                    _ => self.invalid("Literal", *s, *e)
+                }
+            },
+            Node::Ident( .. ) |
+            Node::QualifiedIdentifier( .. ) |
+            Node::UnaryExpression( .. ) |
+            Node::Self_( .. ) => {
+                let ( s , e ) = expression_span(node);
+                let place = self.designator(node);
+                self.place_value(place, s, e)
+            },
+            Node::ParenthesisExpression( _ , _ , _ , x , _ ) |
+            Node::UnaryPlus( _ , _ , _ , x ) => self.expression(x),
+            Node::UnaryMinus( _ , _ , _ , x ) => {
+                let ir_type = self.scalar_type(type_id);
+                let value = self.expression_as(x, ir_type);
+                let operator = match self.base(type_id) {
+                    T_SET => UnaryOp::Not,
+                    _ => UnaryOp::Neg
+                };
+                self.emit(ir_type, Operation::Unary(operator, value))
+            },
+            Node::UnaryNot( _ , _ , _ , x ) => {
+                let value = self.expression_as(x, IrType::Bool);
+                self.emit(IrType::Bool, Operation::Unary(UnaryOp::Not, value))
+            },
+            Node::Plus( _ , _ , l , _ , r ) => self.arithmetic(BinaryOp::Add, type_id, l, r),
+            Node::Minus( _ , _ , l , _ , r ) => {
+                match self.base(type_id) {
+                    T_SET => {
+                        let left = self.expression_as(l, IrType::U32);
+                        let right = self.expression_as(r, IrType::U32);
+                        let complement = self.emit(IrType::U32, Operation::Unary(UnaryOp::Not, right));
+                        self.emit(IrType::U32, Operation::Binary(BinaryOp::And, left, complement))
+                    },
+                    _ => self.arithmetic(BinaryOp::Sub, type_id, l, r)
+                }
+            },
+            Node::Times( _ , _ , l , _ , r ) => self.arithmetic(BinaryOp::Mul, type_id, l, r),
+            Node::Slash( _ , _ , l , _ , r ) => self.arithmetic(BinaryOp::Div, type_id, l, r),
+            Node::Div( _ , _ , l , _ , r ) => self.arithmetic(BinaryOp::Div, type_id, l, r),
+            Node::Mod( _ , _ , l , _ , r ) => self.arithmetic(BinaryOp::Mod, type_id, l, r),
+            Node::And( .. ) |
+            Node::Or( .. ) => {
+                /* Value of short circuit evaluation is merged by phi */
+                let when_true = self.new_block();
+                let when_false = self.new_block();
+                let end = self.new_block();
+                self.condition(node, when_true, when_false);
+                self.start(when_true);
+                let yes = self.constant(IrType::Bool, 1);
+                self.jump(end);
+                self.start(when_false);
+                let no = self.constant(IrType::Bool, 0);
+                self.jump(end);
+                self.start(end);
+                self.emit(IrType::Bool, Operation::Phi(vec![ ( when_true, yes ), ( when_false, no ) ]))
+            },
+            Node::Equal( _ , _ , l , _ , r ) => self.relation(CompareOp::Eq, l, r),
+            Node::NotEqual( _ , _ , l , _ , r ) => self.relation(CompareOp::Ne, l, r),
+            Node::Less( _ , _ , l , _ , r ) => self.relation(CompareOp::Lt, l, r),
+            Node::LessEqual( _ , _ , l , _ , r ) => self.relation(CompareOp::Le, l, r),
+            Node::Greater( _ , _ , l , _ , r ) => self.relation(CompareOp::Gt, l, r),
+            Node::GreaterEqual( _ , _ , l , _ , r ) => self.relation(CompareOp::Ge, l, r),
+            Node::In( _ , _ , l , _ , r ) => {
+                let element = self.expression_as(l, IrType::I64);
+                let set = self.expression_as(r, IrType::U32);
+                let one = self.constant(IrType::U32, 1);
+                let bit = self.emit(IrType::U32, Operation::Binary(BinaryOp::Lsh, one, element));
+                let masked = self.emit(IrType::U32, Operation::Binary(BinaryOp::And, set, bit));
+                let zero = self.constant(IrType::U32, 0);
+                self.emit(IrType::Bool, Operation::Compare(CompareOp::Ne, masked, zero))
+            },
+            Node::Is( _ , _ , l , _ , r ) => {
+                let reference = self.reference(l);
+                let name = self.type_descriptor_name(r);
+                self.emit(IrType::Bool, Operation::TypeTest(reference, name))
+            },
+            Node::Set( _ , _ , _ , elements , _ , _ ) => self.set(elements),
+            Node::Address( s , e , _ , of ) => {
+                match of {
+                    Some( x ) => {
+                        match self.designator(&x.1) {
+                            Place::Variable( address , _ , _ ) => self.emit(IrType::U64, Operation::Convert(address)),
+                            _ => self.invalid("'ADDRESS OF' needs a variable, it", *s, *e)
+                        }
+                    },
+                    _ => self.invalid("Type 'ADDRESS' as value, it", *s, *e)
+                }
+            },
+            Node::Size( s , e , _ , of ) => {
+                match of.as_ref().and_then(|x| self.checker.type_size(self.node_type(&x.1))) {
+                    Some( x ) => self.constant(IrType::I64, x),
+                    _ => self.invalid("'SIZE OF' of value without known size, it", *s, *e)
+                }
+            },
+            Node::New( s , e , _ , type_node , _ , arguments , _ ) => {
+                match ( expression_list(&Some( arguments.clone() )).is_empty() , self.checker.instance_size(type_id) ) {
+                    ( true , Some( size ) ) => {
+                        let name = self.type_descriptor_name(type_node);
+                        let descriptor = self.emit(IrType::Ptr, Operation::TypeDescriptor(name));
+                        let size = self.constant(IrType::I64, size);
+                        self.emit(IrType::Ptr, Operation::Call(Callee::Direct(String::from("Runtime.New")), vec![ descriptor, size ]))
+                    },
+                    _ => self.invalid("'NEW' with initializer arguments or of type without known size, it", *s, *e)
+                }
+            },
+            _ => {
+                let ( s , e ) = expression_span(node);
+                self.invalid("Expression", s, e)
+            }
+        }
+    }
+
+    /// Value of expression converted to type, constants are given the type directly
+    fn expression_as(&mut self, node: &Node, ir_type: IrType) -> VReg {
+        match self.constant_of(node) {
+            Some( ConstValue::Integer( x ) ) if ir_type.is_float() => return self.emit(ir_type, Operation::Constant(Constant::Real(x as f64))),
+            Some( ConstValue::Integer( x ) ) if ir_type.is_integer() => return self.constant(ir_type, x),
+            Some( ConstValue::Set( x ) ) if ir_type.is_integer() => return self.constant(ir_type, x as i64),
+            Some( ConstValue::Real( x ) ) if ir_type.is_float() => return self.emit(ir_type, Operation::Constant(Constant::Real(x))),
+            Some( ConstValue::Char( x ) ) if ir_type.is_integer() => return self.constant(ir_type, x as i64),
+            _ => ()
+        }
+        match self.character_code(node) {
+            Some( x ) if ir_type.is_integer() => return self.constant(ir_type, x),
+            _ => ()
+        }
+
+        let value = self.expression(node);
+        self.convert(value, ir_type)
+    }
+
+    fn arithmetic(&mut self, operator: BinaryOp, type_id: TypeId, left: &Node, right: &Node) -> VReg {
+        let ir_type = self.scalar_type(type_id);
+        let operator = match ( self.base(type_id) , operator ) {
+            ( T_SET , BinaryOp::Add ) => BinaryOp::Or,
+            ( T_SET , BinaryOp::Mul ) => BinaryOp::And,
+            ( T_SET , BinaryOp::Div ) => BinaryOp::Xor,
+            ( _ , x ) => x
+        };
+        let l = self.expression_as(left, ir_type);
+        let r = self.expression_as(right, ir_type);
+        self.emit(ir_type, Operation::Binary(operator, l, r))
+    }
+
+    /// Numbers and other scalars are compared in the larger type of the two. Strings and character arrays are compared
+    /// by the runtime, sets are ordered by inclusion. A character compared to a string of one character compares codes.
+    fn relation(&mut self, operator: CompareOp, left: &Node, right: &Node) -> VReg {
+        let ( l , r ) = ( self.node_type(left) , self.node_type(right) );
+        let types = match ( self.ir_type(l) , self.ir_type(r) ) {
+            ( Some( x ) , None ) if self.character_code(right).is_some() => ( Some( x ), Some( x ) ),
+            ( None , Some( y ) ) if self.character_code(left).is_some() => ( Some( y ), Some( y ) ),
+            x => x
+        };
+
+        match types {
+            ( Some( x ) , Some( y ) ) => {
+                match ( self.base(l) , operator ) {
+                    ( T_SET , CompareOp::Le ) | ( T_SET , CompareOp::Ge ) => {
+                        /* Inclusion, no element of smaller set is missing in larger set */
+                        let ( small , large ) = match operator {
+                            CompareOp::Le => ( left, right ),
+                            _ => ( right, left )
+                        };
+                        let small = self.expression_as(small, IrType::U32);
+                        let large = self.expression_as(large, IrType::U32);
+                        let complement = self.emit(IrType::U32, Operation::Unary(UnaryOp::Not, large));
+                        let missing = self.emit(IrType::U32, Operation::Binary(BinaryOp::And, small, complement));
+                        let zero = self.constant(IrType::U32, 0);
+                        self.emit(IrType::Bool, Operation::Compare(CompareOp::Eq, missing, zero))
+                    },
+                    _ => {
+                        let common = self.common_type(x, y);
+                        let a = self.expression_as(left, common);
+                        let b = self.expression_as(right, common);
+                        self.emit(IrType::Bool, Operation::Compare(operator, a, b))
+                    }
+                }
+            },
+            _ => {
+                let a = self.string_address(left);
+                let b = self.string_address(right);
+                let order = self.emit(IrType::I32, Operation::Call(Callee::Direct(String::from("Runtime.CompareStrings")), vec![ a, b ]));
+                let zero = self.constant(IrType::I32, 0);
+                self.emit(IrType::Bool, Operation::Compare(operator, order, zero))
+            }
+        }
+    }
+
+    /// Set of elements and ranges, bits up to the upper bound are masked by a right shift of all ones
+    fn set(&mut self, elements: &[Box<Node>]) -> VReg {
+        let mut value = self.constant(IrType::U32, 0);
+
+        for element in elements.iter() {
+            let bits = match &**element {
+                Node::Range( _ , _ , Some( from ) , _ , Some( to ) , _ , _ ) => {
+                    let from = self.expression_as(from, IrType::I64);
+                    let to = self.expression_as(to, IrType::I64);
+                    let ones = self.constant(IrType::U32, 0xffffffff);
+                    let low = self.emit(IrType::U32, Operation::Binary(BinaryOp::Lsh, ones, from));
+                    let last = self.constant(IrType::I64, 31);
+                    let count = self.emit(IrType::I64, Operation::Binary(BinaryOp::Sub, to, last));
+                    let high = self.emit(IrType::U32, Operation::Binary(BinaryOp::Lsh, ones, count));
+                    self.emit(IrType::U32, Operation::Binary(BinaryOp::And, low, high))
+                },
+                Node::Range( s , e , .. ) => self.invalid("Set range without both bounds, it", *s, *e),
+                x => {
+                    let element = self.expression_as(x, IrType::I64);
+                    let one = self.constant(IrType::U32, 1);
+                    self.emit(IrType::U32, Operation::Binary(BinaryOp::Lsh, one, element))
+                }
+            };
+            value = self.emit(IrType::U32, Operation::Binary(BinaryOp::Or, value, bits))
+        }
+
+        value
+    }
+
+    /// Address of string constant or character array, a character constant becomes a string of one character
+    fn string_address(&mut self, node: &Node) -> VReg {
+        match self.string_operand(node) {
+            Some( ( x , _ ) ) => x,
+            _ => self.expression(node)
+        }
+    }
+
+    /// Global holding string or character constant and its size in bytes with terminating 0X
+    fn string_operand(&mut self, node: &Node) -> Option<(VReg, i64)> {
+        let text = match self.constant_of(node) {
+            Some( ConstValue::String( x ) ) => x.to_string(),
+            Some( ConstValue::Char( x ) ) => char::from_u32(x).map(|c| c.to_string()).unwrap_or_default(),
+            _ => return None
+        };
+        let size = text.chars().count() as i64 + 1;
+        Some( ( self.string_global(text.as_str()), size ) )
+    }
+
+    fn string_global(&mut self, text: &str) -> VReg {
+        let name = match self.strings.get(text) {
+            Some( x ) => x.clone(),
+            _ => {
+                let name = format!("{}.$String{}", self.name, self.strings.len());
+                let mut data : Vec<u8> = text.chars().map(|c| match c as u32 { x if x < 256 => x as u8, _ => b'?' }).collect();
+                data.push(0);
+                self.ir.globals.push( Global { name: name.clone(), export: false, size: data.len() as i64, align: 1, data: Some( data ) } );
+                self.strings.insert(String::from(text), name.clone());
+                name
+            }
+        };
+        self.emit(IrType::Ptr, Operation::GlobalAddress(name))
+    }
+
+    fn constant_value(&mut self, value: &ConstValue, type_id: TypeId) -> VReg {
+        let ir_type = self.ir_type(type_id);
+
+        match value {
+            ConstValue::Integer( x ) => self.constant(ir_type.unwrap_or(IrType::I64), *x),
+            ConstValue::Real( x ) => self.emit(ir_type.unwrap_or(IrType::F64), Operation::Constant(Constant::Real(*x))),
+            ConstValue::Boolean( x ) => self.constant(IrType::Bool, *x as i64),
+            ConstValue::Char( x ) => self.constant(ir_type.unwrap_or(IrType::U8), *x as i64),
+            ConstValue::String( x ) => self.string_global(x.as_str()),
+            ConstValue::Set( x ) => self.constant(IrType::U32, *x as i64),
+            ConstValue::Nil => self.constant(IrType::Ptr, 0)
+        }
+    }
+
+    /// Value of literal or named constant, without emitting code
+    fn constant_of(&self, node: &Node) -> Option<ConstValue> {
+        match node {
+            Node::Integer( .. ) | Node::Real( .. ) | Node::Character( .. ) | Node::String( .. ) |
+            Node::Nil( .. ) | Node::True( .. ) | Node::False( .. ) => literal(node).ok(),
+            Node::Ident( s , .. ) => self.constant_entry(*s),
+            Node::QualifiedIdentifier( _ , _ , _ , _ , right ) => self.constant_of(right),
+            Node::UnaryExpression( _ , _ , x , None , _ ) |
+            Node::ParenthesisExpression( _ , _ , _ , x , _ ) => self.constant_of(x),
+            Node::UnaryExpression( _ , _ , _ , Some( designators ) , _ ) => {
+                /* Constant of imported module or element of enumeration */
+                match designators.last().map(|x| &**x) {
+                    Some( Node::DotName( _ , _ , _ , id ) ) if designators.len() == 1 => {
+                        match &**id {
+                            Node::Ident( s , .. ) => self.constant_entry(*s),
+                            _ => None
+                        }
+                    },
+                    _ => None
+                }
+            },
+            Node::UnaryMinus( _ , _ , _ , x ) => {
+                match self.constant_of(x) {
+                    Some( ConstValue::Integer( y ) ) => y.checked_neg().map(ConstValue::Integer),
+                    Some( ConstValue::Real( y ) ) => Some( ConstValue::Real(-y) ),
+                    _ => None
+                }
+            },
+            _ => None
+        }
+    }
+
+    fn constant_entry(&self, position: u32) -> Option<ConstValue> {
+        let entry = self.checker.get_resolver().get_binding(self.module, position)?;
+        match self.checker.get_resolver().get_symbol_table().entry(entry).kind {
+            SymbolKind::Constant => self.checker.get_constant(entry).cloned(),
+            _ => None
+        }
+    }
+
+    /// Integer or character constant, like case labels and step of 'FOR'. Strings of one character give its code.
+    fn constant_integer(&self, node: &Node) -> Option<i64> {
+        match self.constant_of(node) {
+            Some( ConstValue::Integer( x ) ) => Some( x ),
+            _ => self.character_code(node)
+        }
+    }
+
+    /// Code of character constant or of string constant with one character, which is assignable to 'CHAR'
+    fn character_code(&self, node: &Node) -> Option<i64> {
+        match self.constant_of(node) {
+            Some( ConstValue::Char( x ) ) => Some( x as i64 ),
+            Some( ConstValue::String( x ) ) if x.chars().count() == 1 => x.chars().next().map(|c| c as i64),
+            _ => None
+        }
+    }
+}
+
+/// Designators
+impl<'a> Lowering<'a> {
+    fn designator(&mut self, node: &Node) -> Place {
+        match node {
+            Node::Ident( s , e , _ ) => {
+                match self.checker.get_resolver().get_binding(self.module, *s) {
+                    Some( x ) => self.entry_place(x, *s, *e),
+                    _ => {
+                        let ( name , _ , s , e ) = identifier_definition(node).unwrap_or(( String::new(), Export::None, *s, *e ));
+                        let value = self.error(format!("Undeclared identifier '{}'", name), s, e);
+                        Place::Value(value, T_UNKNOWN)
+                    }
+                }
+            },
+            Node::QualifiedIdentifier( _ , _ , left , _ , right ) => {
+                match self.designator(left) {
+                    Place::Module( module ) => self.member(module, right),
+                    _ => self.designator(right)
+                }
+            },
+            Node::UnaryExpression( _ , _ , primary , designators , _ ) => {
+                let mut place = self.designator(primary);
+                let whole = self.node_type(node);
+                if let Some( nodes ) = designators {
+                    for designator in nodes.iter() {
+                        place = self.selector(place, designator, whole)
+                    }
+                }
+                place
+            },
+            Node::Self_( s , e , _ ) => {
+                match self.receiver() {
+                    Some( x ) => Place::Value(x, self.node_type(node)),
+                    _ => {
+                        let value = self.invalid("'SELF' outside of method, it", *s, *e);
+                        Place::Value(value, T_UNKNOWN)
+                    }
+                }
+            },
+            Node::ParenthesisExpression( _ , _ , _ , x , _ ) => {
+                match &**x {
+                    Node::Ident( .. ) | Node::QualifiedIdentifier( .. ) | Node::UnaryExpression( .. ) => self.designator(x),
+                    _ => {
+                        let value = self.expression(x);
+                        Place::Value(value, self.node_type(x))
+                    }
+                }
+            },
+            _ => {
+                let value = self.expression(node);
+                Place::Value(value, self.node_type(node))
+            }
+        }
+    }
+
+    /// Place of declared identifier. Fields and methods named without designator belong to 'SELF'.
+    fn entry_place(&mut self, entry: EntryId, start: u32, end: u32) -> Place {
+        let checker = self.checker;
+        let table = checker.get_resolver().get_symbol_table();
+        let symbol = table.entry(entry);
+        let type_id = self.guards.get(&entry).copied().or(checker.get_entry_type(entry)).unwrap_or(T_UNKNOWN);
+        let owner = table.scope(symbol.scope).kind.clone();
+
+        match ( &symbol.kind , owner ) {
+            ( SymbolKind::Constant , _ ) => {
+                match checker.get_constant(entry) {
+                    Some( x ) => Place::Constant(x.clone(), type_id),
+                    _ => {
+                        let value = self.invalid(format!("Constant '{}' without value, it", symbol.name).as_str(), start, end);
+                        Place::Value(value, T_UNKNOWN)
+                    }
+                }
+            },
+            ( SymbolKind::Type , _ ) => Place::Type(type_id, Some( entry )),
+            ( SymbolKind::Module , _ ) => Place::Module(checker.get_resolver().get_import(entry).cloned().unwrap_or(symbol.name.clone())),
+            ( SymbolKind::Procedure , _ ) if symbol.scope == UNIVERSE_SCOPE => Place::Builtin(symbol.name.clone()),
+            ( SymbolKind::Procedure , ScopeKind::Record ) | ( SymbolKind::Procedure , ScopeKind::Object ) => {
+                let receiver = self.receiver();
+                Place::Procedure(Callee::Method(self.qualified(entry)), type_id, receiver)
+            },
+            ( SymbolKind::Procedure , _ ) => Place::Procedure(Callee::Direct(self.qualified(entry)), type_id, None),
+            ( _ , ScopeKind::Module ) => {
+                let address = self.emit(IrType::Ptr, Operation::GlobalAddress(self.qualified(entry)));
+                Place::Variable(address, type_id, None)
+            },
+            ( _ , ScopeKind::Record ) | ( _ , ScopeKind::Object ) => {
+                match ( self.receiver() , checker.field_offset(entry) ) {
+                    ( Some( receiver ) , Some( offset ) ) => {
+                        let offset = self.constant(IrType::I64, offset);
+                        let address = self.emit(IrType::Ptr, Operation::Offset(receiver, offset));
+                        Place::Variable(address, type_id, None)
+                    },
+                    _ => {
+                        let value = self.invalid(format!("Field '{}' outside of method, it", symbol.name).as_str(), start, end);
+                        Place::Value(value, T_UNKNOWN)
+                    }
+                }
+            },
+            _ => self.local(entry, type_id, start, end)
+        }
+    }
+
+    /// Member of imported module. Modules outside of the build are not checked, their members are taken
+    /// as procedures linked by qualified name.
+    fn member(&mut self, module: String, id: &Node) -> Place {
+        match identifier_definition(id) {
+            Some( ( _ , _ , s , _ ) ) if self.checker.get_resolver().get_binding(self.module, s).is_some() => self.designator(id),
+            Some( ( name , .. ) ) => Place::Procedure(Callee::Direct(format!("{}.{}", module, name)), T_UNKNOWN, None),
+            _ => {
+                let ( s , e ) = expression_span(id);
+                let value = self.error(String::from("Expecting name of module member"), s, e);
+                Place::Value(value, T_UNKNOWN)
+            }
+        }
+    }
+
+    /// Parameter or local variable, of enclosing procedure when not found in current one
+    fn local(&mut self, entry: EntryId, type_id: TypeId, start: u32, end: u32) -> Place {
+        let count = self.frames.len();
+        let found = ( 0 .. count ).rev().find_map(|x| self.frames[x].storage.get(&entry).map(|y| ( count - 1 - x , y.clone() )));
+
+        match found {
+            Some( ( levels , Storage::Value( slot ) ) ) => {
+                let address = self.slot_address(levels, slot);
+                Place::Variable(address, type_id, None)
+            },
+            Some( ( levels , Storage::Reference( slot ) ) ) => {
+                let address = self.slot_address(levels, slot);
+                let reference = self.emit(IrType::Ptr, Operation::Load(address));
+                Place::Variable(reference, type_id, None)
+            },
+            Some( ( levels , Storage::OpenArray( slot , length_slot ) ) ) => {
+                let address = self.slot_address(levels, slot);
+                let reference = self.emit(IrType::Ptr, Operation::Load(address));
+                let address = self.slot_address(levels, length_slot);
+                let length = self.emit(IrType::I64, Operation::Load(address));
+                Place::Variable(reference, type_id, Some( length ))
+            },
+            _ => {
+                let name = self.checker.get_resolver().get_symbol_table().entry(entry).name.clone();
+                let value = self.invalid(format!("Variable '{}' that is not in reach, it", name).as_str(), start, end);
+                Place::Value(value, T_UNKNOWN)
+            }
+        }
+    }
+
+    fn slot_address(&mut self, levels: usize, slot: SlotId) -> VReg {
+        match levels {
+            0 => self.emit(IrType::Ptr, Operation::SlotAddress(slot)),
+            x => self.emit(IrType::Ptr, Operation::OuterSlotAddress(x as u32, slot))
+        }
+    }
+
+    /// 'SELF' of method or object body being lowered, or of the method enclosing it
+    fn receiver(&mut self) -> Option<VReg> {
+        let count = self.frames.len();
+        let ( levels , slot ) = ( 0 .. count ).rev().find_map(|x| self.frames[x].receiver.map(|y| ( count - 1 - x , y )))?;
+        let address = self.slot_address(levels, slot);
+        Some( self.emit(IrType::Ptr, Operation::Load(address)) )
+    }
+
+    /// Apply field selector, index, dereference, call or type guard to place. Whole is the type of the complete designator.
+    fn selector(&mut self, place: Place, selector: &Node, whole: TypeId) -> Place {
+        match selector {
+            Node::DotName( s , e , _ , id ) => {
+                let ( start , end ) = match &**id {
+                    Node::Ident( s , e , _ ) => ( *s, *e ),
+                    _ => ( *s, *e )
+                };
+                let entry = match ( self.checker.get_resolver().get_binding(self.module, start) , place.clone() ) {
+                    ( None , Place::Module( module ) ) => return self.member(module, id),
+                    ( Some( x ) , _ ) => x,
+                    _ => {
+                        let value = self.error(String::from("Unknown field"), start, end);
+                        return Place::Value(value, T_UNKNOWN)
+                    }
+                };
+
+                match place {
+                    Place::Module( _ ) | Place::Type( .. ) => return self.entry_place(entry, start, end),
+                    _ => ()
+                }
+
+                let checker = self.checker;
+                let symbol = checker.get_resolver().get_symbol_table().entry(entry);
+                let type_id = checker.get_entry_type(entry).unwrap_or(T_UNKNOWN);
+                let base = self.record_base(place, *s, *e);
+
+                match ( &symbol.kind , checker.field_offset(entry) ) {
+                    ( SymbolKind::Procedure , _ ) => Place::Procedure(Callee::Method(self.qualified(entry)), type_id, Some( base )),
+                    ( _ , Some( offset ) ) => {
+                        let offset = self.constant(IrType::I64, offset);
+                        let address = self.emit(IrType::Ptr, Operation::Offset(base, offset));
+                        Place::Variable(address, type_id, None)
+                    },
+                    _ => {
+                        let value = self.invalid(format!("Field '{}' without known offset, it", symbol.name).as_str(), start, end);
+                        Place::Value(value, T_UNKNOWN)
+                    }
+                }
+            },
+            Node::Index( s , e , _ , indexes , _ ) => {
+                let mut place = place;
+                for index in expression_list(indexes).iter() {
+                    place = self.index(place, index, *s, *e)
+                }
+                place
+            },
+            Node::Arrow( s , e , _ ) => {
+                match place {
+                    Place::Procedure( Callee::Method( name ) , type_id , receiver ) => Place::Procedure(Callee::Direct(name), type_id, receiver),
+                    x => self.dereference(x, *s, *e)
+                }
+            },
+            Node::Call( s , e , _ , arguments , _ ) => {
+                let arguments = expression_list(arguments);
+                match place {
+                    Place::Procedure( callee , type_id , receiver ) => self.call(callee, type_id, receiver, &arguments, *s, *e),
+                    Place::Builtin( name ) => self.builtin(name.as_str(), &arguments, whole, *s, *e),
+                    Place::Type( type_id , _ ) => {
+                        /* Type conversion */
+                        let ir_type = self.scalar_type(type_id);
+                        let value = match arguments.first() {
+                            Some( x ) => self.expression_as(x, ir_type),
+                            _ => self.invalid("Type conversion without argument, it", *s, *e)
+                        };
+                        Place::Value(value, type_id)
+                    },
+                    Place::Variable( _ , type_id , _ ) | Place::Value( _ , type_id ) if matches!(self.checker.get_type(self.base(type_id)), Type::Procedure( .. )) => {
+                        let procedure = self.place_value(place, *s, *e);
+                        self.call(Callee::Indirect(procedure), type_id, None, &arguments, *s, *e)
+                    },
+                    x => {
+                        /* Type guard, traps when dynamic type is not the guard type or an extension of it */
+                        let guard = match arguments.first() {
+                            Some( y ) => self.designator(y),
+                            _ => Place::NoValue
+                        };
+                        match guard {
+                            Place::Type( type_id , Some( entry ) ) => {
+                                let reference = self.place_reference(x.clone(), *s, *e);
+                                let test = self.emit(IrType::Bool, Operation::TypeTest(reference, self.qualified(entry)));
+                                let ok = self.new_block();
+                                let fail = self.new_block();
+                                self.terminate(Terminator::Branch(test, ok, fail));
+                                self.start(fail);
+                                self.terminate(Terminator::Trap(TRAP_TYPE_GUARD));
+                                self.start(ok);
+                                match x {
+                                    Place::Variable( address , _ , length ) => Place::Variable(address, type_id, length),
+                                    Place::Value( value , _ ) => Place::Value(value, type_id),
+                                    y => y
+                                }
+                            },
+                            _ => {
+                                let value = self.invalid("Call of designator that is not a procedure, it", *s, *e);
+                                Place::Value(value, T_UNKNOWN)
+                            }
+                        }
+                    }
+                }
+            },
+            _ => {
+                let ( s , e ) = expression_span(selector);
+                let value = self.invalid("Selector", s, e);
+                Place::Value(value, T_UNKNOWN)
+            }
+        }
+    }
+
+    /// Address of record holding fields, pointers and objects are followed
+    fn record_base(&mut self, place: Place, start: u32, end: u32) -> VReg {
+        match place {
+            Place::Variable( address , type_id , _ ) => {
+                match self.checker.get_type(self.base(type_id)) {
+                    Type::Pointer( _ ) | Type::Object( .. ) | Type::Definition( _ ) => self.emit(IrType::Ptr, Operation::Load(address)),
+                    _ => address
+                }
+            },
+            Place::Value( value , _ ) => value,
+            _ => self.invalid("Field of designator that is not a record, it", start, end)
+        }
+    }
+
+    /// Address of memory block with dynamic type, for type tests and guards
+    fn reference(&mut self, node: &Node) -> VReg {
+        let ( s , e ) = expression_span(node);
+        let place = self.designator(node);
+        self.place_reference(place, s, e)
+    }
+
+    fn place_reference(&mut self, place: Place, start: u32, end: u32) -> VReg {
+        match place {
+            Place::Variable( address , type_id , _ ) => {
+                match self.ir_type(type_id) {
+                    Some( IrType::Ptr ) => self.emit(IrType::Ptr, Operation::Load(address)),
+                    _ => address
+                }
+            },
+            Place::Value( value , _ ) => value,
+            _ => self.invalid("Type test of designator that is not a variable, it", start, end)
+        }
+    }
+
+    /// Pointer target, open arrays on the heap have their number of elements just before the first element
+    fn dereference(&mut self, place: Place, start: u32, end: u32) -> Place {
+        let ( pointer , type_id ) = match place {
+            Place::Variable( address , type_id , _ ) => ( self.emit(IrType::Ptr, Operation::Load(address)), type_id ),
+            Place::Value( value , type_id ) => ( value, type_id ),
+            _ => {
+                let value = self.invalid("'^' of designator that is not a pointer, it", start, end);
+                return Place::Value(value, T_UNKNOWN)
+            }
+        };
+
+        match self.checker.get_type(self.base(type_id)).clone() {
+            Type::Pointer( target ) => {
+                let length = match self.open_array(target) {
+                    Some( _ ) => {
+                        let offset = self.constant(IrType::I64, -8);
+                        let address = self.emit(IrType::Ptr, Operation::Offset(pointer, offset));
+                        Some( self.emit(IrType::I64, Operation::Load(address)) )
+                    },
+                    _ => None
+                };
+                Place::Variable(pointer, target, length)
+            },
+            _ => Place::Value(pointer, type_id)
+        }
+    }
+
+    /// Element of array, index is checked against number of elements
+    fn index(&mut self, place: Place, index: &Node, start: u32, end: u32) -> Place {
+        let place = match place {
+            Place::Variable( _ , type_id , _ ) | Place::Value( _ , type_id ) if matches!(self.checker.get_type(self.base(type_id)), Type::Pointer( _ )) => self.dereference(place, start, end),
+            x => x
+        };
+
+        let ( address , type_id , length ) = match place {
+            Place::Variable( address , type_id , length ) => ( address, type_id, length ),
+            Place::Value( value , type_id ) => ( value, type_id, None ),
+            _ => {
+                let value = self.invalid("Index of designator that is not an array, it", start, end);
+                return Place::Value(value, T_UNKNOWN)
+            }
+        };
+
+        let ( element , count ) = match self.checker.get_type(self.base(type_id)).clone() {
+            Type::Array( Some( n ) , x ) | Type::MathArray( Some( n ) , x ) => ( x, Some( self.constant(IrType::I64, n) ) ),
+            Type::Array( None , x ) | Type::MathArray( None , x ) => ( x, length ),
+            Type::String( n ) => ( T_CHAR8, Some( self.constant(IrType::I64, n as i64 + 1) ) ),
+            _ => ( T_UNKNOWN, None )
+        };
+
+        let ( size , count ) = match ( self.checker.type_size(element) , count ) {
+            ( Some( x ) , Some( y ) ) => ( x, y ),
+            _ => {
+                let value = self.invalid("Index of array with elements of unknown size, it", start, end);
+                return Place::Value(value, T_UNKNOWN)
+            }
+        };
+
+        let value = self.expression_as(index, IrType::I64);
+
+        /* Negative index is a large unsigned number */
+        let unsigned_value = self.emit(IrType::U64, Operation::Convert(value));
+        let unsigned_count = self.emit(IrType::U64, Operation::Convert(count));
+        let inside = self.emit(IrType::Bool, Operation::Compare(CompareOp::Lt, unsigned_value, unsigned_count));
+        let ok = self.new_block();
+        let fail = self.new_block();
+        self.terminate(Terminator::Branch(inside, ok, fail));
+        self.start(fail);
+        self.terminate(Terminator::Trap(TRAP_INDEX));
+        self.start(ok);
+
+        let size = self.constant(IrType::I64, size);
+        let offset = self.emit(IrType::I64, Operation::Binary(BinaryOp::Mul, value, size));
+        let address = self.emit(IrType::Ptr, Operation::Offset(address, offset));
+        Place::Variable(address, element, None)
+    }
+
+    /// Value of place in virtual register, structured variables give their address
+    fn place_value(&mut self, place: Place, start: u32, end: u32) -> VReg {
+        match place {
+            Place::Variable( address , type_id , _ ) => {
+                match self.ir_type(type_id) {
+                    Some( x ) => self.emit(x, Operation::Load(address)),
+                    _ => address
+                }
+            },
+            Place::Value( value , _ ) => value,
+            Place::Constant( value , type_id ) => self.constant_value(&value, type_id),
+            Place::Procedure( Callee::Direct( name ) , _ , _ ) => self.emit(IrType::Ptr, Operation::ProcedureAddress(name)),
+            Place::Procedure( .. ) => self.invalid("Method as value, it", start, end),
+            Place::Builtin( _ ) => self.invalid("Predeclared procedure as value, it", start, end),
+            Place::Type( .. ) | Place::Module( _ ) => self.invalid("Type or module as value, it", start, end),
+            Place::NoValue => self.invalid("Call of proper procedure as value, it", start, end)
+        }
+    }
+
+    /// Call with arguments for formal parameters of procedure type, receiver of methods is the first argument
+    fn call(&mut self, callee: Callee, type_id: TypeId, receiver: Option<VReg>, arguments: &Vec<&Node>, start: u32, end: u32) -> Place {
+        if let ( T_UNKNOWN , Callee::Direct( _ ) ) = ( type_id , &callee ) {
+            return self.external_call(callee, arguments)
+        }
+
+        let ( formals , result ) = match self.checker.get_type(self.base(type_id)) {
+            Type::Procedure( x , y ) => ( x.to_vec(), *y ),
+            _ => {
+                let value = self.invalid("Call of procedure without known type, it", start, end);
+                return Place::Value(value, T_UNKNOWN)
+            }
+        };
+
+        let mut values = Vec::<VReg>::new();
+        if let Some( x ) = receiver {
+            values.push(x)
+        }
+
+        for ( argument , ( mode , formal ) ) in arguments.iter().zip(formals.iter()) {
+            match ( self.open_array(*formal) , mode , self.ir_type(*formal) ) {
+                ( Some( _ ) , _ , _ ) => {
+                    let ( address , length ) = self.array_argument(argument);
+                    values.push(address);
+                    values.push(length)
+                },
+                ( _ , ParameterMode::Var , _ ) => {
+                    let ( s , e ) = expression_span(argument);
+                    match self.designator(argument) {
+                        Place::Variable( address , _ , _ ) => values.push(address),
+                        _ => {
+                            let value = self.invalid("Argument for 'VAR' parameter that is not a variable, it", s, e);
+                            values.push(value)
+                        }
+                    }
+                },
+                ( _ , _ , Some( x ) ) => {
+                    let value = self.expression_as(argument, x);
+                    values.push(value)
+                },
+                _ => {
+                    let value = self.string_address(argument);
+                    values.push(value)
+                }
+            }
+        }
+
+        match ( result , self.ir_type(result) ) {
+            ( T_NOTYPE , _ ) => {
+                self.emit_void(Operation::Call(callee, values));
+                Place::NoValue
+            },
+            ( _ , Some( x ) ) => {
+                let value = self.emit(x, Operation::Call(callee, values));
+                Place::Value(value, result)
+            },
+            _ => {
+                let value = self.invalid("Call of procedure with structured result, it", start, end);
+                Place::Value(value, T_UNKNOWN)
+            }
+        }
+    }
+
+    /// Call of procedure in module outside of the build, its signature is unknown. Arguments are passed by value
+    /// as the type checker typed them, structured values and strings by address.
+    fn external_call(&mut self, callee: Callee, arguments: &Vec<&Node>) -> Place {
+        let mut values = Vec::<VReg>::new();
+
+        for argument in arguments.iter() {
+            let value = match self.ir_type(self.node_type(argument)) {
+                Some( x ) => self.expression_as(argument, x),
+                _ => {
+                    match self.string_operand(argument) {
+                        Some( ( address , _ ) ) => address,
+                        _ => self.expression(argument)
+                    }
+                }
+            };
+            values.push(value)
+        }
+
+        self.emit_void(Operation::Call(callee, values));
+        Place::NoValue
+    }
+
+    /// Address and number of elements of array, string or character constant passed for open array
+    fn array_argument(&mut self, argument: &Node) -> (VReg, VReg) {
+        if let Some( ( address , size ) ) = self.string_operand(argument) {
+            let length = self.constant(IrType::I64, size);
+            return ( address, length )
+        }
+
+        let ( s , e ) = expression_span(argument);
+        let place = self.designator(argument);
+        let place = match place {
+            Place::Variable( _ , type_id , _ ) | Place::Value( _ , type_id ) if matches!(self.checker.get_type(self.base(type_id)), Type::Pointer( _ )) => self.dereference(place, s, e),
+            x => x
+        };
+
+        match place {
+            Place::Variable( address , type_id , length ) => {
+                match ( self.checker.get_type(self.base(type_id)).clone() , length ) {
+                    ( _ , Some( x ) ) => ( address, x ),
+                    ( Type::Array( Some( n ) , _ ) , _ ) | ( Type::MathArray( Some( n ) , _ ) , _ ) => {
+                        let length = self.constant(IrType::I64, n);
+                        ( address, length )
+                    },
+                    _ => {
+                        let length = self.invalid("Argument for open array that is not an array, it", s, e);
+                        ( address, length )
+                    }
+                }
+            },
+            _ => {
+                let value = self.invalid("Argument for open array that is not a variable, it", s, e);
+                ( value, value )
+            }
+        }
+    }
+
+    /// Predeclared procedures and functions, result is the type of the designator for those with a result
+    fn builtin(&mut self, name: &str, arguments: &Vec<&Node>, result: TypeId, start: u32, end: u32) -> Place {
+        let first = arguments.first().copied();
+        let second = arguments.get(1).copied();
+
+        match ( name , first , second ) {
+            ( "INC" , Some( x ) , _ ) | ( "DEC" , Some( x ) , _ ) => {
+                let ( s , e ) = expression_span(x);
+                match self.designator(x) {
+                    Place::Variable( address , type_id , _ ) => {
+                        let ir_type = self.scalar_type(type_id);
+                        let old = self.emit(ir_type, Operation::Load(address));
+                        let amount = match second {
+                            Some( y ) => self.expression_as(y, ir_type),
+                            _ => self.constant(ir_type, 1)
+                        };
+                        let operator = match name {
+                            "INC" => BinaryOp::Add,
+                            _ => BinaryOp::Sub
+                        };
+                        let new = self.emit(ir_type, Operation::Binary(operator, old, amount));
+                        self.emit_void(Operation::Store(address, new));
+                    },
+                    _ => {
+                        self.invalid(format!("'{}' of designator that is not a variable, it", name).as_str(), s, e);
+                    }
+                }
+                Place::NoValue
+            },
+            ( "INCL" , Some( x ) , Some( y ) ) | ( "EXCL" , Some( x ) , Some( y ) ) => {
+                let ( s , e ) = expression_span(x);
+                match self.designator(x) {
+                    Place::Variable( address , _ , _ ) => {
+                        let old = self.emit(IrType::U32, Operation::Load(address));
+                        let element = self.expression_as(y, IrType::I64);
+                        let one = self.constant(IrType::U32, 1);
+                        let bit = self.emit(IrType::U32, Operation::Binary(BinaryOp::Lsh, one, element));
+                        let new = match name {
+                            "INCL" => self.emit(IrType::U32, Operation::Binary(BinaryOp::Or, old, bit)),
+                            _ => {
+                                let complement = self.emit(IrType::U32, Operation::Unary(UnaryOp::Not, bit));
+                                self.emit(IrType::U32, Operation::Binary(BinaryOp::And, old, complement))
+                            }
+                        };
+                        self.emit_void(Operation::Store(address, new));
+                    },
+                    _ => {
+                        self.invalid(format!("'{}' of designator that is not a variable, it", name).as_str(), s, e);
+                    }
+                }
+                Place::NoValue
+            },
+            ( "ASSERT" , Some( x ) , _ ) => {
+                let trap = match second.map(|y| self.constant_integer(y)) {
+                    Some( Some( y ) ) => y,
+                    Some( None ) => {
+                        self.unsupported("Trap number of 'ASSERT' that is not a constant", start, end);
+                        TRAP_ASSERT
+                    },
+                    _ => TRAP_ASSERT
+                };
+                let ok = self.new_block();
+                let fail = self.new_block();
+                self.condition(x, ok, fail);
+                self.start(fail);
+                self.terminate(Terminator::Trap(trap));
+                self.start(ok);
+                Place::NoValue
+            },
+            ( "HALT" , Some( x ) , _ ) => {
+                match self.constant_integer(x) {
+                    Some( y ) => self.terminate(Terminator::Trap(y)),
+                    _ => self.unsupported("Trap number of 'HALT' that is not a constant", start, end)
+                }
+                Place::NoValue
+            },
+            ( "ABS" , Some( x ) , _ ) => {
+                let ir_type = self.scalar_type(result);
+                let value = self.expression_as(x, ir_type);
+                let zero = match ir_type.is_float() {
+                    true => self.emit(ir_type, Operation::Constant(Constant::Real(0.0))),
+                    _ => self.constant(ir_type, 0)
+                };
+                let negative = self.emit(IrType::Bool, Operation::Compare(CompareOp::Lt, value, zero));
+                let negated = self.emit(ir_type, Operation::Unary(UnaryOp::Neg, value));
+                Place::Value(self.select(ir_type, negative, negated, value), result)
+            },
+            ( "ODD" , Some( x ) , _ ) => {
+                let ir_type = self.scalar_type(self.node_type(x));
+                let value = self.expression_as(x, ir_type);
+                let one = self.constant(ir_type, 1);
+                let bit = self.emit(ir_type, Operation::Binary(BinaryOp::And, value, one));
+                let zero = self.constant(ir_type, 0);
+                Place::Value(self.emit(IrType::Bool, Operation::Compare(CompareOp::Ne, bit, zero)), T_BOOLEAN)
+            },
+            ( "ORD" , Some( x ) , _ ) => Place::Value(self.expression_as(x, IrType::I16), T_SIGNED16),
+            ( "CHR" , Some( x ) , _ ) => Place::Value(self.expression_as(x, IrType::U8), T_CHAR8),
+            ( "CAP" , Some( x ) , _ ) => {
+                let value = self.expression_as(x, IrType::U8);
+                let a = self.constant(IrType::U8, 'a' as i64);
+                let z = self.constant(IrType::U8, 'z' as i64);
+                let above = self.emit(IrType::Bool, Operation::Compare(CompareOp::Ge, value, a));
+                let below = self.emit(IrType::Bool, Operation::Compare(CompareOp::Le, value, z));
+                let letter = self.emit(IrType::Bool, Operation::Binary(BinaryOp::And, above, below));
+                let difference = self.constant(IrType::U8, 32);
+                let capital = self.emit(IrType::U8, Operation::Binary(BinaryOp::Sub, value, difference));
+                Place::Value(self.select(IrType::U8, letter, capital, value), T_CHAR8)
+            },
+            ( "LEN" , Some( x ) , None ) => {
+                let ( _ , length ) = self.array_argument(x);
+                Place::Value(length, T_SIZE)
+            },
+            ( "ASH" , Some( x ) , Some( y ) ) | ( "LSH" , Some( x ) , Some( y ) ) | ( "ROT" , Some( x ) , Some( y ) ) |
+            ( "ROL" , Some( x ) , Some( y ) ) | ( "ROR" , Some( x ) , Some( y ) ) => {
+                let ir_type = self.scalar_type(result);
+                let value = self.expression_as(x, ir_type);
+                let count = self.expression_as(y, IrType::I64);
+                let ( operator , count ) = match name {
+                    "ASH" => ( BinaryOp::Ash, count ),
+                    "LSH" => ( BinaryOp::Lsh, count ),
+                    "ROR" => ( BinaryOp::Rot, self.emit(IrType::I64, Operation::Unary(UnaryOp::Neg, count)) ),
+                    _ => ( BinaryOp::Rot, count )
+                };
+                Place::Value(self.emit(ir_type, Operation::Binary(operator, value, count)), result)
+            },
+            ( "SHORT" , Some( x ) , _ ) | ( "LONG" , Some( x ) , _ ) | ( "ENTIER" , Some( x ) , _ ) | ( "ENTIERH" , Some( x ) , _ ) => {
+                let result = match name {
+                    "ENTIER" => T_SIGNED32,
+                    "ENTIERH" => T_SIGNED64,
+                    _ => result
+                };
+                let ir_type = self.scalar_type(result);
+                Place::Value(self.expression_as(x, ir_type), result)
+            },
+            ( "MIN" , Some( x ) , None ) | ( "MAX" , Some( x ) , None ) => {
+                let ( s , e ) = expression_span(x);
+                match self.designator(x) {
+                    Place::Type( type_id , _ ) => self.limit(name == "MAX", type_id, s, e),
+                    _ => {
+                        let value = self.invalid(format!("'{}' of value that is not a type, it", name).as_str(), s, e);
+                        Place::Value(value, T_UNKNOWN)
+                    }
+                }
+            },
+            ( "MIN" , Some( x ) , Some( y ) ) | ( "MAX" , Some( x ) , Some( y ) ) => {
+                let ir_type = self.scalar_type(result);
+                let a = self.expression_as(x, ir_type);
+                let b = self.expression_as(y, ir_type);
+                let operator = match name {
+                    "MIN" => CompareOp::Lt,
+                    _ => CompareOp::Gt
+                };
+                let first = self.emit(IrType::Bool, Operation::Compare(operator, a, b));
+                Place::Value(self.select(ir_type, first, a, b), result)
+            },
+            ( "SIZEOF" , Some( x ) , _ ) => {
+                let ( s , e ) = expression_span(x);
+                match self.designator(x) {
+                    Place::Type( type_id , _ ) if self.checker.type_size(type_id).is_some() => {
+                        let size = self.checker.type_size(type_id).unwrap_or(0);
+                        Place::Value(self.constant(IrType::I64, size), T_SIZE)
+                    },
+                    _ => {
+                        let value = self.invalid("'SIZEOF' of type without known size, it", s, e);
+                        Place::Value(value, T_UNKNOWN)
+                    }
+                }
+            },
+            ( "COPY" , Some( x ) , Some( y ) ) => {
+                let ( source , source_length ) = self.array_argument(x);
+                let ( target , target_length ) = self.array_argument(y);
+                self.emit_void(Operation::Call(Callee::Direct(String::from("Runtime.CopyString")), vec![ source, source_length, target, target_length ]));
+                Place::NoValue
+            },
+            _ => {
+                let value = self.invalid(format!("Predeclared procedure '{}' with these arguments, it", name).as_str(), start, end);
+                Place::Value(value, T_UNKNOWN)
+            }
+        }
+    }
+
+    /// Smallest or largest value of basic type
+    fn limit(&mut self, largest: bool, type_id: TypeId, start: u32, end: u32) -> Place {
+        let value = match ( self.checker.get_type(self.base(type_id)) , largest ) {
+            ( Type::Set , false ) => return Place::Value(self.constant(IrType::I16, 0), T_SIGNED16),
+            ( Type::Set , true ) => return Place::Value(self.constant(IrType::I16, 31), T_SIGNED16),
+            ( Type::Real( 32 ) , false ) => return Place::Value(self.emit(IrType::F32, Operation::Constant(Constant::Real(f32::MIN as f64))), type_id),
+            ( Type::Real( 32 ) , true ) => return Place::Value(self.emit(IrType::F32, Operation::Constant(Constant::Real(f32::MAX as f64))), type_id),
+            ( Type::Real( _ ) , false ) => return Place::Value(self.emit(IrType::F64, Operation::Constant(Constant::Real(f64::MIN))), type_id),
+            ( Type::Real( _ ) , true ) => return Place::Value(self.emit(IrType::F64, Operation::Constant(Constant::Real(f64::MAX))), type_id),
+            ( Type::Boolean , x ) => x as i64,
+            ( Type::Signed( bits ) , false ) => -1i64 << ( *bits - 1 ),
+            ( Type::Signed( bits ) , true ) => ( ( 1i128 << ( *bits - 1 ) ) - 1 ) as i64,
+            ( Type::Char( _ ) , false ) | ( Type::Unsigned( _ ) , false ) => 0,
+            ( Type::Char( bits ) , true ) | ( Type::Unsigned( bits ) , true ) => ( ( 1i128 << *bits ) - 1 ) as i64,
+            _ => {
+                let value = self.invalid("'MIN' or 'MAX' of type that is not basic, it", start, end);
+                return Place::Value(value, T_UNKNOWN)
+            }
+        };
+        let ir_type = self.scalar_type(type_id);
+        Place::Value(self.constant(ir_type, value), type_id)
+    }
+
+    /// One of two values by condition, merged by phi after a diamond of blocks
+    fn select(&mut self, ir_type: IrType, condition: VReg, when_true: VReg, when_false: VReg) -> VReg {
+        let yes = self.new_block();
+        let no = self.new_block();
+        let end = self.new_block();
+        self.terminate(Terminator::Branch(condition, yes, no));
+        self.start(yes);
+        self.jump(end);
+        self.start(no);
+        self.jump(end);
+        self.start(end);
+        self.emit(ir_type, Operation::Phi(vec![ ( yes, when_true ), ( no, when_false ) ]))
+    }
+
+    /// Qualified name of type node, naming the run time description of the type
+    fn type_descriptor_name(&mut self, type_node: &Node) -> String {
+        match self.designator(type_node) {
+            Place::Type( _ , Some( entry ) ) => self.qualified(entry),
+            _ => {
+                let ( s , e ) = expression_span(type_node);
+                self.unsupported("Type test with type that is not named", s, e);
+                String::new()
+            }
+        }
+    }
+
+    fn type_of(&mut self, type_node: &Node) -> TypeId {
+        match self.designator(type_node) {
+            Place::Type( x , _ ) => x,
+            _ => self.node_type(type_node)
+        }
+    }
+}
+
+/// Types, names and emitting of instructions
+impl<'a> Lowering<'a> {
+    fn base(&self, type_id: TypeId) -> TypeId {
+        let mut current = type_id;
+        loop {
+            match self.checker.get_type(current) {
+                Type::Alias( x ) if *x != current => current = *x,
+                _ => return current
+            }
+        }
+    }
+
+    /// Type of register holding value of type, structured types have none
+    fn ir_type(&self, type_id: TypeId) -> Option<IrType> {
+        match self.checker.get_type(self.base(type_id)) {
+            Type::Boolean => Some( IrType::Bool ),
+            Type::Char( 8 ) | Type::Unsigned( 8 ) => Some( IrType::U8 ),
+            Type::Char( 16 ) | Type::Unsigned( 16 ) => Some( IrType::U16 ),
+            Type::Char( _ ) | Type::Unsigned( 32 ) | Type::Set => Some( IrType::U32 ),
+            Type::Unsigned( _ ) | Type::Address => Some( IrType::U64 ),
+            Type::Signed( 8 ) => Some( IrType::I8 ),
+            Type::Signed( 16 ) => Some( IrType::I16 ),
+            Type::Signed( 32 ) | Type::Enumeration( _ ) => Some( IrType::I32 ),
+            Type::Signed( _ ) | Type::Size => Some( IrType::I64 ),
+            Type::Real( 32 ) => Some( IrType::F32 ),
+            Type::Real( _ ) => Some( IrType::F64 ),
+            Type::Pointer( _ ) | Type::Object( .. ) | Type::Definition( _ ) | Type::Procedure( .. ) | Type::Nil => Some( IrType::Ptr ),
+            _ => None
+        }
+    }
+
+    /// Register type of value, address for structured values
+    fn scalar_type(&self, type_id: TypeId) -> IrType {
+        self.ir_type(type_id).unwrap_or(IrType::Ptr)
+    }
+
+    /// Element type of open array
+    fn open_array(&self, type_id: TypeId) -> Option<TypeId> {
+        match self.checker.get_type(self.base(type_id)) {
+            Type::Array( None , x ) => Some( *x ),
+            _ => None
+        }
+    }
+
+    /// Type both operands of comparison are converted to
+    fn common_type(&self, left: IrType, right: IrType) -> IrType {
+        match ( left.is_float() , right.is_float() ) {
+            ( true , false ) => left,
+            ( false , true ) => right,
+            _ if right.size() > left.size() => right,
+            _ => left
+        }
+    }
+
+    fn node_type(&self, node: &Node) -> TypeId {
+        let ( s , e ) = expression_span(node);
+        self.checker.get_expression_type(self.module, s, e).unwrap_or(T_UNKNOWN)
+    }
+
+    /// Name of scope qualified by module and enclosing scopes
+    fn scope_path(&self, scope: ScopeId) -> String {
+        let table = self.checker.get_resolver().get_symbol_table();
+        let mut names = Vec::<String>::new();
+        let mut current = Some( scope );
+
+        loop {
+            match current {
+                Some( x ) if x != UNIVERSE_SCOPE => {
+                    let name = &table.scope(x).name;
+                    if !name.is_empty() {
+                        names.push(name.clone())
+                    }
+                    current = table.scope(x).parent
+                },
+                _ => break
+            }
+        }
+
+        names.reverse();
+        names.join(".")
+    }
+
+    fn qualified(&self, entry: EntryId) -> String {
+        let symbol = self.checker.get_resolver().get_symbol_table().entry(entry);
+        format!("{}.{}", self.scope_path(symbol.scope), symbol.name)
+    }
+
+    fn frame(&mut self) -> &mut Frame {
+        let last = self.frames.len() - 1;
+        &mut self.frames[last]
+    }
+
+    fn new_block(&mut self) -> BlockId {
+        self.frame().function.new_block()
+    }
+
+    /// Continue emitting in block
+    fn start(&mut self, block: BlockId) {
+        self.frame().current = block
+    }
+
+    /// End current block, code following it goes into a new block that is removed when nothing reaches it
+    fn terminate(&mut self, terminator: Terminator) {
+        let frame = self.frame();
+        frame.function.blocks[frame.current].terminator = Some( terminator );
+        frame.current = frame.function.new_block()
+    }
+
+    fn jump(&mut self, block: BlockId) {
+        self.terminate(Terminator::Jump(block))
+    }
+
+    fn emit(&mut self, ir_type: IrType, operation: Operation) -> VReg {
+        let frame = self.frame();
+        let result = frame.function.new_register(ir_type);
+        frame.function.blocks[frame.current].instructions.push( Instruction { result: Some( result ), operation } );
+        result
+    }
+
+    fn emit_void(&mut self, operation: Operation) {
+        let frame = self.frame();
+        frame.function.blocks[frame.current].instructions.push( Instruction { result: None, operation } )
+    }
+
+    fn constant(&mut self, ir_type: IrType, value: i64) -> VReg {
+        self.emit(ir_type, Operation::Constant(Constant::Integer(value)))
+    }
+
+    fn convert(&mut self, value: VReg, ir_type: IrType) -> VReg {
+        match self.frame().function.registers[value] == ir_type {
+            true => value,
+            _ => self.emit(ir_type, Operation::Convert(value))
+        }
+    }
+
+    fn store_slot(&mut self, slot: SlotId, value: VReg) {
+        let address = self.emit(IrType::Ptr, Operation::SlotAddress(slot));
+        self.emit_void(Operation::Store(address, value))
+    }
+
+    fn load_slot(&mut self, slot: SlotId, ir_type: IrType) -> VReg {
+        let address = self.emit(IrType::Ptr, Operation::SlotAddress(slot));
+        self.emit(ir_type, Operation::Load(address))
+    }
+
+    fn unsupported(&mut self, what: &str, start: u32, end: u32) {
+        self.errors.push(Diagnostic::error(E_LOWERING, format!("{} is not supported by code generation", what), start, end))
+    }
+
+    /// Report construct that cannot be lowered, giving a register so that lowering can go on
+    fn invalid(&mut self, what: &str, start: u32, end: u32) -> VReg {
+        self.error(format!("{} is not supported by code generation", what), start, end)
+    }
+
+    /// Report error in tree, giving a register so that lowering can go on
+    fn error(&mut self, message: String, start: u32, end: u32) -> VReg {
+        self.errors.push(Diagnostic::error(E_LOWERING, message, start, end));
+        match self.frames.is_empty() {
+            true => 0,
+            _ => self.constant(IrType::I64, 0)
+        }
+    }
+}
+
+// Unittests for lowering module
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{Parser, ParserMethods, BlockRules};
+    use crate::scanner::{Scanner, ScannerMethods};
+    use crate::type_checker::{TypeChecker, TypeCheckerMethods};
+    use crate::diagnostics::E_LOWERING;
+    use crate::ir::{IrModule, IrModuleMethods, Function, FunctionMethods, IrType, Operation, Terminator, Callee, CompareOp, TRAP_CASE, TRAP_RETURN, TRAP_WITH, TRAP_INDEX, TRAP_TYPE_GUARD};
+    use std::collections::HashMap;
+    use crate::ir_lowering::lower_module;
+
+    fn lower(source: &'static str) -> IrModule {
+        let mut checker = TypeChecker::new();
+        let module = Parser::new(Box::new(Scanner::new(source))).parse_module().unwrap();
+        let res = checker.check_modules(&[ &*module ]);
+        assert_eq!(res[0].len(), 0);
+        lower_module(&checker, &module, &HashMap::new()).unwrap()
+    }
+
+    fn terminators(function: &Function) -> Vec<Terminator> {
+        function.blocks.iter().map(|x| x.terminator.clone().unwrap()).collect()
+    }
+
+    fn operations(function: &Function) -> Vec<Operation> {
+        function.blocks.iter().flat_map(|x| x.instructions.iter().map(|y| y.operation.clone())).collect()
+    }
+
+    #[test]
+    fn if_elsif_else() {
+        let ir = lower("MODULE Test; VAR i: INTEGER; BEGIN IF i = 1 THEN i := 2 ELSIF (i > 2) & (i < 5) THEN i := 3 ELSE i := 4 END END Test.");
+        let body = ir.function("Test.$Body").unwrap();
+        let branches = terminators(body).iter().filter(|x| matches!(x, Terminator::Branch( .. ))).count();
+
+        assert_eq!(branches, 3);
+        assert!(body.reachable().iter().all(|x| *x))
+    }
+
+    #[test]
+    fn while_and_repeat_loops() {
+        let ir = lower("MODULE Test; VAR i: INTEGER; BEGIN WHILE i < 10 DO INC(i) END; REPEAT DEC(i) UNTIL i = 0 END Test.");
+        let body = ir.function("Test.$Body").unwrap();
+        /* Block 1 returns, it is the only earlier block that is not a loop header */
+        let back_edges = ( 0 .. body.blocks.len() ).flat_map(|x| body.successors(x).into_iter().filter(move |y| *y <= x && *y != 1)).count();
+
+        assert_eq!(back_edges, 2)
+    }
+
+    #[test]
+    fn for_with_negative_step() {
+        let ir = lower("MODULE Test; VAR i, n: INTEGER; BEGIN FOR i := 10 TO 0 BY -2 DO INC(n) END END Test.");
+        let operations = operations(ir.function("Test.$Body").unwrap());
+
+        assert!(operations.iter().any(|x| matches!(x, Operation::Compare( CompareOp::Ge , .. ))));
+        assert!(operations.contains(&Operation::Constant(crate::ir::Constant::Integer(-2))))
+    }
+
+    #[test]
+    fn case_without_else_traps() {
+        let ir = lower("MODULE Test; VAR i: INTEGER; BEGIN CASE i OF 0: i := 1 | 1 .. 3, 5: i := 2 END END Test.");
+        let terminators = terminators(ir.function("Test.$Body").unwrap());
+
+        match terminators.iter().find(|x| matches!(x, Terminator::Switch( .. ))) {
+            Some( Terminator::Switch( _ , cases , _ ) ) => {
+                assert_eq!(cases.iter().map(|x| ( x.0, x.1 )).collect::<Vec<_>>(), vec![ ( 0, 0 ), ( 1, 3 ), ( 5, 5 ) ]);
+                assert_eq!(cases[1].2, cases[2].2)
+            },
+            _ => assert!(false)
+        }
+        assert!(terminators.contains(&Terminator::Trap(TRAP_CASE)))
+    }
+
+    #[test]
+    fn case_on_characters() {
+        let ir = lower("MODULE Test; CONST Z = 'z'; VAR c: CHAR; i: INTEGER; BEGIN CASE c OF 'a' .. Z: i := 1 | \"0\", 41X: i := 2 ELSE i := 3 END END Test.");
+        let terminators = terminators(ir.function("Test.$Body").unwrap());
+
+        match terminators.iter().find(|x| matches!(x, Terminator::Switch( .. ))) {
+            Some( Terminator::Switch( _ , cases , _ ) ) => {
+                assert_eq!(cases.iter().map(|x| ( x.0, x.1 )).collect::<Vec<_>>(), vec![ ( 97, 122 ), ( 48, 48 ), ( 65, 65 ) ])
+            },
+            _ => assert!(false)
+        }
+        assert!(!terminators.contains(&Terminator::Trap(TRAP_CASE)))
+    }
+
+    #[test]
+    fn string_of_one_character_as_char() {
+        let ir = lower("MODULE Test; VAR c: CHAR; b: BOOLEAN; BEGIN c := \"A\"; b := c = \"B\"; b := \"C\" # c END Test.");
+        let operations = operations(ir.function("Test.$Body").unwrap());
+
+        assert!(operations.contains(&Operation::Constant(crate::ir::Constant::Integer(65))));
+        assert!(operations.contains(&Operation::Constant(crate::ir::Constant::Integer(66))));
+        assert!(operations.contains(&Operation::Constant(crate::ir::Constant::Integer(67))));
+        assert_eq!(operations.iter().filter(|x| matches!(x, Operation::Compare( .. ))).count(), 2);
+        assert!(!operations.iter().any(|x| matches!(x, Operation::Convert( _ ) | Operation::Call( .. ))));
+        assert!(!ir.globals.iter().any(|x| x.name.contains("$String")))
+    }
+
+    #[test]
+    fn loop_exit() {
+        let ir = lower("MODULE Test; VAR i: INTEGER; BEGIN LOOP IF i > 5 THEN EXIT END; INC(i) END; i := 0 END Test.");
+        let body = ir.function("Test.$Body").unwrap();
+
+        assert!(terminators(body).contains(&Terminator::Return(None)));
+        assert!(body.reachable().iter().all(|x| *x))
+    }
+
+    #[test]
+    fn return_through_finally() {
+        let ir = lower("MODULE Test; PROCEDURE P(n: INTEGER): INTEGER; VAR k: INTEGER; BEGIN k := n; RETURN k FINALLY k := -1 END P; END Test.");
+        let function = ir.function("Test.P").unwrap();
+        let finally = function.finally.unwrap();
+
+        assert_eq!(function.result, IrType::I16);
+        assert_eq!(function.parameters.len(), 1);
+        assert_eq!(function.successors(0), vec![ finally ]);
+        assert!(!terminators(function).contains(&Terminator::Trap(TRAP_RETURN)));
+        assert!(matches!(function.blocks[function.successors(finally)[0]].terminator, Some( Terminator::Return( Some( _ ) ) )))
+    }
+
+    #[test]
+    fn function_without_return_traps() {
+        let ir = lower("MODULE Test; PROCEDURE P(n: INTEGER): INTEGER; BEGIN IF n > 0 THEN RETURN n END END P; END Test.");
+
+        assert!(terminators(ir.function("Test.P").unwrap()).contains(&Terminator::Trap(TRAP_RETURN)))
+    }
+
+    #[test]
+    fn with_type_tests() {
+        let ir = lower("MODULE Test; TYPE R = RECORD END; P = POINTER TO R; Q = POINTER TO RECORD (R) x: INTEGER END; VAR p: P; BEGIN WITH p : Q DO p.x := 1 END END Test.");
+        let body = ir.function("Test.$Body").unwrap();
+
+        assert_eq!(operations(body).iter().filter(|x| matches!(x, Operation::TypeTest( _ , name ) if name == "Test.Q")).count(), 1);
+        assert!(terminators(body).contains(&Terminator::Trap(TRAP_WITH)));
+        assert!(!terminators(body).contains(&Terminator::Trap(TRAP_TYPE_GUARD)))
+    }
+
+    #[test]
+    fn method_of_definition_gets_object() {
+        let ir = lower("MODULE Test; DEFINITION Shape; PROCEDURE Scale(f: REAL); END Shape; VAR sh: Shape; BEGIN sh.Scale(2.0) END Test.");
+        let instructions : Vec<_> = ir.function("Test.$Body").unwrap().blocks.iter().flat_map(|x| x.instructions.iter()).collect();
+        let receiver = instructions.iter().find_map(|x| match &x.operation {
+            Operation::Call( Callee::Method( name ) , args ) if name == "Test.Shape.Scale" => args.first().cloned(),
+            _ => None
+        }).unwrap();
+
+        assert!(instructions.iter().any(|x| x.result == Some( receiver ) && matches!(x.operation, Operation::Load( _ ))))
+    }
+
+    #[test]
+    fn var_parameters_and_nested_procedures() {
+        let ir = lower("MODULE Test; VAR g: INTEGER; a: ARRAY 4 OF INTEGER; PROCEDURE P(VAR x: INTEGER; y: ARRAY OF INTEGER); VAR k: INTEGER; PROCEDURE Q; BEGIN k := x END Q; BEGIN x := y[1]; Q END P; BEGIN P(g, a) END Test.");
+        let names : Vec<&str> = ir.functions.iter().map(|x| x.name.as_str()).collect();
+        let body = operations(ir.function("Test.$Body").unwrap());
+        let nested = ir.function("Test.P.Q").unwrap();
+
+        assert_eq!(names, vec![ "Test.P", "Test.P.Q", "Test.$Body" ]);
+        assert_eq!(ir.function("Test.P").unwrap().parameters.len(), 3);
+        assert_eq!(ir.function("Test.P").unwrap().registers[1], IrType::Ptr);
+        assert_eq!(nested.parent.as_deref(), Some( "Test.P" ));
+        assert!(operations(nested).iter().any(|x| matches!(x, Operation::OuterSlotAddress( 1 , _ ))));
+        assert!(terminators(ir.function("Test.P").unwrap()).contains(&Terminator::Trap(TRAP_INDEX)));
+        assert!(body.iter().any(|x| matches!(x, Operation::Call( Callee::Direct( name ) , args ) if name == "Test.P" && args.len() == 3)))
+    }
+
+    #[test]
+    fn calls_to_imported_procedures() {
+        let ir = lower("MODULE Test; IMPORT O := Out; VAR x: INTEGER; BEGIN O.Int(x, 0); O.Ln END Test.");
+        let body = operations(ir.function("Test.$Body").unwrap());
+
+        assert!(body.iter().any(|x| matches!(x, Operation::Call( Callee::Direct( name ) , args ) if name == "Out.Int" && args.len() == 2)));
+        assert!(body.iter().any(|x| matches!(x, Operation::Call( Callee::Direct( name ) , args ) if name == "Out.Ln" && args.is_empty())));
+
+        let mut checker = TypeChecker::new();
+        let library = Parser::new(Box::new(Scanner::new("MODULE Lib; PROCEDURE P*(VAR a: INTEGER); END P; END Lib."))).parse_module().unwrap();
+        let module = Parser::new(Box::new(Scanner::new("MODULE Test; IMPORT Lib; VAR x: INTEGER; BEGIN Lib.P(x) END Test."))).parse_module().unwrap();
+        let res = checker.check_modules(&[ &*library, &*module ]);
+        assert!(res.iter().all(|x| x.is_empty()));
+        let body = operations(lower_module(&checker, &module, &HashMap::new()).unwrap().function("Test.$Body").unwrap());
+
+        assert!(body.iter().any(|x| matches!(x, Operation::Call( Callee::Direct( name ) , args ) if name == "Lib.P" && args.len() == 1)))
+    }
+
+    #[test]
+    fn unsupported_constructs() {
+        let mut checker = TypeChecker::new();
+        let module = Parser::new(Box::new(Scanner::new("MODULE Test; VAR i: INTEGER; BEGIN TRACE(i) END Test."))).parse_module().unwrap();
+        checker.check_modules(&[ &*module ]);
+
+        assert_eq!(lower_module(&checker, &module, &HashMap::new()).unwrap_err().iter().map(|x| x.code).collect::<Vec<_>>(), vec![ E_LOWERING ])
+    }
+
+    #[test]
+    fn undeclared_identifier() {
+        let mut checker = TypeChecker::new();
+        let module = Parser::new(Box::new(Scanner::new("MODULE Test; VAR i: INTEGER; BEGIN i := k END Test."))).parse_module().unwrap();
+        checker.check_modules(&[ &*module ]);
+        let errors = lower_module(&checker, &module, &HashMap::new()).unwrap_err();
+
+        assert_eq!(errors.iter().map(|x| x.message.as_str()).collect::<Vec<_>>(), vec![ "Undeclared identifier 'k'" ]);
+        assert_eq!(( errors[0].span.start , errors[0].span.end ), ( 40, 41 ))
+    }
+}
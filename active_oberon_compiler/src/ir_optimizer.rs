@@ -0,0 +1,1348 @@
+// ActiveOberon Compiler, a native ARM v8 & X86-64 compiler & Risc V / linker / builder utility.
+// Written by Richard Magnor Stenbro. Licensed under GPL v3
+// IR optimizer module, pass manager running optimizations over intermediate representation with statistics of each pass
+
+use std::collections::{HashMap, HashSet};
+use crate::diagnostics::Diagnostic;
+use crate::ir::{IrModule, Function, FunctionMethods, IrType, IrTypeMethods, VReg, BlockId, SlotId, Instruction, Operation, OperationMethods, Terminator,
+                TerminatorMethods, Constant, Callee, UnaryOp, BinaryOp, CompareOp, ENTRY_BLOCK};
+use crate::ir_inliner::{inline_calls, elide_frames};
+use crate::ir_verifier::verify_module;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Pass {
+    ConstantPropagation,    /* Sparse conditional constant propagation */
+    DeadCodeElimination,
+    ValueNumbering,         /* Global value numbering over dominator tree, removes common subexpressions */
+    CopyPropagation,
+    SimplifyControlFlow,
+    PromoteSlots,           /* Local variables only loaded and stored are kept in registers */
+    Inline(usize),          /* Calls of procedures costing at most the given number of instructions, or marked inline */
+    FrameElision            /* Marks leaf procedures without stack slots as frameless */
+}
+
+pub trait PassMethods {
+    fn name(&self) -> &'static str;
+    fn run(&self, module: &mut IrModule, imported: &[Function]) -> usize;
+}
+
+impl PassMethods for Pass {
+    fn name(&self) -> &'static str {
+        match self {
+            Pass::ConstantPropagation => "sccp",
+            Pass::DeadCodeElimination => "dce",
+            Pass::ValueNumbering => "gvn",
+            Pass::CopyPropagation => "copy-propagation",
+            Pass::SimplifyControlFlow => "simplify-cfg",
+            Pass::PromoteSlots => "mem2reg",
+            Pass::Inline( _ ) => "inline",
+            Pass::FrameElision => "frame-elision"
+        }
+    }
+
+    /// Optimize module, giving the number of changes made. Exported procedures of imported modules
+    /// marked inline can be inlined.
+    fn run(&self, module: &mut IrModule, imported: &[Function]) -> usize {
+        match self {
+            Pass::PromoteSlots => promote_slots(module),
+            Pass::Inline( threshold ) => inline_calls(module, imported, *threshold),
+            Pass::FrameElision => elide_frames(module),
+            _ => {
+                let mut changes = 0;
+                for function in module.functions.iter_mut() {
+                    changes += match self {
+                        Pass::ConstantPropagation => constant_propagation(function),
+                        Pass::DeadCodeElimination => dead_code_elimination(function),
+                        Pass::ValueNumbering => value_numbering(function),
+                        Pass::CopyPropagation => copy_propagation(function),
+                        _ => simplify_control_flow(function)
+                    }
+                }
+                changes
+            }
+        }
+    }
+}
+
+/// What a pass did over all its runs
+#[derive(Clone, PartialEq, Debug)]
+pub struct PassStatistics {
+    pub pass: Pass,
+    pub runs: usize,
+    pub changes: usize,
+    pub removed: i64        /* Instructions removed, negative when pass added instructions */
+}
+
+pub struct PassManager {
+    passes: Vec<Pass>,
+    imported: Vec<Function>,    /* Exported procedures marked inline of imported modules */
+    rounds: usize,          /* Most times pipeline is repeated while passes still make changes */
+    verify: bool,           /* Verify module after each pass */
+    statistics: Vec<PassStatistics>,
+    instructions: (usize, usize)
+}
+
+pub trait PassManagerMethods {
+    fn new(level: u8, verify: bool) -> Self;
+    fn import(&mut self, module: &IrModule) -> ();
+    fn run(&mut self, module: &mut IrModule) -> Result<(), Vec<Box<Diagnostic>>>;
+    fn report(&self) -> String;
+}
+
+impl PassManagerMethods for PassManager {
+    /// Pipeline of optimization level, 0 runs no passes, 1 runs each pass once and inlines only the smallest procedures,
+    /// 2 adds value numbering, inlines larger procedures and repeats the pipeline
+    fn new(level: u8, verify: bool) -> Self {
+        let ( passes , rounds ) = match level {
+            0 => ( Vec::new(), 0 ),
+            1 => ( vec![ Pass::SimplifyControlFlow, Pass::PromoteSlots, Pass::Inline(8), Pass::PromoteSlots, Pass::CopyPropagation, Pass::ConstantPropagation,
+                         Pass::DeadCodeElimination, Pass::SimplifyControlFlow, Pass::FrameElision ], 1 ),
+            _ => ( vec![ Pass::SimplifyControlFlow, Pass::PromoteSlots, Pass::CopyPropagation, Pass::ConstantPropagation, Pass::DeadCodeElimination,
+                         Pass::Inline(32), Pass::PromoteSlots, Pass::CopyPropagation, Pass::ConstantPropagation, Pass::ValueNumbering, Pass::CopyPropagation,
+                         Pass::DeadCodeElimination, Pass::SimplifyControlFlow, Pass::FrameElision ], 4 )
+        };
+
+        PassManager {
+            passes,
+            imported: Vec::new(),
+            rounds,
+            verify,
+            statistics: Vec::new(),
+            instructions: ( 0, 0 )
+        }
+    }
+
+    /// Exported procedures of module marked inline become candidates for inlining into modules importing it.
+    /// Procedures naming anything in their module that is not exported are left out.
+    fn import(&mut self, module: &IrModule) {
+        let prefix = format!("{}.", module.name);
+        let exported : HashSet<&String> = module.functions.iter().filter(|x| x.export).map(|x| &x.name)
+            .chain(module.globals.iter().filter(|x| x.export).map(|x| &x.name)).collect();
+
+        for function in module.functions.iter().filter(|x| x.export && x.inline) {
+            let private = function.blocks.iter().flat_map(|x| x.instructions.iter()).any(|x| {
+                match &x.operation {
+                    Operation::Call( Callee::Direct( name ) , _ ) | Operation::Call( Callee::Method( name ) , _ ) | Operation::GlobalAddress( name ) |
+                    Operation::ProcedureAddress( name ) => name.starts_with(prefix.as_str()) && !exported.contains(name),
+                    Operation::TypeDescriptor( name ) => name.starts_with(prefix.as_str()),
+                    _ => false
+                }
+            });
+            match private {
+                true => (),
+                _ => self.imported.push(function.clone())
+            }
+        }
+    }
+
+    /// Run pipeline over all functions of module, stops at first pass leaving module invalid when verifying
+    fn run(&mut self, module: &mut IrModule) -> Result<(), Vec<Box<Diagnostic>>> {
+        let count = |module: &IrModule| module.functions.iter().map(|x| x.instruction_count()).sum::<usize>();
+        self.instructions = ( count(module), count(module) );
+
+        for _ in 0 .. self.rounds {
+            let mut changed = 0;
+
+            for pass in self.passes.iter() {
+                let before = count(module);
+                let changes = pass.run(module, &self.imported);
+                let removed = before as i64 - count(module) as i64;
+
+                match self.statistics.iter_mut().find(|x| x.pass == *pass) {
+                    Some( x ) => {
+                        x.runs += 1;
+                        x.changes += changes;
+                        x.removed += removed
+                    },
+                    _ => self.statistics.push( PassStatistics { pass: *pass, runs: 1, changes, removed } )
+                }
+
+                if self.verify {
+                    let errors = verify_module(module, format!("pass '{}'", pass.name()).as_str());
+                    match errors.is_empty() {
+                        true => (),
+                        _ => return Err( errors )
+                    }
+                }
+
+                changed += changes
+            }
+
+            if changed == 0 {
+                break
+            }
+        }
+
+        self.instructions.1 = count(module);
+        Ok( () )
+    }
+
+    /// Table of passes with number of runs, changes made and how many instructions each removed or added
+    fn report(&self) -> String {
+        let mut text = format!("{:<20}{:>8}{:>10}{:>16}\r\n", "Pass", "Runs", "Changes", "Instructions");
+        for x in self.statistics.iter() {
+            let size = match x.removed {
+                y if y < 0 => format!("Added {}", -y),
+                y => format!("Removed {}", y)
+            };
+            text.push_str(format!("{:<20}{:>8}{:>10}{:>16}\r\n", x.pass.name(), x.runs, x.changes, size).as_str())
+        }
+        text.push_str(format!("Instructions: {} before, {} after\r\n", self.instructions.0, self.instructions.1).as_str());
+        text
+    }
+}
+
+/// Replace copies, conversions to the same type and phis with only one value by the register they give
+fn copy_propagation(function: &mut Function) -> usize {
+    let mut replacements = HashMap::<VReg, VReg>::new();
+
+    for block in function.blocks.iter() {
+        for instruction in block.instructions.iter() {
+            match ( instruction.result , &instruction.operation ) {
+                ( Some( r ) , Operation::Copy( x ) ) => {
+                    replacements.insert(r, *x);
+                },
+                ( Some( r ) , Operation::Convert( x ) ) if function.registers.get(r) == function.registers.get(*x) => {
+                    replacements.insert(r, *x);
+                },
+                ( Some( r ) , Operation::Phi( values ) ) => {
+                    let mut others : Vec<VReg> = values.iter().map(|x| x.1).filter(|x| *x != r).collect();
+                    others.dedup();
+                    if others.len() == 1 {
+                        replacements.insert(r, others[0]);
+                    }
+                },
+                _ => ()
+            }
+        }
+    }
+
+    match replacements.is_empty() {
+        true => 0,
+        _ => {
+            for block in function.blocks.iter_mut() {
+                block.instructions.retain(|x| !matches!(x.result, Some( r ) if replacements.contains_key(&r)))
+            }
+            function.replace_uses(&replacements);
+            replacements.len()
+        }
+    }
+}
+
+/// Remove pure instructions whose result is not used, until no more can be removed
+fn dead_code_elimination(function: &mut Function) -> usize {
+    let mut removed = 0;
+
+    loop {
+        let mut used = vec![ false; function.registers.len() ];
+        for block in function.blocks.iter() {
+            for instruction in block.instructions.iter() {
+                for operand in instruction.operation.operands() {
+                    if operand < used.len() {
+                        used[operand] = true
+                    }
+                }
+            }
+            match block.terminator.as_ref().and_then(|x| x.operand()) {
+                Some( x ) if x < used.len() => used[x] = true,
+                _ => ()
+            }
+        }
+
+        let mut count = 0;
+        for block in function.blocks.iter_mut() {
+            let before = block.instructions.len();
+            block.instructions.retain(|x| match x.result {
+                Some( r ) => used.get(r) == Some( &true ) || !x.operation.is_pure(),
+                _ => true
+            });
+            count += before - block.instructions.len()
+        }
+
+        match count {
+            0 => return removed,
+            x => removed += x
+        }
+    }
+}
+
+/// Operation read by value numbering, with registers already replaced and operands of commutative operators in order
+fn value_key(operation: &Operation, ir_type: IrType, replacements: &HashMap<VReg, VReg>) -> Option<String> {
+    let mut operation = operation.clone();
+    for operand in operation.operands_mut() {
+        if let Some( x ) = replacements.get(operand) {
+            *operand = *x
+        }
+    }
+
+    match &mut operation {
+        Operation::Binary( BinaryOp::Add , x , y ) | Operation::Binary( BinaryOp::Mul , x , y ) | Operation::Binary( BinaryOp::And , x , y ) |
+        Operation::Binary( BinaryOp::Or , x , y ) | Operation::Binary( BinaryOp::Xor , x , y ) |
+        Operation::Compare( CompareOp::Eq , x , y ) | Operation::Compare( CompareOp::Ne , x , y ) if *x > *y => std::mem::swap(x, y),
+        _ => ()
+    }
+
+    match operation {
+        Operation::Constant( _ ) | Operation::Unary( .. ) | Operation::Binary( .. ) | Operation::Compare( .. ) | Operation::Convert( _ ) |
+        Operation::SlotAddress( _ ) | Operation::OuterSlotAddress( .. ) | Operation::GlobalAddress( _ ) | Operation::ProcedureAddress( _ ) |
+        Operation::TypeDescriptor( _ ) | Operation::Offset( .. ) => Some( format!("{:?} {:?}", ir_type, operation) ),
+        _ => None
+    }
+}
+
+/// Blocks immediately dominated by each block, and blocks dominated by no other block
+fn dominator_tree(function: &Function) -> ( Vec<Vec<BlockId>>, Vec<BlockId> ) {
+    let dominators = function.dominators();
+    let reached = function.reachable();
+    let depth : Vec<usize> = dominators.iter().map(|x| x.iter().filter(|y| **y).count()).collect();
+    let mut children = vec![ Vec::<BlockId>::new(); function.blocks.len() ];
+    let mut roots = Vec::<BlockId>::new();
+
+    for block in 0 .. function.blocks.len() {
+        match reached[block] {
+            true => (),
+            _ => continue
+        }
+        /* Nearest dominator is the one with most dominators itself */
+        let parent = ( 0 .. function.blocks.len() ).filter(|x| *x != block && reached[*x] && dominators[block][*x]).max_by_key(|x| depth[*x]);
+        match parent {
+            Some( x ) => children[x].push(block),
+            _ => roots.push(block)
+        }
+    }
+
+    ( children, roots )
+}
+
+/// Instructions computing a value already computed in a dominating block are replaced by that value
+fn value_numbering(function: &mut Function) -> usize {
+    let ( children , roots ) = dominator_tree(function);
+    let mut available = HashMap::<String, VReg>::new();
+    let mut replacements = HashMap::<VReg, VReg>::new();
+
+    for root in roots.iter() {
+        /* Keys added by block are removed again when leaving it, so only dominating blocks provide values */
+        let mut stack = vec![ ( *root , None::<Vec<String>> ) ];
+        loop {
+            match stack.pop() {
+                Some( ( _ , Some( keys ) ) ) => {
+                    for key in keys.iter() {
+                        available.remove(key);
+                    }
+                },
+                Some( ( block , None ) ) => {
+                    let mut keys = Vec::<String>::new();
+                    for instruction in function.blocks[block].instructions.iter() {
+                        let result = match instruction.result {
+                            Some( x ) => x,
+                            _ => continue
+                        };
+                        let ir_type = function.registers.get(result).copied().unwrap_or(IrType::Void);
+                        if let Some( key ) = value_key(&instruction.operation, ir_type, &replacements) {
+                            match available.get(&key) {
+                                Some( x ) => {
+                                    replacements.insert(result, *x);
+                                },
+                                _ => {
+                                    available.insert(key.clone(), result);
+                                    keys.push(key)
+                                }
+                            }
+                        }
+                    }
+                    stack.push(( block, Some( keys ) ));
+                    for child in children[block].iter().rev() {
+                        stack.push(( *child, None ))
+                    }
+                },
+                None => break
+            }
+        }
+    }
+
+    match replacements.is_empty() {
+        true => 0,
+        _ => {
+            for block in function.blocks.iter_mut() {
+                block.instructions.retain(|x| !matches!(x.result, Some( r ) if replacements.contains_key(&r)))
+            }
+            function.replace_uses(&replacements);
+            replacements.len()
+        }
+    }
+}
+
+/// Keep local variables in registers when their slot address is only used to load and store whole values of one type.
+/// Slots of enclosing procedures used by nested procedures stay in memory.
+fn promote_slots(module: &mut IrModule) -> usize {
+    let parents : HashMap<String, Option<String>> = module.functions.iter().map(|x| ( x.name.clone(), x.parent.clone() )).collect();
+    let mut escaping = HashSet::<(String, SlotId)>::new();
+
+    for function in module.functions.iter() {
+        for instruction in function.blocks.iter().flat_map(|x| x.instructions.iter()) {
+            if let Operation::OuterSlotAddress( levels , slot ) = &instruction.operation {
+                let mut owner = function.parent.clone();
+                for _ in 1 .. *levels {
+                    owner = owner.and_then(|x| parents.get(&x).cloned().flatten())
+                }
+                if let Some( x ) = owner {
+                    escaping.insert(( x, *slot ));
+                }
+            }
+        }
+    }
+
+    let nested : HashSet<String> = module.functions.iter().filter_map(|x| x.parent.clone()).collect();
+    let mut changes = 0;
+    for function in module.functions.iter_mut() {
+        let escaping : HashSet<SlotId> = escaping.iter().filter(|x| x.0 == function.name).map(|x| x.1).collect();
+        changes += promote_function_slots(function, &escaping, nested.contains(&function.name))
+    }
+    changes
+}
+
+fn promote_function_slots(function: &mut Function, escaping: &HashSet<SlotId>, nested: bool) -> usize {
+    /* Trap may continue in 'FINALLY' from anywhere and 'CODE' reaches variables through the frame */
+    if function.finally.is_some() || function.blocks.iter().flat_map(|x| x.instructions.iter()).any(|x| matches!(x.operation, Operation::Code( _ ))) {
+        return 0
+    }
+    function.remove_unreachable_blocks();
+    match function.predecessors()[ENTRY_BLOCK].is_empty() {
+        true => (),
+        _ => return 0
+    }
+
+    /* Slot of each address register, and type of the values loaded and stored through it */
+    let mut addresses = HashMap::<VReg, SlotId>::new();
+    for instruction in function.blocks.iter().flat_map(|x| x.instructions.iter()) {
+        if let ( Some( r ) , Operation::SlotAddress( slot ) ) = ( instruction.result , &instruction.operation ) {
+            addresses.insert(r, *slot);
+        }
+    }
+
+    let mut candidates : HashMap<SlotId, Option<IrType>> = addresses.values().filter(|x| !escaping.contains(*x)).map(|x| ( *x, None )).collect();
+    let accessed = |slot: SlotId, ir_type: Option<IrType>, candidates: &mut HashMap<SlotId, Option<IrType>>| {
+        match ( candidates.get(&slot) , ir_type ) {
+            ( Some( None ) , Some( x ) ) => {
+                candidates.insert(slot, Some( x ));
+            },
+            ( Some( Some( x ) ) , Some( y ) ) if *x == y => (),
+            ( Some( _ ) , _ ) => {
+                candidates.remove(&slot);
+            },
+            _ => ()
+        }
+    };
+    for block in function.blocks.iter() {
+        for instruction in block.instructions.iter() {
+            let register_type = |x: VReg| function.registers.get(x).copied();
+            match &instruction.operation {
+                Operation::Load( a ) if addresses.contains_key(a) => accessed(addresses[a], instruction.result.and_then(register_type), &mut candidates),
+                Operation::Store( a , v ) if addresses.contains_key(a) && !addresses.contains_key(v) => accessed(addresses[a], register_type(*v), &mut candidates),
+                x => {
+                    for operand in x.operands().iter().filter(|y| addresses.contains_key(*y)) {
+                        accessed(addresses[operand], None, &mut candidates)
+                    }
+                }
+            }
+        }
+        match block.terminator.as_ref().and_then(|x| x.operand()) {
+            Some( x ) if addresses.contains_key(&x) => accessed(addresses[&x], None, &mut candidates),
+            _ => ()
+        }
+    }
+    candidates.retain(|slot, ir_type| {
+        match ir_type {
+            Some( x ) => *x != IrType::Void && x.size() == function.slots[*slot].size,
+            _ => true
+        }
+    });
+    if candidates.is_empty() {
+        return 0
+    }
+
+    /* Immediate dominators and dominance frontiers */
+    let ( children , _ ) = dominator_tree(function);
+    let mut idom = vec![ None; function.blocks.len() ];
+    for ( parent , list ) in children.iter().enumerate() {
+        for child in list.iter() {
+            idom[*child] = Some( parent )
+        }
+    }
+    let predecessors = function.predecessors();
+    let mut frontiers = vec![ HashSet::<BlockId>::new(); function.blocks.len() ];
+    for ( block , list ) in predecessors.iter().enumerate() {
+        match list.len() > 1 {
+            true => (),
+            _ => continue
+        }
+        for predecessor in list.iter() {
+            let mut runner = Some( *predecessor );
+            loop {
+                match runner {
+                    Some( x ) if runner != idom[block] => {
+                        frontiers[x].insert(block);
+                        runner = idom[x]
+                    },
+                    _ => break
+                }
+            }
+        }
+    }
+
+    /* Phis where stores to slot meet */
+    let mut phis = HashMap::<(BlockId, SlotId), VReg>::new();
+    let mut slots : Vec<SlotId> = candidates.keys().copied().collect();
+    slots.sort();
+    for slot in slots.iter() {
+        let ir_type = match candidates[slot] {
+            Some( x ) => x,
+            _ => continue
+        };
+        let mut work : Vec<BlockId> = ( 0 .. function.blocks.len() ).filter(|x| function.blocks[*x].instructions.iter().any(|y| {
+            matches!(&y.operation, Operation::Store( a , _ ) if addresses.get(a) == Some( slot ))
+        })).collect();
+        let mut defined : HashSet<BlockId> = work.iter().copied().collect();
+        while let Some( block ) = work.pop() {
+            let mut frontier : Vec<BlockId> = frontiers[block].iter().copied().collect();
+            frontier.sort();
+            for target in frontier {
+                if phis.contains_key(&( target, *slot )) {
+                    continue
+                }
+                let register = function.new_register(ir_type);
+                phis.insert(( target, *slot ), register);
+                if defined.insert(target) {
+                    work.push(target)
+                }
+            }
+        }
+    }
+
+    /* Value of slot where no store reaches is zero */
+    let mut initial = HashMap::<SlotId, VReg>::new();
+    let mut constants = Vec::<Instruction>::new();
+    for slot in slots.iter() {
+        if let Some( x ) = candidates[slot] {
+            let register = function.new_register(x);
+            let value = match x.is_float() {
+                true => Constant::Real(0.0),
+                _ => Constant::Integer(0)
+            };
+            constants.push( Instruction { result: Some( register ), operation: Operation::Constant(value) } );
+            initial.insert(*slot, register);
+        }
+    }
+
+    /* Rename in dominator tree order, each block starts with the values at end of its immediate dominator */
+    let mut outgoing = vec![ HashMap::<SlotId, VReg>::new(); function.blocks.len() ];
+    let mut replacements = HashMap::<VReg, VReg>::new();
+    let mut order = vec![ ENTRY_BLOCK ];
+    let mut index = 0;
+    while index < order.len() {
+        order.extend(children[order[index]].iter());
+        index += 1
+    }
+    for block in order.iter() {
+        let mut current = match idom[*block] {
+            Some( x ) => outgoing[x].clone(),
+            _ => initial.clone()
+        };
+        for slot in slots.iter() {
+            if let Some( x ) = phis.get(&( *block, *slot )) {
+                current.insert(*slot, *x);
+            }
+        }
+
+        let instructions = std::mem::take(&mut function.blocks[*block].instructions);
+        let mut kept = Vec::<Instruction>::with_capacity(instructions.len());
+        for instruction in instructions.into_iter() {
+            match ( instruction.result , &instruction.operation ) {
+                ( Some( r ) , Operation::Load( a ) ) if addresses.get(a).map(|x| candidates.contains_key(x)) == Some( true ) => {
+                    if let Some( x ) = current.get(&addresses[a]) {
+                        replacements.insert(r, *x);
+                    }
+                },
+                ( _ , Operation::Store( a , v ) ) if addresses.get(a).map(|x| candidates.contains_key(x)) == Some( true ) => {
+                    current.insert(addresses[a], *v);
+                },
+                ( _ , Operation::SlotAddress( slot ) ) if candidates.contains_key(slot) => (),
+                _ => kept.push(instruction)
+            }
+        }
+        function.blocks[*block].instructions = kept;
+        outgoing[*block] = current
+    }
+
+    /* Phis go first in their blocks, with the value at end of each predecessor */
+    let mut placed : Vec<(&(BlockId, SlotId), &VReg)> = phis.iter().collect();
+    placed.sort();
+    for ( ( block , slot ) , register ) in placed.into_iter().rev() {
+        let mut sources = predecessors[*block].clone();
+        sources.sort();
+        sources.dedup();
+        let values = sources.iter().map(|x| ( *x, outgoing[*x].get(slot).copied().unwrap_or(initial[slot]) )).collect();
+        function.blocks[*block].instructions.insert(0, Instruction { result: Some( *register ), operation: Operation::Phi(values) } )
+    }
+    for ( position , instruction ) in constants.into_iter().enumerate() {
+        function.blocks[ENTRY_BLOCK].instructions.insert(position, instruction)
+    }
+    function.replace_uses(&replacements);
+
+    /* Slots no longer used are left out of the frame, unless nested procedures number slots of this one */
+    match nested {
+        true => (),
+        _ => {
+            let mut used = vec![ false; function.slots.len() ];
+            for instruction in function.blocks.iter().flat_map(|x| x.instructions.iter()) {
+                if let Operation::SlotAddress( x ) = &instruction.operation {
+                    used[*x] = true
+                }
+            }
+            let mut numbers = Vec::<SlotId>::with_capacity(used.len());
+            let mut count = 0;
+            for x in used.iter() {
+                numbers.push(count);
+                count += *x as usize
+            }
+            let mut index = 0;
+            function.slots.retain(|_| {
+                index += 1;
+                used[index - 1]
+            });
+            for block in function.blocks.iter_mut() {
+                for instruction in block.instructions.iter_mut() {
+                    if let Operation::SlotAddress( x ) = &mut instruction.operation {
+                        *x = numbers[*x]
+                    }
+                }
+            }
+        }
+    }
+
+    candidates.len()
+}
+
+/// Value of register found by constant propagation
+#[derive(Clone, PartialEq, Debug)]
+enum Lattice {
+    Unknown,                /* No executable definition seen yet */
+    Constant(Constant),
+    Varying
+}
+
+fn meet(a: &Lattice, b: &Lattice) -> Lattice {
+    match ( a , b ) {
+        ( Lattice::Unknown , x ) | ( x , Lattice::Unknown ) => x.clone(),
+        ( Lattice::Constant( x ) , Lattice::Constant( y ) ) if x == y => a.clone(),
+        _ => Lattice::Varying
+    }
+}
+
+/// Value truncated to bits of type, sign extended for signed types
+fn normalize(value: i64, ir_type: IrType) -> i64 {
+    match ir_type {
+        IrType::Bool => ( value != 0 ) as i64,
+        IrType::I8 => value as i8 as i64,
+        IrType::I16 => value as i16 as i64,
+        IrType::I32 => value as i32 as i64,
+        IrType::U8 => value as u8 as i64,
+        IrType::U16 => value as u16 as i64,
+        IrType::U32 => value as u32 as i64,
+        _ => value
+    }
+}
+
+fn round(value: f64, ir_type: IrType) -> f64 {
+    match ir_type {
+        IrType::F32 => value as f32 as f64,
+        _ => value
+    }
+}
+
+fn mask(bits: u32) -> u64 {
+    match bits {
+        64 => u64::MAX,
+        x => ( 1u64 << x ) - 1
+    }
+}
+
+fn fold_unary(operator: UnaryOp, value: &Constant, ir_type: IrType) -> Option<Constant> {
+    match ( operator , value ) {
+        ( UnaryOp::Neg , Constant::Integer( x ) ) if ir_type.is_integer() => Some( Constant::Integer(normalize(x.wrapping_neg(), ir_type)) ),
+        ( UnaryOp::Neg , Constant::Real( x ) ) if ir_type.is_float() => Some( Constant::Real(round(-x, ir_type)) ),
+        ( UnaryOp::Not , Constant::Integer( x ) ) if ir_type == IrType::Bool => Some( Constant::Integer(( *x == 0 ) as i64) ),
+        ( UnaryOp::Not , Constant::Integer( x ) ) if ir_type.is_integer() => Some( Constant::Integer(normalize(!x, ir_type)) ),
+        _ => None
+    }
+}
+
+/// Result of binary operator like the target computes it, shifts by the width of type or more are left to the target
+fn fold_binary(operator: BinaryOp, left: &Constant, right: &Constant, ir_type: IrType) -> Option<Constant> {
+    match ( left , right ) {
+        ( Constant::Integer( a ) , Constant::Integer( b ) ) if ir_type.is_integer() || ir_type == IrType::Bool => {
+            let ( a , b ) = ( *a , *b );
+            let bits = ( ir_type.size() * 8 ) as u32;
+            let value = match operator {
+                BinaryOp::Add => a.wrapping_add(b),
+                BinaryOp::Sub => a.wrapping_sub(b),
+                BinaryOp::Mul => a.wrapping_mul(b),
+                BinaryOp::Div | BinaryOp::Mod if b == 0 => return None,
+                BinaryOp::Div | BinaryOp::Mod if ir_type == IrType::U64 => {
+                    match operator {
+                        BinaryOp::Div => ( ( a as u64 ) / ( b as u64 ) ) as i64,
+                        _ => ( ( a as u64 ) % ( b as u64 ) ) as i64
+                    }
+                },
+                BinaryOp::Div | BinaryOp::Mod => {
+                    /* Rounded towards minus infinity, remainder has the sign of divisor */
+                    let ( q , r ) = ( a.wrapping_div(b) , a.wrapping_rem(b) );
+                    let ( q , r ) = match r != 0 && ( r < 0 ) != ( b < 0 ) {
+                        true => ( q - 1, r + b ),
+                        _ => ( q, r )
+                    };
+                    match operator {
+                        BinaryOp::Div => q,
+                        _ => r
+                    }
+                },
+                BinaryOp::And => a & b,
+                BinaryOp::Or => a | b,
+                BinaryOp::Xor => a ^ b,
+                _ if b.unsigned_abs() >= bits as u64 => return None,
+                BinaryOp::Ash if b >= 0 => a.wrapping_shl(b as u32),
+                BinaryOp::Ash => a >> ( -b ),
+                BinaryOp::Lsh if b >= 0 => a.wrapping_shl(b as u32),
+                BinaryOp::Lsh => ( ( ( a as u64 ) & mask(bits) ) >> ( -b ) ) as i64,
+                BinaryOp::Rot => {
+                    let count = b.rem_euclid(bits as i64) as u32;
+                    let value = ( a as u64 ) & mask(bits);
+                    match count {
+                        0 => value as i64,
+                        x => ( ( ( value << x ) | ( value >> ( bits - x ) ) ) & mask(bits) ) as i64
+                    }
+                }
+            };
+            Some( Constant::Integer(normalize(value, ir_type)) )
+        },
+        ( Constant::Real( a ) , Constant::Real( b ) ) if ir_type.is_float() => {
+            let value = match operator {
+                BinaryOp::Add => a + b,
+                BinaryOp::Sub => a - b,
+                BinaryOp::Mul => a * b,
+                BinaryOp::Div => a / b,
+                _ => return None
+            };
+            Some( Constant::Real(round(value, ir_type)) )
+        },
+        _ => None
+    }
+}
+
+/// Comparison of operands of type, unsigned for unsigned 64 bits values and addresses
+fn fold_compare(operator: CompareOp, left: &Constant, right: &Constant, ir_type: IrType) -> Option<Constant> {
+    let ordering = match ( left , right ) {
+        ( Constant::Integer( a ) , Constant::Integer( b ) ) if ir_type == IrType::U64 || ir_type == IrType::Ptr => ( *a as u64 ).cmp(&( *b as u64 )),
+        ( Constant::Integer( a ) , Constant::Integer( b ) ) => a.cmp(b),
+        ( Constant::Real( a ) , Constant::Real( b ) ) => {
+            match a.partial_cmp(b) {
+                Some( x ) => x,
+                None => return Some( Constant::Integer(( operator == CompareOp::Ne ) as i64) )
+            }
+        },
+        _ => return None
+    };
+
+    let value = match operator {
+        CompareOp::Eq => ordering.is_eq(),
+        CompareOp::Ne => ordering.is_ne(),
+        CompareOp::Lt => ordering.is_lt(),
+        CompareOp::Le => ordering.is_le(),
+        CompareOp::Gt => ordering.is_gt(),
+        CompareOp::Ge => ordering.is_ge()
+    };
+    Some( Constant::Integer(value as i64) )
+}
+
+/// Conversion between types, floating point values outside of the integer type are left to the target
+fn fold_convert(value: &Constant, from: IrType, to: IrType) -> Option<Constant> {
+    match value {
+        Constant::Integer( x ) if to.is_float() => {
+            let real = match from {
+                IrType::U64 | IrType::Ptr => ( *x as u64 ) as f64,
+                _ => *x as f64
+            };
+            Some( Constant::Real(round(real, to)) )
+        },
+        Constant::Integer( x ) if to.is_integer() || to == IrType::Ptr => Some( Constant::Integer(normalize(*x, to)) ),
+        Constant::Real( x ) if to.is_float() => Some( Constant::Real(round(*x, to)) ),
+        Constant::Real( x ) if to.is_integer() => {
+            let floor = x.floor();
+            match floor.is_finite() && floor >= i64::MIN as f64 && floor < i64::MAX as f64 && normalize(floor as i64, to) as f64 == floor {
+                true => Some( Constant::Integer(floor as i64) ),
+                _ => None
+            }
+        },
+        _ => None
+    }
+}
+
+/// Sparse conditional constant propagation. Registers proven constant become constants, branches on constants
+/// become jumps and blocks only reached through branches never taken are removed.
+fn constant_propagation(function: &mut Function) -> usize {
+    let count = function.blocks.len();
+    let mut values = vec![ Lattice::Unknown; function.registers.len() ];
+    for parameter in function.parameters.iter() {
+        if *parameter < values.len() {
+            values[*parameter] = Lattice::Varying
+        }
+    }
+
+    /* Instructions and terminators reading each register, terminators as position after last instruction */
+    let mut uses = vec![ Vec::<(BlockId, usize)>::new(); function.registers.len() ];
+    for ( index , block ) in function.blocks.iter().enumerate() {
+        for ( position , instruction ) in block.instructions.iter().enumerate() {
+            for operand in instruction.operation.operands() {
+                if operand < uses.len() {
+                    uses[operand].push(( index, position ))
+                }
+            }
+        }
+        match block.terminator.as_ref().and_then(|x| x.operand()) {
+            Some( x ) if x < uses.len() => uses[x].push(( index, block.instructions.len() )),
+            _ => ()
+        }
+    }
+
+    let mut executable = vec![ false; count ];
+    let mut edges = HashSet::<(BlockId, BlockId)>::new();
+    let mut flow_work = vec![ ( None, ENTRY_BLOCK ) ];
+    if let Some( x ) = function.finally {
+        flow_work.push(( None, x ))
+    }
+    let mut value_work = Vec::<VReg>::new();
+
+    loop {
+        let ( block , positions ) = match flow_work.pop() {
+            Some( ( from , to ) ) => {
+                match from {
+                    Some( x ) if !edges.insert(( x, to )) => continue,
+                    _ => ()
+                }
+                let first = !executable[to];
+                executable[to] = true;
+                /* New edge changes phis, first visit evaluates whole block */
+                let positions : Vec<usize> = match first {
+                    true => ( 0 ..= function.blocks[to].instructions.len() ).collect(),
+                    _ => ( 0 .. function.blocks[to].instructions.len() ).filter(|x| matches!(function.blocks[to].instructions[*x].operation, Operation::Phi( _ ))).collect()
+                };
+                ( to, positions )
+            },
+            _ => {
+                match value_work.pop() {
+                    Some( register ) => {
+                        let positions = uses[register].iter().filter(|x| executable[x.0]).copied().collect::<Vec<_>>();
+                        for ( block , position ) in positions.iter() {
+                            evaluate(function, &mut values, &edges, &mut flow_work, &mut value_work, *block, *position)
+                        }
+                        continue
+                    },
+                    _ => break
+                }
+            }
+        };
+
+        for position in positions.iter() {
+            evaluate(function, &mut values, &edges, &mut flow_work, &mut value_work, block, *position)
+        }
+    }
+
+    /* Rewrite with the values found */
+    let mut changes = 0;
+    for ( index , block ) in function.blocks.iter_mut().enumerate() {
+        match executable[index] {
+            true => (),
+            _ => continue
+        }
+
+        for instruction in block.instructions.iter_mut() {
+            match ( instruction.result.and_then(|x| values.get(x)) , &instruction.operation ) {
+                ( _ , Operation::Constant( _ ) ) => (),
+                ( Some( Lattice::Constant( x ) ) , _ ) => {
+                    instruction.operation = Operation::Constant(x.clone());
+                    changes += 1
+                },
+                _ => ()
+            }
+        }
+        /* Phis replaced by constants must stay after the remaining phis */
+        block.instructions.sort_by_key(|x| !matches!(x.operation, Operation::Phi( _ )));
+
+        let target = match &block.terminator {
+            Some( Terminator::Branch( c , t , f ) ) => {
+                match values.get(*c) {
+                    Some( Lattice::Constant( Constant::Integer( x ) ) ) => Some( if *x != 0 { *t } else { *f } ),
+                    _ => None
+                }
+            },
+            Some( Terminator::Switch( v , cases , default ) ) => {
+                match values.get(*v) {
+                    Some( Lattice::Constant( Constant::Integer( x ) ) ) => Some( switch_target(*x, cases, *default) ),
+                    _ => None
+                }
+            },
+            _ => None
+        };
+        if let Some( x ) = target {
+            block.terminator = Some( Terminator::Jump(x) );
+            changes += 1
+        }
+    }
+
+    let before = function.blocks.len();
+    function.remove_unreachable_blocks();
+    changes + before - function.blocks.len()
+}
+
+fn switch_target(value: i64, cases: &[(i64, i64, BlockId)], default: BlockId) -> BlockId {
+    match cases.iter().find(|x| x.0 <= value && value <= x.1) {
+        Some( x ) => x.2,
+        _ => default
+    }
+}
+
+/// Evaluate instruction at position of block, or its terminator when position is past the last instruction
+fn evaluate(function: &Function, values: &mut [Lattice], edges: &HashSet<(BlockId, BlockId)>, flow_work: &mut Vec<(Option<BlockId>, BlockId)>,
+            value_work: &mut Vec<VReg>, block: BlockId, position: usize) {
+    let basic_block = &function.blocks[block];
+
+    match basic_block.instructions.get(position) {
+        Some( instruction ) => {
+            let result = match instruction.result {
+                Some( x ) if x < values.len() => x,
+                _ => return
+            };
+            let ir_type = function.registers[result];
+            let operand_type = |x: VReg| function.registers.get(x).copied().unwrap_or(IrType::Void);
+            let value = |x: VReg| values.get(x).cloned().unwrap_or(Lattice::Varying);
+
+            let operands = instruction.operation.operands();
+            let new = match &instruction.operation {
+                Operation::Phi( incoming ) => {
+                    incoming.iter().filter(|x| edges.contains(&( x.0 , block ))).fold(Lattice::Unknown, |a, x| meet(&a, &value(x.1)))
+                },
+                Operation::Constant( x ) => Lattice::Constant(x.clone()),
+                Operation::Copy( x ) => value(*x),
+                Operation::Unary( .. ) | Operation::Binary( .. ) | Operation::Compare( .. ) | Operation::Convert( _ ) => {
+                    let inputs : Vec<Lattice> = operands.iter().map(|x| value(*x)).collect();
+                    match ( inputs.contains(&Lattice::Varying) , inputs.contains(&Lattice::Unknown) ) {
+                        ( true , _ ) => Lattice::Varying,
+                        ( _ , true ) => Lattice::Unknown,
+                        _ => {
+                            let constants : Vec<Constant> = inputs.iter().filter_map(|x| match x { Lattice::Constant( y ) => Some( y.clone() ), _ => None }).collect();
+                            let folded = match &instruction.operation {
+                                Operation::Unary( operator , _ ) => fold_unary(*operator, &constants[0], ir_type),
+                                Operation::Binary( operator , _ , _ ) => fold_binary(*operator, &constants[0], &constants[1], ir_type),
+                                Operation::Compare( operator , x , _ ) => fold_compare(*operator, &constants[0], &constants[1], operand_type(*x)),
+                                Operation::Convert( x ) => fold_convert(&constants[0], operand_type(*x), ir_type),
+                                _ => None
+                            };
+                            match folded {
+                                Some( x ) => Lattice::Constant(x),
+                                _ => Lattice::Varying
+                            }
+                        }
+                    }
+                },
+                _ => Lattice::Varying
+            };
+
+            let new = meet(&values[result], &new);
+            match new == values[result] {
+                true => (),
+                _ => {
+                    values[result] = new;
+                    value_work.push(result)
+                }
+            }
+        },
+        _ => {
+            let targets = match &basic_block.terminator {
+                Some( Terminator::Branch( c , t , f ) ) => {
+                    match values.get(*c) {
+                        Some( Lattice::Unknown ) => Vec::new(),
+                        Some( Lattice::Constant( Constant::Integer( x ) ) ) => vec![ if *x != 0 { *t } else { *f } ],
+                        _ => vec![ *t, *f ]
+                    }
+                },
+                Some( Terminator::Switch( v , cases , default ) ) => {
+                    match values.get(*v) {
+                        Some( Lattice::Unknown ) => Vec::new(),
+                        Some( Lattice::Constant( Constant::Integer( x ) ) ) => vec![ switch_target(*x, cases, *default) ],
+                        _ => basic_block.terminator.as_ref().map(|x| x.successors()).unwrap_or_default()
+                    }
+                },
+                Some( x ) => x.successors(),
+                _ => Vec::new()
+            };
+            for target in targets {
+                flow_work.push(( Some( block ), target ))
+            }
+        }
+    }
+}
+
+/// Fold branches with one target or constant condition, skip empty blocks that only jump, and merge
+/// blocks into their only predecessor. Blocks no longer reached are removed.
+fn simplify_control_flow(function: &mut Function) -> usize {
+    let mut changes = 0;
+
+    loop {
+        let mut changed = 0;
+
+        let constants : HashMap<VReg, i64> = function.blocks.iter().flat_map(|x| x.instructions.iter()).filter_map(|x| match ( x.result , &x.operation ) {
+            ( Some( r ) , Operation::Constant( Constant::Integer( y ) ) ) => Some( ( r, *y ) ),
+            _ => None
+        }).collect();
+
+        for block in function.blocks.iter_mut() {
+            let target = match &block.terminator {
+                Some( Terminator::Branch( c , t , f ) ) => {
+                    match ( t == f , constants.get(c) ) {
+                        ( true , _ ) => Some( *t ),
+                        ( _ , Some( x ) ) => Some( if *x != 0 { *t } else { *f } ),
+                        _ => None
+                    }
+                },
+                Some( Terminator::Switch( v , cases , default ) ) => {
+                    match ( cases.iter().all(|x| x.2 == *default) , constants.get(v) ) {
+                        ( true , _ ) => Some( *default ),
+                        ( _ , Some( x ) ) => Some( switch_target(*x, cases, *default) ),
+                        _ => None
+                    }
+                },
+                _ => None
+            };
+            if let Some( x ) = target {
+                block.terminator = Some( Terminator::Jump(x) );
+                changed += 1
+            }
+        }
+
+        /* Empty block only jumping to a block without phis is skipped by its predecessors */
+        let predecessors = function.predecessors();
+        let thread = ( 0 .. function.blocks.len() ).find_map(|x| {
+            match ( &function.blocks[x].terminator , function.blocks[x].instructions.is_empty() ) {
+                ( Some( Terminator::Jump( y ) ) , true ) if *y != x && x != ENTRY_BLOCK && Some( x ) != function.finally && !predecessors[x].is_empty() &&
+                    !function.blocks[*y].instructions.iter().any(|z| matches!(z.operation, Operation::Phi( _ ))) => Some( ( x, *y ) ),
+                _ => None
+            }
+        });
+        if let Some( ( block , target ) ) = thread {
+            for predecessor in predecessors[block].iter() {
+                if let Some( x ) = function.blocks[*predecessor].terminator.as_mut() {
+                    x.replace_successor(block, target)
+                }
+            }
+            changed += 1
+        }
+
+        /* Block reached only by jump from its predecessor continues that block */
+        if changed == 0 {
+            let predecessors = function.predecessors();
+            let merge = ( 0 .. function.blocks.len() ).find(|x| {
+                match predecessors[*x].as_slice() {
+                    [ p ] => *p != *x && *x != ENTRY_BLOCK && Some( *x ) != function.finally &&
+                             function.blocks[*p].terminator == Some( Terminator::Jump(*x) ),
+                    _ => false
+                }
+            });
+            if let Some( block ) = merge {
+                let predecessor = predecessors[block][0];
+                merge_blocks(function, predecessor, block);
+                changed += 1
+            }
+        }
+
+        let before = function.blocks.len();
+        function.remove_unreachable_blocks();
+        changed += before - function.blocks.len();
+
+        match changed {
+            0 => return changes,
+            x => changes += x
+        }
+    }
+}
+
+/// Move instructions and terminator of block to the end of its only predecessor
+fn merge_blocks(function: &mut Function, predecessor: BlockId, block: BlockId) {
+    let mut replacements = HashMap::<VReg, VReg>::new();
+    let instructions = std::mem::take(&mut function.blocks[block].instructions);
+    let terminator = function.blocks[block].terminator.take();
+
+    for instruction in instructions.into_iter() {
+        match ( instruction.result , &instruction.operation ) {
+            ( Some( r ) , Operation::Phi( values ) ) => {
+                if let Some( x ) = values.first() {
+                    replacements.insert(r, x.1);
+                }
+            },
+            _ => function.blocks[predecessor].instructions.push(instruction)
+        }
+    }
+
+    /* Successors now continue from predecessor */
+    if let Some( x ) = &terminator {
+        for successor in x.successors().iter() {
+            for instruction in function.blocks[*successor].instructions.iter_mut() {
+                if let Operation::Phi( values ) = &mut instruction.operation {
+                    for value in values.iter_mut() {
+                        if value.0 == block {
+                            value.0 = predecessor
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    function.blocks[predecessor].terminator = terminator;
+    function.blocks[block].terminator = Some( Terminator::Trap(0) );
+    function.replace_uses(&replacements);
+}
+
+// Unittests for IR optimizer module
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{Parser, ParserMethods, BlockRules};
+    use crate::scanner::{Scanner, ScannerMethods};
+    use crate::type_checker::{TypeChecker, TypeCheckerMethods};
+    use crate::ir::{IrModule, IrModuleMethods, FunctionMethods, Operation, Terminator, Constant, BinaryOp, IrType};
+    use std::collections::HashMap;
+    use crate::ir_lowering::lower_module;
+    use crate::ir_text::{read_module, write_function};
+    use crate::ir_verifier::verify_module;
+    use crate::ir_optimizer::{Pass, PassMethods, PassManager, PassManagerMethods, fold_binary};
+
+    fn run(pass: Pass, text: &str) -> ( usize, String ) {
+        let mut module = read_module(text).unwrap();
+        let changes = pass.run(&mut module, &[]);
+        assert_eq!(verify_module(&module, pass.name()).len(), 0);
+        ( changes, write_function(&module.functions[0]) )
+    }
+
+    fn optimize(source: &'static str, level: u8) -> ( IrModule, PassManager ) {
+        let mut checker = TypeChecker::new();
+        let module = Parser::new(Box::new(Scanner::new(source))).parse_module().unwrap();
+        checker.check_modules(&[ &*module ]);
+        let mut ir = lower_module(&checker, &module, &HashMap::new()).unwrap();
+        let mut manager = PassManager::new(level, true);
+        manager.run(&mut ir).unwrap();
+        ( ir, manager )
+    }
+
+    #[test]
+    fn folding_follows_oberon_semantics() {
+        assert_eq!(fold_binary(BinaryOp::Div, &Constant::Integer(-7), &Constant::Integer(2), IrType::I32), Some( Constant::Integer(-4) ));
+        assert_eq!(fold_binary(BinaryOp::Mod, &Constant::Integer(-7), &Constant::Integer(2), IrType::I32), Some( Constant::Integer(1) ));
+        assert_eq!(fold_binary(BinaryOp::Div, &Constant::Integer(1), &Constant::Integer(0), IrType::I32), None);
+        assert_eq!(fold_binary(BinaryOp::Add, &Constant::Integer(32767), &Constant::Integer(1), IrType::I16), Some( Constant::Integer(-32768) ));
+        assert_eq!(fold_binary(BinaryOp::Lsh, &Constant::Integer(0xffffffff), &Constant::Integer(-28), IrType::U32), Some( Constant::Integer(15) ));
+        assert_eq!(fold_binary(BinaryOp::Ash, &Constant::Integer(-16), &Constant::Integer(-2), IrType::I32), Some( Constant::Integer(-4) ));
+        assert_eq!(fold_binary(BinaryOp::Rot, &Constant::Integer(0x81), &Constant::Integer(1), IrType::U8), Some( Constant::Integer(3) ));
+        assert_eq!(fold_binary(BinaryOp::Lsh, &Constant::Integer(1), &Constant::Integer(32), IrType::U32), None)
+    }
+
+    #[test]
+    fn constant_propagation_folds_branches() {
+        let ( changes , text ) = run(Pass::ConstantPropagation, "module Test function P(%0: i32) -> i32 {
+            b0:
+              %1: i32 = iconst 2
+              %2: i32 = iconst 3
+              %3: i32 = mul %1, %2
+              %4: bool = cmp eq %3, %2
+              branch %4, b1, b2
+            b1:
+              %5: i32 = add %0, %1
+              jump b3
+            b2:
+              %6: i32 = add %3, %1
+              jump b3
+            b3:
+              %7: i32 = phi [b1: %5], [b2: %6]
+              return %7
+            }");
+
+        assert!(changes > 0);
+        assert!(text.contains("%7: i32 = iconst 8"));
+        assert!(text.contains("%4: bool = iconst 0"));
+        assert!(!text.contains("branch"));
+        assert!(!text.contains("%5"))
+    }
+
+    #[test]
+    fn constant_propagation_through_loop() {
+        /* Value is the same around the loop, so the phi is constant */
+        let ( _ , text ) = run(Pass::ConstantPropagation, "module Test function P(%0: bool) -> i32 {
+            b0:
+              %1: i32 = iconst 4
+              jump b1
+            b1:
+              %2: i32 = phi [b0: %1], [b1: %3]
+              %3: i32 = copy %2
+              branch %0, b1, b2
+            b2:
+              return %2
+            }");
+
+        assert!(text.contains("%2: i32 = iconst 4"));
+        assert!(text.contains("%3: i32 = iconst 4"))
+    }
+
+    #[test]
+    fn dead_code_is_removed() {
+        let ( changes , text ) = run(Pass::DeadCodeElimination, "module Test function P(%0: ptr) -> void {
+            b0:
+              %1: i32 = iconst 1
+              %2: i32 = add %1, %1
+              %3: i32 = load %0
+              %4: i32 = call Test.Q()
+              store %0, %1
+              return
+            }");
+
+        assert_eq!(changes, 2);
+        assert!(text.contains("call Test.Q"));
+        assert!(text.contains("%1: i32 = iconst 1"))
+    }
+
+    #[test]
+    fn value_numbering_removes_common_subexpressions() {
+        let ( changes , text ) = run(Pass::ValueNumbering, "module Test function P(%0: i32, %1: i32, %2: bool) -> i32 {
+            b0:
+              %3: i32 = add %0, %1
+              branch %2, b1, b2
+            b1:
+              %4: i32 = add %1, %0
+              %5: i32 = mul %4, %4
+              return %5
+            b2:
+              %6: i32 = add %0, %1
+              %8: ptr = globaladdr Test.g
+              %9: ptr = globaladdr Test.g
+              %10: i32 = load %9
+              %11: i32 = add %6, %10
+              return %11
+            }");
+
+        assert_eq!(changes, 3);
+        assert!(text.contains("%5: i32 = mul %3, %3"));
+        assert!(text.contains("%11: i32 = add %3, %10"));
+        assert!(text.contains("%10: i32 = load %8"))
+    }
+
+    #[test]
+    fn copies_are_propagated() {
+        let ( changes , text ) = run(Pass::CopyPropagation, "module Test function P(%0: i32) -> i32 {
+            b0:
+              %1: i32 = copy %0
+              %2: i32 = convert %1
+              %3: i64 = convert %2
+              jump b1
+            b1:
+              %4: i32 = phi [b0: %2]
+              return %4
+            }");
+
+        assert_eq!(changes, 3);
+        assert!(text.contains("%3: i64 = convert %0"));
+        assert!(text.contains("return %0"))
+    }
+
+    #[test]
+    fn control_flow_is_simplified() {
+        let ( _ , text ) = run(Pass::SimplifyControlFlow, "module Test function P(%0: bool) -> void {
+            b0:
+              branch %0, b1, b1
+            b1:
+              jump b2
+            b2:
+              %1: i32 = iconst 1
+              %2: bool = iconst 0
+              branch %2, b3, b4
+            b3:
+              trap 8
+            b4:
+              return
+            }");
+
+        assert_eq!(text, "function P(%0: bool) -> void {\nb0:\n  %1: i32 = iconst 1\n  %2: bool = iconst 0\n  return\n}\n")
+    }
+
+    #[test]
+    fn slots_are_promoted_to_registers() {
+        let ( changes , text ) = run(Pass::PromoteSlots, "module Test function P(%0: i32, %1: bool) -> i32 {
+              slot s0 x size 4 align 4
+              slot s1 a size 16 align 8
+              slot s2 i size 4 align 4
+            b0:
+              %2: ptr = slotaddr s0
+              store %2, %0
+              %3: ptr = slotaddr s1
+              %4: i64 = iconst 8
+              %5: ptr = offset %3, %4
+              jump b1
+            b1:
+              %6: i32 = load %2
+              %7: i32 = add %6, %6
+              store %2, %7
+              %8: ptr = slotaddr s2
+              %9: i32 = load %8
+              branch %1, b1, b2
+            b2:
+              %10: i32 = load %2
+              return %10
+            }");
+
+        assert_eq!(changes, 2);
+        assert!(text.contains("slot s0 a size 16 align 8\n"));
+        assert!(!text.contains("slot s1"));
+        assert!(text.contains("%11: i32 = phi [b0: %0], [b1: %7]"));
+        assert!(text.contains("%7: i32 = add %11, %11"));
+        assert!(text.contains("%3: ptr = slotaddr s0"));
+        assert!(text.contains("return %7"))
+    }
+
+    #[test]
+    fn pipeline_with_statistics() {
+        let source = "MODULE Test; VAR g: INTEGER; PROCEDURE P(x: INTEGER): INTEGER; CONST N = 4; VAR k: INTEGER; BEGIN k := N * 2; IF k > 10 THEN x := x + 1 END; RETURN x * k + x * k END P; BEGIN g := P(3) END Test.";
+        let ( unoptimized , _ ) = optimize(source, 0);
+        let ( ir , manager ) = optimize(source, 2);
+        let before = unoptimized.function("Test.P").unwrap();
+        let after = ir.function("Test.P").unwrap();
+
+        assert!(after.instruction_count() < before.instruction_count());
+        assert!(after.blocks.len() < before.blocks.len());
+        assert!(!after.blocks.iter().any(|x| matches!(x.terminator, Some( Terminator::Branch( .. ) ))));
+        assert!(after.slots.is_empty() && after.frameless);
+        assert!(after.blocks.iter().flat_map(|x| x.instructions.iter()).any(|x| x.operation == Operation::Constant(Constant::Integer(8))));
+        /* Call is inlined and folded */
+        let body = ir.function("Test.$Body").unwrap();
+        assert!(!body.blocks.iter().flat_map(|x| x.instructions.iter()).any(|x| matches!(x.operation, Operation::Call( .. ))));
+        assert!(body.blocks.iter().flat_map(|x| x.instructions.iter()).any(|x| x.operation == Operation::Constant(Constant::Integer(48))));
+        assert_eq!(manager.statistics.iter().map(|x| x.pass).collect::<Vec<_>>(),
+                   vec![ Pass::SimplifyControlFlow, Pass::PromoteSlots, Pass::CopyPropagation, Pass::ConstantPropagation, Pass::DeadCodeElimination,
+                         Pass::Inline(32), Pass::ValueNumbering, Pass::FrameElision ]);
+        assert!(manager.statistics.iter().all(|x| x.runs >= 1));
+        assert!(manager.report().contains("sccp"));
+        /* Inlining grows the procedure it inlines into */
+        let inline = manager.statistics.iter().find(|x| x.pass == Pass::Inline(32)).unwrap();
+        let report = manager.report();
+        let line = report.lines().find(|x| x.starts_with("inline")).unwrap();
+        assert!(inline.removed < 0);
+        assert!(line.ends_with(format!("Added {}", -inline.removed).as_str()));
+        assert!(!line.contains('-'))
+    }
+}
@@ -0,0 +1,952 @@
+// ActiveOberon Compiler, a native ARM v8 & X86-64 compiler & Risc V / linker / builder utility.
+// Written by Richard Magnor Stenbro. Licensed under GPL v3
+// IR text module, writes intermediate representation as text and reads it back for tests and bug reports
+
+use crate::diagnostics::{Diagnostic, DiagnosticMethods, E_IR_SYNTAX};
+use crate::ir::{IrModule, IrModuleMethods, Function, FunctionMethods, Global, IrType, VReg, BlockId, SlotId,
+                Instruction, Operation, Terminator, Constant, Callee, UnaryOp, BinaryOp, CompareOp};
+
+/*
+    Text of module, ';' starts a comment running to end of line:
+
+    module Test
+
+    global export Test.g size 2 align 2
+    global Test.$String0 size 3 align 1 data "686900"
+
+    function export inline Test.P(%0: i16, %1: ptr) -> i16 parent Test.Q finally b2 {
+      slot s0 n size 2 align 2
+    b0:
+      %2: ptr = slotaddr s0
+      store %2, %0
+      jump b1
+      ...
+    }
+
+    Names holding other characters than letters, digits, '_', '.' and '$' are quoted, like Test."+" written as "Test.\"+\"".
+*/
+
+/// Text of module, reading it back gives the same module except for registers no instruction defines
+pub fn write_module(module: &IrModule) -> String {
+    let mut text = format!("module {}\n", name(&module.name));
+
+    match module.globals.is_empty() {
+        true => (),
+        _ => text.push('\n')
+    }
+    for global in module.globals.iter() {
+        text.push_str(format!("global {}{} size {} align {}", export(global.export), name(&global.name), global.size, global.align).as_str());
+        if let Some( data ) = &global.data {
+            text.push_str(format!(" data \"{}\"", hex(data)).as_str())
+        }
+        text.push('\n')
+    }
+
+    for function in module.functions.iter() {
+        text.push('\n');
+        text.push_str(write_function(function).as_str())
+    }
+
+    text
+}
+
+pub fn write_function(function: &Function) -> String {
+    let parameters : Vec<String> = function.parameters.iter().map(|x| format!("%{}: {}", x, type_name(register_type(function, *x)))).collect();
+    let mut text = format!("function {}{}{}{}({}) -> {}", export(function.export), flag(function.inline, "inline"), flag(function.frameless, "frameless"),
+                           name(&function.name), parameters.join(", "), type_name(function.result));
+    if let Some( x ) = &function.parent {
+        text.push_str(format!(" parent {}", name(x)).as_str())
+    }
+    if let Some( x ) = function.finally {
+        text.push_str(format!(" finally b{}", x).as_str())
+    }
+    text.push_str(" {\n");
+
+    for ( index , slot ) in function.slots.iter().enumerate() {
+        text.push_str(format!("  slot s{} {} size {} align {}\n", index, name(&slot.name), slot.size, slot.align).as_str())
+    }
+
+    for ( index , block ) in function.blocks.iter().enumerate() {
+        text.push_str(format!("b{}:\n", index).as_str());
+        for instruction in block.instructions.iter() {
+            text.push_str("  ");
+            if let Some( x ) = instruction.result {
+                text.push_str(format!("%{}: {} = ", x, type_name(register_type(function, x))).as_str())
+            }
+            text.push_str(operation(&instruction.operation).as_str());
+            text.push('\n')
+        }
+        if let Some( x ) = &block.terminator {
+            text.push_str("  ");
+            text.push_str(terminator(x).as_str());
+            text.push('\n')
+        }
+    }
+
+    text.push_str("}\n");
+    text
+}
+
+fn register_type(function: &Function, register: VReg) -> IrType {
+    function.registers.get(register).copied().unwrap_or(IrType::Void)
+}
+
+fn export(exported: bool) -> &'static str {
+    match exported {
+        true => "export ",
+        _ => ""
+    }
+}
+
+fn flag(set: bool, keyword: &str) -> String {
+    match set {
+        true => format!("{} ", keyword),
+        _ => String::new()
+    }
+}
+
+fn is_name_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_' || c == '$'
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '$'
+}
+
+/// Name as written, quoted when it holds other characters
+fn name(text: &str) -> String {
+    match text.starts_with(is_name_start) && text.chars().all(is_name_char) {
+        true => String::from(text),
+        _ => format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+}
+
+fn type_name(ir_type: IrType) -> &'static str {
+    match ir_type {
+        IrType::Void => "void",
+        IrType::Bool => "bool",
+        IrType::I8 => "i8",
+        IrType::I16 => "i16",
+        IrType::I32 => "i32",
+        IrType::I64 => "i64",
+        IrType::U8 => "u8",
+        IrType::U16 => "u16",
+        IrType::U32 => "u32",
+        IrType::U64 => "u64",
+        IrType::F32 => "f32",
+        IrType::F64 => "f64",
+        IrType::Ptr => "ptr"
+    }
+}
+
+fn binary_name(operator: BinaryOp) -> &'static str {
+    match operator {
+        BinaryOp::Add => "add",
+        BinaryOp::Sub => "sub",
+        BinaryOp::Mul => "mul",
+        BinaryOp::Div => "div",
+        BinaryOp::Mod => "mod",
+        BinaryOp::And => "and",
+        BinaryOp::Or => "or",
+        BinaryOp::Xor => "xor",
+        BinaryOp::Ash => "ash",
+        BinaryOp::Lsh => "lsh",
+        BinaryOp::Rot => "rot"
+    }
+}
+
+fn compare_name(operator: CompareOp) -> &'static str {
+    match operator {
+        CompareOp::Eq => "eq",
+        CompareOp::Ne => "ne",
+        CompareOp::Lt => "lt",
+        CompareOp::Le => "le",
+        CompareOp::Gt => "gt",
+        CompareOp::Ge => "ge"
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|x| format!("{:02x}", x)).collect()
+}
+
+fn registers(values: &[VReg]) -> String {
+    values.iter().map(|x| format!("%{}", x)).collect::<Vec<String>>().join(", ")
+}
+
+fn operation(operation: &Operation) -> String {
+    match operation {
+        Operation::Constant( Constant::Integer( x ) ) => format!("iconst {}", x),
+        Operation::Constant( Constant::Real( x ) ) => format!("fconst {:?}", x),
+        Operation::Copy( x ) => format!("copy %{}", x),
+        Operation::Unary( UnaryOp::Neg , x ) => format!("neg %{}", x),
+        Operation::Unary( UnaryOp::Not , x ) => format!("not %{}", x),
+        Operation::Binary( op , l , r ) => format!("{} %{}, %{}", binary_name(*op), l, r),
+        Operation::Compare( op , l , r ) => format!("cmp {} %{}, %{}", compare_name(*op), l, r),
+        Operation::Convert( x ) => format!("convert %{}", x),
+        Operation::Phi( values ) => {
+            let values : Vec<String> = values.iter().map(|x| format!("[b{}: %{}]", x.0, x.1)).collect();
+            format!("phi {}", values.join(", "))
+        },
+        Operation::SlotAddress( x ) => format!("slotaddr s{}", x),
+        Operation::OuterSlotAddress( levels , x ) => format!("outerslotaddr {}, s{}", levels, x),
+        Operation::GlobalAddress( x ) => format!("globaladdr {}", name(x)),
+        Operation::ProcedureAddress( x ) => format!("procaddr {}", name(x)),
+        Operation::TypeDescriptor( x ) => format!("typedesc {}", name(x)),
+        Operation::Offset( a , o ) => format!("offset %{}, %{}", a, o),
+        Operation::Load( x ) => format!("load %{}", x),
+        Operation::Store( a , v ) => format!("store %{}, %{}", a, v),
+        Operation::CopyMemory( d , s , size ) => format!("copymem %{}, %{}, {}", d, s, size),
+        Operation::Call( Callee::Direct( x ) , arguments ) => format!("call {}({})", name(x), registers(arguments)),
+        Operation::Call( Callee::Method( x ) , arguments ) => format!("call method {}({})", name(x), registers(arguments)),
+        Operation::Call( Callee::Indirect( x ) , arguments ) => format!("call %{}({})", x, registers(arguments)),
+        Operation::TypeTest( x , t ) => format!("typetest %{}, {}", x, name(t)),
+        Operation::Code( bytes ) => format!("code \"{}\"", hex(bytes))
+    }
+}
+
+fn terminator(terminator: &Terminator) -> String {
+    match terminator {
+        Terminator::Jump( x ) => format!("jump b{}", x),
+        Terminator::Branch( c , t , f ) => format!("branch %{}, b{}, b{}", c, t, f),
+        Terminator::Switch( v , cases , default ) => {
+            let cases : Vec<String> = cases.iter().map(|x| format!("{} .. {}: b{}", x.0, x.1, x.2)).collect();
+            format!("switch %{} [{}] default b{}", v, cases.join(", "), default)
+        },
+        Terminator::Return( Some( x ) ) => format!("return %{}", x),
+        Terminator::Return( None ) => String::from("return"),
+        Terminator::Trap( x ) => format!("trap {}", x)
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+enum Token {
+    Name(String),
+    Quoted(String),
+    Register(VReg),
+    Integer(i64),
+    Real(f64),
+    Symbol(&'static str),
+    End
+}
+
+/// Tokens of text with their start and end positions
+fn tokens(text: &str) -> Result<Vec<(Token, u32, u32)>, Box<Diagnostic>> {
+    let chars : Vec<(usize, char)> = text.char_indices().collect();
+    let position = |x: usize| match chars.get(x) { Some( y ) => y.0 as u32, _ => text.len() as u32 };
+    let mut result = Vec::<(Token, u32, u32)>::new();
+    let mut index = 0;
+
+    while let Some( x ) = chars.get(index) {
+        let c = x.1;
+        let start = index;
+
+        let token = match c {
+            ' ' | '\t' | '\r' | '\n' => {
+                index += 1;
+                continue
+            },
+            ';' => {
+                loop {
+                    match chars.get(index) {
+                        Some( ( _ , '\n' ) ) | None => break,
+                        _ => index += 1
+                    }
+                }
+                continue
+            },
+            '%' => {
+                index += 1;
+                let first = index;
+                loop {
+                    match chars.get(index) {
+                        Some( ( _ , x ) ) if x.is_ascii_digit() => index += 1,
+                        _ => break
+                    }
+                }
+                match chars[first .. index].iter().map(|x| x.1).collect::<String>().parse::<VReg>() {
+                    Ok( x ) => Token::Register(x),
+                    _ => return Err( Diagnostic::error(E_IR_SYNTAX, String::from("Expecting number of register after '%'"), position(start), position(index)) )
+                }
+            },
+            '"' => {
+                index += 1;
+                let mut value = String::new();
+                loop {
+                    match chars.get(index).map(|x| x.1) {
+                        Some( '"' ) => {
+                            index += 1;
+                            break
+                        },
+                        Some( '\\' ) => {
+                            if let Some( ( _ , x ) ) = chars.get(index + 1) {
+                                value.push(*x)
+                            }
+                            index += 2
+                        },
+                        Some( x ) => {
+                            value.push(x);
+                            index += 1
+                        },
+                        None => return Err( Diagnostic::error(E_IR_SYNTAX, String::from("Quoted name is not closed"), position(start), position(index)) )
+                    }
+                }
+                Token::Quoted(value)
+            },
+            x if x.is_ascii_digit() || ( x == '-' && chars.get(index + 1).map(|y| y.1.is_ascii_digit()) == Some( true ) ) => {
+                index += 1;
+                let mut real = false;
+                loop {
+                    match ( chars.get(index).map(|y| y.1) , chars.get(index + 1).map(|y| y.1) ) {
+                        ( Some( y ) , _ ) if y.is_ascii_alphanumeric() && y != 'e' => index += 1,
+                        ( Some( '.' ) , Some( z ) ) if z.is_ascii_digit() => {
+                            real = true;
+                            index += 2
+                        },
+                        ( Some( 'e' ) , Some( z ) ) if z.is_ascii_digit() || z == '-' => {
+                            real = true;
+                            index += 2
+                        },
+                        _ => break
+                    }
+                }
+                let number = chars[start .. index].iter().map(|y| y.1).collect::<String>();
+                let token = match real {
+                    true => number.parse::<f64>().map(Token::Real).ok(),
+                    _ => number.parse::<i64>().map(Token::Integer).ok()
+                };
+                match token {
+                    Some( y ) => y,
+                    _ => return Err( Diagnostic::error(E_IR_SYNTAX, format!("Invalid number '{}'", number), position(start), position(index)) )
+                }
+            },
+            x if is_name_start(x) => {
+                loop {
+                    match chars.get(index) {
+                        Some( ( _ , y ) ) if is_name_char(*y) => index += 1,
+                        _ => break
+                    }
+                }
+                Token::Name(chars[start .. index].iter().map(|y| y.1).collect())
+            },
+            _ => {
+                let next = chars.get(index + 1).map(|y| y.1);
+                let symbol = match ( c , next ) {
+                    ( '-' , Some( '>' ) ) => "->",
+                    ( '.' , Some( '.' ) ) => "..",
+                    ( '(' , _ ) => "(",
+                    ( ')' , _ ) => ")",
+                    ( '[' , _ ) => "[",
+                    ( ']' , _ ) => "]",
+                    ( '{' , _ ) => "{",
+                    ( '}' , _ ) => "}",
+                    ( ',' , _ ) => ",",
+                    ( ':' , _ ) => ":",
+                    ( '=' , _ ) => "=",
+                    ( '-' , _ ) => "-",
+                    _ => return Err( Diagnostic::error(E_IR_SYNTAX, format!("Unexpected character '{}'", c), position(start), position(index + 1)) )
+                };
+                index += symbol.len();
+                Token::Symbol(symbol)
+            }
+        };
+
+        result.push(( token, position(start), position(index) ))
+    }
+
+    let end = text.len() as u32;
+    result.push(( Token::End, end, end ));
+    Ok( result )
+}
+
+struct Reader {
+    tokens: Vec<(Token, u32, u32)>,
+    index: usize
+}
+
+/// Module from its text, the first error found is given with its position in text
+pub fn read_module(text: &str) -> Result<IrModule, Box<Diagnostic>> {
+    let mut reader = Reader { tokens: tokens(text)?, index: 0 };
+
+    reader.keyword("module")?;
+    let mut module = IrModule::new(reader.name()?.as_str());
+
+    loop {
+        match reader.peek() {
+            Token::Name( x ) if x == "global" => {
+                reader.advance();
+                let export = reader.optional_keyword("export");
+                let name = reader.name()?;
+                reader.keyword("size")?;
+                let size = reader.integer()?;
+                reader.keyword("align")?;
+                let align = reader.integer()?;
+                let data = match reader.optional_keyword("data") {
+                    true => Some( reader.bytes()? ),
+                    _ => None
+                };
+                module.globals.push( Global { name, export, size, align, data } )
+            },
+            Token::Name( x ) if x == "function" => {
+                reader.advance();
+                let function = reader.function()?;
+                module.functions.push(function)
+            },
+            Token::End => break,
+            _ => return Err( reader.error("Expecting 'global' or 'function'") )
+        }
+    }
+
+    Ok( module )
+}
+
+impl Reader {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.index].0
+    }
+
+    fn advance(&mut self) {
+        match self.tokens[self.index].0 {
+            Token::End => (),
+            _ => self.index += 1
+        }
+    }
+
+    fn error(&self, message: &str) -> Box<Diagnostic> {
+        let ( _ , start , end ) = &self.tokens[self.index];
+        Diagnostic::error(E_IR_SYNTAX, String::from(message), *start, *end)
+    }
+
+    fn keyword(&mut self, keyword: &str) -> Result<(), Box<Diagnostic>> {
+        match self.optional_keyword(keyword) {
+            true => Ok( () ),
+            _ => Err( self.error(format!("Expecting '{}'", keyword).as_str()) )
+        }
+    }
+
+    fn optional_keyword(&mut self, keyword: &str) -> bool {
+        match self.peek() {
+            Token::Name( x ) if x == keyword => {
+                self.advance();
+                true
+            },
+            _ => false
+        }
+    }
+
+    fn symbol(&mut self, symbol: &str) -> Result<(), Box<Diagnostic>> {
+        match self.optional_symbol(symbol) {
+            true => Ok( () ),
+            _ => Err( self.error(format!("Expecting '{}'", symbol).as_str()) )
+        }
+    }
+
+    fn optional_symbol(&mut self, symbol: &str) -> bool {
+        match self.peek() {
+            Token::Symbol( x ) if *x == symbol => {
+                self.advance();
+                true
+            },
+            _ => false
+        }
+    }
+
+    fn name(&mut self) -> Result<String, Box<Diagnostic>> {
+        match self.peek().clone() {
+            Token::Name( x ) | Token::Quoted( x ) => {
+                self.advance();
+                Ok( x )
+            },
+            _ => Err( self.error("Expecting name") )
+        }
+    }
+
+    fn integer(&mut self) -> Result<i64, Box<Diagnostic>> {
+        match self.peek().clone() {
+            Token::Integer( x ) => {
+                self.advance();
+                Ok( x )
+            },
+            _ => Err( self.error("Expecting integer") )
+        }
+    }
+
+    /// Quoted text of two hexadecimal digits for each byte
+    fn bytes(&mut self) -> Result<Vec<u8>, Box<Diagnostic>> {
+        let text = match self.peek().clone() {
+            Token::Quoted( x ) => x,
+            _ => return Err( self.error("Expecting quoted hexadecimal bytes") )
+        };
+        let digits : Vec<char> = text.chars().collect();
+        let bytes : Option<Vec<u8>> = digits.chunks(2).map(|x| match x.len() {
+            2 => u8::from_str_radix(x.iter().collect::<String>().as_str(), 16).ok(),
+            _ => None
+        }).collect();
+        match bytes {
+            Some( x ) => {
+                self.advance();
+                Ok( x )
+            },
+            _ => Err( self.error("Expecting two hexadecimal digits for each byte") )
+        }
+    }
+
+    fn register(&mut self) -> Result<VReg, Box<Diagnostic>> {
+        match self.peek().clone() {
+            Token::Register( x ) => {
+                self.advance();
+                Ok( x )
+            },
+            _ => Err( self.error("Expecting register") )
+        }
+    }
+
+    /// Block or slot written as letter followed by its number
+    fn numbered(&mut self, prefix: char, what: &str) -> Result<usize, Box<Diagnostic>> {
+        let number = match self.peek() {
+            Token::Name( x ) if x.starts_with(prefix) => x[1 ..].parse::<usize>().ok(),
+            _ => None
+        };
+        match number {
+            Some( x ) => {
+                self.advance();
+                Ok( x )
+            },
+            _ => Err( self.error(format!("Expecting {}", what).as_str()) )
+        }
+    }
+
+    fn block(&mut self) -> Result<BlockId, Box<Diagnostic>> {
+        self.numbered('b', "block")
+    }
+
+    fn slot(&mut self) -> Result<SlotId, Box<Diagnostic>> {
+        self.numbered('s', "slot")
+    }
+
+    fn ir_type(&mut self) -> Result<IrType, Box<Diagnostic>> {
+        let ir_type = match self.peek() {
+            Token::Name( x ) => {
+                match x.as_str() {
+                    "void" => Some( IrType::Void ),
+                    "bool" => Some( IrType::Bool ),
+                    "i8" => Some( IrType::I8 ),
+                    "i16" => Some( IrType::I16 ),
+                    "i32" => Some( IrType::I32 ),
+                    "i64" => Some( IrType::I64 ),
+                    "u8" => Some( IrType::U8 ),
+                    "u16" => Some( IrType::U16 ),
+                    "u32" => Some( IrType::U32 ),
+                    "u64" => Some( IrType::U64 ),
+                    "f32" => Some( IrType::F32 ),
+                    "f64" => Some( IrType::F64 ),
+                    "ptr" => Some( IrType::Ptr ),
+                    _ => None
+                }
+            },
+            _ => None
+        };
+        match ir_type {
+            Some( x ) => {
+                self.advance();
+                Ok( x )
+            },
+            _ => Err( self.error("Expecting type") )
+        }
+    }
+
+    /// Register with its type, the type is recorded for the function
+    fn typed_register(&mut self, function: &mut Function) -> Result<VReg, Box<Diagnostic>> {
+        let register = self.register()?;
+        self.symbol(":")?;
+        let ir_type = self.ir_type()?;
+        if function.registers.len() <= register {
+            function.registers.resize(register + 1, IrType::Void)
+        }
+        function.registers[register] = ir_type;
+        Ok( register )
+    }
+
+    fn function(&mut self) -> Result<Function, Box<Diagnostic>> {
+        let export = self.optional_keyword("export");
+        let inline = self.optional_keyword("inline");
+        let frameless = self.optional_keyword("frameless");
+        let name = self.name()?;
+        let mut function = Function::new(name.as_str(), IrType::Void);
+        function.export = export;
+        function.inline = inline;
+        function.frameless = frameless;
+
+        self.symbol("(")?;
+        match self.optional_symbol(")") {
+            true => (),
+            _ => {
+                loop {
+                    let register = self.typed_register(&mut function)?;
+                    function.parameters.push(register);
+                    match self.optional_symbol(",") {
+                        true => (),
+                        _ => break
+                    }
+                }
+                self.symbol(")")?
+            }
+        }
+        self.symbol("->")?;
+        function.result = self.ir_type()?;
+
+        if self.optional_keyword("parent") {
+            function.parent = Some( self.name()? )
+        }
+        if self.optional_keyword("finally") {
+            function.finally = Some( self.block()? )
+        }
+        self.symbol("{")?;
+
+        while self.optional_keyword("slot") {
+            match self.slot()? == function.slots.len() {
+                true => (),
+                _ => return Err( self.error("Expecting slots numbered in order") )
+            }
+            let name = self.name()?;
+            self.keyword("size")?;
+            let size = self.integer()?;
+            self.keyword("align")?;
+            let align = self.integer()?;
+            function.new_slot(name.as_str(), size, align);
+        }
+
+        loop {
+            if self.optional_symbol("}") {
+                break
+            }
+
+            let label = self.block()?;
+            match label == function.blocks.len() {
+                true => (),
+                _ => return Err( self.error("Expecting blocks numbered in order") )
+            }
+            self.symbol(":")?;
+            let block = function.new_block();
+
+            loop {
+                match self.peek() {
+                    Token::Register( _ ) => {
+                        let result = self.typed_register(&mut function)?;
+                        self.symbol("=")?;
+                        let operation = self.operation()?;
+                        function.blocks[block].instructions.push( Instruction { result: Some( result ), operation } )
+                    },
+                    Token::Name( x ) => {
+                        match x.as_str() {
+                            "jump" | "branch" | "switch" | "return" | "trap" => {
+                                function.blocks[block].terminator = Some( self.terminator()? );
+                                break
+                            },
+                            _ => {
+                                match x.starts_with('b') && x[1 ..].parse::<usize>().is_ok() {
+                                    true => break,
+                                    _ => {
+                                        let operation = self.operation()?;
+                                        function.blocks[block].instructions.push( Instruction { result: None, operation } )
+                                    }
+                                }
+                            }
+                        }
+                    },
+                    _ => break
+                }
+            }
+        }
+
+        Ok( function )
+    }
+
+    fn callee_arguments(&mut self) -> Result<Vec<VReg>, Box<Diagnostic>> {
+        let mut arguments = Vec::<VReg>::new();
+        self.symbol("(")?;
+        if self.optional_symbol(")") {
+            return Ok( arguments )
+        }
+        loop {
+            arguments.push(self.register()?);
+            match self.optional_symbol(",") {
+                true => (),
+                _ => break
+            }
+        }
+        self.symbol(")")?;
+        Ok( arguments )
+    }
+
+    fn operation(&mut self) -> Result<Operation, Box<Diagnostic>> {
+        let opcode = match self.peek().clone() {
+            Token::Name( x ) => x,
+            _ => return Err( self.error("Expecting operation") )
+        };
+        self.advance();
+
+        let binary = match opcode.as_str() {
+            "add" => Some( BinaryOp::Add ),
+            "sub" => Some( BinaryOp::Sub ),
+            "mul" => Some( BinaryOp::Mul ),
+            "div" => Some( BinaryOp::Div ),
+            "mod" => Some( BinaryOp::Mod ),
+            "and" => Some( BinaryOp::And ),
+            "or" => Some( BinaryOp::Or ),
+            "xor" => Some( BinaryOp::Xor ),
+            "ash" => Some( BinaryOp::Ash ),
+            "lsh" => Some( BinaryOp::Lsh ),
+            "rot" => Some( BinaryOp::Rot ),
+            _ => None
+        };
+        if let Some( x ) = binary {
+            let l = self.register()?;
+            self.symbol(",")?;
+            let r = self.register()?;
+            return Ok( Operation::Binary(x, l, r) )
+        }
+
+        match opcode.as_str() {
+            "iconst" => Ok( Operation::Constant(Constant::Integer(self.integer()?)) ),
+            "fconst" => {
+                let negative = self.optional_symbol("-");
+                let value = match self.peek().clone() {
+                    Token::Real( x ) => x,
+                    Token::Integer( x ) => x as f64,
+                    Token::Name( x ) if x == "inf" => f64::INFINITY,
+                    Token::Name( x ) if x == "NaN" => f64::NAN,
+                    _ => return Err( self.error("Expecting real number") )
+                };
+                self.advance();
+                match negative {
+                    true => Ok( Operation::Constant(Constant::Real(-value)) ),
+                    _ => Ok( Operation::Constant(Constant::Real(value)) )
+                }
+            },
+            "copy" => Ok( Operation::Copy(self.register()?) ),
+            "neg" => Ok( Operation::Unary(UnaryOp::Neg, self.register()?) ),
+            "not" => Ok( Operation::Unary(UnaryOp::Not, self.register()?) ),
+            "cmp" => {
+                let operator = match self.peek() {
+                    Token::Name( x ) => {
+                        match x.as_str() {
+                            "eq" => Some( CompareOp::Eq ),
+                            "ne" => Some( CompareOp::Ne ),
+                            "lt" => Some( CompareOp::Lt ),
+                            "le" => Some( CompareOp::Le ),
+                            "gt" => Some( CompareOp::Gt ),
+                            "ge" => Some( CompareOp::Ge ),
+                            _ => None
+                        }
+                    },
+                    _ => None
+                };
+                match operator {
+                    Some( x ) => {
+                        self.advance();
+                        let l = self.register()?;
+                        self.symbol(",")?;
+                        let r = self.register()?;
+                        Ok( Operation::Compare(x, l, r) )
+                    },
+                    _ => Err( self.error("Expecting comparison 'eq', 'ne', 'lt', 'le', 'gt' or 'ge'") )
+                }
+            },
+            "convert" => Ok( Operation::Convert(self.register()?) ),
+            "phi" => {
+                let mut values = Vec::<(BlockId, VReg)>::new();
+                loop {
+                    self.symbol("[")?;
+                    let block = self.block()?;
+                    self.symbol(":")?;
+                    let register = self.register()?;
+                    self.symbol("]")?;
+                    values.push(( block, register ));
+                    match self.optional_symbol(",") {
+                        true => (),
+                        _ => break
+                    }
+                }
+                Ok( Operation::Phi(values) )
+            },
+            "slotaddr" => Ok( Operation::SlotAddress(self.slot()?) ),
+            "outerslotaddr" => {
+                let levels = self.integer()?;
+                self.symbol(",")?;
+                let slot = self.slot()?;
+                match u32::try_from(levels) {
+                    Ok( x ) => Ok( Operation::OuterSlotAddress(x, slot) ),
+                    _ => Err( self.error("Expecting levels that are not negative") )
+                }
+            },
+            "globaladdr" => Ok( Operation::GlobalAddress(self.name()?) ),
+            "procaddr" => Ok( Operation::ProcedureAddress(self.name()?) ),
+            "typedesc" => Ok( Operation::TypeDescriptor(self.name()?) ),
+            "offset" => {
+                let address = self.register()?;
+                self.symbol(",")?;
+                Ok( Operation::Offset(address, self.register()?) )
+            },
+            "load" => Ok( Operation::Load(self.register()?) ),
+            "store" => {
+                let address = self.register()?;
+                self.symbol(",")?;
+                Ok( Operation::Store(address, self.register()?) )
+            },
+            "copymem" => {
+                let destination = self.register()?;
+                self.symbol(",")?;
+                let source = self.register()?;
+                self.symbol(",")?;
+                Ok( Operation::CopyMemory(destination, source, self.integer()?) )
+            },
+            "call" => {
+                let callee = match self.peek().clone() {
+                    Token::Register( x ) => {
+                        self.advance();
+                        Callee::Indirect(x)
+                    },
+                    _ => {
+                        match self.optional_keyword("method") {
+                            true => Callee::Method(self.name()?),
+                            _ => Callee::Direct(self.name()?)
+                        }
+                    }
+                };
+                Ok( Operation::Call(callee, self.callee_arguments()?) )
+            },
+            "typetest" => {
+                let register = self.register()?;
+                self.symbol(",")?;
+                Ok( Operation::TypeTest(register, self.name()?) )
+            },
+            "code" => Ok( Operation::Code(self.bytes()?) ),
+            _ => {
+                self.index -= 1;
+                Err( self.error(format!("Unknown operation '{}'", opcode).as_str()) )
+            }
+        }
+    }
+
+    fn terminator(&mut self) -> Result<Terminator, Box<Diagnostic>> {
+        match self.name()?.as_str() {
+            "jump" => Ok( Terminator::Jump(self.block()?) ),
+            "branch" => {
+                let condition = self.register()?;
+                self.symbol(",")?;
+                let when_true = self.block()?;
+                self.symbol(",")?;
+                Ok( Terminator::Branch(condition, when_true, self.block()?) )
+            },
+            "switch" => {
+                let value = self.register()?;
+                let mut cases = Vec::<(i64, i64, BlockId)>::new();
+                self.symbol("[")?;
+                match self.optional_symbol("]") {
+                    true => (),
+                    _ => {
+                        loop {
+                            let low = self.integer()?;
+                            self.symbol("..")?;
+                            let high = self.integer()?;
+                            self.symbol(":")?;
+                            cases.push(( low, high, self.block()? ));
+                            match self.optional_symbol(",") {
+                                true => (),
+                                _ => break
+                            }
+                        }
+                        self.symbol("]")?
+                    }
+                }
+                self.keyword("default")?;
+                Ok( Terminator::Switch(value, cases, self.block()?) )
+            },
+            "return" => {
+                match self.peek() {
+                    Token::Register( _ ) => Ok( Terminator::Return(Some( self.register()? )) ),
+                    _ => Ok( Terminator::Return(None) )
+                }
+            },
+            _ => Ok( Terminator::Trap(self.integer()?) )
+        }
+    }
+}
+
+// Unittests for IR text module
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{Parser, ParserMethods, BlockRules};
+    use crate::scanner::{Scanner, ScannerMethods};
+    use crate::type_checker::{TypeChecker, TypeCheckerMethods};
+    use crate::diagnostics::E_IR_SYNTAX;
+    use crate::ir::{IrModuleMethods, IrType, Operation, Terminator, Constant, Callee};
+    use std::collections::HashMap;
+    use crate::ir_lowering::lower_module;
+    use crate::ir_text::{write_module, read_module};
+
+    #[test]
+    fn lowered_module_round_trip() {
+        let mut checker = TypeChecker::new();
+        let module = Parser::new(Box::new(Scanner::new("MODULE Test; TYPE O = OBJECT VAR x: INTEGER; PROCEDURE Get(): INTEGER; BEGIN RETURN x END Get; END O; VAR g: INTEGER; s: ARRAY 8 OF CHAR; r: REAL; o: O; PROCEDURE -P(VAR a: ARRAY OF INTEGER; n: INTEGER): INTEGER; VAR k: INTEGER; PROCEDURE Q; BEGIN k := n END Q; BEGIN Q; CASE n OF 0: k := a[0] | 1 .. 3: k := -k ELSE END; RETURN k FINALLY k := 0 END P; BEGIN s := \"hi\"; r := 1.5; g := o.Get(); WHILE (g < 10) & (r > 0.0) DO INC(g) END END Test."))).parse_module().unwrap();
+        checker.check_modules(&[ &*module ]);
+        let ir = lower_module(&checker, &module, &HashMap::new()).unwrap();
+
+        let text = write_module(&ir);
+        let read = read_module(text.as_str()).unwrap();
+
+        assert_eq!(write_module(&read), text);
+        assert!(read.function("Test.P").unwrap().inline);
+        assert_eq!(read.globals, ir.globals);
+        for ( a , b ) in read.functions.iter().zip(ir.functions.iter()) {
+            assert_eq!(a.blocks, b.blocks);
+            assert_eq!(a.slots, b.slots);
+            assert_eq!(( &a.name , &a.parent , a.finally , &a.parameters , a.result ), ( &b.name , &b.parent , b.finally , &b.parameters , b.result ));
+            assert_eq!(( a.inline , a.frameless ), ( b.inline , b.frameless ))
+        }
+    }
+
+    #[test]
+    fn read_handwritten_text() {
+        let ir = read_module("
+            module Test   ; comment
+            global export Test.g size 4 align 4
+            global Test.$S size 3 align 1 data \"4f4b00\"
+
+            function \"Test.\\\"+\\\"\"(%0: i32, %1: i32) -> i32 {
+            b0:
+              %2: i32 = add %0, %1
+              %3: f64 = fconst -1.5e-3
+              %4: bool = cmp lt %2, %0
+              call method Test.T.M(%1)
+              code \"90c3\"
+              switch %2 [0 .. 0: b1, 5 .. 9: b1] default b2
+            b1:
+              return %2
+            b2:
+              trap 2
+            }").unwrap();
+
+        let function = ir.function("Test.\"+\"").unwrap();
+        assert_eq!(ir.globals[1].data, Some( vec![ 0x4f, 0x4b, 0 ] ));
+        assert!(ir.globals[0].export);
+        assert_eq!(function.registers, vec![ IrType::I32, IrType::I32, IrType::I32, IrType::F64, IrType::Bool ]);
+        assert_eq!(function.blocks[0].instructions[1].operation, Operation::Constant(Constant::Real(-1.5e-3)));
+        assert_eq!(function.blocks[0].instructions[3].operation, Operation::Call(Callee::Method(String::from("Test.T.M")), vec![ 1 ]));
+        assert_eq!(function.blocks[0].instructions[4].operation, Operation::Code(vec![ 0x90, 0xc3 ]));
+        assert_eq!(function.blocks[0].terminator, Some( Terminator::Switch(2, vec![ ( 0, 0, 1 ), ( 5, 9, 1 ) ], 2) ));
+        assert_eq!(function.blocks[2].terminator, Some( Terminator::Trap(2) ))
+    }
+
+    #[test]
+    fn syntax_errors() {
+        let error = read_module("module Test\nfunction P() -> i32 {\nb0:\n  %0: i32 = frobnicate %1\n}").unwrap_err();
+        assert_eq!(error.code, E_IR_SYNTAX);
+        assert_eq!(error.span.start, 50);
+
+        assert!(read_module("module Test\nfunction P() -> i32 {\nb1:\n}").is_err());
+        assert!(read_module("module Test\nglobal g size 4").is_err())
+    }
+}
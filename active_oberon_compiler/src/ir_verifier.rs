@@ -0,0 +1,408 @@
+// ActiveOberon Compiler, a native ARM v8 & X86-64 compiler & Risc V / linker / builder utility.
+// Written by Richard Magnor Stenbro. Licensed under GPL v3
+// IR verifier module, checks invariants of intermediate representation after lowering and after each optimization
+
+use crate::diagnostics::{Diagnostic, DiagnosticMethods, E_IR_VERIFY};
+use crate::ir::{IrModule, IrModuleMethods, Function, FunctionMethods, IrType, IrTypeMethods, VReg, BlockId, Operation, OperationMethods, Terminator,
+                TerminatorMethods, Constant, Callee, UnaryOp, BinaryOp, runtime_signature};
+
+/// Where virtual register gets its value
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Definition {
+    Parameter,
+    Instruction(BlockId, usize)
+}
+
+/// Problems found in every function of module, stage names the lowering or pass that produced it
+pub fn verify_module(module: &IrModule, stage: &str) -> Vec<Box<Diagnostic>> {
+    let mut errors = Vec::<Box<Diagnostic>>::new();
+    for function in module.functions.iter() {
+        for problem in verify_function(module, function).iter() {
+            errors.push(Diagnostic::error(E_IR_VERIFY, format!("Invalid intermediate representation after {} in '{}': {}", stage, function.name, problem), 0, 0))
+        }
+    }
+    errors
+}
+
+/// Problems found in function: missing terminators, registers defined more than once or used where their
+/// definition does not dominate, operands of wrong type and phis not matching predecessors of their block.
+/// Calls of functions in module and of the runtime are checked against their signature.
+pub fn verify_function(module: &IrModule, function: &Function) -> Vec<String> {
+    let mut problems = Vec::<String>::new();
+    let count = function.blocks.len();
+
+    if count == 0 {
+        return vec![ String::from("function has no blocks") ]
+    }
+
+    /* Control flow must be complete before definitions and uses can be checked */
+    for ( index , block ) in function.blocks.iter().enumerate() {
+        match &block.terminator {
+            Some( x ) => {
+                for target in x.successors().iter() {
+                    match *target < count {
+                        true => (),
+                        _ => problems.push(format!("block b{} continues in missing block b{}", index, target))
+                    }
+                }
+            },
+            None => problems.push(format!("block b{} has no terminator", index))
+        }
+    }
+    match function.finally {
+        Some( x ) if x >= count => problems.push(format!("'FINALLY' block b{} is missing", x)),
+        _ => ()
+    }
+    match problems.is_empty() {
+        true => (),
+        _ => return problems
+    }
+
+    let mut definitions = vec![ None; function.registers.len() ];
+    let mut define = |register: VReg, definition: Definition, problems: &mut Vec<String>| {
+        match definitions.get(register) {
+            Some( None ) => definitions[register] = Some( definition ),
+            Some( Some( _ ) ) => problems.push(format!("register %{} is defined more than once", register)),
+            None => problems.push(format!("register %{} has no type", register))
+        }
+    };
+    for parameter in function.parameters.iter() {
+        define(*parameter, Definition::Parameter, &mut problems)
+    }
+    for ( index , block ) in function.blocks.iter().enumerate() {
+        for ( position , instruction ) in block.instructions.iter().enumerate() {
+            if let Some( x ) = instruction.result {
+                define(x, Definition::Instruction(index, position), &mut problems)
+            }
+        }
+    }
+
+    let reached = function.reachable();
+    let dominators = function.dominators();
+    let predecessors = function.predecessors();
+
+    let verifier = Verifier { module, function, definitions, dominators };
+
+    for ( index , block ) in function.blocks.iter().enumerate() {
+        match reached[index] {
+            true => (),
+            _ => continue
+        }
+
+        let mut phis_done = false;
+        for ( position , instruction ) in block.instructions.iter().enumerate() {
+            let here = format!("b{} instruction {}", index, position);
+            match &instruction.operation {
+                Operation::Phi( values ) => {
+                    if phis_done {
+                        problems.push(format!("{}: phi follows other instructions", here))
+                    }
+
+                    let mut incoming : Vec<BlockId> = values.iter().map(|x| x.0).collect();
+                    let mut expected : Vec<BlockId> = predecessors[index].iter().copied().filter(|x| reached[*x]).collect();
+                    incoming.sort();
+                    expected.sort();
+                    expected.dedup();
+                    match incoming == expected {
+                        true => (),
+                        _ => problems.push(format!("{}: phi has values from {:?}, predecessors are {:?}", here, incoming, expected))
+                    }
+
+                    /* Value from each predecessor must be available at its end */
+                    for ( from , value ) in values.iter() {
+                        match verifier.available_at_end(*value, *from) {
+                            true => (),
+                            _ => problems.push(format!("{}: %{} is not available at end of b{}", here, value, from))
+                        }
+                    }
+                },
+                _ => {
+                    phis_done = true;
+                    for operand in instruction.operation.operands().iter() {
+                        match verifier.available_at(*operand, index, position) {
+                            true => (),
+                            _ => problems.push(format!("{}: %{} is used where its definition does not dominate", here, operand))
+                        }
+                    }
+                }
+            }
+
+            if let Some( x ) = verifier.check_types(&instruction.operation, instruction.result) {
+                problems.push(format!("{}: {}", here, x))
+            }
+        }
+
+        let here = format!("b{} terminator", index);
+        let position = block.instructions.len();
+        let ( operand , problem ) = match &block.terminator {
+            Some( Terminator::Branch( c , _ , _ ) ) => ( Some( *c ), verifier.expect(*c, |x| x == IrType::Bool, "condition must be bool") ),
+            Some( Terminator::Switch( v , cases , _ ) ) => {
+                match cases.iter().find(|x| x.0 > x.1) {
+                    Some( x ) => ( Some( *v ), Some( format!("case range {} .. {} is empty", x.0, x.1) ) ),
+                    _ => ( Some( *v ), verifier.expect(*v, |x| x.is_integer(), "switch value must be integer") )
+                }
+            },
+            Some( Terminator::Return( Some( v ) ) ) => {
+                let result = function.result;
+                ( Some( *v ), verifier.expect(*v, |x| x == result && x != IrType::Void, "returned value must have result type of function") )
+            },
+            Some( Terminator::Return( None ) ) if function.result != IrType::Void => ( None, Some( String::from("function with result returns no value") ) ),
+            _ => ( None, None )
+        };
+        match operand {
+            Some( x ) if !verifier.available_at(x, index, position) => problems.push(format!("{}: %{} is used where its definition does not dominate", here, x)),
+            _ => ()
+        }
+        if let Some( x ) = problem {
+            problems.push(format!("{}: {}", here, x))
+        }
+    }
+
+    problems
+}
+
+struct Verifier<'a> {
+    module: &'a IrModule,
+    function: &'a Function,
+    definitions: Vec<Option<Definition>>,
+    dominators: Vec<Vec<bool>>
+}
+
+impl<'a> Verifier<'a> {
+    fn register_type(&self, register: VReg) -> Option<IrType> {
+        self.function.registers.get(register).copied()
+    }
+
+    /// Register defined before instruction at position of block, on every path reaching it
+    fn available_at(&self, register: VReg, block: BlockId, position: usize) -> bool {
+        match self.definitions.get(register) {
+            Some( Some( Definition::Parameter ) ) => true,
+            Some( Some( Definition::Instruction( b , p ) ) ) if *b == block => *p < position,
+            Some( Some( Definition::Instruction( b , _ ) ) ) => self.dominators[block][*b],
+            _ => false
+        }
+    }
+
+    fn available_at_end(&self, register: VReg, block: BlockId) -> bool {
+        self.available_at(register, block, usize::MAX)
+    }
+
+    /// Arguments and result of call against signature of called function, when it is in module or the runtime
+    fn check_call(&self, callee: &Callee, arguments: &[VReg], result: IrType) -> Option<String> {
+        let name = match callee {
+            Callee::Direct( x ) | Callee::Method( x ) => x,
+            Callee::Indirect( _ ) => return None
+        };
+        let ( parameters , expected ) = match ( self.module.function(name) , runtime_signature(name) ) {
+            ( Some( x ) , _ ) => ( x.parameters.iter().map(|y| x.registers[*y]).collect::<Vec<IrType>>(), x.result ),
+            ( _ , Some( x ) ) => x,
+            _ => return None
+        };
+
+        match ( arguments.len() == parameters.len() , result == expected || result == IrType::Void ) {
+            ( false , _ ) => Some( format!("call of {} has {} arguments, it takes {}", name, arguments.len(), parameters.len()) ),
+            ( _ , false ) => Some( format!("call of {} gives {:?}, it returns {:?}", name, result, expected) ),
+            _ => arguments.iter().zip(parameters.iter()).find_map(|( x , y )| {
+                self.expect(*x, |z| z == *y, format!("argument of {} must have type {:?}", name, y).as_str())
+            })
+        }
+    }
+
+    fn expect(&self, register: VReg, test: impl Fn(IrType) -> bool, message: &str) -> Option<String> {
+        match self.register_type(register) {
+            Some( x ) if test(x) => None,
+            Some( x ) => Some( format!("{}, %{} has type {:?}", message, register, x) ),
+            None => Some( format!("register %{} has no type", register) )
+        }
+    }
+
+    /// Problem with operand types of operation, or with its result
+    fn check_types(&self, operation: &Operation, result: Option<VReg>) -> Option<String> {
+        let result_type = match result {
+            Some( x ) => {
+                match self.register_type(x) {
+                    Some( IrType::Void ) => return Some( format!("result %{} has type void", x) ),
+                    Some( y ) => Some( y ),
+                    None => return Some( format!("register %{} has no type", x) )
+                }
+            },
+            _ => None
+        };
+
+        match ( operation , result_type ) {
+            ( Operation::Store( .. ) , Some( _ ) ) | ( Operation::CopyMemory( .. ) , Some( _ ) ) | ( Operation::Code( _ ) , Some( _ ) ) => {
+                return Some( String::from("operation gives no result") )
+            },
+            ( Operation::Store( .. ) , None ) | ( Operation::CopyMemory( .. ) , None ) | ( Operation::Code( _ ) , None ) | ( Operation::Call( .. ) , None ) => (),
+            ( _ , None ) => return Some( String::from("operation needs a result register") ),
+            _ => ()
+        }
+        let t = result_type.unwrap_or(IrType::Void);
+
+        match operation {
+            Operation::Constant( Constant::Integer( _ ) ) if t.is_integer() || t == IrType::Bool || t == IrType::Ptr => None,
+            Operation::Constant( Constant::Real( _ ) ) if t.is_float() => None,
+            Operation::Constant( _ ) => Some( format!("constant does not fit result of type {:?}", t) ),
+            Operation::Copy( x ) => self.expect(*x, |y| y == t, "copy must have type of result"),
+            Operation::Unary( UnaryOp::Neg , x ) if t.is_integer() || t.is_float() => self.expect(*x, |y| y == t, "operand must have type of result"),
+            Operation::Unary( UnaryOp::Not , x ) if t.is_integer() || t == IrType::Bool => self.expect(*x, |y| y == t, "operand must have type of result"),
+            Operation::Unary( .. ) => Some( format!("unary operation cannot give type {:?}", t) ),
+            Operation::Binary( operator , x , y ) => {
+                let allowed = match operator {
+                    BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div => t.is_integer() || t.is_float(),
+                    BinaryOp::And | BinaryOp::Or | BinaryOp::Xor => t.is_integer() || t == IrType::Bool,
+                    BinaryOp::Mod | BinaryOp::Ash | BinaryOp::Lsh | BinaryOp::Rot => t.is_integer()
+                };
+                match ( allowed , operator ) {
+                    ( false , _ ) => Some( format!("operation {:?} cannot give type {:?}", operator, t) ),
+                    ( _ , BinaryOp::Ash ) | ( _ , BinaryOp::Lsh ) | ( _ , BinaryOp::Rot ) => {
+                        self.expect(*x, |z| z == t, "operand must have type of result").or(self.expect(*y, |z| z == IrType::I64, "shift count must be i64"))
+                    },
+                    _ => self.expect(*x, |z| z == t, "operand must have type of result").or(self.expect(*y, |z| z == t, "operand must have type of result"))
+                }
+            },
+            Operation::Compare( _ , x , y ) => {
+                match ( t , self.register_type(*x) ) {
+                    ( IrType::Bool , Some( z ) ) => self.expect(*y, |w| w == z, "compared operands must have the same type"),
+                    _ => Some( String::from("comparison must give bool") )
+                }
+            },
+            Operation::Convert( x ) => {
+                /* Addresses only become integers of type 'ADDRESS' */
+                match ( self.register_type(*x) , t ) {
+                    ( Some( IrType::Ptr ) , IrType::Ptr ) | ( Some( IrType::Ptr ) , IrType::U64 ) | ( Some( IrType::U64 ) , IrType::Ptr ) => None,
+                    ( Some( y ) , _ ) if y == IrType::Ptr || t == IrType::Ptr => Some( format!("no conversion from {:?} to {:?}, %{} has type {:?}", y, t, x, y) ),
+                    _ => self.expect(*x, |y| y != IrType::Void, "converted value must not be void")
+                }
+            },
+            Operation::Phi( values ) => {
+                values.iter().find_map(|x| self.expect(x.1, |y| y == t, "phi values must have type of result"))
+            },
+            Operation::SlotAddress( x ) if *x >= self.function.slots.len() => Some( format!("slot s{} is missing", x) ),
+            Operation::OuterSlotAddress( levels , _ ) if *levels == 0 || self.function.parent.is_none() => Some( String::from("outer slot of function without enclosing function") ),
+            Operation::SlotAddress( _ ) | Operation::OuterSlotAddress( .. ) | Operation::GlobalAddress( _ ) |
+            Operation::ProcedureAddress( _ ) | Operation::TypeDescriptor( _ ) if t != IrType::Ptr => Some( String::from("address must be ptr") ),
+            Operation::Offset( x , y ) if t == IrType::Ptr => {
+                self.expect(*x, |z| z == IrType::Ptr, "base address must be ptr").or(self.expect(*y, |z| z == IrType::I64, "offset must be i64"))
+            },
+            Operation::Offset( .. ) => Some( String::from("address must be ptr") ),
+            Operation::Load( x ) => self.expect(*x, |y| y == IrType::Ptr, "address must be ptr"),
+            Operation::Store( x , y ) => self.expect(*x, |z| z == IrType::Ptr, "address must be ptr").or(self.expect(*y, |z| z != IrType::Void, "stored value must not be void")),
+            Operation::CopyMemory( x , y , size ) => {
+                match *size >= 0 {
+                    true => self.expect(*x, |z| z == IrType::Ptr, "address must be ptr").or(self.expect(*y, |z| z == IrType::Ptr, "address must be ptr")),
+                    _ => Some( format!("size {} is negative", size) )
+                }
+            },
+            Operation::Call( callee , arguments ) => {
+                let target = match callee {
+                    Callee::Indirect( x ) => self.expect(*x, |y| y == IrType::Ptr, "procedure called must be ptr"),
+                    _ => None
+                };
+                target.or(arguments.iter().find_map(|x| self.expect(*x, |y| y != IrType::Void, "argument must not be void")))
+                      .or(self.check_call(callee, arguments, t))
+            },
+            Operation::TypeTest( x , _ ) if t == IrType::Bool => self.expect(*x, |y| y == IrType::Ptr, "tested reference must be ptr"),
+            Operation::TypeTest( .. ) => Some( String::from("type test must give bool") ),
+            _ => None
+        }
+    }
+}
+
+// Unittests for IR verifier module
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{Parser, ParserMethods, BlockRules};
+    use crate::scanner::{Scanner, ScannerMethods};
+    use crate::type_checker::{TypeChecker, TypeCheckerMethods};
+    use crate::diagnostics::E_IR_VERIFY;
+    use std::collections::HashMap;
+    use crate::ir_lowering::lower_module;
+    use crate::ir_text::read_module;
+    use crate::ir_verifier::{verify_module, verify_function};
+
+    fn problems(text: &str) -> Vec<String> {
+        let module = read_module(text).unwrap();
+        verify_function(&module, &module.functions[0])
+    }
+
+    #[test]
+    fn lowered_module_is_valid() {
+        let mut checker = TypeChecker::new();
+        let module = Parser::new(Box::new(Scanner::new("MODULE Test; TYPE O = OBJECT VAR x: INTEGER; PROCEDURE Get(): INTEGER; BEGIN RETURN x END Get; END O; VAR g: INTEGER; s: SET; r: REAL; o: O; a: ARRAY 4 OF CHAR; PROCEDURE P(VAR a: ARRAY OF INTEGER; n: INTEGER): INTEGER; VAR k: INTEGER; PROCEDURE Q; BEGIN k := n END Q; BEGIN Q; CASE n OF 0: k := a[0] | 1 .. 3: k := -k ELSE END; RETURN MAX(k, ABS(n)) FINALLY k := 0 END P; BEGIN r := 1.5; g := o.Get(); s := {1, 3 .. 5}; IF (g < 10) & (r > 0.0) OR (3 IN s) THEN INC(g) END; a[1] := CAP(a[0]); FOR g := 10 TO 0 BY -1 DO ASSERT(ODD(g) OR (g > 2)) END; LOOP EXIT END END Test."))).parse_module().unwrap();
+        checker.check_modules(&[ &*module ]);
+        let ir = lower_module(&checker, &module, &HashMap::new()).unwrap();
+
+        assert_eq!(verify_module(&ir, "lowering").len(), 0)
+    }
+
+    #[test]
+    fn missing_terminator() {
+        assert_eq!(problems("module Test function P() -> void { b0: %0: i32 = iconst 1 }"), vec![ String::from("block b0 has no terminator") ]);
+        assert_eq!(problems("module Test function P() -> void { b0: jump b4 }"), vec![ String::from("block b0 continues in missing block b4") ])
+    }
+
+    #[test]
+    fn use_not_dominated() {
+        let res = problems("module Test function P(%0: bool) -> i32 { b0: branch %0, b1, b2 b1: %1: i32 = iconst 1 jump b3 b2: jump b3 b3: return %1 }");
+        assert_eq!(res, vec![ String::from("b3 terminator: %1 is used where its definition does not dominate") ]);
+
+        let res = problems("module Test function P() -> i32 { b0: %0: i32 = add %1, %1 %1: i32 = iconst 1 return %0 }");
+        assert_eq!(res.len(), 2)
+    }
+
+    #[test]
+    fn operand_types() {
+        let res = problems("module Test function P(%0: i32, %1: i16) -> i32 { b0: %2: i32 = add %0, %1 %3: i32 = lsh %0, %0 %4: i32 = cmp eq %0, %0 return %2 }");
+        assert_eq!(res, vec![
+            String::from("b0 instruction 0: operand must have type of result, %1 has type I16"),
+            String::from("b0 instruction 1: shift count must be i64, %0 has type I32"),
+            String::from("b0 instruction 2: comparison must give bool")
+        ]);
+
+        assert_eq!(problems("module Test function P(%0: i32) -> i64 { b0: return %0 }").len(), 1)
+    }
+
+    #[test]
+    fn pointer_conversions() {
+        let res = problems("module Test function P(%0: ptr) -> u8 { b0: %1: u8 = convert %0 %2: u64 = convert %0 %3: ptr = convert %2 %4: bool = convert %3 return %1 }");
+        assert_eq!(res, vec![
+            String::from("b0 instruction 0: no conversion from Ptr to U8, %0 has type Ptr"),
+            String::from("b0 instruction 3: no conversion from Ptr to Bool, %3 has type Ptr")
+        ])
+    }
+
+    #[test]
+    fn call_signatures() {
+        let res = problems("module Test function P(%0: u8, %1: ptr) -> i32 { b0: %2: i32 = call Runtime.CompareStrings(%0, %1) %3: i32 = call Test.Q(%1) call Test.Q(%0) return %2 } function Test.Q(%0: u8) -> i64 { b0: return %0 }");
+        assert_eq!(res, vec![
+            String::from("b0 instruction 0: argument of Runtime.CompareStrings must have type Ptr, %0 has type U8"),
+            String::from("b0 instruction 1: call of Test.Q gives I32, it returns I64")
+        ]);
+
+        assert_eq!(problems("module Test function P(%0: ptr) -> void { b0: call Runtime.New(%0) return }").len(), 1)
+    }
+
+    #[test]
+    fn phis_match_predecessors() {
+        let valid = "module Test function P(%0: bool) -> i32 { b0: branch %0, b1, b2 b1: %1: i32 = iconst 1 jump b3 b2: %2: i32 = iconst 2 jump b3 b3: %3: i32 = phi [b1: %1], [b2: %2] return %3 }";
+        assert_eq!(problems(valid).len(), 0);
+
+        let res = problems("module Test function P(%0: bool) -> i32 { b0: branch %0, b1, b2 b1: %1: i32 = iconst 1 jump b3 b2: %2: i32 = iconst 2 jump b3 b3: %3: i32 = phi [b1: %1], [b2: %1] return %3 }");
+        assert_eq!(res, vec![ String::from("b3 instruction 0: %1 is not available at end of b2") ]);
+
+        let res = problems("module Test function P(%0: bool) -> i32 { b0: branch %0, b1, b2 b1: %1: i32 = iconst 1 jump b3 b2: jump b3 b3: %3: i32 = phi [b1: %1] return %3 }");
+        assert_eq!(res, vec![ String::from("b3 instruction 0: phi has values from [1], predecessors are [1, 2]") ])
+    }
+
+    #[test]
+    fn module_diagnostics() {
+        let module = read_module("module Test function Test.P() -> i32 { b0: return }").unwrap();
+        let errors = verify_module(&module, "lowering");
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].code, E_IR_VERIFY);
+        assert_eq!(*errors[0].message, "Invalid intermediate representation after lowering in 'Test.P': b0 terminator: function with result returns no value")
+    }
+}
@@ -0,0 +1,531 @@
+// ActiveOberon Compiler, a native ARM v8 & X86-64 compiler & Risc V / linker / builder utility.
+// Written by Richard Magnor Stenbro. Licensed under GPL v3
+// Linter module for checking syntax and semantic of source files without generating code
+
+use std::fs;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use console::style;
+use crate::compiler::{Compiler, CompilerMethods};
+use crate::diagnostics::{Diagnostic, DiagnosticMethods, SourceMap, SourceMapMethods, W_UNUSED_IMPORT, W_UNUSED_VARIABLE, W_UNREACHABLE_CODE};
+use crate::parser::{Parser, ParserMethods, BlockRules, Node};
+use crate::resolver::{Resolver, ResolverMethods};
+use crate::type_checker::{TypeChecker, TypeCheckerMethods};
+use crate::scanner::{Scanner, ScannerMethods};
+use crate::symbol_table::{SymbolTableMethods, ScopeId, EntryId, ScopeKind, SymbolKind, identifier_definition};
+use crate::frame_layout::deferred_code_variables;
+use crate::inline_assembler::{Target, assemble_code_block, referenced_variables};
+
+
+pub trait LinterMethods {
+    fn new() -> Self;
+    fn set_target(&mut self, target: Target) -> ();
+    fn lint_paths(&mut self, paths: &[String]) -> bool;
+    fn collect_files(&mut self, path: &Path, files: &mut Vec<PathBuf>) -> ();
+    fn report(&mut self, file_name: &str, source: &str, diagnostics: &[Box<Diagnostic>]) -> ();
+    fn lint_sources(&mut self, sources: &[&'static str]) -> Vec<Vec<Box<Diagnostic>>>;
+    fn assemble_deferred(&mut self, checker: &TypeChecker, module: ScopeId, deferred: &[(u32, Vec<char>)]) -> Vec<Box<Diagnostic>>;
+    fn check_module(&mut self, module: &Node) -> ();
+    fn check_unused(&mut self, resolver: &Resolver, module: ScopeId) -> ();
+    fn check_declarations(&mut self, declarations: &Node) -> ();
+    fn check_type(&mut self, type_node: &Node) -> ();
+    fn check_procedure(&mut self, declarations: &Option<Box<Node>>, body: &Option<Box<Node>>) -> ();
+    fn check_body(&mut self, body: &Node) -> ();
+    fn check_statements(&mut self, statement: &Node) -> ();
+}
+
+/// Checks modules for errors and suspicious code, without any backend involved
+pub struct Linter {
+    diagnostics: Vec<Box<Diagnostic>>,      /* Errors and warnings for current module */
+    code_uses: HashSet<EntryId>,            /* Variables named in 'CODE' blocks */
+    files: u32,
+    errors: u32,
+    warnings: u32,
+    target: Target                          /* CPU that inline assembler is checked for */
+}
+
+/// Source span of a statement, used for placing warnings
+fn statement_span(node: &Node) -> Option<(u32, u32)> {
+    match node {
+        Node::StatementBlock( s , e , .. ) |
+        Node::If( s , e , .. ) |
+        Node::With( s , e , .. ) |
+        Node::Case( s , e , .. ) |
+        Node::While( s , e , .. ) |
+        Node::Repeat( s , e , .. ) |
+        Node::For( s , e , .. ) |
+        Node::Loop( s , e , .. ) |
+        Node::Exit( s , e , .. ) |
+        Node::Return( s , e , .. ) |
+        Node::Await( s , e , .. ) |
+        Node::Code( s , e , .. ) |
+        Node::Ignore( s , e , .. ) |
+        Node::BecomesStatement( s , e , .. ) |
+        Node::ExclaimMarkStatement( s , e , .. ) |
+        Node::QuestionmarkStatement( s , e , .. ) |
+        Node::LessLessStatement( s , e , .. ) |
+        Node::GreaterGreaterStatement( s , e , .. ) |
+        Node::UnaryExpression( s , e , .. ) |
+        Node::Ident( s , e , .. ) => Some( ( *s, *e ) ),
+        _ => None
+    }
+}
+
+impl LinterMethods for Linter {
+    fn new() -> Self {
+        Linter {
+            diagnostics: Vec::new(),
+            code_uses: HashSet::new(),
+            files: 0,
+            errors: 0,
+            warnings: 0,
+            target: Target::X86_64
+        }
+    }
+
+    fn set_target(&mut self, target: Target) {
+        self.target = target
+    }
+
+    /// Lint all given files and all '.Mod' files found in given directories. Returns false if any errors.
+    fn lint_paths(&mut self, paths: &[String]) -> bool {
+        let mut files = Vec::<PathBuf>::new();
+
+        match paths.is_empty() {
+            true => self.collect_files(Path::new("."), &mut files),
+            _ => {
+                for path in paths.iter() {
+                    self.collect_files(Path::new(path), &mut files)
+                }
+            }
+        }
+
+        /* All modules are resolved together, so that imports between them are checked */
+        let mut names = Vec::<String>::new();
+        let mut sources = Vec::<&'static str>::new();
+
+        for file in files.iter() {
+            let file_name = file.to_string_lossy().to_string();
+            self.files += 1;
+
+            match Compiler::new().read_source_file(&file_name) {
+                Ok( x ) => {
+                    names.push(file_name);
+                    sources.push(Box::leak(x.into_boxed_str()))
+                },
+                Err( e ) => {
+                    println!("{}\r\n", SourceMap::new(&file_name, "").render(&e));
+                    self.errors += 1
+                }
+            }
+        }
+
+        let results = self.lint_sources(&sources);
+
+        for index in 0 .. names.len() {
+            self.report(&names[index], sources[index], &results[index])
+        }
+
+        println!("\r\nLinted {} file(s): {} error(s), {} warning(s)\r\n",
+                 self.files,
+                 match self.errors { 0 => style(self.errors).green(), _ => style(self.errors).red() },
+                 match self.warnings { 0 => style(self.warnings).green(), _ => style(self.warnings).yellow() });
+
+        self.errors == 0
+    }
+
+    fn collect_files(&mut self, path: &Path, files: &mut Vec<PathBuf>) {
+        match path.is_dir() {
+            true => {
+                let mut entries = match fs::read_dir(path) {
+                    Ok( x ) => x.filter_map(|e| e.ok()).map(|e| e.path()).collect::<Vec<PathBuf>>(),
+                    _ => Vec::new()
+                };
+                entries.sort();
+
+                for entry in entries.iter() {
+                    match entry.is_dir() {
+                        true => self.collect_files(entry, files),
+                        _ => {
+                            match entry.extension() {
+                                Some( ext ) if ext == "Mod" => files.push(entry.clone()),
+                                _ => ()
+                            }
+                        }
+                    }
+                }
+            },
+            _ => files.push(path.to_path_buf()) /* Explicitly given files are linted whatever extension */
+        }
+    }
+
+    fn report(&mut self, file_name: &str, source: &str, diagnostics: &[Box<Diagnostic>]) {
+        let source_map = SourceMap::new(file_name, source);
+
+        for diagnostic in diagnostics.iter() {
+            match diagnostic.is_error() {
+                true => self.errors += 1,
+                _ => self.warnings += 1
+            }
+            println!("{}\r\n", source_map.render(diagnostic))
+        }
+    }
+
+    /// Parse and check modules, returning errors and warnings for each source in same order
+    fn lint_sources(&mut self, sources: &[&'static str]) -> Vec<Vec<Box<Diagnostic>>> {
+        let mut results = Vec::<Vec<Box<Diagnostic>>>::new();
+        let mut modules = Vec::<Option<Box<Node>>>::new();
+        let mut deferred = Vec::<Vec<(u32, Vec<char>)>>::new();
+
+        for source in sources.iter() {
+            let mut parser = Parser::new(Box::new(Scanner::new(source)));
+            parser.set_target(self.target.clone());
+            let res = parser.parse_module();
+            let mut diagnostics = parser.get_errors().clone();
+            deferred.push(parser.get_deferred_code().clone());
+
+            match res {
+                Ok( module ) => {
+                    /* Semantic checks only make sense for modules without syntax errors */
+                    match diagnostics.is_empty() {
+                        true => modules.push(Some( module )),
+                        _ => modules.push(None)
+                    }
+                },
+                Err( e ) => {
+                    diagnostics.push(e);
+                    modules.push(None)
+                }
+            }
+
+            results.push(diagnostics)
+        }
+
+        /* Name resolution and type checking are done for all modules together, so imports can be checked */
+        let mut checker = TypeChecker::new();
+        let valid : Vec<usize> = ( 0 .. modules.len() ).filter(|x| modules[*x].is_some()).collect();
+        let nodes : Vec<&Node> = valid.iter().map(|x| &**modules[*x].as_ref().unwrap()).collect();
+        let semantic = checker.check_modules(&nodes);
+
+        for ( index , mut diagnostics ) in valid.iter().zip(semantic) {
+            results[*index].append(&mut diagnostics)
+        }
+
+        let resolver = checker.get_resolver();
+
+        for index in 0 .. modules.len() {
+            if let Some( module ) = &modules[index] {
+                self.diagnostics = std::mem::take(&mut results[index]);
+                self.check_module(module);
+
+                let scope = match &**module {
+                    Node::Module( _ , _ , _ , _ , id , _ , _ , _ , _ , _ , _ , _ , _ ) => {
+                        identifier_definition(id).and_then(|( name , .. )| resolver.get_module_scope(name.as_str()))
+                    },
+                    _ => None
+                };
+
+                if let Some( scope ) = scope {
+                    let mut errors = self.assemble_deferred(&checker, scope, &deferred[index]);
+                    self.diagnostics.append(&mut errors);
+                    self.check_unused(resolver, scope)
+                }
+
+                results[index] = std::mem::take(&mut self.diagnostics)
+            }
+
+            results[index].sort_by_key(|d| d.span.start)
+        }
+
+        results
+    }
+
+    /// Second pass of compiler over 'CODE' blocks naming Oberon variables, assembled again now that variables are known.
+    /// Names still undefined are reported, and variables named in the blocks count as used.
+    fn assemble_deferred(&mut self, checker: &TypeChecker, module: ScopeId, deferred: &[(u32, Vec<char>)]) -> Vec<Box<Diagnostic>> {
+        let mut errors = Vec::<Box<Diagnostic>>::new();
+        if deferred.is_empty() {
+            return errors
+        }
+
+        let positions : Vec<u32> = deferred.iter().map(|x| x.0).collect();
+        let variables = deferred_code_variables(checker, module, &positions);
+        let table = checker.get_resolver().get_symbol_table();
+
+        for ( position , text ) in deferred.iter() {
+            /* Variables named count as used even when block has other errors */
+            let variables = variables.get(position).cloned().unwrap_or_default();
+            let names = referenced_variables(text, &variables);
+            match assemble_code_block(&self.target, text.clone(), *position, Some( &variables )) {
+                Ok( _ ) => (),
+                Err( e ) => errors.push(e)
+            }
+
+            let scopes = match checker.get_code_scope(module, *position) {
+                Some( scope ) => vec![ scope, module ],
+                _ => vec![ module ]
+            };
+
+            for name in names.iter() {
+                /* Variables of procedure hide module variables with same name */
+                let entry = scopes.iter().find_map(|x| table.scope(*x).entries.iter().find(|y| {
+                    let symbol = table.entry(**y);
+                    symbol.name == *name && matches!(symbol.kind, SymbolKind::Variable | SymbolKind::Parameter)
+                }));
+
+                if let Some( x ) = entry {
+                    self.code_uses.insert(*x);
+                }
+            }
+        }
+
+        errors
+    }
+
+    fn check_module(&mut self, module: &Node) {
+        if let Node::Module( _ , _ , _ , _ , _ , _ , _ , _ , declarations , body , _ , _ , _ ) = module {
+            if let Some( x ) = declarations {
+                self.check_declarations(x)
+            }
+
+            if let Some( x ) = body {
+                self.check_body(x)
+            }
+        }
+    }
+
+    /// Imports and local variables never referenced, as found by the resolver
+    fn check_unused(&mut self, resolver: &Resolver, module: ScopeId) {
+        let table = resolver.get_symbol_table();
+
+        for scope in 0 .. table.scope_count() {
+            let kind = &table.scope(scope).kind;
+            let checked = match kind {
+                ScopeKind::Module => scope == module,
+                ScopeKind::Procedure | ScopeKind::Operator => table.enclosing_module(scope) == module,
+                _ => false
+            };
+
+            if !checked {
+                continue
+            }
+
+            for entry in table.scope(scope).entries.iter() {
+                let symbol = table.entry(*entry);
+                let used = symbol.used || self.code_uses.contains(entry);
+
+                match ( kind , &symbol.kind , used ) {
+                    ( ScopeKind::Module , SymbolKind::Module , false ) => {
+                        self.diagnostics.push(Diagnostic::warning(W_UNUSED_IMPORT, format!("Module '{}' is imported but never used", symbol.name), symbol.start, symbol.end))
+                    },
+                    ( ScopeKind::Procedure , SymbolKind::Variable , false ) |
+                    ( ScopeKind::Operator , SymbolKind::Variable , false ) => {
+                        self.diagnostics.push(Diagnostic::warning(W_UNUSED_VARIABLE, format!("Local variable '{}' is never used", symbol.name), symbol.start, symbol.end))
+                    },
+                    _ => ()
+                }
+            }
+        }
+    }
+
+    fn check_declarations(&mut self, declarations: &Node) {
+        if let Node::DeclarationSequence( _ , _ , _ , types , _ , procedures , operators , _ , _ ) = declarations {
+            for type_declaration in types.iter() {
+                if let Node::TypeDeclaration( _ , _ , _ , elements ) = &**type_declaration {
+                    for element in elements.iter() {
+                        if let Node::TypeDeclarationElement( _ , _ , _ , _ , type_node , _ ) = &**element {
+                            self.check_type(type_node)
+                        }
+                    }
+                }
+            }
+
+            for procedure in procedures.iter() {
+                if let Node::Procedure( _ , _ , _ , _ , _ , _ , _ , _ , decl , body , _ , _ ) = &**procedure {
+                    self.check_procedure(decl, body)
+                }
+            }
+
+            for operator in operators.iter() {
+                if let Node::Operator( _ , _ , _ , _ , _ , _ , _ , _ , _ , decl , body , _ , _ ) = &**operator {
+                    self.check_procedure(decl, body)
+                }
+            }
+        }
+    }
+
+    fn check_type(&mut self, type_node: &Node) {
+        match type_node {
+            Node::ObjectType( _ , _ , _ , _ , _ , _ , decl , body , _ , _ ) |
+            Node::CellType( _ , _ , _ , _ , _ , _ , _ , decl , body , _ , _ ) => {
+                if let Some( x ) = decl {
+                    self.check_declarations(x)
+                }
+                if let Some( x ) = body {
+                    self.check_body(x)
+                }
+            },
+            Node::RecordType( _ , _ , _ , _ , _ , Some( ( nodes , _ ) ) , _ ) => {
+                for procedure in nodes.iter() {
+                    if let Node::Procedure( _ , _ , _ , _ , _ , _ , _ , _ , decl , body , _ , _ ) = &**procedure {
+                        self.check_procedure(decl, body)
+                    }
+                }
+            },
+            Node::PointerType( _ , _ , _ , _ , _ , target ) => self.check_type(target),
+            _ => ()
+        }
+    }
+
+    fn check_procedure(&mut self, declarations: &Option<Box<Node>>, body: &Option<Box<Node>>) {
+        if let Some( x ) = declarations {
+            self.check_declarations(x)
+        }
+
+        if let Some( x ) = body {
+            self.check_body(x)
+        }
+    }
+
+    fn check_body(&mut self, body: &Node) {
+        if let Node::Body( _ , _ , _ , _ , statements , finally ) = body {
+            self.check_statements(statements);
+            if let Some( ( _ , x ) ) = finally {
+                self.check_statements(x)
+            }
+        }
+    }
+
+    /// Find statements following 'RETURN' or 'EXIT' in same statement sequence
+    fn check_statements(&mut self, statement: &Node) {
+        match statement {
+            Node::StatementSequence( _ , _ , nodes , _ ) => {
+                let mut jump : Option<&str> = None;
+
+                for node in nodes.iter() {
+                    if let Some( keyword ) = jump {
+                        if let Some( ( s , e ) ) = statement_span(node) {
+                            self.diagnostics.push(Diagnostic::warning(W_UNREACHABLE_CODE, format!("Unreachable code after '{}'", keyword), s, e));
+                            break
+                        }
+                    }
+
+                    match &**node {
+                        Node::Return( .. ) => jump = Some("RETURN"),
+                        Node::Exit( .. ) => jump = Some("EXIT"),
+                        _ => ()
+                    }
+
+                    self.check_statements(node)
+                }
+            },
+            Node::StatementBlock( _ , _ , _ , _ , x , _ ) |
+            Node::Elsif( _ , _ , _ , _ , _ , x ) |
+            Node::Else( _ , _ , _ , x ) |
+            Node::WithElement( _ , _ , _ , _ , _ , x ) |
+            Node::CaseElement( _ , _ , _ , _ , _ , _ , x ) |
+            Node::While( _ , _ , _ , _ , _ , x , _ ) |
+            Node::Repeat( _ , _ , _ , x , _ , _ ) |
+            Node::For( _ , _ , _ , _ , _ , _ , _ , _ , _ , _ , x , _ ) |
+            Node::Loop( _ , _ , _ , x , _ ) => self.check_statements(x),
+            Node::If( _ , _ , _ , _ , _ , x , elsif_nodes , else_node , _ ) => {
+                self.check_statements(x);
+                if let Some( nodes ) = elsif_nodes {
+                    for node in nodes.iter() {
+                        self.check_statements(node)
+                    }
+                }
+                if let Some( node ) = else_node {
+                    self.check_statements(node)
+                }
+            },
+            Node::With( _ , _ , _ , _ , _ , nodes , else_node , _ ) |
+            Node::Case( _ , _ , _ , _ , _ , nodes , else_node , _ ) => {
+                for node in nodes.iter() {
+                    self.check_statements(node)
+                }
+                if let Some( node ) = else_node {
+                    self.check_statements(node)
+                }
+            },
+            _ => ()
+        }
+    }
+}
+
+// Unittests for linter module
+
+#[cfg(test)]
+mod tests {
+    use crate::diagnostics::{DiagnosticMethods, W_UNUSED_IMPORT, W_UNUSED_VARIABLE, W_UNREACHABLE_CODE, E_SYNTAX, E_ASSEMBLER_UNDEFINED};
+    use crate::linter::{Linter, LinterMethods};
+
+    #[test]
+    fn lint_clean_module() {
+        let mut linter = Linter::new();
+        let res = linter.lint_sources(&[ "MODULE Test; IMPORT Out; PROCEDURE P(): INTEGER; VAR x: INTEGER; BEGIN x := 1; Out.Int(x); RETURN x END P; END Test." ]).remove(0);
+
+        assert_eq!(res.len(), 0)
+    }
+
+    #[test]
+    fn lint_unused_import() {
+        let mut linter = Linter::new();
+        let res = linter.lint_sources(&[ "MODULE Test; IMPORT Out, In; BEGIN Out.Ln END Test." ]).remove(0);
+
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0].code, W_UNUSED_IMPORT);
+        assert_eq!(res[0].span.start, 25);
+        assert!(!res[0].is_error())
+    }
+
+    #[test]
+    fn lint_unused_local_variable() {
+        let mut linter = Linter::new();
+        let res = linter.lint_sources(&[ "MODULE Test; PROCEDURE P; VAR a, b: INTEGER; BEGIN a := 1 END P; END Test." ]).remove(0);
+
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0].code, W_UNUSED_VARIABLE);
+        assert_eq!(res[0].span.start, 33)
+    }
+
+    #[test]
+    fn lint_unreachable_code() {
+        let mut linter = Linter::new();
+        let res = linter.lint_sources(&[ "MODULE Test; VAR a: INTEGER; BEGIN LOOP EXIT; a := 1 END; a := 2 END Test." ]).remove(0);
+
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0].code, W_UNREACHABLE_CODE);
+        assert_eq!(res[0].span.start, 46)
+    }
+
+    #[test]
+    fn lint_reports_all_syntax_errors() {
+        let mut linter = Linter::new();
+        let res = linter.lint_sources(&[ "MODULE Test; BEGIN a := ; b := ) END Test." ]).remove(0);
+
+        assert_eq!(res.len(), 2);
+        assert_eq!(res[0].code, E_SYNTAX);
+        assert_eq!(res[1].code, E_SYNTAX)
+    }
+
+    #[test]
+    fn lint_variables_used_in_code() {
+        let mut linter = Linter::new();
+        let res = linter.lint_sources(&[ "MODULE Test; VAR g: LONGINT; PROCEDURE P; VAR a, b: HUGEINT; BEGIN CODE MOV RAX, [RBP + a] ; b\n MOV g, EAX END END P; END Test." ]).remove(0);
+
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0].code, W_UNUSED_VARIABLE);
+        assert_eq!(res[0].span.start, 49)
+    }
+
+    #[test]
+    fn lint_undefined_name_in_code() {
+        let mut linter = Linter::new();
+        let res = linter.lint_sources(&[ "MODULE Test; PROCEDURE P; VAR a: HUGEINT; BEGIN CODE MOV RAX, [RBP + a]\n MOV RAX, c END END P; END Test." ]).remove(0);
+
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0].code, E_ASSEMBLER_UNDEFINED);
+        assert!(res[0].is_error())
+    }
+}
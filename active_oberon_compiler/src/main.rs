@@ -4,15 +4,30 @@
 // Main driver module for compiling and linking of projects written in ActiveOberon language
 
 
+mod diagnostics;
 mod scanner;
 mod parser;
 mod symbol_table;
 mod compiler;
+mod linter;
+mod resolver;
+mod type_checker;
+mod const_evaluator;
 mod traverse_abstract_syntax_tree;
 mod amd64_instruction_set_neo;
 mod arm64_instruction_set_neo;
 mod riscv_instruction_set_neo;
 mod amd64_assembler;
+mod arm64_assembler;
+mod riscv_assembler;
+mod inline_assembler;
+mod frame_layout;
+mod ir;
+mod ir_lowering;
+mod ir_text;
+mod ir_verifier;
+mod ir_optimizer;
+mod ir_inliner;
 
 use console::style;
 use build_time::{build_time_local};
@@ -20,6 +35,8 @@ use std::path::PathBuf;
 
 use clap::{Parser, Subcommand};
 use crate::compiler::{Compiler, CompilerMethods};
+use crate::linter::{Linter, LinterMethods};
+use crate::inline_assembler::select_target;
 
 
 
@@ -59,6 +76,20 @@ struct Cli {
     #[arg(short, long)]
     dynamic_library: Option<bool>,
 
+    /// Check intermediate representation after lowering and each optimization. On by default, off by default when
+    /// '--release' is given, whatever profile the compiler itself is built with
+    #[arg(long)]
+    verify_ir: Option<bool>,
+
+    /// Optimization level 0 to 2 of intermediate representation, 2 by default when building for release and 0 otherwise
+    #[arg(short = 'O', value_name = "LEVEL", value_parser = clap::value_parser!(u8).range(0..=2))]
+    optimize: Option<u8>,
+
+    /// Print intermediate output of compile, 'disasm' lists machine code of CODE blocks, 'ir' writes intermediate representation next to module,
+    /// 'stats' lists what each optimization pass did
+    #[arg(long, value_name = "KIND")]
+    emit: Option<String>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -70,13 +101,15 @@ enum Commands {
     Build {
 
     },
-    /// Compile and not link current module file only
+    /// Compile and not link module files, each module after the modules it imports. Files ending with '.ir' are read as
+    /// intermediate representation, verified and optimized
     Compile {
-        module_file: String
+        #[arg(required = true)]
+        module_files: Vec<String>
     },
-    /// Check module source file for valid syntax
+    /// Check module files, or all module files in directories, for errors and warnings without generating code
     Lint {
-
+        paths: Vec<String>
     },
     /// Build and Execute all tests in project
     Test {
@@ -96,21 +129,48 @@ fn main() {
 
     let cli = Cli::parse();
 
+    let target = match select_target(cli.x86_64 == Some( true ), cli.arm_v8 == Some( true ), cli.risc_v == Some( true )) {
+        Ok( x ) => x,
+        Err( e ) => {
+            println!("{} {}\r\n", style("error:").red(), e);
+            std::process::exit(1)
+        }
+    };
+
+    match cli.emit.as_deref() {
+        None | Some( "disasm" ) | Some( "ir" ) | Some( "stats" ) => (),
+        Some( x ) => {
+            println!("{} Unknown kind '{}' for '--emit', expecting 'disasm', 'ir' or 'stats'\r\n", style("error:").red(), x);
+            std::process::exit(1)
+        }
+    }
+
     match &cli.command {
         Commands::Build {}  => {
 
         },
-        Commands::Compile { module_file} => {
+        Commands::Compile { module_files } => {
             let mut compiler = Compiler::new();
-
-            let _ = compiler.compile_module(module_file);
+            compiler.set_target(target);
+            compiler.set_emit(cli.emit.clone());
+            /* Default follows '--release' of the program being compiled, not the build of this compiler */
+            compiler.set_verify_ir(cli.verify_ir.unwrap_or(cli.release != Some( true )));
+            compiler.set_optimization(cli.optimize.unwrap_or(if cli.release == Some( true ) { 2 } else { 0 }));
+
+            if !compiler.compile_modules(module_files) {
+                std::process::exit(1)
+            }
         },
-        Commands::Lint {}  => {
+        Commands::Lint { paths }  => {
+            let mut linter = Linter::new();
+            linter.set_target(target);
 
+            if !linter.lint_paths(paths) {
+                std::process::exit(1)
+            }
         },
         Commands::Test {}  => {
 
-        },
-        _ => { }
+        }
     }
 }
@@ -3,13 +3,15 @@
 // Written by Richard Magnor Stenbro. Licensed under GPL v3
 // Parser module for syntax analyzing of source files
 
-use console::style;
+use std::collections::HashMap;
 use crate::scanner::{Scanner, ScannerMethods, Symbols};
-use crate::amd64_assembler::{ AssemblerAMD64, AssemblerAMD64Methods };
+use crate::inline_assembler::{ Target, Relocation, assemble_code_block };
+use crate::diagnostics::{Diagnostic, DiagnosticMethods, E_SYNTAX, E_MODULE_NAME_MISMATCH, E_ASSEMBLER_UNDEFINED};
 
 #[derive(Clone, PartialEq, Debug)]
 pub enum Node {
 	Empty,
+	Error( u32, u32 ),	/* Part of source skipped by error recovery */
 
 	/* Expression nodes */
 	Ident( u32, u32, Box<Symbols> ),
@@ -105,7 +107,7 @@ pub enum Node {
 	TemplateParameter( u32, u32, Box<Symbols>, Box<Node> ),
 	ImportList( u32, u32, Box<Symbols>, Box<Vec<Box<Node>>>, Box<Vec<Box<Symbols>>>, Box<Symbols> ),
 	Import( u32, u32, Box<Node>, Option<(Box<Symbols>, Box<Node>)>, Option<(Box<Symbols>, Box<Node>, Box<Symbols>)>, Option<(Box<Symbols>, Box<Node>)> ),
-	DeclarationSequence( u32, u32, Box<Vec<Box<Node>>>, Box<Vec<Box<Node>>>, Box<Vec<Box<Node>>>, Box<Vec<Box<Node>>>, Box<Vec<Box<Node>>>, Box<Vec<Box<Symbols>>> ),
+	DeclarationSequence( u32, u32, Box<Vec<Box<Node>>>, Box<Vec<Box<Node>>>, Box<Vec<Box<Node>>>, Box<Vec<Box<Node>>>, Box<Vec<Box<Node>>>, Box<Vec<Box<Node>>>, Box<Vec<Box<Symbols>>> ),
 	ConstDeclaration( u32, u32, Box<Symbols>,  Box<Vec<Box<Node>>> ),
 	TypeDeclaration( u32, u32, Box<Symbols>,  Box<Vec<Box<Node>>> ),
 	VarDeclaration( u32, u32, Box<Symbols>,  Box<Vec<Box<Node>>> ),
@@ -117,11 +119,14 @@ pub enum Node {
 	Flag( u32, u32, Box<Node>, Option<(Box<Symbols>, Box<Node>, Box<Symbols>)>, Option<(Box<Symbols>, Box<Node>)> ),
 	Procedure( u32, u32, Box<Symbols>, Option<(Option<Box<Node>>, Option<Box<Symbols>>)>, Option<(Box<Symbols>, Box<Node>, Box<Symbols>)>, Box<Node>, Option<Box<Node>>, Box<Symbols>, Option<Box<Node>>, Option<Box<Node>>, Box<Symbols>, Box<Node> ),
 	Operator( u32, u32, Box<Symbols>, Option<Box<Node>>, Option<Box<Symbols>>, Box<Node>, Option<Box<Symbols>>, Box<Node>, Box<Symbols>, Option<Box<Node>>, Option<Box<Node>>, Box<Symbols>, Box<Node> ),
+	Definition( u32, u32, Box<Symbols>, Box<Node>, Box<Symbols>, Box<Vec<Box<Node>>>, Box<Symbols>, Box<Node> ),
+	ProcedureHeading( u32, u32, Box<Symbols>, Option<Box<Node>>, Box<Node>, Option<Box<Node>>, Box<Symbols> ),
 	FormalParameters( u32, u32, Box<Symbols>, Box<Vec<Box<Node>>>, Box<Vec<Box<Symbols>>>, Box<Symbols>, Option<(Box<Symbols>, Option<Box<Node>>, Box<Node>)> ),
 	ParameterDeclaration( u32, u32, Option<Box<Symbols>>, Box<Vec<Box<Node>>>, Box<Vec<Box<Symbols>>>, Box<Symbols>, Box<Node> ),
 	Parameter( u32, u32, Box<Node>, Option<Box<Node>>, Option<(Box<Symbols>, Box<Node>)> ),
 	Body( u32, u32, Box<Symbols>, Option<Box<Node>>, Box<Node>, Option<(Box<Symbols>, Box<Node>)> ),
 	BodyCode( u32, u32, Box<Symbols>, Box<Node> ),
+	CodeBlock( u32, u32, Box<Vec<u8>> ),	/* Machine code assembled from source text at span */
 	TypeDeclarationElement( u32, u32, Box<Node>, Box<Symbols>, Box<Node>, Box<Symbols> ),
 	ArrayType( u32, u32, Box<Symbols>, Option<(Box<Vec<Box<Node>>>, Box<Vec<Box<Symbols>>>)>,  Box<Symbols>, Box<Node> ),
 	MathArrayType( u32, u32, Box<Symbols>, Option<(Box<Vec<Box<Node>>>, Box<Vec<Box<Symbols>>>)>,  Box<Symbols>, Box<Node> ),
@@ -130,7 +135,7 @@ pub enum Node {
 	PointerType( u32, u32, Box<Symbols>, Option<Box<Node>>, Box<Symbols>, Box<Node> ),
 	ProcedureType( u32, u32, Box<Symbols>, Option<Box<Node>>, Option<Box<Node>> ),
 	ObjectTypeEmpty( u32, u32, Box<Symbols> ),
-	ObjectType( u32, u32, Box<Symbols>, Option<Box<Node>>, Option<(Box<Symbols>, Box<Node>, Box<Symbols>)>, Option<Box<Node>>, Option<Box<Node>>, Box<Symbols>, Option<Box<Node>> ),
+	ObjectType( u32, u32, Box<Symbols>, Option<Box<Node>>, Option<(Box<Symbols>, Box<Node>, Box<Symbols>)>, Option<(Box<Symbols>, Box<Vec<Box<Node>>>, Box<Vec<Box<Symbols>>>)>, Option<Box<Node>>, Option<Box<Node>>, Box<Symbols>, Option<Box<Node>> ),
 	EnumerationType( u32, u32, Box<Symbols>, Option<(Box<Symbols>, Box<Node>, Box<Symbols>)>, Box<Vec<Box<Node>>>, Box<Vec<Box<Symbols>>>, Box<Symbols> ),
 	EnumElement( u32, u32, Box<Node>, Option<(Box<Symbols>, Box<Node>)> ),
 	CellType( u32, u32, Box<Symbols>, Option<Box<Node>>, Option<(Box<Symbols>, Box<Node>, Box<Symbols>)>, Option<Box<Symbols>>, Option<Box<Node>>, Option<Box<Node>>, Option<Box<Node>>, Box<Symbols>, Option<Box<Node>> ),
@@ -144,86 +149,185 @@ pub enum Node {
 
 pub trait ParserMethods {
 	fn new(scanner: Box<Scanner>) -> Parser;
+	fn set_target(&mut self, target: Target) -> ();
+	fn get_deferred_code(&self) -> &Vec<(u32, Vec<char>)>;
+	fn get_code_relocations(&self) -> &HashMap<u32, Vec<Relocation>>;
 	fn advance(&mut self) -> ();
+	fn error(&self, message: String) -> Box<Diagnostic>;
+	fn get_errors(&self) -> &Vec<Box<Diagnostic>>;
+	fn synchronize(&mut self) -> ();
+	fn recover(&mut self, error: Box<Diagnostic>, start_pos: u32) -> Box<Node>;
+	fn assemble_code(&mut self, text: Vec<char>, offset_position: u32) -> Box<Vec<u8>>;
 }
 
 pub trait ExpressionRules {
-	fn parse_expression(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_range_expression(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_simple_expression(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_term(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_factor(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_unary_expression(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_primary_expression(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_designator_operator(&mut self) -> Result<Box<Vec<Box<Node>>>, Box<std::string::String>>;
-	fn parse_expression_list(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_index_list(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_array(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_set(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
+	fn parse_expression(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_range_expression(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_simple_expression(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_term(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_factor(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_unary_expression(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_primary_expression(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_designator_operator(&mut self) -> Result<Box<Vec<Box<Node>>>, Box<Diagnostic>>;
+	fn parse_expression_list(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_index_list(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_array(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_set(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
 }
 
 pub trait StatementRules {
-	fn parse_statement(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_case(&mut self, bar_optional: bool) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_statement_block(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_statement_sequence(&mut self) -> Result<Box<Node>, Box<String>>;
+	fn parse_statement(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_case(&mut self, bar_optional: bool) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_statement_block(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_statement_sequence(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
 }
 
 pub trait BlockRules {
-	fn parse_module(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_template_parameters(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_template_parameter(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_import_list(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_import(&mut self) -> Result<Box<Node>, Box<String>>;
-	fn parse_declaration_sequence(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_constant_declaration(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_variable_declaration(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_variable_name_list(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_variable_name(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_flags(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_flag(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_procedure_declaration(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_operator_declaration(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_formal_parameters(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_parameter_declaration(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_body(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_type_declaration(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_type(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_array_type(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_record_type(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_pointer_type(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_procedure_type(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_object_type(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_enumeration_type(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_cell_type(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_port_list(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_port_declaration(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_port_type(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_qualified_identifier(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
-	fn parse_identifier_definition(&mut self) -> Result<Box<Node>, Box<std::string::String>>;
+	fn parse_module(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_template_parameters(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_template_parameter(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_import_list(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_import(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_declaration_sequence(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_constant_declaration(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_variable_declaration(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_variable_name_list(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_variable_name(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_flags(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_flag(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_procedure_declaration(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_operator_declaration(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_definition(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_procedure_heading(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_formal_parameters(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_parameter_declaration(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_body(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_type_declaration(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_type(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_array_type(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_record_type(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_pointer_type(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_procedure_type(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_object_type(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_enumeration_type(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_cell_type(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_port_list(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_port_declaration(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_port_type(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_qualified_identifier(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
+	fn parse_identifier_definition(&mut self) -> Result<Box<Node>, Box<Diagnostic>>;
 }
 
 /// Parser component for ActiveOberon language grammar
 pub struct Parser {
 	lexer: Box<Scanner>,		/* Lexical analyzer for sourcecode, returning symbols to parser rules */
-	symbol: Result<Symbols, Box<String>>	/* Current symbol being handled in parser rule */
+	symbol: Result<Symbols, Box<Diagnostic>>,	/* Current symbol being handled in parser rule */
+	errors: Vec<Box<Diagnostic>>,	/* Errors found and recovered from during parsing */
+	target: Target,			/* CPU that inline assembler in 'CODE' blocks is written for */
+	deferred_code: Vec<(u32, Vec<char>)>,	/* Position and text of 'CODE' blocks with unknown names, assembled again when variables are known */
+	code_relocations: HashMap<u32, Vec<Relocation>>
 }
 
 impl ParserMethods for Parser {
 	fn new(scanner: Box<Scanner>) -> Parser {
 		Parser {
 			lexer: scanner,
-			symbol: Ok(Symbols::Empty)
+			symbol: Ok(Symbols::Empty),
+			errors: Vec::new(),
+			target: Target::X86_64,
+			deferred_code: Vec::new(),
+			code_relocations: HashMap::new()
 		}
 	}
+
+	fn set_target(&mut self, target: Target) {
+		self.target = target
+	}
+
+	fn get_deferred_code(&self) -> &Vec<(u32, Vec<char>)> {
+		&self.deferred_code
+	}
+
+	fn get_code_relocations(&self) -> &HashMap<u32, Vec<Relocation>> {
+		&self.code_relocations
+	}
+
+	/// Get next valid symbol, lexical errors are collected and skipped
 	fn advance(&mut self) -> () {
-		self.symbol = self.lexer.get_symbol()
+		loop {
+			self.symbol = self.lexer.get_symbol();
+			match &self.symbol {
+				Err( e ) => self.errors.push(e.clone()),
+				_ => break
+			}
+		}
+	}
+
+	/// Syntax error covering the current symbol
+	fn error(&self, message: String) -> Box<Diagnostic> {
+		Diagnostic::error(E_SYNTAX, message, self.lexer.get_start_position(), self.lexer.get_position())
+	}
+
+	fn get_errors(&self) -> &Vec<Box<Diagnostic>> {
+		&self.errors
+	}
+
+	/// Skip symbols until a point where parsing of statements or declarations can restart
+	fn synchronize(&mut self) {
+		loop {
+			match &self.symbol {
+				Ok( Symbols::SemiColon( _ , _ ) ) |
+				Ok( Symbols::End( _ , _ ) ) |
+				Ok( Symbols::Procedure( _ , _ ) ) |
+				Ok( Symbols::Operator( _ , _ ) ) |
+				Ok( Symbols::Definition( _ , _ ) ) |
+				Ok( Symbols::Type( _ , _ ) ) |
+				Ok( Symbols::Var( _ , _ ) ) |
+				Ok( Symbols::Const( _ , _ ) ) |
+				Ok( Symbols::Begin( _ , _ ) ) |
+				Ok( Symbols::Else( _ , _ ) ) |
+				Ok( Symbols::Elsif( _ , _ ) ) |
+				Ok( Symbols::Until( _ , _ ) ) |
+				Ok( Symbols::Finally( _ , _ ) ) |
+				Ok( Symbols::Bar( _ , _ ) ) |
+				Ok( Symbols::EndOfFile( _ ) ) => break,
+				_ => self.advance()
+			}
+		}
+	}
+
+	/// Record error, skip to next synchronization point and return node covering the skipped source
+	fn recover(&mut self, error: Box<Diagnostic>, start_pos: u32) -> Box<Node> {
+		self.errors.push(error);
+		self.synchronize();
+		Box::new( Node::Error(start_pos, self.lexer.get_start_position()) )
+	}
+
+	/// Machine code of 'CODE' block for selected target. Errors are collected, so parsing goes on at 'END'.
+	fn assemble_code(&mut self, text: Vec<char>, offset_position: u32) -> Box<Vec<u8>> {
+		match assemble_code_block(&self.target, text.clone(), offset_position, None) {
+			Ok( x ) => {
+				if !x.relocations.is_empty() {
+					self.code_relocations.insert(offset_position, x.relocations);
+				}
+				x.code
+			},
+			Err( e ) if e.code == E_ASSEMBLER_UNDEFINED => {
+				/* Name may be Oberon variable, not known before declarations are checked */
+				self.deferred_code.push(( offset_position, text ));
+				Box::new(Vec::<u8>::new())
+			},
+			Err( e ) => {
+				self.errors.push(e);
+				Box::new(Vec::<u8>::new())
+			}
+		}
 	}
 }
 
 /// Implements all expression rules in grammar of ActiveOberon
 impl ExpressionRules for Parser {
-	fn parse_expression(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_expression(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 		let left = self.parse_range_expression()?;
 
@@ -341,7 +445,7 @@ impl ExpressionRules for Parser {
 		}
 	}
 
-	fn parse_range_expression(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_range_expression(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 
 		match self.symbol.clone()? {
@@ -406,13 +510,13 @@ impl ExpressionRules for Parser {
 
 						Ok( Box::new( Node::Range(start_pos, self.lexer.get_start_position(), left, upto, right, by, next ) ) )
 					},
-					_ => left.ok_or( Box::new(format!("Missing expression at position: '{}'", self.lexer.get_start_position())) )
+					_ => left.ok_or( self.error(String::from("Missing expression")) )
 				}
 			}
 		}
 	}
 
-	fn parse_simple_expression(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_simple_expression(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 		let mut left = self.parse_term()?;
 
@@ -443,7 +547,7 @@ impl ExpressionRules for Parser {
 		Ok(left)
 	}
 
-	fn parse_term(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_term(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 		let mut left = self.parse_factor()?;
 
@@ -516,7 +620,7 @@ impl ExpressionRules for Parser {
 		Ok(left)
 	}
 
-	fn parse_factor(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_factor(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 
 		match self.symbol.clone()? {
@@ -545,7 +649,7 @@ impl ExpressionRules for Parser {
 		}
 	}
 
-	fn parse_unary_expression(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_unary_expression(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 
 		let left = self.parse_primary_expression()?;
@@ -571,7 +675,7 @@ impl ExpressionRules for Parser {
 		}
 	}
 
-	fn parse_primary_expression(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_primary_expression(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 
 		return match self.symbol.clone() {
@@ -655,7 +759,7 @@ impl ExpressionRules for Parser {
 							Symbols::Of( _ , _ ) => {
 								self.advance()
 							},
-							_ => return Err(Box::new(format!("Expecting 'of' in 'alias' expression at position: '{}'", self.lexer.get_start_position())))
+							_ => return Err(self.error(String::from("Expecting 'of' in 'alias' expression")))
 						}
 
 						let right = self.parse_factor()?;
@@ -670,7 +774,7 @@ impl ExpressionRules for Parser {
 							Symbols::LeftParen( _ , _ ) => {
 								self.advance()
 							},
-							_ => return Err(Box::new(format!("Expecting '(' in 'new' expression at position: '{}'", self.lexer.get_start_position())))
+							_ => return Err(self.error(String::from("Expecting '(' in 'new' expression")))
 						}
 
 						let right = self.parse_expression_list()?;
@@ -680,7 +784,7 @@ impl ExpressionRules for Parser {
 							Symbols::RightParen( _ , _ ) => {
 								self.advance()
 							},
-							_ => return Err(Box::new(format!("Expecting ')' in 'new' expression at position: '{}'", self.lexer.get_start_position())))
+							_ => return Err(self.error(String::from("Expecting ')' in 'new' expression")))
 						}
 
 						Ok( Box::new(Node::New(start_pos, self.lexer.get_start_position(), Box::new(x), left, Box::new(symbol2), right, Box::new(symbol3))))
@@ -695,7 +799,7 @@ impl ExpressionRules for Parser {
 							Symbols::RightParen( _ , _ ) => {
 								self.advance()
 							},
-							_ => return Err(Box::new(format!("Expecting ')' in parenthesized expression at position: '{}'", self.lexer.get_start_position())))
+							_ => return Err(self.error(String::from("Expecting ')' in parenthesized expression")))
 						}
 
 						Ok( Box::new(Node::ParenthesisExpression(start_pos, self.lexer.get_start_position(), Box::new(x), right, Box::new(symbol2))))
@@ -706,7 +810,7 @@ impl ExpressionRules for Parser {
 					Symbols::LeftBrace( _ , _ ) => {
 						self.parse_set()
 					}
-					_ => Err(Box::new(format!("Unexpected or missing literal at position: '{}'", self.lexer.get_start_position())))
+					_ => Err(self.error(String::from("Unexpected or missing literal")))
 				}
 			},
 			Err(e) => {
@@ -715,7 +819,7 @@ impl ExpressionRules for Parser {
 		}
 	}
 
-	fn parse_designator_operator(&mut self) -> Result<Box<Vec<Box<Node>>>, Box<String>> {
+	fn parse_designator_operator(&mut self) -> Result<Box<Vec<Box<Node>>>, Box<Diagnostic>> {
 		let mut start_pos = self.lexer.get_start_position();
 		let mut elements = Vec::<Box<Node>>::new();
 
@@ -739,7 +843,7 @@ impl ExpressionRules for Parser {
 
 							elements.push( Box::new( Node::Call(start_pos, self.lexer.get_start_position(), Box::new(symbol1), right, Box::new(symbol2)) ) )
 						},
-						_ => return Err(Box::new(format!("Missing ')' in call at position: '{}'", self.lexer.get_start_position())))
+						_ => return Err(self.error(String::from("Missing ')' in call")))
 					}
 				},
 				Symbols::Period( _ , _ ) => {
@@ -752,7 +856,7 @@ impl ExpressionRules for Parser {
 							self.advance();
 							elements.push( Box::new( Node::DotName(start_pos, self.lexer.get_start_position(), Box::new(symbol1), Box::new(Node::Ident(start_pos2, self.lexer.get_start_position(), Box::new(symbol2)))) ) )
 						},
-						_ => return Err(Box::new(format!("Expecting name literal after '.' at position: '{}'", self.lexer.get_start_position())))
+						_ => return Err(self.error(String::from("Expecting name literal after '.'")))
 					}
 				},
 				Symbols::LeftBracket( _ , _ ) => {
@@ -771,7 +875,7 @@ impl ExpressionRules for Parser {
 
 							elements.push( Box::new( Node::Index(start_pos, self.lexer.get_start_position(), Box::new(symbol1), right, Box::new(symbol2)) ) )
 						},
-						_ => return Err(Box::new(format!("Missing ']' in index at position: '{}'", self.lexer.get_start_position())))
+						_ => return Err(self.error(String::from("Missing ']' in index")))
 					}
 				},
 				Symbols::Arrow( _ , _ ) => {
@@ -791,7 +895,7 @@ impl ExpressionRules for Parser {
 		Ok( Box::new( elements ) )
 	}
 
-	fn parse_expression_list(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_expression_list(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 
 		let mut elements : Vec<Box<Node>> = Vec::new();
@@ -821,7 +925,7 @@ impl ExpressionRules for Parser {
 		Ok( Box::new( Node::ExpressionList(start_pos, self.lexer.get_start_position(), Box::new(elements), Box::new(separators)) ) )
 	}
 
-	fn parse_index_list(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_index_list(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 
 		let mut left : Option<Box<Node>> = None;
@@ -864,7 +968,7 @@ impl ExpressionRules for Parser {
 									_ => ()
 								}
 							},
-							_ => return Err(Box::new(format!("Expecting '?' in index expression at position: '{}'", self.lexer.get_start_position())))
+							_ => return Err(self.error(String::from("Expecting '?' in index expression")))
 						}
 					},
 					_ => ()
@@ -875,7 +979,7 @@ impl ExpressionRules for Parser {
 		Ok( Box::new( Node::IndexList(start_pos, self.lexer.get_start_position(), left, symbol1, symbol2, symbol3, right) ) )
 	}
 
-	fn parse_array(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_array(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 
 		let symbol1 = match self.symbol.clone()? {
@@ -884,7 +988,7 @@ impl ExpressionRules for Parser {
 				self.advance();
 				_symb1
 			},
-			_ => return Err(Box::new(format!("Expecting '[' in array expression at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting '[' in array expression")))
 		};
 
 		let mut elements : Vec<Box<Node>> = Vec::new();
@@ -909,13 +1013,13 @@ impl ExpressionRules for Parser {
 				self.advance();
 				_symb2
 			},
-			_ => return Err(Box::new(format!("Expecting ']' in array expression at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting ']' in array expression")))
 		};
 
 		Ok( Box::new( Node::Array(start_pos, self.lexer.get_start_position(), Box::new(symbol1), Box::new(elements), Box::new(separators), Box::new(symbol2)) ) )
 	}
 
-	fn parse_set(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_set(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 
 		let symbol1 = match self.symbol.clone()? {
@@ -924,7 +1028,7 @@ impl ExpressionRules for Parser {
 				self.advance();
 				_symb1
 			},
-			_ => return Err(Box::new(format!("Expecting start of set expression at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting start of set expression")))
 		};
 
 		let mut elements : Vec<Box<Node>> = Vec::new();
@@ -949,7 +1053,7 @@ impl ExpressionRules for Parser {
 				self.advance();
 				_symb2
 			},
-			_ => return Err(Box::new(format!("Expecting end of set expression at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting end of set expression")))
 		};
 
 		Ok( Box::new( Node::Set(start_pos, self.lexer.get_start_position(), Box::new(symbol1), Box::new(elements), Box::new(separators), Box::new(symbol2)) ) )
@@ -958,7 +1062,7 @@ impl ExpressionRules for Parser {
 
 /// Implements all statement rules in grammar of ActiveOberon
 impl StatementRules for Parser {
-	fn parse_statement(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_statement(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 
 		match self.symbol.clone()? {
@@ -970,7 +1074,7 @@ impl StatementRules for Parser {
 
 				match self.symbol.clone()? {
 					Symbols::Then( _ , _ ) => (),
-					_ => return Err(Box::new(format!("Expecting 'THEN' in if statement at position: '{}'", self.lexer.get_start_position())))
+					_ => return Err(self.error(String::from("Expecting 'THEN' in if statement")))
 				}
 				let symbol2 = self.symbol.clone()?;
 				self.advance();
@@ -989,7 +1093,7 @@ impl StatementRules for Parser {
 
 							match self.symbol.clone()? {
 								Symbols::Then( _ , _ ) => (),
-								_ => return Err(Box::new(format!("Expecting 'THEN' in elsif statement at position: '{}'", self.lexer.get_start_position())))
+								_ => return Err(self.error(String::from("Expecting 'THEN' in elsif statement")))
 							}
 							let symbol5 = self.symbol.clone()?;
 							self.advance();
@@ -1017,7 +1121,7 @@ impl StatementRules for Parser {
 
 				match self.symbol.clone()? {
 					Symbols::End( _ , _ ) => (),
-					_ => return Err(Box::new(format!("Expecting 'END' in if statement at position: '{}'", self.lexer.get_start_position())))
+					_ => return Err(self.error(String::from("Expecting 'END' in if statement")))
 				}
 				let symbol3 = self.symbol.clone()?;
 				self.advance();
@@ -1040,12 +1144,12 @@ impl StatementRules for Parser {
 						self.advance();
 						Box::new(Node::Ident(start_pos2, self.lexer.get_start_position(), Box::new(symbol2)))
 					},
-					_ => return Err(Box::new(format!("Expecting Identifier in for statement at position: '{}'", self.lexer.get_start_position())))
+					_ => return Err(self.error(String::from("Expecting Identifier in for statement")))
 				};
 
 				match self.symbol.clone()? {
 					Symbols::Colon( _ , _ ) => (),
-					_ => return Err(Box::new(format!("Expecting ':' in with statement at position: '{}'", self.lexer.get_start_position())))
+					_ => return Err(self.error(String::from("Expecting ':' in with statement")))
 				}
 				let symbol2 = self.symbol.clone()?;
 				self.advance();
@@ -1060,7 +1164,7 @@ impl StatementRules for Parser {
 						true => {
 							is_first = false;
 							match self.symbol.clone()? {
-								Symbols::Bar( _ , _ ) => return Err(Box::new(format!("No '|' at first element in with statement at position: '{}'", self.lexer.get_start_position()))),
+								Symbols::Bar( _ , _ ) => return Err(self.error(String::from("No '|' at first element in with statement"))),
 								_ => ()
 							}
 						},
@@ -1078,7 +1182,7 @@ impl StatementRules for Parser {
 
 					match self.symbol.clone()? {
 						Symbols::Do( _ , _ ) => (),
-						_ => return Err(Box::new(format!("Expecting 'DO' in with statement at position: '{}'", self.lexer.get_start_position())))
+						_ => return Err(self.error(String::from("Expecting 'DO' in with statement")))
 					}
 					let symbol_local2 = self.symbol.clone()?;
 					self.advance();
@@ -1103,7 +1207,7 @@ impl StatementRules for Parser {
 
 				match self.symbol.clone()? {
 					Symbols::End( _ , _ ) => (),
-					_ => return Err(Box::new(format!("Expecting 'END' in with statement at position: '{}'", self.lexer.get_start_position())))
+					_ => return Err(self.error(String::from("Expecting 'END' in with statement")))
 				}
 				let symbol3 = self.symbol.clone()?;
 				self.advance();
@@ -1118,7 +1222,7 @@ impl StatementRules for Parser {
 
 				match self.symbol.clone()? {
 					Symbols::Of( _ , _ ) => (),
-					_ => return Err(Box::new(format!("Expecting 'OF' in case statement at position: '{}'", self.lexer.get_start_position())))
+					_ => return Err(self.error(String::from("Expecting 'OF' in case statement")))
 				}
 				let symbol2 = self.symbol.clone()?;
 				self.advance();
@@ -1150,7 +1254,7 @@ impl StatementRules for Parser {
 
 				match self.symbol.clone()? {
 					Symbols::End( _ , _ ) => (),
-					_ => return Err(Box::new(format!("Expecting 'END' in case statement at position: '{}'", self.lexer.get_start_position())))
+					_ => return Err(self.error(String::from("Expecting 'END' in case statement")))
 				}
 				let symbol3 = self.symbol.clone()?;
 				self.advance();
@@ -1165,7 +1269,7 @@ impl StatementRules for Parser {
 
 				match self.symbol.clone()? {
 					Symbols::Do( _ , _ ) => (),
-					_ => return Err(Box::new(format!("Expecting 'DO' in while statement at position: '{}'", self.lexer.get_start_position())))
+					_ => return Err(self.error(String::from("Expecting 'DO' in while statement")))
 				}
 				let symbol2 = self.symbol.clone()?;
 				self.advance();
@@ -1174,7 +1278,7 @@ impl StatementRules for Parser {
 
 				match self.symbol.clone()? {
 					Symbols::End( _ , _ ) => (),
-					_ => return Err(Box::new(format!("Expecting 'END' in while statement at position: '{}'", self.lexer.get_start_position())))
+					_ => return Err(self.error(String::from("Expecting 'END' in while statement")))
 				}
 				let symbol3 = self.symbol.clone()?;
 				self.advance();
@@ -1189,7 +1293,7 @@ impl StatementRules for Parser {
 
 				match self.symbol.clone()? {
 					Symbols::Until( _ , _ ) => (),
-					_ => return Err(Box::new(format!("Expecting 'UNTIL' in repeat statement at position: '{}'", self.lexer.get_start_position())))
+					_ => return Err(self.error(String::from("Expecting 'UNTIL' in repeat statement")))
 				}
 				let symbol2 = self.symbol.clone()?;
 				self.advance();
@@ -1209,12 +1313,12 @@ impl StatementRules for Parser {
 						self.advance();
 						Box::new(Node::Ident(start_pos2, self.lexer.get_start_position(), Box::new(symbol2)))
 					},
-					_ => return Err(Box::new(format!("Expecting Identifier in for statement at position: '{}'", self.lexer.get_start_position())))
+					_ => return Err(self.error(String::from("Expecting Identifier in for statement")))
 				};
 
 				match self.symbol.clone()? {
 					Symbols::Becomes( _ , _ ) => (),
-					_ => return Err(Box::new(format!("Expecting ':=' in for statement at position: '{}'", self.lexer.get_start_position())))
+					_ => return Err(self.error(String::from("Expecting ':=' in for statement")))
 				}
 				let symbol3 = self.symbol.clone()?;
 				self.advance();
@@ -1223,7 +1327,7 @@ impl StatementRules for Parser {
 
 				match self.symbol.clone()? {
 					Symbols::To( _ , _ ) => (),
-					_ => return Err(Box::new(format!("Expecting 'TO' in for statement at position: '{}'", self.lexer.get_start_position())))
+					_ => return Err(self.error(String::from("Expecting 'TO' in for statement")))
 				}
 				let symbol4 = self.symbol.clone()?;
 				self.advance();
@@ -1244,7 +1348,7 @@ impl StatementRules for Parser {
 
 				match self.symbol.clone()? {
 					Symbols::Do( _ , _ ) => (),
-					_ => return Err(Box::new(format!("Expecting 'DO' in for statement at position: '{}'", self.lexer.get_start_position())))
+					_ => return Err(self.error(String::from("Expecting 'DO' in for statement")))
 				}
 				let symbol6 = self.symbol.clone()?;
 				self.advance();
@@ -1253,7 +1357,7 @@ impl StatementRules for Parser {
 
 				match self.symbol.clone()? {
 					Symbols::End( _ , _ ) => (),
-					_ => return Err(Box::new(format!("Expecting 'END' in for statement at position: '{}'", self.lexer.get_start_position())))
+					_ => return Err(self.error(String::from("Expecting 'END' in for statement")))
 				}
 				let symbol7 = self.symbol.clone()?;
 				self.advance();
@@ -1268,7 +1372,7 @@ impl StatementRules for Parser {
 
 				match self.symbol.clone()? {
 					Symbols::End( _ , _ ) => (),
-					_ => return Err(Box::new(format!("Expecting 'END' in loop statement at position: '{}'", self.lexer.get_start_position())))
+					_ => return Err(self.error(String::from("Expecting 'END' in loop statement")))
 				}
 				let symbol2 = self.symbol.clone()?;
 				self.advance();
@@ -1302,19 +1406,18 @@ impl StatementRules for Parser {
 				Ok( Box::new(Node::Await(start_pos, self.lexer.get_start_position(), Box::new(symbol1), right)) )
 			},
 			Symbols::Begin( _ , _ ) => self.parse_statement_block(),
-			Symbols::Code( _ , _ ) => {
+			Symbols::Code( _ , e ) => {
 				let symbol1 = self.symbol.clone()?;
-				self.advance();
 
+				/* Assembler text starts right after 'CODE', slice it before scanner reads it as Oberon symbols */
 				let code_bytes = *self.lexer.slice_assembler_code();
 
-				// This need to be controlled by argument, which assembler to use, later!
-				let mut assembler = AssemblerAMD64::new(code_bytes, self.lexer.get_start_position());
-				let code = assembler.assemble()?;
+				let code = self.assemble_code(code_bytes, e);
+				self.advance();
 
 				match self.symbol.clone()? {
 					Symbols::End( _ , _ ) => (),
-					_ => return Err(Box::new(format!("Expecting 'END' in code statement at position: '{}'", self.lexer.get_start_position())))
+					_ => return Err(self.error(String::from("Expecting 'END' in code statement")))
 				}
 				let symbol2 = self.symbol.clone()?;
 				self.advance();
@@ -1327,6 +1430,13 @@ impl StatementRules for Parser {
 
 				Ok( Box::new(Node::Ignore(start_pos, self.lexer.get_start_position(), Box::new(symbol1), right)) )
 			},
+			Symbols::SemiColon( _ , _ ) |
+			Symbols::End( _ , _ ) |
+			Symbols::Else( _ , _ ) |
+			Symbols::Elsif( _ , _ ) |
+			Symbols::Until( _ , _ ) |
+			Symbols::Finally( _ , _ ) |
+			Symbols::Bar( _ , _ ) => Ok( Box::new(Node::Empty) ), /* Empty statement */
 			_ => {
 				let left = self.parse_expression()?;
 				match self.symbol.clone()? {
@@ -1368,7 +1478,7 @@ impl StatementRules for Parser {
 		}
 	}
 
-	fn parse_case(&mut self, bar_optional: bool) -> Result<Box<Node>, Box<String>> {
+	fn parse_case(&mut self, bar_optional: bool) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 
 		let mut symbol1 = None;
@@ -1381,7 +1491,7 @@ impl StatementRules for Parser {
 			_ => {
 				match bar_optional {
 					true => (),
-					_ => return Err(Box::new(format!("Expecting '|' in case statement at position: '{}'", self.lexer.get_start_position())))
+					_ => return Err(self.error(String::from("Expecting '|' in case statement")))
 				}
 			}
 		}
@@ -1409,7 +1519,7 @@ impl StatementRules for Parser {
 				symbols2 = self.symbol.clone()?;
 				self.advance()
 			},
-			_ => return Err(Box::new(format!("Expecting ':' in case statement at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting ':' in case statement")))
 		}
 
 		let right = self.parse_statement_sequence()?;
@@ -1417,7 +1527,7 @@ impl StatementRules for Parser {
 		Ok( Box::new( Node::CaseElement(start_pos, self.lexer.get_start_position(), symbol1, elements, separators, Box::new(symbols2), right ) ) )
 	}
 
-	fn parse_statement_block(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_statement_block(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 
 		match self.symbol.clone()? {
@@ -1439,32 +1549,52 @@ impl StatementRules for Parser {
 
 						Ok( Box::new( Node::StatementBlock(start_pos, self.lexer.get_start_position(), Box::new(symbol1), flags, right, Box::new(symbol2)) ) )
 					},
-					_ => Err(Box::new(format!("Expecting 'END' in statement block at position: '{}'", self.lexer.get_start_position())))
+					_ => Err(self.error(String::from("Expecting 'END' in statement block")))
 				}
 			},
 			_ => {
-				Err(Box::new(format!("Expecting 'BEGIN' in statement block at position: '{}'", self.lexer.get_start_position())))
+				Err(self.error(String::from("Expecting 'BEGIN' in statement block")))
 			}
 		}
 	}
 
-	fn parse_statement_sequence(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_statement_sequence(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 
 		let mut nodes = Box::new( Vec::<Box<Node>>::new() );
 		let mut separators = Box::new( Vec::<Box<Symbols>>::new() );
 
-		nodes.push( self.parse_statement()? );
-
 		loop {
+			let start_pos2 = self.lexer.get_start_position();
+			let node = match self.parse_statement() {
+				Ok( x ) => x,
+				Err( e ) => self.recover(e, start_pos2)
+			};
+			nodes.push( node );
+
 			match self.symbol.clone()? {
-				Symbols::SemiColon( _ , _ ) => {
-					separators.push( Box::new(self.symbol.clone()?) );
-					self.advance();
-					nodes.push( self.parse_statement()? )
-				},
-				_ => break
+				Symbols::SemiColon( _ , _ ) => (),
+				Symbols::End( _ , _ ) |
+				Symbols::Else( _ , _ ) |
+				Symbols::Elsif( _ , _ ) |
+				Symbols::Until( _ , _ ) |
+				Symbols::Finally( _ , _ ) |
+				Symbols::Bar( _ , _ ) |
+				Symbols::EndOfFile( _ ) => break,
+				_ => {
+					/* Missing ';' or garbage after statement, skip it and continue with next statement */
+					let start_pos3 = self.lexer.get_start_position();
+					let error = self.error(String::from("Expecting ';' or 'END' after statement"));
+					nodes.push( self.recover(error, start_pos3) );
+					match self.symbol.clone()? {
+						Symbols::SemiColon( _ , _ ) => (),
+						_ => break
+					}
+				}
 			}
+
+			separators.push( Box::new(self.symbol.clone()?) );
+			self.advance()
 		}
 
 		Ok( Box::new( Node::StatementSequence(start_pos, self.lexer.get_start_position(), nodes, separators) ) )
@@ -1473,10 +1603,12 @@ impl StatementRules for Parser {
 
 /// Implements all block rules in grammar of ActiveOberon
 impl BlockRules for Parser {
-	fn parse_module(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_module(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 		let mut module_name_start = String::new();
 		let mut module_name_end = String::new();
+		let module_name_start_pos : ( u32, u32 );
+		let module_name_end_pos : ( u32, u32 );
 
 		self.advance(); /* Get the first symbol from source code before starting parsing */
 
@@ -1491,14 +1623,14 @@ impl BlockRules for Parser {
 				};
 
 				let symbol2 = match self.symbol.clone()? {
-					Symbols::Ident( s , _ , t ) => {
+					Symbols::Ident( s , e , t ) => {
 						module_name_start = *t;
-						println!("  Compiling module: '{}'", style(&module_name_start).green());
+						module_name_start_pos = ( s, e );
 						let symbol18 = self.symbol.clone()?;
 						self.advance();
 						Box::new( Node::Ident(s, self.lexer.get_start_position(), Box::new(symbol18)) )
 					},
-					_ => return Err(Box::new(format!("Expecting 'Ident' of module after 'MODULE' at position: '{}'", self.lexer.get_start_position())))
+					_ => return Err(self.error(String::from("Expecting 'Ident' of module after 'MODULE'")))
 				};
 
 				let in_part = match self.symbol.clone()? {
@@ -1514,7 +1646,7 @@ impl BlockRules for Parser {
 
 								Some( (Box::new(symbol16), Box::new(Node::Ident(start_pos2, self.lexer.get_start_position(), Box::new(symbol15)))) )
 							},
-							_ => return Err(Box::new(format!("Expecting 'Ident' of module after 'IN' at position: '{}'", self.lexer.get_start_position())))
+							_ => return Err(self.error(String::from("Expecting 'Ident' of module after 'IN'")))
 						}
 					},
 					_ => None
@@ -1526,7 +1658,7 @@ impl BlockRules for Parser {
 						self.advance();
 						Box::new( symbol17 )
 					},
-					_ => return Err(Box::new(format!("Expecting ';' of module after 'MODULE' ident at position: '{}'", self.lexer.get_start_position())))
+					_ => return Err(self.error(String::from("Expecting ';' of module after 'MODULE' ident")))
 				};
 
 				let imp = match self.symbol.clone()? {
@@ -1549,12 +1681,19 @@ impl BlockRules for Parser {
 					Symbols::Const( _ , _ ) |
 					Symbols::Procedure( _ , _ ) |
 					Symbols::Operator( _ , _ ) |
+					Symbols::Definition( _ , _ ) |
 					Symbols::SemiColon( _ , _ ) => Some( self.parse_declaration_sequence()? ),
 					_ => None
 				};
 
 				let body = match self.symbol.clone()? {
-					Symbols::Begin( _ , _ ) | Symbols::Code( _ , _ ) => Some( self.parse_body()? ),
+					Symbols::Begin( _ , _ ) | Symbols::Code( _ , _ ) => {
+						let start_pos2 = self.lexer.get_start_position();
+						match self.parse_body() {
+							Ok( x ) => Some( x ),
+							Err( e ) => Some( self.recover(e, start_pos2) )
+						}
+					},
 					_ => None
 				};
 
@@ -1566,17 +1705,18 @@ impl BlockRules for Parser {
 						self.advance();
 						Box::new( symbol19 )
 					},
-					_ => return Err(Box::new(format!("Expecting 'END' in module at position: '{}'", self.lexer.get_start_position())))
+					_ => return Err(self.error(String::from("Expecting 'END' in module")))
 				};
 
 				let symbol12 = match self.symbol.clone()? {
-					Symbols::Ident( s , _ , t ) => {
+					Symbols::Ident( s , e , t ) => {
 						module_name_end = *t;
+						module_name_end_pos = ( s, e );
 						let symbol21 = self.symbol.clone()?;
 						self.advance();
 						Box::new( Node::Ident(s, self.lexer.get_start_position(), Box::new( symbol21 )) )
  					},
-					_ => return Err(Box::new(format!("Expecting 'Ident' of module after 'END' at position: '{}'", self.lexer.get_start_position())))
+					_ => return Err(self.error(String::from("Expecting 'Ident' of module after 'END'")))
 				};
 
 				let period = match self.symbol.clone()? {
@@ -1585,20 +1725,21 @@ impl BlockRules for Parser {
 						self.advance();
 						Box::new( symbol20 )
 					},
-					_ => return Err(Box::new(format!("Expecting '.' at end of module at position: '{}'", self.lexer.get_start_position())))
+					_ => return Err(self.error(String::from("Expecting '.' at end of module")))
 				};
 
 				if module_name_start != module_name_end {
-					return Err(Box::new(format!("Expecting 'MODULE' name '{}' to be equal to 'END' name '{}' in module declaration at position: '{}'", module_name_start, module_name_end, self.lexer.get_start_position())))
+					return Err(Diagnostic::error(E_MODULE_NAME_MISMATCH, format!("Expecting 'MODULE' name '{}' to be equal to 'END' name '{}' in module declaration", module_name_start, module_name_end), module_name_end_pos.0, module_name_end_pos.1)
+						.with_label(format!("Module '{}' declared here", module_name_start), module_name_start_pos.0, module_name_start_pos.1))
 				}
 
 				Ok( Box::new( Node::Module(start_pos, self.lexer.get_start_position(), Box::new(symbol1), template, symbol2, in_part, symbol3, imp, decl, body, symbol11, symbol12, period) ) )
 			},
-			_ => Err(Box::new(format!("Expecting 'MODULE' in module declaration at position: '{}'", self.lexer.get_start_position())))
+			_ => Err(self.error(String::from("Expecting 'MODULE' in module declaration")))
 		}
 	}
 
-	fn parse_template_parameters(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_template_parameters(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 		let mut nodes = Box::new(Vec::<Box<Node>>::new());
 		let mut separators = Box::new(Vec::<Box<Symbols>>::new());
@@ -1609,7 +1750,7 @@ impl BlockRules for Parser {
 				self.advance();
 				Box::new(symbol11)
 			},
-			_ => return Err(Box::new(format!("Expecting '(' in template list declaration at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting '(' in template list declaration")))
 		};
 
 		nodes.push( self.parse_template_parameter()? );
@@ -1631,13 +1772,13 @@ impl BlockRules for Parser {
 				self.advance();
 				Box::new(symbol12)
 			},
-			_ => return Err(Box::new(format!("Expecting ')' in template list declaration at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting ')' in template list declaration")))
 		};
 
 		Ok( Box::new(Node::TemplateParameters(start_pos, self.lexer.get_start_position(), symbol1, nodes, separators, symbol2)) )
 	}
 
-	fn parse_template_parameter(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_template_parameter(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 
 		let symbol1 = match self.symbol.clone()? {
@@ -1646,7 +1787,7 @@ impl BlockRules for Parser {
 				self.advance();
 				Box::new(symbol11)
 			},
-			_ => return Err(Box::new(format!("Expecting 'CONST' or 'TYPE' in template declaration at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting 'CONST' or 'TYPE' in template declaration")))
 		};
 
 		let idx = match self.symbol.clone()? {
@@ -1655,13 +1796,13 @@ impl BlockRules for Parser {
 				self.advance();
 				Box::new( Node::Ident(s, self.lexer.get_start_position(), Box::new(symbol12)) )
 			},
-			_ => return Err(Box::new(format!("Expecting 'ident' literal in template declaration at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting 'ident' literal in template declaration")))
 		};
 
 		Ok( Box::new(Node::TemplateParameter(start_pos, self.lexer.get_start_position(), symbol1, idx)) )
 	}
 
-	fn parse_import_list(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_import_list(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 
 		let symbol1 = match self.symbol.clone()? {
@@ -1670,7 +1811,7 @@ impl BlockRules for Parser {
 				self.advance();
 				Box::new(symbol11)
 			},
-			_ => return Err(Box::new(format!("Expecting 'IMPORT' in import declaration at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting 'IMPORT' in import declaration")))
 		};
 
 		let mut nodes = Box::new(Vec::<Box<Node>>::new());
@@ -1695,13 +1836,13 @@ impl BlockRules for Parser {
 				self.advance();
 				Box::new(symbol12)
 			},
-			_ => return Err(Box::new(format!("Expecting ';' in import declaration at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting ';' in import declaration")))
 		};
 
 		Ok( Box::new(Node::ImportList(start_pos, self.lexer.get_start_position(), symbol1, nodes, separators, symbol2)) )
 	}
 
-	fn parse_import(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_import(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 
 		let symbol1 = match self.symbol.clone()? {
@@ -1710,7 +1851,7 @@ impl BlockRules for Parser {
 				self.advance();
 				Box::new( Node::Ident(s, self.lexer.get_start_position(), Box::new(symbol11)) )
 			},
-			_ => return Err(Box::new(format!("Expecting 'ident' literal in import declaration at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting 'ident' literal in import declaration")))
 		};
 
 		let left = match self.symbol.clone()? {
@@ -1725,7 +1866,7 @@ impl BlockRules for Parser {
 						let node = Box::new( Node::Ident(s, self.lexer.get_start_position(), Box::new(symbol22)) );
 						Some( (Box::new(symbol21), node) )
 					},
-					_ => return Err(Box::new(format!("Expecting 'ident' literal in import declaration after ':=' at position: '{}'", self.lexer.get_start_position())))
+					_ => return Err(self.error(String::from("Expecting 'ident' literal in import declaration after ':='")))
 				}
 			},
 			_ => None
@@ -1743,7 +1884,7 @@ impl BlockRules for Parser {
 						self.advance();
 						Some( ( Box::new(symbol31), right2, Box::new(symbol32) ) )
 					},
-					_ => return Err(Box::new(format!("Expecting ')' literal in import declaration at position: '{}'", self.lexer.get_start_position())))
+					_ => return Err(self.error(String::from("Expecting ')' literal in import declaration")))
 				}
 			},
 			_ => None
@@ -1761,7 +1902,7 @@ impl BlockRules for Parser {
 						let node = Box::new( Node::Ident(s, self.lexer.get_start_position(), Box::new(symbol42)) );
 						Some( (Box::new(symbol41), node) )
 					},
-					_ => return Err(Box::new(format!("Expecting 'ident' literal in import declaration after 'IN' at position: '{}'", self.lexer.get_start_position())))
+					_ => return Err(self.error(String::from("Expecting 'ident' literal in import declaration after 'IN'")))
 				}
 			},
 			_ => None
@@ -1770,7 +1911,7 @@ impl BlockRules for Parser {
 		Ok( Box::new(Node::Import(start_pos, self.lexer.get_start_position(), symbol1, left, right, next)) )
 	}
 
-	fn parse_declaration_sequence(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_declaration_sequence(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 
 		let mut const_declarations = Box::new(Vec::<Box<Node>>::new());
@@ -1778,6 +1919,7 @@ impl BlockRules for Parser {
 		let mut var_declarations = Box::new(Vec::<Box<Node>>::new());
 		let mut procedure_declarations = Box::new(Vec::<Box<Node>>::new());
 		let mut operator_declarations = Box::new(Vec::<Box<Node>>::new());
+		let mut definition_declarations = Box::new(Vec::<Box<Node>>::new());
 		let mut separators = Box::new(Vec::<Box<Symbols>>::new());
 
 		loop {
@@ -1787,21 +1929,26 @@ impl BlockRules for Parser {
 					let symbol = self.symbol.clone()?;
 					self.advance();
 					let mut const_declaration_local = Box::new(Vec::<Box<Node>>::new());
-					match self.symbol.clone()? {
-						Symbols::Ident( _ , _ , _ ) => {
-							const_declaration_local.push( self.parse_constant_declaration()? );
-							loop {
-								match self.symbol.clone()? {
-									Symbols::SemiColon( _ , _ ) => {
-										separators.push( Box::new(self.symbol.clone()?) );
-										self.advance();
-										const_declaration_local.push( self.parse_constant_declaration()? )
-									},
-									_ => break
-								}
-							}
-						},
-						_ => ()
+					while let Symbols::Ident( _ , _ , _ ) = self.symbol.clone()? {
+						let start_pos3 = self.lexer.get_start_position();
+						let node = match self.parse_constant_declaration() {
+							Ok( x ) => x,
+							Err( e ) => self.recover(e, start_pos3)
+						};
+						const_declaration_local.push( node );
+
+						match self.symbol.clone()? {
+							Symbols::SemiColon( _ , _ ) => {
+								separators.push( Box::new(self.symbol.clone()?) );
+								self.advance()
+							},
+							Symbols::Ident( _ , _ , _ ) => {
+								/* Missing ';', continue with next declaration */
+								let error = self.error(String::from("Expecting ';' after constant declaration"));
+								self.errors.push(error)
+							},
+							_ => break
+						}
 					}
 					const_declarations.push( Box::new(Node::ConstDeclaration(start_pos2, self.lexer.get_start_position(), Box::new(symbol), const_declaration_local) ) )
 				},
@@ -1811,22 +1958,28 @@ impl BlockRules for Parser {
 					self.advance();
 					let mut type_declaration_local = Box::new(Vec::<Box<Node>>::new());
 
-					match self.symbol.clone()? {
-						Symbols::Ident( _ , _ , _ ) => {
-							let node_first = self.parse_type_declaration()?;
-							type_declaration_local.push( node_first );
-
-							loop {
-								match self.symbol.clone()? {
-									Symbols::Ident( _ , _ , _ ) => {
-										let node_next = self.parse_type_declaration()?;
-										type_declaration_local.push( node_next );
-									},
-									_ => break
-								}
-							}
-						},
-						_ => ()
+					loop {
+						match self.symbol.clone()? {
+							Symbols::Ident( _ , _ , _ ) => {
+								let start_pos3 = self.lexer.get_start_position();
+								let node = match self.parse_type_declaration() {
+									Ok( x ) => x,
+									Err( e ) => {
+										let node_error = self.recover(e, start_pos3);
+										match self.symbol.clone()? {
+											Symbols::SemiColon( _ , _ ) => {
+												separators.push( Box::new(self.symbol.clone()?) );
+												self.advance()
+											},
+											_ => ()
+										}
+										node_error
+									}
+								};
+								type_declaration_local.push( node );
+							},
+							_ => break
+						}
 					}
 
 					type_declarations.push( Box::new(Node::TypeDeclaration(start_pos2, self.lexer.get_start_position(), Box::new(symbol), type_declaration_local) ) )
@@ -1836,29 +1989,52 @@ impl BlockRules for Parser {
 					let symbol = self.symbol.clone()?;
 					self.advance();
 					let mut var_declaration_local = Box::new(Vec::<Box<Node>>::new());
-					match self.symbol.clone()? {
-						Symbols::Ident( _ , _ , _ ) => {
-							var_declaration_local.push( self.parse_variable_declaration()? );
-							loop {
-								match self.symbol.clone()? {
-									Symbols::SemiColon( _ , _ ) => {
-										separators.push( Box::new(self.symbol.clone()?) );
-										self.advance();
-										var_declaration_local.push( self.parse_variable_declaration()? )
-									},
-									_ => break
-								}
-							}
-						},
-						_ => ()
+					while let Symbols::Ident( _ , _ , _ ) = self.symbol.clone()? {
+						let start_pos3 = self.lexer.get_start_position();
+						let node = match self.parse_variable_declaration() {
+							Ok( x ) => x,
+							Err( e ) => self.recover(e, start_pos3)
+						};
+						var_declaration_local.push( node );
+
+						match self.symbol.clone()? {
+							Symbols::SemiColon( _ , _ ) => {
+								separators.push( Box::new(self.symbol.clone()?) );
+								self.advance()
+							},
+							Symbols::Ident( _ , _ , _ ) => {
+								/* Missing ';', continue with next declaration */
+								let error = self.error(String::from("Expecting ';' after variable declaration"));
+								self.errors.push(error)
+							},
+							_ => break
+						}
 					}
 					var_declarations.push( Box::new(Node::VarDeclaration(start_pos2, self.lexer.get_start_position(), Box::new(symbol), var_declaration_local) ) )
 				},
 				Symbols::Procedure(_, _) => {
-					procedure_declarations.push( self.parse_procedure_declaration()? )
+					let start_pos2 = self.lexer.get_start_position();
+					let node = match self.parse_procedure_declaration() {
+						Ok( x ) => x,
+						Err( e ) => self.recover(e, start_pos2)
+					};
+					procedure_declarations.push( node )
 				},
 				Symbols::Operator(_, _) => {
-					operator_declarations.push( self.parse_operator_declaration()? )
+					let start_pos2 = self.lexer.get_start_position();
+					let node = match self.parse_operator_declaration() {
+						Ok( x ) => x,
+						Err( e ) => self.recover(e, start_pos2)
+					};
+					operator_declarations.push( node )
+				},
+				Symbols::Definition(_, _) => {
+					let start_pos2 = self.lexer.get_start_position();
+					let node = match self.parse_definition() {
+						Ok( x ) => x,
+						Err( e ) => self.recover(e, start_pos2)
+					};
+					definition_declarations.push( node )
 				},
 				Symbols::SemiColon(_, _) => {
 					separators.push( Box::new(self.symbol.clone()?) );
@@ -1868,15 +2044,15 @@ impl BlockRules for Parser {
 			}
 		}
 
-		Ok( Box::new(Node::DeclarationSequence(start_pos, self.lexer.get_start_position(), const_declarations, type_declarations, var_declarations, procedure_declarations, operator_declarations, separators)) )
+		Ok( Box::new(Node::DeclarationSequence(start_pos, self.lexer.get_start_position(), const_declarations, type_declarations, var_declarations, procedure_declarations, operator_declarations, definition_declarations, separators)) )
 	}
 
-	fn parse_constant_declaration(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_constant_declaration(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 
 		let first = match self.symbol.clone()? {
 			Symbols::Ident( _ , _ , _ ) => self.parse_identifier_definition()?,
-			_ => return Err(Box::new(format!("Expecting 'ident' literal in const declaration at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting 'ident' literal in const declaration")))
 		};
 
 		let symbol = match self.symbol.clone()? {
@@ -1885,7 +2061,7 @@ impl BlockRules for Parser {
 				self.advance();
 				Box::new(symbol11)
 			},
-			_ => return Err(Box::new(format!("Expecting '=' literal in const declaration at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting '=' literal in const declaration")))
 		};
 
 		let second = self.parse_expression()?;
@@ -1893,12 +2069,12 @@ impl BlockRules for Parser {
 		Ok( Box::new(Node::Const(start_pos, self.lexer.get_start_position(), first, symbol, second)) )
 	}
 
-	fn parse_variable_declaration(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_variable_declaration(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 
 		let first = match self.symbol.clone()? {
 			Symbols::Ident( _ , _ , _ ) => self.parse_variable_name_list()?,
-			_ => return Err(Box::new(format!("Expecting 'indent' literal in var declaration at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting 'indent' literal in var declaration")))
 		};
 
 		let symbol = match self.symbol.clone()? {
@@ -1907,7 +2083,7 @@ impl BlockRules for Parser {
 				self.advance();
 				Box::new(symbol11)
 			},
-			_ => return Err(Box::new(format!("Expecting ':' literal in var declaration at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting ':' literal in var declaration")))
 		};
 
 		let second = self.parse_type()?;
@@ -1915,14 +2091,14 @@ impl BlockRules for Parser {
 		Ok( Box::new(Node::Var(start_pos, self.lexer.get_start_position(), first, symbol, second)) )
 	}
 
-	fn parse_variable_name_list(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_variable_name_list(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 		let mut nodes = Box::new( Vec::<Box<Node>>::new() );
 		let mut separators = Box::new( Vec::<Box<Symbols>>::new() );
 
 		match self.symbol.clone()? {
 			Symbols::Ident( _ , _ , _ ) => (),
-			_ => return Err(Box::new(format!("Expecting 'indent' literal in var declaration list at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting 'indent' literal in var declaration list")))
 		};
 
 		nodes.push( self.parse_variable_name()? );
@@ -1941,12 +2117,12 @@ impl BlockRules for Parser {
 		Ok( Box::new(Node::VarList(start_pos, self.lexer.get_start_position(), nodes, separators)) )
 	}
 
-	fn parse_variable_name(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_variable_name(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 
 		let first = match self.symbol.clone()? {
 			Symbols::Ident( _ , _ , _ ) => self.parse_identifier_definition()?,
-			_ => return Err(Box::new(format!("Expecting 'indent' literal in var declaration at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting 'indent' literal in var declaration")))
 		};
 
 		let flags = match self.symbol.clone()? {
@@ -1970,7 +2146,7 @@ impl BlockRules for Parser {
 						self.advance();
 						Box::new(Node::String(s, self.lexer.get_start_position(), Box::new(symbol2)))
 					},
-					_ => return Err(Box::new(format!("Expecting 'string' literal in var declaration at position: '{}'", self.lexer.get_start_position())))
+					_ => return Err(self.error(String::from("Expecting 'string' literal in var declaration")))
 				};
 
 				Ok( Box::new(Node::VarName(start_pos, self.lexer.get_start_position(), first, flags, Some( (Box::new(symbol1), txt) ))) )
@@ -1981,7 +2157,7 @@ impl BlockRules for Parser {
 		}
 	}
 
-	fn parse_flags(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_flags(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 		let mut nodes = Box::new( Vec::<Box<Node>>::new() );
 		let mut separators = Box::new( Vec::<Box<Symbols>>::new() );
@@ -2014,16 +2190,16 @@ impl BlockRules for Parser {
 						self.advance();
 						Box::new(symbol51)
 					},
-					_ => return Err(Box::new(format!("Expecting end of flag list declaration at position: '{}'", self.lexer.get_start_position())))
+					_ => return Err(self.error(String::from("Expecting end of flag list declaration")))
 				};
 
 				Ok( Box::new(Node::Flags(start_pos, self.lexer.get_start_position(), Box::new(symbol1), nodes, separators, symbol2)) )
 			},
-			_ => Err(Box::new(format!("Expecting start of flag list declaration at position: '{}'", self.lexer.get_start_position())))
+			_ => Err(self.error(String::from("Expecting start of flag list declaration")))
 		}
 	}
 
-	fn parse_flag(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_flag(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 
 		return match self.symbol.clone()? {
@@ -2041,7 +2217,7 @@ impl BlockRules for Parser {
 
 						match self.symbol.clone()? {
 							Symbols::RightParen( _ , _ ) => (),
-							_ => return Err(Box::new(format!("Expecting ')' in flag declaration at position: '{}'", self.lexer.get_start_position())))
+							_ => return Err(self.error(String::from("Expecting ')' in flag declaration")))
 						};
 						let symbol22 = self.symbol.clone()?;
 						self.advance();
@@ -2059,16 +2235,16 @@ impl BlockRules for Parser {
 					}
 				}
 			},
-			_ => Err(Box::new(format!("Expecting 'ident' in flag declaration at position: '{}'", self.lexer.get_start_position())))
+			_ => Err(self.error(String::from("Expecting 'ident' in flag declaration")))
 		}
 	}
 
-	fn parse_procedure_declaration(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_procedure_declaration(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 
 		match self.symbol.clone()? {
 			Symbols::Procedure( _ , _ ) => (),
-			_ => return Err(Box::new(format!("Expecting 'PROCEDURE' in procedure declaration at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting 'PROCEDURE' in procedure declaration")))
 		}
 		let symbol1 = self.symbol.clone()?;
 		self.advance();
@@ -2105,7 +2281,7 @@ impl BlockRules for Parser {
 
 				match self.symbol.clone()? {
 					Symbols::RightParen( _ , _ ) => (),
-					_ => return Err(Box::new(format!("Expecting ')' in procedure declaration at position: '{}'", self.lexer.get_start_position())))
+					_ => return Err(self.error(String::from("Expecting ')' in procedure declaration")))
 				}
 				let symbol52 = self.symbol.clone()?;
 				self.advance();
@@ -2117,7 +2293,7 @@ impl BlockRules for Parser {
 
 		match self.symbol.clone()? {
 			Symbols::Ident( _ , _ , _ ) => (),
-			_ => return Err(Box::new(format!("Expecting name of procedure in declaration at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting name of procedure in declaration")))
 		};
 		let third = self.parse_identifier_definition()?;
 
@@ -2128,7 +2304,7 @@ impl BlockRules for Parser {
 
 		match self.symbol.clone()? {
 			Symbols::SemiColon( _ , _ ) => (),
-			_ => return Err(Box::new(format!("Expecting ';' in procedure declaration at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting ';' in procedure declaration")))
 		}
 		let symbol2 = self.symbol.clone()?;
 		self.advance();
@@ -2150,7 +2326,7 @@ impl BlockRules for Parser {
 
 		match self.symbol.clone()? {
 			Symbols::End( _ , _ ) => (),
-			_ => return Err(Box::new(format!("Expecting 'END' in procedure declaration at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting 'END' in procedure declaration")))
 		}
 		let symbol3 = self.symbol.clone()?;
 		self.advance();
@@ -2161,18 +2337,101 @@ impl BlockRules for Parser {
 				self.advance();
 				Box::new( Node::Ident(s, self.lexer.get_start_position(), Box::new(symbol40)) )
 			},
-			_ => return Err(Box::new(format!("Expecting 'name' literal in procedure declaration at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting 'name' literal in procedure declaration")))
 		};
 
 		Ok( Box::new(Node::Procedure(start_pos, self.lexer.get_start_position(), Box::new(symbol1), first, second, third, forth, Box::new(symbol2), decl, body, Box::new(symbol3), fifth)) )
 	}
 
-	fn parse_operator_declaration(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_definition(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
+		let start_pos = self.lexer.get_start_position();
+
+		match self.symbol.clone()? {
+			Symbols::Definition( _ , _ ) => (),
+			_ => return Err(self.error(String::from("Expecting 'DEFINITION' in definition declaration")))
+		}
+		let symbol1 = self.symbol.clone()?;
+		self.advance();
+
+		match self.symbol.clone()? {
+			Symbols::Ident( _ , _ , _ ) => (),
+			_ => return Err(self.error(String::from("Expecting name of definition in declaration")))
+		};
+		let name = self.parse_identifier_definition()?;
+
+		match self.symbol.clone()? {
+			Symbols::SemiColon( _ , _ ) => (),
+			_ => return Err(self.error(String::from("Expecting ';' in definition declaration")))
+		}
+		let symbol2 = self.symbol.clone()?;
+		self.advance();
+
+		let mut headings = Box::new(Vec::<Box<Node>>::new());
+		while let Symbols::Procedure( _ , _ ) = self.symbol.clone()? {
+			headings.push( self.parse_procedure_heading()? )
+		}
+
+		match self.symbol.clone()? {
+			Symbols::End( _ , _ ) => (),
+			_ => return Err(self.error(String::from("Expecting 'END' in definition declaration")))
+		}
+		let symbol3 = self.symbol.clone()?;
+		self.advance();
+
+		let ident = match self.symbol.clone()? {
+			Symbols::Ident( s , _ , _ ) => {
+				let symbol4 = self.symbol.clone()?;
+				self.advance();
+				Box::new( Node::Ident(s, self.lexer.get_start_position(), Box::new(symbol4)) )
+			},
+			_ => return Err(self.error(String::from("Expecting 'name' literal in definition declaration")))
+		};
+
+		Ok( Box::new(Node::Definition(start_pos, self.lexer.get_start_position(), Box::new(symbol1), name, Box::new(symbol2), headings, Box::new(symbol3), ident)) )
+	}
+
+	fn parse_procedure_heading(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
+		let start_pos = self.lexer.get_start_position();
+
+		match self.symbol.clone()? {
+			Symbols::Procedure( _ , _ ) => (),
+			_ => return Err(self.error(String::from("Expecting 'PROCEDURE' in procedure heading")))
+		}
+		let symbol1 = self.symbol.clone()?;
+		self.advance();
+
+		let flags = match self.symbol.clone()? {
+			Symbols::LeftBrace( _ , _ ) => Some( self.parse_flags()? ),
+			_ => None
+		};
+
+		match self.symbol.clone()? {
+			Symbols::Ident( _ , _ , _ ) => (),
+			_ => return Err(self.error(String::from("Expecting name of procedure in procedure heading")))
+		};
+		let name = self.parse_identifier_definition()?;
+
+		let para = match self.symbol.clone()? {
+			Symbols::LeftParen( _ , _ ) => Some( self.parse_formal_parameters()? ),
+			_ => None
+		};
+
+		match self.symbol.clone()? {
+			Symbols::SemiColon( _ , _ ) => (),
+			_ => return Err(self.error(String::from("Expecting ';' in procedure heading")))
+		}
+		let symbol2 = self.symbol.clone()?;
+		self.advance();
+
+		Ok( Box::new(Node::ProcedureHeading(start_pos, self.lexer.get_start_position(), Box::new(symbol1), flags, name, para, Box::new(symbol2))) )
+	}
+
+	fn parse_operator_declaration(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 
 		match self.symbol.clone()? {
 			Symbols::Operator( _ , _ ) => (),
-			_ => return Err(Box::new(format!("Expecting 'OPERATOR' in operator declaration at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting 'OPERATOR' in operator declaration")))
 		}
 		let symbol1 = self.symbol.clone()?;
 		self.advance();
@@ -2197,7 +2456,7 @@ impl BlockRules for Parser {
 				self.advance();
 				Box::new( Node::String(s, self.lexer.get_start_position(), Box::new(symbol30)) )
 			},
-			_ => return Err(Box::new(format!("Expecting 'string' literal in operator declaration at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting 'string' literal in operator declaration")))
 		};
 
 		let symbol3 = match self.symbol.clone()? {
@@ -2213,7 +2472,7 @@ impl BlockRules for Parser {
 
 		match self.symbol.clone()? {
 			Symbols::SemiColon( _ , _ ) => (),
-			_ => return Err(Box::new(format!("Expecting ';' in operator declaration at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting ';' in operator declaration")))
 		}
 		let symbol4 = self.symbol.clone()?;
 		self.advance();
@@ -2235,7 +2494,7 @@ impl BlockRules for Parser {
 
 		match self.symbol.clone()? {
 			Symbols::End( _ , _ ) => (),
-			_ => return Err(Box::new(format!("Expecting 'END' in operator declaration at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting 'END' in operator declaration")))
 		}
 		let symbol5 = self.symbol.clone()?;
 		self.advance();
@@ -2246,20 +2505,20 @@ impl BlockRules for Parser {
 				self.advance();
 				Box::new( Node::String(s, self.lexer.get_start_position(), Box::new(symbol40)) )
 			},
-			_ => return Err(Box::new(format!("Expecting 'string' literal in operator declaration at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting 'string' literal in operator declaration")))
 		};
 
 		Ok( Box::new(Node::Operator(start_pos, self.lexer.get_start_position(), Box::new(symbol1), flags, symbol2, first, symbol3, second, Box::new(symbol4), decl, body, Box::new(symbol5), third)) )
 	}
 
-	fn parse_formal_parameters(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_formal_parameters(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 		let mut nodes = Box::new(Vec::<Box<Node>>::new());
 		let mut separators = Box::new(Vec::<Box<Symbols>>::new());
 
 		match self.symbol.clone()? {
 			Symbols::LeftParen( _ , _ ) => (),
-			_ => return Err(Box::new(format!("Expecting '(' in formal parameters declaration at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting '(' in formal parameters declaration")))
 		}
 		let symbol1 = self.symbol.clone()?;
 		self.advance();
@@ -2283,7 +2542,7 @@ impl BlockRules for Parser {
 
 		match self.symbol.clone()? {
 			Symbols::RightParen( _ , _ ) => (),
-			_ => return Err(Box::new(format!("Expecting ')' in formal parameters declaration at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting ')' in formal parameters declaration")))
 		}
 		let symbol2 = self.symbol.clone()?;
 		self.advance();
@@ -2308,7 +2567,7 @@ impl BlockRules for Parser {
 		Ok( Box::new(Node::FormalParameters(start_pos, self.lexer.get_start_position(), Box::new(symbol1), nodes, separators, Box::new(symbol2), element)) )
 	}
 
-	fn parse_parameter_declaration(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_parameter_declaration(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 		let mut nodes = Box::new(Vec::<Box<Node>>::new());
 		let mut separators = Box::new(Vec::<Box<Symbols>>::new());
@@ -2345,7 +2604,7 @@ impl BlockRules for Parser {
 					};
 					nodes.push( Box::new(Node::Parameter(s, self.lexer.get_start_position(), node, flags, right )) )
 				},
-				_ => return Err(Box::new(format!("Expecting 'indent' literal in parameter declaration at position: '{}'", self.lexer.get_start_position())))
+				_ => return Err(self.error(String::from("Expecting 'indent' literal in parameter declaration")))
 			}
 
 			match self.symbol.clone()? {
@@ -2366,23 +2625,29 @@ impl BlockRules for Parser {
 
 				(Box::new(symbol3), right)
 			},
-			_ => return Err(Box::new(format!("Expecting ':' in type declaration at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting ':' in type declaration")))
 		};
 
 		Ok( Box::new(Node::ParameterDeclaration(start_pos, self.lexer.get_start_position(), symbol1, nodes, separators, element, node)) )
 	}
 
-	fn parse_body(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_body(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 
 		return match self.symbol.clone()? {
-			Symbols::Code( _ , _ ) => {
+			Symbols::Code( _ , e ) => {
 				let symbol1 = self.symbol.clone()?;
+
+				/* Procedure body written in assembler, 'END' is left for the procedure declaration */
+				let code_bytes = *self.lexer.slice_assembler_code();
+				let code_end = e + code_bytes.len() as u32;
+
+				let code = self.assemble_code(code_bytes, e);
 				self.advance();
 
-				// Handle inline assembler later here!
+				let block = Box::new(Node::CodeBlock(e, code_end, code));
 
-				Ok( Box::new( Node::BodyCode(start_pos, self.lexer.get_start_position(), Box::new(symbol1), Box::new(Node::Empty)) ) )
+				Ok( Box::new( Node::BodyCode(start_pos, self.lexer.get_start_position(), Box::new(symbol1), block) ) )
 			},
 			Symbols::Begin( _ , _ ) => {
 				let symbol1 = self.symbol.clone()?;
@@ -2409,18 +2674,18 @@ impl BlockRules for Parser {
 
 				Ok( Box::new( Node::Body(start_pos, self.lexer.get_start_position(), Box::new(symbol1), flags, right, fin) ) )
 			},
-			_ => Err(Box::new(format!("Expecting ';' in type declaration at position: '{}'", self.lexer.get_start_position())))
+			_ => Err(self.error(String::from("Expecting ';' in type declaration")))
 		}
 	}
 
-	fn parse_type_declaration(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_type_declaration(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 
 		let left = self.parse_identifier_definition()?;
 
 		match self.symbol.clone()? {
 			Symbols::Equal( _ , _ ) => (),
-			_ => return Err(Box::new(format!("Expecting '=' in type declaration at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting '=' in type declaration")))
 		}
 		let symbol1 = self.symbol.clone()?;
 		self.advance();
@@ -2429,7 +2694,7 @@ impl BlockRules for Parser {
 
 		match self.symbol.clone()? {
 			Symbols::SemiColon( _ , _ ) => (),
-			_ => return Err(Box::new(format!("Expecting ';' in type declaration at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting ';' in type declaration")))
 		}
 		let symbol2 = self.symbol.clone()?;
 		self.advance();
@@ -2438,7 +2703,7 @@ impl BlockRules for Parser {
 		Ok( Box::new(Node::TypeDeclarationElement(start_pos, self.lexer.get_start_position(), left, Box::new(symbol1), right, Box::new(symbol2))) )
 	}
 
-	fn parse_type(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_type(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		match self.symbol.clone()? {
 			Symbols::Array( _ , _ ) => self.parse_array_type(),
 			Symbols::Record( _ , _ ) => self.parse_record_type(),
@@ -2447,19 +2712,31 @@ impl BlockRules for Parser {
 			Symbols::Procedure( _ , _ ) => self.parse_procedure_type(),
 			Symbols::Enum( _ , _ ) => self.parse_enumeration_type(),
 			Symbols::Ident( _ , _ , _ ) => self.parse_qualified_identifier(),
+			Symbols::Address( _ , _ ) => {
+				let start_pos = self.lexer.get_start_position();
+				let symbol1 = self.symbol.clone()?;
+				self.advance();
+				Ok( Box::new(Node::Address(start_pos, self.lexer.get_start_position(), Box::new(symbol1), None)) )
+			},
+			Symbols::Size( _ , _ ) => {
+				let start_pos = self.lexer.get_start_position();
+				let symbol1 = self.symbol.clone()?;
+				self.advance();
+				Ok( Box::new(Node::Size(start_pos, self.lexer.get_start_position(), Box::new(symbol1), None)) )
+			},
 			Symbols::Cell( _ , _ ) |
 			Symbols::Cellnet( _ , _ ) => self.parse_cell_type(),
 			Symbols::Port( _ , _ ) => self.parse_port_type(),
-			_ => Err(Box::new(format!("Expecting type at position: '{}'", self.lexer.get_start_position())))
+			_ => Err(self.error(String::from("Expecting type")))
 		}
 	}
 
-	fn parse_array_type(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_array_type(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 
 		match self.symbol.clone()? {
 			Symbols::Array( _ , _ ) => (),
-			_ => return Err(Box::new(format!("Expecting 'ARRAY' in array type at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting 'ARRAY' in array type")))
 		}
 		let symbol1 = self.symbol.clone()?;
 		self.advance();
@@ -2514,7 +2791,7 @@ impl BlockRules for Parser {
 
 		match self.symbol.clone()? {
 			Symbols::Of( _ , _ ) => (),
-			_ => return Err(Box::new(format!("Expecting 'OF' in array type at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting 'OF' in array type")))
 		}
 		let symbol2= self.symbol.clone()?;
 		self.advance();
@@ -2532,12 +2809,12 @@ impl BlockRules for Parser {
 		}
 	}
 
-	fn parse_record_type(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_record_type(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 
 		match self.symbol.clone()? {
 			Symbols::Record( _ , _ ) => (),
-			_ => return Err(Box::new(format!("Expecting 'RECORD' in record type at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting 'RECORD' in record type")))
 		}
 		let symbol1= self.symbol.clone()?;
 		self.advance();
@@ -2551,7 +2828,7 @@ impl BlockRules for Parser {
 
 				match self.symbol.clone()? {
 					Symbols::RightParen( _ , _ ) => (),
-					_ => return Err(Box::new(format!("Expecting ')' in record type at position: '{}'", self.lexer.get_start_position())))
+					_ => return Err(self.error(String::from("Expecting ')' in record type")))
 				}
 				let symbol11= self.symbol.clone()?;
 				self.advance();
@@ -2622,7 +2899,7 @@ impl BlockRules for Parser {
 
 		match self.symbol.clone()? {
 			Symbols::End( _ , _ ) => (),
-			_ => return Err(Box::new(format!("Expecting 'END' in record type at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting 'END' in record type")))
 		}
 		let symbol2= self.symbol.clone()?;
 		self.advance();
@@ -2630,12 +2907,12 @@ impl BlockRules for Parser {
 		Ok( Box::new(Node::RecordType(start_pos, self.lexer.get_start_position(), Box::new(symbol1), base, el_var, el_op, Box::new(symbol2))) )
 	}
 
-	fn parse_pointer_type(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_pointer_type(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 
 		match self.symbol.clone()? {
 			Symbols::Pointer( _ , _ ) => (),
-			_ => return Err(Box::new(format!("Expecting 'POINTER' in pointer type at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting 'POINTER' in pointer type")))
 		}
 		let symbol1= self.symbol.clone()?;
 		self.advance();
@@ -2647,7 +2924,7 @@ impl BlockRules for Parser {
 
 		match self.symbol.clone()? {
 			Symbols::To( _ , _ ) => (),
-			_ => return Err(Box::new(format!("Expecting 'TO' in pointer type at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting 'TO' in pointer type")))
 		}
 		let symbol2= self.symbol.clone()?;
 		self.advance();
@@ -2657,12 +2934,12 @@ impl BlockRules for Parser {
 		Ok( Box::new(Node::PointerType(start_pos, self.lexer.get_start_position(), Box::new(symbol1), flags, Box::new(symbol2), right)) )
 	}
 
-	fn parse_procedure_type(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_procedure_type(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 
 		match self.symbol.clone()? {
 			Symbols::Procedure( _ , _ ) => (),
-			_ => return Err(Box::new(format!("Expecting 'PROCEDURE' in procedure type at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting 'PROCEDURE' in procedure type")))
 		}
 		let symbol1= self.symbol.clone()?;
 		self.advance();
@@ -2680,12 +2957,12 @@ impl BlockRules for Parser {
 		Ok( Box::new(Node::ProcedureType(start_pos, self.lexer.get_start_position(), Box::new(symbol1), flags, para)) )
 	}
 
-	fn parse_object_type(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_object_type(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 
 		match self.symbol.clone()? {
 			Symbols::Object( _ , _ ) => (),
-			_ => return Err(Box::new(format!("Expecting 'OBJECT' in object type at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting 'OBJECT' in object type")))
 		}
 		let symbol1= self.symbol.clone()?;
 		self.advance();
@@ -2693,6 +2970,7 @@ impl BlockRules for Parser {
 		match self.symbol.clone()? {
 			Symbols::LeftParen( _ , _ ) |
 			Symbols::LeftBrace( _ , _ ) |
+			Symbols::Implements( _ , _ ) |
 			Symbols::Begin( _ , _ ) |
 			Symbols::End( _ , _ ) |
 			Symbols::SemiColon( _ , _ ) |
@@ -2716,7 +2994,7 @@ impl BlockRules for Parser {
 
 						match self.symbol.clone()? {
 							Symbols::RightParen( _ , _ ) => (),
-							_ => return Err(Box::new(format!("Expecting ')' in object type at position: '{}'", self.lexer.get_start_position())))
+							_ => return Err(self.error(String::from("Expecting ')' in object type")))
 						}
 						let symbol12= self.symbol.clone()?;
 						self.advance();
@@ -2726,6 +3004,30 @@ impl BlockRules for Parser {
 					_ => None
 				};
 
+				let implements = match self.symbol.clone()? {
+					Symbols::Implements( _ , _ ) => {
+						let symbol13 = self.symbol.clone()?;
+						self.advance();
+
+						let mut nodes = Box::new(Vec::<Box<Node>>::new());
+						let mut separators = Box::new(Vec::<Box<Symbols>>::new());
+						nodes.push( self.parse_qualified_identifier()? );
+						loop {
+							match self.symbol.clone()? {
+								Symbols::Comma( _ , _ ) => {
+									separators.push( Box::new(self.symbol.clone()?) );
+									self.advance();
+									nodes.push( self.parse_qualified_identifier()? )
+								},
+								_ => break
+							}
+						}
+
+						Some( (Box::new(symbol13), nodes, separators) )
+					},
+					_ => None
+				};
+
 				let second = match self.symbol.clone()? {
 					Symbols::SemiColon( _ , _ ) |
 					Symbols::Var( _ , _ ) |
@@ -2745,7 +3047,7 @@ impl BlockRules for Parser {
 
 				match self.symbol.clone()? {
 					Symbols::End( _ , _ ) => (),
-					_ => return Err(Box::new(format!("Expecting 'END' in object type at position: '{}'", self.lexer.get_start_position())))
+					_ => return Err(self.error(String::from("Expecting 'END' in object type")))
 				}
 				let symbol2= self.symbol.clone()?;
 				self.advance();
@@ -2760,18 +3062,18 @@ impl BlockRules for Parser {
 					_ => None
 				};
 
-				Ok( Box::new(Node::ObjectType(start_pos, self.lexer.get_start_position(), Box::new(symbol1), flags, first, second, body, Box::new(symbol2), ident )) )
+				Ok( Box::new(Node::ObjectType(start_pos, self.lexer.get_start_position(), Box::new(symbol1), flags, first, implements, second, body, Box::new(symbol2), ident )) )
 			},
 			_ => Ok( Box::new(Node::ObjectTypeEmpty(start_pos, self.lexer.get_start_position(), Box::new(symbol1))) )
 		}
 	}
 
-	fn parse_enumeration_type(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_enumeration_type(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 
 		match self.symbol.clone()? {
 			Symbols::Enum( _ , _ ) => (),
-			_ => return Err(Box::new(format!("Expecting 'ENUM' in enum type at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting 'ENUM' in enum type")))
 		}
 		let symbol1= self.symbol.clone()?;
 		self.advance();
@@ -2785,7 +3087,7 @@ impl BlockRules for Parser {
 
 				match self.symbol.clone()? {
 					Symbols::RightParen( _ , _ ) => (),
-					_ => return Err(Box::new(format!("Expecting ')' in enum type at position: '{}'", self.lexer.get_start_position())))
+					_ => return Err(self.error(String::from("Expecting ')' in enum type")))
 				}
 				let symbol12= self.symbol.clone()?;
 				self.advance();
@@ -2815,7 +3117,7 @@ impl BlockRules for Parser {
 
 				nodes.push( Box::new(Node::EnumElement(start_pos3, self.lexer.get_start_position(), left, right)) )
 			},
-			_ => return Err(Box::new(format!("Expecting at least one Ident in enum type at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting at least one Ident in enum type")))
 		}
 
 		loop {
@@ -2830,7 +3132,7 @@ impl BlockRules for Parser {
 			let start_pos2 = self.lexer.get_start_position();
 			match self.symbol.clone()? {
 				Symbols::Ident( _ , _ , _ ) => (),
-				_ => return Err(Box::new(format!("Expecting Ident in enum type after ',' at position: '{}'", self.lexer.get_start_position())))
+				_ => return Err(self.error(String::from("Expecting Ident in enum type after ','")))
 			}
 			let left = self.parse_identifier_definition()?;
 
@@ -2849,7 +3151,7 @@ impl BlockRules for Parser {
 
 		match self.symbol.clone()? {
 			Symbols::End( _ , _ ) => (),
-			_ => return Err(Box::new(format!("Expecting 'END' in enum type at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting 'END' in enum type")))
 		}
 		let symbol2= self.symbol.clone()?;
 		self.advance();
@@ -2857,7 +3159,7 @@ impl BlockRules for Parser {
 		Ok( Box::new(Node::EnumerationType(start_pos, self.lexer.get_start_position(), Box::new(symbol1), first, nodes, separators, Box::new(symbol2))) )
 	}
 
-	fn parse_cell_type(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_cell_type(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 
 		let symbol1 = match self.symbol.clone()? {
@@ -2871,7 +3173,7 @@ impl BlockRules for Parser {
 				self.advance();
 				Box::new(res1)
 			},
-			_ => return Err(Box::new(format!("Expecting 'CELL' or 'CELLNET' in cell or cellnet type at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting 'CELL' or 'CELLNET' in cell or cellnet type")))
 		};
 
 		let flags = match self.symbol.clone()? {
@@ -2888,7 +3190,7 @@ impl BlockRules for Parser {
 
 				match self.symbol.clone()? {
 					Symbols::RightParen( _ , _ ) => (),
-					_ => return Err(Box::new(format!("Expecting ')' in cell / cellnet type at position: '{}'", self.lexer.get_start_position())))
+					_ => return Err(self.error(String::from("Expecting ')' in cell / cellnet type")))
 				}
 				let symbol12= self.symbol.clone()?;
 				self.advance();
@@ -2931,7 +3233,7 @@ impl BlockRules for Parser {
 
 		match self.symbol.clone()? {
 			Symbols::End( _ , _ ) => (),
-			_ => return Err(Box::new(format!("Expecting 'END' in cell / cellnet type at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting 'END' in cell / cellnet type")))
 		}
 		let symbol3= self.symbol.clone()?;
 		self.advance();
@@ -2949,7 +3251,7 @@ impl BlockRules for Parser {
 		Ok( Box::new( Node::CellType(start_pos, self.lexer.get_start_position(), symbol1, flags, first, symbol2, import, decl, body, Box::new(symbol3), id  ) ) )
 	}
 
-	fn parse_port_list(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_port_list(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 
 		let mut nodes = Box::new(Vec::<Box<Node>>::new());
@@ -2975,7 +3277,7 @@ impl BlockRules for Parser {
 		Ok( Box::new(Node::PortList(start_pos, self.lexer.get_start_position(), nodes, separators)) )
 	}
 
-	fn parse_port_declaration(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_port_declaration(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 
 		let mut nodes = Box::new(Vec::<Box<(Box<Node>, Option<Box<Node>>)>>::new());
@@ -3001,7 +3303,7 @@ impl BlockRules for Parser {
 					self.advance();
 					Box::new( Node::Ident(start_pos, self.lexer.get_start_position(), Box::new(id)) )
 				},
-				_ => return Err(Box::new(format!("Expecting 'Ident' literal in port declaration at position: '{}'", self.lexer.get_start_position())))
+				_ => return Err(self.error(String::from("Expecting 'Ident' literal in port declaration")))
 			};
 
 			let flags = match self.symbol.clone()? {
@@ -3014,7 +3316,7 @@ impl BlockRules for Parser {
 
 		match self.symbol.clone()? {
 			Symbols::Colon( _ , _ ) => (),
-			_ => return Err(Box::new(format!("Expecting ':' in port declaration at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting ':' in port declaration")))
 		}
 		let symbol1 = self.symbol.clone()?;
 		self.advance();
@@ -3024,12 +3326,12 @@ impl BlockRules for Parser {
 		Ok( Box::new(Node::PortDeclaration(start_pos, self.lexer.get_start_position(), nodes, separators, Box::new(symbol1), right)) )
 	}
 
-	fn parse_port_type(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_port_type(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 
 		match self.symbol.clone()? {
 			Symbols::Port( _ , _ ) => (),
-			_ => return Err(Box::new(format!("Expecting 'PORT' in port type at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting 'PORT' in port type")))
 		}
 		let symbol1 = self.symbol.clone()?;
 		self.advance();
@@ -3041,7 +3343,7 @@ impl BlockRules for Parser {
 				self.advance();
 				Box::new(dir)
 			},
-			_ => return Err(Box::new(format!("Expecting 'IN' or 'OUT' in port type at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting 'IN' or 'OUT' in port type")))
 		};
 
 		let first = match self.symbol.clone()? {
@@ -3053,7 +3355,7 @@ impl BlockRules for Parser {
 
 				match self.symbol.clone()? {
 					Symbols::RightParen( _ , _ ) => (),
-					_ => return Err(Box::new(format!("Expecting ')' in port type at position: '{}'", self.lexer.get_start_position())))
+					_ => return Err(self.error(String::from("Expecting ')' in port type")))
 				}
 				let symbol12= self.symbol.clone()?;
 				self.advance();
@@ -3066,12 +3368,12 @@ impl BlockRules for Parser {
 		Ok( Box::new(Node::PortType(start_pos, self.lexer.get_start_position(), Box::new(symbol1), direction, first )) )
 	}
 
-	fn parse_qualified_identifier(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_qualified_identifier(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 
 		match self.symbol.clone()? {
 			Symbols::Ident( _ , _ , _ ) => (),
-			_ => return Err(Box::new(format!("Expecting identifier at position: '{}'", self.lexer.get_start_position())))
+			_ => return Err(self.error(String::from("Expecting identifier")))
 		}
 		let symbol = self.symbol.clone()?;
 		self.advance();
@@ -3086,7 +3388,7 @@ impl BlockRules for Parser {
 
 				match self.symbol.clone()? {
 					Symbols::Ident( _ , _ , _ ) => (),
-					_ => return Err(Box::new(format!("Expecting identifier after '.' at position: '{}'", self.lexer.get_start_position())))
+					_ => return Err(self.error(String::from("Expecting identifier after '.'")))
 				}
 				let symbol2 = self.symbol.clone()?;
 				self.advance();
@@ -3098,7 +3400,7 @@ impl BlockRules for Parser {
 		}
 	}
 
-	fn parse_identifier_definition(&mut self) -> Result<Box<Node>, Box<String>> {
+	fn parse_identifier_definition(&mut self) -> Result<Box<Node>, Box<Diagnostic>> {
 		let start_pos = self.lexer.get_start_position();
 
 		return match self.symbol.clone()? {
@@ -3125,7 +3427,7 @@ impl BlockRules for Parser {
 					}
 				}
 			},
-			_ => Err(Box::new(format!("Expecting identifier at position: '{}'", self.lexer.get_start_position())))
+			_ => Err(self.error(String::from("Expecting identifier")))
 		}
 	}
 }
@@ -5081,6 +5383,85 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn statement_code_with_instructions() {
+		let mut parser = Parser::new(Box::new(Scanner::new("CODE BITS 64 END")));
+		parser.advance();
+		let res = parser.parse_statement();
+
+		let pattern = Box::new( Node::Code(0, 16,
+													 Box::new( Symbols::Code(0,4) ),
+													 Box::default(),
+													 Box::new( Symbols::End(13, 16) )
+		) );
+
+		match res {
+			Ok(x) => {
+				assert_eq!(pattern, x)
+			}, _ => assert!(false)
+		}
+	}
+
+	#[test]
+	fn statement_code_with_oberon_variable() {
+		let mut parser = Parser::new(Box::new(Scanner::new("CODE MOV RAX, x END")));
+		parser.advance();
+		let res = parser.parse_statement();
+
+		assert!(res.is_ok());
+		assert_eq!(parser.get_errors().len(), 0);
+		assert_eq!(*parser.get_deferred_code(), vec![ ( 4, " MOV RAX, x ".chars().collect() ) ])
+	}
+
+	#[test]
+	fn statement_code_error_position() {
+		let mut parser = Parser::new(Box::new(Scanner::new("CODE ) END")));
+		parser.advance();
+		let res = parser.parse_statement();
+
+		match res {
+			Ok(_) => {
+				let x = &parser.get_errors()[0];
+				assert_eq!(x.code, crate::diagnostics::E_ASSEMBLER_SYNTAX);
+				assert_eq!(x.span.start, 5)
+			}, _ => assert!(false)
+		}
+	}
+
+	#[test]
+	fn statement_code_for_selected_target() {
+		let mut parser = Parser::new(Box::new(Scanner::new("CODE NOP END")));
+		parser.set_target(crate::inline_assembler::Target::RiscV);
+		parser.advance();
+		let res = parser.parse_statement();
+
+		match res {
+			Ok( x ) => {
+				assert_eq!(parser.get_errors().len(), 0);
+				match *x {
+					Node::Code( _ , _ , _ , code , _ ) => assert_eq!(*code, vec![ 0x01, 0x00 ]),
+					_ => assert!(false)
+				}
+			}, _ => assert!(false)
+		}
+	}
+
+	#[test]
+	fn statement_code_for_arm_target() {
+		let mut parser = Parser::new(Box::new(Scanner::new("CODE { SYSTEM.NEON } LDR X0, x END")));
+		parser.set_target(crate::inline_assembler::Target::ArmV8);
+		parser.advance();
+
+		assert!(parser.parse_statement().is_ok());
+		assert_eq!(parser.get_errors().len(), 0);
+		assert_eq!(parser.get_deferred_code().len(), 1);
+
+		let ( position , text ) = parser.get_deferred_code()[0].clone();
+		let variables = std::collections::HashMap::from([ ( String::from("x"), crate::inline_assembler::OberonVariable::Frame(-8, 8) ) ]);
+		let res = crate::inline_assembler::assemble_code_block(&crate::inline_assembler::Target::ArmV8, text, position, Some( &variables ));
+		assert_eq!(res.map(|x| *x.code), Ok( vec![ 0xa0, 0x83, 0x5f, 0xf8 ] ))
+	}
+
 	#[test]
 	fn statement_return_expression() {
 		let mut parser = Parser::new(Box::new(Scanner::new("RETURN test")));
@@ -7001,6 +7382,7 @@ mod tests {
 																),
 															  	Box::new([].to_vec()),
 															  	Box::new([].to_vec()),
+															  	Box::new([].to_vec()),
 															  	Box::new([].to_vec())
 									)
 								)
@@ -7076,6 +7458,7 @@ mod tests {
 															  ),
 															  Box::new([].to_vec()),
 															  Box::new([].to_vec()),
+															  Box::new([].to_vec()),
 															  Box::new([].to_vec())
 									)
 								)
@@ -7465,6 +7848,7 @@ mod tests {
 															 Box::new( [ Box::new(Node::VarDeclaration(41, 45, Box::new(Symbols::Var(41, 44)), Box::new([].to_vec()))) ].to_vec()),
 															 Box::new([].to_vec()),
 															 Box::new([].to_vec()),
+															 Box::new([].to_vec()),
 															 Box::new([].to_vec()))
 							   )
 						   ),
@@ -7535,6 +7919,7 @@ mod tests {
 															 Box::new( [ Box::new(Node::VarDeclaration(41, 45, Box::new(Symbols::Var(41, 44)), Box::new([].to_vec()))) ].to_vec()),
 															 Box::new([].to_vec()),
 															 Box::new([].to_vec()),
+															 Box::new([].to_vec()),
 															 Box::new([].to_vec()))
 							   )
 						   ),
@@ -7657,7 +8042,7 @@ mod tests {
 		let pattern = Box::new(
 			Node::BodyCode(0, 4,
 					   Box::new(Symbols::Code(0, 4)),
-					   Box::new(Node::Empty)
+					   Box::new(Node::CodeBlock(4, 4, Box::default()))
 			)
 		);
 
@@ -7668,6 +8053,28 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn procedure_with_code_body() {
+		let mut parser = Parser::new(Box::new(Scanner::new("PROCEDURE Get; CODE BITS 64 END Get")));
+		parser.advance();
+		let res = parser.parse_procedure_declaration();
+
+		match res {
+			Ok(x) => {
+				match *x {
+					Node::Procedure( _ , _ , _ , _ , _ , _ , _ , _ , _ , Some( body ) , end , _ ) => {
+						assert_eq!(body, Box::new(Node::BodyCode(15, 28,
+							Box::new(Symbols::Code(15, 19)),
+							Box::new(Node::CodeBlock(19, 28, Box::default()))
+						)));
+						assert_eq!(end, Box::new(Symbols::End(28, 31)))
+					},
+					_ => assert!(false)
+				}
+			}, _ => assert!(false)
+		}
+	}
+
 	#[test]
 	fn type_declaration() {
 		let mut parser = Parser::new(Box::new(Scanner::new("test = INT64;")));
@@ -8457,6 +8864,7 @@ mod tests {
 							 None,
 							 None,
 							 None,
+							 None,
 							 Box::new(Symbols::End(7, 10)),
 							 None
 			)
@@ -8482,6 +8890,7 @@ mod tests {
 							 None,
 							 None,
 							 None,
+							 None,
 							 Box::new(Symbols::End(7, 10)),
 							 Some(Box::new(Node::Ident(11, 15, Box::new(Symbols::Ident(11, 15, Box::new(String::from("name")))))))
 			)
@@ -8509,6 +8918,7 @@ mod tests {
 							 None,
 							 None,
 							 None,
+							 None,
 							 Box::new(Symbols::End(10, 13)),
 							 None
 			)
@@ -8542,6 +8952,7 @@ mod tests {
 							 ),
 							 None,
 							 None,
+							 None,
 							 Box::new(Symbols::End(16, 19)),
 							 None
 			)
@@ -8554,6 +8965,47 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn type_object_implements() {
+		let mut parser = Parser::new(Box::new(Scanner::new("OBJECT IMPLEMENTS A, B.C END")));
+		parser.advance();
+		let res = parser.parse_object_type();
+
+		let pattern = Box::new(
+			Node::ObjectType(0, 28,
+							 Box::new(Symbols::Object(0, 6)),
+							 None,
+							 None,
+							 Some(
+								 (
+									Box::new(Symbols::Implements(7, 17)),
+									Box::new([
+										Box::new(Node::Ident(18, 19, Box::new(Symbols::Ident(18, 19, Box::new(String::from("A")))))),
+										Box::new(Node::QualifiedIdentifier(21, 25,
+											Box::new(Node::Ident(21, 22, Box::new(Symbols::Ident(21, 22, Box::new(String::from("B")))))),
+											Box::new(Symbols::Period(22, 23)),
+											Box::new(Node::Ident(23, 25, Box::new(Symbols::Ident(23, 24, Box::new(String::from("C"))))))
+										))
+									].to_vec()),
+									Box::new([
+										Box::new(Symbols::Comma(19, 20))
+									].to_vec())
+								 )
+							 ),
+							 None,
+							 None,
+							 Box::new(Symbols::End(25, 28)),
+							 None
+			)
+		);
+
+		match res {
+			Ok(x) => {
+				assert_eq!(pattern, x)
+			}, _ => assert!(false)
+		}
+	}
+
 	#[test]
 	fn type_enum_simple_empty() {
 		let mut parser = Parser::new(Box::new(Scanner::new("ENUM empty END")));
@@ -8851,6 +9303,7 @@ mod tests {
 				  	Box::new([].to_vec()),
 				  	Box::new([].to_vec()),
 				  	Box::new([].to_vec()),
+				  	Box::new([].to_vec()),
 				  	Box::new([
 						Box::new(Symbols::SemiColon(11, 12))
 					].to_vec())
@@ -8897,6 +9350,7 @@ mod tests {
 									  Box::new([].to_vec()),
 									  Box::new([].to_vec()),
 									  Box::new([].to_vec()),
+									  Box::new([].to_vec()),
 									  Box::new([
 										  Box::new(Symbols::SemiColon(11, 12))
 									  ].to_vec())
@@ -8958,6 +9412,7 @@ mod tests {
 									  ].to_vec()),
 									  Box::new([].to_vec()),
 									  Box::new([].to_vec()),
+									  Box::new([].to_vec()),
 									  Box::new([
 										  Box::new(Symbols::SemiColon(11, 12))
 									  ].to_vec())
@@ -9022,6 +9477,7 @@ mod tests {
 									  ].to_vec()),
 									  Box::new([].to_vec()),
 									  Box::new([].to_vec()),
+									  Box::new([].to_vec()),
 									  Box::new([
 										  Box::new(Symbols::SemiColon(11, 12))
 									  ].to_vec())
@@ -9088,6 +9544,7 @@ mod tests {
 									  ].to_vec()),
 									  Box::new([].to_vec()),
 									  Box::new([].to_vec()),
+									  Box::new([].to_vec()),
 									  Box::new([
 										  Box::new(Symbols::SemiColon(11, 12))
 									  ].to_vec())
@@ -9161,6 +9618,7 @@ mod tests {
 									  ].to_vec()),
 									  Box::new([].to_vec()),
 									  Box::new([].to_vec()),
+									  Box::new([].to_vec()),
 									  Box::new([
 										  Box::new(Symbols::SemiColon(11, 12))
 									  ].to_vec())
@@ -9240,6 +9698,7 @@ mod tests {
 									  ].to_vec()),
 									  Box::new([].to_vec()),
 									  Box::new([].to_vec()),
+									  Box::new([].to_vec()),
 									  Box::new([
 										  Box::new(Symbols::SemiColon(11, 12))
 									  ].to_vec())
@@ -9332,6 +9791,7 @@ mod tests {
 										  )
 									  ].to_vec()),
 									  Box::new([].to_vec()),
+									  Box::new([].to_vec()),
 									  Box::new([
 										  Box::new(Symbols::SemiColon(11, 12)),
 										  Box::new(Symbols::SemiColon(98, 99))
@@ -9443,6 +9903,7 @@ mod tests {
 											  Box::new(Node::String(121, 124, Box::new(Symbols::String(121, 124, Box::new(String::from("\"+\""))))))
 										  ))
 									  ].to_vec()),
+									  Box::new([].to_vec()),
 									  Box::new([
 										  Box::new(Symbols::SemiColon(11, 12)),
 										  Box::new(Symbols::SemiColon(98, 99))
@@ -9470,6 +9931,7 @@ mod tests {
 									  Box::new([].to_vec()),
 									  Box::new([].to_vec()),
 									  Box::new([].to_vec()),
+									  Box::new([].to_vec()),
 									  Box::new([
 										  Box::new(Symbols::SemiColon(0, 1))
 									  ].to_vec())
@@ -9483,5 +9945,255 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn declaration_sequence_definition() {
+		let mut parser = Parser::new(Box::new(Scanner::new("DEFINITION D; END D;")));
+		parser.advance();
+		let res = parser.parse_declaration_sequence();
+
+		let pattern = Box::new(
+			Node::DeclarationSequence(0, 20,
+									  Box::new([].to_vec()),
+									  Box::new([].to_vec()),
+									  Box::new([].to_vec()),
+									  Box::new([].to_vec()),
+									  Box::new([].to_vec()),
+									  Box::new([
+										  Box::new(Node::Definition(0, 19,
+											  Box::new(Symbols::Definition(0, 10)),
+											  Box::new(Node::Ident(11, 12, Box::new(Symbols::Ident(11, 12, Box::new(String::from("D")))))),
+											  Box::new(Symbols::SemiColon(12, 13)),
+											  Box::new([].to_vec()),
+											  Box::new(Symbols::End(14, 17)),
+											  Box::new(Node::Ident(18, 19, Box::new(Symbols::Ident(18, 19, Box::new(String::from("D"))))))
+										  ))
+									  ].to_vec()),
+									  Box::new([
+										  Box::new(Symbols::SemiColon(19, 20))
+									  ].to_vec())
+			)
+		);
+
+		match res {
+			Ok(x) => {
+				assert_eq!(pattern, x)
+			}, _ => assert!(false)
+		}
+	}
+
+	#[test]
+	fn block_definition_with_headings() {
+		let mut parser = Parser::new(Box::new(Scanner::new("DEFINITION D; PROCEDURE P; END D")));
+		parser.advance();
+		let res = parser.parse_definition();
+
+		let pattern = Box::new(
+			Node::Definition(0, 32,
+				Box::new(Symbols::Definition(0, 10)),
+				Box::new(Node::Ident(11, 12, Box::new(Symbols::Ident(11, 12, Box::new(String::from("D")))))),
+				Box::new(Symbols::SemiColon(12, 13)),
+				Box::new([
+					Box::new(Node::ProcedureHeading(14, 27,
+						Box::new(Symbols::Procedure(14, 23)),
+						None,
+						Box::new(Node::Ident(24, 25, Box::new(Symbols::Ident(24, 25, Box::new(String::from("P")))))),
+						None,
+						Box::new(Symbols::SemiColon(25, 26))
+					))
+				].to_vec()),
+				Box::new(Symbols::End(27, 30)),
+				Box::new(Node::Ident(31, 32, Box::new(Symbols::Ident(31, 32, Box::new(String::from("D"))))))
+			)
+		);
 
-}
\ No newline at end of file
+		match res {
+			Ok(x) => {
+				assert_eq!(pattern, x)
+			}, _ => assert!(false)
+		}
+	}
+
+	#[test]
+	fn block_definition_missing_end() {
+		let mut parser = Parser::new(Box::new(Scanner::new("DEFINITION D; PROCEDURE P; D")));
+		parser.advance();
+		let res = parser.parse_definition();
+
+		match res {
+			Err(x) => {
+				assert_eq!(*x.message, String::from("Expecting 'END' in definition declaration"))
+			}, _ => assert!(false)
+		}
+	}
+
+	#[test]
+	fn module_name_mismatch_diagnostic() {
+		let mut parser = Parser::new(Box::new(Scanner::new("MODULE Test; END Tset.")));
+		let res = parser.parse_module();
+
+		match res {
+			Err(x) => {
+				assert_eq!(x.code, crate::diagnostics::E_MODULE_NAME_MISMATCH);
+				assert_eq!(x.span.start, 17);
+				assert_eq!(x.span.end, 21);
+				assert_eq!(x.labels[0].span.start, 7)
+			}, _ => assert!(false)
+		}
+	}
+
+	#[test]
+	fn statement_block_empty() {
+		let mut parser = Parser::new(Box::new(Scanner::new("BEGIN END")));
+		parser.advance();
+		let res = parser.parse_statement_block();
+
+		let pattern = Box::new(Node::StatementBlock(0, 9,
+			Box::new(Symbols::Begin(0, 5)),
+			None,
+			Box::new(Node::StatementSequence(6, 6, Box::new([ Box::new(Node::Empty) ].to_vec()), Box::new([].to_vec()))),
+			Box::new(Symbols::End(6, 9))
+		));
+
+		match res {
+			Ok(x) => {
+				assert_eq!(pattern, x)
+			}, _ => assert!(false)
+		}
+	}
+
+	#[test]
+	fn module_var_trailing_semicolon() {
+		let mut parser = Parser::new(Box::new(Scanner::new("MODULE Test; VAR x: INTEGER; PROCEDURE P; END P; END Test.")));
+		let res = parser.parse_module();
+
+		match res {
+			Ok( _ ) => {
+				assert_eq!(parser.get_errors().len(), 0)
+			}, _ => assert!(false)
+		}
+	}
+
+	#[test]
+	fn module_var_address_and_size_types() {
+		let mut parser = Parser::new(Box::new(Scanner::new("MODULE Test; VAR a: ADDRESS; s: SIZE; END Test.")));
+		let res = parser.parse_module();
+
+		match res {
+			Ok( _ ) => {
+				assert_eq!(parser.get_errors().len(), 0)
+			}, _ => assert!(false)
+		}
+	}
+
+	#[test]
+	fn module_error_recovery_statements() {
+		let mut parser = Parser::new(Box::new(Scanner::new("MODULE Test; BEGIN a := ; b := 1; c := ) ; d := 2 END Test.")));
+		let res = parser.parse_module();
+
+		match res {
+			Ok(x) => {
+				match *x {
+					Node::Module( _ , _ , _ , _ , _ , _ , _ , _ , _ , Some( body ) , _ , _ , _ ) => {
+						match *body {
+							Node::Body( _ , _ , _ , _ , seq , _ ) => {
+								match *seq {
+									Node::StatementSequence( _ , _ , nodes , _ ) => {
+										assert_eq!(nodes.len(), 4);
+										assert_eq!(nodes[0], Box::new(Node::Error(19, 24)));
+										assert_eq!(nodes[2], Box::new(Node::Error(34, 41)))
+									},
+									_ => assert!(false)
+								}
+							},
+							_ => assert!(false)
+						}
+					},
+					_ => assert!(false)
+				}
+				assert_eq!(parser.get_errors().len(), 2);
+				assert_eq!(parser.get_errors()[0].span.start, 24);
+				assert_eq!(parser.get_errors()[1].span.start, 39)
+			}, _ => assert!(false)
+		}
+	}
+
+	#[test]
+	fn module_error_recovery_declarations() {
+		let mut parser = Parser::new(Box::new(Scanner::new("MODULE Test; CONST a = ; VAR x : ; y : INTEGER; PROCEDURE (; END Test.")));
+		let res = parser.parse_module();
+
+		match res {
+			Ok(x) => {
+				match *x {
+					Node::Module( _ , _ , _ , _ , _ , _ , _ , _ , Some( decl ) , _ , _ , _ , _ ) => {
+						match *decl {
+							Node::DeclarationSequence( _ , _ , consts , _ , vars , procedures , _ , _ , _ ) => {
+								assert_eq!(consts.len(), 1);
+								assert_eq!(vars.len(), 1);
+								assert_eq!(procedures.len(), 1);
+								match &*vars[0] {
+									Node::VarDeclaration( _ , _ , _ , elements ) => {
+										assert_eq!(elements.len(), 2);
+										assert_eq!(elements[0], Box::new(Node::Error(29, 33)))
+									},
+									_ => assert!(false)
+								}
+							},
+							_ => assert!(false)
+						}
+					},
+					_ => assert!(false)
+				}
+				assert_eq!(parser.get_errors().len(), 3)
+			}, _ => assert!(false)
+		}
+	}
+
+	#[test]
+	fn module_error_recovery_missing_semicolon_in_declarations() {
+		let mut parser = Parser::new(Box::new(Scanner::new("MODULE Test; CONST a = 1 b = 2; VAR x : INTEGER y : INTEGER; z : ; BEGIN x := END Test.")));
+		let res = parser.parse_module();
+
+		match res {
+			Ok(x) => {
+				match *x {
+					Node::Module( _ , _ , _ , _ , _ , _ , _ , _ , Some( decl ) , _ , _ , _ , _ ) => {
+						match *decl {
+							Node::DeclarationSequence( _ , _ , consts , _ , vars , _ , _ , _ , _ ) => {
+								match ( &*consts[0] , &*vars[0] ) {
+									( Node::ConstDeclaration( _ , _ , _ , a ) , Node::VarDeclaration( _ , _ , _ , b ) ) => {
+										assert_eq!(a.len(), 2);
+										assert_eq!(b.len(), 3)
+									},
+									_ => assert!(false)
+								}
+							},
+							_ => assert!(false)
+						}
+					},
+					_ => assert!(false)
+				}
+				let errors = parser.get_errors();
+				assert_eq!(errors.len(), 4);
+				assert_eq!(*errors[0].message, "Expecting ';' after constant declaration");
+				assert_eq!(errors[0].span.start, 25);
+				assert_eq!(*errors[1].message, "Expecting ';' after variable declaration");
+				assert_eq!(errors[1].span.start, 48)
+			}, _ => assert!(false)
+		}
+	}
+
+	#[test]
+	fn module_lexical_error_recovery() {
+		let mut parser = Parser::new(Box::new(Scanner::new("MODULE Test; BEGIN a := 1 $ ; b := 2 END Test.")));
+		let res = parser.parse_module();
+
+		match res {
+			Ok( _ ) => {
+				assert_eq!(parser.get_errors().len(), 1);
+				assert_eq!(parser.get_errors()[0].code, crate::diagnostics::E_INVALID_CHARACTER)
+			}, _ => assert!(false)
+		}
+	}
+
+}
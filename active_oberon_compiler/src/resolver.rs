@@ -0,0 +1,782 @@
+// ActiveOberon Compiler, a native ARM v8 & X86-64 compiler & Risc V / linker / builder utility.
+// Written by Richard Magnor Stenbro. Licensed under GPL v3
+// Resolver module for binding identifiers in abstract syntax tree to their declarations
+
+use std::collections::HashMap;
+use crate::diagnostics::{Diagnostic, DiagnosticMethods, E_UNDECLARED, E_AMBIGUOUS_IMPORT};
+use crate::parser::Node;
+use crate::scanner::Symbols;
+use crate::symbol_table::{SymbolTable, SymbolTableMethods, ScopeId, EntryId, ScopeKind, SymbolKind, UNIVERSE_SCOPE, identifier_definition};
+use crate::traverse_abstract_syntax_tree::children;
+
+
+pub trait ResolverMethods {
+    fn new() -> Self;
+    fn declare_module(&mut self, module: &Node) -> ();
+    fn resolve_module(&mut self, module: &Node) -> ();
+    fn take_errors(&mut self) -> Vec<Box<Diagnostic>>;
+    fn get_symbol_table(&self) -> &SymbolTable;
+    fn get_module_scope(&self, name: &str) -> Option<ScopeId>;
+    fn get_binding(&self, module: ScopeId, position: u32) -> Option<EntryId>;
+    fn get_node_scope(&self, module: ScopeId, position: u32) -> Option<ScopeId>;
+    fn get_import(&self, entry: EntryId) -> Option<&String>;
+    fn bind(&mut self, module: ScopeId, position: u32, entry: EntryId) -> ();
+    fn declare(&mut self, node: &Node, kind: SymbolKind) -> Option<EntryId>;
+    fn declare_imports(&mut self, import_list: &Node) -> ();
+    fn declare_declarations(&mut self, declarations: &Node) -> ();
+    fn declare_variables(&mut self, var: &Node) -> ();
+    fn declare_parameters(&mut self, parameters: &Node) -> ();
+    fn declare_procedure(&mut self, procedure: &Node) -> ();
+    fn declare_definition(&mut self, definition: &Node) -> ();
+    fn declare_type(&mut self, type_node: &Node, owner: Option<EntryId>) -> ();
+    fn open_node_scope(&mut self, kind: ScopeKind, position: u32, owner: Option<EntryId>) -> ScopeId;
+    fn enter_node_scope(&mut self, position: u32) -> ScopeId;
+    fn resolve_node(&mut self, node: &Node) -> ();
+    fn resolve_optional(&mut self, node: &Option<Box<Node>>) -> ();
+    fn resolve_identifier(&mut self, node: &Node) -> Option<EntryId>;
+    fn resolve_member(&mut self, owner: Option<EntryId>, node: &Node) -> Option<EntryId>;
+}
+
+/// Binds identifiers to symbol table entries. All modules are declared first, then resolved,
+/// so that forward references and references between modules in same build are found.
+pub struct Resolver {
+    table: SymbolTable,
+    module: ScopeId,                                /* Scope of module being handled */
+    modules: HashMap<String, ScopeId>,              /* Module scopes by module name */
+    imports: HashMap<EntryId, String>,              /* Imported module name for each import entry */
+    scopes: HashMap<(ScopeId, u32), ScopeId>,       /* Scope opened by declaration, keyed by module and position of node */
+    bindings: HashMap<(ScopeId, u32), EntryId>,     /* Declaration of identifier, keyed by module and position of identifier */
+    errors: Vec<Box<Diagnostic>>
+}
+
+impl ResolverMethods for Resolver {
+    fn new() -> Self {
+        Resolver {
+            table: SymbolTable::new(),
+            module: UNIVERSE_SCOPE,
+            modules: HashMap::new(),
+            imports: HashMap::new(),
+            scopes: HashMap::new(),
+            bindings: HashMap::new(),
+            errors: Vec::new()
+        }
+    }
+
+    /// First pass, enter all declarations of module into symbol table
+    fn declare_module(&mut self, module: &Node) {
+        if let Node::Module( s , _ , _ , template , id , _ , _ , imports , decl , _ , _ , _ , _ ) = module {
+            let name = match identifier_definition(id) {
+                Some( ( x , _ , _ , _ ) ) => x,
+                _ => String::new()
+            };
+
+            self.table.enter_scope(UNIVERSE_SCOPE);
+            self.module = self.table.open_scope(ScopeKind::Module, name.as_str());
+            self.scopes.insert( ( self.module, *s ), self.module );
+            self.modules.insert(name, self.module);
+
+            if let Some( x ) = template {
+                if let Node::TemplateParameters( _ , _ , _ , nodes , _ , _ ) = &**x {
+                    for node in nodes.iter() {
+                        if let Node::TemplateParameter( _ , _ , symbol , id ) = &**node {
+                            let kind = match **symbol {
+                                Symbols::Const( _ , _ ) => SymbolKind::Constant,
+                                _ => SymbolKind::Type
+                            };
+                            self.declare(id, kind);
+                        }
+                    }
+                }
+            }
+
+            if let Some( x ) = imports {
+                for import_list in x.iter() {
+                    self.declare_imports(import_list)
+                }
+            }
+
+            if let Some( x ) = decl {
+                self.declare_declarations(x)
+            }
+
+            self.table.close_scope()
+        }
+    }
+
+    /// Second pass, bind every identifier use to its declaration
+    fn resolve_module(&mut self, module: &Node) {
+        if let Node::Module( _ , _ , _ , _ , id , _ , _ , _ , decl , body , _ , _ , _ ) = module {
+            self.module = match identifier_definition(id) {
+                Some( ( x , _ , _ , _ ) ) => {
+                    match self.modules.get(&x) {
+                        Some( scope ) => *scope,
+                        _ => return
+                    }
+                },
+                _ => return
+            };
+            self.table.enter_scope(self.module);
+
+            /* Connect imports with modules declared in same build, other modules are accepted without checks */
+            let entries = self.table.scope(self.module).entries.clone();
+            for entry in entries.iter() {
+                if let Some( name ) = self.imports.get(entry) {
+                    if let Some( x ) = self.modules.get(name) {
+                        self.table.set_inner_scope(*entry, *x)
+                    }
+                }
+            }
+
+            self.resolve_optional(decl);
+            self.resolve_optional(body);
+
+            self.table.enter_scope(UNIVERSE_SCOPE)
+        }
+    }
+
+    fn take_errors(&mut self) -> Vec<Box<Diagnostic>> {
+        std::mem::take(&mut self.errors)
+    }
+
+    fn get_symbol_table(&self) -> &SymbolTable {
+        &self.table
+    }
+
+    fn get_module_scope(&self, name: &str) -> Option<ScopeId> {
+        self.modules.get(name).copied()
+    }
+
+    /// Declaration bound to identifier starting at position in module
+    fn get_binding(&self, module: ScopeId, position: u32) -> Option<EntryId> {
+        self.bindings.get(&( module, position )).copied()
+    }
+
+    /// Scope opened by procedure, type or module node starting at position in module
+    fn get_node_scope(&self, module: ScopeId, position: u32) -> Option<ScopeId> {
+        self.scopes.get(&( module, position )).copied()
+    }
+
+    /// Name of module imported by import entry, differs from name of entry for aliases
+    fn get_import(&self, entry: EntryId) -> Option<&String> {
+        self.imports.get(&entry)
+    }
+
+    /// Bind identifier found by later passes, like record fields found through the type of a designator
+    fn bind(&mut self, module: ScopeId, position: u32, entry: EntryId) {
+        self.table.mark_used(entry);
+        self.bindings.insert( ( module, position ), entry );
+    }
+
+    /// Declare identifier definition in current scope, reporting duplicates
+    fn declare(&mut self, node: &Node, kind: SymbolKind) -> Option<EntryId> {
+        match identifier_definition(node) {
+            Some( ( name , export , s , e ) ) => {
+                match self.table.declare(name.as_str(), kind, export, s, e) {
+                    Ok( x ) => Some( x ),
+                    Err( e ) => {
+                        self.errors.push(e);
+                        None
+                    }
+                }
+            },
+            _ => None
+        }
+    }
+
+    fn declare_imports(&mut self, import_list: &Node) {
+        if let Node::ImportList( _ , _ , _ , imports , _ , _ ) = import_list {
+            for import in imports.iter() {
+                if let Node::Import( _ , _ , id , alias , _ , _ ) = &**import {
+                    let ( name , s , e ) = match identifier_definition(id) {
+                        Some( ( x , _ , s , e ) ) => ( x , s , e ),
+                        _ => continue
+                    };
+                    let module_name = match alias {
+                        Some( ( _ , x ) ) => {
+                            match identifier_definition(x) {
+                                Some( ( y , _ , _ , _ ) ) => y,
+                                _ => name.clone()
+                            }
+                        },
+                        _ => name.clone()
+                    };
+
+                    match self.table.lookup_local(self.table.current_scope(), name.as_str()) {
+                        Some( x ) if self.table.entry(x).kind == SymbolKind::Module => {
+                            let previous = self.table.entry(x);
+                            self.errors.push(Diagnostic::error(E_AMBIGUOUS_IMPORT, format!("Ambiguous import alias '{}'", name), s, e)
+                                .with_label(format!("'{}' is already imported here", name), previous.start, previous.end))
+                        },
+                        _ => {
+                            if let Some( x ) = self.declare(id, SymbolKind::Module) {
+                                self.imports.insert(x, module_name);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn declare_declarations(&mut self, declarations: &Node) {
+        if let Node::DeclarationSequence( _ , _ , constants , types , variables , procedures , operators , definitions , _ ) = declarations {
+            for constant_declaration in constants.iter() {
+                if let Node::ConstDeclaration( _ , _ , _ , nodes ) = &**constant_declaration {
+                    for node in nodes.iter() {
+                        if let Node::Const( _ , _ , id , _ , _ ) = &**node {
+                            self.declare(id, SymbolKind::Constant);
+                        }
+                    }
+                }
+            }
+
+            for type_declaration in types.iter() {
+                if let Node::TypeDeclaration( _ , _ , _ , nodes ) = &**type_declaration {
+                    for node in nodes.iter() {
+                        if let Node::TypeDeclarationElement( _ , _ , id , _ , type_node , _ ) = &**node {
+                            let owner = self.declare(id, SymbolKind::Type);
+                            self.declare_type(type_node, owner)
+                        }
+                    }
+                }
+            }
+
+            for var_declaration in variables.iter() {
+                if let Node::VarDeclaration( _ , _ , _ , nodes ) = &**var_declaration {
+                    for node in nodes.iter() {
+                        self.declare_variables(node)
+                    }
+                }
+            }
+
+            for procedure in procedures.iter() {
+                self.declare_procedure(procedure)
+            }
+
+            for operator in operators.iter() {
+                self.declare_procedure(operator)
+            }
+
+            for definition in definitions.iter() {
+                self.declare_definition(definition)
+            }
+        }
+    }
+
+    fn declare_variables(&mut self, var: &Node) {
+        if let Node::Var( _ , _ , list , _ , type_node ) = var {
+            if let Node::VarList( _ , _ , names , _ ) = &**list {
+                for name in names.iter() {
+                    if let Node::VarName( _ , _ , id , _ , _ ) = &**name {
+                        self.declare(id, SymbolKind::Variable);
+                    }
+                }
+            }
+            self.declare_type(type_node, None)
+        }
+    }
+
+    fn declare_parameters(&mut self, parameters: &Node) {
+        match parameters {
+            Node::FormalParameters( _ , _ , _ , nodes , _ , _ , _ ) => {
+                for node in nodes.iter() {
+                    self.declare_parameters(node)
+                }
+            },
+            Node::ParameterDeclaration( _ , _ , _ , nodes , _ , _ , _ ) => {
+                for node in nodes.iter() {
+                    if let Node::Parameter( _ , _ , id , _ , _ ) = &**node {
+                        self.declare(id, SymbolKind::Parameter);
+                    }
+                }
+            },
+            _ => ()
+        }
+    }
+
+    fn declare_procedure(&mut self, procedure: &Node) {
+        match procedure {
+            Node::Procedure( s , _ , _ , _ , receiver , id , parameters , _ , decl , _ , _ , _ ) => {
+                /* Procedures bound to a record type with receiver are found through the record, not by name in scope */
+                let owner = match receiver {
+                    Some( _ ) => None,
+                    _ => self.declare(id, SymbolKind::Procedure)
+                };
+
+                self.open_node_scope(ScopeKind::Procedure, *s, owner);
+
+                if let Some( ( _ , x , _ ) ) = receiver {
+                    self.declare_parameters(x)
+                }
+
+                if let Some( x ) = parameters {
+                    self.declare_parameters(x)
+                }
+
+                if let Some( x ) = decl {
+                    self.declare_declarations(x)
+                }
+
+                self.table.close_scope()
+            },
+            Node::Operator( s , _ , _ , _ , _ , _ , _ , parameters , _ , decl , _ , _ , _ ) => {
+                self.open_node_scope(ScopeKind::Operator, *s, None);
+                self.declare_parameters(parameters);
+
+                if let Some( x ) = decl {
+                    self.declare_declarations(x)
+                }
+
+                self.table.close_scope()
+            },
+            _ => ()
+        }
+    }
+
+    /// Definition is a type with a scope holding its procedure headings, which objects implementing it must provide
+    fn declare_definition(&mut self, definition: &Node) {
+        if let Node::Definition( s , _ , _ , id , _ , headings , _ , _ ) = definition {
+            let owner = self.declare(id, SymbolKind::Type);
+            self.open_node_scope(ScopeKind::Definition, *s, owner);
+
+            for heading in headings.iter() {
+                if let Node::ProcedureHeading( _ , _ , _ , _ , x , _ , _ ) = &**heading {
+                    self.declare(x, SymbolKind::Procedure);
+                }
+            }
+
+            self.table.close_scope()
+        }
+    }
+
+    /// Open scopes for structured types. Owner is the named type, so that fields and enumerators can be found through it.
+    fn declare_type(&mut self, type_node: &Node, owner: Option<EntryId>) {
+        match type_node {
+            Node::RecordType( s , _ , _ , _ , fields , procedures , _ ) => {
+                self.open_node_scope(ScopeKind::Record, *s, owner);
+
+                if let Some( ( nodes , _ ) ) = fields {
+                    for node in nodes.iter() {
+                        self.declare_variables(node)
+                    }
+                }
+
+                if let Some( ( nodes , _ ) ) = procedures {
+                    for node in nodes.iter() {
+                        self.declare_procedure(node)
+                    }
+                }
+
+                self.table.close_scope()
+            },
+            Node::ObjectType( s , _ , _ , _ , _ , _ , decl , _ , _ , _ ) => {
+                self.open_node_scope(ScopeKind::Object, *s, owner);
+
+                if let Some( x ) = decl {
+                    self.declare_declarations(x)
+                }
+
+                self.table.close_scope()
+            },
+            Node::CellType( s , _ , _ , _ , ports , _ , imports , decl , _ , _ , _ ) => {
+                self.open_node_scope(ScopeKind::Cell, *s, owner);
+
+                if let Some( ( _ , x , _ ) ) = ports {
+                    if let Node::PortList( _ , _ , nodes , _ ) = &**x {
+                        for node in nodes.iter() {
+                            if let Node::PortDeclaration( _ , _ , names , _ , _ , _ ) = &**node {
+                                for name in names.iter() {
+                                    self.declare(&name.0, SymbolKind::Variable);
+                                }
+                            }
+                        }
+                    }
+                }
+
+                if let Some( x ) = imports {
+                    self.declare_imports(x)
+                }
+
+                if let Some( x ) = decl {
+                    self.declare_declarations(x)
+                }
+
+                self.table.close_scope()
+            },
+            Node::EnumerationType( s , _ , _ , _ , elements , _ , _ ) => {
+                self.open_node_scope(ScopeKind::Enumeration, *s, owner);
+
+                for element in elements.iter() {
+                    if let Node::EnumElement( _ , _ , id , _ ) = &**element {
+                        self.declare(id, SymbolKind::Constant);
+                    }
+                }
+
+                self.table.close_scope()
+            },
+            Node::PointerType( _ , _ , _ , _ , _ , target ) => self.declare_type(target, owner),
+            Node::ArrayType( _ , _ , _ , _ , _ , element ) |
+            Node::MathArrayType( _ , _ , _ , _ , _ , element ) => self.declare_type(element, None),
+            _ => ()
+        }
+    }
+
+    fn open_node_scope(&mut self, kind: ScopeKind, position: u32, owner: Option<EntryId>) -> ScopeId {
+        let name = match owner {
+            Some( x ) => self.table.entry(x).name.clone(),
+            _ => String::new()
+        };
+        let scope = self.table.open_scope(kind, name.as_str());
+        self.scopes.insert( ( self.module, position ), scope );
+
+        if let Some( x ) = owner {
+            self.table.set_inner_scope(x, scope)
+        }
+
+        scope
+    }
+
+    /// Make scope from first pass current again, returning the scope to go back to afterwards
+    fn enter_node_scope(&mut self, position: u32) -> ScopeId {
+        let previous = self.table.current_scope();
+
+        if let Some( x ) = self.scopes.get(&( self.module, position )) {
+            self.table.enter_scope(*x)
+        }
+
+        previous
+    }
+
+    fn resolve_node(&mut self, node: &Node) {
+        match node {
+            Node::Ident( .. ) => {
+                self.resolve_identifier(node);
+            },
+            Node::QualifiedIdentifier( _ , _ , left , _ , right ) => {
+                let owner = self.resolve_identifier(left);
+                self.resolve_member(owner, right);
+            },
+            Node::UnaryExpression( _ , _ , primary , designators , flags ) => {
+                self.resolve_node(primary);
+
+                /* Selectors on modules and enumeration types are bound here, record fields need types and are left to the type checker */
+                let mut owner = match &**primary {
+                    Node::Ident( s , _ , _ ) => self.bindings.get(&( self.module, *s )).copied(),
+                    _ => None
+                };
+
+                if let Some( nodes ) = designators {
+                    for designator in nodes.iter() {
+                        match &**designator {
+                            Node::DotName( _ , _ , _ , id ) => {
+                                owner = match owner {
+                                    Some( x ) if self.table.entry(x).kind == SymbolKind::Module || self.table.entry(x).kind == SymbolKind::Type => self.resolve_member(Some( x ), id),
+                                    _ => None
+                                }
+                            },
+                            _ => {
+                                owner = None;
+                                self.resolve_node(designator)
+                            }
+                        }
+                    }
+                }
+
+                self.resolve_optional(flags)
+            },
+            Node::DotName( .. ) => (),  /* Field selector outside designator is left to type checker */
+            Node::Flag( _ , _ , _ , arguments , value ) => {
+                /* Flag names are compiler directives, not identifiers */
+                if let Some( ( _ , x , _ ) ) = arguments {
+                    self.resolve_node(x)
+                }
+                if let Some( ( _ , x ) ) = value {
+                    self.resolve_node(x)
+                }
+            },
+            Node::Const( _ , _ , _ , _ , value ) => self.resolve_node(value),
+            Node::TypeDeclarationElement( _ , _ , _ , _ , type_node , _ ) => self.resolve_node(type_node),
+            Node::Var( _ , _ , list , _ , type_node ) => {
+                self.resolve_node(type_node);
+
+                if let Node::VarList( _ , _ , names , _ ) = &**list {
+                    for name in names.iter() {
+                        if let Node::VarName( _ , _ , _ , flags , initializer ) = &**name {
+                            self.resolve_optional(flags);
+                            if let Some( ( _ , x ) ) = initializer {
+                                self.resolve_node(x)
+                            }
+                        }
+                    }
+                }
+            },
+            Node::Parameter( _ , _ , _ , flags , default ) => {
+                self.resolve_optional(flags);
+                if let Some( ( _ , x ) ) = default {
+                    self.resolve_node(x)
+                }
+            },
+            Node::Procedure( s , _ , _ , flags , receiver , _ , parameters , _ , decl , body , _ , _ ) => {
+                if let Some( ( Some( x ) , _ ) ) = flags {
+                    self.resolve_node(x)
+                }
+
+                let previous = self.enter_node_scope(*s);
+
+                if let Some( ( _ , x , _ ) ) = receiver {
+                    self.resolve_node(x)
+                }
+                self.resolve_optional(parameters);
+                self.resolve_optional(decl);
+                self.resolve_optional(body);
+
+                self.table.enter_scope(previous)
+            },
+            Node::Operator( s , _ , _ , flags , _ , _ , _ , parameters , _ , decl , body , _ , _ ) => {
+                self.resolve_optional(flags);
+
+                let previous = self.enter_node_scope(*s);
+
+                self.resolve_node(parameters);
+                self.resolve_optional(decl);
+                self.resolve_optional(body);
+
+                self.table.enter_scope(previous)
+            },
+            Node::ObjectType( s , _ , _ , _ , base , implements , _ , _ , _ , _ ) => {
+                /* Base type and implemented definitions are found from scope around the type */
+                let mut outside = Vec::<&Node>::new();
+                if let Some( ( _ , x , _ ) ) = base {
+                    outside.push(x)
+                }
+                if let Some( ( _ , nodes , _ ) ) = implements {
+                    outside.extend(nodes.iter().map(|x| &**x))
+                }
+
+                for x in outside.iter() {
+                    self.resolve_node(x)
+                }
+
+                let previous = self.enter_node_scope(*s);
+
+                for child in children(node).iter() {
+                    match outside.iter().any(|x| std::ptr::eq(*child, *x)) {
+                        true => (),
+                        _ => self.resolve_node(child)
+                    }
+                }
+
+                self.table.enter_scope(previous)
+            },
+            Node::Definition( _ , _ , _ , _ , _ , headings , _ , _ ) => {
+                /* Names of headings are declarations, only flags and parameter types are bound */
+                for heading in headings.iter() {
+                    if let Node::ProcedureHeading( _ , _ , _ , flags , _ , parameters , _ ) = &**heading {
+                        self.resolve_optional(flags);
+                        self.resolve_optional(parameters)
+                    }
+                }
+            },
+            Node::RecordType( s , _ , _ , base , _ , _ , _ ) |
+            Node::EnumerationType( s , _ , _ , base , _ , _ , _ ) => {
+                /* Base type is found from scope around the type */
+                if let Some( ( _ , x , _ ) ) = base {
+                    self.resolve_node(x)
+                }
+
+                let previous = self.enter_node_scope(*s);
+
+                for child in children(node).iter() {
+                    match base {
+                        Some( ( _ , x , _ ) ) if std::ptr::eq(*child, &**x) => (),
+                        _ => self.resolve_node(child)
+                    }
+                }
+
+                self.table.enter_scope(previous)
+            },
+            Node::CellType( s , _ , _ , _ , _ , _ , _ , _ , _ , _ , _ ) => {
+                let previous = self.enter_node_scope(*s);
+
+                for child in children(node).iter() {
+                    self.resolve_node(child)
+                }
+
+                self.table.enter_scope(previous)
+            },
+            Node::EnumElement( _ , _ , _ , value ) => {
+                if let Some( ( _ , x ) ) = value {
+                    self.resolve_node(x)
+                }
+            },
+            Node::PortDeclaration( _ , _ , _ , _ , _ , type_node ) => self.resolve_node(type_node),
+            Node::ImportList( .. ) |
+            Node::TemplateParameters( .. ) |
+            Node::Code( .. ) => (),
+            _ => {
+                for child in children(node).iter() {
+                    self.resolve_node(child)
+                }
+            }
+        }
+    }
+
+    fn resolve_optional(&mut self, node: &Option<Box<Node>>) {
+        if let Some( x ) = node {
+            self.resolve_node(x)
+        }
+    }
+
+    /// Bind identifier to nearest declaration with that name
+    fn resolve_identifier(&mut self, node: &Node) -> Option<EntryId> {
+        match node {
+            Node::Ident( _ , _ , symbol ) => {
+                match &**symbol {
+                    Symbols::Ident( s , e , name ) => {
+                        match self.table.lookup(name.as_str()) {
+                            Some( x ) => {
+                                self.table.mark_used(x);
+                                self.bindings.insert( ( self.module, *s ), x );
+                                Some( x )
+                            },
+                            _ => {
+                                self.errors.push(Diagnostic::error(E_UNDECLARED, format!("Undeclared identifier '{}'", name), *s, *e));
+                                None
+                            }
+                        }
+                    },
+                    _ => None
+                }
+            },
+            _ => None
+        }
+    }
+
+    /// Bind selector to member of module or named type. Unknown modules accept any member.
+    fn resolve_member(&mut self, owner: Option<EntryId>, node: &Node) -> Option<EntryId> {
+        let scope = match owner {
+            Some( x ) => self.table.entry(x).inner,
+            _ => None
+        };
+
+        match ( scope , node ) {
+            ( Some( scope ) , Node::Ident( _ , _ , symbol ) ) => {
+                match &**symbol {
+                    Symbols::Ident( s , e , name ) => {
+                        match self.table.lookup_qualified(scope, name.as_str(), *s, *e) {
+                            Ok( Some( x ) ) => {
+                                self.table.mark_used(x);
+                                self.bindings.insert( ( self.module, *s ), x );
+                                Some( x )
+                            },
+                            Ok( None ) => {
+                                self.errors.push(Diagnostic::error(E_UNDECLARED, format!("Undeclared identifier '{}.{}'", self.table.scope(scope).name, name), *s, *e));
+                                None
+                            },
+                            Err( e ) => {
+                                self.errors.push(e);
+                                None
+                            }
+                        }
+                    },
+                    _ => None
+                }
+            },
+            _ => None
+        }
+    }
+}
+
+// Unittests for resolver module
+
+#[cfg(test)]
+mod tests {
+    use crate::diagnostics::{E_UNDECLARED, E_NOT_EXPORTED, E_AMBIGUOUS_IMPORT, E_DUPLICATE_DECLARATION};
+    use crate::parser::{Parser, ParserMethods, BlockRules};
+    use crate::resolver::{Resolver, ResolverMethods};
+    use crate::scanner::{Scanner, ScannerMethods};
+    use crate::symbol_table::{SymbolTableMethods, SymbolKind};
+
+    fn resolve(sources: Vec<&'static str>) -> Resolver {
+        let mut resolver = Resolver::new();
+        let modules = sources.iter().map(|x| Parser::new(Box::new(Scanner::new(x))).parse_module().unwrap()).collect::<Vec<_>>();
+
+        for module in modules.iter() {
+            resolver.declare_module(module)
+        }
+        for module in modules.iter() {
+            resolver.resolve_module(module)
+        }
+
+        resolver
+    }
+
+    #[test]
+    fn resolve_local_and_global() {
+        let resolver = resolve(vec![ "MODULE Test; VAR a: INTEGER; PROCEDURE P(b: INTEGER); VAR c: INTEGER; BEGIN c := a + b END P; END Test." ]);
+        let module = resolver.get_module_scope("Test").unwrap();
+
+        assert_eq!(resolver.errors.len(), 0);
+        match resolver.get_binding(module, 81) {
+            Some( x ) => assert_eq!(resolver.get_symbol_table().entry(x).kind, SymbolKind::Variable),
+            _ => assert!(false)
+        }
+        match resolver.get_binding(module, 85) {
+            Some( x ) => assert_eq!(resolver.get_symbol_table().entry(x).kind, SymbolKind::Parameter),
+            _ => assert!(false)
+        }
+    }
+
+    #[test]
+    fn resolve_undeclared() {
+        let resolver = resolve(vec![ "MODULE Test; BEGIN x := 1 END Test." ]);
+
+        assert_eq!(resolver.errors.len(), 1);
+        assert_eq!(resolver.errors[0].code, E_UNDECLARED);
+        assert_eq!(resolver.errors[0].span.start, 19)
+    }
+
+    #[test]
+    fn resolve_forward_references() {
+        let resolver = resolve(vec![ "MODULE Test; TYPE List = POINTER TO Node; Node = RECORD next: List; value: INTEGER END; PROCEDURE A; BEGIN B END A; PROCEDURE B; BEGIN A END B; END Test." ]);
+
+        assert_eq!(resolver.errors.len(), 0)
+    }
+
+    #[test]
+    fn resolve_duplicate_declaration() {
+        let resolver = resolve(vec![ "MODULE Test; VAR a: INTEGER; a: BOOLEAN; END Test." ]);
+
+        assert_eq!(resolver.errors.len(), 1);
+        assert_eq!(resolver.errors[0].code, E_DUPLICATE_DECLARATION)
+    }
+
+    #[test]
+    fn resolve_qualified_between_modules() {
+        let resolver = resolve(vec![
+            "MODULE Lib; VAR open*: INTEGER; hidden: INTEGER; END Lib.",
+            "MODULE Test; IMPORT L := Lib, Out; BEGIN L.open := 1; L.hidden := 2; L.missing := 3; Out.Ln END Test."
+        ]);
+
+        assert_eq!(resolver.errors.len(), 2);
+        assert_eq!(resolver.errors[0].code, E_NOT_EXPORTED);
+        assert_eq!(resolver.errors[1].code, E_UNDECLARED)
+    }
+
+    #[test]
+    fn resolve_ambiguous_import_alias() {
+        let resolver = resolve(vec![ "MODULE Test; IMPORT Out, Out := Texts; END Test." ]);
+
+        assert_eq!(resolver.errors.len(), 1);
+        assert_eq!(resolver.errors[0].code, E_AMBIGUOUS_IMPORT);
+        assert_eq!(resolver.errors[0].span.start, 25)
+    }
+
+    #[test]
+    fn resolve_enumeration_members() {
+        let resolver = resolve(vec![ "MODULE Test; TYPE Color = ENUM Red, Green END; VAR c: Color; BEGIN c := Color.Green; c := Color.Blue END Test." ]);
+
+        assert_eq!(resolver.errors.len(), 1);
+        assert_eq!(resolver.errors[0].code, E_UNDECLARED)
+    }
+}
@@ -0,0 +1,1256 @@
+// ActiveOberon Compiler, a native ARM v8 & X86-64 compiler & Risc V / linker / builder utility.
+// Written by Richard Magnor Stenbro. Licensed under GPL v3
+// Inline assembler for Risc V module for compiling and linking of projects written in ActiveOberon language
+
+use std::collections::{HashMap, HashSet};
+use crate::diagnostics::{Diagnostic, DiagnosticMethods, E_ASSEMBLER_SYNTAX, E_ASSEMBLER_CPU_FLAG, E_ASSEMBLER_UNDEFINED, E_ASSEMBLER_DUPLICATE, E_ASSEMBLER_OPERAND, E_DIVISION_BY_ZERO};
+use crate::inline_assembler::{InlineAssembler, OberonVariable, Relocation};
+use crate::riscv_instruction_set_neo::{RiscVOperand, RegisterRiscV, register_risc_v, rounding_risc_v, csr_risc_v, fence_set_risc_v, encode_instruction_risc_v, CpuFlags, CPU_RV64I, CPU_M, CPU_A, CPU_F, CPU_D, CPU_C, CPU_RV64GC};
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum RiscVSymbols {
+    Ident(u32, u32, Box<String>),
+    Label(u32, u32, Box<String>),
+    Number(u32, u32, i64),
+    String_(u32, u32, Box<String>),
+    Period(u32, u32),
+    Comma(u32, u32),
+    Plus(u32, u32),
+    Minus(u32, u32),
+    Times(u32, u32),
+    Div(u32, u32),
+    Modulo(u32, u32),
+    Negate(u32, u32),
+    LeftParen(u32, u32),
+    RightParen(u32, u32),
+    LeftCurly(u32, u32),
+    RightCurly(u32, u32),
+    Dollar(u32, u32),
+    NewLine(u32, u32),
+    EndOfFile(u32),
+    None
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum RiscVNode {
+    None,
+    Number(u32, u32, i64),
+    String(u32, u32, Box<String>),
+    Ident(u32, u32, Box<String>),
+    Times(u32, u32, Box<RiscVNode>, Box<RiscVSymbols>, Box<RiscVNode>),
+    Div(u32, u32, Box<RiscVNode>, Box<RiscVSymbols>, Box<RiscVNode>),
+    Modulo(u32, u32, Box<RiscVNode>, Box<RiscVSymbols>, Box<RiscVNode>),
+    Minus(u32, u32, Box<RiscVNode>, Box<RiscVSymbols>, Box<RiscVNode>),
+    Plus(u32, u32, Box<RiscVNode>, Box<RiscVSymbols>, Box<RiscVNode>),
+    UnaryMinus(u32, u32, Box<RiscVSymbols>, Box<RiscVNode>),
+    UnaryPlus(u32, u32, Box<RiscVSymbols>, Box<RiscVNode>),
+    Negate(u32, u32, Box<RiscVSymbols>, Box<RiscVNode>),
+    Location(u32, u32),
+    Memory(u32, u32, Box<RiscVNode>, Box<RiscVNode>),      /* Offset and base register, like '8(sp)' */
+    Instruction(u32, u32, Box<String>, Box<Vec<Box<RiscVNode>>>),
+    Label(u32, u32, Box<String>),
+    Data(u32, u32, u32, Box<Vec<Box<RiscVNode>>>),     /* DB, DW, DD and DQ with size of each item in bytes */
+    Repeat(u32, u32, Box<RiscVNode>, Box<RiscVNode>),  /* TIMES count line */
+    Align(u32, u32, Box<RiscVNode>)
+}
+
+/// Start and end position of node inside assembler text
+fn node_span(node: &RiscVNode) -> (u32, u32) {
+    match node {
+        RiscVNode::Number(s, e, _) |
+        RiscVNode::String(s, e, _) |
+        RiscVNode::Ident(s, e, _) |
+        RiscVNode::Times(s, e, _ , _ , _ ) |
+        RiscVNode::Div(s, e, _ , _ , _ ) |
+        RiscVNode::Modulo(s, e, _ , _ , _ ) |
+        RiscVNode::Minus(s, e, _ , _ , _ ) |
+        RiscVNode::Plus(s, e, _ , _ , _ ) |
+        RiscVNode::UnaryMinus(s, e, _ , _ ) |
+        RiscVNode::UnaryPlus(s, e, _ , _ ) |
+        RiscVNode::Negate(s, e, _ , _ ) |
+        RiscVNode::Location(s, e) |
+        RiscVNode::Memory(s, e, _ , _ ) |
+        RiscVNode::Instruction(s, e, _ , _ ) |
+        RiscVNode::Label(s, e, _ ) |
+        RiscVNode::Data(s, e, _ , _ ) |
+        RiscVNode::Repeat(s, e, _ , _ ) |
+        RiscVNode::Align(s, e, _ ) => (*s, *e),
+        RiscVNode::None => (0, 0)
+    }
+}
+
+/// Bytes read or written by load and store, taken from mnemonic
+fn access_size(mnemonic: &str) -> Option<u32> {
+    match mnemonic {
+        "LB" | "LBU" | "SB" => Some( 1 ),
+        "LH" | "LHU" | "SH" => Some( 2 ),
+        "LW" | "LWU" | "SW" | "FLW" | "FSW" => Some( 4 ),
+        "LD" | "SD" | "FLD" | "FSD" => Some( 8 ),
+        _ => None
+    }
+}
+
+/// Frame pointer that Oberon parameters and local variables are relative to
+const FRAME_POINTER : RegisterRiscV = RegisterRiscV::Integer(8);
+
+pub trait AssemblerRiscVMethods {
+    fn new(text: Vec<char>, offset_position: u32) -> Self;
+    fn get_position(&self) -> u32;
+    fn get_char(&mut self) -> char;
+    fn peek_char(&self) -> char;
+    fn next_char(&mut self) -> ();
+    fn skip_whitespace(&mut self) -> ();
+    fn get_ident(&mut self) -> Box<String>;
+    fn get_number(&mut self, start: u32) -> Result<Box<RiscVSymbols>, Box<Diagnostic>>;
+    fn get_string(&mut self) -> Box<String>;
+    fn get_symbol(&mut self) -> Result<Box<RiscVSymbols>, Box<Diagnostic>>;
+    fn factor(&mut self) -> Result<Box<RiscVNode>, Box<Diagnostic>>;
+    fn term(&mut self) -> Result<Box<RiscVNode>, Box<Diagnostic>>;
+    fn expression(&mut self) -> Result<Box<RiscVNode>, Box<Diagnostic>>;
+    fn base_register(&mut self) -> Result<Option<Box<RiscVNode>>, Box<Diagnostic>>;
+    fn operand(&mut self) -> Result<Box<RiscVNode>, Box<Diagnostic>>;
+    fn instruction(&mut self, start: u32, end: u32, mnemonic: Box<String>) -> Result<Box<RiscVNode>, Box<Diagnostic>>;
+    fn statement(&mut self, start: u32, end: u32, keyword: Box<String>) -> Result<Box<RiscVNode>, Box<Diagnostic>>;
+    fn end_of_line(&self, node: Box<RiscVNode>) -> Result<Box<RiscVNode>, Box<Diagnostic>>;
+    fn evaluate(&self, node: &RiscVNode) -> Result<i64, Box<Diagnostic>>;
+    fn register(&self, node: &RiscVNode) -> Result<RegisterRiscV, Box<Diagnostic>>;
+    fn resolve_operand(&self, node: &RiscVNode) -> Result<RiscVOperand, Box<Diagnostic>>;
+    fn define(&mut self, name: &str, value: i64, is_label: bool, start: u32, end: u32) -> Result<(), Box<Diagnostic>>;
+    fn qualified(&self, name: &str) -> Box<String>;
+    fn emit(&mut self, line: &RiscVNode, code: &mut Vec<u8>) -> Result<(), Box<Diagnostic>>;
+    fn count(&self, node: &RiscVNode) -> Result<i64, Box<Diagnostic>>;
+    fn is_defined(&self, name: &str) -> bool;
+    fn variable(&self, name: &str) -> Option<&OberonVariable>;
+    fn sized_variable(&self, node: &RiscVNode) -> Option<(String, u32)>;
+
+    fn advance(&mut self) -> ();
+    fn error(&self, code: &'static str, message: String) -> Box<Diagnostic>;
+    fn error_at(&self, code: &'static str, message: String, start: u32, end: u32) -> Box<Diagnostic>;
+}
+
+pub struct AssemblerRiscV {
+    buffer: Vec<char>,	/* Sourcecode as a vector of chars */
+    offset_position: u32,	/* Position of code block in Oberon source file */
+    start_pos: u32,		/* Start of current analyzed symbol */
+    index: u32,			/* Position into vector */
+    symbol: Result<Box<RiscVSymbols>, Box<Diagnostic>>,
+    location: i64,		/* Value of '$', offset of current instruction from start of code block */
+    labels: HashMap<String, i64>,
+    constants: HashMap<String, i64>,	/* Names defined with 'EQU' */
+    scope: Box<String>,		/* Last label not starting with '@', local labels are only visible until next such label */
+    pending: HashSet<String>,	/* Labels not yet reached in first pass, they are assumed to be at current location */
+    flags: CpuFlags,		/* CPU flags of code block */
+    variables: HashMap<String, OberonVariable>,	/* Oberon variables visible in code block */
+    relocations: Vec<Relocation>
+}
+
+impl AssemblerRiscVMethods for AssemblerRiscV {
+    fn new(text: Vec<char>, offset_position: u32) -> Self {
+        AssemblerRiscV {
+            buffer: text,
+            offset_position,
+            start_pos: 0,
+            index: 0,
+            symbol: Ok(Box::new(RiscVSymbols::None)),
+            location: 0,
+            labels: HashMap::new(),
+            constants: HashMap::new(),
+            scope: Box::new(String::new()),
+            pending: HashSet::new(),
+            flags: CPU_RV64GC,
+            variables: HashMap::new(),
+            relocations: Vec::new()
+        }
+    }
+
+    fn get_position(&self) -> u32 {
+        self.index
+    }
+
+    fn get_char(&mut self) -> char {
+        match self.buffer.get(self.index as usize) {
+            Some(x) => *x,
+            _ => '\0'
+        }
+    }
+
+    /// Character after the current one, used for '//' comments
+    fn peek_char(&self) -> char {
+        match self.buffer.get(self.index as usize + 1) {
+            Some(x) => *x,
+            _ => '\0'
+        }
+    }
+
+    fn next_char(&mut self) {
+        if (self.index as usize) < self.buffer.len() {
+            self.index += 1;
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        loop {
+            match self.get_char() {
+                ' ' | '\t' => {
+                    self.next_char();
+                    continue
+                }, /* Remove whitespace */
+                '/' if self.peek_char() != '/' => break,
+                ';' | '/' | '#' => { /* Remove comments, starting with ';', '//' or '#' */
+                    loop {
+                        match self.get_char() {
+                            '\r' | '\n' | '\0' => break,
+                            _ => self.next_char()
+                        }
+                    }
+                    break
+                },
+                _ => break
+            }
+        }
+    }
+
+    fn get_ident(&mut self) -> Box<String> {
+        let mut text = String::new();
+        while let '0' ..= '9' | 'a' ..= 'z' | 'A' ..= 'Z' | '_' = self.get_char() {
+            text.push(self.get_char());
+            self.next_char();
+        }
+
+        Box::new(text)
+    }
+
+    /// Decimal number or hexadecimal number like '0x1F' or '1FH'
+    fn get_number(&mut self, start: u32) -> Result<Box<RiscVSymbols>, Box<Diagnostic>> {
+        let mut text = String::new();
+
+        while let '0' ..= '9' | 'a' ..= 'z' | 'A' ..= 'Z' | '_' = self.get_char() {
+            text.push(self.get_char());
+            self.next_char();
+        }
+
+        let upper = text.to_uppercase();
+        let value = match ( upper.strip_prefix("0X") , upper.strip_suffix('H') ) {
+            ( Some( x ) , _ ) | ( _ , Some( x ) ) => u64::from_str_radix(x, 16),
+            _ => upper.parse::<u64>()
+        };
+
+        match value {
+            Ok( x ) => Ok(Box::new(RiscVSymbols::Number(start, self.get_position(), x as i64))),
+            _ => Err(self.error(E_ASSEMBLER_SYNTAX, format!("Invalid number '{}' in assembler code", text)))
+        }
+    }
+
+    fn get_string(&mut self) -> Box<String> {
+        let mut text = String::new();
+        text.push(self.get_char());
+        self.next_char();
+
+        loop {
+            match self.get_char() {
+                '\'' => {
+                    text.push(self.get_char());
+                    self.next_char();
+                    break
+                },
+                '\0' => break,
+                _ => {
+                    text.push(self.get_char())
+                }
+            }
+            self.next_char()
+        }
+
+        Box::new(text)
+    }
+
+    /// Assembler lexer for Risc V syntax
+    fn get_symbol(&mut self) -> Result<Box<RiscVSymbols>, Box<Diagnostic>> {
+        self.skip_whitespace();
+        let start_pos = self.get_position();
+        self.start_pos = start_pos;
+
+        match self.get_char() {
+            'a' ..= 'z' | 'A' ..= 'Z' | '_' | '@' => {
+                /* Local label like '@loop' starts with '@' */
+                let local = self.get_char() == '@';
+                if local {
+                    self.next_char()
+                }
+                let symbol = match local {
+                    true => Box::new(format!("@{}", self.get_ident())),
+                    _ => self.get_ident()
+                };
+                if symbol.as_str() == "@" {
+                    return Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Expecting name of local label after '@'")))
+                }
+                let end = self.get_position();
+                self.skip_whitespace();
+                match self.get_char() {
+                    ':' => {
+                        self.next_char();
+                        Ok(Box::new(RiscVSymbols::Label(start_pos, self.get_position(), symbol)))
+                    },
+                    _ => Ok(Box::new(RiscVSymbols::Ident(start_pos, end, symbol)))
+                }
+            },
+            '0' ..= '9' => self.get_number(start_pos),
+            '\'' => {
+                let symbol = self.get_string();
+                Ok(Box::new(RiscVSymbols::String_(start_pos, self.get_position(), symbol)))
+            },
+            '\0' => Ok(Box::new(RiscVSymbols::EndOfFile(self.get_position()))),
+            '\r' => {
+                self.next_char();
+                if self.get_char() == '\n' {
+                    self.next_char()
+                }
+                Ok(Box::new(RiscVSymbols::NewLine(start_pos, self.get_position())))
+            },
+            '.' => {
+                self.next_char();
+                Ok(Box::new(RiscVSymbols::Period(start_pos, self.get_position())))
+            },
+            ',' => {
+                self.next_char();
+                Ok(Box::new(RiscVSymbols::Comma(start_pos, self.get_position())))
+            },
+            '+' => {
+                self.next_char();
+                Ok(Box::new(RiscVSymbols::Plus(start_pos, self.get_position())))
+            },
+            '-' => {
+                self.next_char();
+                Ok(Box::new(RiscVSymbols::Minus(start_pos, self.get_position())))
+            },
+            '*' => {
+                self.next_char();
+                Ok(Box::new(RiscVSymbols::Times(start_pos, self.get_position())))
+            },
+            '/' => {
+                self.next_char();
+                Ok(Box::new(RiscVSymbols::Div(start_pos, self.get_position())))
+            },
+            '%' => {
+                self.next_char();
+                Ok(Box::new(RiscVSymbols::Modulo(start_pos, self.get_position())))
+            },
+            '~' => {
+                self.next_char();
+                Ok(Box::new(RiscVSymbols::Negate(start_pos, self.get_position())))
+            },
+            '(' => {
+                self.next_char();
+                Ok(Box::new(RiscVSymbols::LeftParen(start_pos, self.get_position())))
+            },
+            ')' => {
+                self.next_char();
+                Ok(Box::new(RiscVSymbols::RightParen(start_pos, self.get_position())))
+            },
+            '{' => {
+                self.next_char();
+                Ok(Box::new(RiscVSymbols::LeftCurly(start_pos, self.get_position())))
+            },
+            '}' => {
+                self.next_char();
+                Ok(Box::new(RiscVSymbols::RightCurly(start_pos, self.get_position())))
+            },
+            '$' => {
+                self.next_char();
+                Ok(Box::new(RiscVSymbols::Dollar(start_pos, self.get_position())))
+            },
+            '\n' => {
+                self.next_char();
+                Ok(Box::new(RiscVSymbols::NewLine(start_pos, self.get_position())))
+            },
+            _ => Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Invalid symbol in inline assembler")))
+        }
+    }
+
+    /// factor = number | string | '$' | ident | '(' expression ')' | ( '-' | '+' | '~' ) factor
+    fn factor(&mut self) -> Result<Box<RiscVNode>, Box<Diagnostic>> {
+        match *self.symbol.clone()? {
+            RiscVSymbols::Number(s, e, n) => {
+                self.advance();
+                Ok(Box::new(RiscVNode::Number(s, e, n)))
+            },
+            RiscVSymbols::String_(s, e, t) => {
+                self.advance();
+                Ok(Box::new(RiscVNode::String(s, e, t)))
+            },
+            RiscVSymbols::Ident(s, e, t) => {
+                self.advance();
+                Ok(Box::new(RiscVNode::Ident(s, e, self.qualified(t.as_str()))))
+            },
+            RiscVSymbols::Dollar(s, e) => {
+                self.advance();
+                Ok(Box::new(RiscVNode::Location(s, e)))
+            },
+            RiscVSymbols::LeftParen( _ , _ ) => {
+                self.advance();
+                let node = self.expression()?;
+                match *self.symbol.clone()? {
+                    RiscVSymbols::RightParen( _ , _ ) => {
+                        self.advance();
+                        Ok(node)
+                    },
+                    _ => Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Expecting ')' in expression")))
+                }
+            },
+            RiscVSymbols::Minus(s, _ ) => {
+                let symbol = self.symbol.clone()?;
+                self.advance();
+                let right = self.factor()?;
+                Ok(Box::new(RiscVNode::UnaryMinus(s, node_span(&right).1, symbol, right)))
+            },
+            RiscVSymbols::Plus(s, _ ) => {
+                let symbol = self.symbol.clone()?;
+                self.advance();
+                let right = self.factor()?;
+                Ok(Box::new(RiscVNode::UnaryPlus(s, node_span(&right).1, symbol, right)))
+            },
+            RiscVSymbols::Negate(s, _ ) => {
+                let symbol = self.symbol.clone()?;
+                self.advance();
+                let right = self.factor()?;
+                Ok(Box::new(RiscVNode::Negate(s, node_span(&right).1, symbol, right)))
+            },
+            _ => Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Expecting operand in expression")))
+        }
+    }
+
+    /// term = factor { ( '*' | '/' | '%' ) factor }
+    fn term(&mut self) -> Result<Box<RiscVNode>, Box<Diagnostic>> {
+        let mut left = self.factor()?;
+
+        loop {
+            let symbol = self.symbol.clone()?;
+            match *symbol {
+                RiscVSymbols::Times( _ , _ ) |
+                RiscVSymbols::Div( _ , _ ) |
+                RiscVSymbols::Modulo( _ , _ ) => {
+                    self.advance();
+                    let right = self.factor()?;
+                    let ( s , e ) = ( node_span(&left).0, node_span(&right).1 );
+                    left = match *symbol {
+                        RiscVSymbols::Times( _ , _ ) => Box::new(RiscVNode::Times(s, e, left, symbol, right)),
+                        RiscVSymbols::Div( _ , _ ) => Box::new(RiscVNode::Div(s, e, left, symbol, right)),
+                        _ => Box::new(RiscVNode::Modulo(s, e, left, symbol, right))
+                    }
+                },
+                _ => break
+            }
+        }
+
+        Ok(left)
+    }
+
+    /// expression = term { ( '+' | '-' ) term }
+    fn expression(&mut self) -> Result<Box<RiscVNode>, Box<Diagnostic>> {
+        let mut left = self.term()?;
+
+        loop {
+            let symbol = self.symbol.clone()?;
+            match *symbol {
+                RiscVSymbols::Plus( _ , _ ) |
+                RiscVSymbols::Minus( _ , _ ) => {
+                    self.advance();
+                    let right = self.term()?;
+                    let ( s , e ) = ( node_span(&left).0, node_span(&right).1 );
+                    left = match *symbol {
+                        RiscVSymbols::Plus( _ , _ ) => Box::new(RiscVNode::Plus(s, e, left, symbol, right)),
+                        _ => Box::new(RiscVNode::Minus(s, e, left, symbol, right))
+                    }
+                },
+                _ => break
+            }
+        }
+
+        Ok(left)
+    }
+
+    /// base = '(' register ')', called at '(' and gives none with position restored when no register follows
+    fn base_register(&mut self) -> Result<Option<Box<RiscVNode>>, Box<Diagnostic>> {
+        let saved = ( self.index , self.start_pos , self.symbol.clone() );
+        self.advance();
+
+        match *self.symbol.clone()? {
+            RiscVSymbols::Ident( s , e , t ) if register_risc_v(t.as_str()).is_some() => {
+                self.advance();
+                match *self.symbol.clone()? {
+                    RiscVSymbols::RightParen( _ , _ ) => {
+                        self.advance();
+                        return Ok(Some( Box::new(RiscVNode::Ident(s, e, t)) ))
+                    },
+                    _ => return Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Expecting ')' after base register")))
+                }
+            },
+            _ => ()
+        }
+
+        ( self.index , self.start_pos , self.symbol ) = saved;
+        Ok(None)
+    }
+
+    /// operand = '(' register ')' | expression [ '(' register ')' ]
+    fn operand(&mut self) -> Result<Box<RiscVNode>, Box<Diagnostic>> {
+        let start = self.start_pos;
+
+        if let RiscVSymbols::LeftParen( _ , _ ) = *self.symbol.clone()? {
+            if let Some( base ) = self.base_register()? {
+                return Ok(Box::new(RiscVNode::Memory(start, node_span(&base).1 + 1, Box::new(RiscVNode::None), base)))
+            }
+        }
+
+        let node = self.expression()?;
+        match *self.symbol.clone()? {
+            RiscVSymbols::LeftParen( _ , _ ) => {
+                match self.base_register()? {
+                    Some( base ) => Ok(Box::new(RiscVNode::Memory(start, node_span(&base).1 + 1, node, base))),
+                    _ => Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Expecting base register in memory operand")))
+                }
+            },
+            _ => Ok(node)
+        }
+    }
+
+    /// instruction = mnemonic { '.' name } [ operand { ',' operand } ] ( newline | end of file ), called after mnemonic is read
+    fn instruction(&mut self, start: u32, end: u32, mnemonic: Box<String>) -> Result<Box<RiscVNode>, Box<Diagnostic>> {
+        let mut end = end;
+        let mut operands = Box::new(Vec::<Box<RiscVNode>>::new());
+        let mut mnemonic = Box::new(mnemonic.to_uppercase());
+
+        /* Mnemonic like 'FADD.S' or 'AMOADD.W.AQ', parts follow without space */
+        loop {
+            match *self.symbol.clone()? {
+                RiscVSymbols::Period( s , _ ) if s == end => {
+                    self.advance();
+                    match *self.symbol.clone()? {
+                        RiscVSymbols::Ident( s , e , t ) if s == end + 1 => {
+                            mnemonic = Box::new(format!("{}.{}", mnemonic, t.to_uppercase()));
+                            end = e;
+                            self.advance()
+                        },
+                        _ => return Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Expecting name after '.' in mnemonic")))
+                    }
+                },
+                _ => break
+            }
+        }
+
+        match *self.symbol.clone()? {
+            RiscVSymbols::NewLine( _ , _ ) | RiscVSymbols::EndOfFile( _ ) => (),
+            _ => {
+                loop {
+                    let node = self.operand()?;
+                    end = node_span(&node).1;
+                    operands.push(node);
+                    match *self.symbol.clone()? {
+                        RiscVSymbols::Comma( _ , _ ) => self.advance(),
+                        _ => break
+                    }
+                }
+            }
+        }
+
+        match *self.symbol.clone()? {
+            RiscVSymbols::NewLine( _ , _ ) | RiscVSymbols::EndOfFile( _ ) => Ok(Box::new(RiscVNode::Instruction(start, end, mnemonic, operands))),
+            _ => Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Expecting end of line after instruction")))
+        }
+    }
+
+    /// statement = directive | 'TIMES' expression statement | instruction, called after first identifier is read
+    fn statement(&mut self, start: u32, end: u32, keyword: Box<String>) -> Result<Box<RiscVNode>, Box<Diagnostic>> {
+        match keyword.to_uppercase().as_str() {
+            "ALIGN" => {
+                let node = self.expression()?;
+                self.end_of_line(Box::new(RiscVNode::Align(start, node_span(&node).1, node)))
+            },
+            "DB" | "DW" | "DD" | "DQ" => {
+                let size = match keyword.to_uppercase().as_str() {
+                    "DB" => 1,
+                    "DW" => 2,
+                    "DD" => 4,
+                    _ => 8
+                };
+                let mut items = Box::new(Vec::<Box<RiscVNode>>::new());
+                loop {
+                    items.push(self.expression()?);
+                    match *self.symbol.clone()? {
+                        RiscVSymbols::Comma( _ , _ ) => self.advance(),
+                        _ => break
+                    }
+                }
+                let end = node_span(&items[items.len() - 1]).1;
+                self.end_of_line(Box::new(RiscVNode::Data(start, end, size, items)))
+            },
+            "TIMES" => {
+                let count = self.expression()?;
+                match *self.symbol.clone()? {
+                    RiscVSymbols::Ident(s, e, t) => {
+                        self.advance();
+                        let line = self.statement(s, e, t)?;
+                        Ok(Box::new(RiscVNode::Repeat(start, node_span(&line).1, count, line)))
+                    },
+                    _ => Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Expecting instruction or data after 'TIMES' count")))
+                }
+            },
+            _ => self.instruction(start, end, keyword)
+        }
+    }
+
+    /// Directive node when it is followed by end of line, else a syntax error
+    fn end_of_line(&self, node: Box<RiscVNode>) -> Result<Box<RiscVNode>, Box<Diagnostic>> {
+        match *self.symbol.clone()? {
+            RiscVSymbols::NewLine( _ , _ ) | RiscVSymbols::EndOfFile( _ ) => Ok(node),
+            _ => Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Expecting end of line after directive")))
+        }
+    }
+
+    /// Value of expression, using current location for '$', labels and 'EQU' constants
+    fn evaluate(&self, node: &RiscVNode) -> Result<i64, Box<Diagnostic>> {
+        let ( s , e ) = node_span(node);
+
+        match node {
+            RiscVNode::Number( _ , _ , n ) => Ok(*n),
+            RiscVNode::String( _ , _ , t ) => {
+                let text : Vec<char> = t.trim_matches('\'').chars().collect();
+                if text.len() > 8 {
+                    return Err(self.error_at(E_ASSEMBLER_OPERAND, String::from("String is too long for a numeric value"), s, e))
+                }
+                /* Characters are packed little endian, first character in lowest byte */
+                let mut value : i64 = 0;
+                for ( i , c ) in text.iter().enumerate() {
+                    value |= ((*c as u32 & 0xff) as i64) << (8 * i)
+                }
+                Ok(value)
+            },
+            RiscVNode::Location( _ , _ ) => Ok(self.location),
+            RiscVNode::Ident( _ , _ , t ) => {
+                if register_risc_v(t.as_str()).is_some() {
+                    return Err(self.error_at(E_ASSEMBLER_OPERAND, format!("Register '{}' is not allowed in expression", t), s, e))
+                }
+                if let Some( x ) = self.constants.get(t.as_str()) {
+                    return Ok(*x)
+                }
+                if self.pending.contains(t.as_str()) {
+                    return Ok(self.location)
+                }
+                match self.labels.get(t.as_str()) {
+                    Some( x ) => Ok(*x),
+                    _ if self.variables.contains_key(t.as_str()) => {
+                        match self.variables.get(t.as_str()) {
+                            Some( OberonVariable::Frame( offset , _ ) ) => Ok(*offset),
+                            _ => Err(self.error_at(E_ASSEMBLER_OPERAND, format!("Module variable '{}' cannot be reached from Risc V inline assembler", t), s, e))
+                        }
+                    },
+                    _ => {
+                        let name = match t.find('@') {
+                            Some( i ) => &t[i ..],
+                            _ => t.as_str()
+                        };
+                        Err(self.error_at(E_ASSEMBLER_UNDEFINED, format!("Undefined symbol '{}' in assembler code", name), s, e))
+                    }
+                }
+            },
+            RiscVNode::Plus( _ , _ , l , _ , r ) => Ok(self.evaluate(l)?.wrapping_add(self.evaluate(r)?)),
+            RiscVNode::Minus( _ , _ , l , _ , r ) => Ok(self.evaluate(l)?.wrapping_sub(self.evaluate(r)?)),
+            RiscVNode::Times( _ , _ , l , _ , r ) => Ok(self.evaluate(l)?.wrapping_mul(self.evaluate(r)?)),
+            RiscVNode::Div( _ , _ , l , _ , r ) |
+            RiscVNode::Modulo( _ , _ , l , _ , r ) => {
+                let left = self.evaluate(l)?;
+                let right = self.evaluate(r)?;
+                match ( right , node ) {
+                    ( 0 , _ ) => Err(self.error_at(E_DIVISION_BY_ZERO, String::from("Division by zero in assembler expression"), s, e)),
+                    ( _ , RiscVNode::Div( .. ) ) => Ok(left.wrapping_div(right)),
+                    _ => Ok(left.wrapping_rem(right))
+                }
+            },
+            RiscVNode::UnaryMinus( _ , _ , _ , x ) => Ok(self.evaluate(x)?.wrapping_neg()),
+            RiscVNode::UnaryPlus( _ , _ , _ , x ) => self.evaluate(x),
+            RiscVNode::Negate( _ , _ , _ , x ) => Ok(!self.evaluate(x)?),
+            _ => Err(self.error_at(E_ASSEMBLER_OPERAND, String::from("Expecting constant expression"), s, e))
+        }
+    }
+
+    /// Integer register named by node, used for base of memory operands
+    fn register(&self, node: &RiscVNode) -> Result<RegisterRiscV, Box<Diagnostic>> {
+        let ( s , e ) = node_span(node);
+
+        match node {
+            RiscVNode::Ident( _ , _ , t ) => {
+                match register_risc_v(t.as_str()) {
+                    Some( r @ RegisterRiscV::Integer( _ ) ) => Ok(r),
+                    _ => Err(self.error_at(E_ASSEMBLER_OPERAND, format!("Expecting integer register in memory operand, found '{}'", t), s, e))
+                }
+            },
+            _ => Err(self.error_at(E_ASSEMBLER_OPERAND, String::from("Expecting register in memory operand"), s, e))
+        }
+    }
+
+    /// Register, immediate, rounding mode or memory operand, names not defined in assembler text may be
+    /// Oberon variables, rounding modes, control and status registers or memory sets of 'FENCE'
+    fn resolve_operand(&self, node: &RiscVNode) -> Result<RiscVOperand, Box<Diagnostic>> {
+        let ( s , e ) = node_span(node);
+
+        match node {
+            RiscVNode::Ident( _ , _ , t ) if register_risc_v(t.as_str()).is_some() => {
+                match register_risc_v(t.as_str()) {
+                    Some( r ) => Ok(RiscVOperand::Register(r)),
+                    _ => Err(self.error_at(E_ASSEMBLER_OPERAND, format!("Unknown register '{}'", t), s, e))
+                }
+            },
+            RiscVNode::Ident( _ , _ , t ) if self.variable(t.as_str()).is_some() => {
+                /* Parameter or local variable by name alone is a memory operand relative to frame pointer */
+                match self.variable(t.as_str()) {
+                    Some( OberonVariable::Frame( offset , _ ) ) => Ok(RiscVOperand::Memory(FRAME_POINTER, *offset)),
+                    _ => Err(self.error_at(E_ASSEMBLER_OPERAND, format!("Module variable '{}' cannot be reached from Risc V inline assembler", t), s, e))
+                }
+            },
+            RiscVNode::Ident( _ , _ , t ) if !self.is_defined(t.as_str()) => {
+                match ( rounding_risc_v(t.as_str()) , csr_risc_v(t.as_str()) , fence_set_risc_v(t.as_str()) ) {
+                    ( Some( x ) , _ , _ ) => Ok(RiscVOperand::Rounding(x)),
+                    ( _ , Some( x ) , _ ) => Ok(RiscVOperand::Immediate(x as i64)),
+                    ( _ , _ , Some( x ) ) => Ok(RiscVOperand::Immediate(x as i64)),
+                    _ => Ok(RiscVOperand::Immediate(self.evaluate(node)?))
+                }
+            },
+            RiscVNode::Memory( _ , _ , offset , base ) => {
+                let offset = match &**offset {
+                    RiscVNode::None => 0,
+                    x => self.evaluate(x)?
+                };
+                Ok(RiscVOperand::Memory(self.register(base)?, offset))
+            },
+            _ => Ok(RiscVOperand::Immediate(self.evaluate(node)?))
+        }
+    }
+
+    /// Defines label or 'EQU' constant, each name can only be defined once
+    fn define(&mut self, name: &str, value: i64, is_label: bool, start: u32, end: u32) -> Result<(), Box<Diagnostic>> {
+        if register_risc_v(name).is_some() {
+            return Err(self.error_at(E_ASSEMBLER_DUPLICATE, format!("Register name '{}' cannot be redefined", name), start, end))
+        }
+        if self.labels.contains_key(name) || self.constants.contains_key(name) {
+            return Err(self.error_at(E_ASSEMBLER_DUPLICATE, format!("Symbol '{}' is already defined in assembler code", name), start, end))
+        }
+        match is_label {
+            true => self.labels.insert(String::from(name), value),
+            _ => self.constants.insert(String::from(name), value)
+        };
+        Ok(())
+    }
+
+    /// Name of local label '@name' inside the scope of the last label before it, other names are unchanged
+    fn qualified(&self, name: &str) -> Box<String> {
+        match name.starts_with('@') {
+            true => Box::new(format!("{}{}", self.scope, name)),
+            _ => Box::new(String::from(name))
+        }
+    }
+
+    /// Encode line at current location into code
+    fn emit(&mut self, line: &RiscVNode, code: &mut Vec<u8>) -> Result<(), Box<Diagnostic>> {
+        let ( s , e ) = node_span(line);
+
+        match line {
+            RiscVNode::Instruction( _ , _ , mnemonic, operands) => {
+                let alignment = match self.flags & CPU_C {
+                    0 => 4,
+                    _ => 2
+                };
+                if self.location % alignment != 0 {
+                    return Err(self.error_at(E_ASSEMBLER_OPERAND, format!("Instruction is not aligned to {} bytes, use 'ALIGN {}' after data", alignment, alignment), s, e))
+                }
+                let mut values = Vec::<RiscVOperand>::new();
+                for x in operands.iter() {
+                    values.push(self.resolve_operand(x)?)
+                }
+
+                /* Load or store of Oberon variable must transfer as many bytes as the variable has */
+                let sized = operands.iter().find_map(|x| self.sized_variable(x));
+                match ( sized , access_size(mnemonic.as_str()) ) {
+                    ( Some( ( name , size ) ) , Some( bytes ) ) if bytes != size => {
+                        return Err(self.error_at(E_ASSEMBLER_OPERAND, format!("Operands of '{}' do not match size of '{}', which is {} bytes", mnemonic, name, size), s, e))
+                    },
+                    _ => ()
+                }
+
+                let bytes = match encode_instruction_risc_v(mnemonic.clone(), Box::new(values), self.location, self.flags) {
+                    Ok( x ) => x,
+                    Err( x ) => return Err(self.error_at(x.code, (*x.message).clone(), s, e))
+                };
+                self.location += bytes.len() as i64;
+                code.extend_from_slice(&bytes);
+                Ok(())
+            },
+            RiscVNode::Data( _ , _ , size, items ) => {
+                let mut bytes = Vec::<u8>::new();
+                for item in items.iter() {
+                    match &**item {
+                        RiscVNode::String( _ , _ , t ) => {
+                            /* Strings are stored one character per byte, padded with zero to a whole item */
+                            for c in t.trim_matches('\'').chars() {
+                                bytes.push((c as u32 & 0xff) as u8)
+                            }
+                            while !bytes.len().is_multiple_of(*size as usize) {
+                                bytes.push(0)
+                            }
+                        },
+                        _ => {
+                            let value = self.evaluate(item)?;
+                            let bits = 8 * *size;
+                            if bits < 64 && ( value < -(1 << (bits - 1)) || value >= 1 << bits ) {
+                                let ( a , b ) = node_span(item);
+                                return Err(self.error_at(E_ASSEMBLER_OPERAND, format!("Value does not fit in {} bits", bits), a, b))
+                            }
+                            bytes.extend_from_slice(&value.to_le_bytes()[.. *size as usize])
+                        }
+                    }
+                }
+                self.location += bytes.len() as i64;
+                code.extend_from_slice(&bytes);
+                Ok(())
+            },
+            RiscVNode::Repeat( _ , _ , count, line ) => {
+                for _ in 0 .. self.count(count)? {
+                    self.emit(line, code)?
+                }
+                Ok(())
+            },
+            RiscVNode::Align( _ , _ , x ) => {
+                let alignment = self.evaluate(x)?;
+                if alignment <= 0 || alignment & (alignment - 1) != 0 {
+                    return Err(self.error_at(E_ASSEMBLER_OPERAND, String::from("Alignment must be a power of two"), s, e))
+                }
+                /* Padding is zero bytes up to a whole instruction, then 'NOP' or 'C.NOP' instructions */
+                let padding = (alignment - self.location.rem_euclid(alignment)) % alignment;
+                let mut filled = 0;
+                while filled < padding {
+                    match ( ( self.location + filled ) % 4 , padding - filled , self.flags & CPU_C ) {
+                        ( 0 , x , _ ) if x >= 4 => {
+                            code.extend_from_slice(&0x0000_0013u32.to_le_bytes());
+                            filled += 4
+                        },
+                        ( 0 | 2 , x , c ) if x >= 2 && c != 0 => {
+                            code.extend_from_slice(&0x0001u16.to_le_bytes());
+                            filled += 2
+                        },
+                        _ => {
+                            code.push(0);
+                            filled += 1
+                        }
+                    }
+                }
+                self.location += padding;
+                Ok(())
+            },
+            _ => Ok(())
+        }
+    }
+
+    /// Value of repeat count, it must not be negative
+    fn count(&self, node: &RiscVNode) -> Result<i64, Box<Diagnostic>> {
+        let ( s , e ) = node_span(node);
+
+        match self.evaluate(node)? {
+            x if !(0 ..= 0x10_0000).contains(&x) => Err(self.error_at(E_ASSEMBLER_OPERAND, format!("Count {} is out of range", x), s, e)),
+            x => Ok(x)
+        }
+    }
+
+    /// True when name is label or 'EQU' constant of assembler text
+    fn is_defined(&self, name: &str) -> bool {
+        self.labels.contains_key(name) || self.constants.contains_key(name) || self.pending.contains(name)
+    }
+
+    /// Oberon variable with name, names defined in assembler text hide variables
+    fn variable(&self, name: &str) -> Option<&OberonVariable> {
+        match self.is_defined(name) {
+            true => None,
+            _ => self.variables.get(name)
+        }
+    }
+
+    /// Name and size of Oberon variable that memory operand is exactly, like 'x' or 'x(fp)', when it has size of a register
+    fn sized_variable(&self, node: &RiscVNode) -> Option<(String, u32)> {
+        let name = match node {
+            RiscVNode::Ident( _ , _ , t ) => t,
+            RiscVNode::Memory( _ , _ , offset , base ) => {
+                match ( &**offset , &**base ) {
+                    ( RiscVNode::Ident( _ , _ , a ) , RiscVNode::Ident( _ , _ , b ) ) if register_risc_v(b.as_str()) == Some( FRAME_POINTER ) => a,
+                    _ => return None
+                }
+            },
+            _ => return None
+        };
+
+        match self.variable(name.as_str()) {
+            Some( OberonVariable::Frame( _ , size ) ) if [ 1, 2, 4, 8 ].contains(size) => Some( ( (**name).clone() , *size ) ),
+            _ => None
+        }
+    }
+
+    fn advance(&mut self) {
+        self.symbol = self.get_symbol();
+    }
+
+    /// Error covering the current symbol, positioned in the Oberon source file
+    fn error(&self, code: &'static str, message: String) -> Box<Diagnostic> {
+        Diagnostic::error(code, message, self.offset_position + self.start_pos, self.offset_position + self.index)
+    }
+
+    /// Error covering part of assembler text, positioned in the Oberon source file
+    fn error_at(&self, code: &'static str, message: String, start: u32, end: u32) -> Box<Diagnostic> {
+        Diagnostic::error(code, message, self.offset_position + start, self.offset_position + end)
+    }
+}
+
+impl InlineAssembler for AssemblerRiscV {
+    fn set_variables(&mut self, variables: HashMap<String, OberonVariable>) {
+        self.variables = variables
+    }
+
+    fn get_relocations(&self) -> &Vec<Relocation> {
+        &self.relocations
+    }
+
+    /// Entry point for inline assemble of block of code in RV64 instruction set
+    fn assemble(&mut self) -> Result<Box<Vec<u8>>, Box<Diagnostic>> {
+        let mut flags : CpuFlags = CPU_RV64I;
+        let mut selected = false;
+
+        self.advance();
+
+        // Flags handling for setting base instruction set and extensions
+        if let RiscVSymbols::LeftCurly( _ , _ ) = *self.symbol.clone()? {
+            self.advance();
+
+            loop {
+                match *self.symbol.clone()? {
+                    RiscVSymbols::Ident( _ , _ , t ) => {
+                        match t.as_str() {
+                            "SYSTEM" => {
+                                self.advance();
+
+                                match *self.symbol.clone()? {
+                                    RiscVSymbols::Period( _ , _ )  => self.advance(),
+                                    _ => return Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Identifier missing in assembler code")))
+                                }
+
+                                if let RiscVSymbols::Ident( _ , _ , t ) = *self.symbol.clone()? {
+                                    match t.as_str() {
+                                        "RV64I" => (),
+                                        "RV64GC" => flags |= CPU_RV64GC,
+                                        "M" => flags |= CPU_M,
+                                        "A" => flags |= CPU_A,
+                                        "F" => flags |= CPU_F,
+                                        "D" => flags |= CPU_D,
+                                        "C" => flags |= CPU_C,
+                                        _ => return Err(self.error(E_ASSEMBLER_CPU_FLAG, String::from("Unknown CPU type flag in assembler code")))
+                                    }
+                                    selected = true;
+                                    self.advance();
+                                }
+
+                                match *self.symbol.clone()? {
+                                    RiscVSymbols::RightCurly( _ , _ ) => {
+                                        self.advance();
+                                        break;
+                                    },
+                                    RiscVSymbols::Comma( _ , _ ) => {
+                                        self.advance()
+                                    },
+                                    _ => return Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Target identifier expected in assembler code")))
+                                }
+                            },
+                            _ => return Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Unsupported target identifier in assembler code")))
+                        }
+                    },
+                    RiscVSymbols::RightCurly( _ , _ ) => {
+                        self.advance();
+                        break
+                    }
+                    _ => return Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Missing target identifier in assembler code")))
+                }
+            }
+        }
+
+        /* Code block without CPU flags may use the extensions of RV64GC, which general purpose processors have */
+        self.flags = match selected {
+            false => CPU_RV64GC,
+            _ => flags
+        };
+
+        let mut lines = Vec::<Box<RiscVNode>>::new();
+
+        // High level instructions in assembler language
+        loop {
+            match *self.symbol.clone()? {
+                RiscVSymbols::NewLine( _ , _ ) => {
+                    self.advance();
+                },
+                RiscVSymbols::Label(s, e, t) => {
+                    self.advance();
+                    let name = self.qualified(t.as_str());
+                    match *self.symbol.clone()? {
+                        RiscVSymbols::Ident( _ , _ , x ) if x.as_str() == "EQU" => {
+                            self.advance();
+                            let node = self.expression()?;
+                            let value = self.evaluate(&node)?;
+                            self.define(name.as_str(), value, false, s, e)?
+                        },
+                        _ => {
+                            self.define(name.as_str(), 0, true, s, e)?;
+                            if !t.starts_with('@') {
+                                self.scope = t
+                            }
+                            lines.push(Box::new(RiscVNode::Label(s, e, name)))
+                        }
+                    }
+                },
+                RiscVSymbols::Ident(s, e, t) => {
+                    match t.as_str() {
+                        "EQU" => {
+                            return Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Missing name before 'EQU' in assembler code")))
+                        },
+                        _ => {
+                            self.advance();
+                            match *self.symbol.clone()? {
+                                RiscVSymbols::Ident( _ , _ , x ) if x.as_str() == "EQU" => {
+                                    self.advance();
+                                    let node = self.expression()?;
+                                    let value = self.evaluate(&node)?;
+                                    let name = self.qualified(t.as_str());
+                                    self.define(name.as_str(), value, false, s, e)?
+                                },
+                                _ => lines.push(self.statement(s, e, t)?)
+                            }
+                        }
+                    }
+                },
+                RiscVSymbols::EndOfFile( _ ) => break,
+                _ => return Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Invalid symbol in inline assembler")))
+            }
+        }
+
+        /* Compressed instructions and pseudo instructions change size with distance to labels, so passes are
+           repeated until no label moves */
+        let mut code = Vec::<u8>::new();
+        let mut passes = 0;
+
+        for line in lines.iter() {
+            if let RiscVNode::Label( _ , _ , t ) = &**line {
+                self.pending.insert((**t).clone());
+            }
+        }
+
+        loop {
+            let mut changed = false;
+            code.clear();
+            self.relocations.clear();
+            self.location = 0;
+
+            for line in lines.iter() {
+                match &**line {
+                    RiscVNode::Label( _ , _ , t ) => {
+                        self.pending.remove(t.as_str());
+                        match self.labels.insert((**t).clone(), self.location) {
+                            Some( x ) if x == self.location && passes > 0 => (),
+                            _ => changed = true
+                        }
+                    },
+                    x => self.emit(x, &mut code)?
+                }
+            }
+
+            passes += 1;
+            match ( changed , passes ) {
+                ( false , _ ) => break,
+                ( _ , x ) if x > 8 => return Err(self.error(E_ASSEMBLER_SYNTAX, String::from("Labels in assembler code do not settle"))),
+                _ => ()
+            }
+        }
+
+        Ok(Box::new(code))
+    }
+}
+
+// Unittests for inline assembler for Risc V module
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use crate::riscv_assembler::{ AssemblerRiscV, AssemblerRiscVMethods, RiscVSymbols };
+    use crate::diagnostics::{ E_ASSEMBLER_SYNTAX, E_ASSEMBLER_UNDEFINED, E_ASSEMBLER_DUPLICATE, E_ASSEMBLER_OPERAND, E_ASSEMBLER_INSTRUCTION, E_ASSEMBLER_CPU_FLAG };
+    use crate::inline_assembler::{ InlineAssembler, OberonVariable };
+
+    /// Machine code as bytes, or code of first error
+    fn assemble(text: &str) -> Result<Vec<u8>, &'static str> {
+        let mut assembler = AssemblerRiscV::new(text.chars().collect(), 0);
+        match assembler.assemble() {
+            Ok( x ) => Ok( *x ),
+            Err( e ) => Err( e.code )
+        }
+    }
+
+    /// Machine code of code block without compressed instructions as instruction words
+    fn words(text: &str) -> Result<Vec<u32>, &'static str> {
+        assemble(format!("{{ SYSTEM.RV64I, SYSTEM.M, SYSTEM.A, SYSTEM.D }}\n{}", text).as_str())
+            .map(|x| x.chunks(4).map(|w| u32::from_le_bytes([ w[0], w[1], w[2], w[3] ])).collect())
+    }
+
+    #[test]
+    fn test_lexer_symbols() {
+        let mut assembler = AssemblerRiscV::new("ld a0, -8(sp) # comment\r\n@loop: fadd.d 1FH".chars().collect(), 0);
+        let mut symbols = Vec::<RiscVSymbols>::new();
+        loop {
+            match *assembler.get_symbol().unwrap() {
+                RiscVSymbols::EndOfFile( _ ) => break,
+                x => symbols.push(x)
+            }
+        }
+
+        assert_eq!(symbols, vec![
+            RiscVSymbols::Ident(0, 2, Box::new(String::from("ld"))),
+            RiscVSymbols::Ident(3, 5, Box::new(String::from("a0"))),
+            RiscVSymbols::Comma(5, 6),
+            RiscVSymbols::Minus(7, 8),
+            RiscVSymbols::Number(8, 9, 8),
+            RiscVSymbols::LeftParen(9, 10),
+            RiscVSymbols::Ident(10, 12, Box::new(String::from("sp"))),
+            RiscVSymbols::RightParen(12, 13),
+            RiscVSymbols::NewLine(23, 25),
+            RiscVSymbols::Label(25, 31, Box::new(String::from("@loop"))),
+            RiscVSymbols::Ident(32, 36, Box::new(String::from("fadd"))),
+            RiscVSymbols::Period(36, 37),
+            RiscVSymbols::Ident(37, 38, Box::new(String::from("d"))),
+            RiscVSymbols::Number(39, 42, 31)
+        ])
+    }
+
+    #[test]
+    fn test_assembler_risc_v_instructions() {
+        let source = "addi a0, a1, 16\nLD A0, -8(SP)\nsd ra, 8(sp)\nMUL a0, a1, a2\nfadd.d fa0, fa1, fa2\nlr.w.aq a0, (a1)\nCSRR a0, mhartid\nFENCE rw, w\nret";
+        assert_eq!(words(source), Ok( vec![ 0x0105_8513, 0xff81_3503, 0x0011_3423, 0x02c5_8533, 0x02c5_f553, 0x1405_a52f, 0xf140_2573, 0x0310_000f, 0x0000_8067 ] ));
+
+        /* Without CPU flags extension C is enabled and instructions are compressed where possible */
+        assert_eq!(assemble("addi a0, a0, 1\nmv a0, a1\nret\nadd a0, a1, a2"), Ok( vec![ 0x05, 0x05, 0x2e, 0x85, 0x82, 0x80, 0x33, 0x85, 0xc5, 0x00 ] ))
+    }
+
+    #[test]
+    fn test_assembler_risc_v_labels_and_branches() {
+        let source = "loop: addi a0, a0, -1\nbnez a0, loop\nbeq a1, a2, done\nj loop\ndone: ret";
+        assert_eq!(words(source), Ok( vec![ 0xfff5_0513, 0xfe05_1ee3, 0x00c5_8463, 0xff5f_f06f, 0x0000_8067 ] ));
+
+        /* Local labels belong to the label before them, address is loaded relative to instruction */
+        let source = "first:\n@again: bnez a0, @again\nsecond:\n@again: j @again\nla a1, value\nvalue: DD 1";
+        assert_eq!(words(source), Ok( vec![ 0x0005_1063, 0x0000_006f, 0x0000_0597, 0x0085_8593, 0x0000_0001 ] ));
+
+        /* Forward branch grows from compressed form when target is out of its reach */
+        let mut code = assemble("beqz a0, far\nTIMES 100 add a0, a1, a2\nfar: ret").unwrap();
+        assert_eq!(code.len(), 4 + 400 + 2);
+        code.truncate(4);
+        assert_eq!(code, vec![ 0x63, 0x0a, 0x05, 0x18 ])
+    }
+
+    #[test]
+    fn test_assembler_risc_v_expressions_and_data() {
+        let source = "size EQU 4 * 2\naddi a0, a1, size + 1\nli a2, $\nDB 1, 'A'\nALIGN 4\nDD 12345678H, 0x10\nTIMES 2 nop";
+        assert_eq!(words(source), Ok( vec![ 0x0095_8513, 0x0040_0613, 0x0000_4101, 0x1234_5678, 0x0000_0010, 0x0000_0013, 0x0000_0013 ] ));
+
+        assert_eq!(words("nop\nALIGN 16\nret"), Ok( vec![ 0x0000_0013, 0x0000_0013, 0x0000_0013, 0x0000_0013, 0x0000_8067 ] ))
+    }
+
+    #[test]
+    fn test_assembler_risc_v_cpu_flags() {
+        assert_eq!(assemble("{ SYSTEM.RV64I }\nmul a0, a1, a2"), Err( E_ASSEMBLER_CPU_FLAG ));
+        assert_eq!(assemble("{ SYSTEM.RV64I, SYSTEM.M }\nmul a0, a1, a2"), Ok( vec![ 0x33, 0x85, 0xc5, 0x02 ] ));
+        assert_eq!(assemble("{ SYSTEM.RV64I, SYSTEM.F }\nfadd.d fa0, fa1, fa2"), Err( E_ASSEMBLER_CPU_FLAG ));
+        assert_eq!(assemble("{ SYSTEM.RV64GC }\nli a0, 1"), Ok( vec![ 0x05, 0x45 ] ));
+        assert_eq!(assemble("{ SYSTEM.SSE }\nnop"), Err( E_ASSEMBLER_CPU_FLAG ));
+        assert_eq!(assemble("{ SYSTEM.M \nnop"), Err( E_ASSEMBLER_SYNTAX ))
+    }
+
+    #[test]
+    fn test_assembler_risc_v_errors() {
+        let cases = [
+            ( "ld a0, 8(sp" , E_ASSEMBLER_SYNTAX ),
+            ( "addi a0, a1," , E_ASSEMBLER_SYNTAX ),
+            ( "fadd.", E_ASSEMBLER_SYNTAX ),
+            ( "li a0, 0x" , E_ASSEMBLER_SYNTAX ),
+            ( "ld a0, 8(16)" , E_ASSEMBLER_SYNTAX ),
+            ( "nop )" , E_ASSEMBLER_SYNTAX ),
+            ( "j nowhere" , E_ASSEMBLER_UNDEFINED ),
+            ( "here: nop\nhere: nop" , E_ASSEMBLER_DUPLICATE ),
+            ( "a0: nop" , E_ASSEMBLER_DUPLICATE ),
+            ( "DB 1\nnop" , E_ASSEMBLER_OPERAND ),
+            ( "addi a0, a1, a2 + 1" , E_ASSEMBLER_OPERAND ),
+            ( "ld a0, 8(fa0)" , E_ASSEMBLER_OPERAND ),
+            ( "addi a0, a1, 4096" , E_ASSEMBLER_OPERAND ),
+            ( "ALIGN 3" , E_ASSEMBLER_OPERAND ),
+            ( "blx a0" , E_ASSEMBLER_INSTRUCTION )
+        ];
+
+        for ( text , code ) in cases.iter() {
+            assert_eq!(assemble(text), Err( *code ), "{}", text)
+        }
+
+        let mut assembler = AssemblerRiscV::new("nop\naddi a0, a1, 4096".chars().collect(), 100);
+        match assembler.assemble() {
+            Err( e ) => {
+                assert_eq!(e.code, E_ASSEMBLER_OPERAND);
+                assert_eq!(e.span.start, 104);
+                assert_eq!(e.span.end, 121)
+            },
+            _ => assert!(false)
+        }
+    }
+
+    fn with_variables(text: &str) -> AssemblerRiscV {
+        let mut assembler = AssemblerRiscV::new(text.chars().collect(), 0);
+        assembler.set_variables(HashMap::from([
+            ( String::from("a"), OberonVariable::Frame(16, 8) ),
+            ( String::from("x"), OberonVariable::Frame(-8, 8) ),
+            ( String::from("y"), OberonVariable::Frame(-12, 4) ),
+            ( String::from("g"), OberonVariable::Global(String::from("Test.g"), 4) )
+        ]));
+        assembler
+    }
+
+    #[test]
+    fn test_assembler_risc_v_oberon_variables() {
+        /* Name alone is memory operand relative to frame pointer, in expressions it is the offset */
+        let mut assembler = with_variables("{ SYSTEM.RV64I }\nld a0, a\nsd a0, x\nlw a1, y\nld a2, a(fp)\naddi a3, s0, a + 0");
+        let pattern = Box::new(vec![
+            0x03, 0x35, 0x04, 0x01,
+            0x23, 0x3c, 0xa4, 0xfe,
+            0x83, 0x25, 0x44, 0xff,
+            0x03, 0x36, 0x04, 0x01,
+            0x93, 0x06, 0x04, 0x01
+        ]);
+        assert_eq!(assembler.assemble(), Ok( pattern ));
+
+        let mut assembler = with_variables("lw a0, a");
+        match assembler.assemble() {
+            Err( e ) => {
+                assert_eq!(e.code, E_ASSEMBLER_OPERAND);
+                assert_eq!(*e.message, String::from("Operands of 'LW' do not match size of 'a', which is 8 bytes"))
+            },
+            _ => assert!(false)
+        }
+
+        let cases = [ ( "lw a0, g" , E_ASSEMBLER_OPERAND ), ( "addi a0, a1, g" , E_ASSEMBLER_OPERAND ), ( "ld a0, z" , E_ASSEMBLER_UNDEFINED ) ];
+        for ( text , code ) in cases.iter() {
+            let mut assembler = with_variables(text);
+            match assembler.assemble() {
+                Err( e ) => assert_eq!(e.code, *code, "{}", text),
+                _ => assert!(false, "{}", text)
+            }
+        }
+
+        /* Label of assembler text hides Oberon variable with same name */
+        let mut assembler = with_variables("{ SYSTEM.RV64I }\nx: j x");
+        assert_eq!(assembler.assemble(), Ok( Box::new(vec![ 0x6f, 0x00, 0x00, 0x00 ]) ))
+    }
+}
@@ -1,15 +1,1474 @@
-
 // ActiveOberon Compiler, a native ARM v8 & X86-64 compiler & Risc V / linker / builder utility.
 // Written by Richard Magnor Stenbro. Licensed under GPL v3
 // Instruction set for Risc V module for compiling and linking of projects written in ActiveOberon language
 
-type CpuFlags = u32;
+use crate::diagnostics::{Diagnostic, DiagnosticMethods, E_ASSEMBLER_CPU_FLAG, E_ASSEMBLER_OPERAND, E_ASSEMBLER_INSTRUCTION};
+
+pub const CPU_RV64I : u32 = 0;
+pub const CPU_M : u32 = 1;          /* Integer multiplication and division */
+pub const CPU_A : u32 = 2;          /* Atomic memory operations */
+pub const CPU_F : u32 = 4;          /* Single precision floating point */
+pub const CPU_D : u32 = 8;          /* Double precision floating point */
+pub const CPU_C : u32 = 16;         /* Compressed 16 bit instructions */
+pub const CPU_RV64GC : u32 = CPU_M | CPU_A | CPU_F | CPU_D | CPU_C;
+
+pub type CpuFlags = u32;
+
+/// Registers of RV64, integer registers x0 - x31 and floating point registers f0 - f31
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum RegisterRiscV {
+    Integer(u8),    /* x0 is hardwired zero */
+    Float(u8)
+}
+
+pub trait RegisterRiscVMethods {
+    fn number(&self) -> u8;
+}
+
+impl RegisterRiscVMethods for RegisterRiscV {
+    /// Register number as encoded in instruction word
+    fn number(&self) -> u8 {
+        match self {
+            RegisterRiscV::Integer( n ) |
+            RegisterRiscV::Float( n ) => *n
+        }
+    }
+}
+
+/// Operand of RV64 instruction after expressions are evaluated, branch and jump targets are absolute addresses
+#[derive(Clone, PartialEq, Debug)]
+pub enum RiscVOperand {
+    Register(RegisterRiscV),
+    Immediate(i64),
+    Memory(RegisterRiscV, i64),     /* Base register and offset, written as offset(base) */
+    Rounding(u8)                    /* Rounding mode of floating point instruction */
+}
+
+const INTEGER_NAMES : [&str; 32] = [
+    "zero", "ra", "sp", "gp", "tp", "t0", "t1", "t2", "s0", "s1", "a0", "a1", "a2", "a3", "a4", "a5",
+    "a6", "a7", "s2", "s3", "s4", "s5", "s6", "s7", "s8", "s9", "s10", "s11", "t3", "t4", "t5", "t6"
+];
+
+const FLOAT_NAMES : [&str; 32] = [
+    "ft0", "ft1", "ft2", "ft3", "ft4", "ft5", "ft6", "ft7", "fs0", "fs1", "fa0", "fa1", "fa2", "fa3", "fa4", "fa5",
+    "fa6", "fa7", "fs2", "fs3", "fs4", "fs5", "fs6", "fs7", "fs8", "fs9", "fs10", "fs11", "ft8", "ft9", "ft10", "ft11"
+];
+
+const ROUNDING_NAMES : [&str; 8] = [ "rne", "rtz", "rdn", "rup", "rmm", "", "", "dyn" ];
+
+const CSR_NAMES : [( &str , u16 ); 28] = [
+    ( "fflags", 0x001 ), ( "frm", 0x002 ), ( "fcsr", 0x003 ),
+    ( "cycle", 0xC00 ), ( "time", 0xC01 ), ( "instret", 0xC02 ),
+    ( "sstatus", 0x100 ), ( "sie", 0x104 ), ( "stvec", 0x105 ), ( "sscratch", 0x140 ), ( "sepc", 0x141 ),
+    ( "scause", 0x142 ), ( "stval", 0x143 ), ( "sip", 0x144 ), ( "satp", 0x180 ),
+    ( "mstatus", 0x300 ), ( "misa", 0x301 ), ( "medeleg", 0x302 ), ( "mideleg", 0x303 ), ( "mie", 0x304 ), ( "mtvec", 0x305 ),
+    ( "mscratch", 0x340 ), ( "mepc", 0x341 ), ( "mcause", 0x342 ), ( "mtval", 0x343 ), ( "mip", 0x344 ),
+    ( "mhartid", 0xF14 ), ( "mvendorid", 0xF11 )
+];
+
+/// Register with given ABI name or x0 - x31 and f0 - f31, upper or lower case, or none when name is not a register
+pub fn register_risc_v(name: &str) -> Option<RegisterRiscV> {
+    let lower = name.to_lowercase();
+
+    if lower == "fp" {
+        return Some( RegisterRiscV::Integer(8) )
+    }
+    if let Some( n ) = INTEGER_NAMES.iter().position(|x| *x == lower) {
+        return Some( RegisterRiscV::Integer(n as u8) )
+    }
+    if let Some( n ) = FLOAT_NAMES.iter().position(|x| *x == lower) {
+        return Some( RegisterRiscV::Float(n as u8) )
+    }
+
+    let ( float , digits ) = match ( lower.strip_prefix('x') , lower.strip_prefix('f') ) {
+        ( Some( x ) , _ ) => ( false , x ),
+        ( _ , Some( x ) ) => ( true , x ),
+        _ => return None
+    };
+    match digits.parse::<u8>() {
+        Ok( n ) if n < 32 && digits == n.to_string() => {
+            match float {
+                true => Some( RegisterRiscV::Float(n) ),
+                _ => Some( RegisterRiscV::Integer(n) )
+            }
+        },
+        _ => None
+    }
+}
+
+/// ABI name of register
+pub fn register_name_risc_v(register: &RegisterRiscV) -> String {
+    match register {
+        RegisterRiscV::Integer( n ) => String::from(INTEGER_NAMES[(*n & 31) as usize]),
+        RegisterRiscV::Float( n ) => String::from(FLOAT_NAMES[(*n & 31) as usize])
+    }
+}
+
+/// Rounding mode of floating point instruction like 'rtz', or none when name is not a rounding mode
+pub fn rounding_risc_v(name: &str) -> Option<u8> {
+    let lower = name.to_lowercase();
+
+    ROUNDING_NAMES.iter().position(|x| !x.is_empty() && *x == lower).map(|n| n as u8)
+}
+
+/// Number of control and status register with given name, or none when name is not a known register
+pub fn csr_risc_v(name: &str) -> Option<u16> {
+    let lower = name.to_lowercase();
+
+    CSR_NAMES.iter().find(|x| x.0 == lower).map(|( _ , n )| *n)
+}
+
+/// Predecessor or successor set of FENCE like 'rw' as bits i, o, r and w, or none when name is not a set
+pub fn fence_set_risc_v(name: &str) -> Option<u8> {
+    let lower = name.to_lowercase();
+    let mut set = 0;
+
+    for c in lower.chars() {
+        let bit = match c {
+            'i' => 8,
+            'o' => 4,
+            'r' => 2,
+            'w' => 1,
+            _ => return None
+        };
+        if set & bit != 0 {
+            return None
+        }
+        set |= bit
+    }
+
+    match set {
+        0 => None,
+        x => Some( x )
+    }
+}
+
+/// Operand kinds of instruction forms, each kind knows where its bits are placed in the instruction word
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum OperandKind {
+    Rd, Rs1, Rs2,                   /* Integer register in bits 7, 15 and 20 */
+    Fd, Fs1, Fs2, Fs3,              /* Floating point register in bits 7, 15, 20 and 27 */
+    Rm,                             /* Rounding mode in bits 12 - 14, dynamic rounding when left out */
+    I12, B13, J21, U20,             /* Immediates of I, B, J and U type instructions */
+    Shamt6, Shamt5, Csr12, Zimm5,   /* Shift amounts, control and status register and its immediate source */
+    Pred, Succ,                     /* Memory access sets of FENCE */
+    MemI, MemS, MemA,               /* offset(base) of loads and stores, atomic instructions have no offset */
+    CRd, CRdNoSp, CRs2, CRs2Any,    /* Compressed register in bits 7 or 2, not zero register unless 'Any' */
+    CP7, CP2, CFd, CFs2, CFP2,      /* Compressed x8 - x15 in bits 7 or 2 and floating point registers */
+    Sp,                             /* Stack pointer that is implied by compressed instruction */
+    CImm6, CNzImm6, CShamt, CLui,   /* Compressed immediates in bit 12 and bits 2 - 6 */
+    C16sp, C4spn, CB9, CJ12,        /* Compressed stack adjustment, branch and jump */
+    CMemW, CMemD,                   /* offset(x8 - x15) of compressed loads and stores */
+    CMemWsp, CMemDsp,               /* offset(sp) of compressed loads */
+    CStoreWsp, CStoreDsp            /* offset(sp) of compressed stores */
+}
+
+/// Operand of instruction that compressed instruction is a short form of
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Expansion {
+    Op(u8),         /* Operand of compressed instruction */
+    Zero,           /* Register x0 */
+    Ra,             /* Register x1 */
+    ImmZero         /* Immediate 0 */
+}
+
+/// One encoding of a mnemonic, shared by encoder and decoder
+pub struct InstructionForm {
+    pub mnemonic: &'static str,
+    pub operands: &'static [OperandKind],
+    pub opcode: u32,                        /* Instruction word with all operand fields zero */
+    pub mask: u32,                          /* Bits of instruction word that are given by opcode */
+    pub base: &'static str,                 /* Instruction that compressed form is a short form of, empty when not compressed */
+    pub expansion: &'static [Expansion],    /* Operands of base instruction */
+    pub flags: CpuFlags                     /* Extensions required */
+}
+
+const fn form(mnemonic: &'static str, operands: &'static [OperandKind], opcode: u32, mask: u32, flags: CpuFlags) -> InstructionForm {
+    InstructionForm { mnemonic, operands, opcode, mask, base: "", expansion: &[], flags }
+}
+
+const fn compressed(mnemonic: &'static str, operands: &'static [OperandKind], opcode: u32, mask: u32, base: &'static str, expansion: &'static [Expansion], flags: CpuFlags) -> InstructionForm {
+    InstructionForm { mnemonic, operands, opcode, mask, base, expansion, flags }
+}
+
+use OperandKind::*;
+use Expansion::*;
+
+/// Supported instruction forms. First matching form is used, and compressed forms are tried in order when instruction is compressed.
+pub static INSTRUCTIONS_RISC_V : &[InstructionForm] = &[
+    form("LUI", &[ Rd, U20 ], 0x0000_0037, 0x0000_007f, CPU_RV64I),
+    form("AUIPC", &[ Rd, U20 ], 0x0000_0017, 0x0000_007f, CPU_RV64I),
+    form("JAL", &[ Rd, J21 ], 0x0000_006f, 0x0000_007f, CPU_RV64I),
+    form("JALR", &[ Rd, MemI ], 0x0000_0067, 0x0000_707f, CPU_RV64I),
+    form("JALR", &[ Rd, Rs1, I12 ], 0x0000_0067, 0x0000_707f, CPU_RV64I),
+
+    form("BEQ", &[ Rs1, Rs2, B13 ], 0x0000_0063, 0x0000_707f, CPU_RV64I),
+    form("BNE", &[ Rs1, Rs2, B13 ], 0x0000_1063, 0x0000_707f, CPU_RV64I),
+    form("BLT", &[ Rs1, Rs2, B13 ], 0x0000_4063, 0x0000_707f, CPU_RV64I),
+    form("BGE", &[ Rs1, Rs2, B13 ], 0x0000_5063, 0x0000_707f, CPU_RV64I),
+    form("BLTU", &[ Rs1, Rs2, B13 ], 0x0000_6063, 0x0000_707f, CPU_RV64I),
+    form("BGEU", &[ Rs1, Rs2, B13 ], 0x0000_7063, 0x0000_707f, CPU_RV64I),
+
+    form("LB", &[ Rd, MemI ], 0x0000_0003, 0x0000_707f, CPU_RV64I),
+    form("LH", &[ Rd, MemI ], 0x0000_1003, 0x0000_707f, CPU_RV64I),
+    form("LW", &[ Rd, MemI ], 0x0000_2003, 0x0000_707f, CPU_RV64I),
+    form("LD", &[ Rd, MemI ], 0x0000_3003, 0x0000_707f, CPU_RV64I),
+    form("LBU", &[ Rd, MemI ], 0x0000_4003, 0x0000_707f, CPU_RV64I),
+    form("LHU", &[ Rd, MemI ], 0x0000_5003, 0x0000_707f, CPU_RV64I),
+    form("LWU", &[ Rd, MemI ], 0x0000_6003, 0x0000_707f, CPU_RV64I),
+    form("SB", &[ Rs2, MemS ], 0x0000_0023, 0x0000_707f, CPU_RV64I),
+    form("SH", &[ Rs2, MemS ], 0x0000_1023, 0x0000_707f, CPU_RV64I),
+    form("SW", &[ Rs2, MemS ], 0x0000_2023, 0x0000_707f, CPU_RV64I),
+    form("SD", &[ Rs2, MemS ], 0x0000_3023, 0x0000_707f, CPU_RV64I),
+
+    form("ADDI", &[ Rd, Rs1, I12 ], 0x0000_0013, 0x0000_707f, CPU_RV64I),
+    form("SLTI", &[ Rd, Rs1, I12 ], 0x0000_2013, 0x0000_707f, CPU_RV64I),
+    form("SLTIU", &[ Rd, Rs1, I12 ], 0x0000_3013, 0x0000_707f, CPU_RV64I),
+    form("XORI", &[ Rd, Rs1, I12 ], 0x0000_4013, 0x0000_707f, CPU_RV64I),
+    form("ORI", &[ Rd, Rs1, I12 ], 0x0000_6013, 0x0000_707f, CPU_RV64I),
+    form("ANDI", &[ Rd, Rs1, I12 ], 0x0000_7013, 0x0000_707f, CPU_RV64I),
+    form("SLLI", &[ Rd, Rs1, Shamt6 ], 0x0000_1013, 0xfc00_707f, CPU_RV64I),
+    form("SRLI", &[ Rd, Rs1, Shamt6 ], 0x0000_5013, 0xfc00_707f, CPU_RV64I),
+    form("SRAI", &[ Rd, Rs1, Shamt6 ], 0x4000_5013, 0xfc00_707f, CPU_RV64I),
+
+    form("ADD", &[ Rd, Rs1, Rs2 ], 0x0000_0033, 0xfe00_707f, CPU_RV64I),
+    form("SUB", &[ Rd, Rs1, Rs2 ], 0x4000_0033, 0xfe00_707f, CPU_RV64I),
+    form("SLL", &[ Rd, Rs1, Rs2 ], 0x0000_1033, 0xfe00_707f, CPU_RV64I),
+    form("SLT", &[ Rd, Rs1, Rs2 ], 0x0000_2033, 0xfe00_707f, CPU_RV64I),
+    form("SLTU", &[ Rd, Rs1, Rs2 ], 0x0000_3033, 0xfe00_707f, CPU_RV64I),
+    form("XOR", &[ Rd, Rs1, Rs2 ], 0x0000_4033, 0xfe00_707f, CPU_RV64I),
+    form("SRL", &[ Rd, Rs1, Rs2 ], 0x0000_5033, 0xfe00_707f, CPU_RV64I),
+    form("SRA", &[ Rd, Rs1, Rs2 ], 0x4000_5033, 0xfe00_707f, CPU_RV64I),
+    form("OR", &[ Rd, Rs1, Rs2 ], 0x0000_6033, 0xfe00_707f, CPU_RV64I),
+    form("AND", &[ Rd, Rs1, Rs2 ], 0x0000_7033, 0xfe00_707f, CPU_RV64I),
+
+    form("ADDIW", &[ Rd, Rs1, I12 ], 0x0000_001b, 0x0000_707f, CPU_RV64I),
+    form("SLLIW", &[ Rd, Rs1, Shamt5 ], 0x0000_101b, 0xfe00_707f, CPU_RV64I),
+    form("SRLIW", &[ Rd, Rs1, Shamt5 ], 0x0000_501b, 0xfe00_707f, CPU_RV64I),
+    form("SRAIW", &[ Rd, Rs1, Shamt5 ], 0x4000_501b, 0xfe00_707f, CPU_RV64I),
+    form("ADDW", &[ Rd, Rs1, Rs2 ], 0x0000_003b, 0xfe00_707f, CPU_RV64I),
+    form("SUBW", &[ Rd, Rs1, Rs2 ], 0x4000_003b, 0xfe00_707f, CPU_RV64I),
+    form("SLLW", &[ Rd, Rs1, Rs2 ], 0x0000_103b, 0xfe00_707f, CPU_RV64I),
+    form("SRLW", &[ Rd, Rs1, Rs2 ], 0x0000_503b, 0xfe00_707f, CPU_RV64I),
+    form("SRAW", &[ Rd, Rs1, Rs2 ], 0x4000_503b, 0xfe00_707f, CPU_RV64I),
+
+    form("FENCE", &[ Pred, Succ ], 0x0000_000f, 0xf00f_ffff, CPU_RV64I),
+    form("FENCE.I", &[  ], 0x0000_100f, 0xffff_ffff, CPU_RV64I),
+    form("ECALL", &[  ], 0x0000_0073, 0xffff_ffff, CPU_RV64I),
+    form("EBREAK", &[  ], 0x0010_0073, 0xffff_ffff, CPU_RV64I),
+    form("MRET", &[  ], 0x3020_0073, 0xffff_ffff, CPU_RV64I),
+    form("SRET", &[  ], 0x1020_0073, 0xffff_ffff, CPU_RV64I),
+    form("WFI", &[  ], 0x1050_0073, 0xffff_ffff, CPU_RV64I),
+    form("CSRRW", &[ Rd, Csr12, Rs1 ], 0x0000_1073, 0x0000_707f, CPU_RV64I),
+    form("CSRRS", &[ Rd, Csr12, Rs1 ], 0x0000_2073, 0x0000_707f, CPU_RV64I),
+    form("CSRRC", &[ Rd, Csr12, Rs1 ], 0x0000_3073, 0x0000_707f, CPU_RV64I),
+    form("CSRRWI", &[ Rd, Csr12, Zimm5 ], 0x0000_5073, 0x0000_707f, CPU_RV64I),
+    form("CSRRSI", &[ Rd, Csr12, Zimm5 ], 0x0000_6073, 0x0000_707f, CPU_RV64I),
+    form("CSRRCI", &[ Rd, Csr12, Zimm5 ], 0x0000_7073, 0x0000_707f, CPU_RV64I),
+
+    form("MUL", &[ Rd, Rs1, Rs2 ], 0x0200_0033, 0xfe00_707f, CPU_M),
+    form("MULH", &[ Rd, Rs1, Rs2 ], 0x0200_1033, 0xfe00_707f, CPU_M),
+    form("MULHSU", &[ Rd, Rs1, Rs2 ], 0x0200_2033, 0xfe00_707f, CPU_M),
+    form("MULHU", &[ Rd, Rs1, Rs2 ], 0x0200_3033, 0xfe00_707f, CPU_M),
+    form("DIV", &[ Rd, Rs1, Rs2 ], 0x0200_4033, 0xfe00_707f, CPU_M),
+    form("DIVU", &[ Rd, Rs1, Rs2 ], 0x0200_5033, 0xfe00_707f, CPU_M),
+    form("REM", &[ Rd, Rs1, Rs2 ], 0x0200_6033, 0xfe00_707f, CPU_M),
+    form("REMU", &[ Rd, Rs1, Rs2 ], 0x0200_7033, 0xfe00_707f, CPU_M),
+    form("MULW", &[ Rd, Rs1, Rs2 ], 0x0200_003b, 0xfe00_707f, CPU_M),
+    form("DIVW", &[ Rd, Rs1, Rs2 ], 0x0200_403b, 0xfe00_707f, CPU_M),
+    form("DIVUW", &[ Rd, Rs1, Rs2 ], 0x0200_503b, 0xfe00_707f, CPU_M),
+    form("REMW", &[ Rd, Rs1, Rs2 ], 0x0200_603b, 0xfe00_707f, CPU_M),
+    form("REMUW", &[ Rd, Rs1, Rs2 ], 0x0200_703b, 0xfe00_707f, CPU_M),
+
+    form("LR.W", &[ Rd, MemA ], 0x1000_202f, 0xfff0_707f, CPU_A),
+    form("LR.W.AQ", &[ Rd, MemA ], 0x1400_202f, 0xfff0_707f, CPU_A),
+    form("LR.W.RL", &[ Rd, MemA ], 0x1200_202f, 0xfff0_707f, CPU_A),
+    form("LR.W.AQRL", &[ Rd, MemA ], 0x1600_202f, 0xfff0_707f, CPU_A),
+    form("SC.W", &[ Rd, Rs2, MemA ], 0x1800_202f, 0xfe00_707f, CPU_A),
+    form("SC.W.AQ", &[ Rd, Rs2, MemA ], 0x1c00_202f, 0xfe00_707f, CPU_A),
+    form("SC.W.RL", &[ Rd, Rs2, MemA ], 0x1a00_202f, 0xfe00_707f, CPU_A),
+    form("SC.W.AQRL", &[ Rd, Rs2, MemA ], 0x1e00_202f, 0xfe00_707f, CPU_A),
+    form("AMOSWAP.W", &[ Rd, Rs2, MemA ], 0x0800_202f, 0xfe00_707f, CPU_A),
+    form("AMOSWAP.W.AQ", &[ Rd, Rs2, MemA ], 0x0c00_202f, 0xfe00_707f, CPU_A),
+    form("AMOSWAP.W.RL", &[ Rd, Rs2, MemA ], 0x0a00_202f, 0xfe00_707f, CPU_A),
+    form("AMOSWAP.W.AQRL", &[ Rd, Rs2, MemA ], 0x0e00_202f, 0xfe00_707f, CPU_A),
+    form("AMOADD.W", &[ Rd, Rs2, MemA ], 0x0000_202f, 0xfe00_707f, CPU_A),
+    form("AMOADD.W.AQ", &[ Rd, Rs2, MemA ], 0x0400_202f, 0xfe00_707f, CPU_A),
+    form("AMOADD.W.RL", &[ Rd, Rs2, MemA ], 0x0200_202f, 0xfe00_707f, CPU_A),
+    form("AMOADD.W.AQRL", &[ Rd, Rs2, MemA ], 0x0600_202f, 0xfe00_707f, CPU_A),
+    form("AMOXOR.W", &[ Rd, Rs2, MemA ], 0x2000_202f, 0xfe00_707f, CPU_A),
+    form("AMOXOR.W.AQ", &[ Rd, Rs2, MemA ], 0x2400_202f, 0xfe00_707f, CPU_A),
+    form("AMOXOR.W.RL", &[ Rd, Rs2, MemA ], 0x2200_202f, 0xfe00_707f, CPU_A),
+    form("AMOXOR.W.AQRL", &[ Rd, Rs2, MemA ], 0x2600_202f, 0xfe00_707f, CPU_A),
+    form("AMOAND.W", &[ Rd, Rs2, MemA ], 0x6000_202f, 0xfe00_707f, CPU_A),
+    form("AMOAND.W.AQ", &[ Rd, Rs2, MemA ], 0x6400_202f, 0xfe00_707f, CPU_A),
+    form("AMOAND.W.RL", &[ Rd, Rs2, MemA ], 0x6200_202f, 0xfe00_707f, CPU_A),
+    form("AMOAND.W.AQRL", &[ Rd, Rs2, MemA ], 0x6600_202f, 0xfe00_707f, CPU_A),
+    form("AMOOR.W", &[ Rd, Rs2, MemA ], 0x4000_202f, 0xfe00_707f, CPU_A),
+    form("AMOOR.W.AQ", &[ Rd, Rs2, MemA ], 0x4400_202f, 0xfe00_707f, CPU_A),
+    form("AMOOR.W.RL", &[ Rd, Rs2, MemA ], 0x4200_202f, 0xfe00_707f, CPU_A),
+    form("AMOOR.W.AQRL", &[ Rd, Rs2, MemA ], 0x4600_202f, 0xfe00_707f, CPU_A),
+    form("AMOMIN.W", &[ Rd, Rs2, MemA ], 0x8000_202f, 0xfe00_707f, CPU_A),
+    form("AMOMIN.W.AQ", &[ Rd, Rs2, MemA ], 0x8400_202f, 0xfe00_707f, CPU_A),
+    form("AMOMIN.W.RL", &[ Rd, Rs2, MemA ], 0x8200_202f, 0xfe00_707f, CPU_A),
+    form("AMOMIN.W.AQRL", &[ Rd, Rs2, MemA ], 0x8600_202f, 0xfe00_707f, CPU_A),
+    form("AMOMAX.W", &[ Rd, Rs2, MemA ], 0xa000_202f, 0xfe00_707f, CPU_A),
+    form("AMOMAX.W.AQ", &[ Rd, Rs2, MemA ], 0xa400_202f, 0xfe00_707f, CPU_A),
+    form("AMOMAX.W.RL", &[ Rd, Rs2, MemA ], 0xa200_202f, 0xfe00_707f, CPU_A),
+    form("AMOMAX.W.AQRL", &[ Rd, Rs2, MemA ], 0xa600_202f, 0xfe00_707f, CPU_A),
+    form("AMOMINU.W", &[ Rd, Rs2, MemA ], 0xc000_202f, 0xfe00_707f, CPU_A),
+    form("AMOMINU.W.AQ", &[ Rd, Rs2, MemA ], 0xc400_202f, 0xfe00_707f, CPU_A),
+    form("AMOMINU.W.RL", &[ Rd, Rs2, MemA ], 0xc200_202f, 0xfe00_707f, CPU_A),
+    form("AMOMINU.W.AQRL", &[ Rd, Rs2, MemA ], 0xc600_202f, 0xfe00_707f, CPU_A),
+    form("AMOMAXU.W", &[ Rd, Rs2, MemA ], 0xe000_202f, 0xfe00_707f, CPU_A),
+    form("AMOMAXU.W.AQ", &[ Rd, Rs2, MemA ], 0xe400_202f, 0xfe00_707f, CPU_A),
+    form("AMOMAXU.W.RL", &[ Rd, Rs2, MemA ], 0xe200_202f, 0xfe00_707f, CPU_A),
+    form("AMOMAXU.W.AQRL", &[ Rd, Rs2, MemA ], 0xe600_202f, 0xfe00_707f, CPU_A),
+    form("LR.D", &[ Rd, MemA ], 0x1000_302f, 0xfff0_707f, CPU_A),
+    form("LR.D.AQ", &[ Rd, MemA ], 0x1400_302f, 0xfff0_707f, CPU_A),
+    form("LR.D.RL", &[ Rd, MemA ], 0x1200_302f, 0xfff0_707f, CPU_A),
+    form("LR.D.AQRL", &[ Rd, MemA ], 0x1600_302f, 0xfff0_707f, CPU_A),
+    form("SC.D", &[ Rd, Rs2, MemA ], 0x1800_302f, 0xfe00_707f, CPU_A),
+    form("SC.D.AQ", &[ Rd, Rs2, MemA ], 0x1c00_302f, 0xfe00_707f, CPU_A),
+    form("SC.D.RL", &[ Rd, Rs2, MemA ], 0x1a00_302f, 0xfe00_707f, CPU_A),
+    form("SC.D.AQRL", &[ Rd, Rs2, MemA ], 0x1e00_302f, 0xfe00_707f, CPU_A),
+    form("AMOSWAP.D", &[ Rd, Rs2, MemA ], 0x0800_302f, 0xfe00_707f, CPU_A),
+    form("AMOSWAP.D.AQ", &[ Rd, Rs2, MemA ], 0x0c00_302f, 0xfe00_707f, CPU_A),
+    form("AMOSWAP.D.RL", &[ Rd, Rs2, MemA ], 0x0a00_302f, 0xfe00_707f, CPU_A),
+    form("AMOSWAP.D.AQRL", &[ Rd, Rs2, MemA ], 0x0e00_302f, 0xfe00_707f, CPU_A),
+    form("AMOADD.D", &[ Rd, Rs2, MemA ], 0x0000_302f, 0xfe00_707f, CPU_A),
+    form("AMOADD.D.AQ", &[ Rd, Rs2, MemA ], 0x0400_302f, 0xfe00_707f, CPU_A),
+    form("AMOADD.D.RL", &[ Rd, Rs2, MemA ], 0x0200_302f, 0xfe00_707f, CPU_A),
+    form("AMOADD.D.AQRL", &[ Rd, Rs2, MemA ], 0x0600_302f, 0xfe00_707f, CPU_A),
+    form("AMOXOR.D", &[ Rd, Rs2, MemA ], 0x2000_302f, 0xfe00_707f, CPU_A),
+    form("AMOXOR.D.AQ", &[ Rd, Rs2, MemA ], 0x2400_302f, 0xfe00_707f, CPU_A),
+    form("AMOXOR.D.RL", &[ Rd, Rs2, MemA ], 0x2200_302f, 0xfe00_707f, CPU_A),
+    form("AMOXOR.D.AQRL", &[ Rd, Rs2, MemA ], 0x2600_302f, 0xfe00_707f, CPU_A),
+    form("AMOAND.D", &[ Rd, Rs2, MemA ], 0x6000_302f, 0xfe00_707f, CPU_A),
+    form("AMOAND.D.AQ", &[ Rd, Rs2, MemA ], 0x6400_302f, 0xfe00_707f, CPU_A),
+    form("AMOAND.D.RL", &[ Rd, Rs2, MemA ], 0x6200_302f, 0xfe00_707f, CPU_A),
+    form("AMOAND.D.AQRL", &[ Rd, Rs2, MemA ], 0x6600_302f, 0xfe00_707f, CPU_A),
+    form("AMOOR.D", &[ Rd, Rs2, MemA ], 0x4000_302f, 0xfe00_707f, CPU_A),
+    form("AMOOR.D.AQ", &[ Rd, Rs2, MemA ], 0x4400_302f, 0xfe00_707f, CPU_A),
+    form("AMOOR.D.RL", &[ Rd, Rs2, MemA ], 0x4200_302f, 0xfe00_707f, CPU_A),
+    form("AMOOR.D.AQRL", &[ Rd, Rs2, MemA ], 0x4600_302f, 0xfe00_707f, CPU_A),
+    form("AMOMIN.D", &[ Rd, Rs2, MemA ], 0x8000_302f, 0xfe00_707f, CPU_A),
+    form("AMOMIN.D.AQ", &[ Rd, Rs2, MemA ], 0x8400_302f, 0xfe00_707f, CPU_A),
+    form("AMOMIN.D.RL", &[ Rd, Rs2, MemA ], 0x8200_302f, 0xfe00_707f, CPU_A),
+    form("AMOMIN.D.AQRL", &[ Rd, Rs2, MemA ], 0x8600_302f, 0xfe00_707f, CPU_A),
+    form("AMOMAX.D", &[ Rd, Rs2, MemA ], 0xa000_302f, 0xfe00_707f, CPU_A),
+    form("AMOMAX.D.AQ", &[ Rd, Rs2, MemA ], 0xa400_302f, 0xfe00_707f, CPU_A),
+    form("AMOMAX.D.RL", &[ Rd, Rs2, MemA ], 0xa200_302f, 0xfe00_707f, CPU_A),
+    form("AMOMAX.D.AQRL", &[ Rd, Rs2, MemA ], 0xa600_302f, 0xfe00_707f, CPU_A),
+    form("AMOMINU.D", &[ Rd, Rs2, MemA ], 0xc000_302f, 0xfe00_707f, CPU_A),
+    form("AMOMINU.D.AQ", &[ Rd, Rs2, MemA ], 0xc400_302f, 0xfe00_707f, CPU_A),
+    form("AMOMINU.D.RL", &[ Rd, Rs2, MemA ], 0xc200_302f, 0xfe00_707f, CPU_A),
+    form("AMOMINU.D.AQRL", &[ Rd, Rs2, MemA ], 0xc600_302f, 0xfe00_707f, CPU_A),
+    form("AMOMAXU.D", &[ Rd, Rs2, MemA ], 0xe000_302f, 0xfe00_707f, CPU_A),
+    form("AMOMAXU.D.AQ", &[ Rd, Rs2, MemA ], 0xe400_302f, 0xfe00_707f, CPU_A),
+    form("AMOMAXU.D.RL", &[ Rd, Rs2, MemA ], 0xe200_302f, 0xfe00_707f, CPU_A),
+    form("AMOMAXU.D.AQRL", &[ Rd, Rs2, MemA ], 0xe600_302f, 0xfe00_707f, CPU_A),
+
+    form("FLW", &[ Fd, MemI ], 0x0000_2007, 0x0000_707f, CPU_F),
+    form("FSW", &[ Fs2, MemS ], 0x0000_2027, 0x0000_707f, CPU_F),
+    form("FADD.S", &[ Fd, Fs1, Fs2, Rm ], 0x0000_0053, 0xfe00_007f, CPU_F),
+    form("FSUB.S", &[ Fd, Fs1, Fs2, Rm ], 0x0800_0053, 0xfe00_007f, CPU_F),
+    form("FMUL.S", &[ Fd, Fs1, Fs2, Rm ], 0x1000_0053, 0xfe00_007f, CPU_F),
+    form("FDIV.S", &[ Fd, Fs1, Fs2, Rm ], 0x1800_0053, 0xfe00_007f, CPU_F),
+    form("FSQRT.S", &[ Fd, Fs1, Rm ], 0x5800_0053, 0xfff0_007f, CPU_F),
+    form("FSGNJ.S", &[ Fd, Fs1, Fs2 ], 0x2000_0053, 0xfe00_707f, CPU_F),
+    form("FSGNJN.S", &[ Fd, Fs1, Fs2 ], 0x2000_1053, 0xfe00_707f, CPU_F),
+    form("FSGNJX.S", &[ Fd, Fs1, Fs2 ], 0x2000_2053, 0xfe00_707f, CPU_F),
+    form("FMIN.S", &[ Fd, Fs1, Fs2 ], 0x2800_0053, 0xfe00_707f, CPU_F),
+    form("FMAX.S", &[ Fd, Fs1, Fs2 ], 0x2800_1053, 0xfe00_707f, CPU_F),
+    form("FEQ.S", &[ Rd, Fs1, Fs2 ], 0xa000_2053, 0xfe00_707f, CPU_F),
+    form("FLT.S", &[ Rd, Fs1, Fs2 ], 0xa000_1053, 0xfe00_707f, CPU_F),
+    form("FLE.S", &[ Rd, Fs1, Fs2 ], 0xa000_0053, 0xfe00_707f, CPU_F),
+    form("FCLASS.S", &[ Rd, Fs1 ], 0xe000_1053, 0xfff0_707f, CPU_F),
+    form("FCVT.W.S", &[ Rd, Fs1, Rm ], 0xc000_0053, 0xfff0_007f, CPU_F),
+    form("FCVT.WU.S", &[ Rd, Fs1, Rm ], 0xc010_0053, 0xfff0_007f, CPU_F),
+    form("FCVT.L.S", &[ Rd, Fs1, Rm ], 0xc020_0053, 0xfff0_007f, CPU_F),
+    form("FCVT.LU.S", &[ Rd, Fs1, Rm ], 0xc030_0053, 0xfff0_007f, CPU_F),
+    form("FCVT.S.W", &[ Fd, Rs1, Rm ], 0xd000_0053, 0xfff0_007f, CPU_F),
+    form("FCVT.S.WU", &[ Fd, Rs1, Rm ], 0xd010_0053, 0xfff0_007f, CPU_F),
+    form("FCVT.S.L", &[ Fd, Rs1, Rm ], 0xd020_0053, 0xfff0_007f, CPU_F),
+    form("FCVT.S.LU", &[ Fd, Rs1, Rm ], 0xd030_0053, 0xfff0_007f, CPU_F),
+    form("FMV.X.W", &[ Rd, Fs1 ], 0xe000_0053, 0xfff0_707f, CPU_F),
+    form("FMV.W.X", &[ Fd, Rs1 ], 0xf000_0053, 0xfff0_707f, CPU_F),
+    form("FMADD.S", &[ Fd, Fs1, Fs2, Fs3, Rm ], 0x0000_0043, 0x0600_007f, CPU_F),
+    form("FMSUB.S", &[ Fd, Fs1, Fs2, Fs3, Rm ], 0x0000_0047, 0x0600_007f, CPU_F),
+    form("FNMSUB.S", &[ Fd, Fs1, Fs2, Fs3, Rm ], 0x0000_004b, 0x0600_007f, CPU_F),
+    form("FNMADD.S", &[ Fd, Fs1, Fs2, Fs3, Rm ], 0x0000_004f, 0x0600_007f, CPU_F),
+
+    form("FLD", &[ Fd, MemI ], 0x0000_3007, 0x0000_707f, CPU_D),
+    form("FSD", &[ Fs2, MemS ], 0x0000_3027, 0x0000_707f, CPU_D),
+    form("FADD.D", &[ Fd, Fs1, Fs2, Rm ], 0x0200_0053, 0xfe00_007f, CPU_D),
+    form("FSUB.D", &[ Fd, Fs1, Fs2, Rm ], 0x0a00_0053, 0xfe00_007f, CPU_D),
+    form("FMUL.D", &[ Fd, Fs1, Fs2, Rm ], 0x1200_0053, 0xfe00_007f, CPU_D),
+    form("FDIV.D", &[ Fd, Fs1, Fs2, Rm ], 0x1a00_0053, 0xfe00_007f, CPU_D),
+    form("FSQRT.D", &[ Fd, Fs1, Rm ], 0x5a00_0053, 0xfff0_007f, CPU_D),
+    form("FSGNJ.D", &[ Fd, Fs1, Fs2 ], 0x2200_0053, 0xfe00_707f, CPU_D),
+    form("FSGNJN.D", &[ Fd, Fs1, Fs2 ], 0x2200_1053, 0xfe00_707f, CPU_D),
+    form("FSGNJX.D", &[ Fd, Fs1, Fs2 ], 0x2200_2053, 0xfe00_707f, CPU_D),
+    form("FMIN.D", &[ Fd, Fs1, Fs2 ], 0x2a00_0053, 0xfe00_707f, CPU_D),
+    form("FMAX.D", &[ Fd, Fs1, Fs2 ], 0x2a00_1053, 0xfe00_707f, CPU_D),
+    form("FEQ.D", &[ Rd, Fs1, Fs2 ], 0xa200_2053, 0xfe00_707f, CPU_D),
+    form("FLT.D", &[ Rd, Fs1, Fs2 ], 0xa200_1053, 0xfe00_707f, CPU_D),
+    form("FLE.D", &[ Rd, Fs1, Fs2 ], 0xa200_0053, 0xfe00_707f, CPU_D),
+    form("FCLASS.D", &[ Rd, Fs1 ], 0xe200_1053, 0xfff0_707f, CPU_D),
+    form("FCVT.W.D", &[ Rd, Fs1, Rm ], 0xc200_0053, 0xfff0_007f, CPU_D),
+    form("FCVT.WU.D", &[ Rd, Fs1, Rm ], 0xc210_0053, 0xfff0_007f, CPU_D),
+    form("FCVT.L.D", &[ Rd, Fs1, Rm ], 0xc220_0053, 0xfff0_007f, CPU_D),
+    form("FCVT.LU.D", &[ Rd, Fs1, Rm ], 0xc230_0053, 0xfff0_007f, CPU_D),
+    form("FCVT.D.W", &[ Fd, Rs1 ], 0xd200_0053, 0xfff0_707f, CPU_D),
+    form("FCVT.D.WU", &[ Fd, Rs1 ], 0xd210_0053, 0xfff0_707f, CPU_D),
+    form("FCVT.D.L", &[ Fd, Rs1, Rm ], 0xd220_0053, 0xfff0_007f, CPU_D),
+    form("FCVT.D.LU", &[ Fd, Rs1, Rm ], 0xd230_0053, 0xfff0_007f, CPU_D),
+    form("FMV.X.D", &[ Rd, Fs1 ], 0xe200_0053, 0xfff0_707f, CPU_D),
+    form("FMV.D.X", &[ Fd, Rs1 ], 0xf200_0053, 0xfff0_707f, CPU_D),
+    form("FMADD.D", &[ Fd, Fs1, Fs2, Fs3, Rm ], 0x0200_0043, 0x0600_007f, CPU_D),
+    form("FMSUB.D", &[ Fd, Fs1, Fs2, Fs3, Rm ], 0x0200_0047, 0x0600_007f, CPU_D),
+    form("FNMSUB.D", &[ Fd, Fs1, Fs2, Fs3, Rm ], 0x0200_004b, 0x0600_007f, CPU_D),
+    form("FNMADD.D", &[ Fd, Fs1, Fs2, Fs3, Rm ], 0x0200_004f, 0x0600_007f, CPU_D),
+
+    form("FCVT.S.D", &[ Fd, Fs1, Rm ], 0x4010_0053, 0xfff0_007f, CPU_D),
+    form("FCVT.D.S", &[ Fd, Fs1 ], 0x4200_0053, 0xfff0_707f, CPU_D),
+
+    compressed("C.ADDI4SPN", &[ CP2, Sp, C4spn ], 0x0000, 0xe003, "ADDI", &[ Op(0), Op(1), Op(2) ], CPU_C),
+    compressed("C.FLD", &[ CFP2, CMemD ], 0x2000, 0xe003, "FLD", &[ Op(0), Op(1) ], CPU_C | CPU_D),
+    compressed("C.LW", &[ CP2, CMemW ], 0x4000, 0xe003, "LW", &[ Op(0), Op(1) ], CPU_C),
+    compressed("C.LD", &[ CP2, CMemD ], 0x6000, 0xe003, "LD", &[ Op(0), Op(1) ], CPU_C),
+    compressed("C.FSD", &[ CFP2, CMemD ], 0xa000, 0xe003, "FSD", &[ Op(0), Op(1) ], CPU_C | CPU_D),
+    compressed("C.SW", &[ CP2, CMemW ], 0xc000, 0xe003, "SW", &[ Op(0), Op(1) ], CPU_C),
+    compressed("C.SD", &[ CP2, CMemD ], 0xe000, 0xe003, "SD", &[ Op(0), Op(1) ], CPU_C),
+
+    compressed("C.NOP", &[  ], 0x0001, 0xffff, "ADDI", &[ Zero, Zero, ImmZero ], CPU_C),
+    compressed("C.ADDI", &[ CRd, CNzImm6 ], 0x0001, 0xe003, "ADDI", &[ Op(0), Op(0), Op(1) ], CPU_C),
+    compressed("C.ADDIW", &[ CRd, CImm6 ], 0x2001, 0xe003, "ADDIW", &[ Op(0), Op(0), Op(1) ], CPU_C),
+    compressed("C.LI", &[ CRd, CImm6 ], 0x4001, 0xe003, "ADDI", &[ Op(0), Zero, Op(1) ], CPU_C),
+    compressed("C.ADDI16SP", &[ Sp, C16sp ], 0x6101, 0xef83, "ADDI", &[ Op(0), Op(0), Op(1) ], CPU_C),
+    compressed("C.LUI", &[ CRdNoSp, CLui ], 0x6001, 0xe003, "LUI", &[ Op(0), Op(1) ], CPU_C),
+    compressed("C.SRLI", &[ CP7, CShamt ], 0x8001, 0xec03, "SRLI", &[ Op(0), Op(0), Op(1) ], CPU_C),
+    compressed("C.SRAI", &[ CP7, CShamt ], 0x8401, 0xec03, "SRAI", &[ Op(0), Op(0), Op(1) ], CPU_C),
+    compressed("C.ANDI", &[ CP7, CImm6 ], 0x8801, 0xec03, "ANDI", &[ Op(0), Op(0), Op(1) ], CPU_C),
+    compressed("C.SUB", &[ CP7, CP2 ], 0x8c01, 0xfc63, "SUB", &[ Op(0), Op(0), Op(1) ], CPU_C),
+    compressed("C.XOR", &[ CP7, CP2 ], 0x8c21, 0xfc63, "XOR", &[ Op(0), Op(0), Op(1) ], CPU_C),
+    compressed("C.OR", &[ CP7, CP2 ], 0x8c41, 0xfc63, "OR", &[ Op(0), Op(0), Op(1) ], CPU_C),
+    compressed("C.AND", &[ CP7, CP2 ], 0x8c61, 0xfc63, "AND", &[ Op(0), Op(0), Op(1) ], CPU_C),
+    compressed("C.SUBW", &[ CP7, CP2 ], 0x9c01, 0xfc63, "SUBW", &[ Op(0), Op(0), Op(1) ], CPU_C),
+    compressed("C.ADDW", &[ CP7, CP2 ], 0x9c21, 0xfc63, "ADDW", &[ Op(0), Op(0), Op(1) ], CPU_C),
+    compressed("C.J", &[ CJ12 ], 0xa001, 0xe003, "JAL", &[ Zero, Op(0) ], CPU_C),
+    compressed("C.BEQZ", &[ CP7, CB9 ], 0xc001, 0xe003, "BEQ", &[ Op(0), Zero, Op(1) ], CPU_C),
+    compressed("C.BNEZ", &[ CP7, CB9 ], 0xe001, 0xe003, "BNE", &[ Op(0), Zero, Op(1) ], CPU_C),
+
+    compressed("C.SLLI", &[ CRd, CShamt ], 0x0002, 0xe003, "SLLI", &[ Op(0), Op(0), Op(1) ], CPU_C),
+    compressed("C.FLDSP", &[ CFd, CMemDsp ], 0x2002, 0xe003, "FLD", &[ Op(0), Op(1) ], CPU_C | CPU_D),
+    compressed("C.LWSP", &[ CRd, CMemWsp ], 0x4002, 0xe003, "LW", &[ Op(0), Op(1) ], CPU_C),
+    compressed("C.LDSP", &[ CRd, CMemDsp ], 0x6002, 0xe003, "LD", &[ Op(0), Op(1) ], CPU_C),
+    compressed("C.JR", &[ CRd ], 0x8002, 0xf07f, "JALR", &[ Zero, Op(0), ImmZero ], CPU_C),
+    compressed("C.MV", &[ CRd, CRs2 ], 0x8002, 0xf003, "ADD", &[ Op(0), Zero, Op(1) ], CPU_C),
+    compressed("C.MV", &[ CRd, CRs2 ], 0x8002, 0xf003, "ADDI", &[ Op(0), Op(1), ImmZero ], CPU_C),
+    compressed("C.EBREAK", &[  ], 0x9002, 0xffff, "EBREAK", &[  ], CPU_C),
+    compressed("C.JALR", &[ CRd ], 0x9002, 0xf07f, "JALR", &[ Ra, Op(0), ImmZero ], CPU_C),
+    compressed("C.ADD", &[ CRd, CRs2 ], 0x9002, 0xf003, "ADD", &[ Op(0), Op(0), Op(1) ], CPU_C),
+    compressed("C.FSDSP", &[ CFs2, CStoreDsp ], 0xa002, 0xe003, "FSD", &[ Op(0), Op(1) ], CPU_C | CPU_D),
+    compressed("C.SWSP", &[ CRs2Any, CStoreWsp ], 0xc002, 0xe003, "SW", &[ Op(0), Op(1) ], CPU_C),
+    compressed("C.SDSP", &[ CRs2Any, CStoreDsp ], 0xe002, 0xe003, "SD", &[ Op(0), Op(1) ], CPU_C),
+];
+
+/// Flags implied by the extensions given in code block, double precision includes single precision
+pub fn implied_flags(flags: CpuFlags) -> CpuFlags {
+    let rules = [
+        ( CPU_D, CPU_F )
+    ];
+    let mut result = flags;
+
+    for ( cpu , implied ) in rules.iter() {
+        if result & cpu != 0 {
+            result |= implied
+        }
+    }
+
+    result
+}
+
+/// Length in bytes of instruction form, compressed instructions have low bits other than 11
+fn form_length(form: &InstructionForm) -> usize {
+    match form.opcode & 3 {
+        3 => 4,
+        _ => 2
+    }
+}
+
+/// Bits of an immediate, each segment is highest and lowest bit of value and position of lowest bit in instruction word
+struct ImmediateField {
+    segments: &'static [( u32 , u32 , u32 )],
+    signed: bool,
+    nonzero: bool,
+    relative: bool      /* Operand is absolute address and field holds offset from instruction */
+}
+
+const fn field(segments: &'static [( u32 , u32 , u32 )], signed: bool, nonzero: bool, relative: bool) -> ImmediateField {
+    ImmediateField { segments, signed, nonzero, relative }
+}
+
+/// Immediate field of operand kind, for memory operands the field of the offset
+fn immediate_field(kind: OperandKind) -> Option<ImmediateField> {
+    match kind {
+        I12 | MemI => Some( field(&[ ( 11, 0, 20 ) ], true, false, false) ),
+        MemS => Some( field(&[ ( 11, 5, 25 ), ( 4, 0, 7 ) ], true, false, false) ),
+        B13 => Some( field(&[ ( 12, 12, 31 ), ( 10, 5, 25 ), ( 4, 1, 8 ), ( 11, 11, 7 ) ], true, false, true) ),
+        J21 => Some( field(&[ ( 20, 20, 31 ), ( 10, 1, 21 ), ( 11, 11, 20 ), ( 19, 12, 12 ) ], true, false, true) ),
+        U20 => Some( field(&[ ( 19, 0, 12 ) ], false, false, false) ),
+        Shamt6 => Some( field(&[ ( 5, 0, 20 ) ], false, false, false) ),
+        Shamt5 => Some( field(&[ ( 4, 0, 20 ) ], false, false, false) ),
+        Csr12 => Some( field(&[ ( 11, 0, 20 ) ], false, false, false) ),
+        Zimm5 => Some( field(&[ ( 4, 0, 15 ) ], false, false, false) ),
+        Pred => Some( field(&[ ( 3, 0, 24 ) ], false, false, false) ),
+        Succ => Some( field(&[ ( 3, 0, 20 ) ], false, false, false) ),
+        CImm6 => Some( field(&[ ( 5, 5, 12 ), ( 4, 0, 2 ) ], true, false, false) ),
+        CNzImm6 | CLui => Some( field(&[ ( 5, 5, 12 ), ( 4, 0, 2 ) ], true, true, false) ),
+        CShamt => Some( field(&[ ( 5, 5, 12 ), ( 4, 0, 2 ) ], false, true, false) ),
+        C16sp => Some( field(&[ ( 9, 9, 12 ), ( 4, 4, 6 ), ( 6, 6, 5 ), ( 8, 7, 3 ), ( 5, 5, 2 ) ], true, true, false) ),
+        C4spn => Some( field(&[ ( 5, 4, 11 ), ( 9, 6, 7 ), ( 2, 2, 6 ), ( 3, 3, 5 ) ], false, true, false) ),
+        CB9 => Some( field(&[ ( 8, 8, 12 ), ( 4, 3, 10 ), ( 7, 6, 5 ), ( 2, 1, 3 ), ( 5, 5, 2 ) ], true, false, true) ),
+        CJ12 => Some( field(&[ ( 11, 11, 12 ), ( 4, 4, 11 ), ( 9, 8, 9 ), ( 10, 10, 8 ), ( 6, 6, 7 ), ( 7, 7, 6 ), ( 3, 1, 3 ), ( 5, 5, 2 ) ], true, false, true) ),
+        CMemW => Some( field(&[ ( 5, 3, 10 ), ( 2, 2, 6 ), ( 6, 6, 5 ) ], false, false, false) ),
+        CMemD => Some( field(&[ ( 5, 3, 10 ), ( 7, 6, 5 ) ], false, false, false) ),
+        CMemWsp => Some( field(&[ ( 5, 5, 12 ), ( 4, 2, 4 ), ( 7, 6, 2 ) ], false, false, false) ),
+        CMemDsp => Some( field(&[ ( 5, 5, 12 ), ( 4, 3, 5 ), ( 8, 6, 2 ) ], false, false, false) ),
+        CStoreWsp => Some( field(&[ ( 5, 2, 9 ), ( 7, 6, 7 ) ], false, false, false) ),
+        CStoreDsp => Some( field(&[ ( 5, 3, 10 ), ( 8, 6, 7 ) ], false, false, false) ),
+        _ => None
+    }
+}
+
+/// Kind of base register of memory operand kind, or none when kind is not a memory operand
+fn memory_base(kind: OperandKind) -> Option<OperandKind> {
+    match kind {
+        MemI | MemS | MemA => Some( Rs1 ),
+        CMemW | CMemD => Some( CP7 ),
+        CMemWsp | CMemDsp | CStoreWsp | CStoreDsp => Some( Sp ),
+        _ => None
+    }
+}
+
+/// Immediate bits of value placed in instruction word, or error when value does not fit the field
+fn scatter(field: &ImmediateField, value: i64) -> Result<u32, String> {
+    let low = field.segments.iter().map(|x| x.1).min().unwrap_or(0);
+    let high = field.segments.iter().map(|x| x.0).max().unwrap_or(0);
+    let ( min , max ) = match field.signed {
+        true => ( -(1i64 << high) , (1i64 << high) - 1 ),
+        _ => ( 0 , (1i64 << (high + 1)) - 1 )
+    };
+
+    if value < min || value > max {
+        return Err(format!("Immediate {} out of range", value))
+    }
+    if value & ((1i64 << low) - 1) != 0 {
+        return Err(format!("Immediate {} is not a multiple of {}", value, 1i64 << low))
+    }
+    if field.nonzero && value == 0 {
+        return Err(String::from("Immediate must not be zero"))
+    }
+
+    let mut word = 0;
+    for ( high , low , position ) in field.segments.iter() {
+        word |= (((value >> low) as u32) & ((1u32 << (high - low + 1)) - 1)) << position
+    }
+
+    Ok(word)
+}
+
+/// Immediate value from its bits in instruction word
+fn gather(field: &ImmediateField, word: u32) -> i64 {
+    let high = field.segments.iter().map(|x| x.0).max().unwrap_or(0);
+    let mut value = 0i64;
+
+    for ( high , low , position ) in field.segments.iter() {
+        value |= (((word >> position) & ((1u32 << (high - low + 1)) - 1)) as i64) << low
+    }
+
+    match field.signed {
+        true => (value << (63 - high)) >> (63 - high),
+        _ => value
+    }
+}
+
+/// Immediate as stored in field, relative targets become offsets and upper immediates of LUI accept negative values
+fn immediate_value(kind: OperandKind, value: i64, location: i64) -> i64 {
+    match kind {
+        U20 if ( -0x80000 .. 0 ).contains(&value) => value + 0x100000,
+        CLui if ( 0x80000 .. 0x100000 ).contains(&value) => value - 0x100000,
+        B13 | J21 | CB9 | CJ12 => value - location,
+        _ => value
+    }
+}
+
+/// Tells if operand kind is a floating point register
+fn is_float(kind: OperandKind) -> bool {
+    matches!(kind, Fd | Fs1 | Fs2 | Fs3 | CFd | CFs2 | CFP2)
+}
+
+fn register_matches(kind: OperandKind, register: &RegisterRiscV) -> bool {
+    match ( kind , register ) {
+        ( Rd | Rs1 | Rs2 | CRs2Any , RegisterRiscV::Integer( _ ) ) => true,
+        ( CRd | CRs2 , RegisterRiscV::Integer( n ) ) => *n != 0,
+        ( CRdNoSp , RegisterRiscV::Integer( n ) ) => *n != 0 && *n != 2,
+        ( CP7 | CP2 , RegisterRiscV::Integer( n ) ) => ( 8 .. 16 ).contains(n),
+        ( Sp , RegisterRiscV::Integer( n ) ) => *n == 2,
+        ( CFP2 , RegisterRiscV::Float( n ) ) => ( 8 .. 16 ).contains(n),
+        ( _ , RegisterRiscV::Float( _ ) ) => is_float(kind),
+        _ => false
+    }
+}
+
+fn operand_matches(kind: OperandKind, operand: &RiscVOperand) -> bool {
+    match operand {
+        RiscVOperand::Register( r ) => register_matches(kind, r),
+        RiscVOperand::Immediate( _ ) => memory_base(kind).is_none() && immediate_field(kind).is_some(),
+        RiscVOperand::Memory( r , _ ) => {
+            match memory_base(kind) {
+                Some( x ) => register_matches(x, r),
+                None => false
+            }
+        },
+        RiscVOperand::Rounding( x ) => kind == Rm && !ROUNDING_NAMES[(*x & 7) as usize].is_empty()
+    }
+}
+
+/// Tells if operands fit form, rounding mode may be left out
+fn operands_match(form: &InstructionForm, operands: &[RiscVOperand]) -> bool {
+    let count = match form.operands.last() {
+        Some( Rm ) if operands.len() + 1 == form.operands.len() => operands.len(),
+        _ => form.operands.len()
+    };
+
+    count == operands.len() && form.operands.iter().zip(operands.iter()).all(|( k , o )| operand_matches(*k, o))
+}
+
+/// Register number placed in instruction word
+fn register_bits(kind: OperandKind, number: u8) -> u32 {
+    let number = number as u32;
+
+    match kind {
+        Rd | Fd | CRd | CRdNoSp | CFd => number << 7,
+        Rs1 | Fs1 => number << 15,
+        Rs2 | Fs2 => number << 20,
+        Fs3 => number << 27,
+        CRs2 | CRs2Any | CFs2 => number << 2,
+        CP7 => (number & 7) << 7,
+        CP2 | CFP2 => (number & 7) << 2,
+        _ => 0
+    }
+}
+
+/// Register number from instruction word
+fn register_field(kind: OperandKind, word: u32) -> u8 {
+    let number = match kind {
+        Rd | Fd | CRd | CRdNoSp | CFd => word >> 7,
+        Rs1 | Fs1 => word >> 15,
+        Rs2 | Fs2 => word >> 20,
+        Fs3 => word >> 27,
+        CRs2 | CRs2Any | CFs2 => word >> 2,
+        CP7 => 8 | ((word >> 7) & 7),
+        CP2 | CFP2 => 8 | ((word >> 2) & 7),
+        _ => 2
+    };
+
+    (number & 31) as u8
+}
+
+fn encode_operand(kind: OperandKind, operand: &RiscVOperand, location: i64) -> Result<u32, String> {
+    match operand {
+        RiscVOperand::Register( r ) => Ok(register_bits(kind, r.number())),
+        RiscVOperand::Rounding( x ) => Ok((*x as u32) << 12),
+        RiscVOperand::Memory( r , offset ) => {
+            let bits = register_bits(memory_base(kind).unwrap_or(Sp), r.number());
+            match immediate_field(kind) {
+                Some( x ) => Ok(bits | scatter(&x, *offset)?),
+                None if *offset == 0 => Ok(bits),
+                None => Err(String::from("Memory operand of atomic instruction has no offset"))
+            }
+        },
+        RiscVOperand::Immediate( x ) => {
+            match immediate_field(kind) {
+                Some( f ) => scatter(&f, immediate_value(kind, *x, location)),
+                None => Err(String::from("Immediate not allowed"))
+            }
+        }
+    }
+}
+
+/// Instruction word of form with operands that match it
+fn encode_form(form: &InstructionForm, operands: &[RiscVOperand], location: i64) -> Result<u32, String> {
+    let mut word = form.opcode;
+
+    for ( kind , operand ) in form.operands.iter().zip(operands.iter()) {
+        word |= encode_operand(*kind, operand, location)?
+    }
+    if operands.len() < form.operands.len() {
+        word |= 7 << 12     /* Dynamic rounding mode */
+    }
+
+    Ok(word)
+}
+
+/// Operands of compressed form when operands of base instruction have the shape compressed form is a short form of
+fn compressed_operands(form: &InstructionForm, operands: &[RiscVOperand]) -> Option<Vec<RiscVOperand>> {
+    let mut result : Vec<Option<RiscVOperand>> = vec![ None; form.operands.len() ];
+
+    if form.expansion.len() != operands.len() {
+        return None
+    }
+    for ( expansion , operand ) in form.expansion.iter().zip(operands.iter()) {
+        match expansion {
+            Op( i ) => {
+                match &result[*i as usize] {
+                    Some( x ) if x != operand => return None,
+                    _ => result[*i as usize] = Some( operand.clone() )
+                }
+            },
+            Zero if *operand == RiscVOperand::Register(RegisterRiscV::Integer(0)) => (),
+            Ra if *operand == RiscVOperand::Register(RegisterRiscV::Integer(1)) => (),
+            ImmZero if *operand == RiscVOperand::Immediate(0) => (),
+            _ => return None
+        }
+    }
+
+    result.into_iter().collect()
+}
+
+fn word_bytes(form: &InstructionForm, word: u32) -> Vec<u8> {
+    match form_length(form) {
+        2 => (word as u16).to_le_bytes().to_vec(),
+        _ => word.to_le_bytes().to_vec()
+    }
+}
+
+/// Encode one machine instruction, with compressed form when extension C is enabled and 'compress' is set
+fn encode_single(mnemonic: &str, operands: &[RiscVOperand], location: i64, flags: CpuFlags, compress: bool) -> Result<Vec<u8>, Box<Diagnostic>> {
+    let mut known = false;
+    let mut matches = Vec::<&InstructionForm>::new();
+
+    if compress && flags & CPU_C != 0 {
+        for form in INSTRUCTIONS_RISC_V.iter() {
+            if form.base != mnemonic || form.flags & !flags != 0 {
+                continue
+            }
+            match compressed_operands(form, operands) {
+                Some( x ) if operands_match(form, &x) => {
+                    if let Ok( word ) = encode_form(form, &x, location) {
+                        return Ok(word_bytes(form, word))
+                    }
+                },
+                _ => ()
+            }
+        }
+    }
+
+    for form in INSTRUCTIONS_RISC_V.iter() {
+        if form.mnemonic != mnemonic {
+            continue
+        }
+        known = true;
+        if operands_match(form, operands) {
+            matches.push(form)
+        }
+    }
+
+    if !known {
+        return Err(Diagnostic::error(E_ASSEMBLER_INSTRUCTION, format!("Unknown instruction '{}'", mnemonic), 0, 0))
+    }
+    if matches.is_empty() {
+        return Err(Diagnostic::error(E_ASSEMBLER_OPERAND, format!("Invalid combination of operands for '{}'", mnemonic), 0, 0))
+    }
+
+    let mut error = Diagnostic::error(E_ASSEMBLER_CPU_FLAG, format!("Instruction '{}' is not enabled by CPU flags of code block", mnemonic), 0, 0);
+    for form in matches.iter() {
+        if form.flags & !flags != 0 {
+            continue
+        }
+        match encode_form(form, operands, location) {
+            Ok( word ) => return Ok(word_bytes(form, word)),
+            Err( x ) => error = Diagnostic::error(E_ASSEMBLER_OPERAND, format!("{} for '{}'", x, mnemonic), 0, 0)
+        }
+    }
+
+    Err(error)
+}
+
+type Sequence = Vec<( &'static str , Vec<RiscVOperand> )>;
+
+/// Instructions loading constant into register, the same sequence as the GNU and LLVM assemblers use
+fn load_immediate(rd: &RiscVOperand, value: i64) -> Sequence {
+    let zero = RiscVOperand::Register(RegisterRiscV::Integer(0));
+    let low = (value << 52) >> 52;
+    let mut result = Sequence::new();
+
+    if value == value as i32 as i64 {
+        let high = ((value + 0x800) >> 12) & 0xfffff;
+        if high != 0 {
+            result.push( ( "LUI" , vec![ rd.clone() , RiscVOperand::Immediate(high) ] ) )
+        }
+        match ( high , low ) {
+            ( 0 , _ ) => result.push( ( "ADDI" , vec![ rd.clone() , zero , RiscVOperand::Immediate(low) ] ) ),
+            ( _ , 0 ) => (),
+            _ => result.push( ( "ADDIW" , vec![ rd.clone() , rd.clone() , RiscVOperand::Immediate(low) ] ) )
+        }
+        return result
+    }
+
+    /* Upper bits are loaded first and shifted into place, dropping trailing zero bits */
+    let upper = ((value as u64).wrapping_add(0x800) >> 12) as i64;
+    let shift = 12 + upper.trailing_zeros();
+    let upper = ((upper >> (shift - 12)) << shift) >> shift;
+
+    result = load_immediate(rd, upper);
+    result.push( ( "SLLI" , vec![ rd.clone() , rd.clone() , RiscVOperand::Immediate(shift as i64) ] ) );
+    if low != 0 {
+        result.push( ( "ADDI" , vec![ rd.clone() , rd.clone() , RiscVOperand::Immediate(low) ] ) )
+    }
+
+    result
+}
+
+/// Upper 20 bits for AUIPC and lower 12 bits of offset from instruction, or error when offset is beyond 2 GB
+fn pc_relative(mnemonic: &str, offset: i64) -> Result<( i64 , i64 ), Box<Diagnostic>> {
+    match offset == offset as i32 as i64 {
+        true => {
+            let high = (offset + 0x800) >> 12;
+            Ok( ( high & 0xfffff , offset - (high << 12) ) )
+        },
+        _ => Err(Diagnostic::error(E_ASSEMBLER_OPERAND, format!("Target out of range for '{}'", mnemonic), 0, 0))
+    }
+}
+
+/// Instructions of pseudo instruction and if they may be compressed, or none when mnemonic and operands are not a pseudo instruction
+fn expand_pseudo(mnemonic: &str, operands: &[RiscVOperand], location: i64) -> Result<Option<( Sequence , bool )>, Box<Diagnostic>> {
+    use RiscVOperand::{ Register, Immediate };
+    use RegisterRiscV::{ Integer, Float };
+
+    let zero = Register(Integer(0));
+    let ra = Register(Integer(1));
+
+    let sequence = match ( mnemonic , operands ) {
+        ( "NOP" , [] ) => vec![ ( "ADDI" , vec![ zero.clone() , zero , Immediate(0) ] ) ],
+        ( "LI" , [ rd @ Register(Integer( _ )) , Immediate( x ) ] ) => load_immediate(rd, *x),
+        ( "LA" , [ rd @ Register(Integer( _ )) , Immediate( x ) ] ) => {
+            let ( high , low ) = pc_relative(mnemonic, *x - location)?;
+            return Ok(Some( ( vec![
+                ( "AUIPC" , vec![ rd.clone() , Immediate(high) ] ),
+                ( "ADDI" , vec![ rd.clone() , rd.clone() , Immediate(low) ] )
+            ] , false ) ))
+        },
+        ( "CALL" | "TAIL" , [ Immediate( x ) ] ) => {
+            let ( high , low ) = pc_relative(mnemonic, *x - location)?;
+            let ( rd , scratch ) = match mnemonic {
+                "CALL" => ( ra.clone() , ra ),
+                _ => ( zero , Register(Integer(6)) )
+            };
+            return Ok(Some( ( vec![
+                ( "AUIPC" , vec![ scratch.clone() , Immediate(high) ] ),
+                ( "JALR" , vec![ rd , scratch , Immediate(low) ] )
+            ] , false ) ))
+        },
+        ( "RET" , [] ) => vec![ ( "JALR" , vec![ zero , ra , Immediate(0) ] ) ],
+        ( "MV" , [ rd @ Register(Integer( _ )) , rs @ Register(Integer( _ )) ] ) => vec![ ( "ADDI" , vec![ rd.clone() , rs.clone() , Immediate(0) ] ) ],
+        ( "NOT" , [ rd , rs ] ) => vec![ ( "XORI" , vec![ rd.clone() , rs.clone() , Immediate(-1) ] ) ],
+        ( "NEG" , [ rd , rs ] ) => vec![ ( "SUB" , vec![ rd.clone() , zero , rs.clone() ] ) ],
+        ( "NEGW" , [ rd , rs ] ) => vec![ ( "SUBW" , vec![ rd.clone() , zero , rs.clone() ] ) ],
+        ( "SEXT.W" , [ rd , rs ] ) => vec![ ( "ADDIW" , vec![ rd.clone() , rs.clone() , Immediate(0) ] ) ],
+        ( "SEQZ" , [ rd , rs ] ) => vec![ ( "SLTIU" , vec![ rd.clone() , rs.clone() , Immediate(1) ] ) ],
+        ( "SNEZ" , [ rd , rs ] ) => vec![ ( "SLTU" , vec![ rd.clone() , zero , rs.clone() ] ) ],
+        ( "BEQZ" , [ rs , target ] ) => vec![ ( "BEQ" , vec![ rs.clone() , zero , target.clone() ] ) ],
+        ( "BNEZ" , [ rs , target ] ) => vec![ ( "BNE" , vec![ rs.clone() , zero , target.clone() ] ) ],
+        ( "J" , [ target ] ) => vec![ ( "JAL" , vec![ zero , target.clone() ] ) ],
+        ( "JAL" , [ target @ Immediate( _ ) ] ) => vec![ ( "JAL" , vec![ ra , target.clone() ] ) ],
+        ( "JR" , [ rs ] ) => vec![ ( "JALR" , vec![ zero , rs.clone() , Immediate(0) ] ) ],
+        ( "JALR" , [ rs @ Register( _ ) ] ) => vec![ ( "JALR" , vec![ ra , rs.clone() , Immediate(0) ] ) ],
+        ( "FENCE" , [] ) => vec![ ( "FENCE" , vec![ Immediate(15) , Immediate(15) ] ) ],
+        ( "CSRR" , [ rd , csr ] ) => vec![ ( "CSRRS" , vec![ rd.clone() , csr.clone() , zero ] ) ],
+        ( "CSRW" , [ csr , rs ] ) => vec![ ( "CSRRW" , vec![ zero , csr.clone() , rs.clone() ] ) ],
+        ( "FMV.S" | "FMV.D" , [ rd @ Register(Float( _ )) , rs @ Register(Float( _ )) ] ) |
+        ( "FNEG.S" | "FNEG.D" | "FABS.S" | "FABS.D" , [ rd , rs ] ) => {
+            let base = match mnemonic {
+                "FMV.S" => "FSGNJ.S",
+                "FMV.D" => "FSGNJ.D",
+                "FNEG.S" => "FSGNJN.S",
+                "FNEG.D" => "FSGNJN.D",
+                "FABS.S" => "FSGNJX.S",
+                _ => "FSGNJX.D"
+            };
+            vec![ ( base , vec![ rd.clone() , rs.clone() , rs.clone() ] ) ]
+        },
+        _ => return Ok(None)
+    };
+
+    Ok(Some( ( sequence , true ) ))
+}
+
+/// Encode a single assembler instruction with operands, location is address of instruction for relative jumps.
+/// Pseudo instructions give several instructions, and compressed instructions are used when extension C is enabled.
+pub fn encode_instruction_risc_v(instruction: Box<String>, operands: Box<Vec<RiscVOperand>>, location: i64, flags: CpuFlags) -> Result<Box<Vec<u8>>, Box<Diagnostic>> {
+    let mnemonic = instruction.to_uppercase();
+    let flags = implied_flags(flags);
+
+    match expand_pseudo(mnemonic.as_str(), &operands, location)? {
+        Some( ( sequence , compress ) ) => {
+            let mut bytes = Vec::<u8>::new();
+            for ( name , x ) in sequence.iter() {
+                let code = encode_single(name, x, location + bytes.len() as i64, flags, compress)?;
+                bytes.extend(code)
+            }
+            Ok(Box::new(bytes))
+        },
+        None => Ok(Box::new(encode_single(mnemonic.as_str(), &operands, location, flags, true)?))
+    }
+}
+
+/// Operand in assembler syntax, relative targets are absolute addresses in hexadecimal
+pub fn operand_text_risc_v(kind: OperandKind, operand: &RiscVOperand) -> String {
+    match operand {
+        RiscVOperand::Register( r ) => register_name_risc_v(r),
+        RiscVOperand::Memory( r , offset ) => format!("{}({})", offset, register_name_risc_v(r)),
+        RiscVOperand::Rounding( x ) => String::from(ROUNDING_NAMES[(*x & 7) as usize]),
+        RiscVOperand::Immediate( x ) => {
+            match kind {
+                B13 | J21 | CB9 | CJ12 => format!("0x{:x}", x),
+                Csr12 => {
+                    match CSR_NAMES.iter().find(|c| c.1 as i64 == *x) {
+                        Some( ( name , _ ) ) => String::from(*name),
+                        None => format!("0x{:x}", x)
+                    }
+                },
+                Pred | Succ => "iorw".chars().enumerate().filter(|( i , _ )| x & (8 >> i) != 0).map(|( _ , c )| c).collect(),
+                _ => format!("{}", x)
+            }
+        }
+    }
+}
+
+/// Operands of form when instruction word is an encoding of it
+fn decode_form(form: &InstructionForm, word: u32, location: i64) -> Option<Vec<RiscVOperand>> {
+    let mut operands = Vec::<RiscVOperand>::new();
+
+    if word & form.mask != form.opcode {
+        return None
+    }
+    for kind in form.operands.iter() {
+        let register = match is_float(*kind) {
+            true => RegisterRiscV::Float(register_field(*kind, word)),
+            _ => RegisterRiscV::Integer(register_field(*kind, word))
+        };
+        let operand = match ( *kind , memory_base(*kind) , immediate_field(*kind) ) {
+            ( Rm , _ , _ ) if (word >> 12) & 7 == 7 => continue,
+            ( Rm , _ , _ ) => RiscVOperand::Rounding(((word >> 12) & 7) as u8),
+            ( _ , Some( b ) , Some( f ) ) => RiscVOperand::Memory(RegisterRiscV::Integer(register_field(b, word)), gather(&f, word)),
+            ( _ , Some( b ) , None ) => RiscVOperand::Memory(RegisterRiscV::Integer(register_field(b, word)), 0),
+            ( _ , _ , Some( f ) ) if f.relative => RiscVOperand::Immediate(location + gather(&f, word)),
+            ( _ , _ , Some( f ) ) => RiscVOperand::Immediate(gather(&f, word)),
+            _ => RiscVOperand::Register(register)
+        };
+        operands.push(operand)
+    }
+
+    /* Register and immediate restrictions of form are checked by encoding operands again */
+    match operands_match(form, &operands) && encode_form(form, &operands, location) == Ok(word) {
+        true => Some(operands),
+        _ => None
+    }
+}
+
+/// Decode instruction at start of bytes to assembler syntax and its length. Location is address of instruction for relative jumps.
+/// Compressed instructions are decoded with their own mnemonic like 'c.addi'.
+pub fn decode_instruction_risc_v(code: &[u8], location: i64, flags: CpuFlags) -> Result<(Box<String>, usize), Box<String>> {
+    let enabled = implied_flags(flags);
+    let length = match code.first() {
+        Some( x ) if x & 3 != 3 => 2,
+        _ => 4
+    };
 
-fn encode_instruction_risc_v(instruction: Box<String>, operands: Box<Vec<Box<String>>>, flags: CpuFlags) -> Result<Box<Vec<u8>>, Box<String>> {
+    if code.len() < length {
+        return Err(Box::new(String::from("Instruction is incomplete")))
+    }
 
-    Ok(Box::new(Vec::<u8>::new()))
+    let word = match length {
+        2 => u16::from_le_bytes([ code[0], code[1] ]) as u32,
+        _ => u32::from_le_bytes([ code[0], code[1], code[2], code[3] ])
+    };
+
+    for form in INSTRUCTIONS_RISC_V.iter() {
+        if form_length(form) != length || ( flags != 0 && form.flags & !enabled != 0 ) {
+            continue
+        }
+        if let Some( operands ) = decode_form(form, word, location) {
+            let text : Vec<String> = form.operands.iter().zip(operands.iter()).map(|( k , o )| operand_text_risc_v(*k, o)).collect();
+            let mnemonic = form.mnemonic.to_lowercase();
+            return match text.is_empty() {
+                true => Ok( ( Box::new(mnemonic) , length ) ),
+                _ => Ok( ( Box::new(format!("{} {}", mnemonic, text.join(", "))) , length ) )
+            }
+        }
+    }
+
+    match length {
+        2 => Err(Box::new(format!("Unknown instruction {:04x}", word))),
+        _ => Err(Box::new(format!("Unknown instruction {:08x}", word)))
+    }
+}
+
+/// Listing of machine code with address, bytes and instruction on each line. Unknown bytes are listed as 'DW'.
+pub fn listing_risc_v(code: &[u8], location: i64) -> String {
+    let mut text = String::new();
+    let mut index = 0;
+
+    while index < code.len() {
+        let ( instruction , length ) = match decode_instruction_risc_v(&code[index ..], location + index as i64, 0) {
+            Ok( x ) => ( *x.0 , x.1 ),
+            _ => {
+                let length = ( code.len() - index ).min(2);
+                ( format!("DW 0x{:x}", code[index .. index + length].iter().rev().fold(0u32, |v, x| (v << 8) | *x as u32)) , length )
+            }
+        };
+        let bytes : Vec<String> = code[index .. index + length].iter().map(|x| format!("{:02X}", x)).collect();
+        text.push_str(format!("{:08X}  {:<12} {}\r\n", location + index as i64, bytes.join(" "), instruction).as_str());
+        index += length
+    }
+
+    text
 }
 
-fn decode_instruction_risc_v(code: Box<Vec<u8>>, flags: CpuFlags) -> Result<Box<String>, Box<String>>{
-    Ok(Box::new(String::new()))
-}
\ No newline at end of file
+// Unittests for instruction set for Risc V module
+
+#[cfg(test)]
+mod tests {
+    use crate::riscv_instruction_set_neo::{ RiscVOperand, RegisterRiscV, register_risc_v, register_name_risc_v, rounding_risc_v, csr_risc_v, fence_set_risc_v, encode_instruction_risc_v, decode_instruction_risc_v, listing_risc_v, implied_flags, CpuFlags, CPU_RV64I, CPU_M, CPU_A, CPU_F, CPU_D, CPU_RV64GC };
+    use crate::diagnostics::{ E_ASSEMBLER_CPU_FLAG, E_ASSEMBLER_OPERAND, E_ASSEMBLER_INSTRUCTION };
+
+    /// Operand from assembler text like the inline assembler gives after evaluating expressions
+    fn operand(text: &str) -> RiscVOperand {
+        let text = text.trim();
+
+        if let Some( i ) = text.find('(') {
+            let offset = match &text[.. i] {
+                "" => 0,
+                x => x.parse::<i64>().unwrap()
+            };
+            return RiscVOperand::Memory(register_risc_v(text[i + 1 .. text.len() - 1].trim()).unwrap(), offset)
+        }
+
+        match ( register_risc_v(text) , rounding_risc_v(text) , csr_risc_v(text) , fence_set_risc_v(text) ) {
+            ( Some( x ) , _ , _ , _ ) => RiscVOperand::Register(x),
+            ( _ , Some( x ) , _ , _ ) => RiscVOperand::Rounding(x),
+            ( _ , _ , Some( x ) , _ ) => RiscVOperand::Immediate(x as i64),
+            ( _ , _ , _ , Some( x ) ) => RiscVOperand::Immediate(x as i64),
+            _ => {
+                match ( text.strip_prefix("0x") , text.strip_prefix("-0x") ) {
+                    ( Some( h ) , _ ) => RiscVOperand::Immediate(u64::from_str_radix(h, 16).unwrap() as i64),
+                    ( _ , Some( h ) ) => RiscVOperand::Immediate(-i64::from_str_radix(h, 16).unwrap()),
+                    _ => RiscVOperand::Immediate(text.parse::<i64>().unwrap())
+                }
+            }
+        }
+    }
+
+    fn parse(text: &str) -> ( String , Vec<RiscVOperand> ) {
+        match text.find(' ') {
+            Some( i ) => ( String::from(&text[.. i]) , text[i + 1 ..].split(',').map(operand).collect() ),
+            None => ( String::from(text) , Vec::new() )
+        }
+    }
+
+    fn assemble(text: &str, location: i64, flags: CpuFlags) -> Result<Vec<u8>, String> {
+        let ( mnemonic , operands ) = parse(text);
+
+        match encode_instruction_risc_v(Box::new(mnemonic), Box::new(operands), location, flags) {
+            Ok( x ) => Ok(*x),
+            Err( x ) => Err(String::from(x.code))
+        }
+    }
+
+    /// Encodings are checked against llvm-mc, and decoded text must assemble to the same bytes again
+    fn check(cases: &[( &str , &[u8] )], location: i64, flags: CpuFlags) {
+        for ( text , expected ) in cases.iter() {
+            let code = assemble(text, location, flags).unwrap_or_else(|x| panic!("{}: {}", text, x));
+            assert_eq!(code.as_slice(), *expected, "{}", text);
+
+            let mut index = 0;
+            while index < code.len() {
+                let ( decoded , length ) = decode_instruction_risc_v(&code[index ..], location + index as i64, flags).unwrap_or_else(|x| panic!("{}: {}", text, x));
+                let again = assemble(decoded.as_str(), location + index as i64, flags).unwrap_or_else(|x| panic!("{}: {}", decoded, x));
+                assert_eq!(again.as_slice(), &code[index .. index + length], "{} decoded as {}", text, decoded);
+                index += length
+            }
+        }
+    }
+
+    fn error(text: &str, flags: CpuFlags) -> String {
+        match assemble(text, 0, flags) {
+            Err( x ) => x,
+            Ok( x ) => panic!("{} encoded as {:?}", text, x)
+        }
+    }
+
+    #[test]
+    fn register_names() {
+        assert_eq!(register_risc_v("zero"), Some( RegisterRiscV::Integer(0) ));
+        assert_eq!(register_risc_v("SP"), Some( RegisterRiscV::Integer(2) ));
+        assert_eq!(register_risc_v("fp"), Some( RegisterRiscV::Integer(8) ));
+        assert_eq!(register_risc_v("s0"), Some( RegisterRiscV::Integer(8) ));
+        assert_eq!(register_risc_v("a0"), Some( RegisterRiscV::Integer(10) ));
+        assert_eq!(register_risc_v("t6"), Some( RegisterRiscV::Integer(31) ));
+        assert_eq!(register_risc_v("x31"), Some( RegisterRiscV::Integer(31) ));
+        assert_eq!(register_risc_v("f7"), Some( RegisterRiscV::Float(7) ));
+        assert_eq!(register_risc_v("fa0"), Some( RegisterRiscV::Float(10) ));
+        assert_eq!(register_risc_v("ft11"), Some( RegisterRiscV::Float(31) ));
+        assert_eq!(register_risc_v("x32"), None);
+        assert_eq!(register_risc_v("x01"), None);
+        assert_eq!(register_risc_v("a8"), None);
+        assert_eq!(register_name_risc_v(&RegisterRiscV::Integer(1)), "ra");
+        assert_eq!(register_name_risc_v(&RegisterRiscV::Float(9)), "fs1");
+        assert_eq!(rounding_risc_v("RTZ"), Some( 1 ));
+        assert_eq!(rounding_risc_v("dyn"), Some( 7 ));
+        assert_eq!(csr_risc_v("mstatus"), Some( 0x300 ));
+        assert_eq!(fence_set_risc_v("iorw"), Some( 15 ));
+        assert_eq!(fence_set_risc_v("rr"), None);
+    }
+
+    #[test]
+    fn cpu_flags_implied() {
+        assert_eq!(implied_flags(CPU_D), CPU_D | CPU_F);
+        assert_eq!(implied_flags(CPU_RV64GC), CPU_RV64GC);
+        assert_eq!(implied_flags(CPU_RV64I), CPU_RV64I);
+    }
+
+    #[test]
+    fn encode_base_integer() {
+        check(&[
+            ( "lui a0, 18", &[ 0x37, 0x25, 0x01, 0x00 ] ),
+            ( "auipc t0, 1048575", &[ 0x97, 0xf2, 0xff, 0xff ] ),
+            ( "auipc t0, -1", &[ 0x97, 0xf2, 0xff, 0xff ] ),
+            ( "addi a0, a1, -2048", &[ 0x13, 0x85, 0x05, 0x80 ] ),
+            ( "slti t1, t2, 5", &[ 0x13, 0xa3, 0x53, 0x00 ] ),
+            ( "sltiu s0, s1, 2047", &[ 0x13, 0xb4, 0xf4, 0x7f ] ),
+            ( "xori a2, a3, -1", &[ 0x13, 0xc6, 0xf6, 0xff ] ),
+            ( "ori a4, a5, 255", &[ 0x13, 0xe7, 0xf7, 0x0f ] ),
+            ( "andi a6, a7, 15", &[ 0x13, 0xf8, 0xf8, 0x00 ] ),
+            ( "slli a0, a1, 63", &[ 0x13, 0x95, 0xf5, 0x03 ] ),
+            ( "srli a0, a1, 1", &[ 0x13, 0xd5, 0x15, 0x00 ] ),
+            ( "srai s2, s3, 40", &[ 0x13, 0xd9, 0x89, 0x42 ] ),
+            ( "add s4, s5, s6", &[ 0x33, 0x8a, 0x6a, 0x01 ] ),
+            ( "sub s7, s8, s9", &[ 0xb3, 0x0b, 0x9c, 0x41 ] ),
+            ( "sll s10, s11, t3", &[ 0x33, 0x9d, 0xcd, 0x01 ] ),
+            ( "slt x29, x30, x31", &[ 0xb3, 0x2e, 0xff, 0x01 ] ),
+            ( "sltu a0, zero, a1", &[ 0x33, 0x35, 0xb0, 0x00 ] ),
+            ( "xor a0, a1, a2", &[ 0x33, 0xc5, 0xc5, 0x00 ] ),
+            ( "srl a0, a1, a2", &[ 0x33, 0xd5, 0xc5, 0x00 ] ),
+            ( "sra a0, a1, a2", &[ 0x33, 0xd5, 0xc5, 0x40 ] ),
+            ( "or a0, a1, a2", &[ 0x33, 0xe5, 0xc5, 0x00 ] ),
+            ( "and a0, a1, a2", &[ 0x33, 0xf5, 0xc5, 0x00 ] ),
+            ( "addiw a0, a1, -1", &[ 0x1b, 0x85, 0xf5, 0xff ] ),
+            ( "slliw a0, a1, 31", &[ 0x1b, 0x95, 0xf5, 0x01 ] ),
+            ( "srliw a0, a1, 3", &[ 0x1b, 0xd5, 0x35, 0x00 ] ),
+            ( "sraiw a0, a1, 3", &[ 0x1b, 0xd5, 0x35, 0x40 ] ),
+            ( "addw a0, a1, a2", &[ 0x3b, 0x85, 0xc5, 0x00 ] ),
+            ( "subw a0, a1, a2", &[ 0x3b, 0x85, 0xc5, 0x40 ] ),
+            ( "sllw a0, a1, a2", &[ 0x3b, 0x95, 0xc5, 0x00 ] ),
+            ( "srlw a0, a1, a2", &[ 0x3b, 0xd5, 0xc5, 0x00 ] ),
+            ( "sraw a0, a1, a2", &[ 0x3b, 0xd5, 0xc5, 0x40 ] ),
+            ( "fence rw, w", &[ 0x0f, 0x00, 0x10, 0x03 ] ),
+            ( "fence.i", &[ 0x0f, 0x10, 0x00, 0x00 ] ),
+            ( "ecall", &[ 0x73, 0x00, 0x00, 0x00 ] ),
+            ( "ebreak", &[ 0x73, 0x00, 0x10, 0x00 ] ),
+            ( "mret", &[ 0x73, 0x00, 0x20, 0x30 ] ),
+            ( "sret", &[ 0x73, 0x00, 0x20, 0x10 ] ),
+            ( "wfi", &[ 0x73, 0x00, 0x50, 0x10 ] ),
+            ( "csrrw a0, mstatus, a1", &[ 0x73, 0x95, 0x05, 0x30 ] ),
+            ( "csrrs a0, 0x7c0, zero", &[ 0x73, 0x25, 0x00, 0x7c ] ),
+            ( "csrrc a0, fcsr, a1", &[ 0x73, 0xb5, 0x35, 0x00 ] ),
+            ( "csrrwi a0, mtvec, 31", &[ 0x73, 0xd5, 0x5f, 0x30 ] ),
+            ( "csrrsi a0, sstatus, 1", &[ 0x73, 0xe5, 0x00, 0x10 ] ),
+            ( "csrrci zero, mie, 8", &[ 0x73, 0x70, 0x44, 0x30 ] )
+        ], 0, CPU_RV64I);
+    }
+
+    #[test]
+    fn encode_loads_and_stores() {
+        check(&[
+            ( "lb a0, -1(a1)", &[ 0x03, 0x85, 0xf5, 0xff ] ),
+            ( "lh a0, 2(a1)", &[ 0x03, 0x95, 0x25, 0x00 ] ),
+            ( "lw a0, 2047(a1)", &[ 0x03, 0xa5, 0xf5, 0x7f ] ),
+            ( "ld a0, -2048(sp)", &[ 0x03, 0x35, 0x01, 0x80 ] ),
+            ( "lbu a0, 0(a1)", &[ 0x03, 0xc5, 0x05, 0x00 ] ),
+            ( "lhu a0, (a1)", &[ 0x03, 0xd5, 0x05, 0x00 ] ),
+            ( "lwu a0, 4(a1)", &[ 0x03, 0xe5, 0x45, 0x00 ] ),
+            ( "sb a0, -1(a1)", &[ 0xa3, 0x8f, 0xa5, 0xfe ] ),
+            ( "sh a0, 2(a1)", &[ 0x23, 0x91, 0xa5, 0x00 ] ),
+            ( "sw a0, 2047(a1)", &[ 0xa3, 0xaf, 0xa5, 0x7e ] ),
+            ( "sd ra, -8(sp)", &[ 0x23, 0x3c, 0x11, 0xfe ] ),
+            ( "flw fa0, 4(a0)", &[ 0x07, 0x25, 0x45, 0x00 ] ),
+            ( "fsw fa0, -4(sp)", &[ 0x27, 0x2e, 0xa1, 0xfe ] ),
+            ( "fld ft0, 8(s0)", &[ 0x07, 0x30, 0x84, 0x00 ] ),
+            ( "fsd fs11, 2040(t6)", &[ 0x27, 0xbc, 0xbf, 0x7f ] )
+        ], 0, CPU_D);
+    }
+
+    #[test]
+    fn encode_jumps_and_branches() {
+        check(&[
+            ( "jal ra, 0x1800", &[ 0xef, 0x00, 0x10, 0x00 ] ),
+            ( "jal zero, 0xffc", &[ 0x6f, 0xf0, 0xdf, 0xff ] ),
+            ( "jalr ra, 16(a0)", &[ 0xe7, 0x00, 0x05, 0x01 ] ),
+            ( "jalr ra, a0, 16", &[ 0xe7, 0x00, 0x05, 0x01 ] ),
+            ( "beq a0, a1, 0x1010", &[ 0x63, 0x08, 0xb5, 0x00 ] ),
+            ( "bne a0, zero, 0", &[ 0x63, 0x10, 0x05, 0x80 ] ),
+            ( "blt t0, t1, 0x1ffe", &[ 0xe3, 0xcf, 0x62, 0x7e ] ),
+            ( "bge a0, a1, 0xffe", &[ 0xe3, 0x5f, 0xb5, 0xfe ] ),
+            ( "bltu a0, a1, 0x1008", &[ 0x63, 0x64, 0xb5, 0x00 ] ),
+            ( "bgeu a0, a1, 0x1008", &[ 0x63, 0x74, 0xb5, 0x00 ] )
+        ], 0x1000, CPU_RV64I);
+    }
+
+    #[test]
+    fn encode_multiply_and_atomic() {
+        check(&[
+            ( "mul a0, a1, a2", &[ 0x33, 0x85, 0xc5, 0x02 ] ),
+            ( "mulh a0, a1, a2", &[ 0x33, 0x95, 0xc5, 0x02 ] ),
+            ( "mulhsu a0, a1, a2", &[ 0x33, 0xa5, 0xc5, 0x02 ] ),
+            ( "mulhu a0, a1, a2", &[ 0x33, 0xb5, 0xc5, 0x02 ] ),
+            ( "div a0, a1, a2", &[ 0x33, 0xc5, 0xc5, 0x02 ] ),
+            ( "divu a0, a1, a2", &[ 0x33, 0xd5, 0xc5, 0x02 ] ),
+            ( "rem a0, a1, a2", &[ 0x33, 0xe5, 0xc5, 0x02 ] ),
+            ( "remu a0, a1, a2", &[ 0x33, 0xf5, 0xc5, 0x02 ] ),
+            ( "mulw a0, a1, a2", &[ 0x3b, 0x85, 0xc5, 0x02 ] ),
+            ( "divw a0, a1, a2", &[ 0x3b, 0xc5, 0xc5, 0x02 ] ),
+            ( "divuw a0, a1, a2", &[ 0x3b, 0xd5, 0xc5, 0x02 ] ),
+            ( "remw a0, a1, a2", &[ 0x3b, 0xe5, 0xc5, 0x02 ] ),
+            ( "remuw a0, a1, a2", &[ 0x3b, 0xf5, 0xc5, 0x02 ] ),
+            ( "lr.w a0, (a1)", &[ 0x2f, 0xa5, 0x05, 0x10 ] ),
+            ( "lr.d.aq a0, (a1)", &[ 0x2f, 0xb5, 0x05, 0x14 ] ),
+            ( "sc.w.rl a0, a2, (a1)", &[ 0x2f, 0xa5, 0xc5, 0x1a ] ),
+            ( "sc.d.aqrl a0, a2, (a1)", &[ 0x2f, 0xb5, 0xc5, 0x1e ] ),
+            ( "amoswap.w a0, a2, (a1)", &[ 0x2f, 0xa5, 0xc5, 0x08 ] ),
+            ( "amoadd.d.aq a0, a2, (a1)", &[ 0x2f, 0xb5, 0xc5, 0x04 ] ),
+            ( "amoxor.w a0, a2, (a1)", &[ 0x2f, 0xa5, 0xc5, 0x20 ] ),
+            ( "amoand.d a0, a2, (a1)", &[ 0x2f, 0xb5, 0xc5, 0x60 ] ),
+            ( "amoor.w.aqrl a0, a2, (a1)", &[ 0x2f, 0xa5, 0xc5, 0x46 ] ),
+            ( "amomin.d a0, a2, 0(a1)", &[ 0x2f, 0xb5, 0xc5, 0x80 ] ),
+            ( "amomax.w a0, a2, (a1)", &[ 0x2f, 0xa5, 0xc5, 0xa0 ] ),
+            ( "amominu.d.rl a0, a2, (a1)", &[ 0x2f, 0xb5, 0xc5, 0xc2 ] ),
+            ( "amomaxu.w a0, a2, (a1)", &[ 0x2f, 0xa5, 0xc5, 0xe0 ] )
+        ], 0, CPU_M | CPU_A);
+    }
+
+    #[test]
+    fn encode_floating_point() {
+        check(&[
+            ( "fadd.s fa0, fa1, fa2", &[ 0x53, 0xf5, 0xc5, 0x00 ] ),
+            ( "fsub.d fa0, fa1, fa2, rtz", &[ 0x53, 0x95, 0xc5, 0x0a ] ),
+            ( "fmul.s ft0, ft1, ft2, rne", &[ 0x53, 0x80, 0x20, 0x10 ] ),
+            ( "fdiv.d ft0, ft1, ft2", &[ 0x53, 0xf0, 0x20, 0x1a ] ),
+            ( "fsqrt.s fa0, fa1", &[ 0x53, 0xf5, 0x05, 0x58 ] ),
+            ( "fsqrt.d fa0, fa1, rup", &[ 0x53, 0xb5, 0x05, 0x5a ] ),
+            ( "fsgnj.d fa0, fa1, fa2", &[ 0x53, 0x85, 0xc5, 0x22 ] ),
+            ( "fsgnjn.s fa0, fa1, fa2", &[ 0x53, 0x95, 0xc5, 0x20 ] ),
+            ( "fsgnjx.d fa0, fa1, fa2", &[ 0x53, 0xa5, 0xc5, 0x22 ] ),
+            ( "fmin.s fa0, fa1, fa2", &[ 0x53, 0x85, 0xc5, 0x28 ] ),
+            ( "fmax.d fa0, fa1, fa2", &[ 0x53, 0x95, 0xc5, 0x2a ] ),
+            ( "feq.d a0, fa1, fa2", &[ 0x53, 0xa5, 0xc5, 0xa2 ] ),
+            ( "flt.s a0, fa1, fa2", &[ 0x53, 0x95, 0xc5, 0xa0 ] ),
+            ( "fle.d a0, fa1, fa2", &[ 0x53, 0x85, 0xc5, 0xa2 ] ),
+            ( "fclass.s a0, fa1", &[ 0x53, 0x95, 0x05, 0xe0 ] ),
+            ( "fclass.d a0, fa1", &[ 0x53, 0x95, 0x05, 0xe2 ] ),
+            ( "fcvt.w.d a0, fa0, rtz", &[ 0x53, 0x15, 0x05, 0xc2 ] ),
+            ( "fcvt.wu.s a0, fa0", &[ 0x53, 0x75, 0x15, 0xc0 ] ),
+            ( "fcvt.l.d a0, fa0, rdn", &[ 0x53, 0x25, 0x25, 0xc2 ] ),
+            ( "fcvt.lu.d a0, fa0", &[ 0x53, 0x75, 0x35, 0xc2 ] ),
+            ( "fcvt.d.w fa0, a0", &[ 0x53, 0x05, 0x05, 0xd2 ] ),
+            ( "fcvt.s.lu fa0, a0, rmm", &[ 0x53, 0x45, 0x35, 0xd0 ] ),
+            ( "fcvt.s.d fa0, fa1", &[ 0x53, 0xf5, 0x15, 0x40 ] ),
+            ( "fcvt.d.s fa0, fa1", &[ 0x53, 0x85, 0x05, 0x42 ] ),
+            ( "fmv.x.w a0, fa0", &[ 0x53, 0x05, 0x05, 0xe0 ] ),
+            ( "fmv.w.x fa0, a0", &[ 0x53, 0x05, 0x05, 0xf0 ] ),
+            ( "fmv.x.d a0, fa0", &[ 0x53, 0x05, 0x05, 0xe2 ] ),
+            ( "fmv.d.x fa0, a0", &[ 0x53, 0x05, 0x05, 0xf2 ] ),
+            ( "fmadd.s fa0, fa1, fa2, fa3", &[ 0x43, 0xf5, 0xc5, 0x68 ] ),
+            ( "fmsub.d fa0, fa1, fa2, fa3, rtz", &[ 0x47, 0x95, 0xc5, 0x6a ] ),
+            ( "fnmsub.s fa0, fa1, fa2, fa3", &[ 0x4b, 0xf5, 0xc5, 0x68 ] ),
+            ( "fnmadd.d ft11, ft10, ft9, ft8", &[ 0xcf, 0x7f, 0xdf, 0xe3 ] )
+        ], 0, CPU_D);
+    }
+
+    #[test]
+    fn encode_compressed() {
+        check(&[
+            ( "c.addi4spn a0, sp, 16", &[ 0x08, 0x08 ] ),
+            ( "c.fld fa0, 8(a1)", &[ 0x88, 0x25 ] ),
+            ( "c.lw a0, 4(a1)", &[ 0xc8, 0x41 ] ),
+            ( "c.ld s0, 248(s1)", &[ 0xe0, 0x7c ] ),
+            ( "c.fsd fa5, 0(a5)", &[ 0x9c, 0xa3 ] ),
+            ( "c.sw a0, 124(a1)", &[ 0xe8, 0xdd ] ),
+            ( "c.sd a0, 8(a1)", &[ 0x88, 0xe5 ] ),
+            ( "c.nop", &[ 0x01, 0x00 ] ),
+            ( "c.addi a0, -32", &[ 0x01, 0x15 ] ),
+            ( "c.addiw a0, 31", &[ 0x7d, 0x25 ] ),
+            ( "c.li s11, -1", &[ 0xfd, 0x5d ] ),
+            ( "c.addi16sp sp, -512", &[ 0x01, 0x71 ] ),
+            ( "c.lui a0, 1048575", &[ 0x7d, 0x75 ] ),
+            ( "c.lui t0, 31", &[ 0xfd, 0x62 ] ),
+            ( "c.srli a0, 63", &[ 0x7d, 0x91 ] ),
+            ( "c.srai a5, 1", &[ 0x85, 0x87 ] ),
+            ( "c.andi a1, -1", &[ 0xfd, 0x99 ] ),
+            ( "c.sub a0, a1", &[ 0x0d, 0x8d ] ),
+            ( "c.xor a0, a1", &[ 0x2d, 0x8d ] ),
+            ( "c.or a0, a1", &[ 0x4d, 0x8d ] ),
+            ( "c.and a0, a1", &[ 0x6d, 0x8d ] ),
+            ( "c.subw a0, a1", &[ 0x0d, 0x9d ] ),
+            ( "c.addw a0, a1", &[ 0x2d, 0x9d ] ),
+            ( "c.j 0x800", &[ 0x01, 0xb0 ] ),
+            ( "c.beqz a0, 0x10fe", &[ 0x7d, 0xcd ] ),
+            ( "c.bnez s1, 0xf00", &[ 0x81, 0xf0 ] ),
+            ( "c.slli t6, 1", &[ 0x86, 0x0f ] ),
+            ( "c.fldsp fa0, 504(sp)", &[ 0x7e, 0x35 ] ),
+            ( "c.lwsp a0, 252(sp)", &[ 0x7e, 0x55 ] ),
+            ( "c.ldsp ra, 8(sp)", &[ 0xa2, 0x60 ] ),
+            ( "c.jr ra", &[ 0x82, 0x80 ] ),
+            ( "c.mv a0, a1", &[ 0x2e, 0x85 ] ),
+            ( "c.ebreak", &[ 0x02, 0x90 ] ),
+            ( "c.jalr a0", &[ 0x02, 0x95 ] ),
+            ( "c.add a0, s11", &[ 0x6e, 0x95 ] ),
+            ( "c.fsdsp fa0, 8(sp)", &[ 0x2a, 0xa4 ] ),
+            ( "c.swsp zero, 4(sp)", &[ 0x02, 0xc2 ] ),
+            ( "c.sdsp ra, 504(sp)", &[ 0x86, 0xff ] )
+        ], 0x1000, CPU_RV64GC);
+    }
+
+    #[test]
+    fn encode_compressed_automatically() {
+        check(&[
+            ( "addi sp, sp, 16", &[ 0x41, 0x01 ] ),
+            ( "addi sp, sp, -64", &[ 0x39, 0x71 ] ),
+            ( "addi a0, sp, 8", &[ 0x28, 0x00 ] ),
+            ( "addi a0, zero, 5", &[ 0x15, 0x45 ] ),
+            ( "addi a0, a1, 0", &[ 0x2e, 0x85 ] ),
+            ( "add a0, zero, a1", &[ 0x2e, 0x85 ] ),
+            ( "add a0, a0, a1", &[ 0x2e, 0x95 ] ),
+            ( "ld ra, 8(sp)", &[ 0xa2, 0x60 ] ),
+            ( "sd s0, 0(sp)", &[ 0x22, 0xe0 ] ),
+            ( "lw a0, 4(a1)", &[ 0xc8, 0x41 ] ),
+            ( "ld a0, 8(a5)", &[ 0x88, 0x67 ] ),
+            ( "jalr zero, ra, 0", &[ 0x82, 0x80 ] ),
+            ( "jal zero, 0x1064", &[ 0x95, 0xa0 ] ),
+            ( "beq a0, zero, 0x1040", &[ 0x21, 0xc1 ] ),
+            ( "bne a1, zero, 0xffe", &[ 0xfd, 0xfd ] ),
+            ( "lui a0, 1", &[ 0x05, 0x65 ] ),
+            ( "slli a0, a0, 3", &[ 0x0e, 0x05 ] ),
+            ( "andi s0, s0, 7", &[ 0x1d, 0x88 ] ),
+            ( "sub a2, a2, a3", &[ 0x15, 0x8e ] ),
+            ( "ebreak", &[ 0x02, 0x90 ] ),
+            ( "addi zero, zero, 0", &[ 0x01, 0x00 ] ),
+            ( "addi a0, a1, 1", &[ 0x13, 0x85, 0x15, 0x00 ] ),
+            ( "ld a0, 8(a6)", &[ 0x03, 0x35, 0x88, 0x00 ] )
+        ], 0x1000, CPU_RV64GC);
+    }
+
+    #[test]
+    fn encode_pseudo_instructions() {
+        check(&[
+            ( "li a0, 0", &[ 0x13, 0x05, 0x00, 0x00 ] ),
+            ( "li a0, 2047", &[ 0x13, 0x05, 0xf0, 0x7f ] ),
+            ( "li a0, -2048", &[ 0x13, 0x05, 0x00, 0x80 ] ),
+            ( "li a0, 0x12345", &[ 0x37, 0x25, 0x01, 0x00, 0x1b, 0x05, 0x55, 0x34 ] ),
+            ( "li a0, 0x1000", &[ 0x37, 0x15, 0x00, 0x00 ] ),
+            ( "li a0, 0x7fffffff", &[ 0x37, 0x05, 0x00, 0x80, 0x1b, 0x05, 0xf5, 0xff ] ),
+            ( "li a0, -0x80000000", &[ 0x37, 0x05, 0x00, 0x80 ] ),
+            ( "li a0, 0x80000000", &[ 0x13, 0x05, 0x10, 0x00, 0x13, 0x15, 0xf5, 0x01 ] ),
+            ( "li a0, 0x123456789", &[ 0x37, 0x25, 0x09, 0x00, 0x1b, 0x05, 0xb5, 0xa2, 0x13, 0x15, 0xd5, 0x00, 0x13, 0x05, 0x95, 0x78 ] ),
+            ( "li a0, 0x1234567890abcdef", &[
+                0x37, 0x75, 0x24, 0x00, 0x1b, 0x05, 0xd5, 0x8a, 0x13, 0x15, 0xf5, 0x00, 0x13, 0x05, 0x15, 0x89,
+                0x13, 0x15, 0xc5, 0x00, 0x13, 0x05, 0xd5, 0xab, 0x13, 0x15, 0xc5, 0x00, 0x13, 0x05, 0xf5, 0xde ] ),
+            ( "li t0, -0x1000000000", &[ 0x93, 0x02, 0xf0, 0xff, 0x93, 0x92, 0x42, 0x02 ] ),
+            ( "mv a0, a1", &[ 0x13, 0x85, 0x05, 0x00 ] ),
+            ( "ret", &[ 0x67, 0x80, 0x00, 0x00 ] ),
+            ( "nop", &[ 0x13, 0x00, 0x00, 0x00 ] ),
+            ( "not a2, a3", &[ 0x13, 0xc6, 0xf6, 0xff ] ),
+            ( "snez a0, a1", &[ 0x33, 0x35, 0xb0, 0x00 ] ),
+            ( "beqz a0, 0x1040", &[ 0x63, 0x00, 0x05, 0x04 ] ),
+            ( "j 0xffc", &[ 0x6f, 0xf0, 0xdf, 0xff ] ),
+            ( "jal 0x1800", &[ 0xef, 0x00, 0x10, 0x00 ] ),
+            ( "jr a0", &[ 0x67, 0x00, 0x05, 0x00 ] ),
+            ( "fence", &[ 0x0f, 0x00, 0xf0, 0x0f ] ),
+            ( "fmv.d fa0, fa1", &[ 0x53, 0x85, 0xb5, 0x22 ] ),
+            ( "la a0, 0x2345", &[ 0x17, 0x15, 0x00, 0x00, 0x13, 0x05, 0x55, 0x34 ] ),
+            ( "la a0, 0x800", &[ 0x17, 0x05, 0x00, 0x00, 0x13, 0x05, 0x05, 0x80 ] ),
+            ( "call 0x1800", &[ 0x97, 0x10, 0x00, 0x00, 0xe7, 0x80, 0x00, 0x80 ] ),
+            ( "call 0x1801000", &[ 0x97, 0x00, 0x80, 0x01, 0xe7, 0x80, 0x00, 0x00 ] ),
+            ( "tail 0x900", &[ 0x17, 0x03, 0x00, 0x00, 0x67, 0x00, 0x03, 0x90 ] )
+        ], 0x1000, CPU_D);
+        check(&[
+            ( "li a0, 0x12345", &[ 0x49, 0x65, 0x1b, 0x05, 0x55, 0x34 ] ),
+            ( "li a0, -1", &[ 0x7d, 0x55 ] ),
+            ( "mv a0, a1", &[ 0x2e, 0x85 ] ),
+            ( "ret", &[ 0x82, 0x80 ] ),
+            ( "call 0x1000", &[ 0x97, 0x00, 0x00, 0x00, 0xe7, 0x80, 0x00, 0x00 ] )
+        ], 0x1000, CPU_RV64GC);
+    }
+
+    #[test]
+    fn decode_instructions() {
+        let cases : &[( &[u8] , &str , usize )] = &[
+            ( &[ 0x13, 0x85, 0x05, 0x80 ], "addi a0, a1, -2048", 4 ),
+            ( &[ 0x63, 0x08, 0xb5, 0x00 ], "beq a0, a1, 0x1010", 4 ),
+            ( &[ 0x6f, 0xf0, 0xdf, 0xff ], "jal zero, 0xffc", 4 ),
+            ( &[ 0xe7, 0x00, 0x05, 0x01 ], "jalr ra, 16(a0)", 4 ),
+            ( &[ 0x23, 0x3c, 0x11, 0xfe ], "sd ra, -8(sp)", 4 ),
+            ( &[ 0x2f, 0xb5, 0x05, 0x14 ], "lr.d.aq a0, 0(a1)", 4 ),
+            ( &[ 0x53, 0x95, 0xc5, 0x0a ], "fsub.d fa0, fa1, fa2, rtz", 4 ),
+            ( &[ 0x53, 0xf5, 0xc5, 0x00 ], "fadd.s fa0, fa1, fa2", 4 ),
+            ( &[ 0x73, 0x95, 0x05, 0x30 ], "csrrw a0, mstatus, a1", 4 ),
+            ( &[ 0x0f, 0x00, 0x10, 0x03 ], "fence rw, w", 4 ),
+            ( &[ 0x37, 0x25, 0x01, 0x00 ], "lui a0, 18", 4 ),
+            ( &[ 0x41, 0x01, 0x00, 0x00 ], "c.addi sp, 16", 2 ),
+            ( &[ 0x7d, 0x75 ], "c.lui a0, -1", 2 ),
+            ( &[ 0x82, 0x80 ], "c.jr ra", 2 ),
+            ( &[ 0x2e, 0x85 ], "c.mv a0, a1", 2 ),
+            ( &[ 0x7d, 0xcd ], "c.beqz a0, 0x10fe", 2 )
+        ];
+
+        for ( code , text , length ) in cases.iter() {
+            let ( decoded , size ) = decode_instruction_risc_v(code, 0x1000, 0).unwrap();
+            assert_eq!(decoded.as_str(), *text);
+            assert_eq!(size, *length)
+        }
+
+        assert!(decode_instruction_risc_v(&[ 0x00, 0x00 ], 0, 0).is_err());
+        assert!(decode_instruction_risc_v(&[ 0xff, 0xff, 0xff, 0xff ], 0, 0).is_err());
+        assert!(decode_instruction_risc_v(&[ 0x13, 0x85 ], 0, 0).is_err());
+        assert!(decode_instruction_risc_v(&[ 0x41, 0x01 ], 0, CPU_D).is_err());
+    }
+
+    #[test]
+    fn listing_of_code() {
+        let text = listing_risc_v(&[ 0x41, 0x11, 0x13, 0x05, 0x10, 0x00, 0x00, 0x00 ], 0x10);
+        assert_eq!(text, "00000010  41 11        c.addi sp, -16\r\n00000012  13 05 10 00  addi a0, zero, 1\r\n00000016  00 00        DW 0x0\r\n")
+    }
+
+    #[test]
+    fn encode_errors() {
+        assert_eq!(error("addx a0, a1, a2", CPU_RV64GC), E_ASSEMBLER_INSTRUCTION);
+        assert_eq!(error("add a0, a1", CPU_RV64GC), E_ASSEMBLER_OPERAND);
+        assert_eq!(error("add a0, a1, fa2", CPU_RV64GC), E_ASSEMBLER_OPERAND);
+        assert_eq!(error("addi a0, a1, 2048", CPU_RV64GC), E_ASSEMBLER_OPERAND);
+        assert_eq!(error("beq a0, a1, 3", CPU_RV64GC), E_ASSEMBLER_OPERAND);
+        assert_eq!(error("slli a0, a1, 64", CPU_RV64GC), E_ASSEMBLER_OPERAND);
+        assert_eq!(error("lr.w a0, 4(a1)", CPU_RV64GC), E_ASSEMBLER_OPERAND);
+        assert_eq!(error("c.addi a0, 0", CPU_RV64GC), E_ASSEMBLER_OPERAND);
+        assert_eq!(error("c.lw a0, 4(a6)", CPU_RV64GC), E_ASSEMBLER_OPERAND);
+        assert_eq!(error("call 0x100000000", CPU_RV64GC), E_ASSEMBLER_OPERAND);
+        assert_eq!(error("mul a0, a1, a2", CPU_RV64I), E_ASSEMBLER_CPU_FLAG);
+        assert_eq!(error("amoadd.w a0, a1, (a2)", CPU_M), E_ASSEMBLER_CPU_FLAG);
+        assert_eq!(error("fadd.d fa0, fa1, fa2", CPU_F), E_ASSEMBLER_CPU_FLAG);
+        assert_eq!(error("c.nop", CPU_D), E_ASSEMBLER_CPU_FLAG);
+    }
+}
@@ -4,6 +4,7 @@
 // Scanner module for lexical analyzing of source files
 
 use std::string;
+use crate::diagnostics::{Diagnostic, DiagnosticMethods, E_INVALID_CHARACTER, E_MALFORMED_NUMBER, E_UNTERMINATED_COMMENT, E_UNTERMINATED_STRING};
 
 #[derive(Clone, PartialEq, Debug)]
 pub enum Symbols
@@ -37,6 +38,7 @@ pub enum Symbols
 	In(u32, u32),
 	Is(u32, u32),
 	Import(u32, u32),
+	Implements(u32, u32),
 	Loop(u32, u32),
 	Module(u32, u32),
 	Mod(u32, u32),
@@ -134,10 +136,11 @@ pub trait ScannerMethods
 	fn get_char(&mut self) -> char;
 	fn peek_char(&self) -> char;
 	fn get_start_position(&self) -> u32;
-	fn get_symbol(&mut self) -> Result<Symbols, Box<std::string::String>>;
-	fn peek_symbol(&mut self) -> Result<Symbols, Box<std::string::String>>;
+	fn get_position(&self) -> u32;
+	fn get_symbol(&mut self) -> Result<Symbols, Box<Diagnostic>>;
+	fn peek_symbol(&mut self) -> Result<Symbols, Box<Diagnostic>>;
 	fn is_reserved_keyword(&self, start : u32, end: u32, keyword: &str) -> Option<Symbols>;
-	fn is_string_or_character(&mut self) -> Result<Symbols, Box<std::string::String>>;
+	fn is_string_or_character(&mut self) -> Result<Symbols, Box<Diagnostic>>;
 	fn slice_assembler_code(&mut self) -> Box<Vec<char>>;
 }
 
@@ -187,8 +190,13 @@ impl ScannerMethods for Scanner
 		self.start_pos
 	}
 
+	/// Position just after the last character read, that is the end of the current symbol.
+	fn get_position(&self) -> u32 {
+		self.index
+	}
+
 	/// Get the next valid symbol in source file and return it to the parser
-	fn get_symbol(&mut self) -> Result<Symbols, Box<std::string::String>> {
+	fn get_symbol(&mut self) -> Result<Symbols, Box<Diagnostic>> {
 
 		/* Remove whitespace */
 		loop {
@@ -255,7 +263,7 @@ impl ScannerMethods for Scanner
 							}
 						}
 						if level != 0 {
-							return Err(Box::new(format!("Unterminated comment at position: '{}'", self.index)))
+							return Err(Diagnostic::error(E_UNTERMINATED_COMMENT, std::string::String::from("Unterminated comment"), self.start_pos, self.index))
 						}
 						self.get_symbol()
 					},
@@ -516,7 +524,7 @@ impl ScannerMethods for Scanner
 								'A' | 'B' | 'C' | 'D' | 'E' | 'F' => {
 									buffer.push(self.get_char());
 								},
-								_ => return Err(Box::new(format!("Need hex digit in hex integer at position: '{}'", self.index)))
+								_ => return Err(Diagnostic::error(E_MALFORMED_NUMBER, std::string::String::from("Need hex digit in hex integer"), self.start_pos, self.index))
 							}
 							loop {
 								match self.peek_char() {
@@ -531,7 +539,7 @@ impl ScannerMethods for Scanner
 											'0' | '1' | '2' | '3' | '4' | '5' | '6' | '7' | '8' | '9' |
 											'a' | 'b' | 'c' | 'd' | 'e' | 'f' |
 											'A' | 'B' | 'C' | 'D' | 'E' | 'F' => continue,
-											_ =>  return Err(Box::new(format!("Need hex digit in hex integer at position: '{}'", self.index)))
+											_ =>  return Err(Diagnostic::error(E_MALFORMED_NUMBER, std::string::String::from("Need hex digit in hex integer"), self.start_pos, self.index))
 										}
 									},
 									_ => break
@@ -542,7 +550,7 @@ impl ScannerMethods for Scanner
 						'b' => {
 							buffer.push(self.get_char());
 							if self.peek_char() != '0' && self.peek_char() != '1' && self.peek_char() != '`' {
-								return Err(Box::new(format!("Need '0' or '1' in binary integer at position: '{}'", self.index)))
+								return Err(Diagnostic::error(E_MALFORMED_NUMBER, std::string::String::from("Need '0' or '1' in binary integer"), self.start_pos, self.index))
 							}
 							loop {
 								match self.peek_char() {
@@ -553,7 +561,7 @@ impl ScannerMethods for Scanner
 									'`' => {
 										buffer.push(self.get_char());
 										if self.peek_char() != '0' && self.peek_char() != '1' {
-											return Err(Box::new(format!("Need '0' or '1' in binary integer at position: '{}'", self.index)))
+											return Err(Diagnostic::error(E_MALFORMED_NUMBER, std::string::String::from("Need '0' or '1' in binary integer"), self.start_pos, self.index))
 										}
 										continue
 									},
@@ -561,7 +569,7 @@ impl ScannerMethods for Scanner
 								}
 							}
 							if self.peek_char().is_ascii_digit() {
-								return Err(Box::new(format!("Found digits not in binary integer at position: '{}'", self.index)))
+								return Err(Diagnostic::error(E_MALFORMED_NUMBER, std::string::String::from("Found digits not in binary integer"), self.start_pos, self.index))
 							}
 							return Ok(Symbols::Integer(self.start_pos, self.index, Box::new(std::string::String::from(buffer.as_str()))))
 						},
@@ -591,7 +599,7 @@ impl ScannerMethods for Scanner
 								'0' | '1' | '2' | '3' | '4' | '5' | '6' | '7' | '8' | '9' |
 								'a' | 'b' | 'c' | 'd' | 'e' | 'f' |
 								'A' | 'B' | 'C' | 'D' | 'E' | 'F' => continue,
-								_ =>  return Err(Box::new(format!("Need digit in integer at position: '{}'", self.index)))
+								_ =>  return Err(Diagnostic::error(E_MALFORMED_NUMBER, std::string::String::from("Need digit in integer"), self.start_pos, self.index))
 							}
 						},
 						_ => break
@@ -609,7 +617,7 @@ impl ScannerMethods for Scanner
 					'.' => {
 						buffer.push(self.get_char());
 						if !self.peek_char().is_ascii_digit() {
-							return Err(Box::new(format!("Need digit(s) after '.' in real number at position: '{}'", self.index)))
+							return Err(Diagnostic::error(E_MALFORMED_NUMBER, std::string::String::from("Need digit(s) after '.' in real number"), self.start_pos, self.index))
 						}
 						loop {
 							match self.peek_char() {
@@ -627,7 +635,7 @@ impl ScannerMethods for Scanner
 									buffer.push(self.get_char());
 								}
 								if !self.peek_char().is_ascii_digit() {
-									return Err(Box::new(format!("Need digit(s) after scale factor in real number at position: '{}'", self.index)))
+									return Err(Diagnostic::error(E_MALFORMED_NUMBER, std::string::String::from("Need digit(s) after scale factor in real number"), self.start_pos, self.index))
 								}
 								loop {
 									match self.peek_char() {
@@ -645,7 +653,7 @@ impl ScannerMethods for Scanner
 					},
 					_ => {
 						if found_hex {
-							return Err(Box::new(format!("Need 'H' at end of hex integer at position: '{}'", self.index)))
+							return Err(Diagnostic::error(E_MALFORMED_NUMBER, std::string::String::from("Need 'H' at end of hex integer"), self.start_pos, self.index))
 						}
 						Ok(Symbols::Integer(self.start_pos, self.index, Box::new(std::string::String::from(buffer.as_str()))))
 					}
@@ -701,11 +709,12 @@ impl ScannerMethods for Scanner
 			}
 		}
 
-		Err(Box::new(format!("Invalid symbol in source file at position: '{}'", self.index)))
+		let _ = self.get_char(); /* Skip invalid character, so that parser can continue after error */
+		Err(Diagnostic::error(E_INVALID_CHARACTER, std::string::String::from("Invalid symbol in source file"), self.start_pos, self.index))
 	}
 
 	/// Check next symbol in source code without advancing position in source code.
-	fn peek_symbol(&mut self) -> Result<Symbols, Box<string::String>> {
+	fn peek_symbol(&mut self) -> Result<Symbols, Box<Diagnostic>> {
 		let save_start_pos = self.index;
 		let symbol = self.get_symbol();
 		self.index = save_start_pos;
@@ -741,6 +750,7 @@ impl ScannerMethods for Scanner
 			"IN" 			=> Some(Symbols::In(start, end)),
 			"IS" 			=> Some(Symbols::Is(start, end)),
 			"IMPORT"		=> Some(Symbols::Import(start, end)),
+			"IMPLEMENTS"	=> Some(Symbols::Implements(start, end)),
 			"LOOP"			=> Some(Symbols::Loop(start, end)),
 			"MODULE"		=> Some(Symbols::Module(start, end)),
 			"MOD"			=> Some(Symbols::Mod(start, end)),
@@ -777,7 +787,7 @@ impl ScannerMethods for Scanner
 	}
 
 	/// Handling string or character
-	fn is_string_or_character(&mut self) -> Result<Symbols, Box<std::string::String>> {
+	fn is_string_or_character(&mut self) -> Result<Symbols, Box<Diagnostic>> {
 		let mut buffer = std::string::String::new();
 		let mut single_quote = true;
 
@@ -804,7 +814,7 @@ impl ScannerMethods for Scanner
 							}
 						},
 						'\0' => {
-							return Err(Box::new(format!("Unterminated string of type '\\\"' at position: '{}'", self.index)))
+							return Err(Diagnostic::error(E_UNTERMINATED_STRING, std::string::String::from("Unterminated string of type '\\\"'"), self.start_pos, self.index))
 						}
 						_ => {
 							buffer.push(self.get_char())
@@ -818,7 +828,7 @@ impl ScannerMethods for Scanner
 				loop {
 					match self.peek_char() {
 						'\0' => {
-							return Err(Box::new(format!("Unterminated string of type '\"' at position: '{}'", self.index)))
+							return Err(Diagnostic::error(E_UNTERMINATED_STRING, std::string::String::from("Unterminated string of type '\"'"), self.start_pos, self.index))
 						},
 						'"' => {
 							buffer.push(self.get_char() );
@@ -835,7 +845,7 @@ impl ScannerMethods for Scanner
 				loop {
 					match self.peek_char() {
 						'\0' => {
-							return Err(Box::new(format!("Unterminated string of type '\'' at position: '{}'", self.index)))
+							return Err(Diagnostic::error(E_UNTERMINATED_STRING, std::string::String::from("Unterminated string of type '\''"), self.start_pos, self.index))
 						},
 						'\'' => {
 							buffer.push(self.get_char() );
@@ -862,33 +872,30 @@ impl ScannerMethods for Scanner
 		}
 	}
 
+	/// Source text of inline assembler up to the 'END' closing it, scanner continues at 'END'
 	fn slice_assembler_code(&mut self) -> Box<Vec<char>> {
-		let start: usize = self.index as usize;
+		let start = self.index as usize;
 		let max = self.length() as usize;
-		let mut cur : usize = start;
-		let mut end = start;
+		let mut cur = start;
+
+		/* 'END' must stand alone, not be part of a longer name like 'SEND' or 'ENDIAN' */
+		let is_name = | c: char | c.is_ascii_alphanumeric() || c == '_';
 
 		loop {
-			if max - 3 > cur {
-				match ( self.buffer[cur as usize], self.buffer[(cur + 1) as usize], self.buffer[(cur + 2) as usize] ) {
-					( 'E' , 'N', 'D' ) => {
-						end = cur - 1;
-						break;
-					},
-					_ => {
-						cur += 1;
-					}
-				}
+			if cur + 3 > max {
+				cur = max;
+				break
 			}
-			else {
-				end = self.length() as usize - 1;
-				break;
+
+			match ( self.buffer[cur], self.buffer[cur + 1], self.buffer[cur + 2] ) {
+				( 'E' , 'N' , 'D' ) if ( cur == start || !is_name(self.buffer[cur - 1]) ) && ( cur + 3 == max || !is_name(self.buffer[cur + 3]) ) => break,
+				_ => cur += 1
 			}
 		}
 
 		self.index = cur as u32;
 
-		Box::new(self.buffer[ start ..= end].to_vec())
+		Box::new(self.buffer[ start .. cur ].to_vec())
 	}
 }
 
@@ -1051,6 +1058,23 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn reserved_keyword_implements() {
+		let mut scan = Box::new(Scanner::new("IMPLEMENTS"));
+		let symbol = scan.get_symbol();
+		match symbol {
+			Ok(x) => {
+				match x {
+					Symbols::Implements(s, e) => {
+						assert_eq!(s, 0);
+						assert_eq!(e, 10);
+					},
+					_ => assert!(false)
+				}
+			}, _ => assert!(false)
+		}
+	}
+
 	#[test]
 	fn reserved_keyword_do() {
 		let mut scan = Box::new(Scanner::new("DO"));
@@ -3332,4 +3356,46 @@ mod tests {
 		}
 	}
 
-}
\ No newline at end of file
+	#[test]
+	fn unterminated_string_diagnostic() {
+		let mut scan = Box::new(Scanner::new("  \"Missing end"));
+		let symbol = scan.get_symbol();
+		match symbol {
+			Err(x) => {
+				assert_eq!(x.code, crate::diagnostics::E_UNTERMINATED_STRING);
+				assert_eq!(x.span.start, 2)
+			},
+			_ => assert!(false)
+		}
+	}
+
+
+	#[test]
+	fn slice_assembler_code_until_end() {
+		let mut scan = Box::new(Scanner::new("CODE SEND ENDIAN END"));
+		let _ = scan.get_symbol();
+		let code = scan.slice_assembler_code();
+
+		assert_eq!(code.iter().collect::<String>(), String::from(" SEND ENDIAN "));
+		match scan.get_symbol() {
+			Ok(Symbols::End(s, e)) => {
+				assert_eq!(s, 17);
+				assert_eq!(e, 20)
+			},
+			_ => assert!(false)
+		}
+	}
+
+	#[test]
+	fn slice_assembler_code_without_end() {
+		let mut scan = Box::new(Scanner::new("CODE NOP"));
+		let _ = scan.get_symbol();
+		let code = scan.slice_assembler_code();
+
+		assert_eq!(code.iter().collect::<String>(), String::from(" NOP"));
+		match scan.get_symbol() {
+			Ok(Symbols::EndOfFile(x)) => assert_eq!(x, 8),
+			_ => assert!(false)
+		}
+	}
+}
@@ -1,20 +1,332 @@
-
 // ActiveOberon Compiler, a native ARM v8 & X86-64 compiler & Risc V / linker / builder utility.
 // Written by Richard Magnor Stenbro. Licensed under GPL v3
 // Symbol table module for compiling and linking of projects written in ActiveOberon language
 
+use crate::diagnostics::{Diagnostic, DiagnosticMethods, E_DUPLICATE_DECLARATION, E_INVALID_EXPORT, E_NOT_EXPORTED};
+use crate::parser::Node;
+use crate::scanner::Symbols;
+
+pub type ScopeId = usize;
+pub type EntryId = usize;
+
+/// Scope holding predeclared identifiers, parent of every module scope
+pub const UNIVERSE_SCOPE : ScopeId = 0;
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum ScopeKind {
+    Universe,
+    Module,
+    Procedure,
+    Operator,
+    Record,
+    Object,
+    Cell,
+    Enumeration,
+    Definition
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum SymbolKind {
+    Constant,
+    Type,
+    Variable,
+    Parameter,
+    Procedure,
+    Module
+}
+
+/// Export mark of declaration, '*' is read write and '-' is read only for client modules
+#[derive(Clone, PartialEq, Debug)]
+pub enum Export {
+    None,
+    ReadWrite,
+    ReadOnly
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct SymbolEntry {
+    pub name: String,
+    pub kind: SymbolKind,
+    pub export: Export,
+    pub start: u32,                 /* Span of identifier in declaration */
+    pub end: u32,
+    pub scope: ScopeId,             /* Scope this entry is declared in */
+    pub inner: Option<ScopeId>,     /* Scope opened by this entry, like procedure body or record fields */
+    pub used: bool
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Scope {
+    pub kind: ScopeKind,
+    pub name: String,
+    pub parent: Option<ScopeId>,
+    pub entries: Vec<EntryId>      /* In order of declaration */
+}
+
 pub trait SymbolTableMethods {
     fn new() -> Self;
+    fn open_scope(&mut self, kind: ScopeKind, name: &str) -> ScopeId;
+    fn close_scope(&mut self) -> ();
+    fn enter_scope(&mut self, scope: ScopeId) -> ();
+    fn current_scope(&self) -> ScopeId;
+    fn scope(&self, scope: ScopeId) -> &Scope;
+    fn scope_count(&self) -> usize;
+    fn entry(&self, entry: EntryId) -> &SymbolEntry;
+    fn declare(&mut self, name: &str, kind: SymbolKind, export: Export, start: u32, end: u32) -> Result<EntryId, Box<Diagnostic>>;
+    fn set_inner_scope(&mut self, entry: EntryId, scope: ScopeId) -> ();
+    fn lookup(&self, name: &str) -> Option<EntryId>;
+    fn lookup_local(&self, scope: ScopeId, name: &str) -> Option<EntryId>;
+    fn lookup_qualified(&self, scope: ScopeId, name: &str, start: u32, end: u32) -> Result<Option<EntryId>, Box<Diagnostic>>;
+    fn enclosing_module(&self, scope: ScopeId) -> ScopeId;
+    fn mark_used(&mut self, entry: EntryId) -> ();
 }
 
+/// Nested scopes of declarations, stored as arena of scopes and entries indexed by id
 pub struct SymbolTable {
+    scopes: Vec<Scope>,
+    entries: Vec<SymbolEntry>,
+    current: ScopeId
+}
 
+/// Name, export mark and span from identifier definition in parse tree
+pub fn identifier_definition(node: &Node) -> Option<(String, Export, u32, u32)> {
+    match node {
+        Node::Ident( _ , _ , symbol ) => {
+            match &**symbol {
+                Symbols::Ident( s , e , name ) => Some( ( *name.clone(), Export::None, *s, *e ) ),
+                _ => None
+            }
+        },
+        Node::IdentifierReadWrite( _ , _ , id , _ ) => {
+            identifier_definition(id).map(|( name , _ , s , e )| ( name, Export::ReadWrite, s, e ))
+        },
+        Node::IdentifierRead( _ , _ , id , _ ) => {
+            identifier_definition(id).map(|( name , _ , s , e )| ( name, Export::ReadOnly, s, e ))
+        },
+        _ => None
+    }
 }
 
 impl SymbolTableMethods for SymbolTable {
     fn new() -> Self {
-        SymbolTable {
+        let mut table = SymbolTable {
+            scopes: vec![ Scope { kind: ScopeKind::Universe, name: String::from("UNIVERSE"), parent: None, entries: Vec::new() } ],
+            entries: Vec::new(),
+            current: UNIVERSE_SCOPE
+        };
 
+        /* Predeclared types, both classic Oberon names and sized names */
+        for name in [ "BOOLEAN", "CHAR", "CHAR8", "CHAR16", "CHAR32", "SHORTINT", "INTEGER", "LONGINT", "HUGEINT", "REAL", "LONGREAL",
+                      "SET", "SIGNED8", "SIGNED16", "SIGNED32", "SIGNED64", "UNSIGNED8", "UNSIGNED16", "UNSIGNED32", "UNSIGNED64",
+                      "FLOAT32", "FLOAT64", "RANGE", "COMPLEX", "LONGCOMPLEX", "ANYTYPE" ] {
+            let _ = table.declare(name, SymbolKind::Type, Export::None, 0, 0);
         }
+
+        /* Predeclared procedures and functions */
+        for name in [ "ABS", "ASH", "ASSERT", "CAP", "CHR", "COPY", "DEC", "DISPOSE", "ENTIER", "ENTIERH", "EXCL", "HALT", "INC", "INCL",
+                      "LEN", "LONG", "LSH", "MAX", "MIN", "ODD", "ORD", "ROL", "ROR", "ROT", "SHORT", "SUM", "DIM", "FIRST", "LAST",
+                      "STEP", "RE", "IM", "INCR", "CAS", "TRACE", "SIZEOF" ] {
+            let _ = table.declare(name, SymbolKind::Procedure, Export::None, 0, 0);
+        }
+
+        table
+    }
+
+    /// Create new scope inside current scope and make it current
+    fn open_scope(&mut self, kind: ScopeKind, name: &str) -> ScopeId {
+        self.scopes.push( Scope { kind, name: String::from(name), parent: Some(self.current), entries: Vec::new() } );
+        self.current = self.scopes.len() - 1;
+        self.current
+    }
+
+    fn close_scope(&mut self) {
+        if let Some( x ) = self.scopes[self.current].parent {
+            self.current = x
+        }
+    }
+
+    /// Make an existing scope current again, used when walking tree a second time
+    fn enter_scope(&mut self, scope: ScopeId) {
+        self.current = scope
+    }
+
+    fn current_scope(&self) -> ScopeId {
+        self.current
+    }
+
+    fn scope(&self, scope: ScopeId) -> &Scope {
+        &self.scopes[scope]
+    }
+
+    fn scope_count(&self) -> usize {
+        self.scopes.len()
+    }
+
+    fn entry(&self, entry: EntryId) -> &SymbolEntry {
+        &self.entries[entry]
+    }
+
+    /// Add entry to current scope. Names must be unique within a scope and only module level and field declarations can be exported
+    fn declare(&mut self, name: &str, kind: SymbolKind, export: Export, start: u32, end: u32) -> Result<EntryId, Box<Diagnostic>> {
+        if let Some( x ) = self.lookup_local(self.current, name) {
+            let previous = &self.entries[x];
+            return Err(Diagnostic::error(E_DUPLICATE_DECLARATION, format!("'{}' is already declared in this scope", name), start, end)
+                .with_label(format!("Previous declaration of '{}' is here", name), previous.start, previous.end))
+        }
+
+        if export != Export::None {
+            match self.scopes[self.current].kind {
+                ScopeKind::Module | ScopeKind::Record | ScopeKind::Object | ScopeKind::Cell | ScopeKind::Enumeration | ScopeKind::Definition => (),
+                _ => return Err(Diagnostic::error(E_INVALID_EXPORT, format!("'{}' cannot be exported, only module level declarations and fields can have export mark", name), start, end))
+            }
+        }
+
+        self.entries.push( SymbolEntry {
+            name: String::from(name),
+            kind,
+            export,
+            start,
+            end,
+            scope: self.current,
+            inner: None,
+            used: false
+        } );
+
+        let id = self.entries.len() - 1;
+        self.scopes[self.current].entries.push(id);
+        Ok( id )
     }
-}
\ No newline at end of file
+
+    fn set_inner_scope(&mut self, entry: EntryId, scope: ScopeId) {
+        self.entries[entry].inner = Some(scope)
+    }
+
+    /// Find name in current scope or nearest enclosing scope, ending with predeclared identifiers
+    fn lookup(&self, name: &str) -> Option<EntryId> {
+        let mut scope = self.current;
+
+        loop {
+            match ( self.lookup_local(scope, name), self.scopes[scope].parent ) {
+                ( Some( entry ) , _ ) => return Some( entry ),
+                ( _ , Some( parent ) ) => scope = parent,
+                _ => return None
+            }
+        }
+    }
+
+    fn lookup_local(&self, scope: ScopeId, name: &str) -> Option<EntryId> {
+        self.scopes[scope].entries.iter().find(|x| self.entries[**x].name == name).copied()
+    }
+
+    /// Find member of module, record or object scope. Members of other modules are only visible when exported.
+    fn lookup_qualified(&self, scope: ScopeId, name: &str, start: u32, end: u32) -> Result<Option<EntryId>, Box<Diagnostic>> {
+        match self.lookup_local(scope, name) {
+            Some( x ) => {
+                let entry = &self.entries[x];
+                if entry.export == Export::None && self.enclosing_module(scope) != self.enclosing_module(self.current) {
+                    /* Declaration is in source of other module, so it is pointed out by a note and not a label */
+                    return Err(Diagnostic::error(E_NOT_EXPORTED, format!("'{}' is not exported from '{}'", name, self.scopes[scope].name), start, end)
+                        .with_note(format!("'{}' is declared without export mark in '{}'", name, self.scopes[self.enclosing_module(scope)].name)))
+                }
+                Ok( Some( x ) )
+            },
+            _ => Ok( None )
+        }
+    }
+
+    fn enclosing_module(&self, scope: ScopeId) -> ScopeId {
+        let mut current = scope;
+
+        loop {
+            match ( &self.scopes[current].kind, self.scopes[current].parent ) {
+                ( ScopeKind::Module , _ ) | ( _ , None ) => return current,
+                ( _ , Some( x ) ) => current = x
+            }
+        }
+    }
+
+    fn mark_used(&mut self, entry: EntryId) {
+        self.entries[entry].used = true
+    }
+}
+
+// Unittests for symbol table module
+
+#[cfg(test)]
+mod tests {
+    use crate::diagnostics::{E_DUPLICATE_DECLARATION, E_INVALID_EXPORT, E_NOT_EXPORTED};
+    use crate::symbol_table::{SymbolTable, SymbolTableMethods, ScopeKind, SymbolKind, Export, UNIVERSE_SCOPE};
+
+    #[test]
+    fn universe_scope_types() {
+        let table = SymbolTable::new();
+
+        match table.lookup("INTEGER") {
+            Some( x ) => {
+                assert_eq!(table.entry(x).kind, SymbolKind::Type);
+                assert_eq!(table.entry(x).scope, UNIVERSE_SCOPE)
+            },
+            _ => assert!(false)
+        }
+    }
+
+    #[test]
+    fn nested_scope_shadowing() {
+        let mut table = SymbolTable::new();
+        table.open_scope(ScopeKind::Module, "Test");
+        let outer = table.declare("x", SymbolKind::Variable, Export::None, 10, 11).unwrap();
+        table.open_scope(ScopeKind::Procedure, "P");
+        let inner = table.declare("x", SymbolKind::Parameter, Export::None, 20, 21).unwrap();
+
+        assert_eq!(table.lookup("x"), Some(inner));
+        table.close_scope();
+        assert_eq!(table.lookup("x"), Some(outer));
+        assert_eq!(table.lookup("y"), None)
+    }
+
+    #[test]
+    fn duplicate_declaration() {
+        let mut table = SymbolTable::new();
+        table.open_scope(ScopeKind::Module, "Test");
+        let _ = table.declare("x", SymbolKind::Variable, Export::None, 10, 11);
+
+        match table.declare("x", SymbolKind::Constant, Export::None, 20, 21) {
+            Err( e ) => {
+                assert_eq!(e.code, E_DUPLICATE_DECLARATION);
+                assert_eq!(e.span.start, 20);
+                assert_eq!(e.labels[0].span.start, 10)
+            },
+            _ => assert!(false)
+        }
+    }
+
+    #[test]
+    fn export_mark_in_procedure() {
+        let mut table = SymbolTable::new();
+        table.open_scope(ScopeKind::Module, "Test");
+        assert!(table.declare("a", SymbolKind::Variable, Export::ReadWrite, 10, 11).is_ok());
+        table.open_scope(ScopeKind::Procedure, "P");
+
+        match table.declare("b", SymbolKind::Variable, Export::ReadOnly, 20, 21) {
+            Err( e ) => assert_eq!(e.code, E_INVALID_EXPORT),
+            _ => assert!(false)
+        }
+    }
+
+    #[test]
+    fn qualified_lookup_export() {
+        let mut table = SymbolTable::new();
+        let lib = table.open_scope(ScopeKind::Module, "Lib");
+        let _ = table.declare("Open", SymbolKind::Procedure, Export::ReadWrite, 10, 14);
+        let _ = table.declare("Hidden", SymbolKind::Procedure, Export::None, 20, 26);
+        table.close_scope();
+        table.open_scope(ScopeKind::Module, "Test");
+
+        assert!(matches!(table.lookup_qualified(lib, "Open", 0, 0), Ok( Some( _ ) )));
+        assert!(matches!(table.lookup_qualified(lib, "Missing", 0, 0), Ok( None )));
+        match table.lookup_qualified(lib, "Hidden", 30, 36) {
+            Err( e ) => assert_eq!(e.code, E_NOT_EXPORTED),
+            _ => assert!(false)
+        }
+    }
+}
@@ -15,6 +15,695 @@ pub struct TraverseAST {
 
 }
 
+/// Direct child nodes of a node in source order, for passes that only need to visit part of the tree
+pub fn children(node: &Node) -> Vec<&Node> {
+    let mut nodes = Vec::<&Node>::new();
+
+    match node {
+        Node::Empty => (),
+        Node::Error( .. ) => (),
+        Node::Ident( .. ) => (),
+        Node::Integer( .. ) => (),
+        Node::Real( .. ) => (),
+        Node::Character( .. ) => (),
+        Node::String( .. ) => (),
+        Node::Nil( .. ) => (),
+        Node::Imag( .. ) => (),
+        Node::True( .. ) => (),
+        Node::False( .. ) => (),
+        Node::Self_( .. ) => (),
+        Node::Result( .. ) => (),
+        Node::Address( _ , _ , _ , f3 ) => {
+            if let Some( x ) = f3 {
+                nodes.push(&*x.1)
+            }
+        },
+        Node::Size( _ , _ , _ , f3 ) => {
+            if let Some( x ) = f3 {
+                nodes.push(&*x.1)
+            }
+        },
+        Node::Alias( _ , _ , _ , _ , f4 ) => {
+            nodes.push(&**f4);
+        },
+        Node::New( _ , _ , _ , f3 , _ , f5 , _ ) => {
+            nodes.push(&**f3);
+            nodes.push(&**f5);
+        },
+        Node::ParenthesisExpression( _ , _ , _ , f3 , _ ) => {
+            nodes.push(&**f3);
+        },
+        Node::UnaryExpression( _ , _ , f2 , f3 , f4 ) => {
+            nodes.push(&**f2);
+
+            if let Some( x ) = f3 {
+                nodes.extend(x.iter().map(|y| &**y))
+            }
+
+            if let Some( x ) = f4 {
+                nodes.push(&**x)
+            }
+        },
+        Node::UnaryPlus( _ , _ , _ , f3 ) => {
+            nodes.push(&**f3);
+        },
+        Node::UnaryMinus( _ , _ , _ , f3 ) => {
+            nodes.push(&**f3);
+        },
+        Node::UnaryNot( _ , _ , _ , f3 ) => {
+            nodes.push(&**f3);
+        },
+        Node::Times( _ , _ , f2 , _ , f4 ) => {
+            nodes.push(&**f2);
+            nodes.push(&**f4);
+        },
+        Node::Slash( _ , _ , f2 , _ , f4 ) => {
+            nodes.push(&**f2);
+            nodes.push(&**f4);
+        },
+        Node::Div( _ , _ , f2 , _ , f4 ) => {
+            nodes.push(&**f2);
+            nodes.push(&**f4);
+        },
+        Node::Mod( _ , _ , f2 , _ , f4 ) => {
+            nodes.push(&**f2);
+            nodes.push(&**f4);
+        },
+        Node::And( _ , _ , f2 , _ , f4 ) => {
+            nodes.push(&**f2);
+            nodes.push(&**f4);
+        },
+        Node::DotTimes( _ , _ , f2 , _ , f4 ) => {
+            nodes.push(&**f2);
+            nodes.push(&**f4);
+        },
+        Node::DotSlash( _ , _ , f2 , _ , f4 ) => {
+            nodes.push(&**f2);
+            nodes.push(&**f4);
+        },
+        Node::Backslash( _ , _ , f2 , _ , f4 ) => {
+            nodes.push(&**f2);
+            nodes.push(&**f4);
+        },
+        Node::TimesTimes( _ , _ , f2 , _ , f4 ) => {
+            nodes.push(&**f2);
+            nodes.push(&**f4);
+        },
+        Node::PlusTimes( _ , _ , f2 , _ , f4 ) => {
+            nodes.push(&**f2);
+            nodes.push(&**f4);
+        },
+        Node::Plus( _ , _ , f2 , _ , f4 ) => {
+            nodes.push(&**f2);
+            nodes.push(&**f4);
+        },
+        Node::Minus( _ , _ , f2 , _ , f4 ) => {
+            nodes.push(&**f2);
+            nodes.push(&**f4);
+        },
+        Node::Or( _ , _ , f2 , _ , f4 ) => {
+            nodes.push(&**f2);
+            nodes.push(&**f4);
+        },
+        Node::Range( _ , _ , f2 , _ , f4 , _ , f6 ) => {
+            if let Some( x ) = f2 {
+                nodes.push(&**x)
+            }
+
+            if let Some( x ) = f4 {
+                nodes.push(&**x)
+            }
+
+            if let Some( x ) = f6 {
+                nodes.push(&**x)
+            }
+        },
+        Node::Equal( _ , _ , f2 , _ , f4 ) => {
+            nodes.push(&**f2);
+            nodes.push(&**f4);
+        },
+        Node::NotEqual( _ , _ , f2 , _ , f4 ) => {
+            nodes.push(&**f2);
+            nodes.push(&**f4);
+        },
+        Node::Less( _ , _ , f2 , _ , f4 ) => {
+            nodes.push(&**f2);
+            nodes.push(&**f4);
+        },
+        Node::LessEqual( _ , _ , f2 , _ , f4 ) => {
+            nodes.push(&**f2);
+            nodes.push(&**f4);
+        },
+        Node::GreaterEqual( _ , _ , f2 , _ , f4 ) => {
+            nodes.push(&**f2);
+            nodes.push(&**f4);
+        },
+        Node::Greater( _ , _ , f2 , _ , f4 ) => {
+            nodes.push(&**f2);
+            nodes.push(&**f4);
+        },
+        Node::In( _ , _ , f2 , _ , f4 ) => {
+            nodes.push(&**f2);
+            nodes.push(&**f4);
+        },
+        Node::Is( _ , _ , f2 , _ , f4 ) => {
+            nodes.push(&**f2);
+            nodes.push(&**f4);
+        },
+        Node::DotEqual( _ , _ , f2 , _ , f4 ) => {
+            nodes.push(&**f2);
+            nodes.push(&**f4);
+        },
+        Node::DotUnequal( _ , _ , f2 , _ , f4 ) => {
+            nodes.push(&**f2);
+            nodes.push(&**f4);
+        },
+        Node::DotLess( _ , _ , f2 , _ , f4 ) => {
+            nodes.push(&**f2);
+            nodes.push(&**f4);
+        },
+        Node::DotLessEqual( _ , _ , f2 , _ , f4 ) => {
+            nodes.push(&**f2);
+            nodes.push(&**f4);
+        },
+        Node::DotGreater( _ , _ , f2 , _ , f4 ) => {
+            nodes.push(&**f2);
+            nodes.push(&**f4);
+        },
+        Node::DotGreaterEqual( _ , _ , f2 , _ , f4 ) => {
+            nodes.push(&**f2);
+            nodes.push(&**f4);
+        },
+        Node::QuestionMarks( _ , _ , f2 , _ , f4 ) => {
+            nodes.push(&**f2);
+            nodes.push(&**f4);
+        },
+        Node::ExplainMarks( _ , _ , f2 , _ , f4 ) => {
+            nodes.push(&**f2);
+            nodes.push(&**f4);
+        },
+        Node::LessLessQ( _ , _ , f2 , _ , f4 ) => {
+            nodes.push(&**f2);
+            nodes.push(&**f4);
+        },
+        Node::GreaterGreaterQ( _ , _ , f2 , _ , f4 ) => {
+            nodes.push(&**f2);
+            nodes.push(&**f4);
+        },
+        Node::Array( _ , _ , _ , f3 , _ , _ ) => {
+            nodes.extend(f3.iter().map(|x| &**x));
+        },
+        Node::Set( _ , _ , _ , f3 , _ , _ ) => {
+            nodes.extend(f3.iter().map(|x| &**x));
+        },
+        Node::ExpressionList( _ , _ , f2 , _ ) => {
+            nodes.extend(f2.iter().map(|x| &**x));
+        },
+        Node::IndexList( _ , _ , f2 , _ , _ , _ , f6 ) => {
+            if let Some( x ) = f2 {
+                nodes.push(&**x)
+            }
+
+            if let Some( x ) = f6 {
+                nodes.push(&**x)
+            }
+        },
+        Node::Call( _ , _ , _ , f3 , _ ) => {
+            if let Some( x ) = f3 {
+                nodes.push(&**x)
+            }
+        },
+        Node::DotName( _ , _ , _ , f3 ) => {
+            nodes.push(&**f3);
+        },
+        Node::Index( _ , _ , _ , f3 , _ ) => {
+            if let Some( x ) = f3 {
+                nodes.push(&**x)
+            }
+        },
+        Node::Arrow( .. ) => (),
+        Node::Transpose( .. ) => (),
+        Node::StatementSequence( _ , _ , f2 , _ ) => {
+            nodes.extend(f2.iter().map(|x| &**x));
+        },
+        Node::StatementBlock( _ , _ , _ , f3 , f4 , _ ) => {
+            if let Some( x ) = f3 {
+                nodes.push(&**x)
+            }
+
+            nodes.push(&**f4);
+        },
+        Node::If( _ , _ , _ , f3 , _ , f5 , f6 , f7 , _ ) => {
+            nodes.push(&**f3);
+            nodes.push(&**f5);
+
+            if let Some( x ) = f6 {
+                nodes.extend(x.iter().map(|y| &**y))
+            }
+
+            if let Some( x ) = f7 {
+                nodes.push(&**x)
+            }
+        },
+        Node::Elsif( _ , _ , _ , f3 , _ , f5 ) => {
+            nodes.push(&**f3);
+            nodes.push(&**f5);
+        },
+        Node::Else( _ , _ , _ , f3 ) => {
+            nodes.push(&**f3);
+        },
+        Node::With( _ , _ , _ , f3 , _ , f5 , f6 , _ ) => {
+            nodes.push(&**f3);
+            nodes.extend(f5.iter().map(|x| &**x));
+
+            if let Some( x ) = f6 {
+                nodes.push(&**x)
+            }
+        },
+        Node::WithElement( _ , _ , _ , f3 , _ , f5 ) => {
+            nodes.push(&**f3);
+            nodes.push(&**f5);
+        },
+        Node::Case( _ , _ , _ , f3 , _ , f5 , f6 , _ ) => {
+            nodes.push(&**f3);
+            nodes.extend(f5.iter().map(|x| &**x));
+
+            if let Some( x ) = f6 {
+                nodes.push(&**x)
+            }
+        },
+        Node::CaseElement( _ , _ , _ , f3 , _ , _ , f6 ) => {
+            nodes.extend(f3.iter().map(|x| &**x));
+            nodes.push(&**f6);
+        },
+        Node::While( _ , _ , _ , f3 , _ , f5 , _ ) => {
+            nodes.push(&**f3);
+            nodes.push(&**f5);
+        },
+        Node::Repeat( _ , _ , _ , f3 , _ , f5 ) => {
+            nodes.push(&**f3);
+            nodes.push(&**f5);
+        },
+        Node::For( _ , _ , _ , f3 , _ , f5 , _ , f7 , f8 , _ , f10 , _ ) => {
+            nodes.push(&**f3);
+            nodes.push(&**f5);
+            nodes.push(&**f7);
+
+            if let Some( x ) = f8 {
+                nodes.push(&*x.1)
+            }
+
+            nodes.push(&**f10);
+        },
+        Node::Loop( _ , _ , _ , f3 , _ ) => {
+            nodes.push(&**f3);
+        },
+        Node::Exit( .. ) => (),
+        Node::Return( _ , _ , _ , f3 ) => {
+            if let Some( x ) = f3 {
+                nodes.push(&**x)
+            }
+        },
+        Node::Await( _ , _ , _ , f3 ) => {
+            nodes.push(&**f3);
+        },
+        Node::Code( .. ) => (),
+        Node::Ignore( _ , _ , _ , f3 ) => {
+            nodes.push(&**f3);
+        },
+        Node::BecomesStatement( _ , _ , f2 , _ , f4 ) => {
+            nodes.push(&**f2);
+            nodes.push(&**f4);
+        },
+        Node::ExclaimMarkStatement( _ , _ , f2 , _ , f4 ) => {
+            nodes.push(&**f2);
+            nodes.push(&**f4);
+        },
+        Node::QuestionmarkStatement( _ , _ , f2 , _ , f4 ) => {
+            nodes.push(&**f2);
+            nodes.push(&**f4);
+        },
+        Node::LessLessStatement( _ , _ , f2 , _ , f4 ) => {
+            nodes.push(&**f2);
+            nodes.push(&**f4);
+        },
+        Node::GreaterGreaterStatement( _ , _ , f2 , _ , f4 ) => {
+            nodes.push(&**f2);
+            nodes.push(&**f4);
+        },
+        Node::Module( _ , _ , _ , f3 , f4 , f5 , _ , f7 , f8 , f9 , _ , f11 , _ ) => {
+            if let Some( x ) = f3 {
+                nodes.push(&**x)
+            }
+
+            nodes.push(&**f4);
+
+            if let Some( x ) = f5 {
+                nodes.push(&*x.1)
+            }
+
+            if let Some( x ) = f7 {
+                nodes.extend(x.iter().map(|y| &**y))
+            }
+
+            if let Some( x ) = f8 {
+                nodes.push(&**x)
+            }
+
+            if let Some( x ) = f9 {
+                nodes.push(&**x)
+            }
+
+            nodes.push(&**f11);
+        },
+        Node::TemplateParameters( _ , _ , _ , f3 , _ , _ ) => {
+            nodes.extend(f3.iter().map(|x| &**x));
+        },
+        Node::TemplateParameter( _ , _ , _ , f3 ) => {
+            nodes.push(&**f3);
+        },
+        Node::ImportList( _ , _ , _ , f3 , _ , _ ) => {
+            nodes.extend(f3.iter().map(|x| &**x));
+        },
+        Node::Import( _ , _ , f2 , f3 , f4 , f5 ) => {
+            nodes.push(&**f2);
+
+            if let Some( x ) = f3 {
+                nodes.push(&*x.1)
+            }
+
+            if let Some( x ) = f4 {
+                nodes.push(&*x.1)
+            }
+
+            if let Some( x ) = f5 {
+                nodes.push(&*x.1)
+            }
+        },
+        Node::DeclarationSequence( _ , _ , f2 , f3 , f4 , f5 , f6 , f7 , _ ) => {
+            nodes.extend(f2.iter().map(|x| &**x));
+            nodes.extend(f3.iter().map(|x| &**x));
+            nodes.extend(f4.iter().map(|x| &**x));
+            nodes.extend(f5.iter().map(|x| &**x));
+            nodes.extend(f6.iter().map(|x| &**x));
+            nodes.extend(f7.iter().map(|x| &**x));
+        },
+        Node::ConstDeclaration( _ , _ , _ , f3 ) => {
+            nodes.extend(f3.iter().map(|x| &**x));
+        },
+        Node::TypeDeclaration( _ , _ , _ , f3 ) => {
+            nodes.extend(f3.iter().map(|x| &**x));
+        },
+        Node::VarDeclaration( _ , _ , _ , f3 ) => {
+            nodes.extend(f3.iter().map(|x| &**x));
+        },
+        Node::Const( _ , _ , f2 , _ , f4 ) => {
+            nodes.push(&**f2);
+            nodes.push(&**f4);
+        },
+        Node::Var( _ , _ , f2 , _ , f4 ) => {
+            nodes.push(&**f2);
+            nodes.push(&**f4);
+        },
+        Node::VarList( _ , _ , f2 , _ ) => {
+            nodes.extend(f2.iter().map(|x| &**x));
+        },
+        Node::VarName( _ , _ , f2 , f3 , f4 ) => {
+            nodes.push(&**f2);
+
+            if let Some( x ) = f3 {
+                nodes.push(&**x)
+            }
+
+            if let Some( x ) = f4 {
+                nodes.push(&*x.1)
+            }
+        },
+        Node::Flags( _ , _ , _ , f3 , _ , _ ) => {
+            nodes.extend(f3.iter().map(|x| &**x));
+        },
+        Node::Flag( _ , _ , f2 , f3 , f4 ) => {
+            nodes.push(&**f2);
+
+            if let Some( x ) = f3 {
+                nodes.push(&*x.1)
+            }
+
+            if let Some( x ) = f4 {
+                nodes.push(&*x.1)
+            }
+        },
+        Node::Procedure( _ , _ , _ , f3 , f4 , f5 , f6 , _ , f8 , f9 , _ , f11 ) => {
+            if let Some( ( Some( x ) , _ ) ) = f3 {
+                nodes.push(&**x)
+            }
+
+            if let Some( x ) = f4 {
+                nodes.push(&*x.1)
+            }
+
+            nodes.push(&**f5);
+
+            if let Some( x ) = f6 {
+                nodes.push(&**x)
+            }
+
+            if let Some( x ) = f8 {
+                nodes.push(&**x)
+            }
+
+            if let Some( x ) = f9 {
+                nodes.push(&**x)
+            }
+
+            nodes.push(&**f11);
+        },
+        Node::Operator( _ , _ , _ , f3 , _ , f5 , _ , f7 , _ , f9 , f10 , _ , f12 ) => {
+            if let Some( x ) = f3 {
+                nodes.push(&**x)
+            }
+
+            nodes.push(&**f5);
+            nodes.push(&**f7);
+
+            if let Some( x ) = f9 {
+                nodes.push(&**x)
+            }
+
+            if let Some( x ) = f10 {
+                nodes.push(&**x)
+            }
+
+            nodes.push(&**f12);
+        },
+        Node::Definition( _ , _ , _ , f3 , _ , f5 , _ , f7 ) => {
+            nodes.push(&**f3);
+            nodes.extend(f5.iter().map(|x| &**x));
+            nodes.push(&**f7);
+        },
+        Node::ProcedureHeading( _ , _ , _ , f3 , f4 , f5 , _ ) => {
+            if let Some( x ) = f3 {
+                nodes.push(&**x)
+            }
+
+            nodes.push(&**f4);
+
+            if let Some( x ) = f5 {
+                nodes.push(&**x)
+            }
+        },
+        Node::FormalParameters( _ , _ , _ , f3 , _ , _ , f6 ) => {
+            nodes.extend(f3.iter().map(|x| &**x));
+
+            match f6 {
+                Some( ( _ , Some( x ) , y ) ) => {
+                    nodes.push(&**x);
+                    nodes.push(&**y)
+                },
+                Some( ( _ , _ , y ) ) => nodes.push(&**y),
+                _ => ()
+            }
+        },
+        Node::ParameterDeclaration( _ , _ , _ , f3 , _ , _ , f6 ) => {
+            nodes.extend(f3.iter().map(|x| &**x));
+            nodes.push(&**f6);
+        },
+        Node::Parameter( _ , _ , f2 , f3 , f4 ) => {
+            nodes.push(&**f2);
+
+            if let Some( x ) = f3 {
+                nodes.push(&**x)
+            }
+
+            if let Some( x ) = f4 {
+                nodes.push(&*x.1)
+            }
+        },
+        Node::Body( _ , _ , _ , f3 , f4 , f5 ) => {
+            if let Some( x ) = f3 {
+                nodes.push(&**x)
+            }
+
+            nodes.push(&**f4);
+
+            if let Some( x ) = f5 {
+                nodes.push(&*x.1)
+            }
+        },
+        Node::BodyCode( _ , _ , _ , f3 ) => {
+            nodes.push(&**f3);
+        },
+        Node::CodeBlock( .. ) => (),
+        Node::TypeDeclarationElement( _ , _ , f2 , _ , f4 , _ ) => {
+            nodes.push(&**f2);
+            nodes.push(&**f4);
+        },
+        Node::ArrayType( _ , _ , _ , f3 , _ , f5 ) => {
+            if let Some( x ) = f3 {
+                nodes.extend(x.0.iter().map(|y| &**y))
+            }
+
+            nodes.push(&**f5);
+        },
+        Node::MathArrayType( _ , _ , _ , f3 , _ , f5 ) => {
+            if let Some( x ) = f3 {
+                nodes.extend(x.0.iter().map(|y| &**y))
+            }
+
+            nodes.push(&**f5);
+        },
+        Node::MathArraySize( _ , _ , f2 , _ ) => {
+            if let Some( x ) = f2 {
+                nodes.push(&**x)
+            }
+        },
+        Node::RecordType( _ , _ , _ , f3 , f4 , f5 , _ ) => {
+            if let Some( x ) = f3 {
+                nodes.push(&*x.1)
+            }
+
+            if let Some( x ) = f4 {
+                nodes.extend(x.0.iter().map(|y| &**y))
+            }
+
+            if let Some( x ) = f5 {
+                nodes.extend(x.0.iter().map(|y| &**y))
+            }
+        },
+        Node::PointerType( _ , _ , _ , f3 , _ , f5 ) => {
+            if let Some( x ) = f3 {
+                nodes.push(&**x)
+            }
+
+            nodes.push(&**f5);
+        },
+        Node::ProcedureType( _ , _ , _ , f3 , f4 ) => {
+            if let Some( x ) = f3 {
+                nodes.push(&**x)
+            }
+
+            if let Some( x ) = f4 {
+                nodes.push(&**x)
+            }
+        },
+        Node::ObjectTypeEmpty( .. ) => (),
+        Node::ObjectType( _ , _ , _ , f3 , f4 , f5 , f6 , f7 , _ , f9 ) => {
+            if let Some( x ) = f3 {
+                nodes.push(&**x)
+            }
+
+            if let Some( x ) = f4 {
+                nodes.push(&*x.1)
+            }
+
+            if let Some( x ) = f5 {
+                nodes.extend(x.1.iter().map(|x| &**x))
+            }
+
+            if let Some( x ) = f6 {
+                nodes.push(&**x)
+            }
+
+            if let Some( x ) = f7 {
+                nodes.push(&**x)
+            }
+
+            if let Some( x ) = f9 {
+                nodes.push(&**x)
+            }
+        },
+        Node::EnumerationType( _ , _ , _ , f3 , f4 , _ , _ ) => {
+            if let Some( x ) = f3 {
+                nodes.push(&*x.1)
+            }
+
+            nodes.extend(f4.iter().map(|x| &**x));
+        },
+        Node::EnumElement( _ , _ , f2 , f3 ) => {
+            nodes.push(&**f2);
+
+            if let Some( x ) = f3 {
+                nodes.push(&*x.1)
+            }
+        },
+        Node::CellType( _ , _ , _ , f3 , f4 , _ , f6 , f7 , f8 , _ , f10 ) => {
+            if let Some( x ) = f3 {
+                nodes.push(&**x)
+            }
+
+            if let Some( x ) = f4 {
+                nodes.push(&*x.1)
+            }
+
+            if let Some( x ) = f6 {
+                nodes.push(&**x)
+            }
+
+            if let Some( x ) = f7 {
+                nodes.push(&**x)
+            }
+
+            if let Some( x ) = f8 {
+                nodes.push(&**x)
+            }
+
+            if let Some( x ) = f10 {
+                nodes.push(&**x)
+            }
+        },
+        Node::PortList( _ , _ , f2 , _ ) => {
+            nodes.extend(f2.iter().map(|x| &**x));
+        },
+        Node::PortDeclaration( _ , _ , f2 , _ , _ , f5 ) => {
+            for x in f2.iter() {
+                nodes.push(&*x.0);
+                if let Some( y ) = &x.1 {
+                    nodes.push(&**y)
+                }
+            }
+
+            nodes.push(&**f5);
+        },
+        Node::PortType( _ , _ , _ , _ , f4 ) => {
+            if let Some( x ) = f4 {
+                nodes.push(&*x.1)
+            }
+        },
+        Node::QualifiedIdentifier( _ , _ , f2 , _ , f4 ) => {
+            nodes.push(&**f2);
+            nodes.push(&**f4);
+        },
+        Node::IdentifierReadWrite( _ , _ , f2 , _ ) => {
+            nodes.push(&**f2);
+        },
+        Node::IdentifierRead( _ , _ , f2 , _ ) => {
+            nodes.push(&**f2);
+        },
+    }
+
+    nodes
+}
+
 impl TraverseASTMethods for TraverseAST {
     fn new() -> Self {
         TraverseAST {
@@ -26,6 +715,10 @@ impl TraverseASTMethods for TraverseAST {
         match *tree {
             Node::Empty => {},
 
+            Node::Error( _ , _ ) => {
+                /* Skipped by error recovery in parser */
+            },
+
             Node::Ident( _ , _ , id ) => {
                 /* Handle ident */
             },
@@ -652,7 +1345,7 @@ impl TraverseASTMethods for TraverseAST {
                 }
             },
 
-            Node::DeclarationSequence( _ , _ , const_decl , type_decl , var_decl , proc_decl , oper_decl , _ ) => {
+            Node::DeclarationSequence( _ , _ , const_decl , type_decl , var_decl , proc_decl , oper_decl , def_decl , _ ) => {
                 for el in const_decl.iter() {
                     self.traverse(el.clone())
                 }
@@ -672,6 +1365,10 @@ impl TraverseASTMethods for TraverseAST {
                 for el in oper_decl.iter() {
                     self.traverse(el.clone())
                 }
+
+                for el in def_decl.iter() {
+                    self.traverse(el.clone())
+                }
             },
 
             Node::ConstDeclaration( _ , _ , _ , nodes ) => {
@@ -876,6 +1573,34 @@ impl TraverseASTMethods for TraverseAST {
                 self.traverse(id_back);
             },
 
+            Node::Definition( _ , _ , _ , id_front , _ , headings , _ , id_back ) => {
+                self.traverse(id_front);
+
+                for el in headings.iter() {
+                    self.traverse(el.clone())
+                }
+
+                self.traverse(id_back);
+            },
+
+            Node::ProcedureHeading( _ , _ , _ , flags , id_def , para , _ ) => {
+                match flags {
+                    Some( flags_node ) => {
+                        self.traverse(flags_node)
+                    },
+                    _ => ()
+                }
+
+                self.traverse(id_def);
+
+                match para {
+                    Some( para_node ) => {
+                        self.traverse(para_node)
+                    },
+                    _ => ()
+                }
+            },
+
             Node::FormalParameters( _ , _ , _ , nodes , _ , _ , type_expr ) => {
                 for el in nodes.iter() {
                     self.traverse(el.clone())
@@ -955,8 +1680,12 @@ impl TraverseASTMethods for TraverseAST {
                 }
             },
 
-            Node::BodyCode( _ , _ , _ , _ ) => {
-                /* Handle later! */
+            Node::BodyCode( _ , _ , _ , block ) => {
+                self.traverse(block)
+            },
+
+            Node::CodeBlock( _ , _ , _ ) => {
+                /* Machine code is already assembled by parser */
             },
 
             Node::TypeDeclarationElement( _ , _ , left , _ , right , _ ) => {
@@ -1072,7 +1801,7 @@ impl TraverseASTMethods for TraverseAST {
                 /* Empty Object */
             },
 
-            Node::ObjectType( _ , _ , _ , flags , formal , decl , body , _ , ident ) => {
+            Node::ObjectType( _ , _ , _ , flags , formal , implements , decl , body , _ , ident ) => {
                 match flags {
                     Some( flags_node ) => {
                         self.traverse(flags_node)
@@ -1087,6 +1816,12 @@ impl TraverseASTMethods for TraverseAST {
                     _ => ()
                 }
 
+                if let Some( ( _ , nodes , _ ) ) = implements {
+                    for el in nodes.iter() {
+                        self.traverse(el.clone())
+                    }
+                }
+
                 match decl {
                     Some( decl_node ) => {
                         self.traverse(decl_node)
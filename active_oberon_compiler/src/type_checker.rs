@@ -0,0 +1,2802 @@
+// ActiveOberon Compiler, a native ARM v8 & X86-64 compiler & Risc V / linker / builder utility.
+// Written by Richard Magnor Stenbro. Licensed under GPL v3
+// Type checker module for static checking of expressions and statements in ActiveOberon language
+
+use std::collections::HashMap;
+use crate::diagnostics::{Diagnostic, DiagnosticMethods, E_TYPE_MISMATCH, E_INCOMPATIBLE_ASSIGNMENT, E_NOT_ASSIGNABLE, E_ARGUMENT, E_NO_FIELD, E_NOT_CALLABLE, E_RESULT, E_NOT_IMPLEMENTED, E_SIGNATURE_MISMATCH, E_NOT_EXPORTED, E_NOT_CONSTANT, E_OUT_OF_RANGE};
+use crate::parser::Node;
+use crate::resolver::{Resolver, ResolverMethods};
+use crate::scanner::Symbols;
+use crate::symbol_table::{SymbolTableMethods, ScopeId, EntryId, SymbolKind, Export, UNIVERSE_SCOPE, identifier_definition};
+use crate::const_evaluator::{ConstValue, ConstError, Folded, SET_ELEMENTS, literal, unary, binary, builtin, set_elements, not_constant};
+
+pub type TypeId = usize;
+
+/* Predeclared types, always at the same place in type table */
+pub const T_UNKNOWN : TypeId = 0;
+pub const T_NOTYPE : TypeId = 1;
+pub const T_BOOLEAN : TypeId = 2;
+pub const T_CHAR8 : TypeId = 3;
+pub const T_CHAR16 : TypeId = 4;
+pub const T_CHAR32 : TypeId = 5;
+pub const T_SIGNED8 : TypeId = 6;
+pub const T_SIGNED16 : TypeId = 7;
+pub const T_SIGNED32 : TypeId = 8;
+pub const T_SIGNED64 : TypeId = 9;
+pub const T_UNSIGNED8 : TypeId = 10;
+pub const T_UNSIGNED16 : TypeId = 11;
+pub const T_UNSIGNED32 : TypeId = 12;
+pub const T_UNSIGNED64 : TypeId = 13;
+pub const T_REAL : TypeId = 14;
+pub const T_LONGREAL : TypeId = 15;
+pub const T_COMPLEX : TypeId = 16;
+pub const T_LONGCOMPLEX : TypeId = 17;
+pub const T_SET : TypeId = 18;
+pub const T_ADDRESS : TypeId = 19;
+pub const T_SIZE : TypeId = 20;
+pub const T_RANGE : TypeId = 21;
+pub const T_ANYTYPE : TypeId = 22;
+pub const T_NIL : TypeId = 23;
+pub const T_OBJECT : TypeId = 24;
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum ParameterMode {
+    Value,
+    Var,
+    Const
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Type {
+    Unknown,                                                /* Error already reported, compatible with everything */
+    Void,                                                   /* Result of proper procedures */
+    Alias(TypeId),                                          /* Named type declared as other type */
+    Boolean,
+    Char(u32),                                              /* Size in bits */
+    Signed(u32),
+    Unsigned(u32),
+    Real(u32),
+    Complex(u32),
+    Set,
+    Address,
+    Size,
+    Range,
+    Any,
+    Nil,
+    String(u32),                                            /* String constant with number of characters */
+    Array(Option<i64>, TypeId),                             /* Length, none for open arrays, and element type */
+    MathArray(Option<i64>, TypeId),
+    Record(Option<TypeId>, Option<ScopeId>),                /* Base type and scope with fields and methods */
+    Object(Option<TypeId>, Option<ScopeId>),
+    Pointer(TypeId),
+    Procedure(Box<Vec<(ParameterMode, TypeId)>>, TypeId),   /* Parameters and result type */
+    Enumeration(Option<ScopeId>),
+    Definition(Option<ScopeId>)                             /* Scope with procedure headings objects must implement */
+}
+
+/// What a designator or expression denotes, decides where it can be used
+#[derive(Clone, PartialEq, Debug)]
+pub enum OperandMode {
+    Value,
+    Variable,
+    ReadOnly,
+    Constant,
+    Type,
+    Procedure,
+    Builtin(String),
+    Module
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Operand {
+    pub mode: OperandMode,
+    pub type_id: TypeId
+}
+
+/// Passes over declarations, each pass is done for all modules before the next starts
+#[derive(Clone, PartialEq, Debug)]
+enum Pass {
+    Placeholders,   /* Type table slot for every named type, so that forward references can be found */
+    Types,          /* Named types */
+    Signatures,     /* Constants, variables, fields and procedure headings */
+    Bodies          /* Statements and initializers */
+}
+
+/// Named constant or enumeration element, evaluated when its value is first needed
+struct ConstantDeclaration {
+    scope: ScopeId,
+    value: Option<Box<Node>>,
+    previous: Option<EntryId>       /* Enumeration element before this one, when value is not given */
+}
+
+pub trait TypeCheckerMethods {
+    fn new() -> Self;
+    fn check_modules(&mut self, modules: &[&Node]) -> Vec<Vec<Box<Diagnostic>>>;
+    fn get_resolver(&self) -> &Resolver;
+    fn get_type(&self, type_id: TypeId) -> &Type;
+    fn get_entry_type(&self, entry: EntryId) -> Option<TypeId>;
+    fn get_expression_type(&self, module: ScopeId, start: u32, end: u32) -> Option<TypeId>;
+    fn type_name(&self, type_id: TypeId) -> String;
+    fn get_constant(&self, entry: EntryId) -> Option<&ConstValue>;
+    fn type_size(&self, type_id: TypeId) -> Option<i64>;
+    fn get_parameter_mode(&self, entry: EntryId) -> Option<ParameterMode>;
+    fn get_code_scope(&self, module: ScopeId, position: u32) -> Option<ScopeId>;
+    fn field_offset(&self, field: EntryId) -> Option<i64>;
+    fn instance_size(&self, type_id: TypeId) -> Option<i64>;
+}
+
+/// Static type checker, gives every expression a type and checks assignments, calls and comparisons
+pub struct TypeChecker {
+    resolver: Resolver,
+    types: Vec<Type>,
+    names: HashMap<TypeId, String>,                             /* Name of declared types for messages */
+    entry_types: HashMap<EntryId, TypeId>,
+    parameter_modes: HashMap<EntryId, ParameterMode>,
+    results: HashMap<ScopeId, TypeId>,                          /* Result type of procedure scopes */
+    expression_types: HashMap<(ScopeId, u32, u32), TypeId>,     /* Type of expression, keyed by module and span */
+    constant_declarations: HashMap<EntryId, ConstantDeclaration>,
+    constants: HashMap<EntryId, Option<ConstValue>>,            /* None when evaluation failed */
+    constant_errors: HashMap<EntryId, ConstError>,              /* Reported at declaration of constant */
+    evaluating: Vec<EntryId>,                                   /* Constants being evaluated, for finding cycles */
+    implementations: HashMap<TypeId, Vec<(TypeId, u32, u32)>>,  /* Definitions named by object types, with position of name */
+    code_scopes: HashMap<(ScopeId, u32), ScopeId>,              /* Scope of 'CODE' blocks, keyed by module and start of assembler text */
+    module: ScopeId,
+    scope: ScopeId,
+    result: TypeId,                                             /* Result type of procedure being checked */
+    self_type: TypeId,                                          /* Type of 'SELF' in methods */
+    guards: HashMap<EntryId, TypeId>,                           /* Variables narrowed to guard type by enclosing 'WITH' */
+    errors: Vec<Box<Diagnostic>>
+}
+
+/// Value of integer literal with decimal, hexadecimal ('H' or '0x') or binary ('0b') digits
+pub fn integer_value(text: &str) -> Option<i128> {
+    let digits : String = text.chars().filter(|c| *c != '`').collect();
+
+    match ( digits.starts_with("0x") , digits.starts_with("0b") , digits.ends_with('H') ) {
+        ( true , _ , _ ) => i128::from_str_radix(&digits[2..], 16).ok(),
+        ( _ , true , _ ) => i128::from_str_radix(&digits[2..], 2).ok(),
+        ( _ , _ , true ) => i128::from_str_radix(&digits[.. digits.len() - 1], 16).ok(),
+        _ => digits.parse::<i128>().ok()
+    }
+}
+
+/// Smallest integer type holding value
+fn integer_type(value: i128) -> TypeId {
+    match value {
+        x if x >= i8::MIN as i128 && x <= i8::MAX as i128 => T_SIGNED8,
+        x if x >= i16::MIN as i128 && x <= i16::MAX as i128 => T_SIGNED16,
+        x if x >= i32::MIN as i128 && x <= i32::MAX as i128 => T_SIGNED32,
+        _ => T_SIGNED64
+    }
+}
+
+/// Source span of expression node, used for error messages and expression type table
+pub fn expression_span(node: &Node) -> (u32, u32) {
+    match node {
+        Node::Error( s , e ) |
+        Node::Ident( s , e , .. ) |
+        Node::Integer( s , e , .. ) |
+        Node::Real( s , e , .. ) |
+        Node::Character( s , e , .. ) |
+        Node::String( s , e , .. ) |
+        Node::Nil( s , e , .. ) |
+        Node::Imag( s , e , .. ) |
+        Node::True( s , e , .. ) |
+        Node::False( s , e , .. ) |
+        Node::Self_( s , e , .. ) |
+        Node::Result( s , e , .. ) |
+        Node::Address( s , e , .. ) |
+        Node::Size( s , e , .. ) |
+        Node::Alias( s , e , .. ) |
+        Node::New( s , e , .. ) |
+        Node::ParenthesisExpression( s , e , .. ) |
+        Node::UnaryExpression( s , e , .. ) |
+        Node::UnaryPlus( s , e , .. ) |
+        Node::UnaryMinus( s , e , .. ) |
+        Node::UnaryNot( s , e , .. ) |
+        Node::Times( s , e , .. ) |
+        Node::Slash( s , e , .. ) |
+        Node::Div( s , e , .. ) |
+        Node::Mod( s , e , .. ) |
+        Node::And( s , e , .. ) |
+        Node::DotTimes( s , e , .. ) |
+        Node::DotSlash( s , e , .. ) |
+        Node::Backslash( s , e , .. ) |
+        Node::TimesTimes( s , e , .. ) |
+        Node::PlusTimes( s , e , .. ) |
+        Node::Plus( s , e , .. ) |
+        Node::Minus( s , e , .. ) |
+        Node::Or( s , e , .. ) |
+        Node::Range( s , e , .. ) |
+        Node::Equal( s , e , .. ) |
+        Node::NotEqual( s , e , .. ) |
+        Node::Less( s , e , .. ) |
+        Node::LessEqual( s , e , .. ) |
+        Node::GreaterEqual( s , e , .. ) |
+        Node::Greater( s , e , .. ) |
+        Node::In( s , e , .. ) |
+        Node::Is( s , e , .. ) |
+        Node::DotEqual( s , e , .. ) |
+        Node::DotUnequal( s , e , .. ) |
+        Node::DotLess( s , e , .. ) |
+        Node::DotLessEqual( s , e , .. ) |
+        Node::DotGreater( s , e , .. ) |
+        Node::DotGreaterEqual( s , e , .. ) |
+        Node::QuestionMarks( s , e , .. ) |
+        Node::ExplainMarks( s , e , .. ) |
+        Node::LessLessQ( s , e , .. ) |
+        Node::GreaterGreaterQ( s , e , .. ) |
+        Node::Array( s , e , .. ) |
+        Node::Set( s , e , .. ) |
+        Node::QualifiedIdentifier( s , e , .. ) => ( *s, *e ),
+        _ => ( 0, 0 )
+    }
+}
+
+/// Expressions of an optional expression list, as found in calls and index operators
+pub fn expression_list(node: &Option<Box<Node>>) -> Vec<&Node> {
+    match node {
+        Some( x ) => {
+            match &**x {
+                Node::ExpressionList( _ , _ , nodes , _ ) => nodes.iter().map(|n| &**n).collect(),
+                Node::IndexList( _ , _ , left , _ , _ , _ , right ) => {
+                    let mut nodes = expression_list(left);
+                    nodes.append(&mut expression_list(right));
+                    nodes
+                },
+                _ => vec![ &**x ]
+            }
+        },
+        _ => Vec::new()
+    }
+}
+
+impl TypeCheckerMethods for TypeChecker {
+    fn new() -> Self {
+        let mut checker = TypeChecker {
+            resolver: Resolver::new(),
+            types: vec![ Type::Unknown, Type::Void, Type::Boolean, Type::Char(8), Type::Char(16), Type::Char(32),
+                         Type::Signed(8), Type::Signed(16), Type::Signed(32), Type::Signed(64),
+                         Type::Unsigned(8), Type::Unsigned(16), Type::Unsigned(32), Type::Unsigned(64),
+                         Type::Real(32), Type::Real(64), Type::Complex(32), Type::Complex(64),
+                         Type::Set, Type::Address, Type::Size, Type::Range, Type::Any, Type::Nil, Type::Object(None, None) ],
+            names: HashMap::new(),
+            entry_types: HashMap::new(),
+            parameter_modes: HashMap::new(),
+            results: HashMap::new(),
+            expression_types: HashMap::new(),
+            constant_declarations: HashMap::new(),
+            constants: HashMap::new(),
+            constant_errors: HashMap::new(),
+            evaluating: Vec::new(),
+            implementations: HashMap::new(),
+            code_scopes: HashMap::new(),
+            module: UNIVERSE_SCOPE,
+            scope: UNIVERSE_SCOPE,
+            result: T_NOTYPE,
+            self_type: T_UNKNOWN,
+            guards: HashMap::new(),
+            errors: Vec::new()
+        };
+
+        let table = checker.resolver.get_symbol_table();
+        for entry in table.scope(UNIVERSE_SCOPE).entries.iter() {
+            let type_id = match table.entry(*entry).name.as_str() {
+                "BOOLEAN" => T_BOOLEAN,
+                "CHAR" | "CHAR8" => T_CHAR8,
+                "CHAR16" => T_CHAR16,
+                "CHAR32" => T_CHAR32,
+                "SHORTINT" | "SIGNED8" => T_SIGNED8,
+                "INTEGER" | "SIGNED16" => T_SIGNED16,
+                "LONGINT" | "SIGNED32" => T_SIGNED32,
+                "HUGEINT" | "SIGNED64" => T_SIGNED64,
+                "UNSIGNED8" => T_UNSIGNED8,
+                "UNSIGNED16" => T_UNSIGNED16,
+                "UNSIGNED32" => T_UNSIGNED32,
+                "UNSIGNED64" => T_UNSIGNED64,
+                "REAL" | "FLOAT32" => T_REAL,
+                "LONGREAL" | "FLOAT64" => T_LONGREAL,
+                "COMPLEX" => T_COMPLEX,
+                "LONGCOMPLEX" => T_LONGCOMPLEX,
+                "SET" => T_SET,
+                "RANGE" => T_RANGE,
+                "ANYTYPE" => T_ANYTYPE,
+                _ => continue
+            };
+            checker.entry_types.insert(*entry, type_id);
+        }
+
+        checker
+    }
+
+    /// Resolve and type check modules together, returning errors for each module in same order
+    fn check_modules(&mut self, modules: &[&Node]) -> Vec<Vec<Box<Diagnostic>>> {
+        let mut results : Vec<Vec<Box<Diagnostic>>> = modules.iter().map(|_| Vec::new()).collect();
+
+        for index in 0 .. modules.len() {
+            self.resolver.declare_module(modules[index]);
+            results[index].append(&mut self.resolver.take_errors())
+        }
+
+        for index in 0 .. modules.len() {
+            self.resolver.resolve_module(modules[index]);
+            results[index].append(&mut self.resolver.take_errors())
+        }
+
+        for pass in [ Pass::Placeholders, Pass::Types, Pass::Signatures, Pass::Bodies ] {
+            for index in 0 .. modules.len() {
+                self.check_module(modules[index], &pass);
+                results[index].append(&mut self.errors)
+            }
+        }
+
+        /* Constant expressions may be folded more than once, report each error only once */
+        for result in results.iter_mut() {
+            result.sort_by(|a, b| ( a.span.start, a.span.end, a.code, &a.message ).cmp(&( b.span.start, b.span.end, b.code, &b.message )));
+            result.dedup()
+        }
+
+        results
+    }
+
+    fn get_resolver(&self) -> &Resolver {
+        &self.resolver
+    }
+
+    fn get_type(&self, type_id: TypeId) -> &Type {
+        &self.types[self.base(type_id)]
+    }
+
+    fn get_entry_type(&self, entry: EntryId) -> Option<TypeId> {
+        self.entry_types.get(&entry).copied()
+    }
+
+    fn get_expression_type(&self, module: ScopeId, start: u32, end: u32) -> Option<TypeId> {
+        self.expression_types.get(&( module, start, end )).copied()
+    }
+
+    fn type_name(&self, type_id: TypeId) -> String {
+        if let Some( x ) = self.names.get(&type_id) {
+            return x.clone()
+        }
+
+        match &self.types[type_id] {
+            Type::Alias( x ) => self.type_name(*x),
+            Type::Unknown => String::from("unknown type"),
+            Type::Void => String::from("no type"),
+            Type::Boolean => String::from("BOOLEAN"),
+            Type::Char( 8 ) => String::from("CHAR"),
+            Type::Char( x ) => format!("CHAR{}", x),
+            Type::Signed( 8 ) => String::from("SHORTINT"),
+            Type::Signed( 16 ) => String::from("INTEGER"),
+            Type::Signed( 32 ) => String::from("LONGINT"),
+            Type::Signed( _ ) => String::from("HUGEINT"),
+            Type::Unsigned( x ) => format!("UNSIGNED{}", x),
+            Type::Real( 32 ) => String::from("REAL"),
+            Type::Real( _ ) => String::from("LONGREAL"),
+            Type::Complex( 32 ) => String::from("COMPLEX"),
+            Type::Complex( _ ) => String::from("LONGCOMPLEX"),
+            Type::Set => String::from("SET"),
+            Type::Address => String::from("ADDRESS"),
+            Type::Size => String::from("SIZE"),
+            Type::Range => String::from("RANGE"),
+            Type::Any => String::from("ANYTYPE"),
+            Type::Nil => String::from("NIL"),
+            Type::String( _ ) => String::from("string"),
+            Type::Array( _ , x ) => format!("ARRAY OF {}", self.type_name(*x)),
+            Type::MathArray( _ , x ) => format!("ARRAY [*] OF {}", self.type_name(*x)),
+            Type::Record( .. ) => String::from("RECORD"),
+            Type::Object( .. ) => String::from("OBJECT"),
+            Type::Pointer( x ) => format!("POINTER TO {}", self.type_name(*x)),
+            Type::Procedure( .. ) => String::from("PROCEDURE"),
+            Type::Enumeration( _ ) => String::from("ENUM"),
+            Type::Definition( _ ) => String::from("DEFINITION")
+        }
+    }
+
+    fn get_constant(&self, entry: EntryId) -> Option<&ConstValue> {
+        match self.constants.get(&entry) {
+            Some( Some( x ) ) => Some( x ),
+            _ => None
+        }
+    }
+
+    fn get_parameter_mode(&self, entry: EntryId) -> Option<ParameterMode> {
+        self.parameter_modes.get(&entry).cloned()
+    }
+
+    /// Scope that names in 'CODE' block starting at position are looked up in
+    fn get_code_scope(&self, module: ScopeId, position: u32) -> Option<ScopeId> {
+        self.code_scopes.get(&( module, position )).copied()
+    }
+
+    /// Size in bytes of type, open arrays and types with errors have no size
+    fn type_size(&self, type_id: TypeId) -> Option<i64> {
+        match &self.types[self.base(type_id)] {
+            Type::Boolean => Some( 1 ),
+            Type::Char( x ) | Type::Signed( x ) | Type::Unsigned( x ) | Type::Real( x ) => Some( *x as i64 / 8 ),
+            Type::Complex( x ) => Some( *x as i64 / 4 ),
+            Type::Set | Type::Enumeration( _ ) => Some( 4 ),
+            Type::Address | Type::Size | Type::Nil | Type::Pointer( _ ) | Type::Object( .. ) | Type::Definition( _ ) | Type::Procedure( .. ) => Some( 8 ),
+            Type::Range => Some( 24 ),
+            Type::String( x ) => Some( *x as i64 + 1 ),
+            Type::Array( Some( n ) , x ) | Type::MathArray( Some( n ) , x ) => self.type_size(*x).and_then(|size| size.checked_mul(*n)),
+            Type::Record( .. ) => self.fields_layout(type_id, None).map(|x| x.0),
+            _ => None
+        }
+    }
+
+    /// Offset in bytes of record or object field from start of its record or object
+    fn field_offset(&self, field: EntryId) -> Option<i64> {
+        let scope = self.resolver.get_symbol_table().entry(field).scope;
+        let owner = self.types.iter().position(|x| matches!(x, Type::Record( _ , Some( s ) ) | Type::Object( _ , Some( s ) ) if *s == scope))?;
+        self.fields_layout(owner, Some( field ))?.1
+    }
+
+    /// Size in bytes of record, or of the memory block an object or pointer to record refers to
+    fn instance_size(&self, type_id: TypeId) -> Option<i64> {
+        self.fields_layout(type_id, None).map(|x| x.0)
+    }
+}
+
+/// Internal parts of type checker
+impl TypeChecker {
+    /// Size of record or object fields and offset of field when given. Fields of base type come first,
+    /// fields are aligned to their own size and the record to its largest field.
+    fn fields_layout(&self, type_id: TypeId, field: Option<EntryId>) -> Option<(i64, Option<i64>)> {
+        let ( base , scope ) = match &self.types[self.dereference(type_id)] {
+            Type::Record( b , s ) | Type::Object( b , s ) => ( *b, *s ),
+            _ => return None
+        };
+
+        let ( mut size , mut offset ) = match base {
+            Some( x ) => self.fields_layout(x, field)?,
+            _ => ( 0, None )
+        };
+        let mut alignment = 1;
+        if let Some( x ) = scope {
+            let table = self.resolver.get_symbol_table();
+            for entry in table.scope(x).entries.iter() {
+                if table.entry(*entry).kind == SymbolKind::Variable {
+                    let bytes = self.type_size(self.entry_types.get(entry).copied().unwrap_or(T_UNKNOWN))?;
+                    let align = bytes.clamp(1, 8);
+                    alignment = alignment.max(align);
+                    size = ( size + align - 1 ) / align * align;
+                    if field == Some( *entry ) {
+                        offset = Some( size )
+                    }
+                    size += bytes
+                }
+            }
+        }
+
+        Some( ( ( size + alignment - 1 ) / alignment * alignment, offset ) )
+    }
+
+    fn new_type(&mut self, value: Type) -> TypeId {
+        self.types.push(value);
+        self.types.len() - 1
+    }
+
+    /// Follow aliases to the type that defines the structure
+    fn base(&self, type_id: TypeId) -> TypeId {
+        let mut current = type_id;
+
+        loop {
+            match self.types[current] {
+                Type::Alias( x ) if x != current => current = x,
+                _ => return current
+            }
+        }
+    }
+
+    fn error(&mut self, code: &'static str, message: String, start: u32, end: u32) {
+        self.errors.push(Diagnostic::error(code, message, start, end))
+    }
+
+    /// Entry declared by identifier definition in current scope
+    fn declared_entry(&self, id: &Node) -> Option<EntryId> {
+        match identifier_definition(id) {
+            Some( ( name , _ , s , _ ) ) => {
+                let table = self.resolver.get_symbol_table();
+                match table.lookup_local(self.scope, name.as_str()) {
+                    Some( x ) if table.entry(x).start == s => Some( x ),
+                    _ => None
+                }
+            },
+            _ => None
+        }
+    }
+
+    /// Make scope opened by declaration node current, returning scope to restore
+    fn enter_scope(&mut self, position: u32) -> ScopeId {
+        let previous = self.scope;
+
+        if let Some( x ) = self.resolver.get_node_scope(self.module, position) {
+            self.scope = x
+        }
+
+        previous
+    }
+
+    /// Register enumeration elements as constants, so they have values before the enumeration type is checked
+    fn register_enumeration(&mut self, type_node: &Node) {
+        if let Node::EnumerationType( s , _ , _ , _ , elements , _ , _ ) = type_node {
+            let scope = match self.resolver.get_node_scope(self.module, *s) {
+                Some( x ) => x,
+                _ => return
+            };
+            let outer = self.scope;
+            self.scope = scope;
+
+            let mut previous = None;
+            for element in elements.iter() {
+                if let Node::EnumElement( _ , _ , id , value ) = &**element {
+                    if let Some( x ) = self.declared_entry(id) {
+                        match self.constant_declarations.contains_key(&x) {
+                            true => (),
+                            _ => {
+                                let value = match value {
+                                    Some( ( _ , v ) ) => Some( v.clone() ),
+                                    _ => None
+                                };
+                                self.constant_declarations.insert(x, ConstantDeclaration { scope, value, previous });
+                            }
+                        }
+                        previous = Some( x )
+                    }
+                }
+            }
+
+            self.scope = outer
+        }
+    }
+
+    /// Value of named constant or enumeration element, evaluated in scope of its declaration
+    fn constant_entry(&mut self, entry: EntryId, start: u32, end: u32) -> Folded {
+        match self.constants.get(&entry) {
+            Some( Some( x ) ) => return Ok( x.clone() ),
+            Some( None ) => return Err(ConstError::Propagated),
+            _ => ()
+        }
+
+        if self.evaluating.contains(&entry) {
+            let name = self.resolver.get_symbol_table().entry(entry).name.clone();
+            return Err(ConstError::Invalid(Diagnostic::error(E_NOT_CONSTANT, format!("Constant '{}' is defined in terms of itself", name), start, end)))
+        }
+
+        let ( scope , value , previous ) = match self.constant_declarations.get(&entry) {
+            Some( x ) => ( x.scope, x.value.clone(), x.previous ),
+            _ => return Err(ConstError::NotConstant(start, end))
+        };
+
+        let ( module , outer ) = ( self.module , self.scope );
+        self.module = self.resolver.get_symbol_table().enclosing_module(scope);
+        self.scope = scope;
+        self.evaluating.push(entry);
+
+        let folded = match ( value , previous ) {
+            ( Some( x ) , _ ) => self.constant(&x),
+            ( None , Some( x ) ) => {
+                let symbol = self.resolver.get_symbol_table().entry(entry);
+                let ( s , e ) = ( symbol.start , symbol.end );
+                match self.constant_entry(x, s, e) {
+                    Ok( ConstValue::Integer( v ) ) => {
+                        match v.checked_add(1) {
+                            Some( y ) => Ok( ConstValue::Integer(y) ),
+                            _ => Err(ConstError::Invalid(Diagnostic::error(E_OUT_OF_RANGE, String::from("Enumeration value out of range"), s, e)))
+                        }
+                    },
+                    Ok( _ ) => Err(ConstError::Propagated),
+                    Err( x ) => Err(x)
+                }
+            },
+            _ => Ok( ConstValue::Integer(0) )
+        };
+
+        self.evaluating.pop();
+        self.module = module;
+        self.scope = outer;
+
+        match folded {
+            Ok( x ) => {
+                self.constants.insert(entry, Some( x.clone() ));
+                Ok( x )
+            },
+            Err( x ) => {
+                self.constants.insert(entry, None);
+                match x {
+                    ConstError::Propagated => (),
+                    _ => {
+                        self.constant_errors.insert(entry, x);
+                    }
+                }
+                Err(ConstError::Propagated)
+            }
+        }
+    }
+
+    /// Errors found when evaluating constant are reported at its declaration
+    fn report_constant_error(&mut self, entry: EntryId) {
+        match self.constant_errors.remove(&entry) {
+            Some( ConstError::NotConstant( s , e ) ) => self.errors.push(not_constant(s, e)),
+            Some( ConstError::Invalid( x ) ) => self.errors.push(x),
+            _ => ()
+        }
+    }
+
+    /// Value of expression that must be constant, errors are reported
+    fn constant_value(&mut self, node: &Node) -> Option<ConstValue> {
+        match self.constant(node) {
+            Ok( x ) => Some( x ),
+            Err( ConstError::NotConstant( s , e ) ) => {
+                self.errors.push(not_constant(s, e));
+                None
+            },
+            Err( ConstError::Invalid( x ) ) => {
+                self.errors.push(x);
+                None
+            },
+            _ => None
+        }
+    }
+
+    /// Length of array dimension, must be constant and not negative
+    fn array_length(&mut self, node: &Node) -> Option<i64> {
+        let length_type = self.expression(node);
+        let ( s , e ) = expression_span(node);
+
+        match self.is_integer(length_type) || length_type == T_UNKNOWN {
+            true => (),
+            _ => {
+                self.error(E_TYPE_MISMATCH, format!("Array length must be an integer, found '{}'", self.type_name(length_type)), s, e);
+                return None
+            }
+        }
+
+        match self.constant_value(node) {
+            Some( ConstValue::Integer( x ) ) if x >= 0 => Some( x ),
+            Some( ConstValue::Integer( x ) ) => {
+                self.error(E_OUT_OF_RANGE, format!("Array length {} must not be negative", x), s, e);
+                None
+            },
+            _ => None
+        }
+    }
+
+    /// Entry bound to identifier, or to member name in qualified identifier
+    fn bound_entry(&self, node: &Node) -> Option<EntryId> {
+        match node {
+            Node::Ident( s , _ , _ ) => self.resolver.get_binding(self.module, *s),
+            Node::QualifiedIdentifier( _ , _ , _ , _ , right ) => self.bound_entry(right),
+            Node::UnaryExpression( _ , _ , _ , Some( designators ) , _ ) => {
+                match designators.as_slice() {
+                    [ x ] => {
+                        match &**x {
+                            Node::DotName( _ , _ , _ , id ) => self.bound_entry(id),
+                            _ => None
+                        }
+                    },
+                    _ => None
+                }
+            },
+            _ => None
+        }
+    }
+
+    /// Type denoted by type argument of 'MIN', 'MAX' and 'SIZEOF'
+    fn type_argument(&self, node: &Node) -> Option<TypeId> {
+        match node {
+            Node::Address( _ , _ , _ , None ) => Some( T_ADDRESS ),
+            Node::Size( _ , _ , _ , None ) => Some( T_SIZE ),
+            _ => {
+                let entry = self.bound_entry(node)?;
+                match self.resolver.get_symbol_table().entry(entry).kind {
+                    SymbolKind::Type => self.entry_types.get(&entry).copied(),
+                    _ => None
+                }
+            }
+        }
+    }
+
+    /// Smallest or largest value of basic type
+    fn type_limit(&self, name: &str, type_id: TypeId, start: u32, end: u32) -> Folded {
+        let maximum = name == "MAX";
+
+        match ( &self.types[self.base(type_id)] , maximum ) {
+            ( Type::Signed( x ) , true ) => Ok( ConstValue::Integer(( ( 1i128 << ( x - 1 ) ) - 1 ) as i64) ),
+            ( Type::Signed( x ) , _ ) => Ok( ConstValue::Integer(( -( 1i128 << ( x - 1 ) ) ) as i64) ),
+            ( Type::Unsigned( 64 ) , true ) | ( Type::Address , true ) => {
+                Err(ConstError::Invalid(Diagnostic::error(E_OUT_OF_RANGE, format!("MAX({}) is out of range of HUGEINT", self.type_name(type_id)), start, end)))
+            },
+            ( Type::Unsigned( x ) , true ) => Ok( ConstValue::Integer(( 1i64 << x ) - 1) ),
+            ( Type::Unsigned( _ ) , _ ) | ( Type::Address , _ ) => Ok( ConstValue::Integer(0) ),
+            ( Type::Size , true ) => Ok( ConstValue::Integer(i64::MAX) ),
+            ( Type::Size , _ ) => Ok( ConstValue::Integer(i64::MIN) ),
+            ( Type::Char( 32 ) , true ) => Ok( ConstValue::Char(0x10ffff) ),
+            ( Type::Char( x ) , true ) => Ok( ConstValue::Char(( 1u32 << x ) - 1) ),
+            ( Type::Char( _ ) , _ ) => Ok( ConstValue::Char(0) ),
+            ( Type::Real( 32 ) , true ) => Ok( ConstValue::Real(f32::MAX as f64) ),
+            ( Type::Real( 32 ) , _ ) => Ok( ConstValue::Real(f32::MIN as f64) ),
+            ( Type::Real( _ ) , true ) => Ok( ConstValue::Real(f64::MAX) ),
+            ( Type::Real( _ ) , _ ) => Ok( ConstValue::Real(f64::MIN) ),
+            ( Type::Set , true ) => Ok( ConstValue::Integer(SET_ELEMENTS - 1) ),
+            ( Type::Set , _ ) => Ok( ConstValue::Integer(0) ),
+            ( Type::Boolean , x ) => Ok( ConstValue::Boolean(x) ),
+            ( Type::Unknown , _ ) => Err(ConstError::Propagated),
+            _ => Err(ConstError::NotConstant(start, end))
+        }
+    }
+
+    /// Fold constant expression. Nothing is reported here, the caller decides if a value is required.
+    fn constant(&mut self, node: &Node) -> Folded {
+        let ( start , end ) = expression_span(node);
+
+        match node {
+            Node::Ident( .. ) |
+            Node::QualifiedIdentifier( .. ) => {
+                match self.bound_entry(node) {
+                    Some( x ) => self.constant_entry(x, start, end),
+                    _ => Err(ConstError::Propagated)     /* Undeclared, already reported */
+                }
+            },
+            Node::UnaryExpression( _ , _ , primary , Some( designators ) , _ ) => {
+                match ( designators.as_slice() , self.bound_entry(primary) ) {
+                    ( [ designator ] , Some( x ) ) => {
+                        let ( kind , universe , name ) = {
+                            let symbol = self.resolver.get_symbol_table().entry(x);
+                            ( symbol.kind.clone(), symbol.scope == UNIVERSE_SCOPE, symbol.name.clone() )
+                        };
+
+                        match ( &**designator , kind , universe ) {
+                            ( Node::DotName( .. ) , SymbolKind::Module , _ ) |
+                            ( Node::DotName( .. ) , SymbolKind::Type , _ ) => {
+                                match self.bound_entry(node) {
+                                    Some( x ) => self.constant_entry(x, start, end),
+                                    _ => Err(ConstError::Propagated)
+                                }
+                            },
+                            ( Node::Call( _ , _ , _ , arguments , _ ) , SymbolKind::Procedure , true ) => {
+                                let arguments = expression_list(arguments);
+                                self.constant_builtin(name.as_str(), &arguments, start, end)
+                            },
+                            _ => Err(ConstError::NotConstant(start, end))
+                        }
+                    },
+                    _ => Err(ConstError::NotConstant(start, end))
+                }
+            },
+            Node::ParenthesisExpression( _ , _ , _ , x , _ ) => self.constant(x),
+            Node::UnaryPlus( _ , _ , _ , x ) => {
+                let value = self.constant(x)?;
+                unary("+", value, start, end)
+            },
+            Node::UnaryMinus( _ , _ , _ , x ) => {
+                let value = self.constant(x)?;
+                unary("-", value, start, end)
+            },
+            Node::UnaryNot( _ , _ , _ , x ) => {
+                let value = self.constant(x)?;
+                unary("~", value, start, end)
+            },
+            Node::Times( _ , _ , l , _ , r ) => self.constant_binary("*", l, r, start, end),
+            Node::Slash( _ , _ , l , _ , r ) => self.constant_binary("/", l, r, start, end),
+            Node::Div( _ , _ , l , _ , r ) => self.constant_binary("DIV", l, r, start, end),
+            Node::Mod( _ , _ , l , _ , r ) => self.constant_binary("MOD", l, r, start, end),
+            Node::Plus( _ , _ , l , _ , r ) => self.constant_binary("+", l, r, start, end),
+            Node::Minus( _ , _ , l , _ , r ) => self.constant_binary("-", l, r, start, end),
+            Node::And( _ , _ , l , _ , r ) => self.constant_binary("&", l, r, start, end),
+            Node::Or( _ , _ , l , _ , r ) => self.constant_binary("OR", l, r, start, end),
+            Node::Equal( _ , _ , l , _ , r ) => self.constant_binary("=", l, r, start, end),
+            Node::NotEqual( _ , _ , l , _ , r ) => self.constant_binary("#", l, r, start, end),
+            Node::Less( _ , _ , l , _ , r ) => self.constant_binary("<", l, r, start, end),
+            Node::LessEqual( _ , _ , l , _ , r ) => self.constant_binary("<=", l, r, start, end),
+            Node::Greater( _ , _ , l , _ , r ) => self.constant_binary(">", l, r, start, end),
+            Node::GreaterEqual( _ , _ , l , _ , r ) => self.constant_binary(">=", l, r, start, end),
+            Node::In( _ , _ , l , _ , r ) => self.constant_binary("IN", l, r, start, end),
+            Node::Set( _ , _ , _ , elements , _ , _ ) => {
+                let mut bits : u32 = 0;
+                for element in elements.iter() {
+                    let ( s , e ) = expression_span(element);
+                    let ( from , to ) = match &**element {
+                        Node::Range( _ , _ , Some( from ) , _ , Some( to ) , _ , None ) => ( self.constant(from)? , self.constant(to)? ),
+                        Node::Range( .. ) => return Err(ConstError::NotConstant(s, e)),
+                        _ => {
+                            let value = self.constant(element)?;
+                            ( value.clone() , value )
+                        }
+                    };
+                    match ( from , to ) {
+                        ( ConstValue::Integer( a ) , ConstValue::Integer( b ) ) => {
+                            if let ConstValue::Set( x ) = set_elements(a, b, s, e)? {
+                                bits |= x
+                            }
+                        },
+                        _ => return Err(ConstError::Propagated)
+                    }
+                }
+                Ok( ConstValue::Set(bits) )
+            },
+            Node::Size( _ , _ , _ , Some( x ) ) => {
+                match self.type_argument(&x.1).map(|t| self.type_size(t)) {
+                    Some( Some( size ) ) => Ok( ConstValue::Integer(size) ),
+                    Some( None ) => Err(ConstError::Propagated),
+                    _ => Err(ConstError::NotConstant(start, end))
+                }
+            },
+            _ => literal(node).map_err(|x| match x { ConstError::Propagated => ConstError::NotConstant(start, end), y => y })
+        }
+    }
+
+    fn constant_binary(&mut self, operator: &str, left: &Node, right: &Node, start: u32, end: u32) -> Folded {
+        let l = self.constant(left)?;
+        let r = self.constant(right)?;
+        binary(operator, l, r, start, end)
+    }
+
+    /// Predeclared functions in constant expressions, some of them take a type as argument
+    fn constant_builtin(&mut self, name: &str, arguments: &Vec<&Node>, start: u32, end: u32) -> Folded {
+        match ( name , arguments.len() ) {
+            ( "MIN" , 1 ) | ( "MAX" , 1 ) => {
+                match self.type_argument(arguments[0]) {
+                    Some( x ) => self.type_limit(name, x, start, end),
+                    _ => Err(ConstError::NotConstant(start, end))
+                }
+            },
+            ( "SIZEOF" , 1 ) => {
+                match self.type_argument(arguments[0]).map(|t| self.type_size(t)) {
+                    Some( Some( size ) ) => Ok( ConstValue::Integer(size) ),
+                    Some( None ) => Err(ConstError::Propagated),
+                    _ => Err(ConstError::NotConstant(start, end))
+                }
+            },
+            _ => {
+                let mut values = Vec::<ConstValue>::new();
+                for argument in arguments.iter() {
+                    values.push(self.constant(argument)?)
+                }
+                builtin(name, &values, start, end)
+            }
+        }
+    }
+
+    fn check_module(&mut self, module: &Node, pass: &Pass) {
+        if let Node::Module( _ , _ , _ , _ , id , _ , _ , _ , decl , body , _ , _ , _ ) = module {
+            let scope = match identifier_definition(id) {
+                Some( ( name , _ , _ , _ ) ) => self.resolver.get_module_scope(name.as_str()),
+                _ => None
+            };
+
+            match scope {
+                Some( x ) => {
+                    self.module = x;
+                    self.scope = x
+                },
+                _ => return
+            }
+
+            self.result = T_NOTYPE;
+            self.self_type = T_UNKNOWN;
+
+            if let Some( x ) = decl {
+                self.declarations(x, pass)
+            }
+
+            if let ( Pass::Bodies , Some( x ) ) = ( pass , body ) {
+                self.body(x)
+            }
+        }
+    }
+
+    fn declarations(&mut self, declarations: &Node, pass: &Pass) {
+        if let Node::DeclarationSequence( _ , _ , constants , types , variables , procedures , operators , definitions , _ ) = declarations {
+            for definition in definitions.iter() {
+                self.definition(definition, pass)
+            }
+
+            for type_declaration in types.iter() {
+                if let Node::TypeDeclaration( _ , _ , _ , nodes ) = &**type_declaration {
+                    for node in nodes.iter() {
+                        if let Node::TypeDeclarationElement( _ , _ , id , _ , type_node , _ ) = &**node {
+                            let entry = self.declared_entry(id);
+
+                            match ( pass , entry ) {
+                                ( Pass::Placeholders , Some( x ) ) => {
+                                    let placeholder = self.new_type(Type::Unknown);
+                                    self.entry_types.insert(x, placeholder);
+                                    self.register_enumeration(type_node)
+                                },
+                                ( Pass::Types , Some( x ) ) => {
+                                    let placeholder = self.entry_types[&x];
+                                    let declared = self.type_node(type_node);
+                                    let name = self.resolver.get_symbol_table().entry(x).name.clone();
+
+                                    self.types[placeholder] = Type::Alias(declared);
+                                    self.names.insert(placeholder, name.clone());
+                                    match self.names.contains_key(&declared) || declared <= T_OBJECT {
+                                        true => (),
+                                        _ => {
+                                            self.names.insert(declared, name);
+                                        }
+                                    }
+                                },
+                                _ => ()
+                            }
+
+                            let owner = match entry {
+                                Some( x ) => self.entry_types.get(&x).copied().unwrap_or(T_UNKNOWN),
+                                _ => T_UNKNOWN
+                            };
+                            self.nested(type_node, pass, owner)
+                        }
+                    }
+                }
+            }
+
+            for constant_declaration in constants.iter() {
+                if let Node::ConstDeclaration( _ , _ , _ , nodes ) = &**constant_declaration {
+                    for node in nodes.iter() {
+                        if let Node::Const( _ , _ , id , _ , value ) = &**node {
+                            match ( pass , self.declared_entry(id) ) {
+                                ( Pass::Placeholders , Some( x ) ) => {
+                                    self.constant_declarations.insert(x, ConstantDeclaration { scope: self.scope, value: Some( value.clone() ), previous: None });
+                                },
+                                ( Pass::Signatures , Some( x ) ) => {
+                                    let type_id = self.expression(value);
+                                    let ( s , e ) = expression_span(value);
+                                    let folded = self.constant_entry(x, s, e);
+
+                                    /* Integer constants get the smallest type holding their value */
+                                    let constant_type = match ( folded , self.is_integer(type_id) ) {
+                                        ( Ok( ConstValue::Integer( v ) ) , true ) => integer_type(v as i128),
+                                        _ => type_id
+                                    };
+                                    self.entry_types.insert(x, constant_type);
+                                    self.report_constant_error(x)
+                                },
+                                _ => ()
+                            }
+                        }
+                    }
+                }
+            }
+
+            for var_declaration in variables.iter() {
+                if let Node::VarDeclaration( _ , _ , _ , nodes ) = &**var_declaration {
+                    for node in nodes.iter() {
+                        self.variables(node, pass)
+                    }
+                }
+            }
+
+            for procedure in procedures.iter() {
+                self.procedure(procedure, pass)
+            }
+
+            for operator in operators.iter() {
+                self.procedure(operator, pass)
+            }
+        }
+    }
+
+    /// Definition gets its type before other types can name it, headings get procedure types with signatures
+    fn definition(&mut self, definition: &Node, pass: &Pass) {
+        if let Node::Definition( s , _ , _ , id , _ , headings , _ , _ ) = definition {
+            let entry = self.declared_entry(id);
+            let previous = self.enter_scope(*s);
+
+            match ( pass , entry ) {
+                ( Pass::Placeholders , Some( x ) ) => {
+                    let type_id = self.new_type(Type::Definition(Some( self.scope )));
+                    let name = self.resolver.get_symbol_table().entry(x).name.clone();
+                    self.entry_types.insert(x, type_id);
+                    self.names.insert(type_id, name);
+                },
+                ( Pass::Signatures , _ ) => {
+                    for heading in headings.iter() {
+                        if let Node::ProcedureHeading( _ , _ , _ , _ , x , parameters , _ ) = &**heading {
+                            let procedure_type = match parameters {
+                                Some( p ) => self.formal_parameters(p, false),
+                                _ => Type::Procedure(Box::default(), T_NOTYPE)
+                            };
+                            let type_id = self.new_type(procedure_type);
+
+                            if let Some( e ) = self.declared_entry(x) {
+                                self.entry_types.insert(e, type_id);
+                            }
+                        }
+                    }
+                },
+                _ => ()
+            }
+
+            self.scope = previous
+        }
+    }
+
+    /// Every procedure heading of definitions named by object must be a method of object with same signature
+    fn check_implementations(&mut self, object: TypeId) {
+        let definitions = self.implementations.get(&object).cloned().unwrap_or_default();
+
+        for ( definition , s , e ) in definitions.iter() {
+            let scope = match self.types[self.base(*definition)] {
+                Type::Definition( Some( x ) ) => x,
+                Type::Definition( None ) | Type::Unknown => continue,
+                _ => {
+                    self.error(E_TYPE_MISMATCH, format!("'{}' is not a definition", self.type_name(*definition)), *s, *e);
+                    continue
+                }
+            };
+
+            let headings = self.resolver.get_symbol_table().scope(scope).entries.clone();
+            for heading in headings.iter() {
+                let name = self.resolver.get_symbol_table().entry(*heading).name.clone();
+                let expected = self.entry_types.get(heading).copied().unwrap_or(T_UNKNOWN);
+
+                match self.lookup_field(object, name.as_str()) {
+                    Some( x ) if self.resolver.get_symbol_table().entry(x).kind == SymbolKind::Procedure => {
+                        let found = self.entry_types.get(&x).copied().unwrap_or(T_UNKNOWN);
+
+                        match self.same_type(found, expected) {
+                            true => (),
+                            _ => self.error(E_SIGNATURE_MISMATCH, format!("Method '{}' does not match its heading in definition '{}'", name, self.type_name(*definition)), *s, *e)
+                        }
+                    },
+                    _ => self.error(E_NOT_IMPLEMENTED, format!("Object does not implement method '{}' of definition '{}'", name, self.type_name(*definition)), *s, *e)
+                }
+            }
+        }
+    }
+
+    /// Variables, record fields and object fields
+    fn variables(&mut self, var: &Node, pass: &Pass) {
+        if let Node::Var( _ , _ , list , _ , type_node ) = var {
+            let type_id = match pass {
+                Pass::Signatures => self.type_node(type_node),
+                _ => T_UNKNOWN
+            };
+
+            if let Node::VarList( _ , _ , names , _ ) = &**list {
+                for name in names.iter() {
+                    if let Node::VarName( _ , _ , id , _ , initializer ) = &**name {
+                        match ( pass , self.declared_entry(id) ) {
+                            ( Pass::Signatures , Some( x ) ) => {
+                                self.entry_types.insert(x, type_id);
+                            },
+                            ( Pass::Bodies , Some( x ) ) => {
+                                if let Some( ( _ , value ) ) = initializer {
+                                    let target = self.entry_types.get(&x).copied().unwrap_or(T_UNKNOWN);
+                                    let source = self.expression(value);
+                                    let ( s , e ) = expression_span(value);
+                                    self.check_assignment(source, target, s, e)
+                                }
+                            },
+                            _ => ()
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Procedures and operators, parameters and local declarations are in scope of procedure
+    fn procedure(&mut self, procedure: &Node, pass: &Pass) {
+        let ( start , receiver , id , parameters , decl , body ) = match procedure {
+            Node::Procedure( s , _ , _ , _ , receiver , id , parameters , _ , decl , body , _ , _ ) => {
+                ( *s, match receiver { Some( ( _ , x , _ ) ) => Some( &**x ), _ => None }, Some( &**id ), parameters.as_ref().map(|x| &**x), decl, body )
+            },
+            Node::Operator( s , _ , _ , _ , _ , _ , _ , parameters , _ , decl , body , _ , _ ) => ( *s, None, None, Some( &**parameters ), decl, body ),
+            _ => return
+        };
+
+        /* Procedure bound by receiver is not declared by name */
+        let entry = match ( receiver , id ) {
+            ( None , Some( x ) ) => self.declared_entry(x),
+            _ => None
+        };
+
+        let previous = self.enter_scope(start);
+
+        if pass == &Pass::Signatures {
+            if let Some( x ) = receiver {
+                self.formal_parameters(x, true);
+            }
+
+            let procedure_type = match parameters {
+                Some( x ) => self.formal_parameters(x, true),
+                _ => Type::Procedure(Box::default(), T_NOTYPE)
+            };
+
+            if let Type::Procedure( _ , result ) = &procedure_type {
+                self.results.insert(self.scope, *result);
+            }
+
+            let type_id = self.new_type(procedure_type);
+            if let Some( x ) = entry {
+                self.entry_types.insert(x, type_id);
+            }
+        }
+
+        if let Some( x ) = decl {
+            self.declarations(x, pass)
+        }
+
+        if let ( Pass::Bodies , Some( x ) ) = ( pass , body ) {
+            let result = self.result;
+            self.result = self.results.get(&self.scope).copied().unwrap_or(T_NOTYPE);
+            self.body(x);
+            self.result = result
+        }
+
+        self.scope = previous
+    }
+
+    /// Procedure type from formal parameters. Parameter entries get their types when declared in current scope.
+    fn formal_parameters(&mut self, parameters: &Node, declare: bool) -> Type {
+        match parameters {
+            Node::FormalParameters( _ , _ , _ , nodes , _ , _ , result ) => {
+                let mut list = Vec::<(ParameterMode, TypeId)>::new();
+
+                for node in nodes.iter() {
+                    if let Type::Procedure( x , _ ) = self.formal_parameters(node, declare) {
+                        list.append(&mut x.to_vec())
+                    }
+                }
+
+                let result_type = match result {
+                    Some( ( _ , _ , x ) ) => self.type_node(x),
+                    _ => T_NOTYPE
+                };
+
+                Type::Procedure(Box::new(list), result_type)
+            },
+            Node::ParameterDeclaration( _ , _ , mode , nodes , _ , _ , type_node ) => {
+                let parameter_mode = match mode {
+                    Some( x ) => {
+                        match **x {
+                            Symbols::Var( _ , _ ) => ParameterMode::Var,
+                            Symbols::Const( _ , _ ) => ParameterMode::Const,
+                            _ => ParameterMode::Value
+                        }
+                    },
+                    _ => ParameterMode::Value
+                };
+                let type_id = self.type_node(type_node);
+                let mut list = Vec::<(ParameterMode, TypeId)>::new();
+
+                for node in nodes.iter() {
+                    if let Node::Parameter( _ , _ , id , _ , _ ) = &**node {
+                        list.push( ( parameter_mode.clone(), type_id ) );
+
+                        if let ( true , Some( x ) ) = ( declare , self.declared_entry(id) ) {
+                            self.entry_types.insert(x, type_id);
+                            self.parameter_modes.insert(x, parameter_mode.clone());
+                        }
+                    }
+                }
+
+                Type::Procedure(Box::new(list), T_NOTYPE)
+            },
+            _ => Type::Procedure(Box::default(), T_NOTYPE)
+        }
+    }
+
+    /// Visit scopes inside a named type, like methods of records and declarations of objects
+    fn nested(&mut self, type_node: &Node, pass: &Pass, owner: TypeId) {
+        match type_node {
+            Node::RecordType( s , _ , _ , _ , _ , procedures , _ ) => {
+                let previous = self.enter_scope(*s);
+                let self_type = self.self_type;
+                self.self_type = owner;
+
+                if let Some( ( nodes , _ ) ) = procedures {
+                    for node in nodes.iter() {
+                        self.procedure(node, pass)
+                    }
+                }
+
+                self.self_type = self_type;
+                self.scope = previous
+            },
+            Node::ObjectType( s , _ , _ , _ , _ , _ , decl , body , _ , _ ) => {
+                let previous = self.enter_scope(*s);
+                let self_type = self.self_type;
+                self.self_type = owner;
+
+                if let Some( x ) = decl {
+                    self.declarations(x, pass)
+                }
+
+                if pass == &Pass::Bodies {
+                    self.check_implementations(self.base(owner))
+                }
+
+                if let ( Pass::Bodies , Some( x ) ) = ( pass , body ) {
+                    let result = self.result;
+                    self.result = T_NOTYPE;
+                    self.body(x);
+                    self.result = result
+                }
+
+                self.self_type = self_type;
+                self.scope = previous
+            },
+            Node::PointerType( _ , _ , _ , _ , _ , target ) => self.nested(target, pass, owner),
+            _ => ()
+        }
+    }
+
+    /// Type described by type node, named types give the type table slot of their declaration
+    fn type_node(&mut self, type_node: &Node) -> TypeId {
+        match type_node {
+            Node::Ident( .. ) |
+            Node::QualifiedIdentifier( .. ) => {
+                let operand = self.operand(type_node);
+                match operand.mode {
+                    OperandMode::Type => operand.type_id,
+                    _ => {
+                        match operand.type_id {
+                            T_UNKNOWN => (),
+                            _ => {
+                                let ( s , e ) = expression_span(type_node);
+                                self.error(E_TYPE_MISMATCH, String::from("Expecting a type"), s, e)
+                            }
+                        }
+                        T_UNKNOWN
+                    }
+                }
+            },
+            Node::Address( .. ) => T_ADDRESS,
+            Node::Size( .. ) => T_SIZE,
+            Node::ArrayType( _ , _ , _ , dimensions , _ , element ) => {
+                let mut type_id = self.type_node(element);
+
+                match dimensions {
+                    Some( ( nodes , _ ) ) => {
+                        for node in nodes.iter().rev() {
+                            let length = self.array_length(node);
+                            type_id = self.new_type(Type::Array(length, type_id))
+                        }
+                    },
+                    _ => type_id = self.new_type(Type::Array(None, type_id))
+                }
+
+                type_id
+            },
+            Node::MathArrayType( _ , _ , _ , dimensions , _ , element ) => {
+                let mut type_id = self.type_node(element);
+
+                match dimensions {
+                    Some( ( nodes , _ ) ) => {
+                        for node in nodes.iter().rev() {
+                            let length = match &**node {
+                                Node::MathArraySize( _ , _ , Some( x ) , _ ) => self.array_length(x),
+                                Node::MathArraySize( .. ) => None,
+                                _ => self.array_length(node)
+                            };
+                            type_id = self.new_type(Type::MathArray(length, type_id))
+                        }
+                    },
+                    _ => type_id = self.new_type(Type::MathArray(None, type_id))
+                }
+
+                type_id
+            },
+            Node::RecordType( s , _ , _ , base , fields , _ , _ ) => {
+                let base_type = match base {
+                    Some( ( _ , x , _ ) ) => Some( self.type_node(x) ),
+                    _ => None
+                };
+
+                let previous = self.enter_scope(*s);
+                let scope = self.scope;
+
+                if let Some( ( nodes , _ ) ) = fields {
+                    for node in nodes.iter() {
+                        self.variables(node, &Pass::Signatures)
+                    }
+                }
+
+                self.scope = previous;
+                self.new_type(Type::Record(base_type, Some( scope )))
+            },
+            Node::ObjectType( s , _ , _ , _ , base , implements , _ , _ , _ , _ ) => {
+                let base_type = match base {
+                    Some( ( _ , x , _ ) ) => Some( self.type_node(x) ),
+                    _ => None
+                };
+                let scope = self.resolver.get_node_scope(self.module, *s);
+                let type_id = self.new_type(Type::Object(base_type, scope));
+
+                if let Some( ( _ , nodes , _ ) ) = implements {
+                    let mut definitions = Vec::<(TypeId, u32, u32)>::new();
+                    for node in nodes.iter() {
+                        let ( s , e ) = expression_span(node);
+                        definitions.push( ( self.type_node(node), s, e ) )
+                    }
+                    self.implementations.insert(type_id, definitions);
+                }
+
+                type_id
+            },
+            Node::ObjectTypeEmpty( .. ) => T_OBJECT,
+            Node::PointerType( _ , _ , _ , _ , _ , target ) => {
+                let target_type = self.type_node(target);
+                self.new_type(Type::Pointer(target_type))
+            },
+            Node::ProcedureType( _ , _ , _ , _ , parameters ) => {
+                let procedure_type = match parameters {
+                    Some( x ) => self.formal_parameters(x, false),
+                    _ => Type::Procedure(Box::default(), T_NOTYPE)
+                };
+                self.new_type(procedure_type)
+            },
+            Node::EnumerationType( s , _ , _ , _ , elements , _ , _ ) => {
+                let scope = self.resolver.get_node_scope(self.module, *s);
+                let type_id = self.new_type(Type::Enumeration(scope));
+
+                self.register_enumeration(type_node);
+
+                let previous = self.scope;
+                if let Some( x ) = scope {
+                    self.scope = x
+                }
+
+                for element in elements.iter() {
+                    if let Node::EnumElement( _ , _ , id , value ) = &**element {
+                        if let Some( ( _ , x ) ) = value {
+                            let value_type = self.expression(x);
+                            match self.is_integer(value_type) || value_type == T_UNKNOWN {
+                                true => (),
+                                _ => {
+                                    let ( s , e ) = expression_span(x);
+                                    self.error(E_TYPE_MISMATCH, format!("Enumeration value must be an integer, found '{}'", self.type_name(value_type)), s, e)
+                                }
+                            }
+                        }
+                        if let Some( x ) = self.declared_entry(id) {
+                            let ( s , e ) = expression_span(id);
+                            let _ = self.constant_entry(x, s, e);
+                            self.report_constant_error(x);
+                            self.entry_types.insert(x, type_id);
+                        }
+                    }
+                }
+
+                self.scope = previous;
+                type_id
+            },
+            _ => T_UNKNOWN  /* Cells and ports are not type checked */
+        }
+    }
+
+    fn body(&mut self, body: &Node) {
+        match body {
+            Node::Body( _ , _ , _ , _ , statements , finally ) => {
+                self.statement(statements);
+                if let Some( ( _ , x ) ) = finally {
+                    self.statement(x)
+                }
+            },
+            Node::BodyCode( _ , _ , _ , block ) => {
+                if let Node::CodeBlock( s , _ , _ ) = &**block {
+                    self.code_scopes.insert(( self.module, *s ), self.scope);
+                }
+            },
+            _ => ()
+        }
+    }
+
+    fn statement(&mut self, statement: &Node) {
+        match statement {
+            Node::StatementSequence( _ , _ , nodes , _ ) => {
+                for node in nodes.iter() {
+                    self.statement(node)
+                }
+            },
+            Node::StatementBlock( _ , _ , _ , _ , x , _ ) |
+            Node::Else( _ , _ , _ , x ) |
+            Node::Loop( _ , _ , _ , x , _ ) => self.statement(x),
+            Node::If( _ , _ , _ , condition , _ , x , elsif_nodes , else_node , _ ) => {
+                self.condition(condition);
+                self.statement(x);
+                if let Some( nodes ) = elsif_nodes {
+                    for node in nodes.iter() {
+                        self.statement(node)
+                    }
+                }
+                if let Some( node ) = else_node {
+                    self.statement(node)
+                }
+            },
+            Node::Elsif( _ , _ , _ , condition , _ , x ) |
+            Node::While( _ , _ , _ , condition , _ , x , _ ) |
+            Node::Repeat( _ , _ , _ , x , _ , condition ) => {
+                self.condition(condition);
+                self.statement(x)
+            },
+            Node::Await( _ , _ , _ , condition ) => self.condition(condition),
+            Node::With( _ , _ , _ , designator , _ , nodes , else_node , _ ) => {
+                let tested = self.operand(designator).type_id;
+                let entry = match &**designator {
+                    Node::Ident( s , _ , _ ) => self.resolver.get_binding(self.module, *s),
+                    _ => None
+                };
+                for node in nodes.iter() {
+                    if let Node::WithElement( _ , _ , _ , guard , _ , x ) = &**node {
+                        let guard_type = self.type_node(guard);
+                        match self.extensible(tested) && self.extensible(guard_type) && self.extends(guard_type, tested) {
+                            true => (),
+                            _ => {
+                                let ( s , e ) = expression_span(guard);
+                                self.error(E_TYPE_MISMATCH, format!("'{}' is not an extension of '{}' in 'WITH'", self.type_name(guard_type), self.type_name(tested)), s, e)
+                            }
+                        }
+
+                        /* Variable has the guard type inside the branch */
+                        let previous = entry.and_then(|y| self.guards.insert(y, guard_type));
+                        self.statement(x);
+                        match ( entry , previous ) {
+                            ( Some( y ) , Some( z ) ) => {
+                                self.guards.insert(y, z);
+                            },
+                            ( Some( y ) , None ) => {
+                                self.guards.remove(&y);
+                            },
+                            _ => ()
+                        }
+                    }
+                }
+                if let Some( node ) = else_node {
+                    self.statement(node)
+                }
+            },
+            Node::Case( _ , _ , _ , selector , _ , nodes , else_node , _ ) => {
+                let selector_type = self.expression(selector);
+                let base = self.base(selector_type);
+                match ( self.is_integer(selector_type) , &self.types[base] ) {
+                    ( true , _ ) | ( _ , Type::Char( _ ) ) | ( _ , Type::Enumeration( _ ) ) | ( _ , Type::Unknown ) => (),
+                    _ => {
+                        let ( s , e ) = expression_span(selector);
+                        self.error(E_TYPE_MISMATCH, format!("Case selector must be integer, character or enumeration, found '{}'", self.type_name(selector_type)), s, e)
+                    }
+                }
+
+                for node in nodes.iter() {
+                    if let Node::CaseElement( _ , _ , _ , labels , _ , _ , x ) = &**node {
+                        for label in labels.iter() {
+                            let bounds : Vec<&Node> = match &**label {
+                                Node::Range( _ , _ , from , _ , to , _ , _ ) => [ from , to ].iter().filter_map(|x| x.as_deref()).collect(),
+                                _ => vec![ &**label ]
+                            };
+
+                            for bound in bounds.iter() {
+                                let label_type = self.expression(bound);
+                                let ( s , e ) = expression_span(bound);
+                                match self.comparable(label_type, selector_type, false) {
+                                    true => (),
+                                    _ => self.error(E_TYPE_MISMATCH, format!("Case label of type '{}' does not match selector of type '{}'", self.type_name(label_type), self.type_name(selector_type)), s, e)
+                                }
+                                self.constant_value(bound);
+                            }
+                        }
+                        self.statement(x)
+                    }
+                }
+                if let Some( node ) = else_node {
+                    self.statement(node)
+                }
+            },
+            Node::For( _ , _ , _ , variable , _ , from , _ , to , by , _ , x , _ ) => {
+                let control = self.operand(variable);
+                let ( s , e ) = expression_span(variable);
+                match ( &control.mode , self.is_integer_like(control.type_id) || control.type_id == T_UNKNOWN ) {
+                    ( OperandMode::Variable , true ) => (),
+                    _ => self.error(E_TYPE_MISMATCH, String::from("Control variable of 'FOR' must be an integer variable"), s, e)
+                }
+
+                let mut bounds = vec![ &**from, &**to ];
+                if let Some( ( _ , step ) ) = by {
+                    bounds.push(&**step)
+                }
+                for bound in bounds.iter() {
+                    let bound_type = self.expression(bound);
+                    let ( s , e ) = expression_span(bound);
+                    self.check_assignment(bound_type, control.type_id, s, e)
+                }
+
+                self.statement(x)
+            },
+            Node::Return( s , e , _ , value ) => {
+                match ( value , self.result ) {
+                    ( None , T_NOTYPE ) => (),
+                    ( None , _ ) => self.error(E_RESULT, format!("Function must return a value of type '{}'", self.type_name(self.result)), *s, *e),
+                    ( Some( x ) , T_NOTYPE ) => {
+                        self.expression(x);
+                        let ( s , e ) = expression_span(x);
+                        self.error(E_RESULT, String::from("Procedure without result type cannot return a value"), s, e)
+                    },
+                    ( Some( x ) , result ) => {
+                        let type_id = self.expression(x);
+                        let ( s , e ) = expression_span(x);
+                        self.check_assignment(type_id, result, s, e)
+                    }
+                }
+            },
+            Node::Ignore( _ , _ , _ , x ) => {
+                self.operand(x);
+            },
+            Node::BecomesStatement( _ , _ , left , _ , right ) => {
+                let target = self.operand(left);
+                let ( s , e ) = expression_span(left);
+
+                match ( &target.mode , target.type_id ) {
+                    ( OperandMode::Variable , _ ) | ( _ , T_UNKNOWN ) => (),
+                    ( OperandMode::ReadOnly , _ ) => self.error(E_NOT_ASSIGNABLE, String::from("Cannot assign to read-only designator"), s, e),
+                    _ => self.error(E_NOT_ASSIGNABLE, String::from("Left side of assignment is not a variable"), s, e)
+                }
+
+                let source = self.expression(right);
+                let ( s , e ) = expression_span(right);
+                self.check_assignment(source, target.type_id, s, e)
+            },
+            Node::ExclaimMarkStatement( _ , _ , left , _ , right ) |
+            Node::QuestionmarkStatement( _ , _ , left , _ , right ) |
+            Node::LessLessStatement( _ , _ , left , _ , right ) |
+            Node::GreaterGreaterStatement( _ , _ , left , _ , right ) => {
+                self.operand(left);
+                self.operand(right);
+            },
+            Node::Code( _ , _ , symbol , _ , _ ) => {
+                if let Symbols::Code( _ , e ) = &**symbol {
+                    self.code_scopes.insert(( self.module, *e ), self.scope);
+                }
+            },
+            Node::Empty |
+            Node::Error( .. ) |
+            Node::Exit( .. ) => (),
+            _ => self.call_statement(statement)
+        }
+    }
+
+    /// Procedure call used as statement, procedure names without parameter list are called as well
+    fn call_statement(&mut self, statement: &Node) {
+        let operand = self.operand(statement);
+        let ( s , e ) = expression_span(statement);
+
+        match operand.mode {
+            OperandMode::Procedure => {
+                self.check_arguments(operand.type_id, &Vec::new(), s, e);
+            },
+            OperandMode::Builtin( name ) => {
+                self.builtin(name.as_str(), &Vec::new(), s, e);
+            },
+            _ => {
+                match operand.type_id {
+                    T_NOTYPE | T_UNKNOWN => (),
+                    _ => self.error(E_RESULT, String::from("Result of function call is not used, discard it with 'IGNORE'"), s, e)
+                }
+            }
+        }
+    }
+
+    fn condition(&mut self, condition: &Node) {
+        let type_id = self.expression(condition);
+
+        match self.base(type_id) {
+            T_BOOLEAN | T_UNKNOWN => (),
+            _ => {
+                let ( s , e ) = expression_span(condition);
+                self.error(E_TYPE_MISMATCH, format!("Condition must be BOOLEAN, found '{}'", self.type_name(type_id)), s, e)
+            }
+        }
+    }
+
+    /// Type of expression that must denote a value
+    fn expression(&mut self, node: &Node) -> TypeId {
+        let operand = self.operand(node);
+
+        match operand.mode {
+            OperandMode::Type | OperandMode::Module | OperandMode::Builtin( _ ) => {
+                let ( s , e ) = expression_span(node);
+                self.error(E_TYPE_MISMATCH, String::from("Expecting a value"), s, e);
+                T_UNKNOWN
+            },
+            _ => operand.type_id
+        }
+    }
+
+    fn value(type_id: TypeId) -> Operand {
+        Operand { mode: OperandMode::Value, type_id }
+    }
+
+    /// Operand denoted by symbol table entry
+    fn entry_operand(&self, entry: EntryId) -> Operand {
+        let table = self.resolver.get_symbol_table();
+        let symbol = table.entry(entry);
+        let type_id = self.guards.get(&entry).or(self.entry_types.get(&entry)).copied().unwrap_or(T_UNKNOWN);
+        let foreign = table.enclosing_module(symbol.scope) != self.module;
+
+        let mode = match symbol.kind {
+            SymbolKind::Constant => OperandMode::Constant,
+            SymbolKind::Type => OperandMode::Type,
+            SymbolKind::Module => OperandMode::Module,
+            SymbolKind::Procedure if symbol.scope == UNIVERSE_SCOPE => OperandMode::Builtin(symbol.name.clone()),
+            SymbolKind::Procedure => OperandMode::Procedure,
+            SymbolKind::Variable if foreign && symbol.export == Export::ReadOnly => OperandMode::ReadOnly,
+            SymbolKind::Variable => OperandMode::Variable,
+            SymbolKind::Parameter => {
+                match self.parameter_modes.get(&entry) {
+                    Some( ParameterMode::Const ) => OperandMode::ReadOnly,
+                    _ => OperandMode::Variable
+                }
+            }
+        };
+
+        Operand { mode, type_id }
+    }
+
+    /// Operand of identifier use, bound by resolver
+    fn identifier_operand(&mut self, node: &Node) -> Operand {
+        match node {
+            Node::Ident( s , _ , _ ) => {
+                match self.resolver.get_binding(self.module, *s) {
+                    Some( x ) => self.entry_operand(x),
+                    _ => Operand { mode: OperandMode::Variable, type_id: T_UNKNOWN }   /* Undeclared, already reported */
+                }
+            },
+            _ => Operand { mode: OperandMode::Variable, type_id: T_UNKNOWN }
+        }
+    }
+
+    /// Check expression or designator and record its type
+    fn operand(&mut self, node: &Node) -> Operand {
+        let operand = match node {
+            Node::Ident( .. ) => self.identifier_operand(node),
+            Node::QualifiedIdentifier( _ , _ , left , _ , right ) => {
+                let module = self.identifier_operand(left);
+                match module.mode {
+                    OperandMode::Module => {
+                        match &**right {
+                            Node::Ident( s , _ , _ ) => {
+                                match self.resolver.get_binding(self.module, *s) {
+                                    Some( x ) => self.entry_operand(x),
+                                    _ => Operand { mode: OperandMode::Variable, type_id: T_UNKNOWN }
+                                }
+                            },
+                            _ => Operand { mode: OperandMode::Variable, type_id: T_UNKNOWN }
+                        }
+                    },
+                    _ => Operand { mode: OperandMode::Variable, type_id: T_UNKNOWN }
+                }
+            },
+            Node::Integer( _ , _ , symbol ) => {
+                let value = match &**symbol {
+                    Symbols::Integer( _ , _ , text ) => integer_value(text.as_str()),
+                    _ => None
+                };
+                let type_id = integer_type(value.unwrap_or(i64::MAX as i128));
+                Operand { mode: OperandMode::Constant, type_id }
+            },
+            Node::Real( _ , _ , symbol ) => {
+                let type_id = match &**symbol {
+                    Symbols::Real( _ , _ , text ) if text.contains('D') || text.contains('d') => T_LONGREAL,
+                    _ => T_REAL
+                };
+                Operand { mode: OperandMode::Constant, type_id }
+            },
+            Node::Character( .. ) => Operand { mode: OperandMode::Constant, type_id: T_CHAR8 },
+            Node::String( _ , _ , symbol ) => {
+                let length = match &**symbol {
+                    Symbols::String( _ , _ , text ) => text.chars().count().saturating_sub(2) as u32,
+                    _ => 0
+                };
+                let type_id = self.new_type(Type::String(length));
+                Operand { mode: OperandMode::Constant, type_id }
+            },
+            Node::Nil( .. ) => Operand { mode: OperandMode::Constant, type_id: T_NIL },
+            Node::Imag( .. ) => Operand { mode: OperandMode::Constant, type_id: T_COMPLEX },
+            Node::True( .. ) |
+            Node::False( .. ) => Operand { mode: OperandMode::Constant, type_id: T_BOOLEAN },
+            Node::Self_( .. ) => TypeChecker::value(self.self_type),
+            Node::Result( .. ) => Operand { mode: OperandMode::Variable, type_id: self.result },
+            Node::Address( _ , _ , _ , of ) => {
+                match of {
+                    Some( x ) => {
+                        self.operand(&x.1);
+                        TypeChecker::value(T_ADDRESS)
+                    },
+                    _ => Operand { mode: OperandMode::Type, type_id: T_ADDRESS }
+                }
+            },
+            Node::Size( _ , _ , _ , of ) => {
+                match of {
+                    Some( x ) => {
+                        self.operand(&x.1);
+                        TypeChecker::value(T_SIZE)
+                    },
+                    _ => Operand { mode: OperandMode::Type, type_id: T_SIZE }
+                }
+            },
+            Node::Alias( _ , _ , _ , _ , x ) => {
+                let operand = self.operand(x);
+                Operand { mode: OperandMode::Variable, type_id: operand.type_id }
+            },
+            Node::New( _ , _ , _ , type_node , _ , arguments , _ ) => {
+                let type_id = self.type_node(type_node);
+                for argument in expression_list(&Some( arguments.clone() )).iter() {
+                    self.operand(argument);
+                }
+                TypeChecker::value(type_id)
+            },
+            Node::ParenthesisExpression( _ , _ , _ , x , _ ) => {
+                let type_id = self.expression(x);
+                TypeChecker::value(type_id)
+            },
+            Node::UnaryExpression( _ , _ , primary , designators , _ ) => {
+                let mut operand = self.operand(primary);
+                if let Some( nodes ) = designators {
+                    for designator in nodes.iter() {
+                        operand = self.designator(operand, designator)
+                    }
+                }
+                operand
+            },
+            Node::UnaryPlus( s , e , _ , x ) |
+            Node::UnaryMinus( s , e , _ , x ) => {
+                let type_id = self.expression(x);
+                let base = self.base(type_id);
+                match ( self.is_numeric(type_id) , base ) {
+                    ( true , _ ) | ( _ , T_SET ) | ( _ , T_UNKNOWN ) => (),
+                    _ => self.error(E_TYPE_MISMATCH, format!("Sign needs a numeric operand, found '{}'", self.type_name(type_id)), *s, *e)
+                }
+                TypeChecker::value(type_id)
+            },
+            Node::UnaryNot( s , e , _ , x ) => {
+                let type_id = self.expression(x);
+                match self.base(type_id) {
+                    T_BOOLEAN | T_UNKNOWN => (),
+                    _ => self.error(E_TYPE_MISMATCH, format!("'~' needs a BOOLEAN operand, found '{}'", self.type_name(type_id)), *s, *e)
+                }
+                TypeChecker::value(T_BOOLEAN)
+            },
+            Node::Times( s , e , l , _ , r ) => self.arithmetic("*", l, r, *s, *e),
+            Node::Slash( s , e , l , _ , r ) => self.arithmetic("/", l, r, *s, *e),
+            Node::Div( s , e , l , _ , r ) => self.arithmetic("DIV", l, r, *s, *e),
+            Node::Mod( s , e , l , _ , r ) => self.arithmetic("MOD", l, r, *s, *e),
+            Node::Plus( s , e , l , _ , r ) => self.arithmetic("+", l, r, *s, *e),
+            Node::Minus( s , e , l , _ , r ) => self.arithmetic("-", l, r, *s, *e),
+            Node::And( s , e , l , _ , r ) => self.arithmetic("&", l, r, *s, *e),
+            Node::Or( s , e , l , _ , r ) => self.arithmetic("OR", l, r, *s, *e),
+            Node::DotTimes( s , e , l , _ , r ) => self.arithmetic(".*", l, r, *s, *e),
+            Node::DotSlash( s , e , l , _ , r ) => self.arithmetic("./", l, r, *s, *e),
+            Node::Backslash( s , e , l , _ , r ) => self.arithmetic("\\", l, r, *s, *e),
+            Node::TimesTimes( s , e , l , _ , r ) => self.arithmetic("**", l, r, *s, *e),
+            Node::PlusTimes( s , e , l , _ , r ) => self.arithmetic("+*", l, r, *s, *e),
+            Node::Equal( s , e , l , _ , r ) |
+            Node::NotEqual( s , e , l , _ , r ) => self.relation(false, l, r, *s, *e),
+            Node::Less( s , e , l , _ , r ) |
+            Node::LessEqual( s , e , l , _ , r ) |
+            Node::GreaterEqual( s , e , l , _ , r ) |
+            Node::Greater( s , e , l , _ , r ) => self.relation(true, l, r, *s, *e),
+            Node::DotEqual( _ , _ , l , _ , r ) |
+            Node::DotUnequal( _ , _ , l , _ , r ) |
+            Node::DotLess( _ , _ , l , _ , r ) |
+            Node::DotLessEqual( _ , _ , l , _ , r ) |
+            Node::DotGreater( _ , _ , l , _ , r ) |
+            Node::DotGreaterEqual( _ , _ , l , _ , r ) |
+            Node::QuestionMarks( _ , _ , l , _ , r ) |
+            Node::ExplainMarks( _ , _ , l , _ , r ) |
+            Node::LessLessQ( _ , _ , l , _ , r ) |
+            Node::GreaterGreaterQ( _ , _ , l , _ , r ) => {
+                self.operand(l);
+                self.operand(r);
+                TypeChecker::value(T_BOOLEAN)
+            },
+            Node::In( s , e , l , _ , r ) => {
+                let element = self.expression(l);
+                let set = self.expression(r);
+                match ( self.is_integer(element) || element == T_UNKNOWN , self.base(set) ) {
+                    ( true , T_SET ) | ( true , T_UNKNOWN ) => (),
+                    _ => self.error(E_TYPE_MISMATCH, format!("'IN' needs an integer and a SET, found '{}' and '{}'", self.type_name(element), self.type_name(set)), *s, *e)
+                }
+                TypeChecker::value(T_BOOLEAN)
+            },
+            Node::Is( s , e , l , _ , r ) => {
+                let tested = self.expression(l);
+                let guard = self.type_node(r);
+                match ( self.extensible(tested) , self.extensible(guard) ) {
+                    ( true , true ) => (),
+                    _ => self.error(E_TYPE_MISMATCH, format!("'IS' needs a record, object or pointer, found '{}' and '{}'", self.type_name(tested), self.type_name(guard)), *s, *e)
+                }
+                TypeChecker::value(T_BOOLEAN)
+            },
+            Node::Range( s , e , from , _ , to , _ , by ) => {
+                for x in [ from , to , by ].iter().copied().flatten() {
+                    let type_id = self.expression(x);
+                    match self.is_integer(type_id) || type_id == T_UNKNOWN {
+                        true => (),
+                        _ => self.error(E_TYPE_MISMATCH, format!("Range bounds must be integers, found '{}'", self.type_name(type_id)), *s, *e)
+                    }
+                }
+                TypeChecker::value(T_RANGE)
+            },
+            Node::Set( _ , _ , _ , elements , _ , _ ) => {
+                for element in elements.iter() {
+                    let type_id = self.expression(element);
+                    match self.is_integer(type_id) || self.base(type_id) == T_RANGE || type_id == T_UNKNOWN {
+                        true => (),
+                        _ => {
+                            let ( s , e ) = expression_span(element);
+                            self.error(E_TYPE_MISMATCH, format!("Set element must be an integer, found '{}'", self.type_name(type_id)), s, e)
+                        }
+                    }
+                }
+                TypeChecker::value(T_SET)
+            },
+            Node::Array( _ , _ , _ , elements , _ , _ ) => {
+                let mut element_type = T_UNKNOWN;
+                for element in elements.iter() {
+                    let type_id = self.expression(element);
+                    element_type = match ( element_type , self.is_numeric(type_id) && self.is_numeric(element_type) ) {
+                        ( T_UNKNOWN , _ ) => type_id,
+                        ( _ , true ) => self.numeric_result(element_type, type_id),
+                        _ => element_type
+                    }
+                }
+                let type_id = self.new_type(Type::MathArray(Some( elements.len() as i64 ), element_type));
+                TypeChecker::value(type_id)
+            },
+            _ => TypeChecker::value(T_UNKNOWN)
+        };
+
+        /* Expressions of constants are constants, integer constants get the smallest type holding their value */
+        let operand = match ( &operand.mode , node ) {
+            ( OperandMode::Value , Node::Ident( .. ) ) |
+            ( OperandMode::Value , Node::Self_( .. ) ) |
+            ( OperandMode::Value , Node::New( .. ) ) => operand,
+            ( OperandMode::Value , _ ) if operand.type_id != T_UNKNOWN => {
+                match self.constant(node) {
+                    Ok( ConstValue::Integer( x ) ) if self.is_integer(operand.type_id) => Operand { mode: OperandMode::Constant, type_id: integer_type(x as i128) },
+                    Ok( _ ) => Operand { mode: OperandMode::Constant, type_id: operand.type_id },
+                    Err( ConstError::Invalid( x ) ) => {
+                        self.errors.push(x);
+                        TypeChecker::value(T_UNKNOWN)
+                    },
+                    _ => operand
+                }
+            },
+            _ => operand
+        };
+
+        let ( s , e ) = expression_span(node);
+        self.expression_types.insert( ( self.module, s, e ), operand.type_id );
+
+        operand
+    }
+
+    /// Apply selector, index, dereference or call to designator
+    fn designator(&mut self, operand: Operand, designator: &Node) -> Operand {
+        match designator {
+            Node::DotName( s , e , _ , id ) => {
+                let ( name , name_start , name_end ) = match &**id {
+                    Node::Ident( _ , _ , symbol ) => {
+                        match &**symbol {
+                            Symbols::Ident( s , e , x ) => ( x.to_string(), *s, *e ),
+                            _ => return Operand { mode: OperandMode::Variable, type_id: T_UNKNOWN }
+                        }
+                    },
+                    _ => return Operand { mode: OperandMode::Variable, type_id: T_UNKNOWN }
+                };
+
+                match operand.mode {
+                    OperandMode::Module | OperandMode::Type => {
+                        /* Members of modules and enumerations are bound by the resolver */
+                        return match self.resolver.get_binding(self.module, name_start) {
+                            Some( x ) => self.entry_operand(x),
+                            _ => Operand { mode: OperandMode::Variable, type_id: T_UNKNOWN }
+                        }
+                    },
+                    _ => ()
+                }
+
+                if operand.type_id == T_UNKNOWN {
+                    return Operand { mode: OperandMode::Variable, type_id: T_UNKNOWN }
+                }
+
+                /* Objects are references, their fields are variables like fields reached through pointers */
+                let record = self.dereference(operand.type_id);
+                let dereferenced = record != self.base(operand.type_id) || matches!(self.types[record], Type::Object( .. ));
+
+                match self.lookup_field(record, name.as_str()) {
+                    Some( x ) => {
+                        let ( export , scope ) = {
+                            let entry = self.resolver.get_symbol_table().entry(x);
+                            ( entry.export.clone(), entry.scope )
+                        };
+                        let foreign = self.resolver.get_symbol_table().enclosing_module(scope) != self.module;
+
+                        if foreign && export == Export::None {
+                            self.error(E_NOT_EXPORTED, format!("Field '{}' is not exported", name), name_start, name_end);
+                        }
+
+                        self.resolver.bind(self.module, name_start, x);
+                        let field = self.entry_operand(x);
+
+                        match field.mode {
+                            OperandMode::Variable | OperandMode::ReadOnly => {
+                                let mode = match ( dereferenced , &operand.mode , foreign && export == Export::ReadOnly ) {
+                                    ( _ , _ , true ) => OperandMode::ReadOnly,
+                                    ( true , _ , _ ) => OperandMode::Variable,
+                                    ( _ , mode , _ ) => mode.clone()
+                                };
+                                Operand { mode, type_id: field.type_id }
+                            },
+                            _ => field
+                        }
+                    },
+                    _ => {
+                        match self.types[record] {
+                            Type::Record( .. ) | Type::Object( .. ) | Type::Definition( _ ) => self.error(E_NO_FIELD, format!("'{}' has no field or method '{}'", self.type_name(operand.type_id), name), name_start, name_end),
+                            _ => self.error(E_NO_FIELD, format!("Selector '.{}' needs a record or object, found '{}'", name, self.type_name(operand.type_id)), *s, *e)
+                        }
+                        Operand { mode: OperandMode::Variable, type_id: T_UNKNOWN }
+                    }
+                }
+            },
+            Node::Index( s , e , _ , indexes , _ ) => {
+                let mut type_id = operand.type_id;
+                let mut mode = operand.mode.clone();
+
+                for index in expression_list(indexes).iter() {
+                    let index_type = self.expression(index);
+                    let array = self.dereference(type_id);
+
+                    if array != self.base(type_id) {
+                        mode = OperandMode::Variable
+                    }
+
+                    match ( self.is_integer_like(index_type) || index_type == T_UNKNOWN , self.types[array].clone() ) {
+                        ( _ , Type::Unknown ) => type_id = T_UNKNOWN,
+                        ( true , Type::Array( _ , element ) ) |
+                        ( true , Type::MathArray( _ , element ) ) => type_id = element,
+                        ( false , Type::MathArray( .. ) ) if self.base(index_type) == T_RANGE => (),
+                        ( true , Type::String( _ ) ) => type_id = T_CHAR8,
+                        ( false , _ ) => {
+                            let ( s , e ) = expression_span(index);
+                            self.error(E_TYPE_MISMATCH, format!("Index must be an integer, found '{}'", self.type_name(index_type)), s, e);
+                            type_id = T_UNKNOWN
+                        },
+                        _ => {
+                            self.error(E_TYPE_MISMATCH, format!("Index needs an array, found '{}'", self.type_name(type_id)), *s, *e);
+                            type_id = T_UNKNOWN
+                        }
+                    }
+                }
+
+                Operand { mode, type_id }
+            },
+            Node::Arrow( s , e , _ ) => {
+                match ( &operand.mode , self.types[self.base(operand.type_id)].clone() ) {
+                    ( OperandMode::Procedure , _ ) => operand,  /* Call of overridden method */
+                    ( _ , Type::Pointer( x ) ) => Operand { mode: OperandMode::Variable, type_id: x },
+                    ( _ , Type::Unknown ) => operand,
+                    _ => {
+                        self.error(E_TYPE_MISMATCH, format!("'^' needs a pointer, found '{}'", self.type_name(operand.type_id)), *s, *e);
+                        Operand { mode: OperandMode::Variable, type_id: T_UNKNOWN }
+                    }
+                }
+            },
+            Node::Call( s , e , _ , arguments , _ ) => {
+                let arguments = expression_list(arguments);
+
+                match operand.mode.clone() {
+                    OperandMode::Builtin( name ) => self.builtin(name.as_str(), &arguments, *s, *e),
+                    OperandMode::Type => {
+                        /* Type conversion */
+                        match arguments.len() {
+                            1 => {
+                                self.expression(arguments[0]);
+                            },
+                            _ => self.error(E_ARGUMENT, String::from("Type conversion needs one argument"), *s, *e)
+                        }
+                        TypeChecker::value(operand.type_id)
+                    },
+                    _ => {
+                        let base = self.base(operand.type_id);
+                        match ( self.types[base].clone() , arguments.len() ) {
+                            ( Type::Unknown , _ ) => {
+                                for argument in arguments.iter() {
+                                    self.operand(argument);
+                                }
+                                Operand { mode: OperandMode::Variable, type_id: T_UNKNOWN }
+                            },
+                            ( Type::Procedure( _ , result ) , _ ) => {
+                                self.check_arguments(operand.type_id, &arguments, *s, *e);
+                                TypeChecker::value(result)
+                            },
+                            ( _ , 1 ) if self.extensible(operand.type_id) => {
+                                /* Type guard */
+                                let guard = self.operand(arguments[0]);
+                                match ( &guard.mode , self.extends(guard.type_id, operand.type_id) ) {
+                                    ( OperandMode::Type , true ) => Operand { mode: operand.mode, type_id: guard.type_id },
+                                    ( OperandMode::Type , false ) => {
+                                        self.error(E_TYPE_MISMATCH, format!("Type guard '{}' is not an extension of '{}'", self.type_name(guard.type_id), self.type_name(operand.type_id)), *s, *e);
+                                        Operand { mode: operand.mode, type_id: guard.type_id }
+                                    },
+                                    _ => {
+                                        self.error(E_NOT_CALLABLE, format!("'{}' is not a procedure", self.type_name(operand.type_id)), *s, *e);
+                                        Operand { mode: OperandMode::Variable, type_id: T_UNKNOWN }
+                                    }
+                                }
+                            },
+                            _ => {
+                                for argument in arguments.iter() {
+                                    self.operand(argument);
+                                }
+                                self.error(E_NOT_CALLABLE, format!("'{}' is not a procedure", self.type_name(operand.type_id)), *s, *e);
+                                Operand { mode: OperandMode::Variable, type_id: T_UNKNOWN }
+                            }
+                        }
+                    }
+                }
+            },
+            _ => operand     /* Transpose keeps type of math array */
+        }
+    }
+
+    /// Check arguments against formal parameters of procedure type
+    fn check_arguments(&mut self, procedure: TypeId, arguments: &[&Node], start: u32, end: u32) {
+        let parameters = match &self.types[self.base(procedure)] {
+            Type::Procedure( x , _ ) => x.to_vec(),
+            _ => return
+        };
+
+        if parameters.len() != arguments.len() {
+            self.error(E_ARGUMENT, format!("Expecting {} argument(s), found {}", parameters.len(), arguments.len()), start, end);
+        }
+
+        for ( index , node ) in arguments.iter().enumerate() {
+            let argument = self.operand(node);
+            let ( s , e ) = expression_span(node);
+
+            match parameters.get(index) {
+                Some( ( ParameterMode::Var , formal ) ) => {
+                    /* Each argument gets one diagnostic, type is not checked for arguments that are not variables */
+                    match ( &argument.mode , argument.type_id ) {
+                        ( OperandMode::Variable , _ ) | ( _ , T_UNKNOWN ) => (),
+                        _ => {
+                            self.error(E_ARGUMENT, String::from("Argument for VAR parameter must be a variable"), s, e);
+                            continue
+                        }
+                    }
+                    match self.same_type(argument.type_id, *formal) || self.open_array_compatible(argument.type_id, *formal) || self.record_extends(argument.type_id, *formal) {
+                        true => (),
+                        _ => self.error(E_ARGUMENT, format!("Argument of type '{}' does not match VAR parameter of type '{}'", self.type_name(argument.type_id), self.type_name(*formal)), s, e)
+                    }
+                },
+                Some( ( _ , formal ) ) => {
+                    match argument.mode {
+                        OperandMode::Type | OperandMode::Module | OperandMode::Builtin( _ ) => self.error(E_ARGUMENT, String::from("Expecting a value as argument"), s, e),
+                        _ => {
+                            match self.assignable(argument.type_id, *formal) || self.open_array_compatible(argument.type_id, *formal) {
+                                true => (),
+                                _ => self.error(E_ARGUMENT, format!("Argument of type '{}' does not match parameter of type '{}'", self.type_name(argument.type_id), self.type_name(*formal)), s, e)
+                            }
+                        }
+                    }
+                },
+                _ => ()
+            }
+        }
+    }
+
+    /// Predeclared procedures and functions
+    fn builtin(&mut self, name: &str, arguments: &Vec<&Node>, start: u32, end: u32) -> Operand {
+        let operands : Vec<Operand> = arguments.iter().map(|x| self.operand(x)).collect();
+        let types : Vec<TypeId> = operands.iter().map(|x| x.type_id).collect();
+
+        let ( minimum , maximum ) = match name {
+            "ASH" | "LSH" | "ROL" | "ROR" | "ROT" | "COPY" | "EXCL" | "INCL" => ( 2, 2 ),
+            "ASSERT" | "DEC" | "INC" | "LEN" | "DIM" => ( 1, 2 ),
+            "MAX" | "MIN" => ( 1, 2 ),
+            "CAS" => ( 3, 3 ),
+            "HALT" | "ABS" | "CAP" | "CHR" | "DISPOSE" | "ENTIER" | "ENTIERH" | "LONG" | "ODD" | "ORD" | "SHORT" | "SUM" |
+            "FIRST" | "LAST" | "STEP" | "RE" | "IM" | "INCR" | "SIZEOF" => ( 1, 1 ),
+            _ => ( 0, usize::MAX )
+        };
+
+        if operands.len() < minimum || operands.len() > maximum {
+            self.error(E_ARGUMENT, format!("Wrong number of arguments for '{}'", name), start, end);
+            return TypeChecker::value(T_UNKNOWN)
+        }
+
+        /* Argument kinds for each predeclared procedure, first argument decides result type for most of them */
+        let valid = match name {
+            "ABS" => self.is_numeric(types[0]),
+            "ASH" | "LSH" | "ROL" | "ROR" | "ROT" => self.is_integer_like(types[0]) && self.is_integer(types[1]),
+            "ASSERT" => self.base(types[0]) == T_BOOLEAN && ( types.len() == 1 || self.is_integer(types[1]) ),
+            "CAP" | "ORD" => matches!(self.types[self.base(types[0])], Type::Char( _ ) | Type::String( 1 )),
+            "CHR" | "HALT" | "ODD" => self.is_integer(types[0]),
+            "DEC" | "INC" => operands[0].mode == OperandMode::Variable && self.is_integer_like(types[0]) && ( types.len() == 1 || self.is_integer(types[1]) ),
+            "EXCL" | "INCL" => operands[0].mode == OperandMode::Variable && self.base(types[0]) == T_SET && self.is_integer(types[1]),
+            "ENTIER" | "ENTIERH" => self.is_numeric(types[0]),
+            "LEN" | "DIM" => matches!(self.types[self.dereference(types[0])], Type::Array( .. ) | Type::MathArray( .. ) | Type::String( _ )),
+            "FIRST" | "LAST" | "STEP" => self.base(types[0]) == T_RANGE,
+            "SIZEOF" => operands[0].mode == OperandMode::Type,
+            "RE" | "IM" => self.is_numeric(types[0]),
+            _ => true
+        };
+
+        if !valid && !types.contains(&T_UNKNOWN) {
+            let found = types.iter().map(|x| format!("'{}'", self.type_name(*x))).collect::<Vec<String>>().join(", ");
+            self.error(E_ARGUMENT, format!("Invalid argument(s) {} for '{}'", found, name), start, end);
+            return TypeChecker::value(T_UNKNOWN)
+        }
+
+        let result = match name {
+            "ABS" | "ASH" | "LSH" | "ROL" | "ROR" | "ROT" => types[0],
+            "CAP" | "CHR" => T_CHAR8,
+            "ORD" => T_SIGNED16,
+            "ODD" => T_BOOLEAN,
+            "ENTIER" | "FIRST" | "LAST" | "STEP" => T_SIGNED32,
+            "ENTIERH" => T_SIGNED64,
+            "DIM" | "LEN" | "SIZEOF" => T_SIZE,
+            "CAS" => types[0],
+            "RE" | "IM" => {
+                match self.types[self.base(types[0])] {
+                    Type::Complex( 64 ) | Type::Real( 64 ) => T_LONGREAL,
+                    _ => T_REAL
+                }
+            },
+            "SUM" => {
+                match self.types[self.dereference(types[0])] {
+                    Type::MathArray( _ , x ) | Type::Array( _ , x ) => x,
+                    _ => T_UNKNOWN
+                }
+            },
+            "LONG" | "SHORT" => {
+                let step : i32 = match name { "LONG" => 1, _ => -1 };
+                let ladder = [ [ T_SIGNED8, T_SIGNED16, T_SIGNED32, T_SIGNED64 ], [ T_CHAR8, T_CHAR16, T_CHAR32, T_CHAR32 ], [ T_REAL, T_LONGREAL, T_LONGREAL, T_LONGREAL ], [ T_COMPLEX, T_LONGCOMPLEX, T_LONGCOMPLEX, T_LONGCOMPLEX ] ];
+                let base = self.base(types[0]);
+                let mut found = T_UNKNOWN;
+                for row in ladder.iter() {
+                    if let Some( x ) = row.iter().position(|x| *x == base) {
+                        let index = ( x as i32 + step ).clamp(0, 3) as usize;
+                        found = match row[index] == base && row != &ladder[0] && step < 0 {
+                            true => row[0],
+                            _ => row[index]
+                        };
+                        break
+                    }
+                }
+                found
+            },
+            "MAX" | "MIN" => {
+                match ( &operands[0].mode , types.len() ) {
+                    ( OperandMode::Type , 1 ) if types[0] == T_SET => T_SIGNED16,
+                    ( OperandMode::Type , 1 ) => types[0],
+                    ( _ , 2 ) if self.is_numeric(types[0]) && self.is_numeric(types[1]) => self.numeric_result(types[0], types[1]),
+                    _ => {
+                        match types.contains(&T_UNKNOWN) {
+                            true => (),
+                            _ => self.error(E_ARGUMENT, format!("Invalid argument(s) for '{}'", name), start, end)
+                        }
+                        T_UNKNOWN
+                    }
+                }
+            },
+            _ => T_NOTYPE
+        };
+
+        TypeChecker::value(result)
+    }
+
+    fn arithmetic(&mut self, operator: &str, left: &Node, right: &Node, start: u32, end: u32) -> Operand {
+        let l = self.expression(left);
+        let r = self.expression(right);
+        let ( lb , rb ) = ( self.base(l) , self.base(r) );
+
+        if lb == T_UNKNOWN || rb == T_UNKNOWN {
+            return TypeChecker::value(T_UNKNOWN)
+        }
+
+        let ( l_element , r_element ) = ( self.math_element(lb) , self.math_element(rb) );
+        let integer_like = self.is_integer_like(l) && self.is_integer_like(r);
+
+        /* Math arrays combine with math arrays of the same kind of elements, or with numeric scalars included in their element type */
+        let math = match ( l_element , r_element ) {
+            ( Some( a ) , Some( b ) ) => self.is_numeric(a) && self.numeric_kind(a) == self.numeric_kind(b),
+            ( Some( a ) , None ) => self.is_numeric(a) && self.is_numeric(rb) && self.included(rb, a),
+            ( None , Some( b ) ) => self.is_numeric(b) && self.is_numeric(lb) && self.included(lb, b),
+            _ => false
+        };
+
+        let result = match operator {
+            "&" | "OR" if lb == T_BOOLEAN && rb == T_BOOLEAN => Some( T_BOOLEAN ),
+            "+" | "-" | "*" | "/" if lb == T_SET && rb == T_SET => Some( T_SET ),
+            "+*" if math && l_element.is_some() && r_element.is_some() => l_element.zip(r_element).map(|( a , b )| self.numeric_result(a, b)),
+            "*" if math && l_element.is_some() && r_element.is_some() => {
+                /* Product of matrices and vectors contracts last dimension of left with first dimension of right */
+                let element = l_element.zip(r_element).map(|( a , b )| self.numeric_result(a, b));
+                match ( self.math_rank(lb) + self.math_rank(rb) ).checked_sub(2) {
+                    Some( rank ) => element.map(|x| self.math_array_of(rank, x)),
+                    _ => None
+                }
+            },
+            "+" | "-" | "*" | "/" | ".*" | "./" | "\\" | "**" if math && l_element.is_some() => Some( l ),
+            "+" | "-" | "*" | "/" | ".*" | "./" | "\\" | "**" if math => Some( r ),
+            _ if l_element.is_some() || r_element.is_some() => None,
+            "+" | "-" | "*" | "DIV" | "MOD" if integer_like && ( lb == T_ADDRESS || rb == T_ADDRESS ) => {
+                match ( operator , lb , rb ) {
+                    ( "-" , T_ADDRESS , T_ADDRESS ) => Some( T_SIZE ),
+                    _ => Some( T_ADDRESS )
+                }
+            },
+            "+" | "-" | "*" | "DIV" | "MOD" if integer_like && ( lb == T_SIZE || rb == T_SIZE ) => Some( T_SIZE ),
+            "DIV" | "MOD" if self.is_integer(l) && self.is_integer(r) => Some( self.numeric_result(l, r) ),
+            "+" | "-" | "*" | ".*" | "./" | "\\" | "**" | "+*" if self.is_numeric(l) && self.is_numeric(r) => Some( self.numeric_result(l, r) ),
+            "/" if self.is_numeric(l) && self.is_numeric(r) => {
+                let result = self.numeric_result(l, r);
+                Some( self.numeric_result(result, T_REAL) )
+            },
+            _ => None
+        };
+
+        match result {
+            Some( x ) => TypeChecker::value(x),
+            _ => {
+                self.error(E_TYPE_MISMATCH, format!("Incompatible operand types '{}' and '{}' for '{}'", self.type_name(l), self.type_name(r), operator), start, end);
+                TypeChecker::value(T_UNKNOWN)
+            }
+        }
+    }
+
+    fn relation(&mut self, ordered: bool, left: &Node, right: &Node, start: u32, end: u32) -> Operand {
+        let l = self.expression(left);
+        let r = self.expression(right);
+
+        match self.comparable(l, r, ordered) {
+            true => (),
+            _ => self.error(E_TYPE_MISMATCH, format!("Cannot compare '{}' with '{}'", self.type_name(l), self.type_name(r)), start, end)
+        }
+
+        TypeChecker::value(T_BOOLEAN)
+    }
+
+    /// Comparison rules, ordered relations are only defined for numbers, characters, strings, sets and enumerations
+    fn comparable(&self, left: TypeId, right: TypeId, ordered: bool) -> bool {
+        let ( l , r ) = ( self.base(left) , self.base(right) );
+
+        if l == T_UNKNOWN || r == T_UNKNOWN {
+            return true
+        }
+
+        if self.is_numeric(l) && self.is_numeric(r) {
+            return !ordered || !( matches!(self.types[l], Type::Complex( _ )) || matches!(self.types[r], Type::Complex( _ )) )
+        }
+
+        if self.is_integer_like(l) && self.is_integer_like(r) {
+            return true
+        }
+
+        if self.is_character_sequence(l) && self.is_character_sequence(r) {
+            return true
+        }
+
+        match ( &self.types[l] , &self.types[r] ) {
+            ( Type::Char( _ ) , Type::String( 1 ) ) | ( Type::String( 1 ) , Type::Char( _ ) ) |
+            ( Type::Char( _ ) , Type::Char( _ ) ) |
+            ( Type::Set , Type::Set ) => true,
+            ( Type::Enumeration( a ) , Type::Enumeration( b ) ) => a == b,
+            ( Type::Boolean , Type::Boolean ) |
+            ( Type::Range , Type::Range ) => !ordered,
+            ( Type::Nil , Type::Pointer( _ ) ) | ( Type::Pointer( _ ) , Type::Nil ) |
+            ( Type::Nil , Type::Object( .. ) ) | ( Type::Object( .. ) , Type::Nil ) |
+            ( Type::Nil , Type::Definition( _ ) ) | ( Type::Definition( _ ) , Type::Nil ) |
+            ( Type::Nil , Type::Procedure( .. ) ) | ( Type::Procedure( .. ) , Type::Nil ) |
+            ( Type::Nil , Type::Address ) | ( Type::Address , Type::Nil ) |
+            ( Type::Nil , Type::Nil ) => !ordered,
+            ( Type::Pointer( _ ) , Type::Pointer( _ ) ) |
+            ( Type::Object( .. ) , Type::Object( .. ) ) => !ordered && ( self.extends(l, r) || self.extends(r, l) ),
+            ( Type::Procedure( .. ) , Type::Procedure( .. ) ) => !ordered && self.same_type(l, r),
+            _ => false
+        }
+    }
+
+    /// Assignment compatibility of value of type source to variable of type target
+    fn assignable(&self, source: TypeId, target: TypeId) -> bool {
+        let ( s , t ) = ( self.base(source) , self.base(target) );
+
+        if s == t || s == T_UNKNOWN || t == T_UNKNOWN || t == T_ANYTYPE {
+            return true
+        }
+
+        if self.is_numeric(s) && self.is_numeric(t) {
+            return self.included(s, t)
+        }
+
+        match ( &self.types[s] , &self.types[t] ) {
+            ( Type::Char( a ) , Type::Char( b ) ) => a <= b,
+            ( Type::String( x ) , Type::Char( _ ) ) => *x <= 1,
+            ( Type::String( _ ) , Type::Array( None , x ) ) => matches!(self.types[self.base(*x)], Type::Char( _ )),
+            ( Type::String( n ) , Type::Array( Some( m ) , x ) ) => ( *n as i64 ) < *m && matches!(self.types[self.base(*x)], Type::Char( _ )),
+            ( Type::Nil , Type::Pointer( _ ) ) |
+            ( Type::Nil , Type::Object( .. ) ) |
+            ( Type::Nil , Type::Procedure( .. ) ) |
+            ( Type::Nil , Type::Address ) => true,
+            ( Type::Signed( _ ) , Type::Address ) | ( Type::Unsigned( _ ) , Type::Address ) |
+            ( Type::Signed( _ ) , Type::Size ) | ( Type::Unsigned( _ ) , Type::Size ) |
+            ( Type::Size , Type::Address ) | ( Type::Address , Type::Size ) |
+            ( Type::Address , Type::Signed( 64 ) ) | ( Type::Size , Type::Signed( 64 ) ) => true,
+            ( Type::Pointer( _ ) , Type::Pointer( _ ) ) |
+            ( Type::Record( .. ) , Type::Record( .. ) ) |
+            ( Type::Object( .. ) , Type::Object( .. ) ) => self.extends(s, t),
+            ( Type::Pointer( _ ) , Type::Object( None , None ) ) => true,
+            ( Type::Nil , Type::Definition( _ ) ) => true,
+            ( Type::Object( .. ) , Type::Definition( _ ) ) => self.implements(s, t),
+            ( Type::Procedure( .. ) , Type::Procedure( .. ) ) => self.same_type(s, t),
+            ( Type::Array( Some( n ) , a ) , Type::Array( Some( m ) , b ) ) => n == m && self.same_type(*a, *b),
+            ( Type::MathArray( _ , a ) , Type::MathArray( _ , b ) ) => self.assignable(*a, *b),
+            _ => false
+        }
+    }
+
+    fn check_assignment(&mut self, source: TypeId, target: TypeId, start: u32, end: u32) {
+        match self.assignable(source, target) {
+            true => (),
+            _ => self.error(E_INCOMPATIBLE_ASSIGNMENT, format!("Cannot assign value of type '{}' to variable of type '{}'", self.type_name(source), self.type_name(target)), start, end)
+        }
+    }
+
+    /// Equal types, procedure types are compared by signature
+    fn same_type(&self, left: TypeId, right: TypeId) -> bool {
+        let ( l , r ) = ( self.base(left) , self.base(right) );
+
+        if l == r || l == T_UNKNOWN || r == T_UNKNOWN {
+            return true
+        }
+
+        match ( &self.types[l] , &self.types[r] ) {
+            ( Type::Procedure( a , x ) , Type::Procedure( b , y ) ) => {
+                a.len() == b.len() && self.same_type(*x, *y) && a.iter().zip(b.iter()).all(|( p , q )| p.0 == q.0 && self.same_type(p.1, q.1))
+            },
+            ( Type::Array( None , a ) , Type::Array( None , b ) ) |
+            ( Type::MathArray( None , a ) , Type::MathArray( None , b ) ) => self.same_type(*a, *b),
+            _ => false
+        }
+    }
+
+    /// Actual parameter for open array formal parameter, element types must be equal
+    fn open_array_compatible(&self, actual: TypeId, formal: TypeId) -> bool {
+        let ( a , f ) = ( self.base(actual) , self.base(formal) );
+
+        match ( &self.types[a] , &self.types[f] ) {
+            ( Type::Array( _ , x ) , Type::Array( None , y ) ) |
+            ( Type::MathArray( _ , x ) , Type::MathArray( None , y ) ) => self.same_type(*x, *y) || self.open_array_compatible(*x, *y),
+            ( Type::String( _ ) , Type::Array( None , y ) ) => matches!(self.types[self.base(*y)], Type::Char( _ )),
+            _ => false
+        }
+    }
+
+    /// Record type of VAR parameter accepts extensions of the record
+    fn record_extends(&self, actual: TypeId, formal: TypeId) -> bool {
+        match ( &self.types[self.base(actual)] , &self.types[self.base(formal)] ) {
+            ( Type::Record( .. ) , Type::Record( .. ) ) => self.extends(actual, formal),
+            _ => false
+        }
+    }
+
+    /// Type is same as or extension of base type, pointers are compared by their target type
+    fn extends(&self, extension: TypeId, base: TypeId) -> bool {
+        let target = self.dereference(base);
+        let mut current = self.dereference(extension);
+
+        if target == T_OBJECT || current == T_UNKNOWN || target == T_UNKNOWN {
+            return true
+        }
+
+        loop {
+            if current == target {
+                return true
+            }
+
+            current = match self.types[current] {
+                Type::Record( Some( x ) , _ ) | Type::Object( Some( x ) , _ ) => self.dereference(x),
+                _ => return false
+            }
+        }
+    }
+
+    /// Object type or one of its base types names definition in its IMPLEMENTS clause
+    fn implements(&self, object: TypeId, definition: TypeId) -> bool {
+        let target = self.base(definition);
+        let mut current = self.dereference(object);
+
+        loop {
+            match self.implementations.get(&current) {
+                Some( x ) if x.iter().any(|( d , _ , _ )| self.base(*d) == target) => return true,
+                _ => ()
+            }
+
+            current = match self.types[current] {
+                Type::Object( Some( x ) , _ ) => self.dereference(x),
+                _ => return false
+            }
+        }
+    }
+
+    fn extensible(&self, type_id: TypeId) -> bool {
+        matches!(self.types[self.dereference(type_id)], Type::Record( .. ) | Type::Object( .. ) | Type::Unknown)
+    }
+
+    /// Target of pointer type, other types are returned as they are
+    fn dereference(&self, type_id: TypeId) -> TypeId {
+        let base = self.base(type_id);
+
+        match self.types[base] {
+            Type::Pointer( x ) => self.base(x),
+            _ => base
+        }
+    }
+
+    /// Field or method in record or object type, or in one of its base types
+    fn lookup_field(&self, type_id: TypeId, name: &str) -> Option<EntryId> {
+        let mut current = self.dereference(type_id);
+
+        loop {
+            match self.types[current] {
+                Type::Record( base , Some( scope ) ) | Type::Object( base , Some( scope ) ) => {
+                    if let Some( x ) = self.resolver.get_symbol_table().lookup_local(scope, name) {
+                        return Some( x )
+                    }
+                    match base {
+                        Some( x ) => current = self.dereference(x),
+                        _ => return None
+                    }
+                },
+                Type::Definition( Some( scope ) ) => return self.resolver.get_symbol_table().lookup_local(scope, name),
+                _ => return None
+            }
+        }
+    }
+
+    fn is_integer(&self, type_id: TypeId) -> bool {
+        matches!(self.types[self.base(type_id)], Type::Signed( _ ) | Type::Unsigned( _ ))
+    }
+
+    fn is_integer_like(&self, type_id: TypeId) -> bool {
+        matches!(self.types[self.base(type_id)], Type::Signed( _ ) | Type::Unsigned( _ ) | Type::Address | Type::Size)
+    }
+
+    fn is_numeric(&self, type_id: TypeId) -> bool {
+        matches!(self.types[self.base(type_id)], Type::Signed( _ ) | Type::Unsigned( _ ) | Type::Real( _ ) | Type::Complex( _ ))
+    }
+
+    /// Element type of math array inside all its dimensions, none for other types
+    fn math_element(&self, type_id: TypeId) -> Option<TypeId> {
+        match self.types[self.base(type_id)] {
+            Type::MathArray( _ , x ) => Some( self.math_element(x).unwrap_or(self.base(x)) ),
+            _ => None
+        }
+    }
+
+    /// Number of dimensions of math array, zero for other types
+    fn math_rank(&self, type_id: TypeId) -> usize {
+        match self.types[self.base(type_id)] {
+            Type::MathArray( _ , x ) => self.math_rank(x) + 1,
+            _ => 0
+        }
+    }
+
+    /// Open math array with dimensions around element type, the element itself for no dimensions
+    fn math_array_of(&mut self, rank: usize, element: TypeId) -> TypeId {
+        let mut type_id = element;
+        for _ in 0 .. rank {
+            type_id = self.new_type(Type::MathArray(None, type_id))
+        }
+        type_id
+    }
+
+    /// Integers, reals and complex numbers are different kinds of numbers
+    fn numeric_kind(&self, type_id: TypeId) -> u8 {
+        match self.types[self.base(type_id)] {
+            Type::Real( _ ) => 1,
+            Type::Complex( _ ) => 2,
+            _ => 0
+        }
+    }
+
+    fn is_character_sequence(&self, type_id: TypeId) -> bool {
+        match &self.types[self.base(type_id)] {
+            Type::String( _ ) => true,
+            Type::Array( _ , x ) => matches!(self.types[self.base(*x)], Type::Char( _ )),
+            _ => false
+        }
+    }
+
+    /// Place of numeric type in the inclusion chain SHORTINT, INTEGER, LONGINT, HUGEINT, REAL, LONGREAL, COMPLEX, LONGCOMPLEX
+    fn numeric_rank(&self, type_id: TypeId) -> u32 {
+        match self.types[self.base(type_id)] {
+            Type::Signed( x ) | Type::Unsigned( x ) => x.trailing_zeros() - 2,
+            Type::Real( 32 ) => 5,
+            Type::Real( _ ) => 6,
+            Type::Complex( 32 ) => 7,
+            Type::Complex( _ ) => 8,
+            _ => 0
+        }
+    }
+
+    /// Numeric type source is included in target, LONGREAL is only included in LONGCOMPLEX
+    fn included(&self, source: TypeId, target: TypeId) -> bool {
+        match ( self.base(source) , self.base(target) ) {
+            ( T_LONGREAL , T_COMPLEX ) => false,
+            ( s , t ) => self.numeric_rank(s) <= self.numeric_rank(t)
+        }
+    }
+
+    /// Smallest numeric type including both operand types
+    fn numeric_result(&self, left: TypeId, right: TypeId) -> TypeId {
+        match ( self.base(left) , self.base(right) ) {
+            ( T_LONGREAL , T_COMPLEX ) | ( T_COMPLEX , T_LONGREAL ) => T_LONGCOMPLEX,
+            ( l , r ) if self.numeric_rank(l) >= self.numeric_rank(r) => l,
+            ( _ , r ) => r
+        }
+    }
+}
+
+// Unittests for type checker module
+
+#[cfg(test)]
+mod tests {
+    use crate::diagnostics::{Diagnostic, E_TYPE_MISMATCH, E_INCOMPATIBLE_ASSIGNMENT, E_NOT_ASSIGNABLE, E_ARGUMENT, E_NO_FIELD, E_RESULT, E_NOT_IMPLEMENTED, E_SIGNATURE_MISMATCH, E_NOT_CONSTANT, E_DIVISION_BY_ZERO, E_OUT_OF_RANGE};
+    use crate::parser::{Parser, ParserMethods, BlockRules};
+    use crate::scanner::{Scanner, ScannerMethods};
+    use crate::resolver::ResolverMethods;
+    use crate::symbol_table::SymbolTableMethods;
+    use crate::type_checker::{TypeChecker, TypeCheckerMethods, Type, integer_value, T_SIGNED16, T_SIGNED32, T_REAL, T_BOOLEAN};
+    use crate::const_evaluator::ConstValue;
+
+    fn check(sources: Vec<&'static str>) -> ( TypeChecker, Vec<Vec<Box<Diagnostic>>> ) {
+        let mut checker = TypeChecker::new();
+        let modules = sources.iter().map(|x| Parser::new(Box::new(Scanner::new(x))).parse_module().unwrap()).collect::<Vec<_>>();
+        let res = checker.check_modules(&modules.iter().map(|x| &**x).collect::<Vec<_>>());
+
+        ( checker, res )
+    }
+
+    fn errors(source: &'static str) -> Vec<&'static str> {
+        let ( _ , res ) = check(vec![ source ]);
+        res[0].iter().map(|x| x.code).collect()
+    }
+
+    #[test]
+    fn integer_literal_values() {
+        assert_eq!(integer_value("127"), Some( 127 ));
+        assert_eq!(integer_value("0FFH"), Some( 255 ));
+        assert_eq!(integer_value("0x7`Fa"), Some( 0x7fa ));
+        assert_eq!(integer_value("0b1`01"), Some( 5 ))
+    }
+
+    #[test]
+    fn numeric_inclusion() {
+        assert_eq!(errors("MODULE Test; VAR s: SHORTINT; i: INTEGER; l: LONGINT; h: HUGEINT; r: REAL; x: LONGREAL; BEGIN i := s; l := i; h := l; r := h; x := r; x := s * r END Test.").len(), 0);
+        assert_eq!(errors("MODULE Test; VAR i: INTEGER; l: LONGINT; BEGIN i := l END Test."), vec![ E_INCOMPATIBLE_ASSIGNMENT ]);
+        assert_eq!(errors("MODULE Test; VAR i: INTEGER; r: REAL; BEGIN i := r END Test."), vec![ E_INCOMPATIBLE_ASSIGNMENT ])
+    }
+
+    #[test]
+    fn expression_types() {
+        let ( checker , res ) = check(vec![ "MODULE Test; VAR i: INTEGER; l: LONGINT; b: BOOLEAN; BEGIN b := i + l > 1 / 2 END Test." ]);
+        let module = checker.get_resolver().get_module_scope("Test").unwrap();
+
+        assert_eq!(res[0].len(), 0);
+        assert_eq!(checker.get_expression_type(module, 64, 70), Some( T_SIGNED32 ));
+        assert_eq!(checker.get_expression_type(module, 72, 78), Some( T_REAL ));
+        assert_eq!(checker.get_expression_type(module, 64, 78), Some( T_BOOLEAN ))
+    }
+
+    #[test]
+    fn operators_and_conditions() {
+        assert_eq!(errors("MODULE Test; VAR s: SET; b: BOOLEAN; i: INTEGER; BEGIN s := s + {1, 3 .. 5}; b := (i IN s) & ~b; i := i DIV 2 MOD 3 END Test.").len(), 0);
+        assert_eq!(errors("MODULE Test; VAR b: BOOLEAN; i: INTEGER; BEGIN b := b + i; IF i THEN END END Test."), vec![ E_TYPE_MISMATCH, E_TYPE_MISMATCH ]);
+        assert_eq!(errors("MODULE Test; VAR r: REAL; BEGIN r := r DIV 2 END Test."), vec![ E_TYPE_MISMATCH ])
+    }
+
+    #[test]
+    fn characters_and_strings() {
+        assert_eq!(errors("MODULE Test; VAR c: CHAR; s: ARRAY 10 OF CHAR; b: BOOLEAN; BEGIN c := 'a'; s := \"hello\"; b := s = \"hi\"; b := c < \"z\" END Test.").len(), 0);
+        assert_eq!(errors("MODULE Test; VAR c: CHAR; BEGIN c := \"hello\" END Test."), vec![ E_INCOMPATIBLE_ASSIGNMENT ])
+    }
+
+    #[test]
+    fn records_and_pointers() {
+        let ( checker , res ) = check(vec![ "MODULE Test; TYPE List = POINTER TO Node; Node = RECORD next: List; value: INTEGER END; VAR l: List; BEGIN l.next.value := 1; l^.value := 2; l := NIL END Test." ]);
+        let module = checker.get_resolver().get_module_scope("Test").unwrap();
+
+        assert_eq!(res[0].len(), 0);
+        match checker.get_resolver().get_binding(module, 114) {
+            Some( x ) => assert_eq!(checker.get_resolver().get_symbol_table().entry(x).name, String::from("value")),
+            _ => assert!(false)
+        }
+        assert_eq!(errors("MODULE Test; TYPE R = RECORD a: INTEGER END; VAR r: R; BEGIN r.b := 1 END Test."), vec![ E_NO_FIELD ]);
+        assert_eq!(errors("MODULE Test; VAR i: INTEGER; BEGIN i.a := 1 END Test."), vec![ E_NO_FIELD ])
+    }
+
+    #[test]
+    fn record_extension() {
+        assert_eq!(errors("MODULE Test; TYPE A = POINTER TO RECORD x: INTEGER END; B = POINTER TO RECORD (A) y: INTEGER END; VAR a: A; b: B; f: BOOLEAN; BEGIN a := b; b.x := 1; f := a IS B; a(B).y := 2 END Test.").len(), 0);
+        assert_eq!(errors("MODULE Test; TYPE A = POINTER TO RECORD x: INTEGER END; B = POINTER TO RECORD (A) y: INTEGER END; VAR a: A; b: B; BEGIN b := a END Test."), vec![ E_INCOMPATIBLE_ASSIGNMENT ])
+    }
+
+    #[test]
+    fn with_narrows_to_guard_type() {
+        assert_eq!(errors("MODULE Test; TYPE P = POINTER TO RECORD x: INTEGER END; Q = POINTER TO RECORD (P) y: INTEGER END; VAR p: P; BEGIN WITH p : Q DO p.y := 1 ELSE p.x := 2 END; p.x := 3 END Test.").len(), 0);
+        assert_eq!(errors("MODULE Test; TYPE P = POINTER TO RECORD x: INTEGER END; Q = POINTER TO RECORD (P) y: INTEGER END; VAR p: P; BEGIN WITH p : Q DO END; p.y := 1 END Test."), vec![ E_NO_FIELD ]);
+        assert_eq!(errors("MODULE Test; TYPE P = POINTER TO RECORD x: INTEGER END; R = POINTER TO RECORD y: INTEGER END; VAR p: P; BEGIN WITH p : R DO END END Test."), vec![ E_TYPE_MISMATCH ])
+    }
+
+    #[test]
+    fn arrays_and_math_arrays() {
+        assert_eq!(errors("MODULE Test; VAR a: ARRAY 10, 20 OF INTEGER; m: ARRAY * OF REAL; i: INTEGER; r: REAL; BEGIN a[1, 2] := i; a[i][i] := 3; m[i] := r; r := m[1] END Test.").len(), 0);
+        assert_eq!(errors("MODULE Test; VAR a: ARRAY 10 OF INTEGER; b: BOOLEAN; BEGIN a[b] := 1; b[1] := 2 END Test."), vec![ E_TYPE_MISMATCH, E_TYPE_MISMATCH ])
+    }
+
+    #[test]
+    fn math_array_operators() {
+        assert_eq!(errors("MODULE Test; VAR v, w: ARRAY * OF INTEGER; m: ARRAY *, * OF REAL; x: ARRAY * OF REAL; i: INTEGER; r: REAL; BEGIN v := v + w; v := 2 * v; i := v +* w; m := m * 2.0; x := x .* x; r := x +* x END Test.").len(), 0);
+        assert_eq!(errors("MODULE Test; VAR v: ARRAY * OF INTEGER; BEGIN v := v + TRUE END Test."), vec![ E_TYPE_MISMATCH ]);
+        assert_eq!(errors("MODULE Test; VAR v: ARRAY * OF INTEGER; x: ARRAY * OF REAL; BEGIN v := v - x END Test."), vec![ E_TYPE_MISMATCH ]);
+        assert_eq!(errors("MODULE Test; VAR v: ARRAY * OF INTEGER; BEGIN v := v * 1.5 END Test."), vec![ E_TYPE_MISMATCH ]);
+        assert_eq!(errors("MODULE Test; VAR v, w: ARRAY * OF INTEGER; BEGIN v := v +* w END Test."), vec![ E_INCOMPATIBLE_ASSIGNMENT ]);
+        assert_eq!(errors("MODULE Test; VAR m, n: ARRAY *, * OF REAL; v: ARRAY * OF REAL; r: REAL; BEGIN v := m * v; v := v * m; r := v * v; m := m * n END Test.").len(), 0);
+        assert_eq!(errors("MODULE Test; VAR m: ARRAY *, * OF REAL; v: ARRAY * OF REAL; r: REAL; BEGIN m := m * v; v := v * v END Test."), vec![ E_INCOMPATIBLE_ASSIGNMENT, E_INCOMPATIBLE_ASSIGNMENT ]);
+
+        let ( _ , res ) = check(vec![ "MODULE Test; VAR v: ARRAY * OF INTEGER; b: BOOLEAN; BEGIN v := v .* b END Test." ]);
+        assert_eq!(res[0].len(), 1);
+        assert!(res[0][0].message.contains("for '.*'"))
+    }
+
+    #[test]
+    fn procedure_calls_and_parameters() {
+        let source = "MODULE Test; VAR i: INTEGER; l: LONGINT; s: ARRAY 4 OF CHAR;
+            PROCEDURE P(VAR x: LONGINT; CONST y: ARRAY OF CHAR; z: LONGINT): INTEGER; BEGIN RETURN 0 END P;
+            BEGIN i := P(l, s, i); i := P(l, \"abc\", 7) END Test.";
+        assert_eq!(errors(source).len(), 0);
+        assert_eq!(errors("MODULE Test; VAR i: INTEGER; PROCEDURE P(VAR x: LONGINT); END P; BEGIN P(i); P(1); P() END Test."), vec![ E_ARGUMENT, E_ARGUMENT, E_ARGUMENT ]);
+        assert_eq!(errors("MODULE Test; VAR b: BOOLEAN; PROCEDURE P(a: INTEGER; VAR c: INTEGER); END P; BEGIN P(3, 4); P(3, b); P(b, 4) END Test."), vec![ E_ARGUMENT, E_ARGUMENT, E_ARGUMENT, E_ARGUMENT ]);
+        assert_eq!(errors("MODULE Test; PROCEDURE F(): INTEGER; BEGIN RETURN 1 END F; BEGIN F() END Test."), vec![ E_RESULT ]);
+        assert_eq!(errors("MODULE Test; PROCEDURE F(): INTEGER; BEGIN RETURN TRUE END F; END Test."), vec![ E_INCOMPATIBLE_ASSIGNMENT ])
+    }
+
+    #[test]
+    fn procedure_types() {
+        assert_eq!(errors("MODULE Test; TYPE Handler = PROCEDURE (x: INTEGER): BOOLEAN; VAR h: Handler; b: BOOLEAN; PROCEDURE Even(x: INTEGER): BOOLEAN; BEGIN RETURN ODD(x) END Even; BEGIN h := Even; b := h(2); h := NIL END Test.").len(), 0);
+        assert_eq!(errors("MODULE Test; TYPE Handler = PROCEDURE (x: INTEGER); VAR h: Handler; PROCEDURE Other(x: REAL); END Other; BEGIN h := Other END Test."), vec![ E_INCOMPATIBLE_ASSIGNMENT ])
+    }
+
+    #[test]
+    fn address_size_and_complex() {
+        assert_eq!(errors("MODULE Test; VAR a: ADDRESS; s: SIZE; i: LONGINT; c: COMPLEX; r: REAL; BEGIN a := ADDRESS OF i; a := a + 4; s := a - a; s := SIZE OF INTEGER; c := r + 2 * IMAG END Test.").len(), 0);
+        assert_eq!(errors("MODULE Test; VAR c: COMPLEX; r: REAL; BEGIN r := c END Test."), vec![ E_INCOMPATIBLE_ASSIGNMENT ])
+    }
+
+    #[test]
+    fn constants_and_read_only() {
+        assert_eq!(errors("MODULE Test; CONST N = 10; VAR i: INTEGER; BEGIN N := 1 END Test."), vec![ E_NOT_ASSIGNABLE ]);
+        let ( _ , res ) = check(vec![ "MODULE Lib; VAR count-: INTEGER; END Lib.", "MODULE Test; IMPORT Lib; VAR i: INTEGER; BEGIN i := Lib.count; Lib.count := 1 END Test." ]);
+        assert_eq!(res[1].len(), 1);
+        assert_eq!(res[1][0].code, E_NOT_ASSIGNABLE)
+    }
+
+    #[test]
+    fn builtin_procedures() {
+        assert_eq!(errors("MODULE Test; VAR i: INTEGER; l: LONGINT; n: SIZE; c: CHAR; s: SET; a: ARRAY 5 OF CHAR; BEGIN INC(i); DEC(l, 2); INCL(s, 3); c := CHR(65); i := ORD(c); n := LEN(a); l := ENTIER(1.5); ASSERT(ODD(i)); l := MAX(LONGINT) END Test.").len(), 0);
+        assert_eq!(errors("MODULE Test; TYPE T = RECORD x: INTEGER END; VAR k: SIZE; a: ARRAY 10 OF CHAR; BEGIN FOR k := 0 TO LEN(a) - 1 DO a[k] := 0X END; a[SIZEOF(T)] := 'x' END Test.").len(), 0);
+        assert_eq!(errors("MODULE Test; VAR b: BOOLEAN; BEGIN INC(b); ASSERT(1) END Test."), vec![ E_ARGUMENT, E_ARGUMENT ])
+    }
+
+    #[test]
+    fn enumeration_and_case() {
+        assert_eq!(errors("MODULE Test; TYPE Color = ENUM Red, Green END; VAR c: Color; i: INTEGER; BEGIN c := Color.Green; CASE i OF 1 .. 3: i := 0 | 4: i := 1 END END Test.").len(), 0);
+        assert_eq!(errors("MODULE Test; TYPE Color = ENUM Red, Green END; VAR c: Color; i: INTEGER; BEGIN i := Color.Red END Test."), vec![ E_INCOMPATIBLE_ASSIGNMENT ])
+    }
+
+    #[test]
+    fn object_methods_and_self() {
+        let source = "MODULE Test; TYPE Counter = OBJECT VAR count: INTEGER; PROCEDURE Inc(n: INTEGER); BEGIN SELF.count := count + n END Inc; END Counter;
+            VAR c: Counter; BEGIN c.Inc(1); c.count := 2 END Test.";
+        assert_eq!(errors(source).len(), 0)
+    }
+
+    #[test]
+    fn definitions_implemented_by_objects() {
+        let source = "MODULE Test; DEFINITION Shape; PROCEDURE Draw(x: INTEGER); PROCEDURE Area(): LONGINT; END Shape;
+            TYPE Square = OBJECT IMPLEMENTS Shape VAR side: LONGINT; PROCEDURE Draw(x: INTEGER); END Draw; PROCEDURE Area(): LONGINT; BEGIN RETURN side * side END Area; END Square;
+            VAR s: Shape; q: Square; a: LONGINT; BEGIN s := q; s := NIL; s.Draw(1); a := s.Area() END Test.";
+        assert_eq!(errors(source).len(), 0);
+
+        let inherited = "MODULE Test; DEFINITION Shape; PROCEDURE Draw; END Shape;
+            TYPE Base = OBJECT PROCEDURE Draw; END Draw; END Base; Square = OBJECT (Base) IMPLEMENTS Shape END Square;
+            VAR s: Shape; q: Square; BEGIN s := q END Test.";
+        assert_eq!(errors(inherited).len(), 0)
+    }
+
+    #[test]
+    fn definition_errors() {
+        assert_eq!(errors("MODULE Test; DEFINITION Shape; PROCEDURE Draw; END Shape; TYPE Square = OBJECT IMPLEMENTS Shape END Square; END Test."), vec![ E_NOT_IMPLEMENTED ]);
+        assert_eq!(errors("MODULE Test; DEFINITION Shape; PROCEDURE Draw(x: INTEGER); END Shape; TYPE Square = OBJECT IMPLEMENTS Shape PROCEDURE Draw(x: LONGINT); END Draw; END Square; END Test."), vec![ E_SIGNATURE_MISMATCH ]);
+        assert_eq!(errors("MODULE Test; TYPE Square = OBJECT IMPLEMENTS INTEGER END Square; END Test."), vec![ E_TYPE_MISMATCH ]);
+        assert_eq!(errors("MODULE Test; DEFINITION Shape; END Shape; TYPE Square = OBJECT END Square; VAR s: Shape; q: Square; BEGIN s := q END Test."), vec![ E_INCOMPATIBLE_ASSIGNMENT ])
+    }
+
+    #[test]
+    fn type_of_declared_entries() {
+        let ( checker , _ ) = check(vec![ "MODULE Test; TYPE Int = INTEGER; VAR i: Int; END Test." ]);
+        let table = checker.get_resolver().get_symbol_table();
+        let module = checker.get_resolver().get_module_scope("Test").unwrap();
+        let entry = table.lookup_local(module, "i").unwrap();
+
+        assert_eq!(checker.get_type(checker.get_entry_type(entry).unwrap()), &Type::Signed( 16 ));
+        assert_eq!(checker.type_name(checker.get_entry_type(entry).unwrap()), String::from("Int"));
+        assert_eq!(T_SIGNED16, 7)
+    }
+
+    fn constant(checker: &TypeChecker, name: &str) -> Option<ConstValue> {
+        let table = checker.get_resolver().get_symbol_table();
+        let module = checker.get_resolver().get_module_scope("Test").unwrap();
+        checker.get_constant(table.lookup_local(module, name).unwrap()).cloned()
+    }
+
+    #[test]
+    fn constant_declarations() {
+        let ( checker , res ) = check(vec![ "MODULE Test; CONST A = B * 2 + 1; B = 10 DIV 3; C = {1 .. 3, 5} - {2}; D = ~(A > B) OR (\"abc\" < \"abd\"); E = 1.5 * 2; F = ORD('A') + ASH(1, 4); G = CHR(66); H = MAX(INTEGER) + 1; END Test." ]);
+
+        assert_eq!(res[0].len(), 0);
+        assert_eq!(constant(&checker, "A"), Some( ConstValue::Integer(7) ));
+        assert_eq!(constant(&checker, "B"), Some( ConstValue::Integer(3) ));
+        assert_eq!(constant(&checker, "C"), Some( ConstValue::Set(0b101010) ));
+        assert_eq!(constant(&checker, "D"), Some( ConstValue::Boolean(true) ));
+        assert_eq!(constant(&checker, "E"), Some( ConstValue::Real(3.0) ));
+        assert_eq!(constant(&checker, "F"), Some( ConstValue::Integer(81) ));
+        assert_eq!(constant(&checker, "G"), Some( ConstValue::Char(66) ));
+        assert_eq!(constant(&checker, "H"), Some( ConstValue::Integer(32768) ))
+    }
+
+    #[test]
+    fn constant_types_from_value() {
+        assert_eq!(errors("MODULE Test; CONST N = 100 * 100; VAR s: SHORTINT; i: INTEGER; BEGIN i := N; s := N END Test."), vec![ E_INCOMPATIBLE_ASSIGNMENT ]);
+        assert_eq!(errors("MODULE Test; VAR s: SHORTINT; BEGIN s := 100 + 100 END Test."), vec![ E_INCOMPATIBLE_ASSIGNMENT ])
+    }
+
+    #[test]
+    fn constant_errors() {
+        assert_eq!(errors("MODULE Test; CONST A = 1 DIV 0; END Test."), vec![ E_DIVISION_BY_ZERO ]);
+        assert_eq!(errors("MODULE Test; CONST A = MAX(HUGEINT) + 1; B = A + 1; END Test."), vec![ E_OUT_OF_RANGE ]);
+        assert_eq!(errors("MODULE Test; CONST A = B; B = A; END Test."), vec![ E_NOT_CONSTANT ]);
+        assert_eq!(errors("MODULE Test; VAR i: INTEGER; CONST A = i + 1; END Test."), vec![ E_NOT_CONSTANT ]);
+        assert_eq!(errors("MODULE Test; CONST A = CHR(300); S = {1, 40}; END Test."), vec![ E_OUT_OF_RANGE, E_OUT_OF_RANGE ]);
+        assert_eq!(errors("MODULE Test; VAR i: INTEGER; BEGIN i := 7 MOD (3 - 3) END Test."), vec![ E_DIVISION_BY_ZERO ])
+    }
+
+    #[test]
+    fn constant_array_lengths_and_sizes() {
+        let ( checker , res ) = check(vec![ "MODULE Test; CONST N = 4; TYPE A = ARRAY N * 2 OF INTEGER; R = RECORD c: CHAR; l: LONGINT; a: A END; CONST S = SIZEOF(R); T = SIZE OF A; M = MIN(SHORTINT); END Test." ]);
+
+        assert_eq!(res[0].len(), 0);
+        assert_eq!(constant(&checker, "S"), Some( ConstValue::Integer(24) ));
+        assert_eq!(constant(&checker, "T"), Some( ConstValue::Integer(16) ));
+        assert_eq!(constant(&checker, "M"), Some( ConstValue::Integer(-128) ));
+        assert_eq!(errors("MODULE Test; VAR n: INTEGER; a: ARRAY n OF CHAR; b: ARRAY -1 OF CHAR; END Test."), vec![ E_NOT_CONSTANT, E_OUT_OF_RANGE ]);
+        assert_eq!(errors("MODULE Test; VAR a: ARRAY 2 OF CHAR; BEGIN a := \"abc\" END Test."), vec![ E_INCOMPATIBLE_ASSIGNMENT ])
+    }
+
+    #[test]
+    fn constant_enumerations_and_case_labels() {
+        let ( checker , res ) = check(vec![ "MODULE Test; TYPE Color = ENUM Red, Green = 5, Blue END; CONST B = Color.Blue; END Test." ]);
+
+        assert_eq!(res[0].len(), 0);
+        assert_eq!(constant(&checker, "B"), Some( ConstValue::Integer(6) ));
+        assert_eq!(errors("MODULE Test; VAR i, j: INTEGER; c: CHAR; BEGIN CASE i OF 1, 2 .. 4: | j: END; CASE c OF 'a' .. 'z': END END Test."), vec![ E_NOT_CONSTANT ])
+    }
+}